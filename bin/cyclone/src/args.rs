@@ -1,7 +1,9 @@
 use std::{net::SocketAddr, path::PathBuf, time::Duration};
 
 use clap::{ArgAction, Parser};
-use cyclone_server::{Config, ConfigError, IncomingStream};
+use cyclone_server::{
+    Config, ConfigError, ExecutionLimits, IncomingStream, LangServers, SandboxProfiles,
+};
 
 const NAME: &str = "cyclone";
 
@@ -103,6 +105,44 @@ pub(crate) struct Args {
     /// Cyclone decryption key file location [example: /run/cyclone/cyclone.key]
     #[arg(long)]
     pub(crate) decryption_key: PathBuf,
+
+    /// Limits the CPU time of each function execution, in seconds
+    #[arg(long)]
+    pub(crate) max_cpu_seconds: Option<u64>,
+
+    /// Limits the memory (address space) of each function execution, in bytes
+    #[arg(long)]
+    pub(crate) max_memory_bytes: Option<u64>,
+
+    /// Limits the output of each function execution, in bytes
+    #[arg(long)]
+    pub(crate) max_output_bytes: Option<u64>,
+
+    /// Limits the wall clock time of each function execution, in milliseconds
+    #[arg(long)]
+    pub(crate) execution_timeout_ms: Option<u64>,
+
+    /// Language servers running functions of languages other than JavaScript, as a JSON object of
+    /// programs by language [example: '{"python":"/usr/local/bin/lang-py"}']
+    #[arg(long, value_parser = parse_lang_servers)]
+    pub(crate) lang_servers: Option<LangServers>,
+
+    /// Sandbox profiles that requests can select, as a JSON object of profiles by name
+    /// [example: '{"qualification":{"allowedCommands":["skopeo"]}}']
+    #[arg(long, value_parser = parse_sandbox_profiles)]
+    pub(crate) sandbox_profiles: Option<SandboxProfiles>,
+
+    /// Requires requests to present this bearer token in their `Authorization` header
+    #[arg(long, env = "SI_CYCLONE_BEARER_TOKEN", hide_env_values = true)]
+    pub(crate) bearer_token: Option<String>,
+}
+
+fn parse_lang_servers(value: &str) -> Result<LangServers, serde_json::Error> {
+    serde_json::from_str(value)
+}
+
+fn parse_sandbox_profiles(value: &str) -> Result<SandboxProfiles, serde_json::Error> {
+    serde_json::from_str(value)
 }
 
 impl TryFrom<Args> for Config {
@@ -119,6 +159,9 @@ impl TryFrom<Args> for Config {
         }
 
         builder.try_lang_server_path(args.lang_server)?;
+        if let Some(lang_servers) = args.lang_servers {
+            builder.lang_servers(lang_servers);
+        }
 
         if args.enable_watch {
             builder.watch(Some(Duration::from_secs(args.watch_timeout)));
@@ -144,6 +187,19 @@ impl TryFrom<Args> for Config {
             builder.limit_requests(limit_requests);
         }
 
+        builder.execution_limits(ExecutionLimits {
+            max_cpu_seconds: args.max_cpu_seconds,
+            max_memory_bytes: args.max_memory_bytes,
+            max_output_bytes: args.max_output_bytes,
+            timeout_ms: args.execution_timeout_ms,
+        });
+        if let Some(sandbox_profiles) = args.sandbox_profiles {
+            builder.sandbox_profiles(sandbox_profiles);
+        }
+        if let Some(bearer_token) = args.bearer_token {
+            builder.bearer_token(bearer_token);
+        }
+
         builder.build().map_err(Into::into)
     }
 }
@@ -69,6 +69,7 @@ async function execute(
         executionId,
         error: {
           kind: "InvalidReturnType",
+          category: "invalidReturnType",
           message: "Return type must not be null or undefined",
         },
       };
@@ -84,6 +85,7 @@ async function execute(
         executionId,
         error: {
           kind: "ActionFieldWrongType",
+          category: "invalidReturnType",
           message:
             'The status field type must be either "ok", "warning" or "error"',
         },
@@ -100,6 +102,7 @@ async function execute(
         executionId,
         error: {
           kind: "ActionFieldWrongType",
+          category: "invalidReturnType",
           message:
             'The message field type must be undefined when status is "ok"',
         },
@@ -116,6 +119,7 @@ async function execute(
         executionId,
         error: {
           kind: "ActionFieldWrongType",
+          category: "invalidReturnType",
           message:
             'The message field type must be string when status is either "warning" or "error"',
         },
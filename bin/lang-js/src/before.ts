@@ -0,0 +1,65 @@
+import Debug from "debug";
+import { base64ToJs } from "./base64";
+import { FunctionKind, Request } from "./function";
+import { createSandbox } from "./sandbox";
+import { createNodeVm } from "./vm";
+
+const debug = Debug("langJs:before");
+
+// A function run ahead of the request's function, such as an authentication function which
+// sets the environment variables and secrets the request's function needs through
+// `requestStorage`.
+export interface BeforeFunction {
+  handler: string;
+  codeBase64: string;
+  arg: unknown;
+}
+
+export interface RequestWithBefore extends Request {
+  before?: BeforeFunction[];
+}
+
+export class BeforeFunctionFailed extends Error {
+  constructor(handler: string, cause: Error) {
+    const message = `Before function '${handler}' failed: ${cause.message}`;
+    super(message);
+    this.name = "BeforeFunctionFailed";
+  }
+}
+
+// Runs the request's before functions in order, in this process, so that what they set up is in
+// place for the request's function. The first one to fail fails the request.
+export async function executeBefore(
+  request: RequestWithBefore,
+  kind: FunctionKind
+): Promise<void> {
+  for (const before of request.before ?? []) {
+    const code = wrapCode(base64ToJs(before.codeBase64), before.handler);
+    debug({ handler: before.handler, code });
+
+    const vm = createNodeVm(createSandbox(kind, request.executionId));
+    try {
+      const runner = vm.run(code);
+      await new Promise<void>((resolve, reject) => {
+        runner(before.arg, (err?: Error) => (err ? reject(err) : resolve()));
+      });
+    } catch (err) {
+      throw new BeforeFunctionFailed(before.handler, err as Error);
+    }
+  }
+}
+
+const wrapCode = (code: string, handler: string) => `
+module.exports = function(arg, callback) {
+  ${code}
+  try {
+    const returnValue = ${handler}(arg);
+    if (returnValue instanceof Promise) {
+      returnValue.then(() => callback()).catch((err) => callback(err));
+    } else {
+      callback();
+    }
+  } catch (err) {
+    callback(err);
+  }
+};`;
@@ -38,12 +38,20 @@ export interface ResultSuccess extends Result {
   error?: string;
 }
 
+/**
+ * What sort of failure a function ended in, which callers of the function go
+ * by to decide how to handle it. Timeouts and limits are enforced by cyclone,
+ * which reports those failures itself.
+ */
+export type ErrorCategory = "invalidReturnType" | "userCodeException";
+
 export interface ResultFailure extends Result {
   status: "failure";
   executionId: string;
   error: {
     kind: string;
     message: string;
+    category?: ErrorCategory;
   };
 }
 
@@ -64,6 +72,7 @@ export function failureExecution(
     error: {
       kind: err.name,
       message: err.message,
+      category: "userCodeException",
     },
   };
 }
@@ -75,4 +84,19 @@ export interface OutputLine {
   level: "debug" | "info" | "warn" | "error";
   group?: string;
   message: string;
+  /** Seconds since the UNIX epoch. */
+  timestamp?: number;
+}
+
+export interface ProgressLine {
+  protocol: "progress";
+  executionId: string;
+  /** Units of work done so far. */
+  current: number;
+  /** Units of work in total, if known. */
+  total?: number;
+  /** What the function is working on. */
+  step?: string;
+  /** Seconds since the UNIX epoch. */
+  timestamp: number;
 }
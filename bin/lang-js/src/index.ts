@@ -6,6 +6,7 @@ import Debug from "debug";
 import { failureExecution, FunctionKind, functionKinds } from "./function";
 import { makeConsole } from "./sandbox/console";
 import { executeActionRun } from "./action_run";
+import { executeBefore } from "./before";
 import { executeReconciliation } from "./reconciliation";
 import { executeResolverFunction } from "./resolver_function";
 import { executeSchemaVariantDefinition } from "./schema_variant_definition";
@@ -70,6 +71,8 @@ async function main() {
       throw Error(`Unknown Kind variant: ${kind}`);
     }
 
+    await executeBefore(request, kind);
+
     switch (kind) {
       case FunctionKind.ActionRun:
         await executeActionRun(request);
@@ -69,6 +69,7 @@ async function execute(
         executionId,
         error: {
           kind: "InvalidReturnType",
+          category: "invalidReturnType",
           message: "Return type must not be null or undefined",
         },
       };
@@ -81,6 +82,7 @@ async function execute(
         executionId,
         error: {
           kind: "ReconciliationFieldWrongType",
+          category: "invalidReturnType",
           message: 'The updates field type must be an object',
         },
       };
@@ -93,6 +95,7 @@ async function execute(
         executionId,
         error: {
           kind: "ReconciliationFieldWrongType",
+          category: "invalidReturnType",
           message: 'The actions field type must be an array of strings',
         },
       };
@@ -239,6 +239,7 @@ async function execute(
         executionId,
         error: {
           kind: "InvalidReturnType",
+          category: "invalidReturnType",
           message: "Return type cannot be null or undefined",
         },
       };
@@ -263,6 +264,7 @@ async function execute(
         executionId,
         error: {
           kind: "InvalidReturnType",
+          category: "invalidReturnType",
           message: validationResult.message,
         },
       };
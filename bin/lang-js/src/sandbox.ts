@@ -9,6 +9,8 @@ import yaml from "js-yaml";
 import { FunctionKind } from "./function";
 import { makeConsole } from "./sandbox/console";
 import { makeExec } from "./sandbox/exec";
+import { makeProgress } from "./sandbox/progress";
+import { makeRequestStorage } from "./sandbox/requestStorage";
 import * as assetBuilder from "./asset_builder";
 
 export type Sandbox = Record<string, unknown>;
@@ -24,6 +26,8 @@ export class UnknownSandboxKind extends Error {
 function commonSandbox(executionId: string): Sandbox {
     return {
         console: makeConsole(executionId),
+        progress: makeProgress(executionId),
+        requestStorage: makeRequestStorage(),
         _,
     };
 }
@@ -1,5 +1,7 @@
 import { OutputLine } from "../function";
 
+const DEFAULT_GROUP = "log";
+
 const normalizeMessage = (msg: unknown[]): string => {
   return msg
     .map((m) => {
@@ -10,42 +12,53 @@ const normalizeMessage = (msg: unknown[]): string => {
 };
 
 export const makeConsole = (executionId: string) => {
+  // Labels of the groups opened with `console.group()`, innermost last
+  const groups: string[] = [];
+
   function debug(...args: unknown[]): void {
-    emitOutputLine({
-      protocol: "output",
-      executionId,
-      stream: "stdout",
-      level: "debug",
-      group: "log",
-      message: normalizeMessage(args),
-    });
+    emitOutputLine("stdout", "debug", args);
   }
 
   function error(...args: unknown[]): void {
-    emitOutputLine({
-      protocol: "output",
-      executionId,
-      stream: "stderr",
-      level: "error",
-      group: "log",
-      message: normalizeMessage(args),
-    });
+    emitOutputLine("stderr", "error", args);
+  }
+
+  function info(...args: unknown[]): void {
+    emitOutputLine("stdout", "info", args);
   }
 
   function log(...args: unknown[]): void {
-    emitOutputLine({
+    emitOutputLine("stdout", "info", args);
+  }
+
+  function warn(...args: unknown[]): void {
+    emitOutputLine("stderr", "warn", args);
+  }
+
+  function group(...label: unknown[]): void {
+    groups.push(normalizeMessage(label) || DEFAULT_GROUP);
+  }
+
+  function groupEnd(): void {
+    groups.pop();
+  }
+
+  function emitOutputLine(
+    stream: OutputLine["stream"],
+    level: OutputLine["level"],
+    args: unknown[],
+  ): void {
+    const line: OutputLine = {
       protocol: "output",
       executionId,
-      stream: "stdout",
-      level: "info",
-      group: "log",
+      stream,
+      level,
+      group: groups.length > 0 ? groups.join("/") : DEFAULT_GROUP,
       message: normalizeMessage(args),
-    });
-  }
-
-  function emitOutputLine(line: OutputLine): void {
+      timestamp: Math.floor(Date.now() / 1000),
+    };
     console.log(JSON.stringify(line));
   }
 
-  return { debug, error, log };
+  return { debug, error, group, groupEnd, info, log, warn };
 };
@@ -1,3 +1,4 @@
+import path from "path";
 import execa from "execa";
 import { ExecaReturnValue, Options } from "execa";
 import Debug from "debug";
@@ -6,11 +7,32 @@ const debug = Debug("langJs:siExec");
 //import readline from "readline";
 //import WebSocket from "ws";
 
+// Set by cyclone when the function runs under a sandbox profile that only allows some programs
+const ALLOWED_COMMANDS_ENV_VAR = "SI_EXEC_ALLOWED_COMMANDS";
+
 export type SiExecResult = ExecaReturnValue<string>;
 
+export class CommandNotAllowed extends Error {
+  constructor(command: string) {
+    const message = `Command not allowed by the sandbox profile: ${command}`;
+    super(message);
+    this.name = "CommandNotAllowed";
+  }
+}
+
+function allowedCommands(): string[] | undefined {
+  const allowed = process.env[ALLOWED_COMMANDS_ENV_VAR];
+  if (allowed === undefined) {
+    return undefined;
+  }
+  return JSON.parse(allowed) as string[];
+}
+
 // Note(paulo): This is highly dangerous as it bypasses the sandbox
 // We also are bypassing the VM timeout by using async (NodeVM doesn't have timeout, but it seems we can't await without it)
 export const makeExec = (executionId: string) => {
+  const allowed = allowedCommands();
+
   async function waitUntilEnd(
     execaFile: string,
     execaArgs?: readonly string[],
@@ -22,6 +44,17 @@ export const makeExec = (executionId: string) => {
         ?.join(" ")}"`
     );
 
+    // Commands are matched by program name, so paths are refused, as is running the command
+    // through a shell, which would run whatever the arguments say
+    if (
+      allowed !== undefined &&
+      (path.basename(execaFile) !== execaFile ||
+        !allowed.includes(execaFile) ||
+        execaOptions?.shell)
+    ) {
+      throw new CommandNotAllowed(execaFile);
+    }
+
     const child = await execa(execaFile, execaArgs, {
       all: true,
       buffer: true,
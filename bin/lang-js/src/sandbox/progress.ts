@@ -0,0 +1,20 @@
+import { ProgressLine } from "../function";
+
+export const makeProgress = (executionId: string) => {
+  // Reports how far along the function is, such as `progress.report(3, 10, "syncing
+  // resources")`. Progress is forwarded to whoever requested the execution, separately from
+  // the function's output.
+  function report(current: number, total?: number, step?: string): void {
+    const line: ProgressLine = {
+      protocol: "progress",
+      executionId,
+      current: Math.max(0, Math.floor(current)),
+      total: total === undefined ? undefined : Math.max(0, Math.floor(total)),
+      step,
+      timestamp: Math.floor(Date.now() / 1000),
+    };
+    console.log(JSON.stringify(line));
+  }
+
+  return { report };
+};
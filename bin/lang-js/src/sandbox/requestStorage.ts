@@ -0,0 +1,32 @@
+// Values set for the rest of a request, shared by every function that runs for it in this
+// process. Before functions use it to hand what they set up, such as credentials, to the function
+// that runs after them.
+const env: Record<string, string> = {};
+const items: Record<string, unknown> = {};
+
+export const makeRequestStorage = () => {
+  // Sets an environment variable for the rest of the request, which the programs run with
+  // `siExec` inherit too.
+  function setEnv(key: string, value: string): void {
+    env[key] = value;
+    process.env[key] = value;
+  }
+
+  function getEnv(key: string): string | undefined {
+    return env[key];
+  }
+
+  function getEnvKeys(): string[] {
+    return Object.keys(env);
+  }
+
+  function setItem(key: string, value: unknown): void {
+    items[key] = value;
+  }
+
+  function getItem(key: string): unknown {
+    return items[key];
+  }
+
+  return { setEnv, getEnv, getEnvKeys, setItem, getItem };
+};
@@ -94,6 +94,7 @@ async function execute(
       executionId,
       error: {
         kind: invalidReturnType,
+        category: "invalidReturnType",
         message: "field 'valid' must be boolean",
       },
     };
@@ -109,6 +110,7 @@ async function execute(
       executionId,
       error: {
         kind: invalidReturnType,
+        category: "invalidReturnType",
         message: "field 'message' must be a string or undefined",
       },
     };
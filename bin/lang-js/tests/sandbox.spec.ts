@@ -1,10 +1,81 @@
 import { FunctionKind } from "../src/function";
 import { createSandbox } from "../src/sandbox";
+import { makeConsole } from "../src/sandbox/console";
+import { makeProgress } from "../src/sandbox/progress";
+import { makeRequestStorage } from "../src/sandbox/requestStorage";
 
 describe("createSandbox", () => {
   test("creates a new sandbox environment for execution", () => {
     const sandbox = createSandbox(FunctionKind.ResolverFunction, "poop");
     expect(sandbox).toHaveProperty("console");
+    expect(sandbox).toHaveProperty("progress");
+    expect(sandbox).toHaveProperty("requestStorage");
     expect(sandbox).toHaveProperty("_");
   });
 });
+
+describe("console", () => {
+  test("emits levels, groups and timestamps with each output line", () => {
+    const lines: string[] = [];
+    const spy = jest
+      .spyOn(console, "log")
+      .mockImplementation((line: string) => lines.push(line));
+
+    const sandboxConsole = makeConsole("poop");
+    sandboxConsole.warn("careful");
+    sandboxConsole.group("setup");
+    sandboxConsole.info("inside");
+    sandboxConsole.groupEnd();
+    spy.mockRestore();
+
+    const [warned, inside] = lines.map((line) => JSON.parse(line));
+    expect(warned).toMatchObject({
+      executionId: "poop",
+      stream: "stderr",
+      level: "warn",
+      group: "log",
+      message: "careful",
+    });
+    expect(warned.timestamp).toEqual(expect.any(Number));
+    expect(inside).toMatchObject({ level: "info", group: "setup" });
+  });
+});
+
+describe("progress", () => {
+  test("emits progress lines with counters and a step", () => {
+    const lines: string[] = [];
+    const spy = jest
+      .spyOn(console, "log")
+      .mockImplementation((line: string) => lines.push(line));
+
+    const progress = makeProgress("poop");
+    progress.report(3, 10, "syncing resources");
+    progress.report(4.7);
+    spy.mockRestore();
+
+    const [withTotal, withoutTotal] = lines.map((line) => JSON.parse(line));
+    expect(withTotal).toMatchObject({
+      protocol: "progress",
+      executionId: "poop",
+      current: 3,
+      total: 10,
+      step: "syncing resources",
+    });
+    expect(withTotal.timestamp).toEqual(expect.any(Number));
+    expect(withoutTotal).toMatchObject({ current: 4 });
+    expect(withoutTotal).not.toHaveProperty("total");
+  });
+});
+
+describe("requestStorage", () => {
+  test("shares what one function sets with the functions after it", () => {
+    makeRequestStorage().setEnv("SI_TEST_TOKEN", "ufo");
+    makeRequestStorage().setItem("region", "varginha");
+
+    const storage = makeRequestStorage();
+    expect(storage.getEnv("SI_TEST_TOKEN")).toEqual("ufo");
+    expect(storage.getEnvKeys()).toContain("SI_TEST_TOKEN");
+    expect(storage.getItem("region")).toEqual("varginha");
+    expect(process.env.SI_TEST_TOKEN).toEqual("ufo");
+  });
+});
@@ -42,6 +42,9 @@ async fn run(args: args::Args, mut telemetry: ApplicationTelemetryClient) -> Res
         CycloneSpec::LocalUds(_) => {
             Server::for_cyclone_uds(config).await?.run().await?;
         }
+        CycloneSpec::RemoteHttp(_) => {
+            Server::for_cyclone_remote_http(config).await?.run().await?;
+        }
     }
 
     Ok(())
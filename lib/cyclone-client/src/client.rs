@@ -16,8 +16,10 @@ use cyclone_core::{
     SchemaVariantDefinitionResultSuccess, ValidationRequest, ValidationResultSuccess,
 };
 use http::{
+    header::{InvalidHeaderValue, AUTHORIZATION},
     request::Builder,
     uri::{Authority, InvalidUri, InvalidUriParts, PathAndQuery, Scheme},
+    HeaderValue,
 };
 use hyper::{
     body,
@@ -31,13 +33,23 @@ use tokio::{
     io::{AsyncRead, AsyncWrite},
     net::TcpStream,
 };
-use tokio_tungstenite::WebSocketStream;
+use tokio_rustls::rustls::{client::InvalidDnsNameError, ServerName};
+use tokio_tungstenite::{
+    tungstenite::{client::IntoClientRequest, handshake::client::Request as WebSocketRequest},
+    WebSocketStream,
+};
 
-use crate::{execution, ping, watch, Execution, PingExecution, Watch};
+use crate::{
+    execution, ping,
+    remote::{RemoteConnector, RemoteStream},
+    watch, Execution, PingExecution, Watch,
+};
 
 #[remain::sorted]
 #[derive(Debug, Error)]
 pub enum ClientError {
+    #[error("invalid bearer token")]
+    BearerToken(#[source] InvalidHeaderValue),
     #[error("cannot create client uri")]
     ClientUri(#[source] http::Error),
     #[error("failed to connect")]
@@ -46,6 +58,8 @@ pub enum ClientError {
     InvalidLivenessStatus(#[from] LivenessStatusParseError),
     #[error("invalid readiness status")]
     InvalidReadinessStatus(#[from] ReadinessStatusParseError),
+    #[error("invalid server name for tls: {1}")]
+    InvalidServerName(#[source] InvalidDnsNameError, String),
     #[error("invalid URI")]
     InvalidUri(#[from] InvalidUri),
     #[error("invalid websocket uri scheme: {0}")]
@@ -91,6 +105,8 @@ pub struct Client<Conn, Strm, Sock> {
     connector: Conn,
     socket: Sock,
     uri: Uri,
+    authorization: Option<HeaderValue>,
+    sandbox_profile: Option<String>,
     _phantom: PhantomData<Strm>,
 }
 
@@ -106,6 +122,8 @@ where
             connector: self.connector.clone(),
             socket: self.socket.clone(),
             uri: self.uri.clone(),
+            authorization: self.authorization.clone(),
+            sandbox_profile: self.sandbox_profile.clone(),
             _phantom: PhantomData,
         }
     }
@@ -113,6 +131,7 @@ where
 
 pub type UdsClient = Client<UnixConnector, UnixStream, PathBuf>;
 pub type HttpClient = Client<HttpConnector, TcpStream, SocketAddr>;
+pub type RemoteClient = Client<RemoteConnector, RemoteStream, String>;
 
 #[async_trait]
 pub trait CycloneClient<Strm>
@@ -121,6 +140,10 @@ where
 {
     async fn watch(&mut self) -> result::Result<Watch<Strm>, ClientError>;
 
+    /// Selects the server's sandbox profile that later function executions run under. The
+    /// server's default profile is used if none is selected.
+    fn set_sandbox_profile(&mut self, sandbox_profile: Option<String>);
+
     async fn liveness(&mut self) -> result::Result<LivenessStatus, ClientError>;
 
     async fn readiness(&mut self) -> result::Result<ReadinessStatus, ClientError>;
@@ -189,6 +212,8 @@ impl Client<(), (), ()> {
             connector,
             socket,
             uri,
+            authorization: None,
+            sandbox_profile: None,
             _phantom: PhantomData,
         })
     }
@@ -216,11 +241,57 @@ impl Client<(), (), ()> {
             connector,
             socket,
             uri,
+            authorization: None,
+            sandbox_profile: None,
+            _phantom: PhantomData,
+        })
+    }
+
+    /// Creates a client for a Cyclone server running on another host, which is reached over TLS
+    /// if `tls` is set.
+    pub fn remote(host: impl Into<String>, port: u16, tls: bool) -> Result<RemoteClient> {
+        let host = host.into();
+        let (scheme, connector) = if tls {
+            let server_name = ServerName::try_from(host.as_str())
+                .map_err(|err| ClientError::InvalidServerName(err, host.clone()))?;
+            (Scheme::HTTPS, RemoteConnector::tls(server_name))
+        } else {
+            (Scheme::HTTP, RemoteConnector::plain())
+        };
+        let inner_client = hyper::Client::builder().build(connector.clone());
+        let authority = Authority::try_from(format!("{host}:{port}"))?;
+        let uri = Uri::builder()
+            .scheme(scheme)
+            .authority(authority)
+            .path_and_query("/")
+            .build()
+            .map_err(ClientError::ClientUri)?;
+        let config = Arc::new(ClientConfig::default());
+
+        Ok(Client {
+            config,
+            inner_client,
+            connector,
+            socket: host,
+            uri,
+            authorization: None,
+            sandbox_profile: None,
             _phantom: PhantomData,
         })
     }
 }
 
+impl<Conn, Strm, Sock> Client<Conn, Strm, Sock> {
+    /// Authenticates every request to the server with the given bearer token.
+    pub fn with_bearer_token(mut self, bearer_token: &str) -> Result<Self> {
+        let mut authorization = HeaderValue::try_from(format!("Bearer {bearer_token}"))
+            .map_err(ClientError::BearerToken)?;
+        authorization.set_sensitive(true);
+        self.authorization = Some(authorization);
+        Ok(self)
+    }
+}
+
 #[async_trait]
 impl<Conn, Strm, Sock> CycloneClient<Strm> for Client<Conn, Strm, Sock>
 where
@@ -235,6 +306,10 @@ where
         Ok(watch::watch(stream, self.config.watch_timeout))
     }
 
+    fn set_sandbox_profile(&mut self, sandbox_profile: Option<String>) {
+        self.sandbox_profile = sandbox_profile;
+    }
+
     async fn liveness(&mut self) -> Result<LivenessStatus> {
         let response = self.get("/liveness").await?;
 
@@ -272,7 +347,9 @@ where
         &mut self,
         request: ResolverFunctionRequest,
     ) -> Result<Execution<Strm, ResolverFunctionRequest, ResolverFunctionResultSuccess>> {
-        let stream = self.websocket_stream("/execute/resolver").await?;
+        let stream = self
+            .websocket_stream(self.execute_path("/execute/resolver"))
+            .await?;
         Ok(execution::execute(stream, request))
     }
 
@@ -281,7 +358,9 @@ where
         request: ActionRunRequest,
     ) -> result::Result<Execution<Strm, ActionRunRequest, ActionRunResultSuccess>, ClientError>
     {
-        let stream = self.websocket_stream("/execute/command").await?;
+        let stream = self
+            .websocket_stream(self.execute_path("/execute/command"))
+            .await?;
         Ok(execution::execute(stream, request))
     }
 
@@ -292,7 +371,9 @@ where
         Execution<Strm, ReconciliationRequest, ReconciliationResultSuccess>,
         ClientError,
     > {
-        let stream = self.websocket_stream("/execute/reconciliation").await?;
+        let stream = self
+            .websocket_stream(self.execute_path("/execute/reconciliation"))
+            .await?;
         Ok(execution::execute(stream, request))
     }
 
@@ -302,7 +383,8 @@ where
     ) -> result::Result<Execution<Strm, ValidationRequest, ValidationResultSuccess>, ClientError>
     {
         Ok(execution::execute(
-            self.websocket_stream("/execute/validation").await?,
+            self.websocket_stream(self.execute_path("/execute/validation"))
+                .await?,
             request,
         ))
     }
@@ -315,7 +397,7 @@ where
         ClientError,
     > {
         Ok(execution::execute(
-            self.websocket_stream("/execute/schema_variant_definition")
+            self.websocket_stream(self.execute_path("/execute/schema_variant_definition"))
                 .await?,
             request,
         ))
@@ -329,6 +411,15 @@ where
     Conn::Future: Unpin + Send,
     Strm: AsyncRead + AsyncWrite + Connection + Unpin + Send + Sync + 'static,
 {
+    /// Returns the path of a function execution endpoint, selecting the sandbox profile to run
+    /// under if there is one.
+    fn execute_path(&self, path: &str) -> String {
+        match &self.sandbox_profile {
+            Some(sandbox_profile) => format!("{path}?sandboxProfile={sandbox_profile}"),
+            None => path.to_string(),
+        }
+    }
+
     fn http_request_uri<P>(&self, path_and_query: P) -> Result<Uri>
     where
         P: TryInto<PathAndQuery, Error = InvalidUri>,
@@ -372,25 +463,31 @@ where
     {
         let uri = self.http_request_uri(path_and_query)?;
 
-        Ok(Request::builder().uri(uri))
+        let mut builder = Request::builder().uri(uri);
+        if let Some(authorization) = &self.authorization {
+            builder = builder.header(AUTHORIZATION, authorization.clone());
+        }
+        Ok(builder)
     }
 
-    fn new_ws_request<P>(&self, path_and_query: P) -> Result<Uri>
+    fn new_ws_request<P>(&self, path_and_query: P) -> Result<WebSocketRequest>
     where
         P: TryInto<PathAndQuery, Error = InvalidUri>,
     {
         let uri = self.ws_request_uri(path_and_query)?;
 
         // Tokio Tungstenite now requires that the request be perfectly created
-        // for websocket upgrades. If you use a URL, everything works.
-
-        //let request = Request::builder()
-        //    .uri(uri)
-        //    .method(Method::GET)
-        //    .body(())
-        //    .map_err(ClientError::Request)?;
+        // for websocket upgrades. Starting from a URI fills in the upgrade headers.
+        let mut request = uri
+            .into_client_request()
+            .map_err(ClientError::WebsocketConnection)?;
+        if let Some(authorization) = &self.authorization {
+            request
+                .headers_mut()
+                .insert(AUTHORIZATION, authorization.clone());
+        }
 
-        Ok(uri)
+        Ok(request)
     }
 
     async fn get<P>(&self, path_and_query: P) -> Result<Response<Body>>
@@ -418,8 +515,8 @@ where
             .call(self.uri.clone())
             .await
             .map_err(|err| ClientError::Connect(err.into()))?;
-        let uri = self.new_ws_request(path_and_query)?;
-        let (websocket_stream, response) = tokio_tungstenite::client_async(uri, stream)
+        let request = self.new_ws_request(path_and_query)?;
+        let (websocket_stream, response) = tokio_tungstenite::client_async(request, stream)
             .await
             .map_err(ClientError::WebsocketConnection)?;
 
@@ -452,8 +549,8 @@ mod tests {
     use base64::{engine::general_purpose, Engine};
     use buck2_resources::Buck2Resources;
     use cyclone_core::{
-        ComponentKind, ComponentView, FunctionResult, ProgressMessage, ResolverFunctionComponent,
-        ValidationRequest,
+        ComponentKind, ComponentView, FunctionLanguage, FunctionResult, ProgressMessage,
+        ResolverFunctionComponent, ValidationRequest,
     };
     use cyclone_server::{Config, ConfigBuilder, DecryptionKey, Server, UdsIncomingStream};
     use futures::StreamExt;
@@ -788,6 +885,8 @@ mod tests {
                     return v;
                 }"#,
             ),
+            lang: FunctionLanguage::JavaScript,
+            before: vec![],
         };
 
         // Start the protocol
@@ -878,6 +977,8 @@ mod tests {
                     return v;
                 }"#,
             ),
+            lang: FunctionLanguage::JavaScript,
+            before: vec![],
         };
 
         // Start the protocol
@@ -951,6 +1052,8 @@ mod tests {
                     }
                 }"#,
             ),
+            lang: FunctionLanguage::JavaScript,
+            before: vec![],
         };
         let mut progress = client
             .execute_validation(req)
@@ -969,7 +1072,7 @@ mod tests {
                     );
                     break;
                 }
-                Some(Ok(ProgressMessage::Heartbeat)) => continue,
+                Some(Ok(ProgressMessage::Heartbeat | ProgressMessage::Progress(_))) => continue,
                 Some(Err(err)) => panic!("failed to receive 'bubblegum' output: err={err:?}"),
                 None => panic!("output stream ended early"),
             };
@@ -980,7 +1083,7 @@ mod tests {
                     assert_eq!(output.message, "and i'm all out of gum");
                     break;
                 }
-                Some(Ok(ProgressMessage::Heartbeat)) => continue,
+                Some(Ok(ProgressMessage::Heartbeat | ProgressMessage::Progress(_))) => continue,
                 Some(Err(err)) => {
                     panic!("failed to receive 'all out of gum' output: err={err:?}")
                 }
@@ -993,7 +1096,7 @@ mod tests {
                     assert!(true);
                     break;
                 }
-                Some(Ok(ProgressMessage::Heartbeat)) => continue,
+                Some(Ok(ProgressMessage::Heartbeat | ProgressMessage::Progress(_))) => continue,
                 Some(unexpected) => panic!("output stream should be done: {unexpected:?}"),
             };
         }
@@ -1048,6 +1151,8 @@ mod tests {
                     return { status: 'ok' };
                 }"#,
             ),
+            lang: FunctionLanguage::JavaScript,
+            before: vec![],
         };
 
         // Start the protocol
@@ -1066,7 +1171,7 @@ mod tests {
                     assert_eq!(output.message, "first");
                     break;
                 }
-                Some(Ok(ProgressMessage::Heartbeat)) => continue,
+                Some(Ok(ProgressMessage::Heartbeat | ProgressMessage::Progress(_))) => continue,
                 Some(Err(err)) => panic!("failed to receive 'first' output: err={err:?}"),
                 None => panic!("output stream ended early"),
             };
@@ -1077,7 +1182,7 @@ mod tests {
                     assert_eq!(output.message, "second");
                     break;
                 }
-                Some(Ok(ProgressMessage::Heartbeat)) => continue,
+                Some(Ok(ProgressMessage::Heartbeat | ProgressMessage::Progress(_))) => continue,
                 Some(Err(err)) => panic!("failed to receive 'second' output: err={err:?}"),
                 None => panic!("output stream ended early"),
             }
@@ -1088,7 +1193,7 @@ mod tests {
                     assert!(true);
                     break;
                 }
-                Some(Ok(ProgressMessage::Heartbeat)) => continue,
+                Some(Ok(ProgressMessage::Heartbeat | ProgressMessage::Progress(_))) => continue,
                 Some(unexpected) => panic!("output stream should be done: {unexpected:?}"),
             };
         }
@@ -1123,6 +1228,8 @@ mod tests {
                     return { status: 'ok' };
                 }"#,
             ),
+            lang: FunctionLanguage::JavaScript,
+            before: vec![],
         };
 
         // Start the protocol
@@ -1141,7 +1248,7 @@ mod tests {
                     assert_eq!(output.message, "first");
                     break;
                 }
-                Some(Ok(ProgressMessage::Heartbeat)) => continue,
+                Some(Ok(ProgressMessage::Heartbeat | ProgressMessage::Progress(_))) => continue,
                 Some(Err(err)) => panic!("failed to receive 'first' output: err={err:?}"),
                 None => panic!("output stream ended early"),
             };
@@ -1152,7 +1259,7 @@ mod tests {
                     assert_eq!(output.message, "second");
                     break;
                 }
-                Some(Ok(ProgressMessage::Heartbeat)) => continue,
+                Some(Ok(ProgressMessage::Heartbeat | ProgressMessage::Progress(_))) => continue,
                 Some(Err(err)) => panic!("failed to receive 'second' output: err={err:?}"),
                 None => panic!("output stream ended early"),
             };
@@ -1163,7 +1270,7 @@ mod tests {
                     assert!(true);
                     break;
                 }
-                Some(Ok(ProgressMessage::Heartbeat)) => continue,
+                Some(Ok(ProgressMessage::Heartbeat | ProgressMessage::Progress(_))) => continue,
                 Some(unexpected) => panic!("output stream should be done: {unexpected:?}"),
             };
         }
@@ -1198,6 +1305,8 @@ mod tests {
                     return { updates: { "myid": true }, actions: ["run"] };
                 }"#,
             ),
+            lang: FunctionLanguage::JavaScript,
+            before: vec![],
         };
 
         // Start the protocol
@@ -1216,7 +1325,7 @@ mod tests {
                     assert_eq!(output.message, "first");
                     break;
                 }
-                Some(Ok(ProgressMessage::Heartbeat)) => continue,
+                Some(Ok(ProgressMessage::Heartbeat | ProgressMessage::Progress(_))) => continue,
                 Some(Err(err)) => panic!("failed to receive 'first' output: err={err:?}"),
                 None => panic!("output stream ended early"),
             };
@@ -1227,7 +1336,7 @@ mod tests {
                     assert_eq!(output.message, "second");
                     break;
                 }
-                Some(Ok(ProgressMessage::Heartbeat)) => continue,
+                Some(Ok(ProgressMessage::Heartbeat | ProgressMessage::Progress(_))) => continue,
                 Some(Err(err)) => panic!("failed to receive 'second' output: err={err:?}"),
                 None => panic!("output stream ended early"),
             }
@@ -1238,7 +1347,7 @@ mod tests {
                     assert!(true);
                     break;
                 }
-                Some(Ok(ProgressMessage::Heartbeat)) => continue,
+                Some(Ok(ProgressMessage::Heartbeat | ProgressMessage::Progress(_))) => continue,
                 Some(unexpected) => panic!("output stream should be done: {unexpected:?}"),
             };
         }
@@ -1274,6 +1383,8 @@ mod tests {
                     return { updates: { "myid": true }, actions: ["run"] };
                 }"#,
             ),
+            lang: FunctionLanguage::JavaScript,
+            before: vec![],
         };
 
         // Start the protocol
@@ -1292,7 +1403,7 @@ mod tests {
                     assert_eq!(output.message, "first");
                     break;
                 }
-                Some(Ok(ProgressMessage::Heartbeat)) => continue,
+                Some(Ok(ProgressMessage::Heartbeat | ProgressMessage::Progress(_))) => continue,
                 Some(Err(err)) => panic!("failed to receive 'first' output: err={err:?}"),
                 None => panic!("output stream ended early"),
             };
@@ -1303,7 +1414,7 @@ mod tests {
                     assert_eq!(output.message, "second");
                     break;
                 }
-                Some(Ok(ProgressMessage::Heartbeat)) => continue,
+                Some(Ok(ProgressMessage::Heartbeat | ProgressMessage::Progress(_))) => continue,
                 Some(Err(err)) => panic!("failed to receive 'second' output: err={err:?}"),
                 None => panic!("output stream ended early"),
             };
@@ -1314,7 +1425,7 @@ mod tests {
                     assert!(true);
                     break;
                 }
-                Some(Ok(ProgressMessage::Heartbeat)) => continue,
+                Some(Ok(ProgressMessage::Heartbeat | ProgressMessage::Progress(_))) => continue,
                 Some(unexpected) => panic!("output stream should be done: {unexpected:?}"),
             };
         }
@@ -1353,6 +1464,8 @@ mod tests {
                     return new AssetBuilder().build();
                 }"#,
             ),
+            lang: FunctionLanguage::JavaScript,
+            before: vec![],
         };
 
         // Start the protocol
@@ -1371,7 +1484,7 @@ mod tests {
                     assert_eq!(output.message, "first");
                     break;
                 }
-                Some(Ok(ProgressMessage::Heartbeat)) => continue,
+                Some(Ok(ProgressMessage::Heartbeat | ProgressMessage::Progress(_))) => continue,
                 Some(Err(err)) => panic!("failed to receive 'first' output: err={err:?}"),
                 None => panic!("output stream ended early"),
             };
@@ -1382,7 +1495,7 @@ mod tests {
                     assert_eq!(output.message, "second");
                     break;
                 }
-                Some(Ok(ProgressMessage::Heartbeat)) => continue,
+                Some(Ok(ProgressMessage::Heartbeat | ProgressMessage::Progress(_))) => continue,
                 Some(Err(err)) => panic!("failed to receive 'second' output: err={err:?}"),
                 None => panic!("output stream ended early"),
             };
@@ -1393,7 +1506,7 @@ mod tests {
                     assert!(true);
                     break;
                 }
-                Some(Ok(ProgressMessage::Heartbeat)) => continue,
+                Some(Ok(ProgressMessage::Heartbeat | ProgressMessage::Progress(_))) => continue,
                 Some(unexpected) => panic!("output stream should be done: {unexpected:?}"),
             };
         }
@@ -1432,6 +1545,8 @@ mod tests {
                     return new AssetBuilder().build();
                 }"#,
             ),
+            lang: FunctionLanguage::JavaScript,
+            before: vec![],
         };
 
         // Start the protocol
@@ -1450,7 +1565,7 @@ mod tests {
                     assert_eq!(output.message, "first");
                     break;
                 }
-                Some(Ok(ProgressMessage::Heartbeat)) => continue,
+                Some(Ok(ProgressMessage::Heartbeat | ProgressMessage::Progress(_))) => continue,
                 Some(Err(err)) => panic!("failed to receive 'first' output: err={err:?}"),
                 None => panic!("output stream ended early"),
             };
@@ -1461,7 +1576,7 @@ mod tests {
                     assert_eq!(output.message, "second");
                     break;
                 }
-                Some(Ok(ProgressMessage::Heartbeat)) => continue,
+                Some(Ok(ProgressMessage::Heartbeat | ProgressMessage::Progress(_))) => continue,
                 Some(Err(err)) => panic!("failed to receive 'second' output: err={err:?}"),
                 None => panic!("output stream ended early"),
             };
@@ -1472,7 +1587,7 @@ mod tests {
                     assert!(true);
                     break;
                 }
-                Some(Ok(ProgressMessage::Heartbeat)) => continue,
+                Some(Ok(ProgressMessage::Heartbeat | ProgressMessage::Progress(_))) => continue,
                 Some(unexpected) => panic!("output stream should be done: {unexpected:?}"),
             };
         }
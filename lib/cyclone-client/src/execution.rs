@@ -141,6 +141,10 @@ where
                     Message::OutputStream(output_stream) => {
                         Poll::Ready(Some(Ok(ProgressMessage::OutputStream(output_stream))))
                     }
+                    // We got a progress message, pass it on
+                    Message::Progress(progress) => {
+                        Poll::Ready(Some(Ok(ProgressMessage::Progress(progress))))
+                    }
                     // We got a funtion result message, save it and continue
                     Message::Result(function_result) => {
                         self.result = Some(function_result);
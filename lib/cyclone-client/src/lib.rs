@@ -1,9 +1,10 @@
 mod client;
 mod execution;
 mod ping;
+mod remote;
 mod watch;
 
-pub use client::{Client, ClientError, CycloneClient, HttpClient, UdsClient};
+pub use client::{Client, ClientError, CycloneClient, HttpClient, RemoteClient, UdsClient};
 pub use cyclone_core::{
     ActionRunRequest, ActionRunResultSuccess, EncryptionKey, EncryptionKeyError, LivenessStatus,
     LivenessStatusParseError, ReadinessStatus, ReadinessStatusParseError, ReconciliationRequest,
@@ -14,6 +15,7 @@ pub use execution::{Execution, ExecutionError};
 pub use hyper::client::connect::Connection;
 pub use hyperlocal::UnixStream;
 pub use ping::{PingExecution, PingExecutionError};
+pub use remote::{RemoteConnector, RemoteStream};
 pub use tokio_tungstenite::tungstenite::{
     protocol::frame::CloseFrame as WebSocketCloseFrame, Message as WebSocketMessage,
 };
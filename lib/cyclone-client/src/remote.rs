@@ -0,0 +1,160 @@
+use std::{
+    fmt,
+    future::Future,
+    io,
+    pin::Pin,
+    sync::Arc,
+    task::{Context, Poll},
+};
+
+use hyper::{
+    client::{
+        connect::{Connected, Connection},
+        HttpConnector,
+    },
+    service::Service,
+    Uri,
+};
+use tokio::{
+    io::{AsyncRead, AsyncWrite, ReadBuf},
+    net::TcpStream,
+};
+use tokio_rustls::{
+    client::TlsStream,
+    rustls::{ClientConfig, OwnedTrustAnchor, RootCertStore, ServerName},
+    TlsConnector,
+};
+
+type BoxError = Box<dyn std::error::Error + Send + Sync>;
+
+/// Connects to a Cyclone server over TCP, securing the connection with TLS if the server's name
+/// is given.
+#[derive(Clone)]
+pub struct RemoteConnector {
+    http: HttpConnector,
+    tls: Option<(TlsConnector, ServerName)>,
+}
+
+impl RemoteConnector {
+    pub(crate) fn plain() -> Self {
+        let mut http = HttpConnector::new();
+        http.enforce_http(false);
+        Self { http, tls: None }
+    }
+
+    /// Connects with TLS, verifying the server's certificate against the Mozilla root
+    /// certificates.
+    pub(crate) fn tls(server_name: ServerName) -> Self {
+        let mut root_certs = RootCertStore::empty();
+        root_certs.add_trust_anchors(webpki_roots::TLS_SERVER_ROOTS.0.iter().map(|anchor| {
+            OwnedTrustAnchor::from_subject_spki_name_constraints(
+                anchor.subject,
+                anchor.spki,
+                anchor.name_constraints,
+            )
+        }));
+        let config = ClientConfig::builder()
+            .with_safe_defaults()
+            .with_root_certificates(root_certs)
+            .with_no_client_auth();
+
+        Self {
+            tls: Some((TlsConnector::from(Arc::new(config)), server_name)),
+            ..Self::plain()
+        }
+    }
+}
+
+impl fmt::Debug for RemoteConnector {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RemoteConnector")
+            .field("http", &self.http)
+            .field(
+                "tls",
+                &self.tls.as_ref().map(|(_, server_name)| server_name),
+            )
+            .finish()
+    }
+}
+
+impl Service<Uri> for RemoteConnector {
+    type Response = RemoteStream;
+    type Error = BoxError;
+    type Future = Pin<Box<dyn Future<Output = Result<Self::Response, Self::Error>> + Send>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.http.poll_ready(cx).map_err(Into::into)
+    }
+
+    fn call(&mut self, uri: Uri) -> Self::Future {
+        let connecting = self.http.call(uri);
+        let tls = self.tls.clone();
+
+        Box::pin(async move {
+            let stream = connecting.await?;
+            match tls {
+                Some((connector, server_name)) => {
+                    let stream = connector.connect(server_name, stream).await?;
+                    Ok(RemoteStream::Tls(Box::new(stream)))
+                }
+                None => Ok(RemoteStream::Plain(stream)),
+            }
+        })
+    }
+}
+
+/// A connection to a remote Cyclone server, which may be secured with TLS.
+#[derive(Debug)]
+pub enum RemoteStream {
+    Plain(TcpStream),
+    Tls(Box<TlsStream<TcpStream>>),
+}
+
+impl Connection for RemoteStream {
+    fn connected(&self) -> Connected {
+        match self {
+            Self::Plain(stream) => stream.connected(),
+            Self::Tls(stream) => stream.get_ref().0.connected(),
+        }
+    }
+}
+
+impl AsyncRead for RemoteStream {
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut ReadBuf<'_>,
+    ) -> Poll<io::Result<()>> {
+        match self.get_mut() {
+            Self::Plain(stream) => Pin::new(stream).poll_read(cx, buf),
+            Self::Tls(stream) => Pin::new(stream).poll_read(cx, buf),
+        }
+    }
+}
+
+impl AsyncWrite for RemoteStream {
+    fn poll_write(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<io::Result<usize>> {
+        match self.get_mut() {
+            Self::Plain(stream) => Pin::new(stream).poll_write(cx, buf),
+            Self::Tls(stream) => Pin::new(stream).poll_write(cx, buf),
+        }
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        match self.get_mut() {
+            Self::Plain(stream) => Pin::new(stream).poll_flush(cx),
+            Self::Tls(stream) => Pin::new(stream).poll_flush(cx),
+        }
+    }
+
+    fn poll_shutdown(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        match self.get_mut() {
+            Self::Plain(stream) => Pin::new(stream).poll_shutdown(cx),
+            Self::Tls(stream) => Pin::new(stream).poll_shutdown(cx),
+        }
+    }
+}
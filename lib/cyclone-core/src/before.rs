@@ -0,0 +1,14 @@
+use serde::{Deserialize, Serialize};
+
+/// A function run ahead of a request's main function, in the same process, such as an
+/// authentication function that sets the environment variables and secrets the main function
+/// needs. A request's before functions run in order and a failing one fails the request.
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BeforeFunction {
+    pub handler: String,
+    pub code_base64: String,
+    /// The argument the function is called with, such as the credential it authenticates with.
+    /// Encrypted secrets within it are decrypted by cyclone.
+    pub arg: serde_json::Value,
+}
@@ -59,7 +59,12 @@ impl DecryptionKey {
         base64_encoded: impl AsRef<str>,
     ) -> Result<Vec<u8>, DecryptionKeyError> {
         let crypted = general_purpose::STANDARD_NO_PAD.decode(base64_encoded.as_ref())?;
-        sodiumoxide::crypto::sealedbox::open(&crypted, &self.public_key, &self.secret_key)
+        self.decrypt(crypted)
+    }
+
+    /// Decrypts a message sealed with the matching [`EncryptionKey`](crate::EncryptionKey).
+    pub fn decrypt(&self, crypted: impl AsRef<[u8]>) -> Result<Vec<u8>, DecryptionKeyError> {
+        sodiumoxide::crypto::sealedbox::open(crypted.as_ref(), &self.public_key, &self.secret_key)
             .map_err(|_| DecryptionKeyError::DecryptionFailed)
     }
 }
@@ -41,8 +41,12 @@ impl EncryptionKey {
     }
 
     pub fn encrypt_and_encode(&self, message: impl AsRef<[u8]>) -> String {
-        let crypted = sodiumoxide::crypto::sealedbox::seal(message.as_ref(), &self.public_key);
-        general_purpose::STANDARD_NO_PAD.encode(crypted)
+        general_purpose::STANDARD_NO_PAD.encode(self.encrypt(message))
+    }
+
+    /// Seals a message so that only the holder of cyclone's decryption key can read it.
+    pub fn encrypt(&self, message: impl AsRef<[u8]>) -> Vec<u8> {
+        sodiumoxide::crypto::sealedbox::seal(message.as_ref(), &self.public_key)
     }
 }
 
@@ -0,0 +1,38 @@
+use std::{collections::BTreeMap, fmt, path::PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+/// The language servers a cyclone server runs functions of languages other than its default
+/// language server's with, by language.
+pub type LangServers = BTreeMap<FunctionLanguage, PathBuf>;
+
+/// The language a function is written in, which picks the language server that runs it.
+#[remain::sorted]
+#[derive(
+    Clone, Copy, Debug, Default, Deserialize, Eq, Hash, Ord, PartialEq, PartialOrd, Serialize,
+)]
+#[serde(rename_all = "camelCase")]
+pub enum FunctionLanguage {
+    /// TypeScript or JavaScript, run by Deno.
+    Deno,
+    /// JavaScript, run by cyclone's default language server.
+    #[default]
+    JavaScript,
+    Python,
+}
+
+impl FunctionLanguage {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Deno => "deno",
+            Self::JavaScript => "javaScript",
+            Self::Python => "python",
+        }
+    }
+}
+
+impl fmt::Display for FunctionLanguage {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
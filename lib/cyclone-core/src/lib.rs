@@ -12,27 +12,37 @@
 )]
 
 mod action_run;
+mod before;
 mod canonical_command;
 mod component_view;
+mod decryption_key;
 mod encryption_key;
+mod language;
+mod limits;
 mod liveness;
 pub mod process;
 mod progress;
 mod readiness;
 mod reconciliation;
 mod resolver_function;
+mod sandbox;
+mod schema;
 mod schema_variant_definition;
 mod sensitive_container;
 mod validation;
 
 pub use action_run::{ActionRunRequest, ActionRunResultSuccess, ResourceStatus};
+pub use before::BeforeFunction;
 pub use canonical_command::{CanonicalCommand, CanonicalCommandError};
 pub use component_view::{ComponentKind, ComponentView};
+pub use decryption_key::{DecryptionKey, DecryptionKeyError};
 pub use encryption_key::{EncryptionKey, EncryptionKeyError};
+pub use language::{FunctionLanguage, LangServers};
+pub use limits::{ExecutionLimitExceeded, ExecutionLimits};
 pub use liveness::{LivenessStatus, LivenessStatusParseError};
 pub use progress::{
-    FunctionResult, FunctionResultFailure, FunctionResultFailureError, Message, OutputStream,
-    ProgressMessage,
+    FunctionErrorKind, FunctionProgress, FunctionResult, FunctionResultFailure,
+    FunctionResultFailureError, Message, OutputStream, ProgressMessage,
 };
 pub use readiness::{ReadinessStatus, ReadinessStatusParseError};
 pub use reconciliation::{ReconciliationRequest, ReconciliationResultSuccess};
@@ -40,6 +50,8 @@ pub use resolver_function::{
     ResolverFunctionComponent, ResolverFunctionRequest, ResolverFunctionResponseType,
     ResolverFunctionResultSuccess,
 };
+pub use sandbox::{SandboxProfile, SandboxProfiles, DEFAULT_SANDBOX_PROFILE};
+pub use schema::{schemas, validate_payload, PayloadSchemaError, PAYLOAD_SCHEMA_VERSION};
 pub use schema_variant_definition::{
     SchemaVariantDefinitionRequest, SchemaVariantDefinitionResultSuccess,
 };
@@ -0,0 +1,105 @@
+use std::{fmt, time::Duration};
+
+use serde::{Deserialize, Serialize};
+
+use crate::FunctionErrorKind;
+
+/// Resource limits cyclone enforces on every execution of a language server function. Limits
+/// that are unset aren't enforced.
+#[derive(Clone, Copy, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ExecutionLimits {
+    /// CPU time the function's process may use, in seconds.
+    pub max_cpu_seconds: Option<u64>,
+    /// Address space the function's process may use, in bytes. Language runtimes reserve a good
+    /// deal of address space up front, so this needs to be generous.
+    pub max_memory_bytes: Option<u64>,
+    /// Output the function may produce, counting both its output lines and its result, in bytes.
+    pub max_output_bytes: Option<u64>,
+    /// Wall clock limit for the execution, in milliseconds.
+    pub timeout_ms: Option<u64>,
+}
+
+impl ExecutionLimits {
+    /// Returns the wall clock limit, if there is one.
+    pub fn timeout(&self) -> Option<Duration> {
+        self.timeout_ms.map(Duration::from_millis)
+    }
+}
+
+/// A limit an execution ran into. It is reported as the kind of the execution's
+/// [`FunctionResultFailure`](crate::FunctionResultFailure), so callers can tell it apart from
+/// failures of the function itself.
+#[remain::sorted]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum ExecutionLimitExceeded {
+    CpuTime,
+    Memory,
+    OutputSize,
+    WallClock,
+}
+
+impl ExecutionLimitExceeded {
+    const ALL: [Self; 4] = [
+        Self::CpuTime,
+        Self::Memory,
+        Self::OutputSize,
+        Self::WallClock,
+    ];
+
+    /// The kind of failure recorded for an execution that ran into this limit.
+    pub fn failure_kind(self) -> &'static str {
+        match self {
+            Self::CpuTime => "cpuTimeLimitExceeded",
+            Self::Memory => "memoryLimitExceeded",
+            Self::OutputSize => "outputSizeLimitExceeded",
+            Self::WallClock => "wallClockLimitExceeded",
+        }
+    }
+
+    /// The category of failure recorded for an execution that ran into this limit.
+    pub fn category(self) -> FunctionErrorKind {
+        match self {
+            Self::WallClock => FunctionErrorKind::Timeout,
+            Self::CpuTime | Self::Memory | Self::OutputSize => FunctionErrorKind::KilledByLimit,
+        }
+    }
+
+    /// Returns the limit a failure kind stands for, if it is one of the limit kinds.
+    pub fn from_failure_kind(kind: &str) -> Option<Self> {
+        Self::ALL
+            .into_iter()
+            .find(|limit| limit.failure_kind() == kind)
+    }
+
+    /// Describes the limit that was exceeded, for the message of the failure.
+    pub fn describe(self, limits: &ExecutionLimits) -> String {
+        let limit = match self {
+            Self::CpuTime => limits.max_cpu_seconds.map(|seconds| format!("{seconds}s")),
+            Self::Memory => limits
+                .max_memory_bytes
+                .map(|bytes| format!("{bytes} bytes")),
+            Self::OutputSize => limits
+                .max_output_bytes
+                .map(|bytes| format!("{bytes} bytes")),
+            Self::WallClock => limits
+                .timeout_ms
+                .map(|timeout_ms| format!("{timeout_ms}ms")),
+        };
+        match limit {
+            Some(limit) => format!("function exceeded its {self} limit of {limit}"),
+            None => format!("function exceeded its {self} limit"),
+        }
+    }
+}
+
+impl fmt::Display for ExecutionLimitExceeded {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::CpuTime => "CPU time",
+            Self::Memory => "memory",
+            Self::OutputSize => "output",
+            Self::WallClock => "wall clock",
+        })
+    }
+}
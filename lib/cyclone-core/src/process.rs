@@ -1,9 +1,18 @@
 use std::{io, num::TryFromIntError, process::ExitStatus, time::Duration};
 
-use nix::{sys::signal, unistd::Pid};
+use nix::{
+    sys::{
+        resource::{setrlimit, Resource},
+        signal,
+    },
+    unistd::Pid,
+};
 use telemetry::prelude::*;
 use thiserror::Error;
-use tokio::{process::Child, time};
+use tokio::{
+    process::{Child, Command},
+    time,
+};
 
 pub use nix::sys::signal::Signal;
 
@@ -22,6 +31,133 @@ pub enum ShutdownError {
     StartKill(#[source] io::Error),
 }
 
+/// Limits the CPU time (in seconds) and address space (in bytes) of the process `command` spawns.
+///
+/// The process is sent `SIGXCPU` once it uses up its CPU time, and `SIGKILL` a second later if it
+/// is still running.
+pub fn limit_resources(
+    command: &mut Command,
+    max_cpu_seconds: Option<u64>,
+    max_memory_bytes: Option<u64>,
+) {
+    if max_cpu_seconds.is_none() && max_memory_bytes.is_none() {
+        return;
+    }
+    // Safety: the closure runs in the forked child before it execs, so it may only make
+    // async-signal-safe calls, which `setrlimit` is. It doesn't allocate.
+    unsafe {
+        command.pre_exec(move || {
+            if let Some(seconds) = max_cpu_seconds {
+                setrlimit(Resource::RLIMIT_CPU, seconds, seconds.saturating_add(1))?;
+            }
+            if let Some(bytes) = max_memory_bytes {
+                setrlimit(Resource::RLIMIT_AS, bytes, bytes)?;
+            }
+            Ok(())
+        });
+    }
+}
+
+/// Runs the process `command` spawns in a network namespace of its own, where it has no network
+/// access. The namespace is created in a new user namespace when cyclone lacks the privileges to
+/// create one directly.
+///
+/// Spawning fails on platforms without network namespaces rather than running the process with
+/// network access.
+pub fn isolate_network(command: &mut Command) {
+    // Safety: the closure runs in the forked child before it execs, so it may only make
+    // async-signal-safe calls, which `unshare` is. It doesn't allocate.
+    unsafe {
+        command.pre_exec(|| {
+            #[cfg(target_os = "linux")]
+            {
+                use nix::{
+                    errno::Errno,
+                    sched::{unshare, CloneFlags},
+                };
+
+                match unshare(CloneFlags::CLONE_NEWNET) {
+                    Err(Errno::EPERM) => {
+                        unshare(CloneFlags::CLONE_NEWUSER | CloneFlags::CLONE_NEWNET)?
+                    }
+                    result => result?,
+                }
+                Ok(())
+            }
+            #[cfg(not(target_os = "linux"))]
+            {
+                Err(io::Error::from(io::ErrorKind::Unsupported))
+            }
+        });
+    }
+}
+
+/// The directories covered by [`read_only_tmp`].
+const TEMPORARY_DIRECTORIES: [&str; 3] = ["/tmp", "/var/tmp", "/dev/shm"];
+
+/// Runs the process `command` spawns in a mount namespace of its own, where an empty read-only
+/// tmpfs is mounted over each of the [`TEMPORARY_DIRECTORIES`] that exist. Like
+/// [`isolate_network`], the namespace is created in a new user namespace when cyclone lacks the
+/// privileges to create one directly.
+///
+/// Spawning fails on platforms without mount namespaces, or when a directory can't be covered,
+/// rather than running the process with a writable temporary directory.
+pub fn read_only_tmp(command: &mut Command) {
+    for var in ["TMPDIR", "TMP", "TEMP"] {
+        command.env(var, TEMPORARY_DIRECTORIES[0]);
+    }
+    // Safety: the closure runs in the forked child before it execs, so it may only make
+    // async-signal-safe calls, which `unshare` and `mount` are. It doesn't allocate, as paths this
+    // short are copied onto the stack.
+    unsafe {
+        command.pre_exec(|| {
+            #[cfg(target_os = "linux")]
+            {
+                use nix::{
+                    errno::Errno,
+                    mount::{mount, MsFlags},
+                    sched::{unshare, CloneFlags},
+                };
+
+                match unshare(CloneFlags::CLONE_NEWNS) {
+                    Err(Errno::EPERM) => {
+                        unshare(CloneFlags::CLONE_NEWUSER | CloneFlags::CLONE_NEWNS)?
+                    }
+                    result => result?,
+                }
+                // Keeps the mounts below from propagating back to cyclone's mount namespace
+                mount(
+                    None::<&str>,
+                    "/",
+                    None::<&str>,
+                    MsFlags::MS_REC | MsFlags::MS_PRIVATE,
+                    None::<&str>,
+                )?;
+                for directory in TEMPORARY_DIRECTORIES {
+                    match mount(
+                        Some("tmpfs"),
+                        directory,
+                        Some("tmpfs"),
+                        MsFlags::MS_RDONLY
+                            | MsFlags::MS_NOSUID
+                            | MsFlags::MS_NODEV
+                            | MsFlags::MS_NOEXEC,
+                        None::<&str>,
+                    ) {
+                        Err(Errno::ENOENT) => {}
+                        result => result?,
+                    }
+                }
+                Ok(())
+            }
+            #[cfg(not(target_os = "linux"))]
+            {
+                Err(io::Error::from(io::ErrorKind::Unsupported))
+            }
+        });
+    }
+}
+
 pub async fn child_shutdown(
     child: &mut Child,
     signal: Option<Signal>,
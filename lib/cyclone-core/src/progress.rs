@@ -1,5 +1,9 @@
+use std::fmt;
+
 use serde::{de::DeserializeOwned, Deserialize, Serialize};
 
+use crate::ExecutionLimitExceeded;
+
 /// A line of output, streamed from an executing function.
 ///
 /// An instance of this type typically maps to a single line of output from a process--either on
@@ -20,11 +24,14 @@ pub struct OutputStream {
     /// A "loglevel" tag for the output line.
     ///
     /// Level mimics the log level used in logging and tracing frameworks so level values such as
-    /// `"info"`, `"debug"` are suitable but currently remains free-form.
+    /// `"info"`, `"debug"` are suitable but currently remains free-form. Functions written in
+    /// JavaScript emit `"debug"`, `"info"`, `"warn"` and `"error"`.
     pub level: String,
     /// An option tag to help group together output.
     ///
     /// Group can be used upstream (i.e. a frontend UI) to group sets of `OutputStream`s together.
+    /// Functions written in JavaScript open groups with `console.group()`; nested group labels
+    /// are joined with `/`.
     pub group: Option<String>,
     /// The contents of the output line.
     pub message: String,
@@ -34,6 +41,35 @@ pub struct OutputStream {
     pub timestamp: u64,
 }
 
+/// A report of how far along an executing function is.
+///
+/// Functions report progress as they work through long tasks, such as syncing many resources, so
+/// that whoever is waiting on them can show more than a spinner.
+#[derive(Debug, Deserialize, Eq, PartialEq, Serialize, Clone)]
+pub struct FunctionProgress {
+    /// An identifier for the execution of a particular function.
+    pub execution_id: String,
+    /// How many units of work are done.
+    pub current: u64,
+    /// How many units of work there are in total, if the function knows.
+    pub total: Option<u64>,
+    /// What the function is working on, if it said.
+    pub step: Option<String>,
+    /// A timestamp in seconds since UNIX epoch.
+    pub timestamp: u64,
+}
+
+impl FunctionProgress {
+    /// Returns how much of the work is done as a percentage, if the total is known.
+    pub fn percentage(&self) -> Option<f64> {
+        match self.total {
+            Some(0) => Some(100.0),
+            Some(total) => Some((self.current.min(total) as f64 / total as f64) * 100.0),
+            None => None,
+        }
+    }
+}
+
 /// A message produced as a function is executing.
 ///
 /// A `ProgressMessage` is a way to track and follow how an execution is progressing. Such messages
@@ -49,6 +85,8 @@ pub enum ProgressMessage {
     Heartbeat,
     /// An `OutputStream` message.
     OutputStream(OutputStream),
+    /// A `FunctionProgress` message.
+    Progress(FunctionProgress),
 }
 
 #[remain::sorted]
@@ -58,6 +96,7 @@ pub enum Message<R> {
     Finish,
     Heartbeat,
     OutputStream(OutputStream),
+    Progress(FunctionProgress),
     Result(FunctionResult<R>),
     Start,
 }
@@ -105,9 +144,124 @@ pub struct FunctionResultFailure {
 }
 
 #[derive(Debug, Deserialize, Eq, PartialEq, Serialize, Clone)]
+#[serde(from = "FunctionResultFailureErrorPayload")]
 pub struct FunctionResultFailureError {
+    /// The specific kind of failure, such as the name of the exception thrown by the function.
     pub kind: String,
     pub message: String,
+    /// What sort of failure this is, which callers go by to decide how to handle it.
+    pub category: FunctionErrorKind,
+}
+
+impl FunctionResultFailureError {
+    /// Creates a failure whose category is worked out from its kind.
+    pub fn new(kind: impl Into<String>, message: impl Into<String>) -> Self {
+        let kind = kind.into();
+        Self {
+            category: FunctionErrorKind::classify(&kind),
+            kind,
+            message: message.into(),
+        }
+    }
+
+    /// Returns whether the execution may succeed if it is tried again.
+    pub fn is_retryable(&self) -> bool {
+        self.category.is_retryable()
+    }
+}
+
+/// The shape a [`FunctionResultFailureError`] is received in. Failures from peers that predate
+/// categories come without one, so it is worked out from their kind.
+#[derive(Deserialize)]
+struct FunctionResultFailureErrorPayload {
+    kind: String,
+    message: String,
+    category: Option<FunctionErrorKind>,
+}
+
+impl From<FunctionResultFailureErrorPayload> for FunctionResultFailureError {
+    fn from(value: FunctionResultFailureErrorPayload) -> Self {
+        Self {
+            category: value
+                .category
+                .unwrap_or_else(|| FunctionErrorKind::classify(&value.kind)),
+            kind: value.kind,
+            message: value.message,
+        }
+    }
+}
+
+/// The sort of failure a function execution ended in.
+#[remain::sorted]
+#[derive(Clone, Copy, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum FunctionErrorKind {
+    /// The execution was cancelled by its client.
+    Cancelled,
+    /// The function returned a value that doesn't fit its kind of function.
+    InvalidReturnType,
+    /// The function ran into a CPU time, memory or output limit and was killed.
+    KilledByLimit,
+    /// The execution was refused before it ran.
+    Rejected,
+    /// The function ran past its wall clock limit.
+    Timeout,
+    /// The function's request or result got lost on its way between the client, veritech and
+    /// cyclone.
+    TransportError,
+    /// The function threw.
+    UserCodeException,
+}
+
+impl FunctionErrorKind {
+    /// Works out the category of a failure from its kind, for failures that weren't given one.
+    pub fn classify(kind: &str) -> Self {
+        if let Some(limit) = ExecutionLimitExceeded::from_failure_kind(kind) {
+            return limit.category();
+        }
+        match kind {
+            "cancelled" => Self::Cancelled,
+            "ActionFieldWrongType" | "InvalidReturnType" | "ReconciliationFieldWrongType" => {
+                Self::InvalidReturnType
+            }
+            "rejected" => Self::Rejected,
+            "veritechServer" => Self::TransportError,
+            _ => Self::UserCodeException,
+        }
+    }
+
+    /// Returns whether an execution that failed this way may succeed if it is tried again.
+    ///
+    /// Failures of the function itself, or of the limits it runs under, repeat on every attempt,
+    /// while timeouts and transport errors may be down to a busy or flaky system.
+    pub fn is_retryable(self) -> bool {
+        match self {
+            Self::Timeout | Self::TransportError => true,
+            Self::Cancelled
+            | Self::InvalidReturnType
+            | Self::KilledByLimit
+            | Self::Rejected
+            | Self::UserCodeException => false,
+        }
+    }
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Cancelled => "cancelled",
+            Self::InvalidReturnType => "invalidReturnType",
+            Self::KilledByLimit => "killedByLimit",
+            Self::Rejected => "rejected",
+            Self::Timeout => "timeout",
+            Self::TransportError => "transportError",
+            Self::UserCodeException => "userCodeException",
+        }
+    }
+}
+
+impl fmt::Display for FunctionErrorKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
 }
 
 #[derive(Debug, Deserialize, Eq, PartialEq, Serialize)]
@@ -1,6 +1,8 @@
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 
+use crate::{BeforeFunction, FunctionLanguage};
+
 #[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct ReconciliationRequest {
@@ -8,6 +10,12 @@ pub struct ReconciliationRequest {
     pub handler: String,
     pub code_base64: String,
     pub args: serde_json::Value,
+    /// The language the function is written in.
+    #[serde(default)]
+    pub lang: FunctionLanguage,
+    /// Functions run before this one, such as to authenticate.
+    #[serde(default)]
+    pub before: Vec<BeforeFunction>,
 }
 
 #[derive(Debug, Deserialize, Eq, PartialEq, Serialize)]
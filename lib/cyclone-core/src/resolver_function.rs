@@ -1,7 +1,7 @@
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
 
-use crate::ComponentView;
+use crate::{BeforeFunction, ComponentView, FunctionLanguage};
 
 #[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
 #[serde(rename_all = "camelCase")]
@@ -11,6 +11,12 @@ pub struct ResolverFunctionRequest {
     pub component: ResolverFunctionComponent,
     pub response_type: ResolverFunctionResponseType,
     pub code_base64: String,
+    /// The language the function is written in.
+    #[serde(default)]
+    pub lang: FunctionLanguage,
+    /// Functions run before this one, such as to authenticate.
+    #[serde(default)]
+    pub before: Vec<BeforeFunction>,
 }
 
 #[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize, Default)]
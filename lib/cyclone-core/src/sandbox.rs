@@ -0,0 +1,49 @@
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+
+/// The name of the profile that requests which don't select a profile run under, if a server
+/// offers one by this name.
+pub const DEFAULT_SANDBOX_PROFILE: &str = "default";
+
+/// The sandbox profiles a cyclone server offers, by name.
+pub type SandboxProfiles = BTreeMap<String, SandboxProfile>;
+
+/// Restrictions cyclone places on the process of a language server function. The default profile
+/// places none.
+#[derive(Clone, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct SandboxProfile {
+    /// Runs the function in a network namespace of its own, leaving it without network access.
+    pub no_network: bool,
+    /// Runs the function in a mount namespace of its own, where `/tmp` and the other temporary
+    /// directories are empty and read-only.
+    pub read_only_tmpdir: bool,
+    /// Names of the programs the function may run with `siExec`. Every program is allowed if this
+    /// is unset, and none if it is empty.
+    pub allowed_commands: Option<Vec<String>>,
+}
+
+impl SandboxProfile {
+    /// A profile with every restriction in place: no network, no writable temporary directory and
+    /// no programs.
+    pub fn locked_down() -> Self {
+        Self {
+            no_network: true,
+            read_only_tmpdir: true,
+            allowed_commands: Some(Vec::new()),
+        }
+    }
+
+    /// Allows the function to run the given programs with `siExec`.
+    pub fn allow_commands<I, S>(mut self, commands: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.allowed_commands
+            .get_or_insert_with(Vec::new)
+            .extend(commands.into_iter().map(Into::into));
+        self
+    }
+}
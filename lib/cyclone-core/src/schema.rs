@@ -0,0 +1,517 @@
+//! JSON Schemas for the requests and results exchanged with cyclone (and so, through veritech).
+//!
+//! The schemas are written out by hand next to the types they describe, so a change to one of
+//! those types must be mirrored here. [`validate_payload`] checks a payload against its schema,
+//! which lets integration tests catch the two drifting apart.
+
+use serde_json::{json, Map, Value};
+use thiserror::Error;
+
+/// The version of the payload schemas returned by [`schemas`]. It is bumped whenever a request or
+/// result changes shape, so that external tools can tell which shapes they were written against.
+pub const PAYLOAD_SCHEMA_VERSION: u32 = 4;
+
+const JSON_SCHEMA_DIALECT: &str = "https://json-schema.org/draft/2020-12/schema";
+
+#[remain::sorted]
+#[derive(Debug, Error)]
+pub enum PayloadSchemaError {
+    #[error("payload does not match the {name} schema: {}", .violations.join("; "))]
+    Mismatch {
+        name: String,
+        violations: Vec<String>,
+    },
+    #[error("no payload schema named {0}")]
+    UnknownSchema(String),
+}
+
+/// Returns a JSON Schema document with a definition, under `$defs`, for every request and result
+/// type. Results are defined both on their own (e.g. `ResolverFunctionResultSuccess`) and wrapped
+/// the way they are sent (e.g. `ResolverFunctionResult`, a success or a failure).
+pub fn schemas() -> Value {
+    json!({
+        "$schema": JSON_SCHEMA_DIALECT,
+        "version": PAYLOAD_SCHEMA_VERSION,
+        "$defs": definitions(),
+    })
+}
+
+/// Checks a payload against the schema with the given name (see [`schemas`]).
+pub fn validate_payload(name: &str, payload: &Value) -> Result<(), PayloadSchemaError> {
+    let definitions = definitions();
+    let schema = definitions
+        .get(name)
+        .ok_or_else(|| PayloadSchemaError::UnknownSchema(name.to_string()))?;
+
+    let mut violations = Vec::new();
+    check(&definitions, schema, payload, "$", &mut violations);
+    if violations.is_empty() {
+        Ok(())
+    } else {
+        Err(PayloadSchemaError::Mismatch {
+            name: name.to_string(),
+            violations,
+        })
+    }
+}
+
+fn definitions() -> Map<String, Value> {
+    let mut definitions = Map::new();
+    let mut define = |name: &str, schema: Value| {
+        definitions.insert(name.to_string(), schema);
+    };
+
+    // Shared types
+    define("ComponentKind", string_enum(&["credential", "standard"]));
+    define(
+        "ComponentView",
+        object(
+            &[("kind", reference("ComponentKind")), ("properties", any())],
+            &[],
+        ),
+    );
+    define(
+        "FunctionLanguage",
+        string_enum(&["deno", "javaScript", "python"]),
+    );
+    define(
+        "FunctionErrorKind",
+        string_enum(&[
+            "cancelled",
+            "invalidReturnType",
+            "killedByLimit",
+            "rejected",
+            "timeout",
+            "transportError",
+            "userCodeException",
+        ]),
+    );
+    define(
+        "FunctionResultFailureError",
+        object(
+            &[("kind", string()), ("message", string())],
+            &[("category", reference("FunctionErrorKind"))],
+        ),
+    );
+    define(
+        "FunctionResultFailure",
+        object(
+            &[
+                ("executionId", string()),
+                ("error", reference("FunctionResultFailureError")),
+                ("timestamp", unsigned()),
+            ],
+            &[],
+        ),
+    );
+    define(
+        "BeforeFunction",
+        object(
+            &[
+                ("handler", string()),
+                ("codeBase64", string()),
+                ("arg", any()),
+            ],
+            &[],
+        ),
+    );
+    define(
+        "OutputStream",
+        object(
+            &[
+                ("stream", string()),
+                ("execution_id", string()),
+                ("level", string()),
+                ("message", string()),
+                ("timestamp", unsigned()),
+            ],
+            &[("group", nullable(string()))],
+        ),
+    );
+    define(
+        "FunctionProgress",
+        object(
+            &[
+                ("execution_id", string()),
+                ("current", unsigned()),
+                ("timestamp", unsigned()),
+            ],
+            &[
+                ("total", nullable(unsigned())),
+                ("step", nullable(string())),
+            ],
+        ),
+    );
+
+    // Resolver functions
+    define(
+        "ResolverFunctionComponent",
+        object(
+            &[
+                ("data", reference("ComponentView")),
+                ("parents", array(reference("ComponentView"))),
+            ],
+            &[],
+        ),
+    );
+    define(
+        "ResolverFunctionResponseType",
+        string_enum(&[
+            "Action",
+            "Array",
+            "Boolean",
+            "CodeGeneration",
+            "Confirmation",
+            "Identity",
+            "Integer",
+            "Json",
+            "Map",
+            "Object",
+            "Qualification",
+            "Reconciliation",
+            "SchemaVariantDefinition",
+            "String",
+            "Unset",
+            "Validation",
+        ]),
+    );
+    define(
+        "ResolverFunctionRequest",
+        object(
+            &[
+                ("executionId", string()),
+                ("handler", string()),
+                ("component", reference("ResolverFunctionComponent")),
+                ("responseType", reference("ResolverFunctionResponseType")),
+                ("codeBase64", string()),
+            ],
+            &[
+                ("lang", reference("FunctionLanguage")),
+                ("before", array(reference("BeforeFunction"))),
+            ],
+        ),
+    );
+    define(
+        "ResolverFunctionResultSuccess",
+        object(
+            &[
+                ("executionId", string()),
+                ("data", any()),
+                ("unset", boolean()),
+                ("timestamp", unsigned()),
+            ],
+            &[],
+        ),
+    );
+    define(
+        "ResolverFunctionResult",
+        function_result("ResolverFunctionResultSuccess"),
+    );
+
+    // Validations
+    define(
+        "ValidationRequest",
+        object(
+            &[
+                ("executionId", string()),
+                ("handler", string()),
+                ("value", any()),
+                ("codeBase64", string()),
+            ],
+            &[
+                ("lang", reference("FunctionLanguage")),
+                ("before", array(reference("BeforeFunction"))),
+            ],
+        ),
+    );
+    define(
+        "ValidationResultSuccess",
+        object(
+            &[("executionId", string()), ("valid", boolean())],
+            &[("message", nullable(string()))],
+        ),
+    );
+    define(
+        "ValidationResult",
+        function_result("ValidationResultSuccess"),
+    );
+
+    // Action runs
+    define(
+        "ActionRunRequest",
+        object(
+            &[
+                ("executionId", string()),
+                ("handler", string()),
+                ("codeBase64", string()),
+                ("args", any()),
+            ],
+            &[
+                ("lang", reference("FunctionLanguage")),
+                ("before", array(reference("BeforeFunction"))),
+            ],
+        ),
+    );
+    define("ResourceStatus", string_enum(&["error", "ok", "warning"]));
+    define(
+        "ActionRunResultSuccess",
+        object(
+            &[
+                ("executionId", string()),
+                ("status", reference("ResourceStatus")),
+            ],
+            &[
+                ("payload", any()),
+                ("message", nullable(string())),
+                ("error", nullable(string())),
+            ],
+        ),
+    );
+    define("ActionRunResult", function_result("ActionRunResultSuccess"));
+
+    // Reconciliations
+    define(
+        "ReconciliationRequest",
+        object(
+            &[
+                ("executionId", string()),
+                ("handler", string()),
+                ("codeBase64", string()),
+                ("args", any()),
+            ],
+            &[
+                ("lang", reference("FunctionLanguage")),
+                ("before", array(reference("BeforeFunction"))),
+            ],
+        ),
+    );
+    define(
+        "ReconciliationResultSuccess",
+        object(
+            &[
+                ("executionId", string()),
+                ("updates", map(any())),
+                ("actions", array(string())),
+            ],
+            &[("message", nullable(string()))],
+        ),
+    );
+    define(
+        "ReconciliationResult",
+        function_result("ReconciliationResultSuccess"),
+    );
+
+    // Schema variant definitions
+    define(
+        "SchemaVariantDefinitionRequest",
+        object(
+            &[
+                ("executionId", string()),
+                ("handler", string()),
+                ("codeBase64", string()),
+            ],
+            &[
+                ("lang", reference("FunctionLanguage")),
+                ("before", array(reference("BeforeFunction"))),
+            ],
+        ),
+    );
+    define(
+        "SchemaVariantDefinitionResultSuccess",
+        object(&[("executionId", string()), ("definition", any())], &[]),
+    );
+    define(
+        "SchemaVariantDefinitionResult",
+        function_result("SchemaVariantDefinitionResultSuccess"),
+    );
+
+    definitions
+}
+
+fn any() -> Value {
+    json!({})
+}
+
+fn boolean() -> Value {
+    json!({ "type": "boolean" })
+}
+
+fn string() -> Value {
+    json!({ "type": "string" })
+}
+
+fn unsigned() -> Value {
+    json!({ "type": "integer", "minimum": 0 })
+}
+
+fn string_enum(values: &[&str]) -> Value {
+    json!({ "type": "string", "enum": values })
+}
+
+fn nullable(schema: Value) -> Value {
+    json!({ "anyOf": [schema, { "type": "null" }] })
+}
+
+fn reference(name: &str) -> Value {
+    json!({ "$ref": format!("#/$defs/{name}") })
+}
+
+fn array(items: Value) -> Value {
+    json!({ "type": "array", "items": items })
+}
+
+fn map(values: Value) -> Value {
+    json!({ "type": "object", "additionalProperties": values })
+}
+
+/// An object with the given required and optional properties, and no others.
+fn object(required: &[(&str, Value)], optional: &[(&str, Value)]) -> Value {
+    let properties: Map<String, Value> = required
+        .iter()
+        .chain(optional)
+        .map(|(name, schema)| (name.to_string(), schema.clone()))
+        .collect();
+    let required: Vec<&str> = required.iter().map(|(name, _)| *name).collect();
+    json!({
+        "type": "object",
+        "properties": properties,
+        "required": required,
+        "additionalProperties": false,
+    })
+}
+
+/// A [`FunctionResult`](crate::FunctionResult) of the given success type.
+fn function_result(success: &str) -> Value {
+    json!({
+        "oneOf": [
+            object(&[("Success", reference(success))], &[]),
+            object(&[("Failure", reference("FunctionResultFailure"))], &[]),
+        ]
+    })
+}
+
+/// Checks a value against the subset of JSON Schema used by [`definitions`], recording every
+/// violation found along with the path to the offending value.
+fn check(
+    definitions: &Map<String, Value>,
+    schema: &Value,
+    value: &Value,
+    path: &str,
+    violations: &mut Vec<String>,
+) {
+    if let Some(reference) = schema.get("$ref").and_then(Value::as_str) {
+        let name = reference.trim_start_matches("#/$defs/");
+        match definitions.get(name) {
+            Some(schema) => check(definitions, schema, value, path, violations),
+            None => violations.push(format!("{path}: unknown schema reference {reference}")),
+        }
+        return;
+    }
+
+    for (keyword, exactly_one) in [("anyOf", false), ("oneOf", true)] {
+        if let Some(alternatives) = schema.get(keyword).and_then(Value::as_array) {
+            let matching = alternatives
+                .iter()
+                .filter(|alternative| {
+                    let mut alternative_violations = Vec::new();
+                    check(
+                        definitions,
+                        alternative,
+                        value,
+                        path,
+                        &mut alternative_violations,
+                    );
+                    alternative_violations.is_empty()
+                })
+                .count();
+            if matching == 0 || (exactly_one && matching > 1) {
+                violations.push(format!("{path}: does not match exactly one allowed shape"));
+            }
+            return;
+        }
+    }
+
+    if let Some(expected) = schema.get("type").and_then(Value::as_str) {
+        let matches = match expected {
+            "array" => value.is_array(),
+            "boolean" => value.is_boolean(),
+            "integer" => value.is_i64() || value.is_u64(),
+            "null" => value.is_null(),
+            "object" => value.is_object(),
+            "string" => value.is_string(),
+            _ => true,
+        };
+        if !matches {
+            violations.push(format!("{path}: expected {expected}, found {value}"));
+            return;
+        }
+    }
+
+    if let Some(allowed) = schema.get("enum").and_then(Value::as_array) {
+        if !allowed.contains(value) {
+            violations.push(format!("{path}: {value} is not one of the allowed values"));
+        }
+    }
+
+    if let (Some(minimum), Some(number)) = (
+        schema.get("minimum").and_then(Value::as_f64),
+        value.as_f64(),
+    ) {
+        if number < minimum {
+            violations.push(format!("{path}: {number} is less than {minimum}"));
+        }
+    }
+
+    if let (Some(items), Some(elements)) = (schema.get("items"), value.as_array()) {
+        for (index, element) in elements.iter().enumerate() {
+            check(
+                definitions,
+                items,
+                element,
+                &format!("{path}[{index}]"),
+                violations,
+            );
+        }
+    }
+
+    if let Some(object) = value.as_object() {
+        let properties = schema.get("properties").and_then(Value::as_object);
+        for name in schema
+            .get("required")
+            .and_then(Value::as_array)
+            .into_iter()
+            .flatten()
+            .filter_map(Value::as_str)
+        {
+            if !object.contains_key(name) {
+                violations.push(format!("{path}: missing required property {name}"));
+            }
+        }
+        for (name, property) in object {
+            let property_path = format!("{path}.{name}");
+            match (
+                properties.and_then(|properties| properties.get(name)),
+                schema.get("additionalProperties"),
+            ) {
+                (Some(property_schema), _) => check(
+                    definitions,
+                    property_schema,
+                    property,
+                    &property_path,
+                    violations,
+                ),
+                (None, Some(Value::Bool(false))) => {
+                    violations.push(format!("{property_path}: unexpected property"));
+                }
+                (None, Some(additional)) if additional.is_object() => check(
+                    definitions,
+                    additional,
+                    property,
+                    &property_path,
+                    violations,
+                ),
+                (None, _) => {}
+            }
+        }
+    }
+}
@@ -1,11 +1,19 @@
 use serde::{Deserialize, Serialize};
 
+use crate::{BeforeFunction, FunctionLanguage};
+
 #[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct SchemaVariantDefinitionRequest {
     pub execution_id: String,
     pub handler: String,
     pub code_base64: String,
+    /// The language the function is written in.
+    #[serde(default)]
+    pub lang: FunctionLanguage,
+    /// Functions run before this one, such as to authenticate.
+    #[serde(default)]
+    pub before: Vec<BeforeFunction>,
 }
 
 #[derive(Debug, Deserialize, Eq, PartialEq, Serialize)]
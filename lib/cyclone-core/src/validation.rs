@@ -1,5 +1,7 @@
 use serde::{Deserialize, Serialize};
 
+use crate::{BeforeFunction, FunctionLanguage};
+
 #[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct ValidationRequest {
@@ -7,6 +9,12 @@ pub struct ValidationRequest {
     pub handler: String,
     pub value: serde_json::Value,
     pub code_base64: String,
+    /// The language the function is written in.
+    #[serde(default)]
+    pub lang: FunctionLanguage,
+    /// Functions run before this one, such as to authenticate.
+    #[serde(default)]
+    pub before: Vec<BeforeFunction>,
 }
 
 #[derive(Debug, Deserialize, Eq, PartialEq, Serialize)]
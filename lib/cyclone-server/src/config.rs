@@ -4,6 +4,7 @@ use std::{
     time::Duration,
 };
 
+use cyclone_core::{ExecutionLimits, LangServers, SandboxProfiles};
 use derive_builder::Builder;
 use si_settings::{CanonicalFile, CanonicalFileError};
 use thiserror::Error;
@@ -52,8 +53,20 @@ pub struct Config {
     #[builder(try_setter, setter(into))]
     lang_server_path: CanonicalFile,
 
+    #[builder(default)]
+    lang_servers: LangServers,
+
     #[builder(setter(into), default)]
     limit_requests: Option<u32>,
+
+    #[builder(default)]
+    execution_limits: ExecutionLimits,
+
+    #[builder(default)]
+    sandbox_profiles: SandboxProfiles,
+
+    #[builder(setter(into, strip_option), default)]
+    bearer_token: Option<String>,
 }
 
 impl Config {
@@ -117,11 +130,38 @@ impl Config {
         self.lang_server_path.as_path()
     }
 
+    /// Gets a reference to the language servers that run functions of languages other than
+    /// JavaScript, by language.
+    #[must_use]
+    pub fn lang_servers(&self) -> &LangServers {
+        &self.lang_servers
+    }
+
     /// Gets a reference to the config's limit requests.
     #[must_use]
     pub fn limit_requests(&self) -> Option<u32> {
         self.limit_requests
     }
+
+    /// Gets the resource limits enforced on every function execution.
+    #[must_use]
+    pub fn execution_limits(&self) -> ExecutionLimits {
+        self.execution_limits
+    }
+
+    /// Gets a reference to the sandbox profiles that requests can select.
+    #[must_use]
+    pub fn sandbox_profiles(&self) -> &SandboxProfiles {
+        &self.sandbox_profiles
+    }
+
+    /// Gets the bearer token that requests must present, if any.
+    ///
+    /// When set, every request must carry an `Authorization: Bearer <token>` header.
+    #[must_use]
+    pub fn bearer_token(&self) -> Option<&str> {
+        self.bearer_token.as_deref()
+    }
 }
 
 impl ConfigBuilder {
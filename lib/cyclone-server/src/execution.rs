@@ -1,6 +1,7 @@
 use std::{
-    fmt, io,
+    fmt, future, io,
     marker::{PhantomData, Unpin},
+    os::unix::process::ExitStatusExt,
     path::PathBuf,
     process::Stdio,
     sync::Arc,
@@ -11,8 +12,9 @@ use axum::extract::ws::WebSocket;
 use bytes_lines_codec::BytesLinesCodec;
 use cyclone_core::{
     process::{self, ShutdownError},
+    ExecutionLimitExceeded, ExecutionLimits, FunctionErrorKind, FunctionLanguage, FunctionProgress,
     FunctionResult, FunctionResultFailure, FunctionResultFailureError, Message, OutputStream,
-    SensitiveString,
+    SandboxProfile, SensitiveString,
 };
 use futures::{SinkExt, StreamExt, TryStreamExt};
 use serde::{de::DeserializeOwned, Deserialize, Serialize};
@@ -21,27 +23,36 @@ use telemetry::prelude::*;
 use thiserror::Error;
 use tokio::{
     process::{Child, ChildStderr, ChildStdin, ChildStdout, Command},
-    time,
+    time::{self, Instant},
 };
 use tokio_serde::{formats::SymmetricalJson, Deserializer, Framed, SymmetricallyFramed};
 use tokio_util::codec::{Decoder, FramedRead, FramedWrite};
 
 use crate::{
-    request::{DecryptRequest, ListSecrets},
+    request::{DecryptRequest, ListSecrets, RequestLanguage},
+    state::LangServerPath,
     DecryptionKey, DecryptionKeyError, WebSocketMessage,
 };
 
 const TX_TIMEOUT_SECS: Duration = Duration::from_secs(5);
+/// How long to wait for a child whose output has ended to exit, to learn why it stopped.
+const CHILD_EXIT_TIMEOUT_SECS: Duration = Duration::from_secs(1);
+/// Tells the language server which programs functions may run with `siExec`, as a JSON array.
+const SI_EXEC_ALLOWED_COMMANDS_ENV_VAR: &str = "SI_EXEC_ALLOWED_COMMANDS";
 
 pub fn new<Request, LangServerSuccess, Success>(
-    lang_server_path: impl Into<PathBuf>,
+    lang_server_path: LangServerPath,
     lang_server_debugging: bool,
+    limits: ExecutionLimits,
+    sandbox_profile: SandboxProfile,
     key: Arc<DecryptionKey>,
     command: String,
 ) -> Execution<Request, LangServerSuccess, Success> {
     Execution {
-        lang_server_path: lang_server_path.into(),
+        lang_server_path,
         lang_server_debugging,
+        limits,
+        sandbox_profile,
         key,
         command,
         request_marker: PhantomData,
@@ -63,6 +74,8 @@ pub enum ExecutionError {
     ChildShutdown(#[from] ShutdownError),
     #[error("failed to spawn child process; program={0}")]
     ChildSpawn(#[source] io::Error, PathBuf),
+    #[error("client closed the websocket before the execution finished")]
+    ClientClosed,
     #[error("failed to deserialize json message")]
     JSONDeserialize(#[source] serde_json::Error),
     #[error("failed to serialize json message")]
@@ -73,6 +86,8 @@ pub enum ExecutionError {
     SendTimeout(#[source] tokio::time::error::Elapsed),
     #[error("unexpected websocket message type: {0:?}")]
     UnexpectedMessageType(WebSocketMessage),
+    #[error("no language server runs {0} functions")]
+    UnsupportedLanguage(FunctionLanguage),
     #[error("failed to close websocket")]
     WSClose(#[source] axum::Error),
     #[error("failed to receive websocket message--stream is closed")]
@@ -87,8 +102,10 @@ type Result<T> = std::result::Result<T, ExecutionError>;
 
 #[derive(Debug)]
 pub struct Execution<Request, LangServerSuccess, Success> {
-    lang_server_path: PathBuf,
+    lang_server_path: LangServerPath,
     lang_server_debugging: bool,
+    limits: ExecutionLimits,
+    sandbox_profile: SandboxProfile,
     key: Arc<DecryptionKey>,
     command: String,
     request_marker: PhantomData<Request>,
@@ -98,7 +115,13 @@ pub struct Execution<Request, LangServerSuccess, Success> {
 
 impl<Request, LangServerSuccess, Success> Execution<Request, LangServerSuccess, Success>
 where
-    Request: DecryptRequest + ListSecrets + Serialize + DeserializeOwned + Unpin + core::fmt::Debug,
+    Request: DecryptRequest
+        + ListSecrets
+        + RequestLanguage
+        + Serialize
+        + DeserializeOwned
+        + Unpin
+        + core::fmt::Debug,
     LangServerSuccess: DeserializeOwned,
     Success: Serialize,
 {
@@ -111,7 +134,13 @@ where
         // Now that the server said to start, I am going to read my message!
         let request = Self::read_request(ws).await?;
         let credentials: Vec<SensitiveString> = request.list_secrets(&self.key)?;
-        let mut command = Command::new(&self.lang_server_path);
+        let lang = request.lang();
+        let lang_server_path = self
+            .lang_server_path
+            .for_language(lang)
+            .ok_or(ExecutionError::UnsupportedLanguage(lang))?
+            .to_path_buf();
+        let mut command = Command::new(&lang_server_path);
         command
             .arg(&self.command)
             .stdin(Stdio::piped())
@@ -120,13 +149,23 @@ where
         if self.lang_server_debugging {
             command.env("DEBUG", "*").env("DEBUG_DEPTH", "5");
         }
+        process::limit_resources(
+            &mut command,
+            self.limits.max_cpu_seconds,
+            self.limits.max_memory_bytes,
+        );
+        Self::apply_sandbox(&mut command, &self.sandbox_profile)?;
         debug!(cmd = ?command, "spawning child process");
         let mut child = command
             .spawn()
-            .map_err(|err| ExecutionError::ChildSpawn(err, self.lang_server_path.clone()))?;
+            .map_err(|err| ExecutionError::ChildSpawn(err, lang_server_path))?;
+        let deadline = self
+            .limits
+            .timeout()
+            .map(|timeout| Instant::now() + timeout);
 
         let stdin = child.stdin.take().ok_or(ExecutionError::ChildIO("stdin"))?;
-        Self::child_send_function_request(stdin, request, &self.key).await?;
+        let execution_id = Self::child_send_function_request(stdin, request, &self.key).await?;
 
         let stderr = {
             let stderr = child
@@ -150,10 +189,29 @@ where
             stdout,
             stderr,
             credentials,
+            execution_id,
+            limits: self.limits,
+            deadline,
             success_marker: self.success_marker,
         })
     }
 
+    /// Places the restrictions of a sandbox profile on the child process.
+    fn apply_sandbox(command: &mut Command, profile: &SandboxProfile) -> Result<()> {
+        if profile.no_network {
+            process::isolate_network(command);
+        }
+        if profile.read_only_tmpdir {
+            process::read_only_tmp(command);
+        }
+        if let Some(allowed_commands) = &profile.allowed_commands {
+            let allowed_commands =
+                serde_json::to_string(allowed_commands).map_err(ExecutionError::JSONSerialize)?;
+            command.env(SI_EXEC_ALLOWED_COMMANDS_ENV_VAR, allowed_commands);
+        }
+        Ok(())
+    }
+
     async fn read_request(ws: &mut WebSocket) -> Result<Request> {
         let request = match ws.next().await {
             Some(Ok(WebSocketMessage::Text(json_str))) => {
@@ -178,12 +236,18 @@ where
         Ok(())
     }
 
+    /// Sends the request to the child, returning the request's execution id.
     async fn child_send_function_request(
         stdin: ChildStdin,
         request: Request,
         key: &DecryptionKey,
-    ) -> Result<()> {
+    ) -> Result<String> {
         let value = request.decrypt_request(key)?;
+        let execution_id = value
+            .get("executionId")
+            .and_then(Value::as_str)
+            .unwrap_or_default()
+            .to_string();
 
         let codec = FramedWrite::new(stdin, BytesLinesCodec::new());
         let mut stdin = SymmetricallyFramed::new(codec, SymmetricalJson::default());
@@ -196,7 +260,7 @@ where
             .await
             .map_err(ExecutionError::SendTimeout)?
             .map_err(ExecutionError::ChildSendIO)?;
-        Ok(())
+        Ok(execution_id)
     }
 }
 
@@ -212,6 +276,10 @@ pub struct ExecutionStarted<LangServerSuccess, Success> {
     stdout: SiFramed<SiMessage<LangServerSuccess>>,
     stderr: FramedRead<ChildStderr, BytesLinesCodec>,
     credentials: Vec<SensitiveString>,
+    execution_id: String,
+    limits: ExecutionLimits,
+    /// When the execution exceeds its wall clock limit, if it has one.
+    deadline: Option<Instant>,
     success_marker: PhantomData<Success>,
 }
 
@@ -254,42 +322,145 @@ where
     pub async fn process(self, ws: &mut WebSocket) -> Result<ExecutionClosing<Success>> {
         tokio::spawn(handle_stderr(self.stderr, self.credentials.clone()));
 
-        let mut stream = self
-            .stdout
-            .map(|ls_result| match ls_result {
+        let mut stream = self.stdout.map(|ls_result| -> Result<Message<Success>> {
+            match ls_result {
                 Ok(ls_msg) => match ls_msg {
                     LangServerMessage::Output(mut output) => {
                         Self::filter_output(&mut output, &self.credentials)?;
                         Ok(Message::OutputStream(output.into()))
                     }
+                    LangServerMessage::Progress(mut progress) => {
+                        Self::filter_progress(&mut progress, &self.credentials);
+                        Ok(Message::Progress(progress.into()))
+                    }
                     LangServerMessage::Result(mut result) => {
                         Self::filter_result(&mut result, &self.credentials)?;
                         Ok(Message::Result(result.into()))
                     }
                 },
                 Err(err) => Err(ExecutionError::ChildRecvIO(err)),
-            })
-            .map(|msg_result: Result<_>| match msg_result {
-                Ok(msg) => match msg
-                    .serialize_to_string()
-                    .map_err(ExecutionError::JSONSerialize)
-                {
-                    Ok(json_str) => Ok(WebSocketMessage::Text(json_str)),
-                    Err(err) => Err(err),
+            }
+        });
+
+        let limits = self.limits;
+        let deadline = self.deadline;
+        let timed_out = async move {
+            match deadline {
+                Some(deadline) => time::sleep_until(deadline).await,
+                None => future::pending().await,
+            }
+        };
+        tokio::pin!(timed_out);
+
+        let mut child = self.child;
+        let mut output_bytes: u64 = 0;
+        let mut sent_result = false;
+        let exceeded = loop {
+            tokio::select! {
+                msg = stream.try_next() => match msg? {
+                    Some(msg) => {
+                        let is_result = matches!(msg, Message::Result(_));
+                        let json_str = msg
+                            .serialize_to_string()
+                            .map_err(ExecutionError::JSONSerialize)?;
+                        output_bytes = output_bytes.saturating_add(json_str.len() as u64);
+                        if limits.max_output_bytes.map_or(false, |max| output_bytes > max) {
+                            break Some(ExecutionLimitExceeded::OutputSize);
+                        }
+                        ws.send(WebSocketMessage::Text(json_str))
+                            .await
+                            .map_err(ExecutionError::WSSendIO)?;
+                        sent_result |= is_result;
+                    }
+                    None if sent_result => break None,
+                    None => break Self::limit_exceeded_on_exit(&mut child, &limits).await,
                 },
-                Err(err) => Err(err),
-            });
+                () = &mut timed_out => break Some(ExecutionLimitExceeded::WallClock),
+                // The client sends nothing once the request is read, so a message here (or the
+                // socket closing) means it has cancelled the execution or gone away. Either way
+                // nobody is waiting for the result, so stop the function.
+                client_msg = ws.next() => {
+                    if let Some(Ok(WebSocketMessage::Ping(_) | WebSocketMessage::Pong(_))) =
+                        client_msg
+                    {
+                        continue;
+                    }
+                    debug!("client closed websocket, terminating child process");
+                    if let Err(err) = process::child_shutdown(
+                        &mut child,
+                        Some(process::Signal::SIGTERM),
+                        None,
+                    )
+                    .await
+                    {
+                        warn!(error = ?err, "failed to shutdown child cleanly");
+                    }
+                    return Err(ExecutionError::ClientClosed);
+                }
+            }
+        };
 
-        while let Some(msg) = stream.try_next().await? {
-            ws.send(msg).await.map_err(ExecutionError::WSSendIO)?;
+        if let Some(limit) = exceeded {
+            warn!(%limit, "execution exceeded a limit, terminating child process");
+            if let Err(err) =
+                process::child_shutdown(&mut child, Some(process::Signal::SIGKILL), None).await
+            {
+                warn!(error = ?err, "failed to shutdown child cleanly");
+            }
+            // A result that already made it to the client stands
+            if !sent_result {
+                let msg =
+                    Message::<Success>::Result(FunctionResult::Failure(FunctionResultFailure {
+                        execution_id: self.execution_id,
+                        error: FunctionResultFailureError {
+                            kind: limit.failure_kind().to_string(),
+                            message: limit.describe(&limits),
+                            category: limit.category(),
+                        },
+                        timestamp: crate::timestamp(),
+                    }))
+                    .serialize_to_string()
+                    .map_err(ExecutionError::JSONSerialize)?;
+                ws.send(WebSocketMessage::Text(msg))
+                    .await
+                    .map_err(ExecutionError::WSSendIO)?;
+            }
         }
 
         Ok(ExecutionClosing {
-            child: self.child,
+            child,
             success_marker: PhantomData,
         })
     }
 
+    /// Works out whether the child stopped because it ran into its CPU time or memory limit, once
+    /// its output has ended without a result.
+    async fn limit_exceeded_on_exit(
+        child: &mut Child,
+        limits: &ExecutionLimits,
+    ) -> Option<ExecutionLimitExceeded> {
+        let status = match time::timeout(CHILD_EXIT_TIMEOUT_SECS, child.wait()).await {
+            Ok(Ok(status)) => status,
+            _ => return None,
+        };
+        match status.signal() {
+            // `SIGXCPU` is sent when the CPU time runs out, and `SIGKILL` if that is ignored
+            Some(signal)
+                if limits.max_cpu_seconds.is_some()
+                    && (signal == process::Signal::SIGXCPU as i32
+                        || signal == process::Signal::SIGKILL as i32) =>
+            {
+                Some(ExecutionLimitExceeded::CpuTime)
+            }
+            // Language runtimes treat a failed allocation as fatal, so a process that runs out of
+            // address space exits abnormally
+            _ if limits.max_memory_bytes.is_some() && !status.success() => {
+                Some(ExecutionLimitExceeded::Memory)
+            }
+            _ => None,
+        }
+    }
+
     fn filter_output(output: &mut LangServerOutput, credentials: &[SensitiveString]) -> Result<()> {
         // Note: This brings a possibility of random substrings being matched out of context,
         // exposing that we have a secret by censoring it But trying to infer word boundary might
@@ -303,6 +474,16 @@ where
         Ok(())
     }
 
+    fn filter_progress(progress: &mut LangServerProgress, credentials: &[SensitiveString]) {
+        if let Some(step) = progress.step.as_mut() {
+            for credential in credentials {
+                if step.contains(credential.as_str()) {
+                    *step = step.replace(credential.as_str(), "[redacted]");
+                }
+            }
+        }
+    }
+
     fn filter_result(
         result: &mut LangServerResult<LangServerSuccess>,
         credentials: &[SensitiveString],
@@ -409,6 +590,7 @@ where
 #[serde(tag = "protocol", rename_all = "camelCase")]
 pub enum LangServerMessage<Success> {
     Output(LangServerOutput),
+    Progress(LangServerProgress),
     Result(LangServerResult<Success>),
 }
 
@@ -420,6 +602,8 @@ pub struct LangServerOutput {
     level: String,
     group: Option<String>,
     message: String,
+    /// When the line was emitted, if the language server recorded it.
+    timestamp: Option<u64>,
 }
 
 impl From<LangServerOutput> for OutputStream {
@@ -430,7 +614,30 @@ impl From<LangServerOutput> for OutputStream {
             level: value.level,
             group: value.group,
             message: value.message,
-            timestamp: crate::timestamp(),
+            timestamp: value.timestamp.unwrap_or_else(crate::timestamp),
+        }
+    }
+}
+
+#[derive(Debug, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LangServerProgress {
+    execution_id: String,
+    current: u64,
+    total: Option<u64>,
+    step: Option<String>,
+    /// When the progress was reported, if the language server recorded it.
+    timestamp: Option<u64>,
+}
+
+impl From<LangServerProgress> for FunctionProgress {
+    fn from(value: LangServerProgress) -> Self {
+        Self {
+            execution_id: value.execution_id,
+            current: value.current,
+            total: value.total,
+            step: value.step,
+            timestamp: value.timestamp.unwrap_or_else(crate::timestamp),
         }
     }
 }
@@ -454,6 +661,10 @@ where
             LangServerResult::Failure(failure) => Self::Failure(FunctionResultFailure {
                 execution_id: failure.execution_id,
                 error: FunctionResultFailureError {
+                    category: failure
+                        .error
+                        .category
+                        .unwrap_or_else(|| FunctionErrorKind::classify(&failure.error.kind)),
                     kind: failure.error.kind,
                     message: failure.error.message,
                 },
@@ -476,4 +687,6 @@ pub struct LangServerFailure {
 struct LangServerFailureError {
     kind: String,
     message: String,
+    /// The category of the failure, if the language server knows it.
+    category: Option<FunctionErrorKind>,
 }
@@ -5,15 +5,17 @@ use std::sync::{
 
 use async_trait::async_trait;
 use axum::{
-    extract::{Extension, FromRequestParts},
+    extract::{Extension, FromRef, FromRequestParts, Query},
     http::request::Parts,
     Json,
 };
+use cyclone_core::SandboxProfile;
 use hyper::StatusCode;
+use serde::Deserialize;
 use telemetry::prelude::*;
 use tokio::sync::mpsc;
 
-use super::server::ShutdownSource;
+use super::{server::ShutdownSource, state::SandboxProfileSet};
 
 #[derive(Clone, Debug)]
 pub struct RequestLimiter {
@@ -82,6 +84,55 @@ where
     }
 }
 
+/// The [`SandboxProfile`] a request selected with its `sandboxProfile` query parameter.
+/// Requests selecting a profile the server doesn't offer are rejected.
+pub struct SelectedSandboxProfile(pub SandboxProfile);
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct SandboxProfileQuery {
+    sandbox_profile: Option<String>,
+}
+
+#[async_trait]
+impl<S> FromRequestParts<S> for SelectedSandboxProfile
+where
+    SandboxProfileSet: FromRef<S>,
+    S: Send + Sync,
+{
+    type Rejection = (StatusCode, Json<serde_json::Value>);
+
+    async fn from_request_parts(req: &mut Parts, state: &S) -> Result<Self, Self::Rejection> {
+        let Query(query) = Query::<SandboxProfileQuery>::from_request_parts(req, state)
+            .await
+            .map_err(|err| bad_request(err.body_text()))?;
+        let name = query.sandbox_profile.as_deref();
+
+        SandboxProfileSet::from_ref(state)
+            .select(name)
+            .map(Self)
+            .ok_or_else(|| {
+                bad_request(format!(
+                    "unknown sandbox profile: {}",
+                    name.unwrap_or_default()
+                ))
+            })
+    }
+}
+
+fn bad_request(message: String) -> (StatusCode, Json<serde_json::Value>) {
+    let status_code = StatusCode::BAD_REQUEST;
+    (
+        status_code,
+        Json(serde_json::json!({
+            "error": {
+                "message": message,
+                "statusCode": status_code.as_u16(),
+            },
+        })),
+    )
+}
+
 fn internal_error(err: impl std::error::Error) -> (StatusCode, Json<serde_json::Value>) {
     let status_code = StatusCode::INTERNAL_SERVER_ERROR;
     (
@@ -1,7 +1,6 @@
 use std::{
     fmt,
     marker::{PhantomData, Unpin},
-    path::PathBuf,
     sync::Arc,
 };
 
@@ -13,19 +12,19 @@ use axum::{
     response::IntoResponse,
 };
 use cyclone_core::{
-    ActionRunRequest, ActionRunResultSuccess, LivenessStatus, Message, ReadinessStatus,
-    ReconciliationRequest, ReconciliationResultSuccess, ResolverFunctionRequest,
-    ResolverFunctionResultSuccess, SchemaVariantDefinitionRequest,
+    ActionRunRequest, ActionRunResultSuccess, ExecutionLimits, LivenessStatus, Message,
+    ReadinessStatus, ReconciliationRequest, ReconciliationResultSuccess, ResolverFunctionRequest,
+    ResolverFunctionResultSuccess, SandboxProfile, SchemaVariantDefinitionRequest,
     SchemaVariantDefinitionResultSuccess, ValidationRequest, ValidationResultSuccess,
 };
 use hyper::StatusCode;
 use serde::{de::DeserializeOwned, Serialize};
 use telemetry::prelude::*;
 
-use super::extract::LimitRequestGuard;
+use super::extract::{LimitRequestGuard, SelectedSandboxProfile};
 use crate::{
     execution::{self, Execution},
-    request::{DecryptRequest, ListSecrets},
+    request::{DecryptRequest, ListSecrets, RequestLanguage},
     result::{
         LangServerActionRunResultSuccess, LangServerReconciliationResultSuccess,
         LangServerResolverFunctionResultSuccess, LangServerValidationResultSuccess,
@@ -86,9 +85,10 @@ pub async fn ws_execute_resolver(
     State(lang_server_path): State<LangServerPath>,
     State(key): State<DecryptionKey>,
     State(telemetry_level): State<TelemetryLevel>,
+    State(execution_limits): State<ExecutionLimits>,
+    SelectedSandboxProfile(sandbox_profile): SelectedSandboxProfile,
     limit_request_guard: LimitRequestGuard,
 ) -> impl IntoResponse {
-    let lang_server_path = lang_server_path.as_path().to_path_buf();
     wsu.on_upgrade(move |socket| {
         let request: PhantomData<ResolverFunctionRequest> = PhantomData;
         let lang_server_success: PhantomData<LangServerResolverFunctionResultSuccess> = PhantomData;
@@ -97,6 +97,8 @@ pub async fn ws_execute_resolver(
             socket,
             lang_server_path,
             telemetry_level.is_debug_or_lower(),
+            execution_limits,
+            sandbox_profile,
             key.into(),
             limit_request_guard,
             "resolverfunction".to_owned(),
@@ -113,9 +115,10 @@ pub async fn ws_execute_validation(
     State(lang_server_path): State<LangServerPath>,
     State(key): State<DecryptionKey>,
     State(telemetry_level): State<TelemetryLevel>,
+    State(execution_limits): State<ExecutionLimits>,
+    SelectedSandboxProfile(sandbox_profile): SelectedSandboxProfile,
     limit_request_guard: LimitRequestGuard,
 ) -> impl IntoResponse {
-    let lang_server_path = lang_server_path.as_path().to_path_buf();
     wsu.on_upgrade(move |socket| {
         let request: PhantomData<ValidationRequest> = PhantomData;
         let lang_server_success: PhantomData<LangServerValidationResultSuccess> = PhantomData;
@@ -124,6 +127,8 @@ pub async fn ws_execute_validation(
             socket,
             lang_server_path,
             telemetry_level.is_debug_or_lower(),
+            execution_limits,
+            sandbox_profile,
             key.into(),
             limit_request_guard,
             "validation".to_owned(),
@@ -140,9 +145,10 @@ pub async fn ws_execute_action_run(
     State(lang_server_path): State<LangServerPath>,
     State(key): State<DecryptionKey>,
     State(telemetry_level): State<TelemetryLevel>,
+    State(execution_limits): State<ExecutionLimits>,
+    SelectedSandboxProfile(sandbox_profile): SelectedSandboxProfile,
     limit_request_guard: LimitRequestGuard,
 ) -> impl IntoResponse {
-    let lang_server_path = lang_server_path.as_path().to_path_buf();
     wsu.on_upgrade(move |socket| {
         let request: PhantomData<ActionRunRequest> = PhantomData;
         let lang_server_success: PhantomData<LangServerActionRunResultSuccess> = PhantomData;
@@ -151,6 +157,8 @@ pub async fn ws_execute_action_run(
             socket,
             lang_server_path,
             telemetry_level.is_debug_or_lower(),
+            execution_limits,
+            sandbox_profile,
             key.into(),
             limit_request_guard,
             "actionRun".to_owned(),
@@ -167,9 +175,10 @@ pub async fn ws_execute_reconciliation(
     State(lang_server_path): State<LangServerPath>,
     State(key): State<DecryptionKey>,
     State(telemetry_level): State<TelemetryLevel>,
+    State(execution_limits): State<ExecutionLimits>,
+    SelectedSandboxProfile(sandbox_profile): SelectedSandboxProfile,
     limit_request_guard: LimitRequestGuard,
 ) -> impl IntoResponse {
-    let lang_server_path = lang_server_path.as_path().to_path_buf();
     wsu.on_upgrade(move |socket| {
         let request: PhantomData<ReconciliationRequest> = PhantomData;
         let lang_server_success: PhantomData<LangServerReconciliationResultSuccess> = PhantomData;
@@ -178,6 +187,8 @@ pub async fn ws_execute_reconciliation(
             socket,
             lang_server_path,
             telemetry_level.is_debug_or_lower(),
+            execution_limits,
+            sandbox_profile,
             key.into(),
             limit_request_guard,
             "reconciliation".to_owned(),
@@ -194,9 +205,10 @@ pub async fn ws_execute_schema_variant_definition(
     State(lang_server_path): State<LangServerPath>,
     State(key): State<DecryptionKey>,
     State(telemetry_level): State<TelemetryLevel>,
+    State(execution_limits): State<ExecutionLimits>,
+    SelectedSandboxProfile(sandbox_profile): SelectedSandboxProfile,
     limit_request_guard: LimitRequestGuard,
 ) -> impl IntoResponse {
-    let lang_server_path = lang_server_path.as_path().to_path_buf();
     wsu.on_upgrade(move |socket| {
         let request: PhantomData<SchemaVariantDefinitionRequest> = PhantomData;
         let lang_server_success: PhantomData<SchemaVariantDefinitionResultSuccess> = PhantomData;
@@ -205,6 +217,8 @@ pub async fn ws_execute_schema_variant_definition(
             socket,
             lang_server_path,
             telemetry_level.is_debug_or_lower(),
+            execution_limits,
+            sandbox_profile,
             key.into(),
             limit_request_guard,
             "schemaVariantDefinition".to_owned(),
@@ -218,8 +232,10 @@ pub async fn ws_execute_schema_variant_definition(
 #[allow(clippy::too_many_arguments)]
 async fn handle_socket<Request, LangServerSuccess, Success>(
     mut socket: WebSocket,
-    lang_server_path: PathBuf,
+    lang_server_path: LangServerPath,
     lang_server_debugging: bool,
+    execution_limits: ExecutionLimits,
+    sandbox_profile: SandboxProfile,
     key: Arc<crate::DecryptionKey>,
     _limit_request_guard: LimitRequestGuard,
     sub_command: String,
@@ -227,13 +243,25 @@ async fn handle_socket<Request, LangServerSuccess, Success>(
     _lang_server_success_marker: PhantomData<LangServerSuccess>,
     success_marker: PhantomData<Success>,
 ) where
-    Request: DecryptRequest + ListSecrets + Serialize + DeserializeOwned + Unpin + fmt::Debug,
+    Request: DecryptRequest
+        + ListSecrets
+        + RequestLanguage
+        + Serialize
+        + DeserializeOwned
+        + Unpin
+        + fmt::Debug,
     Success: Serialize + Unpin + fmt::Debug,
     LangServerSuccess: Serialize + DeserializeOwned + Unpin + fmt::Debug + Into<Success>,
 {
     let proto = {
-        let execution: Execution<Request, LangServerSuccess, Success> =
-            execution::new(lang_server_path, lang_server_debugging, key, sub_command);
+        let execution: Execution<Request, LangServerSuccess, Success> = execution::new(
+            lang_server_path,
+            lang_server_debugging,
+            execution_limits,
+            sandbox_profile,
+            key,
+            sub_command,
+        );
         match execution.start(&mut socket).await {
             Ok(started) => started,
             Err(err) => {
@@ -1,6 +1,7 @@
 use cyclone_core::{
-    ActionRunRequest, ComponentKind, ComponentView, ReconciliationRequest, ResolverFunctionRequest,
-    SchemaVariantDefinitionRequest, SensitiveString, ValidationRequest,
+    ActionRunRequest, BeforeFunction, ComponentKind, ComponentView, FunctionLanguage,
+    ReconciliationRequest, ResolverFunctionRequest, SchemaVariantDefinitionRequest,
+    SensitiveString, ValidationRequest,
 };
 use serde_json::Value;
 
@@ -15,6 +16,41 @@ pub trait DecryptRequest {
     fn decrypt_request(self, key: &DecryptionKey) -> Result<serde_json::Value, DecryptionKeyError>;
 }
 
+/// The language a request's function is written in, which picks the language server running it.
+pub trait RequestLanguage {
+    fn lang(&self) -> FunctionLanguage;
+}
+
+impl RequestLanguage for ResolverFunctionRequest {
+    fn lang(&self) -> FunctionLanguage {
+        self.lang
+    }
+}
+
+impl RequestLanguage for ActionRunRequest {
+    fn lang(&self) -> FunctionLanguage {
+        self.lang
+    }
+}
+
+impl RequestLanguage for ReconciliationRequest {
+    fn lang(&self) -> FunctionLanguage {
+        self.lang
+    }
+}
+
+impl RequestLanguage for ValidationRequest {
+    fn lang(&self) -> FunctionLanguage {
+        self.lang
+    }
+}
+
+impl RequestLanguage for SchemaVariantDefinitionRequest {
+    fn lang(&self) -> FunctionLanguage {
+        self.lang
+    }
+}
+
 impl ListSecrets for ComponentView {
     fn list_secrets(
         &self,
@@ -132,6 +168,74 @@ impl DecryptRequest for ComponentView {
     }
 }
 
+impl ListSecrets for BeforeFunction {
+    fn list_secrets(
+        &self,
+        key: &DecryptionKey,
+    ) -> Result<Vec<SensitiveString>, DecryptionKeyError> {
+        credential_view(self.arg.clone()).list_secrets(key)
+    }
+}
+
+impl DecryptRequest for BeforeFunction {
+    fn decrypt_request(self, key: &DecryptionKey) -> Result<serde_json::Value, DecryptionKeyError> {
+        let mut value = serde_json::to_value(&self)?;
+        let mut arg = credential_view(self.arg).decrypt_request(key)?;
+        match (value.pointer_mut("/arg"), arg.pointer_mut("/properties")) {
+            (Some(v), Some(decrypted)) => *v = decrypted.take(),
+            _ => {
+                return Err(DecryptionKeyError::JSONPointerNotFound(
+                    value,
+                    "/arg".to_owned(),
+                ));
+            }
+        }
+        Ok(value)
+    }
+}
+
+/// Wraps a before function's argument as a credential, so that the secrets in it are found the
+/// same way as in a credential component.
+fn credential_view(properties: Value) -> ComponentView {
+    ComponentView {
+        kind: ComponentKind::Credential,
+        properties,
+    }
+}
+
+fn list_before_secrets(
+    before: &[BeforeFunction],
+    key: &DecryptionKey,
+) -> Result<Vec<SensitiveString>, DecryptionKeyError> {
+    let mut secrets = vec![];
+    for function in before {
+        secrets.extend(function.list_secrets(key)?);
+    }
+    Ok(secrets)
+}
+
+/// Replaces the before functions of a serialized request with their decrypted form.
+fn decrypt_before(
+    value: &mut Value,
+    before: Vec<BeforeFunction>,
+    key: &DecryptionKey,
+) -> Result<(), DecryptionKeyError> {
+    let mut decrypted = Vec::with_capacity(before.len());
+    for function in before {
+        decrypted.push(function.decrypt_request(key)?);
+    }
+    match value.pointer_mut("/before") {
+        Some(v) => *v = Value::Array(decrypted),
+        None => {
+            return Err(DecryptionKeyError::JSONPointerNotFound(
+                value.take(),
+                "/before".to_owned(),
+            ));
+        }
+    }
+    Ok(())
+}
+
 impl ListSecrets for ResolverFunctionRequest {
     fn list_secrets(
         &self,
@@ -141,6 +245,7 @@ impl ListSecrets for ResolverFunctionRequest {
         for component in &self.component.parents {
             secrets.extend(component.list_secrets(key)?);
         }
+        secrets.extend(list_before_secrets(&self.before, key)?);
         Ok(secrets)
     }
 }
@@ -174,6 +279,7 @@ impl DecryptRequest for ResolverFunctionRequest {
                 ));
             }
         }
+        decrypt_before(&mut value, self.before, key)?;
         Ok(value)
     }
 }
@@ -181,20 +287,18 @@ impl DecryptRequest for ResolverFunctionRequest {
 impl ListSecrets for ActionRunRequest {
     fn list_secrets(
         &self,
-        _key: &DecryptionKey,
+        key: &DecryptionKey,
     ) -> Result<Vec<SensitiveString>, DecryptionKeyError> {
         // TODO(fnichol): we'll need to populate/consume secrets here shortly
-        Ok(vec![])
+        list_before_secrets(&self.before, key)
     }
 }
 
 impl DecryptRequest for ActionRunRequest {
-    fn decrypt_request(
-        self,
-        _key: &DecryptionKey,
-    ) -> Result<serde_json::Value, DecryptionKeyError> {
-        let value = serde_json::to_value(&self)?;
+    fn decrypt_request(self, key: &DecryptionKey) -> Result<serde_json::Value, DecryptionKeyError> {
+        let mut value = serde_json::to_value(&self)?;
         // TODO(fnichol): we'll need to process the request with decrypted secrets
+        decrypt_before(&mut value, self.before, key)?;
         Ok(value)
     }
 }
@@ -202,20 +306,18 @@ impl DecryptRequest for ActionRunRequest {
 impl ListSecrets for ReconciliationRequest {
     fn list_secrets(
         &self,
-        _key: &DecryptionKey,
+        key: &DecryptionKey,
     ) -> Result<Vec<SensitiveString>, DecryptionKeyError> {
         // TODO(fnichol): we'll need to populate/consume secrets here shortly
-        Ok(vec![])
+        list_before_secrets(&self.before, key)
     }
 }
 
 impl DecryptRequest for ReconciliationRequest {
-    fn decrypt_request(
-        self,
-        _key: &DecryptionKey,
-    ) -> Result<serde_json::Value, DecryptionKeyError> {
-        let value = serde_json::to_value(&self)?;
+    fn decrypt_request(self, key: &DecryptionKey) -> Result<serde_json::Value, DecryptionKeyError> {
+        let mut value = serde_json::to_value(&self)?;
         // TODO(fnichol): we'll need to process the request with decrypted secrets
+        decrypt_before(&mut value, self.before, key)?;
         Ok(value)
     }
 }
@@ -223,20 +325,18 @@ impl DecryptRequest for ReconciliationRequest {
 impl ListSecrets for ValidationRequest {
     fn list_secrets(
         &self,
-        _key: &DecryptionKey,
+        key: &DecryptionKey,
     ) -> Result<Vec<SensitiveString>, DecryptionKeyError> {
         // TODO(fnichol): we'll need to populate/consume secrets here shortly
-        Ok(vec![])
+        list_before_secrets(&self.before, key)
     }
 }
 
 impl DecryptRequest for ValidationRequest {
-    fn decrypt_request(
-        self,
-        _key: &DecryptionKey,
-    ) -> Result<serde_json::Value, DecryptionKeyError> {
-        let value = serde_json::to_value(&self)?;
+    fn decrypt_request(self, key: &DecryptionKey) -> Result<serde_json::Value, DecryptionKeyError> {
+        let mut value = serde_json::to_value(&self)?;
         // TODO(fnichol): we'll need to process the request with decrypted secrets
+        decrypt_before(&mut value, self.before, key)?;
         Ok(value)
     }
 }
@@ -244,20 +344,18 @@ impl DecryptRequest for ValidationRequest {
 impl ListSecrets for SchemaVariantDefinitionRequest {
     fn list_secrets(
         &self,
-        _key: &DecryptionKey,
+        key: &DecryptionKey,
     ) -> Result<Vec<SensitiveString>, DecryptionKeyError> {
         // TODO(fnichol): we'll need to populate/consume secrets here shortly
-        Ok(vec![])
+        list_before_secrets(&self.before, key)
     }
 }
 
 impl DecryptRequest for SchemaVariantDefinitionRequest {
-    fn decrypt_request(
-        self,
-        _key: &DecryptionKey,
-    ) -> Result<serde_json::Value, DecryptionKeyError> {
-        let value = serde_json::to_value(self)?;
+    fn decrypt_request(self, key: &DecryptionKey) -> Result<serde_json::Value, DecryptionKeyError> {
+        let mut value = serde_json::to_value(&self)?;
         // TODO(fnichol): we'll need to process the request with decrypted secrets
+        decrypt_before(&mut value, self.before, key)?;
         Ok(value)
     }
 }
@@ -356,4 +454,42 @@ mod tests {
         });
         assert_eq!(json, decrypted_json);
     }
+
+    #[test]
+    fn before_function_args_are_decrypted() {
+        let (pkey, skey) = gen_keypair();
+        let decryption_key = DecryptionKey::from(skey);
+
+        let secret_json = serde_json::json!({ "accessKeyId": "Varginha's UFO" });
+        let secret = serde_json::to_string(&secret_json).expect("Unable to serialize secret");
+        let encoded = encrypt_and_encode(secret.as_bytes(), &pkey);
+
+        let request = ActionRunRequest {
+            execution_id: "1".to_owned(),
+            handler: "run".to_owned(),
+            code_base64: String::new(),
+            args: serde_json::json!({}),
+            lang: FunctionLanguage::JavaScript,
+            before: vec![BeforeFunction {
+                handler: "auth".to_owned(),
+                code_base64: String::new(),
+                arg: serde_json::json!({
+                    "credential": { "cycloneEncryptedDataMarker": true, "encryptedSecret": encoded },
+                }),
+            }],
+        };
+
+        let secrets = request
+            .list_secrets(&decryption_key)
+            .expect("Unable to list secrets");
+        assert_eq!(secrets.len(), 1);
+
+        let decrypted = request
+            .decrypt_request(&decryption_key)
+            .expect("Unable to decrypt action run request");
+        assert_eq!(
+            decrypted.pointer("/before/0/arg/credential"),
+            Some(&secret_json)
+        );
+    }
 }
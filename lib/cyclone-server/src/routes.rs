@@ -1,6 +1,13 @@
 use std::sync::Arc;
 
-use axum::{routing::get, Extension, Router};
+use axum::{
+    extract::State,
+    http::{header::AUTHORIZATION, Request, StatusCode},
+    middleware::{self, Next},
+    response::{IntoResponse, Response},
+    routing::get,
+    Extension, Router,
+};
 use telemetry::prelude::*;
 use tokio::sync::mpsc;
 
@@ -46,9 +53,35 @@ pub fn routes(
         );
     }
 
+    if let Some(bearer_token) = config.bearer_token() {
+        debug!("requiring bearer token authorization");
+        router = router.route_layer(middleware::from_fn_with_state(
+            Arc::new(format!("Bearer {bearer_token}")),
+            require_bearer_token,
+        ));
+    }
+
     router.with_state(state)
 }
 
+async fn require_bearer_token<B>(
+    State(expected): State<Arc<String>>,
+    request: Request<B>,
+    next: Next<B>,
+) -> Response {
+    let authorized = request
+        .headers()
+        .get(AUTHORIZATION)
+        .map_or(false, |value| value.as_bytes() == expected.as_bytes());
+
+    if authorized {
+        next.run(request).await
+    } else {
+        warn!("rejecting request without a valid bearer token");
+        StatusCode::UNAUTHORIZED.into_response()
+    }
+}
+
 fn execute_routes(config: &Config, shutdown_tx: mpsc::Sender<ShutdownSource>) -> Router<AppState> {
     let mut router = Router::new();
 
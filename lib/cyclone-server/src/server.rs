@@ -149,7 +149,14 @@ fn build_service(
 ) -> Result<(IntoMakeService<Router>, oneshot::Receiver<()>)> {
     let (shutdown_tx, shutdown_rx) = mpsc::channel(4);
 
-    let state = AppState::new(config.lang_server_path(), decryption_key, telemetry_level);
+    let state = AppState::new(
+        config.lang_server_path(),
+        config.lang_servers().clone(),
+        decryption_key,
+        telemetry_level,
+        config.execution_limits(),
+        config.sandbox_profiles().clone(),
+    );
 
     let routes = routes(config, state, shutdown_tx)
         // TODO(fnichol): customize http tracing further, using:
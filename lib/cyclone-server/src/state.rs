@@ -6,6 +6,10 @@ use std::{
 };
 
 use axum::extract::FromRef;
+use cyclone_core::{
+    ExecutionLimits, FunctionLanguage, LangServers, SandboxProfile, SandboxProfiles,
+    DEFAULT_SANDBOX_PROFILE,
+};
 use tokio::sync::mpsc;
 
 #[derive(Clone, FromRef)]
@@ -13,28 +17,57 @@ pub struct AppState {
     lang_server_path: LangServerPath,
     decryption_key: DecryptionKey,
     telemetry_level: TelemetryLevel,
+    execution_limits: ExecutionLimits,
+    sandbox_profiles: SandboxProfileSet,
 }
 
 impl AppState {
     pub fn new(
         lang_server_path: impl Into<PathBuf>,
+        lang_servers: LangServers,
         decryption_key: crate::DecryptionKey,
         telemetry_level: Box<dyn telemetry::TelemetryLevel>,
+        execution_limits: ExecutionLimits,
+        sandbox_profiles: SandboxProfiles,
     ) -> Self {
         Self {
-            lang_server_path: LangServerPath(Arc::new(lang_server_path.into())),
+            lang_server_path: LangServerPath::new(lang_server_path, lang_servers),
             decryption_key: DecryptionKey(Arc::new(decryption_key)),
             telemetry_level: TelemetryLevel(Arc::new(telemetry_level)),
+            execution_limits,
+            sandbox_profiles: SandboxProfileSet(Arc::new(sandbox_profiles)),
         }
     }
 }
 
+/// The language server programs of a server: the default one, which runs JavaScript functions,
+/// and the ones running functions of other languages.
 #[derive(Clone, Debug, FromRef)]
-pub struct LangServerPath(Arc<PathBuf>);
+pub struct LangServerPath {
+    default: Arc<PathBuf>,
+    by_language: Arc<LangServers>,
+}
 
 impl LangServerPath {
-    pub fn as_path(&self) -> &Path {
-        self.0.as_path()
+    pub fn new(default: impl Into<PathBuf>, by_language: LangServers) -> Self {
+        Self {
+            default: Arc::new(default.into()),
+            by_language: Arc::new(by_language),
+        }
+    }
+
+    /// Returns the program that runs functions of the given language, if the server has one. The
+    /// default program runs JavaScript functions.
+    pub fn for_language(&self, lang: FunctionLanguage) -> Option<&Path> {
+        match lang {
+            FunctionLanguage::JavaScript => Some(
+                self.by_language
+                    .get(&FunctionLanguage::JavaScript)
+                    .unwrap_or(&*self.default)
+                    .as_path(),
+            ),
+            lang => self.by_language.get(&lang).map(PathBuf::as_path),
+        }
     }
 }
 
@@ -66,6 +99,26 @@ impl Deref for TelemetryLevel {
     }
 }
 
+#[derive(Clone, Debug, FromRef)]
+pub struct SandboxProfileSet(Arc<SandboxProfiles>);
+
+impl SandboxProfileSet {
+    /// Looks up the profile a request selected by name, falling back to the
+    /// [default profile](DEFAULT_SANDBOX_PROFILE) and then to no restrictions at all if the
+    /// request selected none. Returns `None` if the selected profile doesn't exist.
+    pub fn select(&self, name: Option<&str>) -> Option<SandboxProfile> {
+        match name {
+            Some(name) => self.0.get(name).cloned(),
+            None => Some(
+                self.0
+                    .get(DEFAULT_SANDBOX_PROFILE)
+                    .cloned()
+                    .unwrap_or_default(),
+            ),
+        }
+    }
+}
+
 pub struct WatchKeepalive {
     tx: mpsc::Sender<()>,
     timeout: Duration,
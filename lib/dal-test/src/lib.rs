@@ -23,7 +23,7 @@ use si_std::ResultExt;
 use telemetry::prelude::*;
 use tokio::{fs::File, io::AsyncReadExt, sync::Mutex};
 use uuid::Uuid;
-use veritech_client::EncryptionKey;
+use veritech_client::{EncryptionKey, RecordReplay};
 use veritech_server::StandardConfig;
 
 pub use color_eyre::{
@@ -42,6 +42,8 @@ const ENV_VAR_MODULE_INDEX_URL: &str = "SI_TEST_MODULE_INDEX_URL";
 const ENV_VAR_PG_HOSTNAME: &str = "SI_TEST_PG_HOSTNAME";
 const ENV_VAR_PG_DBNAME: &str = "SI_TEST_PG_DBNAME";
 const ENV_VAR_BUILTIN_SCHEMAS: &str = "SI_TEST_BUILTIN_SCHEMAS";
+const ENV_VAR_VERITECH_RECORD_DIR: &str = "SI_TEST_VERITECH_RECORD_DIR";
+const ENV_VAR_VERITECH_REPLAY_DIR: &str = "SI_TEST_VERITECH_REPLAY_DIR";
 
 pub static COLOR_EYRE_INIT: Once = Once::new();
 
@@ -88,6 +90,9 @@ pub struct Config {
     jwt_signing_private_key_path: String,
     #[builder(default)]
     pkgs_path: Option<PathBuf>,
+    /// Records function executions to, or replays them from, a fixture directory.
+    #[builder(default)]
+    veritech_record_replay: Option<RecordReplay>,
 }
 
 impl Config {
@@ -114,6 +119,12 @@ impl Config {
             config.module_index_url = value;
         }
 
+        if let Ok(value) = env::var(ENV_VAR_VERITECH_REPLAY_DIR) {
+            config.veritech_record_replay = Some(RecordReplay::Replay(value.into()));
+        } else if let Ok(value) = env::var(ENV_VAR_VERITECH_RECORD_DIR) {
+            config.veritech_record_replay = Some(RecordReplay::Record(value.into()));
+        }
+
         Ok(config)
     }
 }
@@ -226,7 +237,10 @@ impl TestContext {
 
     /// Creates a new [`ServicesContext`].
     pub async fn create_services_context(&self) -> ServicesContext {
-        let veritech = veritech_client::Client::new(self.nats_conn.clone());
+        let mut veritech = veritech_client::Client::new(self.nats_conn.clone());
+        if let Some(record_replay) = &self.config.veritech_record_replay {
+            veritech = veritech.with_record_replay(record_replay.clone());
+        }
 
         ServicesContext::new(
             self.pg_pool.clone(),
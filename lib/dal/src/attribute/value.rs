@@ -43,6 +43,7 @@ use si_data_pg::PgError;
 use std::collections::HashMap;
 use telemetry::prelude::*;
 use thiserror::Error;
+use veritech_client::FunctionErrorKind;
 
 use crate::{
     attribute::{
@@ -127,6 +128,7 @@ pub enum AttributeValueError {
         kind: String,
         message: String,
         backend: String,
+        category: FunctionErrorKind,
     },
     #[error("func binding error: {0}")]
     FuncBinding(#[from] FuncBindingError),
@@ -224,6 +226,18 @@ pub enum AttributeValueError {
     WsEvent(#[from] WsEventError),
 }
 
+impl AttributeValueError {
+    /// Returns whether the value's function failed in a way that may not repeat if it is executed
+    /// again.
+    pub fn is_retryable(&self) -> bool {
+        match self {
+            Self::FuncBackendResultFailure { category, .. } => category.is_retryable(),
+            Self::FuncBinding(err) => err.is_retryable(),
+            _ => false,
+        }
+    }
+}
+
 pub type AttributeValueResult<T> = Result<T, AttributeValueError>;
 
 pk!(AttributeValuePk);
@@ -1123,11 +1137,13 @@ impl AttributeValue {
                 kind,
                 message,
                 backend,
+                category,
             }) => {
                 return Err(AttributeValueError::FuncBackendResultFailure {
                     kind,
                     message,
                     backend,
+                    category,
                 })
             }
             Err(err) => Err(err)?,
@@ -0,0 +1,396 @@
+//! This module contains [`ChangeSetReview`], which records review requests and decisions on a
+//! [`ChangeSet`](crate::ChangeSet), and the per-workspace policy for how many approvals are
+//! required before a change set may be applied.
+
+use serde::{Deserialize, Serialize};
+use si_data_nats::NatsError;
+use si_data_pg::PgError;
+use strum::{Display, EnumString};
+use telemetry::prelude::*;
+use thiserror::Error;
+
+use crate::standard_model::{object_option_from_row_option, objects_from_rows};
+use crate::ws_event::{WsEvent, WsEventError, WsPayload};
+use crate::{
+    pk, ChangeSetPk, DalContext, HistoryActor, HistoryEvent, HistoryEventError, StandardModelError,
+    Tenancy, Timestamp, TransactionsError, User, UserError, UserPk, WsEventResult,
+};
+
+const CHANGE_SET_AUTHOR: &str = include_str!("queries/change_set_review/change_set_author.sql");
+const LIST_FOR_CHANGE_SET: &str = include_str!("queries/change_set_review/list_for_change_set.sql");
+const POLICY_GET: &str = include_str!("queries/change_set_review/policy_get.sql");
+
+#[remain::sorted]
+#[derive(Error, Debug)]
+pub enum ChangeSetReviewError {
+    #[error(transparent)]
+    HistoryEvent(#[from] HistoryEventError),
+    #[error("a review decision must be approved or rejected, found: {0}")]
+    InvalidDecision(ChangeSetReviewStatus),
+    #[error("required approvals must not be negative: {0}")]
+    InvalidRequiredApprovals(i32),
+    #[error(transparent)]
+    Nats(#[from] NatsError),
+    #[error("only users can review change sets")]
+    NotAUser,
+    #[error("user {0} must own or administer the workspace to change its review policy")]
+    NotAWorkspaceAdmin(UserPk),
+    #[error("change set {0} requires {1} approvals but has {2}")]
+    NotEnoughApprovals(ChangeSetPk, usize, usize),
+    #[error("user {1} has no pending review request for change set {0}")]
+    NotRequested(ChangeSetPk, UserPk),
+    #[error(transparent)]
+    Pg(#[from] PgError),
+    #[error("change set {0} has been rejected by one or more reviewers")]
+    Rejected(ChangeSetPk),
+    #[error("user {1} authored change set {0} and cannot approve it")]
+    SelfApproval(ChangeSetPk, UserPk),
+    #[error(transparent)]
+    SerdeJson(#[from] serde_json::Error),
+    #[error(transparent)]
+    StandardModel(#[from] StandardModelError),
+    #[error(transparent)]
+    Transactions(#[from] TransactionsError),
+    #[error(transparent)]
+    User(#[from] UserError),
+    #[error(transparent)]
+    WsEvent(#[from] WsEventError),
+}
+
+pub type ChangeSetReviewResult<T> = Result<T, ChangeSetReviewError>;
+
+#[remain::sorted]
+#[derive(Deserialize, Serialize, Debug, Display, EnumString, PartialEq, Eq, Clone, Copy)]
+pub enum ChangeSetReviewStatus {
+    Approved,
+    Rejected,
+    Requested,
+}
+
+pk!(ChangeSetReviewPk);
+
+/// A single reviewer's review of a [`ChangeSet`](crate::ChangeSet).
+#[derive(Deserialize, Serialize, Debug, PartialEq, Eq, Clone)]
+pub struct ChangeSetReview {
+    pub pk: ChangeSetReviewPk,
+    pub change_set_pk: ChangeSetPk,
+    pub reviewer_pk: UserPk,
+    pub requested_by_pk: Option<UserPk>,
+    pub status: ChangeSetReviewStatus,
+    pub comment: Option<String>,
+    #[serde(flatten)]
+    pub tenancy: Tenancy,
+    #[serde(flatten)]
+    pub timestamp: Timestamp,
+}
+
+/// The review state of a [`ChangeSet`](crate::ChangeSet) measured against the workspace's
+/// [`ChangeSetReviewPolicy`].
+#[derive(Deserialize, Serialize, Debug, PartialEq, Eq, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct ChangeSetReviewSummary {
+    pub change_set_pk: ChangeSetPk,
+    pub required_approvals: usize,
+    pub approvals: usize,
+    pub rejections: usize,
+    pub pending: usize,
+    pub can_apply: bool,
+    pub reviews: Vec<ChangeSetReview>,
+}
+
+/// How many approvals a workspace requires before a [`ChangeSet`](crate::ChangeSet) may be
+/// applied. Workspaces without a policy require no approvals.
+#[derive(Deserialize, Serialize, Debug, PartialEq, Eq, Clone)]
+pub struct ChangeSetReviewPolicy {
+    pub required_approvals: i32,
+    #[serde(flatten)]
+    pub tenancy: Tenancy,
+    #[serde(flatten)]
+    pub timestamp: Timestamp,
+}
+
+impl ChangeSetReviewPolicy {
+    #[instrument(skip_all)]
+    pub async fn get(ctx: &DalContext) -> ChangeSetReviewResult<Option<Self>> {
+        let row = ctx
+            .txns()
+            .await?
+            .pg()
+            .query_opt(POLICY_GET, &[ctx.tenancy()])
+            .await?;
+        Ok(object_option_from_row_option(row)?)
+    }
+
+    /// Sets how many approvals the current workspace requires. Only the system and users who own
+    /// or administer the workspace may change it.
+    #[instrument(skip_all)]
+    pub async fn set_required_approvals(
+        ctx: &DalContext,
+        required_approvals: i32,
+    ) -> ChangeSetReviewResult<Self> {
+        if let HistoryActor::User(user_pk) = ctx.history_actor() {
+            let role = match ctx.tenancy().workspace_pk() {
+                Some(workspace_pk) => User::role_in_workspace(ctx, *user_pk, workspace_pk).await?,
+                None => None,
+            };
+            if !matches!(role, Some(role) if role.is_admin()) {
+                return Err(ChangeSetReviewError::NotAWorkspaceAdmin(*user_pk));
+            }
+        }
+        if required_approvals < 0 {
+            return Err(ChangeSetReviewError::InvalidRequiredApprovals(
+                required_approvals,
+            ));
+        }
+        let row = ctx
+            .txns()
+            .await?
+            .pg()
+            .query_one(
+                "SELECT object FROM change_set_review_policy_set_v1($1, $2)",
+                &[ctx.tenancy(), &required_approvals],
+            )
+            .await?;
+        let json: serde_json::Value = row.try_get("object")?;
+        let _history_event = HistoryEvent::new(
+            ctx,
+            "change_set_review_policy.update",
+            "Change Set review policy updated",
+            &json,
+        )
+        .await?;
+        Ok(serde_json::from_value(json)?)
+    }
+
+    /// The number of approvals required in the current workspace.
+    pub async fn required_approvals(ctx: &DalContext) -> ChangeSetReviewResult<usize> {
+        Ok(Self::get(ctx)
+            .await?
+            .map(|policy| usize::try_from(policy.required_approvals).unwrap_or_default())
+            .unwrap_or_default())
+    }
+}
+
+impl ChangeSetReview {
+    /// Requests a review of a [`ChangeSet`](crate::ChangeSet) from a reviewer. Requesting a
+    /// review again from the same reviewer clears any previous decision.
+    #[instrument(skip(ctx))]
+    pub async fn request(
+        ctx: &DalContext,
+        change_set_pk: ChangeSetPk,
+        reviewer_pk: UserPk,
+    ) -> ChangeSetReviewResult<Self> {
+        let requested_by_pk = match ctx.history_actor() {
+            HistoryActor::User(user_pk) => Some(*user_pk),
+            HistoryActor::SystemInit => None,
+        };
+        let row = ctx
+            .txns()
+            .await?
+            .pg()
+            .query_one(
+                "SELECT object FROM change_set_review_request_v1($1, $2, $3, $4)",
+                &[
+                    &change_set_pk,
+                    &reviewer_pk,
+                    &requested_by_pk,
+                    ctx.tenancy(),
+                ],
+            )
+            .await?;
+        let json: serde_json::Value = row.try_get("object")?;
+        let _history_event = HistoryEvent::new(
+            ctx,
+            "change_set.review_requested",
+            "Change Set review requested",
+            &json,
+        )
+        .await?;
+        let object: Self = serde_json::from_value(json)?;
+
+        WsEvent::change_set_review_requested(ctx, change_set_pk)
+            .await?
+            .publish_on_commit(ctx)
+            .await?;
+
+        Ok(object)
+    }
+
+    /// Records the current user's approval or rejection of a [`ChangeSet`](crate::ChangeSet).
+    /// The user must have a pending review request, and the author of the change set may not
+    /// approve it.
+    #[instrument(skip(ctx, comment))]
+    pub async fn decide(
+        ctx: &DalContext,
+        change_set_pk: ChangeSetPk,
+        status: ChangeSetReviewStatus,
+        comment: Option<String>,
+    ) -> ChangeSetReviewResult<Self> {
+        if status == ChangeSetReviewStatus::Requested {
+            return Err(ChangeSetReviewError::InvalidDecision(status));
+        }
+        let reviewer_pk = match ctx.history_actor() {
+            HistoryActor::User(user_pk) => *user_pk,
+            HistoryActor::SystemInit => return Err(ChangeSetReviewError::NotAUser),
+        };
+        if status == ChangeSetReviewStatus::Approved
+            && Self::change_set_author(ctx, change_set_pk).await? == Some(reviewer_pk)
+        {
+            return Err(ChangeSetReviewError::SelfApproval(
+                change_set_pk,
+                reviewer_pk,
+            ));
+        }
+        // A reviewer whose approval went stale may decide again
+        Self::invalidate_stale_approvals(ctx, change_set_pk).await?;
+        let row = ctx
+            .txns()
+            .await?
+            .pg()
+            .query_one(
+                "SELECT object FROM change_set_review_decide_v1($1, $2, $3, $4, $5)",
+                &[
+                    &change_set_pk,
+                    &reviewer_pk,
+                    &status.to_string(),
+                    &comment,
+                    ctx.tenancy(),
+                ],
+            )
+            .await?;
+        let json: serde_json::Value = match row.try_get("object")? {
+            Some(json) => json,
+            None => {
+                return Err(ChangeSetReviewError::NotRequested(
+                    change_set_pk,
+                    reviewer_pk,
+                ))
+            }
+        };
+        let _history_event =
+            HistoryEvent::new(ctx, "change_set.reviewed", "Change Set reviewed", &json).await?;
+        let object: Self = serde_json::from_value(json)?;
+
+        WsEvent::change_set_reviewed(ctx, change_set_pk)
+            .await?
+            .publish_on_commit(ctx)
+            .await?;
+
+        Ok(object)
+    }
+
+    /// The user who created the [`ChangeSet`](crate::ChangeSet), if it was created by a user.
+    async fn change_set_author(
+        ctx: &DalContext,
+        change_set_pk: ChangeSetPk,
+    ) -> ChangeSetReviewResult<Option<UserPk>> {
+        let row = ctx
+            .txns()
+            .await?
+            .pg()
+            .query_opt(CHANGE_SET_AUTHOR, &[ctx.tenancy(), &change_set_pk])
+            .await?;
+        Ok(match row {
+            Some(row) => Some(row.try_get("author_pk")?),
+            None => None,
+        })
+    }
+
+    #[instrument(skip(ctx))]
+    pub async fn list_for_change_set(
+        ctx: &DalContext,
+        change_set_pk: ChangeSetPk,
+    ) -> ChangeSetReviewResult<Vec<Self>> {
+        let rows = ctx
+            .txns()
+            .await?
+            .pg()
+            .query(LIST_FOR_CHANGE_SET, &[ctx.tenancy(), &change_set_pk])
+            .await?;
+        Ok(objects_from_rows(rows)?)
+    }
+
+    /// Turns approvals given before the [`ChangeSet`](crate::ChangeSet) was last modified back
+    /// into pending requests, as they do not cover what would now be applied. Returns how many
+    /// approvals were invalidated.
+    #[instrument(skip(ctx))]
+    pub async fn invalidate_stale_approvals(
+        ctx: &DalContext,
+        change_set_pk: ChangeSetPk,
+    ) -> ChangeSetReviewResult<usize> {
+        let row = ctx
+            .txns()
+            .await?
+            .pg()
+            .query_one(
+                "SELECT invalidated FROM change_set_review_invalidate_stale_v1($1, $2)",
+                &[&change_set_pk, ctx.tenancy()],
+            )
+            .await?;
+        let invalidated: i32 = row.try_get("invalidated")?;
+        Ok(usize::try_from(invalidated).unwrap_or_default())
+    }
+
+    /// Summarizes the reviews of a [`ChangeSet`](crate::ChangeSet), after invalidating stale
+    /// approvals.
+    #[instrument(skip(ctx))]
+    pub async fn summary(
+        ctx: &DalContext,
+        change_set_pk: ChangeSetPk,
+    ) -> ChangeSetReviewResult<ChangeSetReviewSummary> {
+        Self::invalidate_stale_approvals(ctx, change_set_pk).await?;
+        let required_approvals = ChangeSetReviewPolicy::required_approvals(ctx).await?;
+        let reviews = Self::list_for_change_set(ctx, change_set_pk).await?;
+
+        let count = |status| reviews.iter().filter(|r| r.status == status).count();
+        let approvals = count(ChangeSetReviewStatus::Approved);
+        let rejections = count(ChangeSetReviewStatus::Rejected);
+        let pending = count(ChangeSetReviewStatus::Requested);
+
+        Ok(ChangeSetReviewSummary {
+            change_set_pk,
+            required_approvals,
+            approvals,
+            rejections,
+            pending,
+            can_apply: rejections == 0 && approvals >= required_approvals,
+            reviews,
+        })
+    }
+
+    /// Returns an error unless the [`ChangeSet`](crate::ChangeSet) satisfies the workspace's
+    /// [`ChangeSetReviewPolicy`] and has not been rejected.
+    pub async fn ensure_approved(
+        ctx: &DalContext,
+        change_set_pk: ChangeSetPk,
+    ) -> ChangeSetReviewResult<()> {
+        let summary = Self::summary(ctx, change_set_pk).await?;
+        if summary.rejections > 0 {
+            return Err(ChangeSetReviewError::Rejected(change_set_pk));
+        }
+        if summary.approvals < summary.required_approvals {
+            return Err(ChangeSetReviewError::NotEnoughApprovals(
+                change_set_pk,
+                summary.required_approvals,
+                summary.approvals,
+            ));
+        }
+        Ok(())
+    }
+}
+
+impl WsEvent {
+    pub async fn change_set_review_requested(
+        ctx: &DalContext,
+        change_set_pk: ChangeSetPk,
+    ) -> WsEventResult<Self> {
+        WsEvent::new(ctx, WsPayload::ChangeSetReviewRequested(change_set_pk)).await
+    }
+
+    pub async fn change_set_reviewed(
+        ctx: &DalContext,
+        change_set_pk: ChangeSetPk,
+    ) -> WsEventResult<Self> {
+        WsEvent::new(ctx, WsPayload::ChangeSetReviewed(change_set_pk)).await
+    }
+}
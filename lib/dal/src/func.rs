@@ -11,8 +11,8 @@ use thiserror::Error;
 use crate::func::argument::FuncArgumentError;
 use crate::{
     impl_standard_model, pk, standard_model, standard_model_accessor, standard_model_accessor_ro,
-    DalContext, FuncBinding, FuncDescriptionContents, HistoryEventError, StandardModel,
-    StandardModelError, Tenancy, Timestamp, TransactionsError, Visibility,
+    DalContext, FuncBinding, FuncDescriptionContents, HistoryEvent, HistoryEventError,
+    StandardModel, StandardModelError, Tenancy, Timestamp, TransactionsError, Visibility,
 };
 
 use self::backend::{FuncBackendKind, FuncBackendResponseType};
@@ -25,6 +25,7 @@ pub mod description;
 pub mod execution;
 pub mod identity;
 pub mod intrinsics;
+pub mod library;
 
 pub fn is_intrinsic(name: &str) -> bool {
     intrinsics::IntrinsicFunc::iter().any(|intrinsic| intrinsic.name() == name)
@@ -98,6 +99,8 @@ pub struct Func {
     name: String,
     display_name: Option<String>,
     description: Option<String>,
+    /// Free-form labels used to find the func in the func library.
+    tags: Vec<String>,
     link: Option<String>,
     hidden: bool,
     builtin: bool,
@@ -210,6 +213,64 @@ impl Func {
         is_intrinsic(self.name())
     }
 
+    pub fn tags(&self) -> &[String] {
+        &self.tags
+    }
+
+    /// Replaces the tags on the func. Tags are trimmed, lowercased, sorted and de-duplicated, and
+    /// empty tags are dropped.
+    pub async fn set_tags(
+        &mut self,
+        ctx: &DalContext,
+        tags: impl IntoIterator<Item = impl AsRef<str>>,
+    ) -> FuncResult<()> {
+        let mut tags: Vec<String> = tags
+            .into_iter()
+            .map(|tag| tag.as_ref().trim().to_lowercase())
+            .filter(|tag| !tag.is_empty())
+            .collect();
+        tags.sort();
+        tags.dedup();
+
+        let value = serde_json::to_value(&tags)?;
+        let updated_at = standard_model::update(
+            ctx,
+            Self::table_name(),
+            "tags",
+            self.id(),
+            &value,
+            standard_model::TypeHint::JsonB,
+        )
+        .await?;
+        let _history_event = HistoryEvent::new(
+            ctx,
+            &Self::history_event_label(vec!["updated"]),
+            &Self::history_event_message("updated"),
+            &serde_json::json![{
+                "pk": self.pk,
+                "field": "tags",
+                "value": &value,
+            }],
+        )
+        .await?;
+        self.timestamp.updated_at = updated_at;
+        self.tags = tags;
+
+        Ok(())
+    }
+
+    pub async fn add_tag(&mut self, ctx: &DalContext, tag: impl AsRef<str>) -> FuncResult<()> {
+        let mut tags = self.tags.clone();
+        tags.push(tag.as_ref().to_owned());
+        self.set_tags(ctx, tags).await
+    }
+
+    pub async fn remove_tag(&mut self, ctx: &DalContext, tag: impl AsRef<str>) -> FuncResult<()> {
+        let tag = tag.as_ref().trim().to_lowercase();
+        let tags: Vec<String> = self.tags.iter().filter(|t| **t != tag).cloned().collect();
+        self.set_tags(ctx, tags).await
+    }
+
     standard_model_accessor!(name, String, FuncResult);
     standard_model_accessor!(display_name, Option<String>, FuncResult);
     standard_model_accessor!(description, Option<String>, FuncResult);
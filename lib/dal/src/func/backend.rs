@@ -5,8 +5,8 @@ use telemetry::prelude::*;
 use thiserror::Error;
 use tokio::sync::mpsc;
 use veritech_client::{
-    ActionRunResultSuccess, Client as VeritechClient, FunctionResult, OutputStream,
-    ResolverFunctionResponseType,
+    ActionRunResultSuccess, Client as VeritechClient, FunctionErrorKind, FunctionResult,
+    OutputStream, ResolverFunctionResponseType,
 };
 
 use crate::{label_list::ToLabelList, DalContext, Func, FuncId, PropKind, StandardModel};
@@ -25,6 +25,7 @@ pub mod map;
 pub mod object;
 pub mod string;
 pub mod validation;
+pub mod workspace_integration;
 
 #[remain::sorted]
 #[derive(Error, Debug)]
@@ -44,6 +45,7 @@ pub enum FuncBackendError {
         kind: String,
         message: String,
         backend: String,
+        category: FunctionErrorKind,
     },
     #[error("send error")]
     SendError,
@@ -53,6 +55,20 @@ pub enum FuncBackendError {
     Ulid(#[from] ulid::DecodeError),
     #[error("veritech client error: {0}")]
     VeritechClient(#[from] veritech_client::ClientError),
+    #[error("workspace integration error: {0}")]
+    WorkspaceIntegration(String),
+    #[error("workspace integration not found: {0}")]
+    WorkspaceIntegrationNotFound(String),
+}
+
+impl FuncBackendError {
+    /// Returns whether the func failed in a way that may not repeat if it is executed again.
+    pub fn is_retryable(&self) -> bool {
+        match self {
+            Self::ResultFailure { category, .. } => category.is_retryable(),
+            _ => false,
+        }
+    }
 }
 
 pub type FuncBackendResult<T> = Result<T, FuncBackendError>;
@@ -89,6 +105,8 @@ pub enum FuncBackendKind {
     String,
     Unset,
     Validation,
+    /// Looks up a [`WorkspaceIntegration`](crate::WorkspaceIntegration) by name.
+    WorkspaceIntegration,
 }
 
 #[remain::sorted]
@@ -186,9 +204,18 @@ pub struct FuncDispatchContext {
 impl FuncDispatchContext {
     pub fn new(ctx: &DalContext) -> (Self, mpsc::Receiver<OutputStream>) {
         let (output_tx, rx) = mpsc::channel(64);
+        // Tag executions with their workspace so veritech can keep one workspace from starving
+        // the others
+        let veritech = match ctx.tenancy().workspace_pk() {
+            Some(workspace_pk) => ctx
+                .veritech()
+                .clone()
+                .with_workspace_id(workspace_pk.to_string()),
+            None => ctx.veritech().clone(),
+        };
         (
             Self {
-                veritech: ctx.veritech().clone(),
+                veritech,
                 output_tx,
             },
             rx,
@@ -273,6 +300,7 @@ pub trait FuncDispatch: std::fmt::Debug {
                     kind: failure.error.kind,
                     backend,
                     message: failure.error.message,
+                    category: failure.error.category,
                 }));
             }
         };
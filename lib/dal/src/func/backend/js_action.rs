@@ -2,8 +2,10 @@ use async_trait::async_trait;
 use chrono::Utc;
 use serde::{Deserialize, Serialize};
 use telemetry::tracing::trace;
+use ulid::Ulid;
 use veritech_client::{
-    ActionRunRequest, ActionRunResultSuccess, FunctionResult, OutputStream, ResourceStatus,
+    ActionRunRequest, ActionRunResultSuccess, FunctionLanguage, FunctionResult, OutputStream,
+    ResourceStatus,
 };
 
 use crate::func::backend::{
@@ -31,12 +33,12 @@ impl FuncDispatch for FuncBackendJsAction {
         args: Self::Args,
     ) -> Box<Self> {
         let request = ActionRunRequest {
-            // Once we start tracking the state of these executions, then this id will be useful,
-            // but for now it's passed along and back, and is opaue
-            execution_id: "ayrtonsennajscommand".to_string(),
+            execution_id: Ulid::new().to_string(),
             handler: handler.into(),
             code_base64: code_base64.into(),
             args: serde_json::to_value(args).unwrap(),
+            lang: FunctionLanguage::JavaScript,
+            before: vec![],
         };
 
         Box::new(Self { context, request })
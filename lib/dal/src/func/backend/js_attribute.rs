@@ -1,7 +1,8 @@
 use async_trait::async_trait;
 use serde::{Deserialize, Serialize};
+use ulid::Ulid;
 use veritech_client::{
-    FunctionResult, ResolverFunctionComponent, ResolverFunctionRequest,
+    FunctionLanguage, FunctionResult, ResolverFunctionComponent, ResolverFunctionRequest,
     ResolverFunctionResponseType, ResolverFunctionResultSuccess,
 };
 
@@ -31,13 +32,13 @@ impl FuncDispatch for FuncBackendJsAttribute {
         args: Self::Args,
     ) -> Box<Self> {
         let request = ResolverFunctionRequest {
-            // Once we start tracking the state of these executions, then this id will be useful,
-            // but for now it's passed along and back, and is opaue
-            execution_id: "tomcruise".to_string(),
+            execution_id: Ulid::new().to_string(),
             handler: handler.into(),
             component: args.component,
             response_type: args.response_type,
             code_base64: code_base64.into(),
+            lang: FunctionLanguage::JavaScript,
+            before: vec![],
         };
 
         Box::new(Self { context, request })
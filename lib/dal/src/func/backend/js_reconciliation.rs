@@ -2,7 +2,10 @@ use async_trait::async_trait;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::str::FromStr;
-use veritech_client::{FunctionResult, ReconciliationRequest, ReconciliationResultSuccess};
+use ulid::Ulid;
+use veritech_client::{
+    FunctionLanguage, FunctionResult, ReconciliationRequest, ReconciliationResultSuccess,
+};
 
 use crate::func::backend::{ExtractPayload, FuncBackendResult, FuncDispatch, FuncDispatchContext};
 use crate::AttributeValueId;
@@ -43,12 +46,12 @@ impl FuncDispatch for FuncBackendJsReconciliation {
         args: Self::Args,
     ) -> Box<Self> {
         let request = ReconciliationRequest {
-            // Once we start tracking the state of these executions, then this id will be useful,
-            // but for now it's passed along and back, and is opaue
-            execution_id: "freeronaldinhogauchojsreconciliation".to_string(),
+            execution_id: Ulid::new().to_string(),
             handler: handler.into(),
             code_base64: code_base64.into(),
             args: serde_json::to_value(args).unwrap(),
+            lang: FunctionLanguage::JavaScript,
+            before: vec![],
         };
 
         Box::new(Self { context, request })
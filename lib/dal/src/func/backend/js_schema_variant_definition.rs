@@ -1,8 +1,10 @@
 use crate::func::backend::{ExtractPayload, FuncBackendResult, FuncDispatch, FuncDispatchContext};
 use async_trait::async_trait;
 use serde::{Deserialize, Serialize};
+use ulid::Ulid;
 use veritech_client::{
-    FunctionResult, SchemaVariantDefinitionRequest, SchemaVariantDefinitionResultSuccess,
+    FunctionLanguage, FunctionResult, SchemaVariantDefinitionRequest,
+    SchemaVariantDefinitionResultSuccess,
 };
 #[derive(Debug, Clone)]
 pub struct FuncBackendJsSchemaVariantDefinition {
@@ -22,9 +24,11 @@ impl FuncDispatch for FuncBackendJsSchemaVariantDefinition {
         _args: Self::Args,
     ) -> Box<Self> {
         let request = SchemaVariantDefinitionRequest {
-            execution_id: "villanelle".to_string(),
+            execution_id: Ulid::new().to_string(),
             handler: handler.into(),
             code_base64: code_base64.to_owned(),
+            lang: FunctionLanguage::JavaScript,
+            before: vec![],
         };
 
         Box::new(Self { context, request })
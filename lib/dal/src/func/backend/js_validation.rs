@@ -6,7 +6,10 @@ use async_trait::async_trait;
 use chrono::Utc;
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
-use veritech_client::{FunctionResult, OutputStream, ValidationRequest, ValidationResultSuccess};
+use ulid::Ulid;
+use veritech_client::{
+    FunctionLanguage, FunctionResult, OutputStream, ValidationRequest, ValidationResultSuccess,
+};
 
 #[derive(Debug, Clone)]
 pub struct FuncBackendJsValidation {
@@ -37,10 +40,12 @@ impl FuncDispatch for FuncBackendJsValidation {
         args: Self::Args,
     ) -> Box<Self> {
         let request = ValidationRequest {
-            execution_id: "johnwick".to_string(),
+            execution_id: Ulid::new().to_string(),
             handler: handler.into(),
             code_base64: code_base64.to_owned(),
             value: args.value,
+            lang: FunctionLanguage::JavaScript,
+            before: vec![],
         };
 
         Box::new(Self { context, request })
@@ -118,6 +118,41 @@ impl FuncBackend for FuncBackendValidation {
                 },
                 None => Some(value_must_be_present_error),
             },
+            Validation::StringLengthIsBetweenTwoIntegers { value, lower_bound, upper_bound } => match value {
+                Some(value) => {
+                    let length = i64::try_from(value.chars().count()).unwrap_or(i64::MAX);
+                    match length >= lower_bound && length <= upper_bound {
+                        true => None,
+                        false => Some(ValidationError {
+                            message: format!("value ({value}) has a length of {length}, which is not between lower ({lower_bound}) and upper ({upper_bound}) bounds"),
+                            kind: ValidationErrorKind::StringLengthNotInBetweenTwoIntegers,
+                            link: None,
+                            level: None,
+                        }),
+                    }
+                },
+                None => Some(value_must_be_present_error),
+            },
+            Validation::StringMatchesRegex { value, expected } => match value {
+                Some(value) => match Regex::new(&expected) {
+                    Ok(re) => match re.is_match(&value) {
+                        true => None,
+                        false => Some(ValidationError {
+                            message: format!("value ({value}) does not match regex ({expected})"),
+                            kind: ValidationErrorKind::StringDoesNotMatchRegex,
+                            link: None,
+                            level: None,
+                        }),
+                    },
+                    Err(e) => Some(ValidationError {
+                        message: format!("regex ({expected}) is invalid: {e}"),
+                        kind: ValidationErrorKind::InvalidRegex,
+                        link: None,
+                        level: None,
+                    }),
+                },
+                None => Some(value_must_be_present_error),
+            },
             Validation::StringInStringArray {
                 value,
                 expected,
@@ -0,0 +1,52 @@
+use serde::{Deserialize, Serialize};
+
+use crate::func::backend::{FuncBackendError, FuncBackendResult};
+use crate::{DalContext, WorkspaceIntegration};
+
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct FuncBackendWorkspaceIntegrationArgs {
+    pub name: Option<String>,
+}
+
+/// Sets an object to the [`view`](crate::WorkspaceIntegrationView) of the
+/// [`WorkspaceIntegration`] with the given name, so that
+/// [`SchemaVariants`](crate::SchemaVariant) can use the shared provider configuration.
+///
+/// Unlike the other intrinsic backends, this one reads from the database and so needs a
+/// [`DalContext`].
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct FuncBackendWorkspaceIntegration {
+    args: FuncBackendWorkspaceIntegrationArgs,
+}
+
+impl FuncBackendWorkspaceIntegration {
+    pub async fn create_and_execute(
+        ctx: &DalContext,
+        args: &serde_json::Value,
+    ) -> FuncBackendResult<(Option<serde_json::Value>, Option<serde_json::Value>)> {
+        let backend = Self {
+            args: FuncBackendWorkspaceIntegrationArgs::deserialize(args)?,
+        };
+        backend.execute(ctx).await
+    }
+
+    async fn execute(
+        self,
+        ctx: &DalContext,
+    ) -> FuncBackendResult<(Option<serde_json::Value>, Option<serde_json::Value>)> {
+        let name = match self.args.name {
+            Some(name) => name,
+            None => return Ok((None, None)),
+        };
+
+        let view = WorkspaceIntegration::view_by_name(ctx, &name)
+            .await
+            .map_err(|err| FuncBackendError::WorkspaceIntegration(err.to_string()))?
+            .ok_or(FuncBackendError::WorkspaceIntegrationNotFound(name))?;
+
+        Ok((
+            Some(serde_json::to_value(view)?),
+            Some(serde_json::json!({})),
+        ))
+    }
+}
@@ -5,7 +5,7 @@ use si_data_pg::PgError;
 use telemetry::prelude::*;
 use thiserror::Error;
 use tokio::sync::mpsc;
-use veritech_client::{OutputStream, ResolverFunctionComponent};
+use veritech_client::{FunctionErrorKind, OutputStream, ResolverFunctionComponent};
 
 use crate::func::execution::FuncExecutionPk;
 use crate::FuncError;
@@ -25,6 +25,7 @@ use crate::{
         object::FuncBackendObject,
         string::FuncBackendString,
         validation::FuncBackendValidation,
+        workspace_integration::FuncBackendWorkspaceIntegration,
         FuncBackend, FuncDispatch, FuncDispatchContext,
     },
     TransactionsError,
@@ -45,6 +46,8 @@ use super::{
 #[remain::sorted]
 #[derive(Error, Debug)]
 pub enum FuncBindingError {
+    #[error("func backend {0} needs a DalContext to execute")]
+    ContextRequired(FuncBackendKind),
     #[error("func error: {0}")]
     Func(#[from] FuncError),
     #[error("func backend error: {0}")]
@@ -56,6 +59,7 @@ pub enum FuncBindingError {
         kind: String,
         message: String,
         backend: String,
+        category: FunctionErrorKind,
     },
     #[error("func backend return value error: {0}")]
     FuncBindingReturnValue(#[from] FuncBindingReturnValueError),
@@ -81,6 +85,17 @@ pub enum FuncBindingError {
     Transactions(#[from] TransactionsError),
 }
 
+impl FuncBindingError {
+    /// Returns whether the func failed in a way that may not repeat if it is executed again.
+    pub fn is_retryable(&self) -> bool {
+        match self {
+            Self::FuncBackendResultFailure { category, .. } => category.is_retryable(),
+            Self::FuncBackend(err) => err.is_retryable(),
+            _ => false,
+        }
+    }
+}
+
 pub type FuncBindingResult<T> = Result<T, FuncBindingError>;
 
 pk!(FuncBindingPk);
@@ -207,7 +222,15 @@ impl FuncBinding {
     // For a given [`FuncBinding`](Self), execute using veritech.
     pub async fn execute(&self, ctx: &DalContext) -> FuncBindingResult<FuncBindingReturnValue> {
         let (func, execution, context, mut rx) = self.prepare_execution(ctx).await?;
-        let value = self.execute_critical_section(func.clone(), context).await?;
+        let value = match self.backend_kind() {
+            // Workspace integrations are read from the database, which the critical section
+            // has no access to.
+            FuncBackendKind::WorkspaceIntegration => {
+                drop(context);
+                FuncBackendWorkspaceIntegration::create_and_execute(ctx, &self.args).await?
+            }
+            _ => self.execute_critical_section(func.clone(), context).await?,
+        };
 
         let mut output = Vec::new();
         while let Some(output_stream) = rx.recv().await {
@@ -274,6 +297,9 @@ impl FuncBinding {
             FuncBackendKind::Validation => {
                 FuncBackendValidation::create_and_execute(&self.args).await
             }
+            FuncBackendKind::WorkspaceIntegration => {
+                return Err(FuncBindingError::ContextRequired(*self.backend_kind()));
+            }
         };
 
         match execution_result {
@@ -282,10 +308,12 @@ impl FuncBinding {
                 kind,
                 message,
                 backend,
+                category,
             }) => Err(FuncBindingError::FuncBackendResultFailure {
                 kind,
                 message,
                 backend,
+                category,
             }),
             Err(err) => Err(err)?,
         }
@@ -350,7 +378,8 @@ impl FuncBinding {
             | FuncBackendKind::Object
             | FuncBackendKind::String
             | FuncBackendKind::Unset
-            | FuncBackendKind::Validation => {}
+            | FuncBackendKind::Validation
+            | FuncBackendKind::WorkspaceIntegration => {}
 
             FuncBackendKind::JsAction
             | FuncBackendKind::JsAttribute
@@ -19,6 +19,7 @@ pub enum IntrinsicFunc {
     SetString,
     Unset,
     Validation,
+    WorkspaceIntegration,
 }
 
 impl IntrinsicFunc {
@@ -90,6 +91,17 @@ impl IntrinsicFunc {
                 builder.backend_kind(FuncSpecBackendKind::Validation);
                 builder.response_type(FuncSpecBackendResponseType::Validation);
             }
+            Self::WorkspaceIntegration => {
+                builder.backend_kind(FuncSpecBackendKind::WorkspaceIntegration);
+                builder.response_type(FuncSpecBackendResponseType::Object);
+                builder.argument(
+                    FuncArgumentSpec::builder()
+                        .name("name")
+                        .kind(FuncArgumentKind::String)
+                        .build()
+                        .map_err(|e| FuncError::IntrinsicSpecCreation(e.to_string()))?,
+                );
+            }
         };
 
         builder
@@ -108,6 +120,7 @@ impl IntrinsicFunc {
             Self::SetString => "si:setString",
             Self::Unset => "si:unset",
             Self::Validation => "si:validation",
+            Self::WorkspaceIntegration => "si:workspaceIntegration",
         }
     }
 }
@@ -0,0 +1,202 @@
+//! This module contains the func library: searching [`Funcs`](crate::Func) by text, tags and
+//! backend kind, and indexing where each func is bound so that users can find and reuse existing
+//! funcs instead of writing near-identical ones.
+
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+use strum::{AsRefStr, Display, EnumString};
+use telemetry::prelude::*;
+
+use crate::func::backend::FuncBackendKind;
+use crate::func::{FuncId, FuncResult};
+use crate::standard_model::objects_from_rows;
+use crate::{DalContext, Func, StandardModel};
+
+const LIST_USAGES: &str = include_str!("../queries/func/list_usages.sql");
+const SEARCH: &str = include_str!("../queries/func/search.sql");
+
+/// The kind of prototype a [`Func`](crate::Func) is bound through.
+#[remain::sorted]
+#[derive(
+    AsRefStr, Deserialize, Display, EnumString, Serialize, Debug, Clone, Copy, PartialEq, Eq, Hash,
+)]
+#[serde(rename_all = "camelCase")]
+#[strum(serialize_all = "camelCase")]
+pub enum FuncBindingKind {
+    Action,
+    Attribute,
+    CodeGeneration,
+    Qualification,
+    Reconciliation,
+    SchemaVariantDefinition,
+    Validation,
+}
+
+/// How many times a [`Func`](crate::Func) is bound through a given [`FuncBindingKind`].
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct FuncUsage {
+    pub kind: FuncBindingKind,
+    pub count: usize,
+}
+
+/// Narrows the results of [`Func::search()`](crate::Func::search).
+#[derive(Deserialize, Serialize, Debug, Clone, Default, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct FuncSearchFilters {
+    /// Only return funcs with one of these backend kinds. Empty means any kind.
+    #[serde(default)]
+    pub backend_kinds: Vec<FuncBackendKind>,
+    /// Only return funcs carrying every one of these tags.
+    #[serde(default)]
+    pub tags: Vec<String>,
+    #[serde(default)]
+    pub include_hidden: bool,
+    /// Only return funcs that are not bound anywhere.
+    #[serde(default)]
+    pub unused_only: bool,
+}
+
+/// A single result from the func library.
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct FuncLibraryEntry {
+    pub id: FuncId,
+    pub name: String,
+    pub display_name: Option<String>,
+    pub description: Option<String>,
+    pub tags: Vec<String>,
+    pub backend_kind: FuncBackendKind,
+    pub usage_count: usize,
+    pub usages: Vec<FuncUsage>,
+}
+
+impl Func {
+    /// Returns where every [`Func`](crate::Func) in the current visibility is bound, keyed by
+    /// func. Funcs that are not bound anywhere are absent from the index.
+    #[instrument(skip_all)]
+    pub async fn usage_index(ctx: &DalContext) -> FuncResult<HashMap<FuncId, Vec<FuncUsage>>> {
+        Self::list_usages(ctx, None).await
+    }
+
+    /// Returns the bindings for this func.
+    pub async fn usages(&self, ctx: &DalContext) -> FuncResult<Vec<FuncUsage>> {
+        Ok(Self::list_usages(ctx, Some(*self.id()))
+            .await?
+            .remove(self.id())
+            .unwrap_or_default())
+    }
+
+    /// Indexes the bindings of a single func, or of every func when no id is given.
+    async fn list_usages(
+        ctx: &DalContext,
+        func_id: Option<FuncId>,
+    ) -> FuncResult<HashMap<FuncId, Vec<FuncUsage>>> {
+        let rows = ctx
+            .txns()
+            .await?
+            .pg()
+            .query(LIST_USAGES, &[ctx.tenancy(), ctx.visibility(), &func_id])
+            .await?;
+
+        let mut index: HashMap<FuncId, Vec<FuncUsage>> = HashMap::new();
+        for row in rows {
+            let func_id: FuncId = row.try_get("func_id")?;
+            let kind: String = row.try_get("kind")?;
+            let count: i64 = row.try_get("count")?;
+            let kind = match kind.parse::<FuncBindingKind>() {
+                Ok(kind) => kind,
+                Err(_) => {
+                    warn!(%kind, %func_id, "skipping unknown func binding kind");
+                    continue;
+                }
+            };
+            index.entry(func_id).or_default().push(FuncUsage {
+                kind,
+                count: usize::try_from(count).unwrap_or_default(),
+            });
+        }
+
+        Ok(index)
+    }
+
+    /// Searches the func library. The query (if any) is matched case-insensitively against the
+    /// name, display name, description and tags of each func. `%` and `_` in the query match
+    /// literally.
+    #[instrument(skip(ctx))]
+    pub async fn search(
+        ctx: &DalContext,
+        query: Option<&str>,
+        filters: &FuncSearchFilters,
+    ) -> FuncResult<Vec<FuncLibraryEntry>> {
+        let query = query.map(str::trim).filter(|query| !query.is_empty());
+        let pattern = query.map(|query| format!("%{}%", escape_like(query)));
+        let tags = serde_json::to_value(
+            filters
+                .tags
+                .iter()
+                .map(|tag| tag.trim().to_lowercase())
+                .collect::<Vec<String>>(),
+        )?;
+
+        let rows = ctx
+            .txns()
+            .await?
+            .pg()
+            .query(
+                SEARCH,
+                &[
+                    ctx.tenancy(),
+                    ctx.visibility(),
+                    &query,
+                    &filters.include_hidden,
+                    &tags,
+                    &pattern,
+                ],
+            )
+            .await?;
+        let funcs: Vec<Func> = objects_from_rows(rows)?;
+        let mut usage_index = Self::usage_index(ctx).await?;
+
+        let mut entries = Vec::new();
+        for func in funcs {
+            if !filters.backend_kinds.is_empty()
+                && !filters.backend_kinds.contains(func.backend_kind())
+            {
+                continue;
+            }
+
+            let usages = usage_index.remove(func.id()).unwrap_or_default();
+            let usage_count = usages.iter().map(|usage| usage.count).sum();
+            if filters.unused_only && usage_count > 0 {
+                continue;
+            }
+
+            entries.push(FuncLibraryEntry {
+                id: *func.id(),
+                name: func.name().to_owned(),
+                display_name: func.display_name().map(ToOwned::to_owned),
+                description: func.description().map(ToOwned::to_owned),
+                tags: func.tags().to_vec(),
+                backend_kind: *func.backend_kind(),
+                usage_count,
+                usages,
+            });
+        }
+
+        Ok(entries)
+    }
+}
+
+/// Escapes the `LIKE` wildcards (and the `\` escape character itself) in user input.
+fn escape_like(input: &str) -> String {
+    let mut escaped = String::with_capacity(input.len());
+    for c in input.chars() {
+        if matches!(c, '\\' | '%' | '_') {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    escaped
+}
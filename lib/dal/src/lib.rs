@@ -22,6 +22,7 @@ pub mod actor_view;
 pub mod attribute;
 pub mod builtins;
 pub mod change_set;
+pub mod change_set_review;
 pub mod change_status;
 pub mod code_view;
 pub mod component;
@@ -41,6 +42,7 @@ pub mod key_pair;
 pub mod label_list;
 pub mod node;
 pub mod node_menu;
+pub mod onboarding;
 pub mod pkg;
 pub mod prop;
 pub mod prop_tree;
@@ -64,6 +66,7 @@ pub mod user;
 pub mod validation;
 pub mod visibility;
 pub mod workspace;
+pub mod workspace_integration;
 pub mod ws_event;
 
 pub use action_prototype::{
@@ -90,6 +93,10 @@ pub use attribute::{
 };
 pub use builtins::{BuiltinsError, BuiltinsResult};
 pub use change_set::{ChangeSet, ChangeSetError, ChangeSetPk, ChangeSetStatus};
+pub use change_set_review::{
+    ChangeSetReview, ChangeSetReviewError, ChangeSetReviewPolicy, ChangeSetReviewStatus,
+    ChangeSetReviewSummary,
+};
 pub use code_view::{CodeLanguage, CodeView};
 pub use component::{
     resource::ResourceView, status::ComponentStatus, status::HistoryActorTimestamp, Component,
@@ -114,6 +121,7 @@ pub use func::description::FuncDescriptionContents;
 pub use func::{
     backend::{FuncBackendError, FuncBackendKind, FuncBackendResponseType},
     binding::{FuncBinding, FuncBindingError, FuncBindingId},
+    library::{FuncBindingKind, FuncLibraryEntry, FuncSearchFilters, FuncUsage},
     Func, FuncError, FuncId, FuncResult,
 };
 pub use history_event::{HistoryActor, HistoryEvent, HistoryEventError};
@@ -127,6 +135,7 @@ pub use label_list::{LabelEntry, LabelList, LabelListError};
 pub use node::NodeId;
 pub use node::{Node, NodeError, NodeKind};
 pub use node_menu::NodeMenuError;
+pub use onboarding::{OnboardingError, OnboardingManifest, OnboardingResult, StarterTemplate};
 pub use prop::{Prop, PropError, PropId, PropKind, PropPk, PropResult};
 pub use prototype_context::HasPrototypeContext;
 pub use prototype_list_for_func::{
@@ -142,6 +151,10 @@ pub use reconciliation_prototype::{
 pub use schema::variant::leaves::LeafInput;
 pub use schema::variant::leaves::LeafInputLocation;
 pub use schema::variant::leaves::LeafKind;
+pub use schema::variant::migration::{
+    ComponentMigrationReport, ComponentMigrationStatus, SchemaVariantMigration,
+    SchemaVariantMigrationError, SchemaVariantMigrationId,
+};
 pub use schema::variant::root_prop::component_type::ComponentType;
 pub use schema::variant::root_prop::RootProp;
 pub use schema::variant::root_prop::RootPropChild;
@@ -158,7 +171,7 @@ pub use status::{
 };
 pub use tenancy::{Tenancy, TenancyError};
 pub use timestamp::{Timestamp, TimestampError};
-pub use user::{User, UserClaim, UserError, UserPk, UserResult};
+pub use user::{User, UserClaim, UserError, UserPk, UserResult, WorkspaceRole};
 pub use validation::prototype::{
     context::ValidationPrototypeContext, ValidationPrototype, ValidationPrototypeError,
     ValidationPrototypeId,
@@ -168,6 +181,10 @@ pub use validation::resolver::{
 };
 pub use visibility::{Visibility, VisibilityError};
 pub use workspace::{Workspace, WorkspaceError, WorkspacePk, WorkspaceResult, WorkspaceSignup};
+pub use workspace_integration::{
+    WorkspaceIntegration, WorkspaceIntegrationError, WorkspaceIntegrationId,
+    WorkspaceIntegrationResult, WorkspaceIntegrationView,
+};
 pub use ws_event::{WsEvent, WsEventError, WsEventResult, WsPayload};
 
 #[remain::sorted]
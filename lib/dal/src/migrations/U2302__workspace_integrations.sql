@@ -0,0 +1,57 @@
+CREATE TABLE workspace_integrations
+(
+    pk                          ident primary key default ident_create_v1(),
+    id                          ident not null default ident_create_v1(),
+    tenancy_workspace_pk        ident                    NOT NULL,
+    visibility_change_set_pk    ident                    NOT NULL DEFAULT ident_nil_v1(),
+    visibility_deleted_at       timestamp with time zone,
+    created_at                  timestamp with time zone NOT NULL DEFAULT CLOCK_TIMESTAMP(),
+    updated_at                  timestamp with time zone NOT NULL DEFAULT CLOCK_TIMESTAMP(),
+    name                        text                     NOT NULL,
+    provider                    text                     NOT NULL,
+    default_region              text,
+    account_aliases             jsonb                    NOT NULL DEFAULT '{}'::jsonb,
+    credential_secret_id        ident
+);
+CREATE UNIQUE INDEX unique_workspace_integration_names
+    ON workspace_integrations (name,
+                               tenancy_workspace_pk,
+                               visibility_change_set_pk);
+CREATE INDEX ON workspace_integrations (provider);
+SELECT standard_model_table_constraints_v1('workspace_integrations');
+SELECT many_to_many_table_create_v1('workspace_integration_many_to_many_schema_variants',
+                                    'workspace_integrations', 'schema_variants');
+
+INSERT INTO standard_models (table_name, table_type, history_event_label_base, history_event_message_name)
+VALUES ('workspace_integrations', 'model', 'workspace_integration', 'Workspace Integration'),
+       ('workspace_integration_many_to_many_schema_variants', 'many_to_many',
+        'workspace_integration.schema_variants', 'Workspace Integration <> Schema Variant');
+
+CREATE OR REPLACE FUNCTION workspace_integration_create_v1(
+    this_tenancy jsonb,
+    this_visibility jsonb,
+    this_name text,
+    this_provider text,
+    OUT object json) AS
+$$
+DECLARE
+    this_tenancy_record    tenancy_record_v1;
+    this_visibility_record visibility_record_v1;
+    this_new_row           workspace_integrations%ROWTYPE;
+BEGIN
+    this_tenancy_record := tenancy_json_to_columns_v1(this_tenancy);
+    this_visibility_record := visibility_json_to_columns_v1(this_visibility);
+
+    INSERT INTO workspace_integrations (tenancy_workspace_pk,
+                                        visibility_change_set_pk,
+                                        name,
+                                        provider)
+    VALUES (this_tenancy_record.tenancy_workspace_pk,
+            this_visibility_record.visibility_change_set_pk,
+            this_name,
+            this_provider)
+    RETURNING * INTO this_new_row;
+
+    object := row_to_json(this_new_row);
+END;
+$$ LANGUAGE PLPGSQL VOLATILE;
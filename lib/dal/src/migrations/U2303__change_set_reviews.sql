@@ -0,0 +1,95 @@
+CREATE TABLE change_set_reviews
+(
+    pk                          ident primary key default ident_create_v1(),
+    change_set_pk               ident                    NOT NULL,
+    reviewer_pk                 ident                    NOT NULL,
+    requested_by_pk             ident,
+    status                      text                     NOT NULL,
+    comment                     text,
+    tenancy_workspace_pk        ident                    NOT NULL,
+    created_at                  timestamp with time zone NOT NULL DEFAULT CLOCK_TIMESTAMP(),
+    updated_at                  timestamp with time zone NOT NULL DEFAULT CLOCK_TIMESTAMP()
+);
+CREATE UNIQUE INDEX unique_change_set_reviewers
+    ON change_set_reviews (change_set_pk, reviewer_pk);
+
+CREATE TABLE change_set_review_policies
+(
+    tenancy_workspace_pk        ident primary key,
+    required_approvals          integer                  NOT NULL DEFAULT 0,
+    created_at                  timestamp with time zone NOT NULL DEFAULT CLOCK_TIMESTAMP(),
+    updated_at                  timestamp with time zone NOT NULL DEFAULT CLOCK_TIMESTAMP()
+);
+
+CREATE OR REPLACE FUNCTION change_set_review_request_v1(this_change_set_pk ident,
+                                                        this_reviewer_pk ident,
+                                                        this_requested_by_pk ident,
+                                                        this_tenancy jsonb,
+                                                        OUT object json) AS
+$$
+DECLARE
+    this_tenancy_record tenancy_record_v1;
+    this_new_row        change_set_reviews%ROWTYPE;
+BEGIN
+    SELECT * FROM tenancy_json_to_columns_v1(this_tenancy) INTO this_tenancy_record;
+    INSERT INTO change_set_reviews (change_set_pk, reviewer_pk, requested_by_pk, status,
+                                    tenancy_workspace_pk)
+    VALUES (this_change_set_pk, this_reviewer_pk, this_requested_by_pk, 'Requested',
+            this_tenancy_record.tenancy_workspace_pk)
+    ON CONFLICT (change_set_pk, reviewer_pk)
+    DO UPDATE SET status          = 'Requested',
+                  comment         = NULL,
+                  requested_by_pk = this_requested_by_pk,
+                  updated_at      = clock_timestamp()
+    RETURNING * INTO this_new_row;
+    object := row_to_json(this_new_row);
+END;
+$$ LANGUAGE PLPGSQL VOLATILE;
+
+CREATE OR REPLACE FUNCTION change_set_review_decide_v1(this_change_set_pk ident,
+                                                       this_reviewer_pk ident,
+                                                       this_status text,
+                                                       this_comment text,
+                                                       this_tenancy jsonb,
+                                                       OUT object json) AS
+$$
+DECLARE
+    this_tenancy_record tenancy_record_v1;
+    this_new_row        change_set_reviews%ROWTYPE;
+BEGIN
+    SELECT * FROM tenancy_json_to_columns_v1(this_tenancy) INTO this_tenancy_record;
+    -- Only a pending review request may be decided; a reviewer who was never asked (or who has
+    -- already decided) gets no row back.
+    UPDATE change_set_reviews
+    SET status     = this_status,
+        comment    = this_comment,
+        updated_at = clock_timestamp()
+    WHERE change_set_pk = this_change_set_pk
+      AND reviewer_pk = this_reviewer_pk
+      AND status = 'Requested'
+      AND tenancy_workspace_pk = this_tenancy_record.tenancy_workspace_pk
+    RETURNING * INTO this_new_row;
+    IF FOUND THEN
+        object := row_to_json(this_new_row);
+    END IF;
+END;
+$$ LANGUAGE PLPGSQL VOLATILE;
+
+CREATE OR REPLACE FUNCTION change_set_review_policy_set_v1(this_tenancy jsonb,
+                                                           this_required_approvals integer,
+                                                           OUT object json) AS
+$$
+DECLARE
+    this_tenancy_record tenancy_record_v1;
+    this_new_row        change_set_review_policies%ROWTYPE;
+BEGIN
+    SELECT * FROM tenancy_json_to_columns_v1(this_tenancy) INTO this_tenancy_record;
+    INSERT INTO change_set_review_policies (tenancy_workspace_pk, required_approvals)
+    VALUES (this_tenancy_record.tenancy_workspace_pk, this_required_approvals)
+    ON CONFLICT (tenancy_workspace_pk)
+    DO UPDATE SET required_approvals = this_required_approvals,
+                  updated_at         = clock_timestamp()
+    RETURNING * INTO this_new_row;
+    object := row_to_json(this_new_row);
+END;
+$$ LANGUAGE PLPGSQL VOLATILE;
@@ -0,0 +1,50 @@
+CREATE TABLE schema_variant_migrations
+(
+    pk                          ident primary key default ident_create_v1(),
+    id                          ident not null default ident_create_v1(),
+    tenancy_workspace_pk        ident,
+    visibility_change_set_pk    ident                    NOT NULL DEFAULT ident_nil_v1(),
+    visibility_deleted_at       timestamp with time zone,
+    created_at                  timestamp with time zone NOT NULL DEFAULT CLOCK_TIMESTAMP(),
+    updated_at                  timestamp with time zone NOT NULL DEFAULT CLOCK_TIMESTAMP(),
+    from_schema_variant_id      ident                    NOT NULL,
+    to_schema_variant_id        ident                    NOT NULL,
+    func_id                     ident                    NOT NULL
+);
+CREATE INDEX ON schema_variant_migrations (from_schema_variant_id);
+SELECT standard_model_table_constraints_v1('schema_variant_migrations');
+
+INSERT INTO standard_models (table_name, table_type, history_event_label_base, history_event_message_name)
+VALUES ('schema_variant_migrations', 'model', 'schema_variant_migration', 'Schema Variant Migration');
+
+CREATE OR REPLACE FUNCTION schema_variant_migration_create_v1(
+    this_tenancy jsonb,
+    this_visibility jsonb,
+    this_from_schema_variant_id ident,
+    this_to_schema_variant_id ident,
+    this_func_id ident,
+    OUT object json) AS
+$$
+DECLARE
+    this_tenancy_record    tenancy_record_v1;
+    this_visibility_record visibility_record_v1;
+    this_new_row           schema_variant_migrations%ROWTYPE;
+BEGIN
+    this_tenancy_record := tenancy_json_to_columns_v1(this_tenancy);
+    this_visibility_record := visibility_json_to_columns_v1(this_visibility);
+
+    INSERT INTO schema_variant_migrations (tenancy_workspace_pk,
+                                           visibility_change_set_pk,
+                                           from_schema_variant_id,
+                                           to_schema_variant_id,
+                                           func_id)
+    VALUES (this_tenancy_record.tenancy_workspace_pk,
+            this_visibility_record.visibility_change_set_pk,
+            this_from_schema_variant_id,
+            this_to_schema_variant_id,
+            this_func_id)
+    RETURNING * INTO this_new_row;
+
+    object := row_to_json(this_new_row);
+END;
+$$ LANGUAGE PLPGSQL VOLATILE;
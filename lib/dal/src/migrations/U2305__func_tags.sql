@@ -0,0 +1,3 @@
+ALTER TABLE funcs
+    ADD COLUMN tags jsonb NOT NULL DEFAULT '[]'::jsonb;
+CREATE INDEX funcs_tags_idx ON funcs USING gin (tags);
@@ -0,0 +1,63 @@
+CREATE OR REPLACE FUNCTION list_models_paginated_v1(this_table_text text,
+                                                    this_tenancy jsonb,
+                                                    this_visibility jsonb,
+                                                    this_after_id text,
+                                                    this_page_size bigint)
+    RETURNS TABLE
+            (
+                id                       ident,
+                visibility_change_set_pk ident,
+                visibility_deleted_at    timestamp with time zone,
+                object                   json
+            )
+AS
+$$
+DECLARE
+    this_table regclass;
+BEGIN
+    this_table := this_table_text::regclass;
+    RETURN QUERY EXECUTE format('SELECT '
+                                '   table_alias.id, '
+                                '   table_alias.visibility_change_set_pk, '
+                                '   table_alias.visibility_deleted_at, '
+                                '   row_to_json(table_alias.*) AS object '
+                                ' FROM %1$I_v1(%2$L, %3$L) AS table_alias '
+                                ' WHERE (%4$L::ident IS NULL OR table_alias.id > %4$L::ident) '
+                                ' ORDER BY id '
+                                ' LIMIT %5$s '
+        , this_table, this_tenancy, this_visibility, this_after_id, this_page_size);
+END ;
+$$ LANGUAGE PLPGSQL STABLE;
+
+CREATE OR REPLACE FUNCTION find_by_attr_paginated_v1(this_table_text text,
+                                                     this_tenancy jsonb,
+                                                     this_visibility jsonb,
+                                                     this_attr_name text,
+                                                     this_value text,
+                                                     this_after_id text,
+                                                     this_page_size bigint)
+    RETURNS TABLE
+            (
+                id                       ident,
+                visibility_change_set_pk ident,
+                object                   json
+            )
+AS
+$$
+DECLARE
+    this_table regclass;
+BEGIN
+    this_table := this_table_text::regclass;
+    RETURN QUERY EXECUTE format('SELECT '
+                                '   table_alias.id, '
+                                '   table_alias.visibility_change_set_pk, '
+                                '   row_to_json(table_alias.*) AS object '
+                                ' FROM %1$I_v1(%2$L, %3$L) AS table_alias '
+                                ' WHERE table_alias.%4$I = %5$L '
+                                '   AND (%6$L::ident IS NULL OR table_alias.id > %6$L::ident) '
+                                ' ORDER BY id, visibility_change_set_pk DESC '
+                                ' LIMIT %7$s '
+        , this_table, this_tenancy, this_visibility, this_attr_name, this_value, this_after_id,
+                                this_page_size);
+END ;
+$$ LANGUAGE PLPGSQL STABLE;
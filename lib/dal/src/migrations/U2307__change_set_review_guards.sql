@@ -0,0 +1,60 @@
+ALTER TABLE user_belongs_to_workspaces
+    ADD COLUMN role text NOT NULL DEFAULT 'member';
+
+-- Existing workspaces were created by the first user to connect to them, who becomes their owner.
+UPDATE user_belongs_to_workspaces
+SET role = 'owner'
+WHERE pk IN (SELECT DISTINCT ON (workspace_pk) pk
+             FROM user_belongs_to_workspaces
+             ORDER BY workspace_pk, created_at);
+
+CREATE OR REPLACE FUNCTION user_associate_workspace_v2(
+    this_user_pk ident,
+    this_workspace_pk ident,
+    this_role text
+    ) RETURNS void AS
+$$
+BEGIN
+    INSERT INTO user_belongs_to_workspaces (user_pk, workspace_pk, role)
+        VALUES (this_user_pk, this_workspace_pk, this_role)
+        ON CONFLICT DO NOTHING;
+END;
+$$ LANGUAGE PLPGSQL VOLATILE;
+
+CREATE OR REPLACE FUNCTION change_set_review_invalidate_stale_v1(this_change_set_pk ident,
+                                                                 this_tenancy jsonb,
+                                                                 OUT invalidated integer) AS
+$$
+DECLARE
+    this_tenancy_record tenancy_record_v1;
+    standard_model      standard_models%ROWTYPE;
+    this_modified_at    timestamp with time zone;
+    last_modified_at    timestamp with time zone;
+BEGIN
+    SELECT * FROM tenancy_json_to_columns_v1(this_tenancy) INTO this_tenancy_record;
+
+    FOR standard_model IN SELECT * FROM standard_models
+        LOOP
+            EXECUTE format('SELECT max(updated_at) ' ||
+                           'FROM %1$I ' ||
+                           'WHERE visibility_change_set_pk = %2$L ' ||
+                           '  AND in_tenancy_v1(%3$L, tenancy_workspace_pk)',
+                           standard_model.table_name,
+                           this_change_set_pk,
+                           this_tenancy) INTO this_modified_at;
+            last_modified_at := GREATEST(last_modified_at, this_modified_at);
+        END LOOP;
+
+    -- An approval only covers the change set as it was when it was given, so approvals older
+    -- than the last change go back to being pending requests.
+    UPDATE change_set_reviews
+    SET status     = 'Requested',
+        comment    = NULL,
+        updated_at = clock_timestamp()
+    WHERE change_set_pk = this_change_set_pk
+      AND status = 'Approved'
+      AND tenancy_workspace_pk = this_tenancy_record.tenancy_workspace_pk
+      AND updated_at < last_modified_at;
+    GET DIAGNOSTICS invalidated = ROW_COUNT;
+END;
+$$ LANGUAGE PLPGSQL VOLATILE;
@@ -0,0 +1,387 @@
+//! This module contains starter templates for new workspaces. Provisioning a [`StarterTemplate`]
+//! installs the modules it needs, creates a [`ChangeSet`](crate::ChangeSet) with example
+//! [`Components`](crate::Component) and connections, and returns an [`OnboardingManifest`]
+//! describing a guided tour of what was created.
+
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+use telemetry::prelude::*;
+use thiserror::Error;
+
+use crate::builtins::schema::migrate_pkg;
+use crate::edge::{EdgeId, EdgeKind};
+use crate::socket::{SocketEdgeKind, SocketError};
+use crate::{
+    builtins, AttributeReadContext, AttributeValue, AttributeValueError, BuiltinsError, ChangeSet,
+    ChangeSetError, ChangeSetPk, Component, ComponentError, ComponentId, Connection, DalContext,
+    DependentValuesUpdate, DiagramError, ExternalProvider, ExternalProviderError, NodeError,
+    NodeId, Schema, SchemaError, Socket, StandardModel, TransactionsError, Visibility,
+};
+
+#[remain::sorted]
+#[derive(Error, Debug)]
+pub enum OnboardingError {
+    #[error("attribute value error: {0}")]
+    AttributeValue(#[from] AttributeValueError),
+    #[error("attribute value not found for context: {0:?}")]
+    AttributeValueNotFoundForContext(AttributeReadContext),
+    #[error("builtins error: {0}")]
+    Builtins(#[from] BuiltinsError),
+    #[error("change set error: {0}")]
+    ChangeSet(#[from] ChangeSetError),
+    #[error("component error: {0}")]
+    Component(#[from] ComponentError),
+    #[error("diagram error: {0}")]
+    Diagram(#[from] DiagramError),
+    #[error("external provider error: {0}")]
+    ExternalProvider(#[from] ExternalProviderError),
+    #[error("external provider not found for socket: {0}")]
+    ExternalProviderNotFoundForSocket(String),
+    #[error("node error: {0}")]
+    Node(#[from] NodeError),
+    #[error("schema error: {0}")]
+    Schema(#[from] SchemaError),
+    #[error("schema {0} has no default variant")]
+    SchemaVariantNotFound(String),
+    #[error("socket error: {0}")]
+    Socket(#[from] SocketError),
+    #[error("socket {1} not found on starter component {0}")]
+    SocketNotFound(String, String),
+    #[error("starter component not found in template: {0}")]
+    StarterComponentNotFound(String),
+    #[error("starter template not found: {0}")]
+    TemplateNotFound(String),
+    #[error("transactions error: {0}")]
+    Transactions(#[from] TransactionsError),
+}
+
+pub type OnboardingResult<T> = Result<T, OnboardingError>;
+
+/// A component created by a [`StarterTemplate`]. The key is used to refer to the component from
+/// connections and tour steps.
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct StarterComponent {
+    pub key: String,
+    pub schema_name: String,
+    pub name: String,
+    pub x: String,
+    pub y: String,
+}
+
+/// A connection between two components of a [`StarterTemplate`], by socket name.
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct StarterConnection {
+    pub from_component: String,
+    pub from_socket: String,
+    pub to_component: String,
+    pub to_socket: String,
+}
+
+/// A single step of the guided tour, optionally pointing at one of the starter components.
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct TourStep {
+    pub title: String,
+    pub body: String,
+    pub component: Option<String>,
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct StarterTemplate {
+    pub name: String,
+    pub display_name: String,
+    pub description: String,
+    /// The builtin module files (found in the packages path) the template needs.
+    pub modules: Vec<String>,
+    pub components: Vec<StarterComponent>,
+    pub connections: Vec<StarterConnection>,
+    pub tour: Vec<TourStep>,
+}
+
+/// A tour step with its component resolved to the ids created during provisioning.
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct ManifestTourStep {
+    pub title: String,
+    pub body: String,
+    pub component_id: Option<ComponentId>,
+    pub node_id: Option<NodeId>,
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct ManifestComponent {
+    pub key: String,
+    pub component_id: ComponentId,
+    pub node_id: NodeId,
+}
+
+/// What was created when provisioning a [`StarterTemplate`].
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct OnboardingManifest {
+    pub template: String,
+    pub change_set_pk: ChangeSetPk,
+    pub installed_modules: Vec<String>,
+    pub components: Vec<ManifestComponent>,
+    pub connections: Vec<EdgeId>,
+    pub tour: Vec<ManifestTourStep>,
+}
+
+fn component(key: &str, schema_name: &str, name: &str, x: i64, y: i64) -> StarterComponent {
+    StarterComponent {
+        key: key.to_owned(),
+        schema_name: schema_name.to_owned(),
+        name: name.to_owned(),
+        x: x.to_string(),
+        y: y.to_string(),
+    }
+}
+
+fn connection(from: &str, from_socket: &str, to: &str, to_socket: &str) -> StarterConnection {
+    StarterConnection {
+        from_component: from.to_owned(),
+        from_socket: from_socket.to_owned(),
+        to_component: to.to_owned(),
+        to_socket: to_socket.to_owned(),
+    }
+}
+
+fn step(title: &str, body: &str, component: Option<&str>) -> TourStep {
+    TourStep {
+        title: title.to_owned(),
+        body: body.to_owned(),
+        component: component.map(ToOwned::to_owned),
+    }
+}
+
+impl StarterTemplate {
+    /// The starter templates offered to new workspaces.
+    pub fn list() -> Vec<Self> {
+        vec![
+            Self {
+                name: "containerImage".to_owned(),
+                display_name: "Container image on CoreOS".to_owned(),
+                description: "A Docker image turned into a Butane config for Fedora CoreOS."
+                    .to_owned(),
+                modules: vec![
+                    builtins::SI_DOCKER_IMAGE_PKG.to_owned(),
+                    builtins::SI_COREOS_PKG.to_owned(),
+                ],
+                components: vec![
+                    component("image", "Docker Image", "nginx", 0, 0),
+                    component("butane", "Butane", "web server", 600, 0),
+                ],
+                connections: vec![connection(
+                    "image",
+                    "Container Image",
+                    "butane",
+                    "Container Image",
+                )],
+                tour: vec![
+                    step(
+                        "Components",
+                        "Each box on the diagram is a component. This one models a Docker image.",
+                        Some("image"),
+                    ),
+                    step(
+                        "Connections",
+                        "The image is connected to a Butane config, which turns it into a systemd \
+                         unit.",
+                        Some("butane"),
+                    ),
+                    step(
+                        "Change sets",
+                        "Everything here lives in a change set. Apply it when you are happy with \
+                         the model.",
+                        None,
+                    ),
+                ],
+            },
+            Self {
+                name: "awsContainerHost".to_owned(),
+                display_name: "Container host on AWS".to_owned(),
+                description: "A Docker image running on an EC2 instance booted with CoreOS."
+                    .to_owned(),
+                modules: vec![
+                    builtins::SI_AWS_PKG.to_owned(),
+                    builtins::SI_AWS_EC2_PKG.to_owned(),
+                    builtins::SI_DOCKER_IMAGE_PKG.to_owned(),
+                    builtins::SI_COREOS_PKG.to_owned(),
+                ],
+                components: vec![
+                    component("image", "Docker Image", "nginx", 0, 0),
+                    component("butane", "Butane", "web server", 600, 0),
+                    component("instance", "EC2 Instance", "web host", 1200, 0),
+                ],
+                connections: vec![
+                    connection("image", "Container Image", "butane", "Container Image"),
+                    connection("butane", "User Data", "instance", "User Data"),
+                ],
+                tour: vec![
+                    step(
+                        "Components",
+                        "Each box on the diagram is a component. This one models a Docker image.",
+                        Some("image"),
+                    ),
+                    step(
+                        "Connections",
+                        "Values flow along connections: the image becomes a Butane config, which \
+                         becomes the instance's user data.",
+                        Some("butane"),
+                    ),
+                    step(
+                        "Qualifications",
+                        "The instance needs a region and a key pair before it qualifies. Add them \
+                         from the asset palette.",
+                        Some("instance"),
+                    ),
+                    step(
+                        "Change sets",
+                        "Everything here lives in a change set. Apply it when you are happy with \
+                         the model.",
+                        None,
+                    ),
+                ],
+            },
+        ]
+    }
+
+    pub fn find(name: impl AsRef<str>) -> OnboardingResult<Self> {
+        let name = name.as_ref();
+        Self::list()
+            .into_iter()
+            .find(|template| template.name == name)
+            .ok_or_else(|| OnboardingError::TemplateNotFound(name.to_owned()))
+    }
+
+    /// Installs the template's modules on the current visibility, then switches the context to a
+    /// new [`ChangeSet`](crate::ChangeSet) and creates the template's components and connections
+    /// in it. The caller is responsible for committing.
+    #[instrument(skip(ctx))]
+    pub async fn provision(&self, ctx: &mut DalContext) -> OnboardingResult<OnboardingManifest> {
+        for module in &self.modules {
+            migrate_pkg(ctx, module, None).await?;
+        }
+
+        let change_set = ChangeSet::new(ctx, &self.display_name, None).await?;
+        ctx.update_visibility(Visibility::new(change_set.pk, None));
+
+        let mut created: HashMap<&str, ManifestComponent> = HashMap::new();
+        let mut components = Vec::with_capacity(self.components.len());
+        for starter in &self.components {
+            let schema = Schema::find_by_name(ctx, &starter.schema_name).await?;
+            let schema_variant_id = schema.default_schema_variant_id().ok_or_else(|| {
+                OnboardingError::SchemaVariantNotFound(starter.schema_name.clone())
+            })?;
+
+            let (component, mut node) =
+                Component::new(ctx, &starter.name, *schema_variant_id).await?;
+            node.set_geometry(ctx, &starter.x, &starter.y, Some("500"), Some("500"))
+                .await?;
+
+            let manifest_component = ManifestComponent {
+                key: starter.key.clone(),
+                component_id: *component.id(),
+                node_id: *node.id(),
+            };
+            created.insert(starter.key.as_str(), manifest_component.clone());
+            components.push(manifest_component);
+        }
+
+        let mut connections = Vec::with_capacity(self.connections.len());
+        for starter in &self.connections {
+            let from = created
+                .get(starter.from_component.as_str())
+                .ok_or_else(|| {
+                    OnboardingError::StarterComponentNotFound(starter.from_component.clone())
+                })?;
+            let to = created.get(starter.to_component.as_str()).ok_or_else(|| {
+                OnboardingError::StarterComponentNotFound(starter.to_component.clone())
+            })?;
+
+            let from_socket = Socket::find_by_name_for_edge_kind_and_node(
+                ctx,
+                &starter.from_socket,
+                SocketEdgeKind::ConfigurationOutput,
+                from.node_id,
+            )
+            .await?
+            .ok_or_else(|| {
+                OnboardingError::SocketNotFound(from.key.clone(), starter.from_socket.clone())
+            })?;
+            let to_socket = Socket::find_by_name_for_edge_kind_and_node(
+                ctx,
+                &starter.to_socket,
+                SocketEdgeKind::ConfigurationInput,
+                to.node_id,
+            )
+            .await?
+            .ok_or_else(|| {
+                OnboardingError::SocketNotFound(to.key.clone(), starter.to_socket.clone())
+            })?;
+
+            let connection = Connection::new(
+                ctx,
+                from.node_id,
+                *from_socket.id(),
+                to.node_id,
+                *to_socket.id(),
+                EdgeKind::Configuration,
+            )
+            .await?;
+
+            // Propagate the value of the output socket through the new connection.
+            let external_provider = ExternalProvider::find_for_socket(ctx, *from_socket.id())
+                .await?
+                .ok_or_else(|| {
+                    OnboardingError::ExternalProviderNotFoundForSocket(starter.from_socket.clone())
+                })?;
+            let attribute_read_context = AttributeReadContext {
+                external_provider_id: Some(*external_provider.id()),
+                component_id: Some(from.component_id),
+                ..Default::default()
+            };
+            let attribute_value = AttributeValue::find_for_context(ctx, attribute_read_context)
+                .await?
+                .ok_or(OnboardingError::AttributeValueNotFoundForContext(
+                    attribute_read_context,
+                ))?;
+            ctx.enqueue_job(DependentValuesUpdate::new(
+                ctx.access_builder(),
+                *ctx.visibility(),
+                vec![*attribute_value.id()],
+            ))
+            .await?;
+
+            connections.push(connection.id);
+        }
+
+        let tour = self
+            .tour
+            .iter()
+            .map(|step| {
+                let component = step.component.as_deref().and_then(|key| created.get(key));
+                ManifestTourStep {
+                    title: step.title.clone(),
+                    body: step.body.clone(),
+                    component_id: component.map(|c| c.component_id),
+                    node_id: component.map(|c| c.node_id),
+                }
+            })
+            .collect();
+
+        Ok(OnboardingManifest {
+            template: self.name.clone(),
+            change_set_pk: change_set.pk,
+            installed_modules: self.modules.clone(),
+            components,
+            connections,
+            tour,
+        })
+    }
+}
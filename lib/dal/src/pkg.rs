@@ -107,6 +107,8 @@ pub enum PkgError {
     MissingIntrinsicFunc(String),
     #[error("Intrinsic function (0) argument {1} not found")]
     MissingIntrinsicFuncArgument(String, String),
+    #[error("Cannot find item prop for installed array prop {0}")]
+    MissingItemPropForArrayProp(PropId),
     #[error("Cannot find item prop for installed map prop {0}")]
     MissingItemPropForMapProp(PropId),
     #[error("Cannot find installed prop {0}")]
@@ -181,6 +183,7 @@ impl From<FuncBackendKind> for FuncSpecBackendKind {
             FuncBackendKind::String => Self::String,
             FuncBackendKind::Unset => Self::Unset,
             FuncBackendKind::Validation => Self::Validation,
+            FuncBackendKind::WorkspaceIntegration => Self::WorkspaceIntegration,
         }
     }
 }
@@ -203,6 +206,7 @@ impl From<FuncSpecBackendKind> for FuncBackendKind {
             FuncSpecBackendKind::String => Self::String,
             FuncSpecBackendKind::Unset => Self::Unset,
             FuncSpecBackendKind::Validation => Self::Validation,
+            FuncSpecBackendKind::WorkspaceIntegration => Self::WorkspaceIntegration,
         }
     }
 }
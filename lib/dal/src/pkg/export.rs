@@ -891,6 +891,19 @@ async fn get_validations_for_prop(
                 Validation::StringIsHexColor { .. } => {
                     spec_builder.kind(ValidationSpecKind::StringIsHexColor);
                 }
+                Validation::StringLengthIsBetweenTwoIntegers {
+                    lower_bound,
+                    upper_bound,
+                    ..
+                } => {
+                    spec_builder.kind(ValidationSpecKind::StringLengthIsBetweenTwoIntegers);
+                    spec_builder.upper_bound(upper_bound);
+                    spec_builder.lower_bound(lower_bound);
+                }
+                Validation::StringMatchesRegex { expected, .. } => {
+                    spec_builder.kind(ValidationSpecKind::StringMatchesRegex);
+                    spec_builder.expected_string(expected);
+                }
             },
             None => {
                 let func_spec = func_specs
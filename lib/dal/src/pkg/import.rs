@@ -1,3 +1,4 @@
+use sodiumoxide::crypto::sign::PublicKey;
 use std::path::Path;
 use telemetry::prelude::*;
 use tokio::sync::Mutex;
@@ -41,6 +42,9 @@ pub struct ImportOptions {
     /// If set to `true`, the importer will install the assets from the module
     /// but will not make a record of the install as an "installed module".
     pub no_record: bool,
+    /// If set, the module must be signed by one of these keys and unchanged since, or the import
+    /// is refused before anything is installed.
+    pub trusted_keys: Option<Vec<PublicKey>>,
 }
 
 pub async fn import_pkg_from_pkg(
@@ -55,6 +59,11 @@ pub async fn import_pkg_from_pkg(
 
     let options = options.unwrap_or_default();
 
+    if let Some(trusted_keys) = &options.trusted_keys {
+        let signed_by = pkg.verify(trusted_keys)?;
+        debug!(%file_name, ?signed_by, "verified module signature");
+    }
+
     if InstalledPkg::find_by_hash(ctx, &root_hash).await?.is_some() {
         return Err(PkgError::PackageAlreadyInstalled(root_hash));
     }
@@ -108,7 +117,11 @@ pub async fn import_pkg_from_pkg(
 
     let mut installed_schema_variant_ids = vec![];
 
-    for schema_spec in pkg.schemas()? {
+    // Schemas are read one at a time, so a package opened with `SiPkg::open` never has more than
+    // one of them in memory
+    for schema_tree in pkg.schema_trees()? {
+        let schema_tree = schema_tree?;
+        let schema_spec = schema_tree.schema()?;
         match &options.schemas {
             None => {}
             Some(schemas) => {
@@ -136,7 +149,7 @@ pub async fn import_pkg_from_pkg(
 pub async fn import_pkg(ctx: &DalContext, pkg_file_path: impl AsRef<Path>) -> PkgResult<SiPkg> {
     let pkg_file_path_str = pkg_file_path.as_ref().to_string_lossy().to_string();
 
-    let pkg = SiPkg::load_from_file(&pkg_file_path).await?;
+    let pkg = SiPkg::open(&pkg_file_path)?;
 
     import_pkg_from_pkg(ctx, &pkg, &pkg_file_path_str, None).await?;
 
@@ -370,6 +383,10 @@ struct AttrFuncInfo {
 #[remain::sorted]
 #[derive(Clone, Debug)]
 enum DefaultValueInfo {
+    Array {
+        prop_id: PropId,
+        default_value: Vec<serde_json::Value>,
+    },
     Boolean {
         prop_id: PropId,
         default_value: bool,
@@ -378,6 +395,10 @@ enum DefaultValueInfo {
         prop_id: PropId,
         default_value: i64,
     },
+    Map {
+        prop_id: PropId,
+        default_value: Vec<(String, serde_json::Value)>,
+    },
     String {
         prop_id: PropId,
         default_value: String,
@@ -789,7 +810,9 @@ async fn set_default_value(
     default_value_info: DefaultValueInfo,
 ) -> PkgResult<()> {
     let prop = match &default_value_info {
-        DefaultValueInfo::Number { prop_id, .. }
+        DefaultValueInfo::Array { prop_id, .. }
+        | DefaultValueInfo::Number { prop_id, .. }
+        | DefaultValueInfo::Map { prop_id, .. }
         | DefaultValueInfo::String { prop_id, .. }
         | DefaultValueInfo::Boolean { prop_id, .. } => Prop::get_by_id(ctx, prop_id)
             .await?
@@ -797,9 +820,29 @@ async fn set_default_value(
     };
 
     match default_value_info {
+        DefaultValueInfo::Array { default_value, .. } => {
+            let item_prop = prop
+                .child_props(ctx)
+                .await?
+                .pop()
+                .ok_or(PkgError::MissingItemPropForArrayProp(*prop.id()))?;
+            for item in default_value {
+                insert_default_item(ctx, &prop, &item_prop, item, None).await?;
+            }
+        }
         DefaultValueInfo::Boolean { default_value, .. } => {
             prop.set_default_value(ctx, default_value).await?
         }
+        DefaultValueInfo::Map { default_value, .. } => {
+            let item_prop = prop
+                .child_props(ctx)
+                .await?
+                .pop()
+                .ok_or(PkgError::MissingItemPropForMapProp(*prop.id()))?;
+            for (key, item) in default_value {
+                insert_default_item(ctx, &prop, &item_prop, item, Some(key)).await?;
+            }
+        }
         DefaultValueInfo::Number { default_value, .. } => {
             prop.set_default_value(ctx, default_value).await?
         }
@@ -811,6 +854,35 @@ async fn set_default_value(
     Ok(())
 }
 
+/// Inserts an item into the default value of an array or map prop, in the order the package lists
+/// them.
+async fn insert_default_item(
+    ctx: &DalContext,
+    prop: &Prop,
+    item_prop: &Prop,
+    item: serde_json::Value,
+    key: Option<String>,
+) -> PkgResult<()> {
+    let parent_value =
+        AttributeValue::find_for_context(ctx, AttributeReadContext::default_with_prop(*prop.id()))
+            .await?
+            .ok_or(AttributeValueError::Missing)?;
+    let item_write_context = AttributeContextBuilder::new()
+        .set_prop_id(*item_prop.id())
+        .to_context()?;
+
+    AttributeValue::insert_for_context(
+        ctx,
+        item_write_context,
+        *parent_value.id(),
+        Some(item),
+        key,
+    )
+    .await?;
+
+    Ok(())
+}
+
 async fn create_attribute_function_for_prop(
     ctx: &DalContext,
     schema_variant_id: SchemaVariantId,
@@ -1054,6 +1126,21 @@ async fn create_prop_validation(
         SiPkgValidation::StringIsValidIpAddr { .. } => {
             ValidationKind::Builtin(Validation::StringIsValidIpAddr { value: None })
         }
+        SiPkgValidation::StringLengthIsBetweenTwoIntegers {
+            lower_bound,
+            upper_bound,
+            ..
+        } => ValidationKind::Builtin(Validation::StringLengthIsBetweenTwoIntegers {
+            value: None,
+            lower_bound,
+            upper_bound,
+        }),
+        SiPkgValidation::StringMatchesRegex { expected, .. } => {
+            ValidationKind::Builtin(Validation::StringMatchesRegex {
+                value: None,
+                expected,
+            })
+        }
         SiPkgValidation::CustomValidation { func_unique_id, .. } => ValidationKind::Custom(
             *ctx.func_map
                 .get(&func_unique_id)
@@ -1181,8 +1268,41 @@ async fn create_prop(
                 default_value,
             })
         }
-        // Default values for complex types are not yet supported in packages
-        _ => None,
+        SiPkgProp::Array { default_value, .. } => match default_value {
+            Some(serde_json::Value::Array(items)) => Some(DefaultValueInfo::Array {
+                prop_id,
+                default_value: items.to_owned(),
+            }),
+            Some(other) => {
+                warn!(
+                    "skipping default value {} for array prop {}, since it is not an array",
+                    other,
+                    spec.name()
+                );
+                None
+            }
+            None => None,
+        },
+        SiPkgProp::Map { default_value, .. } => match default_value {
+            Some(serde_json::Value::Object(entries)) => Some(DefaultValueInfo::Map {
+                prop_id,
+                default_value: entries
+                    .iter()
+                    .map(|(key, value)| (key.to_owned(), value.to_owned()))
+                    .collect(),
+            }),
+            Some(other) => {
+                warn!(
+                    "skipping default value {} for map prop {}, since it is not an object",
+                    other,
+                    spec.name()
+                );
+                None
+            }
+            None => None,
+        },
+        // Default values for objects are set through the default values of their children
+        SiPkgProp::Object { .. } => None,
     } {
         ctx.default_values.lock().await.push(default_value_info);
     }
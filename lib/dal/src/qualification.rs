@@ -6,6 +6,7 @@ use thiserror::Error;
 
 use crate::component::qualification::QualificationEntry;
 use crate::func::binding_return_value::FuncBindingReturnValueId;
+use crate::standard_model::DEFAULT_PAGE_SIZE;
 use crate::{
     func::binding_return_value::{FuncBindingReturnValue, FuncBindingReturnValueError},
     ws_event::{WsEvent, WsPayload},
@@ -61,45 +62,48 @@ impl QualificationSummary {
         let mut components_failed = 0;
         let mut total = 0;
 
-        for component in Component::list(ctx).await? {
-            let component_id = *component.id();
-            let qualifications = Component::list_qualifications(ctx, component_id).await?;
+        let mut pages = Component::paginate(DEFAULT_PAGE_SIZE);
+        while let Some(components) = pages.next_page(ctx).await? {
+            for component in components {
+                let component_id = *component.id();
+                let qualifications = Component::list_qualifications(ctx, component_id).await?;
 
-            let individual_total = qualifications.len() as i64;
-            let mut succeeded = 0;
-            let mut warned = 0;
-            let mut failed = 0;
-            for qualification in qualifications {
-                if let Some(result) = qualification.result {
-                    match result.status {
-                        QualificationSubCheckStatus::Success => succeeded += 1,
-                        QualificationSubCheckStatus::Warning => warned += 1,
-                        QualificationSubCheckStatus::Failure => failed += 1,
-                        QualificationSubCheckStatus::Unknown => {}
+                let individual_total = qualifications.len() as i64;
+                let mut succeeded = 0;
+                let mut warned = 0;
+                let mut failed = 0;
+                for qualification in qualifications {
+                    if let Some(result) = qualification.result {
+                        match result.status {
+                            QualificationSubCheckStatus::Success => succeeded += 1,
+                            QualificationSubCheckStatus::Warning => warned += 1,
+                            QualificationSubCheckStatus::Failure => failed += 1,
+                            QualificationSubCheckStatus::Unknown => {}
+                        }
                     }
                 }
-            }
 
-            let individual_summary = QualificationSummaryForComponent {
-                component_id,
-                component_name: component.name(ctx).await?,
-                total: individual_total,
-                succeeded,
-                warned,
-                failed,
-            };
+                let individual_summary = QualificationSummaryForComponent {
+                    component_id,
+                    component_name: component.name(ctx).await?,
+                    total: individual_total,
+                    succeeded,
+                    warned,
+                    failed,
+                };
 
-            // Update counters for all components.
-            if failed > 0 {
-                components_failed += 1;
-            } else if warned > 0 {
-                components_warned += 1;
-            } else {
-                components_succeeded += 1;
-            }
-            total += individual_total;
+                // Update counters for all components.
+                if failed > 0 {
+                    components_failed += 1;
+                } else if warned > 0 {
+                    components_warned += 1;
+                } else {
+                    components_succeeded += 1;
+                }
+                total += individual_total;
 
-            component_summaries.push(individual_summary);
+                component_summaries.push(individual_summary);
+            }
         }
 
         Ok(QualificationSummary {
@@ -0,0 +1,9 @@
+SELECT (history_events.actor ->> 'User')::ident AS author_pk
+FROM history_events
+WHERE
+    history_events.label = 'change_set.create'
+    AND history_events.actor ? 'User'
+    AND (history_events.data ->> 'pk')::ident = $2
+    AND in_tenancy_v1($1, history_events.tenancy_workspace_pk)
+ORDER BY history_events.created_at
+LIMIT 1
@@ -0,0 +1,6 @@
+SELECT row_to_json(change_set_reviews) AS object
+FROM change_set_reviews
+WHERE
+    change_set_reviews.change_set_pk = $2
+    AND in_tenancy_v1($1, change_set_reviews.tenancy_workspace_pk)
+ORDER BY change_set_reviews.created_at
@@ -0,0 +1,3 @@
+SELECT row_to_json(change_set_review_policies) AS object
+FROM change_set_review_policies
+WHERE in_tenancy_v1($1, change_set_review_policies.tenancy_workspace_pk)
@@ -0,0 +1,36 @@
+SELECT bindings.func_id AS func_id,
+       bindings.kind    AS kind,
+       count(*)         AS count
+FROM ((SELECT ap.func_id AS func_id,
+              CASE funcs.backend_response_type
+                  WHEN 'Qualification' THEN 'qualification'
+                  WHEN 'CodeGeneration' THEN 'codeGeneration'
+                  ELSE 'attribute'
+                  END    AS kind
+       FROM attribute_prototypes_v1($1, $2) AS ap
+                JOIN funcs_v1($1, $2) AS funcs
+                     ON funcs.id = ap.func_id
+       -- component specific prototypes are not library usages
+       WHERE ap.attribute_context_component_id = ident_nil_v1())
+
+      UNION ALL
+
+      (SELECT action_prototypes.func_id AS func_id, 'action' AS kind
+       FROM action_prototypes_v1($1, $2) AS action_prototypes)
+
+      UNION ALL
+
+      (SELECT validation_prototypes.func_id AS func_id, 'validation' AS kind
+       FROM validation_prototypes_v1($1, $2) AS validation_prototypes)
+
+      UNION ALL
+
+      (SELECT reconciliation_prototypes.func_id AS func_id, 'reconciliation' AS kind
+       FROM reconciliation_prototypes_v1($1, $2) AS reconciliation_prototypes)
+
+      UNION ALL
+
+      (SELECT svd.func_id AS func_id, 'schemaVariantDefinition' AS kind
+       FROM schema_variant_definitions_v1($1, $2) AS svd)) AS bindings
+WHERE $3::ident IS NULL OR bindings.func_id = $3
+GROUP BY bindings.func_id, bindings.kind
@@ -0,0 +1,10 @@
+SELECT row_to_json(funcs.*) AS object
+FROM funcs_v1($1, $2) AS funcs
+WHERE ($3::text IS NULL
+    OR funcs.name ILIKE $6 ESCAPE '\'
+    OR funcs.display_name ILIKE $6 ESCAPE '\'
+    OR funcs.description ILIKE $6 ESCAPE '\'
+    OR funcs.tags ? lower($3))
+  AND ($4::bool OR NOT funcs.hidden)
+  AND funcs.tags @> $5::jsonb
+ORDER BY funcs.name
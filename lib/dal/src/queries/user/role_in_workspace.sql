@@ -0,0 +1,5 @@
+SELECT to_json(user_belongs_to_workspaces.role) AS role
+FROM user_belongs_to_workspaces
+WHERE user_belongs_to_workspaces.user_pk = $1
+  AND user_belongs_to_workspaces.workspace_pk = $2
+  AND user_belongs_to_workspaces.visibility_deleted_at IS NULL
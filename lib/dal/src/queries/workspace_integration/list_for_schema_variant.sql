@@ -0,0 +1,6 @@
+SELECT row_to_json(workspace_integrations.*) AS object
+FROM workspace_integrations_v1($1, $2) AS workspace_integrations
+         JOIN workspace_integration_many_to_many_schema_variants_v1($1, $2) AS integration_to_schema_variant
+              ON workspace_integrations.id = integration_to_schema_variant.left_object_id
+                  AND integration_to_schema_variant.right_object_id = $3
+ORDER BY workspace_integrations.name
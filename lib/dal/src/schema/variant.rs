@@ -41,6 +41,7 @@ use self::leaves::{LeafInput, LeafInputLocation, LeafKind};
 
 pub mod definition;
 pub mod leaves;
+pub mod migration;
 pub mod root_prop;
 
 const ALL_FUNCS: &str = include_str!("../queries/schema_variant/all_related_funcs.sql");
@@ -0,0 +1,634 @@
+//! This module contains [`SchemaVariantMigration`], which pairs an older
+//! [`SchemaVariant`](crate::SchemaVariant) with a newer one and a [`Func`](crate::Func) that
+//! transforms the "domain" values of a [`Component`](crate::Component) from the old prop tree
+//! into the new prop tree. Migrations are run when upgrading a component to a newer variant so
+//! that breaking prop renames do not silently drop user data.
+
+use std::collections::{HashMap, VecDeque};
+
+use serde::{Deserialize, Serialize};
+use si_data_nats::NatsError;
+use si_data_pg::PgError;
+use telemetry::prelude::*;
+use thiserror::Error;
+
+use crate::attribute::context::AttributeContextBuilder;
+use crate::component::view::{ComponentView, ComponentViewError};
+use crate::edge::EdgeObjectId;
+use crate::property_editor::schema::WidgetKind;
+use crate::schema::variant::root_prop::SiPropChild;
+use crate::socket::SocketError;
+use crate::{
+    impl_standard_model, pk, standard_model, standard_model_accessor, AttributeContextBuilderError,
+    AttributeValue, AttributeValueError, Component, ComponentError, ComponentId, DalContext, Edge,
+    EdgeError, Func, FuncBackendKind, FuncBinding, FuncBindingError, FuncError, FuncId,
+    HistoryEventError, NodeError, Prop, PropError, RootPropChild, SchemaVariant,
+    SchemaVariantError, SchemaVariantId, Socket, SocketId, StandardModel, StandardModelError,
+    Tenancy, Timestamp, TransactionsError, Visibility,
+};
+
+#[remain::sorted]
+#[derive(Error, Debug)]
+pub enum SchemaVariantMigrationError {
+    #[error("attribute context builder error: {0}")]
+    AttributeContextBuilder(#[from] AttributeContextBuilderError),
+    #[error("attribute value error: {0}")]
+    AttributeValue(#[from] AttributeValueError),
+    #[error("component error: {0}")]
+    Component(#[from] ComponentError),
+    #[error("component not found: {0}")]
+    ComponentNotFound(ComponentId),
+    #[error("component view error: {0}")]
+    ComponentView(#[from] ComponentViewError),
+    #[error("edge error: {0}")]
+    Edge(#[from] EdgeError),
+    #[error("func error: {0}")]
+    Func(#[from] FuncError),
+    #[error("func binding error: {0}")]
+    FuncBinding(#[from] FuncBindingError),
+    #[error("migration func ({0}) must be JsAttribute")]
+    FuncMustBeJsAttribute(FuncId),
+    #[error("func not found: {0}")]
+    FuncNotFound(FuncId),
+    #[error("history event error: {0}")]
+    HistoryEvent(#[from] HistoryEventError),
+    #[error("nats txn error: {0}")]
+    Nats(#[from] NatsError),
+    #[error("no migration path from schema variant {0} to schema variant {1}")]
+    NoMigrationPath(SchemaVariantId, SchemaVariantId),
+    #[error("node error: {0}")]
+    Node(#[from] NodeError),
+    #[error("parent attribute value not found for root prop child of component: {0}")]
+    ParentNotFound(ComponentId),
+    #[error("pg error: {0}")]
+    Pg(#[from] PgError),
+    #[error("prop error: {0}")]
+    Prop(#[from] PropError),
+    #[error("schema variant error: {0}")]
+    SchemaVariant(#[from] SchemaVariantError),
+    #[error("schema variant not found: {0}")]
+    SchemaVariantNotFound(SchemaVariantId),
+    #[error("error serializing/deserializing json: {0}")]
+    SerdeJson(#[from] serde_json::Error),
+    #[error("socket error: {0}")]
+    Socket(#[from] SocketError),
+    #[error("connected socket \"{0}\" does not exist on schema variant {1}")]
+    SocketMissingOnTarget(String, SchemaVariantId),
+    #[error("standard model error: {0}")]
+    StandardModelError(#[from] StandardModelError),
+    #[error("transactions error: {0}")]
+    Transactions(#[from] TransactionsError),
+}
+
+pub type SchemaVariantMigrationResult<T> = Result<T, SchemaVariantMigrationError>;
+
+pk!(SchemaVariantMigrationPk);
+pk!(SchemaVariantMigrationId);
+
+/// Declares how to migrate the "domain" values of a [`Component`](crate::Component) from one
+/// [`SchemaVariant`](crate::SchemaVariant) to another. The [`Func`](crate::Func) receives
+/// `{ "domain": <old domain> }` and must return the new domain object.
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq, Eq)]
+pub struct SchemaVariantMigration {
+    pk: SchemaVariantMigrationPk,
+    id: SchemaVariantMigrationId,
+    from_schema_variant_id: SchemaVariantId,
+    to_schema_variant_id: SchemaVariantId,
+    func_id: FuncId,
+    #[serde(flatten)]
+    tenancy: Tenancy,
+    #[serde(flatten)]
+    timestamp: Timestamp,
+    #[serde(flatten)]
+    visibility: Visibility,
+}
+
+impl_standard_model! {
+    model: SchemaVariantMigration,
+    pk: SchemaVariantMigrationPk,
+    id: SchemaVariantMigrationId,
+    table_name: "schema_variant_migrations",
+    history_event_label_base: "schema_variant_migration",
+    history_event_message_name: "Schema Variant Migration"
+}
+
+#[remain::sorted]
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum ComponentMigrationStatus {
+    /// The component could not be upgraded and was left untouched.
+    Failed,
+    /// The component was upgraded.
+    Succeeded,
+}
+
+/// The outcome of upgrading a single [`Component`](crate::Component).
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct ComponentMigrationReport {
+    pub component_id: ComponentId,
+    /// The component that replaced this one on the target variant, if the upgrade succeeded.
+    pub upgraded_component_id: Option<ComponentId>,
+    pub from_schema_variant_id: SchemaVariantId,
+    pub to_schema_variant_id: SchemaVariantId,
+    /// The migration funcs that were run, in order.
+    pub applied_migrations: Vec<FuncId>,
+    /// Domain keys that were present before the upgrade and absent afterwards, as `/` separated
+    /// paths from the domain root (e.g. `tags/owner`).
+    pub dropped_keys: Vec<String>,
+    pub status: ComponentMigrationStatus,
+    pub error: Option<String>,
+}
+
+impl SchemaVariantMigration {
+    #[instrument(skip_all)]
+    pub async fn new(
+        ctx: &DalContext,
+        from_schema_variant_id: SchemaVariantId,
+        to_schema_variant_id: SchemaVariantId,
+        func_id: FuncId,
+    ) -> SchemaVariantMigrationResult<Self> {
+        let func = Func::get_by_id(ctx, &func_id)
+            .await?
+            .ok_or(SchemaVariantMigrationError::FuncNotFound(func_id))?;
+        if *func.backend_kind() != FuncBackendKind::JsAttribute {
+            return Err(SchemaVariantMigrationError::FuncMustBeJsAttribute(func_id));
+        }
+
+        let row = ctx
+            .txns()
+            .await?
+            .pg()
+            .query_one(
+                "SELECT object FROM schema_variant_migration_create_v1($1, $2, $3, $4, $5)",
+                &[
+                    ctx.tenancy(),
+                    ctx.visibility(),
+                    &from_schema_variant_id,
+                    &to_schema_variant_id,
+                    &func_id,
+                ],
+            )
+            .await?;
+        let object = standard_model::finish_create_from_row(ctx, row).await?;
+        Ok(object)
+    }
+
+    standard_model_accessor!(
+        from_schema_variant_id,
+        Pk(SchemaVariantId),
+        SchemaVariantMigrationResult
+    );
+    standard_model_accessor!(
+        to_schema_variant_id,
+        Pk(SchemaVariantId),
+        SchemaVariantMigrationResult
+    );
+    standard_model_accessor!(func_id, Pk(FuncId), SchemaVariantMigrationResult);
+
+    pub async fn list_from_schema_variant(
+        ctx: &DalContext,
+        from_schema_variant_id: SchemaVariantId,
+    ) -> SchemaVariantMigrationResult<Vec<Self>> {
+        Ok(Self::find_by_attr(ctx, "from_schema_variant_id", &from_schema_variant_id).await?)
+    }
+
+    /// Finds the shortest chain of migrations that leads from one variant to another. An empty
+    /// chain is returned when both variants are the same.
+    pub async fn path(
+        ctx: &DalContext,
+        from_schema_variant_id: SchemaVariantId,
+        to_schema_variant_id: SchemaVariantId,
+    ) -> SchemaVariantMigrationResult<Vec<Self>> {
+        // Breadth first search, remembering the migration used to first reach each variant so
+        // that every variant is visited once and cycles cannot loop forever.
+        let mut reached_by: HashMap<SchemaVariantId, Self> = HashMap::new();
+        let mut queue = VecDeque::from([from_schema_variant_id]);
+        while let Some(current) = queue.pop_front() {
+            if current == to_schema_variant_id {
+                let mut path = Vec::new();
+                let mut step = current;
+                while let Some(migration) = reached_by.remove(&step) {
+                    step = migration.from_schema_variant_id;
+                    path.push(migration);
+                }
+                path.reverse();
+                return Ok(path);
+            }
+            for migration in Self::list_from_schema_variant(ctx, current).await? {
+                let next = migration.to_schema_variant_id;
+                if next != from_schema_variant_id && !reached_by.contains_key(&next) {
+                    reached_by.insert(next, migration);
+                    queue.push_back(next);
+                }
+            }
+        }
+        Err(SchemaVariantMigrationError::NoMigrationPath(
+            from_schema_variant_id,
+            to_schema_variant_id,
+        ))
+    }
+
+    /// Runs the migration func against a domain object through veritech.
+    pub async fn migrate_domain(
+        &self,
+        ctx: &DalContext,
+        domain: serde_json::Value,
+    ) -> SchemaVariantMigrationResult<serde_json::Value> {
+        let (_, return_value) = FuncBinding::create_and_execute(
+            ctx,
+            serde_json::json!({ "domain": domain }),
+            self.func_id,
+        )
+        .await?;
+        Ok(return_value
+            .value()
+            .cloned()
+            .unwrap_or_else(|| serde_json::json!({})))
+    }
+
+    /// Upgrades a [`Component`](crate::Component) to a newer
+    /// [`SchemaVariant`](crate::SchemaVariant), running every migration func on the path between
+    /// the two variants.
+    ///
+    /// The component is replaced: a component is created on the target variant with the migrated
+    /// domain and it takes over the resource, the secrets, the "si" values and the diagram position
+    /// of the old one. Every connection is moved to the socket of the same name on the new
+    /// component and the old component is deleted without scheduling the destruction of its
+    /// resource, which now belongs to the new component.
+    ///
+    /// The migration funcs and the replacement run as one unit. If any step fails, for instance
+    /// because a func errors, the component is protected or the target variant lacks a socket that
+    /// the component is connected through, everything is rolled back so that the original
+    /// component is left untouched and the failure is recorded in the returned report.
+    #[instrument(skip(ctx))]
+    pub async fn upgrade_component(
+        ctx: &DalContext,
+        component_id: ComponentId,
+        to_schema_variant_id: SchemaVariantId,
+    ) -> SchemaVariantMigrationResult<ComponentMigrationReport> {
+        let mut component = Component::get_by_id(ctx, &component_id)
+            .await?
+            .ok_or(SchemaVariantMigrationError::ComponentNotFound(component_id))?;
+        let from_schema_variant_id = Component::schema_variant_id(ctx, component_id).await?;
+        let path = Self::path(ctx, from_schema_variant_id, to_schema_variant_id).await?;
+
+        let mut report = ComponentMigrationReport {
+            component_id,
+            upgraded_component_id: None,
+            from_schema_variant_id,
+            to_schema_variant_id,
+            applied_migrations: Vec::new(),
+            dropped_keys: Vec::new(),
+            status: ComponentMigrationStatus::Failed,
+            error: None,
+        };
+
+        ctx.txns()
+            .await?
+            .pg()
+            .batch_execute("SAVEPOINT upgrade_component")
+            .await?;
+        match Self::replace_component(ctx, &mut component, &path, &mut report).await {
+            Ok(upgraded_component_id) => {
+                ctx.txns()
+                    .await?
+                    .pg()
+                    .batch_execute("RELEASE SAVEPOINT upgrade_component")
+                    .await?;
+                report.upgraded_component_id = Some(upgraded_component_id);
+                report.status = ComponentMigrationStatus::Succeeded;
+            }
+            Err(err) => {
+                warn!(error = ?err, %component_id, "component upgrade failed, rolling it back");
+                ctx.txns()
+                    .await?
+                    .pg()
+                    .batch_execute(
+                        "ROLLBACK TO SAVEPOINT upgrade_component; RELEASE SAVEPOINT upgrade_component",
+                    )
+                    .await?;
+                report.dropped_keys.clear();
+                report.error = Some(err.to_string());
+            }
+        }
+        Ok(report)
+    }
+
+    /// Runs the migration funcs on the domain of the component and replaces it with a component
+    /// on the target variant, returning the id of the new component. Callers are expected to roll
+    /// back everything done here if an error is returned.
+    async fn replace_component(
+        ctx: &DalContext,
+        component: &mut Component,
+        path: &[Self],
+        report: &mut ComponentMigrationReport,
+    ) -> SchemaVariantMigrationResult<ComponentId> {
+        let component_id = *component.id();
+        let to_schema_variant_id = report.to_schema_variant_id;
+
+        let old_domain = ComponentView::new(ctx, component_id)
+            .await?
+            .properties
+            .get("domain")
+            .cloned()
+            .unwrap_or_else(|| serde_json::json!({}));
+
+        let mut domain = old_domain.clone();
+        for migration in path {
+            domain = migration.migrate_domain(ctx, domain).await?;
+            report.applied_migrations.push(migration.func_id);
+        }
+
+        // Replacing the component deletes it, which protected components do not allow.
+        if component.get_protected(ctx).await? {
+            return Err(ComponentError::ComponentProtected(component_id).into());
+        }
+        let socket_map =
+            Self::map_connected_sockets(ctx, component_id, to_schema_variant_id).await?;
+
+        carry_secrets(
+            ctx,
+            report.from_schema_variant_id,
+            to_schema_variant_id,
+            &old_domain,
+            &mut domain,
+        )
+        .await?;
+        collect_dropped_keys(&old_domain, &domain, "", &mut report.dropped_keys);
+
+        let name = component.name(ctx).await?;
+        let (upgraded, mut upgraded_node) = Component::new(ctx, name, to_schema_variant_id).await?;
+        let upgraded_id = *upgraded.id();
+        set_root_prop_child(ctx, upgraded_id, RootPropChild::Domain, domain).await?;
+
+        let resource = component.resource(ctx).await?;
+        if resource.payload.is_some() {
+            set_root_prop_child(
+                ctx,
+                upgraded_id,
+                RootPropChild::Resource,
+                serde_json::to_value(resource)?,
+            )
+            .await?;
+        }
+
+        let component_type = component.get_type(ctx).await?;
+        if upgraded.get_type(ctx).await? != component_type {
+            upgraded.set_type(ctx, component_type).await?;
+        }
+        if let Some(color) = component.color(ctx).await? {
+            let color_attribute_value = Component::find_si_child_attribute_value(
+                ctx,
+                upgraded_id,
+                to_schema_variant_id,
+                SiPropChild::Color,
+            )
+            .await?;
+            let si_attribute_value = color_attribute_value
+                .parent_attribute_value(ctx)
+                .await?
+                .ok_or(SchemaVariantMigrationError::ParentNotFound(upgraded_id))?;
+            let update_attribute_context =
+                AttributeContextBuilder::from(color_attribute_value.context)
+                    .set_component_id(upgraded_id)
+                    .to_context()?;
+            AttributeValue::update_for_context(
+                ctx,
+                *color_attribute_value.id(),
+                Some(*si_attribute_value.id()),
+                update_attribute_context,
+                Some(serde_json::to_value(color)?),
+                None,
+            )
+            .await?;
+        }
+
+        let node = component
+            .node(ctx)
+            .await?
+            .pop()
+            .ok_or(ComponentError::NodeNotFoundForComponent(component_id))?;
+        upgraded_node
+            .set_geometry(ctx, node.x(), node.y(), node.width(), node.height())
+            .await?;
+
+        // Move every connection over to the matching socket on the new component. The old edge is
+        // removed first so that a frame with attached children can be deleted afterwards.
+        let old_object_id = EdgeObjectId::from(component_id);
+        for mut edge in Edge::list_for_component(ctx, component_id).await? {
+            let (head_node_id, head_socket_id) = if edge.head_object_id() == old_object_id {
+                (*upgraded_node.id(), socket_map[&edge.head_socket_id()])
+            } else {
+                (edge.head_node_id(), edge.head_socket_id())
+            };
+            let (tail_node_id, tail_socket_id) = if edge.tail_object_id() == old_object_id {
+                (*upgraded_node.id(), socket_map[&edge.tail_socket_id()])
+            } else {
+                (edge.tail_node_id(), edge.tail_socket_id())
+            };
+            let kind = edge.kind().clone();
+            edge.delete_and_propagate(ctx).await?;
+            Edge::new_for_connection(
+                ctx,
+                head_node_id,
+                head_socket_id,
+                tail_node_id,
+                tail_socket_id,
+                kind,
+            )
+            .await?;
+        }
+
+        component.delete_and_propagate(ctx).await?;
+
+        // The resource now belongs to the upgraded component, so deleting the old one must not
+        // destroy it.
+        let deleted_ctx = &ctx.clone_with_delete_visibility();
+        let mut deleted = Component::get_by_id(deleted_ctx, &component_id)
+            .await?
+            .ok_or(SchemaVariantMigrationError::ComponentNotFound(component_id))?;
+        if deleted.needs_destroy() {
+            deleted.set_needs_destroy(deleted_ctx, false).await?;
+        }
+
+        Ok(upgraded_id)
+    }
+
+    /// Maps every socket the component is connected through to the socket with the same name and
+    /// edge kind on the target [`SchemaVariant`](crate::SchemaVariant).
+    async fn map_connected_sockets(
+        ctx: &DalContext,
+        component_id: ComponentId,
+        to_schema_variant_id: SchemaVariantId,
+    ) -> SchemaVariantMigrationResult<HashMap<SocketId, SocketId>> {
+        let target_sockets = SchemaVariant::get_by_id(ctx, &to_schema_variant_id)
+            .await?
+            .ok_or(SchemaVariantMigrationError::SchemaVariantNotFound(
+                to_schema_variant_id,
+            ))?
+            .sockets(ctx)
+            .await?;
+        let sockets = Socket::list_for_component(ctx, component_id).await?;
+
+        let old_object_id = EdgeObjectId::from(component_id);
+        let mut socket_map = HashMap::new();
+        for edge in Edge::list_for_component(ctx, component_id).await? {
+            let mut connected = Vec::new();
+            if edge.head_object_id() == old_object_id {
+                connected.push(edge.head_socket_id());
+            }
+            if edge.tail_object_id() == old_object_id {
+                connected.push(edge.tail_socket_id());
+            }
+            for socket_id in connected {
+                if socket_map.contains_key(&socket_id) {
+                    continue;
+                }
+                let socket = sockets
+                    .iter()
+                    .find(|socket| *socket.id() == socket_id)
+                    .ok_or(EdgeError::SocketNotFound(socket_id))?;
+                let target = target_sockets
+                    .iter()
+                    .find(|target| {
+                        target.name() == socket.name() && target.edge_kind() == socket.edge_kind()
+                    })
+                    .ok_or_else(|| {
+                        SchemaVariantMigrationError::SocketMissingOnTarget(
+                            socket.name().to_owned(),
+                            to_schema_variant_id,
+                        )
+                    })?;
+                socket_map.insert(socket_id, *target.id());
+            }
+        }
+        Ok(socket_map)
+    }
+
+    /// Upgrades several components, returning one report per component.
+    pub async fn upgrade_components(
+        ctx: &DalContext,
+        component_ids: &[ComponentId],
+        to_schema_variant_id: SchemaVariantId,
+    ) -> SchemaVariantMigrationResult<Vec<ComponentMigrationReport>> {
+        let mut reports = Vec::with_capacity(component_ids.len());
+        for component_id in component_ids {
+            reports.push(Self::upgrade_component(ctx, *component_id, to_schema_variant_id).await?);
+        }
+        Ok(reports)
+    }
+}
+
+/// Sets the whole tree below one of the children of the root prop for a component.
+async fn set_root_prop_child(
+    ctx: &DalContext,
+    component_id: ComponentId,
+    child: RootPropChild,
+    value: serde_json::Value,
+) -> SchemaVariantMigrationResult<()> {
+    let attribute_value =
+        Component::root_prop_child_attribute_value_for_component(ctx, component_id, child).await?;
+    let root_attribute_value = attribute_value
+        .parent_attribute_value(ctx)
+        .await?
+        .ok_or(SchemaVariantMigrationError::ParentNotFound(component_id))?;
+    let update_attribute_context = AttributeContextBuilder::from(attribute_value.context)
+        .set_component_id(component_id)
+        .to_context()?;
+    AttributeValue::update_for_context(
+        ctx,
+        *attribute_value.id(),
+        Some(*root_attribute_value.id()),
+        update_attribute_context,
+        Some(value),
+        None,
+    )
+    .await?;
+    Ok(())
+}
+
+/// Copies the value of every secret prop in the old domain to the migrated domain when the target
+/// variant has a secret prop at the same path and the migration funcs left it unset.
+async fn carry_secrets(
+    ctx: &DalContext,
+    from_schema_variant_id: SchemaVariantId,
+    to_schema_variant_id: SchemaVariantId,
+    old_domain: &serde_json::Value,
+    domain: &mut serde_json::Value,
+) -> SchemaVariantMigrationResult<()> {
+    for prop in SchemaVariant::all_props(ctx, from_schema_variant_id).await? {
+        if *prop.widget_kind() != WidgetKind::SecretSelect {
+            continue;
+        }
+        let parts = prop.path().as_owned_parts();
+        let keys = match parts.as_slice() {
+            [root, child, keys @ ..] if root == "root" && child == "domain" && !keys.is_empty() => {
+                keys
+            }
+            _ => continue,
+        };
+        let pointer: String = keys
+            .iter()
+            .map(|key| format!("/{}", key.replace('~', "~0").replace('/', "~1")))
+            .collect();
+        let value = match old_domain.pointer(&pointer) {
+            Some(value) if !value.is_null() => value.clone(),
+            _ => continue,
+        };
+        if domain.pointer(&pointer).is_some() {
+            continue;
+        }
+        match Prop::find_prop_by_path(ctx, to_schema_variant_id, &prop.path()).await {
+            Ok(target) if *target.widget_kind() == WidgetKind::SecretSelect => {
+                insert_at(domain, keys, value)
+            }
+            Ok(_) | Err(PropError::NotFoundAtPath(..)) => {}
+            Err(err) => return Err(err.into()),
+        }
+    }
+    Ok(())
+}
+
+/// Sets `value` at the path made of `keys`, creating the objects along the way. Nothing is set if
+/// the path goes through something that is not an object.
+fn insert_at(mut object: &mut serde_json::Value, keys: &[String], value: serde_json::Value) {
+    let (last, parents) = match keys.split_last() {
+        Some(split) => split,
+        None => return,
+    };
+    for key in parents {
+        object = match object.as_object_mut() {
+            Some(map) => map
+                .entry(key.to_owned())
+                .or_insert_with(|| serde_json::json!({})),
+            None => return,
+        };
+    }
+    if let Some(map) = object.as_object_mut() {
+        map.insert(last.to_owned(), value);
+    }
+}
+
+/// Pushes the path of every object key in `old` that is absent from `new`, descending into
+/// objects present on both sides.
+fn collect_dropped_keys(
+    old: &serde_json::Value,
+    new: &serde_json::Value,
+    prefix: &str,
+    dropped_keys: &mut Vec<String>,
+) {
+    if let (Some(old), Some(new)) = (old.as_object(), new.as_object()) {
+        for (key, old_value) in old {
+            let path = if prefix.is_empty() {
+                key.to_owned()
+            } else {
+                format!("{prefix}/{key}")
+            };
+            match new.get(key) {
+                Some(new_value) => collect_dropped_keys(old_value, new_value, &path, dropped_keys),
+                None => dropped_keys.push(path),
+            }
+        }
+    }
+}
@@ -1,10 +1,11 @@
 use crate::{Tenancy, TransactionsError, UserError, UserPk};
 use chrono::{DateTime, Utc};
 use postgres_types::ToSql;
-use serde::{de::DeserializeOwned, Serialize};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
 use si_data_nats::NatsError;
 use si_data_pg::{PgError, PgRow};
 use std::fmt::Debug;
+use std::marker::PhantomData;
 use strum::AsRefStr;
 use telemetry::prelude::*;
 use thiserror::Error;
@@ -444,7 +445,13 @@ pub async fn disassociate_all_many_to_many<LeftId: Send + Sync + ToSql>(
 pub fn objects_from_rows<OBJECT: DeserializeOwned>(
     rows: Vec<PgRow>,
 ) -> StandardModelResult<Vec<OBJECT>> {
-    let mut result = Vec::new();
+    if rows.len() > OVERSIZED_QUERY_ROW_COUNT {
+        warn!(
+            row_count = rows.len(),
+            "oversized standard model query; consider a paginated query instead"
+        );
+    }
+    let mut result = Vec::with_capacity(rows.len());
     for row in rows.into_iter() {
         let json: serde_json::Value = row.try_get("object")?;
         let object: OBJECT = serde_json::from_value(json)?;
@@ -525,6 +532,199 @@ pub async fn list<OBJECT: DeserializeOwned>(
     objects_from_rows(rows)
 }
 
+/// The page size used by callers that have no particular preference.
+pub const DEFAULT_PAGE_SIZE: i64 = 500;
+/// The largest page a single paginated query may return. Larger requests are clamped.
+pub const MAX_PAGE_SIZE: i64 = 5_000;
+/// Unpaginated queries returning more rows than this are logged, so that they can be moved to
+/// the paginated queries.
+pub const OVERSIZED_QUERY_ROW_COUNT: usize = 10_000;
+
+/// A page of objects from a paginated query. Pages are ordered by id, and the cursor is the id of
+/// the last object in the page. It is `None` once there are no more pages.
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct Page<T> {
+    pub items: Vec<T>,
+    pub next_cursor: Option<String>,
+}
+
+fn clamp_page_size(page_size: i64) -> i64 {
+    if page_size > MAX_PAGE_SIZE {
+        warn!(
+            page_size,
+            max_page_size = MAX_PAGE_SIZE,
+            "requested page size is over the limit; clamping"
+        );
+        MAX_PAGE_SIZE
+    } else {
+        page_size.max(1)
+    }
+}
+
+fn page_from_rows<OBJECT: DeserializeOwned>(
+    rows: Vec<PgRow>,
+    page_size: i64,
+) -> StandardModelResult<Page<OBJECT>> {
+    let is_full = i64::try_from(rows.len()).unwrap_or(i64::MAX) >= page_size;
+    let mut last_id = None;
+    let mut items = Vec::with_capacity(rows.len());
+    for row in rows {
+        let json: serde_json::Value = row.try_get("object")?;
+        last_id = json
+            .get("id")
+            .and_then(|id| id.as_str())
+            .map(ToOwned::to_owned);
+        items.push(serde_json::from_value(json)?);
+    }
+    Ok(Page {
+        items,
+        next_cursor: if is_full { last_id } else { None },
+    })
+}
+
+#[instrument(level = "trace", skip(ctx))]
+pub async fn list_paginated<OBJECT: DeserializeOwned>(
+    ctx: &DalContext,
+    table: &str,
+    cursor: Option<&str>,
+    page_size: i64,
+) -> StandardModelResult<Page<OBJECT>> {
+    let page_size = clamp_page_size(page_size);
+    let rows = ctx
+        .txns()
+        .await?
+        .pg()
+        .query(
+            "SELECT * FROM list_models_paginated_v1($1, $2, $3, $4, $5)",
+            &[&table, ctx.tenancy(), ctx.visibility(), &cursor, &page_size],
+        )
+        .await?;
+    page_from_rows(rows, page_size)
+}
+
+#[instrument(level = "trace", skip(ctx))]
+pub async fn find_by_attr_paginated<V: Send + Sync + ToString + Debug, OBJECT: DeserializeOwned>(
+    ctx: &DalContext,
+    table: &str,
+    attr_name: &str,
+    value: &V,
+    cursor: Option<&str>,
+    page_size: i64,
+) -> StandardModelResult<Page<OBJECT>> {
+    let page_size = clamp_page_size(page_size);
+    let rows = ctx
+        .txns()
+        .await?
+        .pg()
+        .query(
+            "SELECT * FROM find_by_attr_paginated_v1($1, $2, $3, $4, $5, $6, $7)",
+            &[
+                &table,
+                ctx.tenancy(),
+                ctx.visibility(),
+                &attr_name,
+                &value.to_string(),
+                &cursor,
+                &page_size,
+            ],
+        )
+        .await?;
+    page_from_rows(rows, page_size)
+}
+
+#[derive(Debug, Clone)]
+enum PaginatorQuery {
+    FindByAttr { attr_name: String, value: String },
+    List,
+}
+
+/// Walks a standard model query one [`Page`] at a time, so that large result sets can be
+/// processed without loading them into memory all at once.
+///
+/// ```ignore
+/// let mut pages = Component::paginate(DEFAULT_PAGE_SIZE);
+/// while let Some(components) = pages.next_page(ctx).await? {
+///     // ...
+/// }
+/// ```
+#[derive(Debug, Clone)]
+pub struct Paginator<OBJECT> {
+    table: &'static str,
+    query: PaginatorQuery,
+    page_size: i64,
+    cursor: Option<String>,
+    done: bool,
+    object: PhantomData<OBJECT>,
+}
+
+impl<OBJECT: DeserializeOwned> Paginator<OBJECT> {
+    pub fn list(table: &'static str, page_size: i64) -> Self {
+        Self::new(table, PaginatorQuery::List, page_size)
+    }
+
+    pub fn find_by_attr(
+        table: &'static str,
+        attr_name: impl Into<String>,
+        value: &impl ToString,
+        page_size: i64,
+    ) -> Self {
+        Self::new(
+            table,
+            PaginatorQuery::FindByAttr {
+                attr_name: attr_name.into(),
+                value: value.to_string(),
+            },
+            page_size,
+        )
+    }
+
+    fn new(table: &'static str, query: PaginatorQuery, page_size: i64) -> Self {
+        Self {
+            table,
+            query,
+            page_size,
+            cursor: None,
+            done: false,
+            object: PhantomData,
+        }
+    }
+
+    /// Fetches the next page, returning `None` once every page has been returned.
+    pub async fn next_page(
+        &mut self,
+        ctx: &DalContext,
+    ) -> StandardModelResult<Option<Vec<OBJECT>>> {
+        if self.done {
+            return Ok(None);
+        }
+
+        let page: Page<OBJECT> = match &self.query {
+            PaginatorQuery::FindByAttr { attr_name, value } => {
+                find_by_attr_paginated(
+                    ctx,
+                    self.table,
+                    attr_name,
+                    value,
+                    self.cursor.as_deref(),
+                    self.page_size,
+                )
+                .await?
+            }
+            PaginatorQuery::List => {
+                list_paginated(ctx, self.table, self.cursor.as_deref(), self.page_size).await?
+            }
+        };
+
+        self.cursor = page.next_cursor;
+        self.done = self.cursor.is_none();
+        if page.items.is_empty() {
+            return Ok(None);
+        }
+        Ok(Some(page.items))
+    }
+}
+
 #[instrument(level = "trace", skip(ctx))]
 pub async fn delete_by_id<ID: Send + Sync + ToSql + std::fmt::Display>(
     ctx: &DalContext,
@@ -732,6 +932,60 @@ pub trait StandardModel {
         Ok(result)
     }
 
+    #[instrument(level = "trace", skip(ctx), fields(table = %Self::table_name()))]
+    async fn list_paginated(
+        ctx: &DalContext,
+        cursor: Option<&str>,
+        page_size: i64,
+    ) -> StandardModelResult<Page<Self>>
+    where
+        Self: Sized + DeserializeOwned,
+    {
+        crate::standard_model::list_paginated(ctx, Self::table_name(), cursor, page_size).await
+    }
+
+    #[instrument(level = "trace", skip(ctx), fields(table = %Self::table_name()))]
+    async fn find_by_attr_paginated<V: Send + Sync + ToString + Debug>(
+        ctx: &DalContext,
+        attr_name: &str,
+        value: &V,
+        cursor: Option<&str>,
+        page_size: i64,
+    ) -> StandardModelResult<Page<Self>>
+    where
+        Self: Sized + DeserializeOwned,
+    {
+        crate::standard_model::find_by_attr_paginated(
+            ctx,
+            Self::table_name(),
+            attr_name,
+            value,
+            cursor,
+            page_size,
+        )
+        .await
+    }
+
+    /// Returns a [`Paginator`] over every object of this model.
+    fn paginate(page_size: i64) -> Paginator<Self>
+    where
+        Self: Sized + DeserializeOwned,
+    {
+        Paginator::list(Self::table_name(), page_size)
+    }
+
+    /// Returns a [`Paginator`] over the objects of this model whose `attr_name` matches `value`.
+    fn paginate_by_attr<V: ToString>(
+        attr_name: impl Into<String>,
+        value: &V,
+        page_size: i64,
+    ) -> Paginator<Self>
+    where
+        Self: Sized + DeserializeOwned,
+    {
+        Paginator::find_by_attr(Self::table_name(), attr_name, value, page_size)
+    }
+
     #[instrument(level = "trace", skip_all, fields(table = %Self::table_name(), pk = %self.pk()))]
     async fn delete_by_pk(&mut self, ctx: &DalContext) -> StandardModelResult<()>
     where
@@ -1,6 +1,7 @@
 use serde::{Deserialize, Serialize};
 use si_data_nats::NatsError;
 use si_data_pg::PgError;
+use strum::{Display, EnumString};
 use telemetry::prelude::*;
 use thiserror::Error;
 use tokio::task::JoinError;
@@ -11,6 +12,7 @@ use crate::{
 };
 
 const USER_GET_BY_PK: &str = include_str!("queries/user/get_by_pk.sql");
+const USER_ROLE_IN_WORKSPACE: &str = include_str!("queries/user/role_in_workspace.sql");
 
 #[remain::sorted]
 #[derive(Error, Debug)]
@@ -39,6 +41,28 @@ pub type UserResult<T> = Result<T, UserError>;
 
 pk!(UserPk);
 
+/// What a [`User`] may do in a [`Workspace`](crate::Workspace) they belong to.
+#[remain::sorted]
+#[derive(Deserialize, Serialize, Debug, Display, EnumString, PartialEq, Eq, Clone, Copy)]
+#[serde(rename_all = "snake_case")]
+#[strum(serialize_all = "snake_case")]
+pub enum WorkspaceRole {
+    /// Administers the workspace on behalf of its owner.
+    Admin,
+    /// Works in the workspace without administering it.
+    Member,
+    /// Created the workspace.
+    Owner,
+}
+
+impl WorkspaceRole {
+    /// Whether the role may change how the workspace is administered, such as its
+    /// [`ChangeSetReviewPolicy`](crate::ChangeSetReviewPolicy).
+    pub fn is_admin(&self) -> bool {
+        matches!(self, Self::Owner | Self::Admin)
+    }
+}
+
 #[derive(Deserialize, Serialize, Debug, Clone, PartialEq, Eq)]
 pub struct User {
     pk: UserPk,
@@ -122,21 +146,42 @@ impl User {
         Ok(true)
     }
 
+    /// Makes the user a member of a workspace with the given role. Users who already belong to
+    /// the workspace keep their role.
     pub async fn associate_workspace(
         &self,
         ctx: &DalContext,
         workspace_pk: WorkspacePk,
+        role: WorkspaceRole,
     ) -> UserResult<()> {
         ctx.txns()
             .await?
             .pg()
             .execute(
-                "SELECT user_associate_workspace_v1($1, $2)",
-                &[&self.pk, &workspace_pk],
+                "SELECT user_associate_workspace_v2($1, $2, $3)",
+                &[&self.pk, &workspace_pk, &role.to_string()],
             )
             .await?;
         Ok(())
     }
+
+    /// The role of a user in a workspace, if they belong to it.
+    pub async fn role_in_workspace(
+        ctx: &DalContext,
+        user_pk: UserPk,
+        workspace_pk: WorkspacePk,
+    ) -> UserResult<Option<WorkspaceRole>> {
+        let row = ctx
+            .txns()
+            .await?
+            .pg()
+            .query_opt(USER_ROLE_IN_WORKSPACE, &[&user_pk, &workspace_pk])
+            .await?;
+        Ok(match row {
+            Some(row) => Some(serde_json::from_value(row.try_get("role")?)?),
+            None => None,
+        })
+    }
 }
 
 #[derive(Deserialize, Serialize, Debug, Clone, Copy)]
@@ -85,6 +85,18 @@ pub enum Validation {
     StringIsNotEmpty { value: Option<String> },
     /// Validate that the "value" string is a valid [IpAddr](std::net::IpAddr).
     StringIsValidIpAddr { value: Option<String> },
+    /// Validate that the number of characters in the "value" string is between the lower and
+    /// upper bound integers, inclusive.
+    StringLengthIsBetweenTwoIntegers {
+        value: Option<String>,
+        lower_bound: i64,
+        upper_bound: i64,
+    },
+    /// Validate that the "value" string matches the expected regular expression.
+    StringMatchesRegex {
+        value: Option<String>,
+        expected: String,
+    },
 }
 
 impl Validation {
@@ -130,6 +142,21 @@ impl Validation {
             Validation::StringIsNotEmpty { value: _ } => Validation::StringIsNotEmpty {
                 value: Self::value_as_string(value)?,
             },
+            Validation::StringLengthIsBetweenTwoIntegers {
+                value: _,
+                lower_bound,
+                upper_bound,
+            } => Validation::StringLengthIsBetweenTwoIntegers {
+                value: Self::value_as_string(value)?,
+                lower_bound,
+                upper_bound,
+            },
+            Validation::StringMatchesRegex { value: _, expected } => {
+                Validation::StringMatchesRegex {
+                    value: Self::value_as_string(value)?,
+                    expected,
+                }
+            }
         };
         Ok(validation)
     }
@@ -167,9 +194,12 @@ pub enum ValidationErrorKind {
     IntegerNotInBetweenTwoIntegers,
     InvalidHexString,
     InvalidIpAddr,
+    InvalidRegex,
     JsValidation,
     StringDoesNotEqual,
     StringDoesNotHavePrefix,
+    StringDoesNotMatchRegex,
+    StringLengthNotInBetweenTwoIntegers,
     StringNotInStringArray,
     ValueMustBePresent,
 }
@@ -180,8 +210,11 @@ impl ValidationErrorKind {
             Self::IntegerNotInBetweenTwoIntegers => "IntegerNotInBetweenTwoIntegers",
             Self::InvalidHexString => "InvalidHexString",
             Self::InvalidIpAddr => "InvalidIpAddr",
+            Self::InvalidRegex => "InvalidRegex",
             Self::StringDoesNotEqual => "StringDoesNotEqual",
             Self::StringDoesNotHavePrefix => "StringDoesNotHavePrefix",
+            Self::StringDoesNotMatchRegex => "StringDoesNotMatchRegex",
+            Self::StringLengthNotInBetweenTwoIntegers => "StringLengthNotInBetweenTwoIntegers",
             Self::StringNotInStringArray => "StringNotInStringArray",
             Self::ValueMustBePresent => "ValueMustBePresent",
             Self::JsValidation => "JsValidation",
@@ -0,0 +1,245 @@
+//! This module contains [`WorkspaceIntegration`], which stores provider-level configuration
+//! (default region, account aliases and a reference to the credential [`Secret`](crate::Secret))
+//! once per workspace so that [`SchemaVariants`](crate::SchemaVariant) can share it.
+
+use serde::{Deserialize, Serialize};
+use si_data_nats::NatsError;
+use si_data_pg::PgError;
+use telemetry::prelude::*;
+use thiserror::Error;
+
+use crate::{
+    impl_standard_model, pk, standard_model, standard_model_accessor, standard_model_many_to_many,
+    DalContext, HistoryEventError, SchemaVariant, SchemaVariantId, SecretId, StandardModel,
+    StandardModelError, Tenancy, Timestamp, TransactionsError, Visibility,
+};
+
+type JsonValue = serde_json::Value;
+
+const LIST_FOR_SCHEMA_VARIANT: &str =
+    include_str!("queries/workspace_integration/list_for_schema_variant.sql");
+
+#[remain::sorted]
+#[derive(Error, Debug)]
+pub enum WorkspaceIntegrationError {
+    #[error("history event error: {0}")]
+    HistoryEvent(#[from] HistoryEventError),
+    #[error("account aliases must be a json object, found: {0}")]
+    InvalidAccountAliases(JsonValue),
+    #[error("nats txn error: {0}")]
+    Nats(#[from] NatsError),
+    #[error("workspace integration not found: {0}")]
+    NotFound(WorkspaceIntegrationId),
+    #[error("pg error: {0}")]
+    Pg(#[from] PgError),
+    #[error("error serializing/deserializing json: {0}")]
+    SerdeJson(#[from] serde_json::Error),
+    #[error("standard model error: {0}")]
+    StandardModelError(#[from] StandardModelError),
+    #[error("transactions error: {0}")]
+    Transactions(#[from] TransactionsError),
+}
+
+pub type WorkspaceIntegrationResult<T> = Result<T, WorkspaceIntegrationError>;
+
+pk!(WorkspaceIntegrationPk);
+pk!(WorkspaceIntegrationId);
+
+/// A `WorkspaceIntegration` holds the configuration for a cloud provider (e.g. "aws") that is
+/// shared across a workspace. [`SchemaVariants`](crate::SchemaVariant) that need the
+/// configuration are associated with the integration, which is also how usage is tracked.
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq, Eq)]
+pub struct WorkspaceIntegration {
+    pk: WorkspaceIntegrationPk,
+    id: WorkspaceIntegrationId,
+    name: String,
+    provider: String,
+    default_region: Option<String>,
+    /// A json object mapping a human friendly alias to a provider account identifier.
+    account_aliases: JsonValue,
+    credential_secret_id: Option<SecretId>,
+    #[serde(flatten)]
+    tenancy: Tenancy,
+    #[serde(flatten)]
+    timestamp: Timestamp,
+    #[serde(flatten)]
+    visibility: Visibility,
+}
+
+/// The shape of a [`WorkspaceIntegration`] when it is provided as an input to a function.
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct WorkspaceIntegrationView {
+    pub name: String,
+    pub provider: String,
+    pub default_region: Option<String>,
+    pub account_aliases: JsonValue,
+    pub credential_secret_id: Option<SecretId>,
+}
+
+impl_standard_model! {
+    model: WorkspaceIntegration,
+    pk: WorkspaceIntegrationPk,
+    id: WorkspaceIntegrationId,
+    table_name: "workspace_integrations",
+    history_event_label_base: "workspace_integration",
+    history_event_message_name: "Workspace Integration"
+}
+
+impl WorkspaceIntegration {
+    #[instrument(skip_all)]
+    pub async fn new(
+        ctx: &DalContext,
+        name: impl AsRef<str>,
+        provider: impl AsRef<str>,
+    ) -> WorkspaceIntegrationResult<Self> {
+        let name = name.as_ref();
+        let provider = provider.as_ref();
+        let row = ctx
+            .txns()
+            .await?
+            .pg()
+            .query_one(
+                "SELECT object FROM workspace_integration_create_v1($1, $2, $3, $4)",
+                &[ctx.tenancy(), ctx.visibility(), &name, &provider],
+            )
+            .await?;
+        let object = standard_model::finish_create_from_row(ctx, row).await?;
+        Ok(object)
+    }
+
+    standard_model_accessor!(name, String, WorkspaceIntegrationResult);
+    standard_model_accessor!(provider, String, WorkspaceIntegrationResult);
+    standard_model_accessor!(default_region, Option<String>, WorkspaceIntegrationResult);
+    standard_model_accessor!(
+        credential_secret_id,
+        Option<Pk(SecretId)>,
+        WorkspaceIntegrationResult
+    );
+    standard_model_accessor!(account_aliases, Json<JsonValue>, WorkspaceIntegrationResult);
+
+    standard_model_many_to_many!(
+        lookup_fn: schema_variants,
+        associate_fn: add_schema_variant,
+        disassociate_fn: remove_schema_variant,
+        disassociate_all_fn: remove_all_schema_variants,
+        table_name: "workspace_integration_many_to_many_schema_variants",
+        left_table: "workspace_integrations",
+        left_id: WorkspaceIntegrationId,
+        right_table: "schema_variants",
+        right_id: SchemaVariantId,
+        which_table_is_this: "left",
+        returns: SchemaVariant,
+        result: WorkspaceIntegrationResult,
+    );
+
+    pub async fn find_by_name(
+        ctx: &DalContext,
+        name: impl AsRef<str>,
+    ) -> WorkspaceIntegrationResult<Option<Self>> {
+        let name = name.as_ref();
+        Ok(Self::find_by_attr(ctx, "name", &name).await?.pop())
+    }
+
+    pub async fn list_for_provider(
+        ctx: &DalContext,
+        provider: impl AsRef<str>,
+    ) -> WorkspaceIntegrationResult<Vec<Self>> {
+        let provider = provider.as_ref();
+        Ok(Self::find_by_attr(ctx, "provider", &provider).await?)
+    }
+
+    /// Adds (or replaces) a single alias within the account aliases.
+    pub async fn set_account_alias(
+        &mut self,
+        ctx: &DalContext,
+        alias: impl Into<String>,
+        account: impl Into<String>,
+    ) -> WorkspaceIntegrationResult<()> {
+        let mut aliases = self.account_aliases.clone();
+        aliases
+            .as_object_mut()
+            .ok_or_else(|| WorkspaceIntegrationError::InvalidAccountAliases(aliases.clone()))?
+            .insert(alias.into(), JsonValue::String(account.into()));
+        self.set_account_aliases(ctx, aliases).await
+    }
+
+    /// Removes a single alias from the account aliases, if present.
+    pub async fn remove_account_alias(
+        &mut self,
+        ctx: &DalContext,
+        alias: impl AsRef<str>,
+    ) -> WorkspaceIntegrationResult<()> {
+        let mut aliases = self.account_aliases.clone();
+        aliases
+            .as_object_mut()
+            .ok_or_else(|| WorkspaceIntegrationError::InvalidAccountAliases(aliases.clone()))?
+            .remove(alias.as_ref());
+        self.set_account_aliases(ctx, aliases).await
+    }
+
+    /// The number of [`SchemaVariants`](crate::SchemaVariant) that reference this integration.
+    pub async fn usage_count(&self, ctx: &DalContext) -> WorkspaceIntegrationResult<usize> {
+        Ok(self.schema_variants(ctx).await?.len())
+    }
+
+    /// Deletes the integration, removing any associations with
+    /// [`SchemaVariants`](crate::SchemaVariant) first.
+    pub async fn remove(mut self, ctx: &DalContext) -> WorkspaceIntegrationResult<()> {
+        self.remove_all_schema_variants(ctx).await?;
+        self.delete_by_id(ctx).await?;
+        Ok(())
+    }
+
+    pub fn view(&self) -> WorkspaceIntegrationView {
+        WorkspaceIntegrationView {
+            name: self.name.clone(),
+            provider: self.provider.clone(),
+            default_region: self.default_region.clone(),
+            account_aliases: self.account_aliases.clone(),
+            credential_secret_id: self.credential_secret_id,
+        }
+    }
+
+    /// Lists the integrations associated with a [`SchemaVariant`](crate::SchemaVariant).
+    pub async fn list_for_schema_variant(
+        ctx: &DalContext,
+        schema_variant_id: SchemaVariantId,
+    ) -> WorkspaceIntegrationResult<Vec<Self>> {
+        let rows = ctx
+            .txns()
+            .await?
+            .pg()
+            .query(
+                LIST_FOR_SCHEMA_VARIANT,
+                &[ctx.tenancy(), ctx.visibility(), &schema_variant_id],
+            )
+            .await?;
+        Ok(standard_model::objects_from_rows(rows)?)
+    }
+
+    /// Finds the integrations for a [`SchemaVariant`](crate::SchemaVariant), as views suitable
+    /// for use as function inputs.
+    pub async fn views_for_schema_variant(
+        ctx: &DalContext,
+        schema_variant_id: SchemaVariantId,
+    ) -> WorkspaceIntegrationResult<Vec<WorkspaceIntegrationView>> {
+        Ok(Self::list_for_schema_variant(ctx, schema_variant_id)
+            .await?
+            .iter()
+            .map(Self::view)
+            .collect())
+    }
+
+    /// Finds the view of an integration by name, which is what the
+    /// [`WorkspaceIntegration`](crate::func::intrinsics::IntrinsicFunc::WorkspaceIntegration)
+    /// intrinsic func returns.
+    pub async fn view_by_name(
+        ctx: &DalContext,
+        name: impl AsRef<str>,
+    ) -> WorkspaceIntegrationResult<Option<WorkspaceIntegrationView>> {
+        Ok(Self::find_by_name(ctx, name)
+            .await?
+            .map(|integration| integration.view()))
+    }
+}
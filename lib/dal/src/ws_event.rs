@@ -41,6 +41,8 @@ pub enum WsPayload {
     ChangeSetApplied(ChangeSetPk),
     ChangeSetCanceled(ChangeSetPk),
     ChangeSetCreated(ChangeSetPk),
+    ChangeSetReviewed(ChangeSetPk),
+    ChangeSetReviewRequested(ChangeSetPk),
     ChangeSetWritten(ChangeSetPk),
     CheckedQualifications(QualificationCheckPayload),
     CodeGenerated(CodeGeneratedPayload),
@@ -1,5 +1,13 @@
-use dal::{ChangeSet, ChangeSetStatus, DalContext, Visibility};
-use dal_test::{helpers::create_change_set, test, DalContextHeadMutRef, DalContextHeadRef};
+use dal::{
+    ChangeSet, ChangeSetReview, ChangeSetReviewError, ChangeSetReviewPolicy, ChangeSetReviewStatus,
+    ChangeSetStatus, DalContext, HistoryActor, Visibility, WorkspaceRole,
+};
+use dal_test::{
+    helpers::{create_change_set, create_user},
+    test,
+    test_harness::create_schema,
+    DalContextHeadMutRef, DalContextHeadRef,
+};
 
 #[test]
 async fn new(DalContextHeadRef(ctx): DalContextHeadRef<'_>) {
@@ -84,3 +92,200 @@ async fn get_by_pk(DalContextHeadRef(ctx): DalContextHeadRef<'_>) {
         .expect("change set pk should exist");
     assert_eq!(&change_set, &result);
 }
+
+#[test]
+async fn review_policy_gates_apply(DalContextHeadRef(ctx): DalContextHeadRef<'_>) {
+    let change_set = create_change_set(ctx).await;
+    let reviewer = create_user(ctx).await;
+    let other_reviewer = create_user(ctx).await;
+
+    ChangeSetReview::ensure_approved(ctx, change_set.pk)
+        .await
+        .expect("change set should be approved without a policy");
+
+    ChangeSetReviewPolicy::set_required_approvals(ctx, 1)
+        .await
+        .expect("could not set required approvals");
+    ChangeSetReview::request(ctx, change_set.pk, reviewer.pk())
+        .await
+        .expect("could not request review");
+    ChangeSetReview::request(ctx, change_set.pk, other_reviewer.pk())
+        .await
+        .expect("could not request review");
+
+    let summary = ChangeSetReview::summary(ctx, change_set.pk)
+        .await
+        .expect("could not get review summary");
+    assert_eq!(summary.required_approvals, 1);
+    assert_eq!(summary.pending, 2);
+    assert!(!summary.can_apply);
+    assert!(matches!(
+        ChangeSetReview::ensure_approved(ctx, change_set.pk).await,
+        Err(ChangeSetReviewError::NotEnoughApprovals(_, 1, 0))
+    ));
+
+    let reviewer_ctx = ctx.clone_with_new_history_actor(HistoryActor::User(reviewer.pk()));
+    ChangeSetReview::decide(
+        &reviewer_ctx,
+        change_set.pk,
+        ChangeSetReviewStatus::Approved,
+        Some("looks good".to_string()),
+    )
+    .await
+    .expect("could not approve change set");
+    ChangeSetReview::ensure_approved(ctx, change_set.pk)
+        .await
+        .expect("change set should be approved");
+
+    let other_reviewer_ctx =
+        ctx.clone_with_new_history_actor(HistoryActor::User(other_reviewer.pk()));
+    ChangeSetReview::decide(
+        &other_reviewer_ctx,
+        change_set.pk,
+        ChangeSetReviewStatus::Rejected,
+        None,
+    )
+    .await
+    .expect("could not reject change set");
+    assert!(matches!(
+        ChangeSetReview::ensure_approved(ctx, change_set.pk).await,
+        Err(ChangeSetReviewError::Rejected(_))
+    ));
+}
+
+#[test]
+async fn review_decisions_require_a_request(DalContextHeadRef(ctx): DalContextHeadRef<'_>) {
+    let author = create_user(ctx).await;
+    let reviewer = create_user(ctx).await;
+    let author_ctx = ctx.clone_with_new_history_actor(HistoryActor::User(author.pk()));
+    let reviewer_ctx = ctx.clone_with_new_history_actor(HistoryActor::User(reviewer.pk()));
+    let change_set = create_change_set(&author_ctx).await;
+
+    assert!(matches!(
+        ChangeSetReview::decide(
+            &reviewer_ctx,
+            change_set.pk,
+            ChangeSetReviewStatus::Approved,
+            None,
+        )
+        .await,
+        Err(ChangeSetReviewError::NotRequested(_, _))
+    ));
+
+    ChangeSetReview::request(ctx, change_set.pk, author.pk())
+        .await
+        .expect("could not request review");
+    assert!(matches!(
+        ChangeSetReview::decide(
+            &author_ctx,
+            change_set.pk,
+            ChangeSetReviewStatus::Approved,
+            None,
+        )
+        .await,
+        Err(ChangeSetReviewError::SelfApproval(_, _))
+    ));
+
+    ChangeSetReview::request(ctx, change_set.pk, reviewer.pk())
+        .await
+        .expect("could not request review");
+    ChangeSetReview::decide(
+        &reviewer_ctx,
+        change_set.pk,
+        ChangeSetReviewStatus::Approved,
+        None,
+    )
+    .await
+    .expect("could not approve change set");
+    assert!(matches!(
+        ChangeSetReview::decide(
+            &reviewer_ctx,
+            change_set.pk,
+            ChangeSetReviewStatus::Rejected,
+            None,
+        )
+        .await,
+        Err(ChangeSetReviewError::NotRequested(_, _))
+    ));
+}
+
+#[test]
+async fn review_policy_requires_a_workspace_admin(DalContextHeadRef(ctx): DalContextHeadRef<'_>) {
+    let workspace_pk = ctx
+        .tenancy()
+        .workspace_pk()
+        .expect("tenancy should have a workspace");
+    let member = create_user(ctx).await;
+    member
+        .associate_workspace(ctx, workspace_pk, WorkspaceRole::Member)
+        .await
+        .expect("could not associate workspace");
+    let admin = create_user(ctx).await;
+    admin
+        .associate_workspace(ctx, workspace_pk, WorkspaceRole::Admin)
+        .await
+        .expect("could not associate workspace");
+
+    let member_ctx = ctx.clone_with_new_history_actor(HistoryActor::User(member.pk()));
+    assert!(matches!(
+        ChangeSetReviewPolicy::set_required_approvals(&member_ctx, 0).await,
+        Err(ChangeSetReviewError::NotAWorkspaceAdmin(_))
+    ));
+
+    let admin_ctx = ctx.clone_with_new_history_actor(HistoryActor::User(admin.pk()));
+    let policy = ChangeSetReviewPolicy::set_required_approvals(&admin_ctx, 2)
+        .await
+        .expect("could not set required approvals");
+    assert_eq!(policy.required_approvals, 2);
+}
+
+#[test]
+async fn modifying_a_change_set_invalidates_approvals(
+    DalContextHeadRef(ctx): DalContextHeadRef<'_>,
+) {
+    let change_set = create_change_set(ctx).await;
+    let reviewer = create_user(ctx).await;
+
+    ChangeSetReviewPolicy::set_required_approvals(ctx, 1)
+        .await
+        .expect("could not set required approvals");
+    ChangeSetReview::request(ctx, change_set.pk, reviewer.pk())
+        .await
+        .expect("could not request review");
+    let reviewer_ctx = ctx.clone_with_new_history_actor(HistoryActor::User(reviewer.pk()));
+    ChangeSetReview::decide(
+        &reviewer_ctx,
+        change_set.pk,
+        ChangeSetReviewStatus::Approved,
+        None,
+    )
+    .await
+    .expect("could not approve change set");
+    ChangeSetReview::ensure_approved(ctx, change_set.pk)
+        .await
+        .expect("change set should be approved");
+
+    let change_set_ctx = ctx.clone_with_new_visibility(Visibility::new(change_set.pk, None));
+    create_schema(&change_set_ctx).await;
+
+    assert!(matches!(
+        ChangeSetReview::ensure_approved(ctx, change_set.pk).await,
+        Err(ChangeSetReviewError::NotEnoughApprovals(_, 1, 0))
+    ));
+    let summary = ChangeSetReview::summary(ctx, change_set.pk)
+        .await
+        .expect("could not get review summary");
+    assert_eq!(summary.pending, 1);
+
+    ChangeSetReview::decide(
+        &reviewer_ctx,
+        change_set.pk,
+        ChangeSetReviewStatus::Approved,
+        None,
+    )
+    .await
+    .expect("could not approve change set again");
+    ChangeSetReview::ensure_approved(ctx, change_set.pk)
+        .await
+        .expect("change set should be approved again");
+}
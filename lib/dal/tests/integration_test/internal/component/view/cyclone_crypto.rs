@@ -4,7 +4,7 @@ use dal::DalContext;
 use dal_test::test;
 use pretty_assertions_sorted::assert_eq;
 use tokio::sync::mpsc;
-use veritech_client::ResolverFunctionResponseType;
+use veritech_client::{FunctionLanguage, ResolverFunctionResponseType};
 
 #[test]
 async fn cyclone_crypto_e2e(ctx: &DalContext) {
@@ -40,6 +40,8 @@ async fn cyclone_crypto_e2e(ctx: &DalContext) {
         },
         response_type: ResolverFunctionResponseType::Boolean,
         code_base64: general_purpose::STANDARD_NO_PAD.encode(&code),
+        lang: FunctionLanguage::JavaScript,
+        before: vec![],
     };
     let result = ctx
         .veritech()
@@ -7,7 +7,7 @@ use dal::{
         execution::FuncExecution,
     },
     generate_name, ChangeSetPk, DalContext, Func, FuncBackendKind, FuncBackendResponseType, FuncId,
-    StandardModel, Visibility,
+    FuncSearchFilters, StandardModel, Visibility,
 };
 use dal_test::{
     test,
@@ -173,3 +173,80 @@ async fn func_argument_find_by_name_for_func(ctx: &DalContext) {
     assert_eq!(name, arg.name());
     assert_eq!(func_id, arg.func_id());
 }
+
+#[test]
+async fn search_by_tag_and_text(ctx: &DalContext) {
+    let mut tagged = Func::new(
+        ctx,
+        generate_name(),
+        FuncBackendKind::JsAttribute,
+        FuncBackendResponseType::String,
+    )
+    .await
+    .expect("cannot create func");
+    tagged
+        .set_description(ctx, Some("Normalizes an AWS region name".to_string()))
+        .await
+        .expect("cannot set description");
+    tagged
+        .set_tags(ctx, [" AWS ", "region", "aws", ""])
+        .await
+        .expect("cannot set tags");
+    assert_eq!(tagged.tags(), &["aws".to_string(), "region".to_string()]);
+
+    let untagged = Func::new(
+        ctx,
+        generate_name(),
+        FuncBackendKind::JsValidation,
+        FuncBackendResponseType::Validation,
+    )
+    .await
+    .expect("cannot create func");
+
+    let filters = FuncSearchFilters {
+        tags: vec!["aws".to_string()],
+        ..Default::default()
+    };
+    let results = Func::search(ctx, None, &filters)
+        .await
+        .expect("cannot search funcs");
+    assert!(results.iter().any(|entry| entry.id == *tagged.id()));
+    assert!(!results.iter().any(|entry| entry.id == *untagged.id()));
+
+    let results = Func::search(ctx, Some("aws region"), &FuncSearchFilters::default())
+        .await
+        .expect("cannot search funcs");
+    let entry = results
+        .iter()
+        .find(|entry| entry.id == *tagged.id())
+        .expect("tagged func not found by description");
+    assert_eq!(entry.usage_count, 0);
+    assert!(tagged
+        .usages(ctx)
+        .await
+        .expect("cannot list usages")
+        .is_empty());
+
+    // Wildcards in the query match literally.
+    let results = Func::search(ctx, Some("aws_region"), &FuncSearchFilters::default())
+        .await
+        .expect("cannot search funcs");
+    assert!(!results.iter().any(|entry| entry.id == *tagged.id()));
+
+    let filters = FuncSearchFilters {
+        backend_kinds: vec![FuncBackendKind::JsValidation],
+        unused_only: true,
+        ..Default::default()
+    };
+    let results = Func::search(ctx, None, &filters)
+        .await
+        .expect("cannot search funcs");
+    assert!(results.iter().any(|entry| entry.id == *untagged.id()));
+    assert!(!results.iter().any(|entry| entry.id == *tagged.id()));
+
+    tagged
+        .remove_tag(ctx, "AWS")
+        .await
+        .expect("cannot remove tag");
+    assert_eq!(tagged.tags(), &["region".to_string()]);
+}
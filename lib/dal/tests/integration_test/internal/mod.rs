@@ -11,6 +11,7 @@ mod history_event;
 mod key_pair;
 mod node;
 mod node_menu;
+mod onboarding;
 mod pkg;
 mod prop;
 mod prop_tree;
@@ -27,3 +28,4 @@ mod validation_prototype;
 mod validation_resolver;
 mod visibility;
 mod workspace;
+mod workspace_integration;
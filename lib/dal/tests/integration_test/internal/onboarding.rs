@@ -0,0 +1,51 @@
+use dal::{installed_pkg::InstalledPkg, DalContext, StandardModel, StarterTemplate};
+use dal_test::test;
+
+#[test]
+async fn provision_container_image(ctx: &mut DalContext) {
+    let template = StarterTemplate::find("containerImage").expect("could not find template");
+    let manifest = template
+        .provision(ctx)
+        .await
+        .expect("could not provision template");
+
+    assert_eq!(manifest.change_set_pk, ctx.visibility().change_set_pk);
+    assert_eq!(template.components.len(), manifest.components.len());
+    assert_eq!(template.connections.len(), manifest.connections.len());
+
+    let image = manifest
+        .components
+        .iter()
+        .find(|component| component.key == "image")
+        .expect("could not find image component");
+    assert_eq!(Some(image.component_id), manifest.tour[0].component_id);
+}
+
+#[test]
+async fn provision_twice(ctx: &mut DalContext) {
+    let visibility = *ctx.visibility();
+    let template = StarterTemplate::find("containerImage").expect("could not find template");
+    let first = template
+        .provision(ctx)
+        .await
+        .expect("could not provision template");
+
+    // Provisioning again, from where the first one started, must not install the modules twice
+    ctx.update_visibility(visibility);
+    let second = template
+        .provision(ctx)
+        .await
+        .expect("could not provision template a second time");
+
+    assert_ne!(first.change_set_pk, second.change_set_pk);
+    assert_eq!(template.components.len(), second.components.len());
+    assert_eq!(template.connections.len(), second.connections.len());
+
+    ctx.update_visibility(visibility);
+    for module in &template.modules {
+        let installed_pkgs = InstalledPkg::find_by_attr(ctx, "name", module)
+            .await
+            .expect("could not find installed pkgs");
+        assert_eq!(1, installed_pkgs.len());
+    }
+}
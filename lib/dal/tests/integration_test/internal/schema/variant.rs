@@ -1,8 +1,17 @@
 use dal::{
+    attribute::context::AttributeContextBuilder,
     schema::{variant::leaves::LeafKind, SchemaVariant},
-    DalContext, InternalProvider, Prop, PropId, RootPropChild, Schema, StandardModel,
+    AttributeReadContext, AttributeValue, Component, ComponentMigrationStatus, ComponentView,
+    DalContext, Func, FuncBackendKind, FuncBackendResponseType, InternalProvider, Prop, PropId,
+    PropKind, RootPropChild, Schema, SchemaVariantMigration, SchemaVariantMigrationError,
+    StandardModel,
+};
+use dal_test::{
+    test,
+    test_harness::{
+        create_schema, create_schema_variant, create_schema_variant_with_root, generate_fake_name,
+    },
 };
-use dal_test::{test, test_harness::create_schema};
 use pretty_assertions_sorted::assert_eq;
 
 #[test]
@@ -249,3 +258,233 @@ async fn list_implicit_internal_providers_for_root_children(ctx: &DalContext) {
         );
     }
 }
+
+#[test]
+async fn migration_path(ctx: &DalContext) {
+    let schema = create_schema(ctx).await;
+    let v0 = create_schema_variant(ctx, *schema.id()).await;
+    let v1 = create_schema_variant(ctx, *schema.id()).await;
+    let v2 = create_schema_variant(ctx, *schema.id()).await;
+    let dead_end = create_schema_variant(ctx, *schema.id()).await;
+
+    let func = Func::new(
+        ctx,
+        generate_fake_name(),
+        FuncBackendKind::JsAttribute,
+        FuncBackendResponseType::Object,
+    )
+    .await
+    .expect("cannot create func");
+
+    SchemaVariantMigration::new(ctx, *v0.id(), *dead_end.id(), *func.id())
+        .await
+        .expect("cannot create migration");
+    let first = SchemaVariantMigration::new(ctx, *v0.id(), *v1.id(), *func.id())
+        .await
+        .expect("cannot create migration");
+    let second = SchemaVariantMigration::new(ctx, *v1.id(), *v2.id(), *func.id())
+        .await
+        .expect("cannot create migration");
+
+    let path = SchemaVariantMigration::path(ctx, *v0.id(), *v2.id())
+        .await
+        .expect("cannot find migration path");
+    assert_eq!(vec![first, second], path);
+
+    let result = SchemaVariantMigration::path(ctx, *v2.id(), *v0.id()).await;
+    assert!(matches!(
+        result,
+        Err(SchemaVariantMigrationError::NoMigrationPath(_, _))
+    ));
+}
+
+#[test]
+async fn upgrade_component(ctx: &DalContext) {
+    let schema = create_schema(ctx).await;
+    let (mut v0, v0_root) = create_schema_variant_with_root(ctx, *schema.id()).await;
+    let poop_prop = Prop::new(
+        ctx,
+        "poop",
+        PropKind::String,
+        None,
+        *v0.id(),
+        Some(v0_root.domain_prop_id),
+    )
+    .await
+    .expect("could not create prop");
+    let (mut v1, v1_root) = create_schema_variant_with_root(ctx, *schema.id()).await;
+    Prop::new(
+        ctx,
+        "canoe",
+        PropKind::String,
+        None,
+        *v1.id(),
+        Some(v1_root.domain_prop_id),
+    )
+    .await
+    .expect("could not create prop");
+    v0.finalize(ctx, None)
+        .await
+        .expect("unable to finalize schema variant");
+    v1.finalize(ctx, None)
+        .await
+        .expect("unable to finalize schema variant");
+
+    let mut func = Func::new(
+        ctx,
+        generate_fake_name(),
+        FuncBackendKind::JsAttribute,
+        FuncBackendResponseType::Object,
+    )
+    .await
+    .expect("cannot create func");
+    func.set_code_plaintext(
+        ctx,
+        Some("function migrate(input) { return { canoe: input.domain.poop }; }"),
+    )
+    .await
+    .expect("set code");
+    func.set_handler(ctx, Some("migrate"))
+        .await
+        .expect("set handler");
+    SchemaVariantMigration::new(ctx, *v0.id(), *v1.id(), *func.id())
+        .await
+        .expect("cannot create migration");
+
+    let (component, mut node) = Component::new(ctx, "paddler", *v0.id())
+        .await
+        .expect("cannot create component");
+    node.set_geometry(ctx, "123", "-10", Some("500"), Some("500"))
+        .await
+        .expect("could not set geometry");
+    let poop_read_context = AttributeReadContext {
+        prop_id: Some(*poop_prop.id()),
+        component_id: Some(*component.id()),
+        ..AttributeReadContext::default()
+    };
+    let poop_value = AttributeValue::find_for_context(ctx, poop_read_context)
+        .await
+        .expect("could not perform find for context")
+        .expect("attribute value not found");
+    let domain_value = poop_value
+        .parent_attribute_value(ctx)
+        .await
+        .expect("could not perform parent attribute value")
+        .expect("parent attribute value not found");
+    AttributeValue::update_for_context(
+        ctx,
+        *poop_value.id(),
+        Some(*domain_value.id()),
+        AttributeContextBuilder::from(poop_read_context)
+            .to_context()
+            .expect("could not convert builder to context"),
+        Some(serde_json::json!["paddle"]),
+        None,
+    )
+    .await
+    .expect("could not update for context");
+    ctx.blocking_commit()
+        .await
+        .expect("could not commit & run jobs");
+
+    let report = SchemaVariantMigration::upgrade_component(ctx, *component.id(), *v1.id())
+        .await
+        .expect("cannot upgrade component");
+    assert_eq!(ComponentMigrationStatus::Succeeded, report.status);
+    assert_eq!(vec![*func.id()], report.applied_migrations);
+    assert_eq!(vec!["poop".to_string()], report.dropped_keys);
+    ctx.blocking_commit()
+        .await
+        .expect("could not commit & run jobs");
+
+    let upgraded_component_id = report
+        .upgraded_component_id
+        .expect("upgraded component id not set");
+    let view = ComponentView::new(ctx, upgraded_component_id)
+        .await
+        .expect("could not create component view");
+    assert_eq!(
+        Some(&serde_json::json!({ "canoe": "paddle" })),
+        view.properties.get("domain")
+    );
+    assert_eq!(
+        *v1.id(),
+        Component::schema_variant_id(ctx, upgraded_component_id)
+            .await
+            .expect("could not get schema variant id")
+    );
+    let upgraded_node = Component::get_by_id(ctx, &upgraded_component_id)
+        .await
+        .expect("could not get component")
+        .expect("upgraded component not found")
+        .node(ctx)
+        .await
+        .expect("could not get node")
+        .pop()
+        .expect("node not found");
+    assert_eq!(("123", "-10"), (upgraded_node.x(), upgraded_node.y()));
+    assert!(Component::get_by_id(ctx, component.id())
+        .await
+        .expect("could not get component")
+        .is_none());
+}
+
+#[test]
+async fn upgrade_component_rolls_back_failed_migration(ctx: &DalContext) {
+    let schema = create_schema(ctx).await;
+    let (mut v0, _) = create_schema_variant_with_root(ctx, *schema.id()).await;
+    let (mut v1, _) = create_schema_variant_with_root(ctx, *schema.id()).await;
+    v0.finalize(ctx, None)
+        .await
+        .expect("unable to finalize schema variant");
+    v1.finalize(ctx, None)
+        .await
+        .expect("unable to finalize schema variant");
+
+    let mut func = Func::new(
+        ctx,
+        generate_fake_name(),
+        FuncBackendKind::JsAttribute,
+        FuncBackendResponseType::Object,
+    )
+    .await
+    .expect("cannot create func");
+    func.set_code_plaintext(
+        ctx,
+        Some("function migrate(input) { throw new Error(\"no canoe\"); }"),
+    )
+    .await
+    .expect("set code");
+    func.set_handler(ctx, Some("migrate"))
+        .await
+        .expect("set handler");
+    SchemaVariantMigration::new(ctx, *v0.id(), *v1.id(), *func.id())
+        .await
+        .expect("cannot create migration");
+
+    let (component, _) = Component::new(ctx, "paddler", *v0.id())
+        .await
+        .expect("cannot create component");
+    ctx.blocking_commit()
+        .await
+        .expect("could not commit & run jobs");
+
+    let report = SchemaVariantMigration::upgrade_component(ctx, *component.id(), *v1.id())
+        .await
+        .expect("cannot upgrade component");
+    assert_eq!(ComponentMigrationStatus::Failed, report.status);
+    assert!(report.error.is_some());
+    assert!(report.upgraded_component_id.is_none());
+    ctx.blocking_commit()
+        .await
+        .expect("could not commit & run jobs");
+
+    assert!(Component::get_by_id(ctx, component.id())
+        .await
+        .expect("could not get component")
+        .is_some());
+    assert!(Component::list_for_schema_variant(ctx, *v1.id())
+        .await
+        .expect("could not list components")
+        .is_empty());
+}
@@ -93,6 +93,33 @@ async fn list(ctx: &DalContext) {
     );
 }
 
+#[test]
+async fn list_paginated(ctx: &DalContext) {
+    let created = vec![
+        *create_schema(ctx).await.id(),
+        *create_schema(ctx).await.id(),
+        *create_schema(ctx).await.id(),
+    ];
+
+    let all: Vec<Schema> = standard_model::list(ctx, "schemas")
+        .await
+        .expect("could not list schemas");
+
+    let mut paged = Vec::new();
+    let mut pages = Schema::paginate(2);
+    while let Some(page) = pages.next_page(ctx).await.expect("could not fetch page") {
+        assert!(page.len() <= 2, "page is larger than the page size");
+        paged.extend(page.into_iter().map(|schema| *schema.id()));
+    }
+
+    assert_eq!(all.len(), paged.len());
+    assert!(
+        paged.windows(2).all(|ids| ids[0] < ids[1]),
+        "ids are not ordered"
+    );
+    assert!(created.iter().all(|id| paged.contains(id)));
+}
+
 #[test]
 async fn update(ctx: &mut DalContext) {
     let schema = create_schema(ctx).await;
@@ -0,0 +1,137 @@
+use dal::{DalContext, Func, FuncBinding, StandardModel, WorkspaceIntegration, WorkspaceSignup};
+use dal_test::{
+    test,
+    test_harness::{create_schema, create_schema_variant, create_secret},
+};
+
+#[test]
+async fn new(ctx: &DalContext) {
+    let integration = WorkspaceIntegration::new(ctx, "aws-prod", "aws")
+        .await
+        .expect("cannot create workspace integration");
+    assert_eq!(integration.name(), "aws-prod");
+    assert_eq!(integration.provider(), "aws");
+    assert_eq!(integration.default_region(), None);
+    assert_eq!(integration.account_aliases(), &serde_json::json!({}));
+
+    let found = WorkspaceIntegration::find_by_name(ctx, "aws-prod")
+        .await
+        .expect("cannot find workspace integration")
+        .expect("workspace integration not found");
+    assert_eq!(found, integration);
+}
+
+#[test]
+async fn defaults_and_aliases(ctx: &DalContext, nw: &WorkspaceSignup) {
+    let secret = create_secret(ctx, nw.key_pair.pk()).await;
+    let mut integration = WorkspaceIntegration::new(ctx, "aws-dev", "aws")
+        .await
+        .expect("cannot create workspace integration");
+
+    integration
+        .set_default_region(ctx, Some("us-east-2".to_string()))
+        .await
+        .expect("cannot set default region");
+    integration
+        .set_credential_secret_id(ctx, Some(*secret.id()))
+        .await
+        .expect("cannot set credential secret");
+    integration
+        .set_account_alias(ctx, "dev", "123456789012")
+        .await
+        .expect("cannot set account alias");
+
+    let view = integration.view();
+    assert_eq!(view.default_region.as_deref(), Some("us-east-2"));
+    assert_eq!(view.credential_secret_id, Some(*secret.id()));
+    assert_eq!(
+        view.account_aliases,
+        serde_json::json!({ "dev": "123456789012" })
+    );
+
+    integration
+        .remove_account_alias(ctx, "dev")
+        .await
+        .expect("cannot remove account alias");
+    assert_eq!(integration.account_aliases(), &serde_json::json!({}));
+}
+
+#[test]
+async fn usage_tracking(ctx: &DalContext) {
+    let schema = create_schema(ctx).await;
+    let variant = create_schema_variant(ctx, *schema.id()).await;
+    let integration = WorkspaceIntegration::new(ctx, "aws-shared", "aws")
+        .await
+        .expect("cannot create workspace integration");
+
+    assert_eq!(
+        integration
+            .usage_count(ctx)
+            .await
+            .expect("cannot count usage"),
+        0
+    );
+
+    integration
+        .add_schema_variant(ctx, variant.id())
+        .await
+        .expect("cannot associate schema variant");
+    assert_eq!(
+        integration
+            .usage_count(ctx)
+            .await
+            .expect("cannot count usage"),
+        1
+    );
+
+    let views = WorkspaceIntegration::views_for_schema_variant(ctx, *variant.id())
+        .await
+        .expect("cannot find integrations for schema variant");
+    assert_eq!(views, vec![integration.view()]);
+
+    integration
+        .remove(ctx)
+        .await
+        .expect("cannot remove workspace integration");
+    assert!(WorkspaceIntegration::find_by_name(ctx, "aws-shared")
+        .await
+        .expect("cannot find workspace integration")
+        .is_none());
+}
+
+#[test]
+async fn intrinsic_func(ctx: &DalContext) {
+    let mut integration = WorkspaceIntegration::new(ctx, "aws-intrinsic", "aws")
+        .await
+        .expect("cannot create workspace integration");
+    integration
+        .set_default_region(ctx, Some("us-west-1".to_string()))
+        .await
+        .expect("cannot set default region");
+
+    let func = Func::find_by_attr(ctx, "name", &"si:workspaceIntegration")
+        .await
+        .expect("cannot find func")
+        .pop()
+        .expect("intrinsic func not found");
+
+    let (_, return_value) = FuncBinding::create_and_execute(
+        ctx,
+        serde_json::json!({ "name": "aws-intrinsic" }),
+        *func.id(),
+    )
+    .await
+    .expect("cannot execute intrinsic func");
+    assert_eq!(
+        return_value.unprocessed_value(),
+        Some(&serde_json::to_value(integration.view()).expect("cannot serialize view"))
+    );
+
+    assert!(FuncBinding::create_and_execute(
+        ctx,
+        serde_json::json!({ "name": "aws-missing" }),
+        *func.id(),
+    )
+    .await
+    .is_err());
+}
@@ -108,6 +108,14 @@ async fn execute(
     while let Some(message) = progress.try_next().await? {
         match message {
             ProgressMessage::Heartbeat => info!("heartbeat"),
+            ProgressMessage::Progress(progress) => {
+                info!(
+                    execution_id = &progress.execution_id.as_str(),
+                    current = progress.current,
+                    total = progress.total,
+                    step = progress.step.as_deref(),
+                );
+            }
             ProgressMessage::OutputStream(output) => {
                 info!(
                     execution_id = &output.execution_id.as_str(),
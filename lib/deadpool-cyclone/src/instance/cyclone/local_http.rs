@@ -11,10 +11,10 @@ use cyclone_client::{
 };
 use cyclone_core::{
     process::{self, ShutdownError},
-    ActionRunRequest, ActionRunResultSuccess, CanonicalCommand, ReconciliationRequest,
-    ReconciliationResultSuccess, ResolverFunctionRequest, ResolverFunctionResultSuccess,
-    SchemaVariantDefinitionRequest, SchemaVariantDefinitionResultSuccess, ValidationRequest,
-    ValidationResultSuccess,
+    ActionRunRequest, ActionRunResultSuccess, CanonicalCommand, ExecutionLimits, LangServers,
+    ReconciliationRequest, ReconciliationResultSuccess, ResolverFunctionRequest,
+    ResolverFunctionResultSuccess, SandboxProfiles, SchemaVariantDefinitionRequest,
+    SchemaVariantDefinitionResultSuccess, ValidationRequest, ValidationResultSuccess,
 };
 use derive_builder::Builder;
 use futures::StreamExt;
@@ -112,6 +112,10 @@ impl CycloneClient<TcpStream> for LocalHttpInstance {
         self.client.watch().await
     }
 
+    fn set_sandbox_profile(&mut self, sandbox_profile: Option<String>) {
+        self.client.set_sandbox_profile(sandbox_profile);
+    }
+
     async fn liveness(&mut self) -> result::Result<LivenessStatus, ClientError> {
         self.ensure_healthy_client()
             .await
@@ -289,6 +293,19 @@ pub struct LocalHttpInstanceSpec {
     /// Enables the `action` execution endpoint for a spawned Cyclone server.
     #[builder(private, setter(name = "_action"), default = "false")]
     action: bool,
+
+    /// Language servers a spawned Cyclone server runs functions of other languages than
+    /// JavaScript with.
+    #[builder(default)]
+    lang_servers: LangServers,
+
+    /// Resource limits a spawned Cyclone server enforces on each function execution.
+    #[builder(default)]
+    execution_limits: ExecutionLimits,
+
+    /// Sandbox profiles that requests to a spawned Cyclone server can select.
+    #[builder(default)]
+    sandbox_profiles: SandboxProfiles,
 }
 
 #[async_trait]
@@ -370,6 +387,38 @@ impl LocalHttpInstanceSpec {
         if self.action {
             cmd.arg("--enable-action-run");
         }
+        if let Some(max_cpu_seconds) = self.execution_limits.max_cpu_seconds {
+            cmd.arg("--max-cpu-seconds")
+                .arg(max_cpu_seconds.to_string());
+        }
+        if let Some(max_memory_bytes) = self.execution_limits.max_memory_bytes {
+            cmd.arg("--max-memory-bytes")
+                .arg(max_memory_bytes.to_string());
+        }
+        if let Some(max_output_bytes) = self.execution_limits.max_output_bytes {
+            cmd.arg("--max-output-bytes")
+                .arg(max_output_bytes.to_string());
+        }
+        if let Some(timeout_ms) = self.execution_limits.timeout_ms {
+            cmd.arg("--execution-timeout-ms")
+                .arg(timeout_ms.to_string());
+        }
+        if !self.lang_servers.is_empty() {
+            match serde_json::to_string(&self.lang_servers) {
+                Ok(lang_servers) => {
+                    cmd.arg("--lang-servers").arg(lang_servers);
+                }
+                Err(err) => warn!(error = ?err, "failed to serialize lang servers"),
+            }
+        }
+        if !self.sandbox_profiles.is_empty() {
+            match serde_json::to_string(&self.sandbox_profiles) {
+                Ok(sandbox_profiles) => {
+                    cmd.arg("--sandbox-profiles").arg(sandbox_profiles);
+                }
+                Err(err) => warn!(error = ?err, "failed to serialize sandbox profiles"),
+            }
+        }
 
         cmd
     }
@@ -0,0 +1,207 @@
+use std::{fmt, result};
+
+use async_trait::async_trait;
+use cyclone_client::{
+    Client, ClientError, CycloneClient, Execution, LivenessStatus, PingExecution, ReadinessStatus,
+    RemoteClient, RemoteStream, Watch,
+};
+use cyclone_core::{
+    ActionRunRequest, ActionRunResultSuccess, ReconciliationRequest, ReconciliationResultSuccess,
+    ResolverFunctionRequest, ResolverFunctionResultSuccess, SchemaVariantDefinitionRequest,
+    SchemaVariantDefinitionResultSuccess, ValidationRequest, ValidationResultSuccess,
+};
+use derive_builder::Builder;
+use thiserror::Error;
+use tracing::debug;
+
+use crate::instance::{Instance, Spec, SpecBuilder};
+
+/// Error type for [`RemoteHttpInstance`].
+#[remain::sorted]
+#[derive(Debug, Error)]
+pub enum RemoteHttpInstanceError {
+    /// Spec builder error.
+    #[error(transparent)]
+    Builder(#[from] RemoteHttpInstanceSpecBuilderError),
+    /// Cyclone client error.
+    #[error(transparent)]
+    Client(#[from] ClientError),
+}
+
+type Result<T> = result::Result<T, RemoteHttpInstanceError>;
+
+/// A remote Cyclone [`Instance`], running as a separately managed server which is reached over
+/// HTTP (optionally secured with TLS).
+///
+/// Unlike the local instances, the lifecycle of the server is not managed here: spawning an
+/// instance connects to the server and terminating it only drops the connection.
+#[derive(Debug)]
+pub struct RemoteHttpInstance {
+    client: RemoteClient,
+}
+
+#[async_trait]
+impl Instance for RemoteHttpInstance {
+    type SpecBuilder = RemoteHttpInstanceSpecBuilder;
+    type Error = RemoteHttpInstanceError;
+
+    async fn terminate(mut self) -> result::Result<(), Self::Error> {
+        Ok(())
+    }
+
+    async fn ensure_healthy(&mut self) -> result::Result<(), Self::Error> {
+        match self.client.readiness().await? {
+            ReadinessStatus::Ready => {}
+        }
+
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl CycloneClient<RemoteStream> for RemoteHttpInstance {
+    async fn watch(&mut self) -> result::Result<Watch<RemoteStream>, ClientError> {
+        self.client.watch().await
+    }
+
+    fn set_sandbox_profile(&mut self, sandbox_profile: Option<String>) {
+        self.client.set_sandbox_profile(sandbox_profile);
+    }
+
+    async fn liveness(&mut self) -> result::Result<LivenessStatus, ClientError> {
+        self.client.liveness().await
+    }
+
+    async fn readiness(&mut self) -> result::Result<ReadinessStatus, ClientError> {
+        self.client.readiness().await
+    }
+
+    async fn execute_ping(&mut self) -> result::Result<PingExecution<RemoteStream>, ClientError> {
+        self.client.execute_ping().await
+    }
+
+    async fn execute_resolver(
+        &mut self,
+        request: ResolverFunctionRequest,
+    ) -> result::Result<
+        Execution<RemoteStream, ResolverFunctionRequest, ResolverFunctionResultSuccess>,
+        ClientError,
+    > {
+        self.client.execute_resolver(request).await
+    }
+
+    async fn execute_validation(
+        &mut self,
+        request: ValidationRequest,
+    ) -> result::Result<
+        Execution<RemoteStream, ValidationRequest, ValidationResultSuccess>,
+        ClientError,
+    > {
+        self.client.execute_validation(request).await
+    }
+
+    async fn execute_action_run(
+        &mut self,
+        request: ActionRunRequest,
+    ) -> result::Result<
+        Execution<RemoteStream, ActionRunRequest, ActionRunResultSuccess>,
+        ClientError,
+    > {
+        self.client.execute_action_run(request).await
+    }
+
+    async fn execute_reconciliation(
+        &mut self,
+        request: ReconciliationRequest,
+    ) -> result::Result<
+        Execution<RemoteStream, ReconciliationRequest, ReconciliationResultSuccess>,
+        ClientError,
+    > {
+        self.client.execute_reconciliation(request).await
+    }
+
+    async fn execute_schema_variant_definition(
+        &mut self,
+        request: SchemaVariantDefinitionRequest,
+    ) -> result::Result<
+        Execution<
+            RemoteStream,
+            SchemaVariantDefinitionRequest,
+            SchemaVariantDefinitionResultSuccess,
+        >,
+        ClientError,
+    > {
+        self.client.execute_schema_variant_definition(request).await
+    }
+}
+
+/// The [`Spec`] for [`RemoteHttpInstance`]
+#[derive(Builder, Clone, Eq, PartialEq)]
+pub struct RemoteHttpInstanceSpec {
+    /// Host name or IP address of the remote Cyclone server.
+    #[builder(setter(into))]
+    host: String,
+
+    /// Port of the remote Cyclone server.
+    #[builder(default = "5157")]
+    port: u16,
+
+    /// Connects to the remote Cyclone server over TLS.
+    #[builder(default)]
+    tls: bool,
+
+    /// Bearer token presented to the remote Cyclone server on every request.
+    #[builder(setter(into, strip_option), default)]
+    bearer_token: Option<String>,
+}
+
+impl fmt::Debug for RemoteHttpInstanceSpec {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RemoteHttpInstanceSpec")
+            .field("host", &self.host)
+            .field("port", &self.port)
+            .field("tls", &self.tls)
+            .field(
+                "bearer_token",
+                &self.bearer_token.as_ref().map(|_| "<redacted>"),
+            )
+            .finish()
+    }
+}
+
+#[async_trait]
+impl Spec for RemoteHttpInstanceSpec {
+    type Instance = RemoteHttpInstance;
+    type Error = RemoteHttpInstanceError;
+
+    async fn spawn(&self) -> result::Result<Self::Instance, Self::Error> {
+        let mut instance = Self::Instance {
+            client: self.client()?,
+        };
+
+        debug!(host = %self.host, port = self.port, "connecting to remote cyclone server");
+        instance.ensure_healthy().await?;
+
+        Ok(instance)
+    }
+}
+
+impl RemoteHttpInstanceSpec {
+    fn client(&self) -> Result<RemoteClient> {
+        let client = Client::remote(&self.host, self.port, self.tls)?;
+
+        match &self.bearer_token {
+            Some(bearer_token) => client.with_bearer_token(bearer_token).map_err(Into::into),
+            None => Ok(client),
+        }
+    }
+}
+
+impl SpecBuilder for RemoteHttpInstanceSpecBuilder {
+    type Spec = RemoteHttpInstanceSpec;
+    type Error = RemoteHttpInstanceError;
+
+    fn build(&self) -> result::Result<Self::Spec, Self::Error> {
+        self.build().map_err(Into::into)
+    }
+}
@@ -19,21 +19,28 @@ use async_trait::async_trait;
 use deadpool::managed;
 use thiserror::Error;
 
-pub use self::instance::{Instance, Spec};
+pub use self::{
+    instance::{Instance, Spec},
+    warm_pool::{WarmPool, WarmPoolConfig},
+};
 
 pub use cyclone_client::{
-    ClientError, CycloneClient, EncryptionKey, EncryptionKeyError, ExecutionError,
+    ClientError, Connection, CycloneClient, EncryptionKey, EncryptionKeyError, Execution,
+    ExecutionError, RemoteStream, UnixStream,
 };
 pub use cyclone_core::{
-    ActionRunRequest, ActionRunResultSuccess, ComponentView, FunctionResult, FunctionResultFailure,
-    FunctionResultFailureError, OutputStream, ProgressMessage, ReconciliationRequest,
+    ActionRunRequest, ActionRunResultSuccess, BeforeFunction, ComponentView, DecryptionKey,
+    DecryptionKeyError, ExecutionLimitExceeded, ExecutionLimits, FunctionErrorKind,
+    FunctionLanguage, FunctionProgress, FunctionResult, FunctionResultFailure,
+    FunctionResultFailureError, LangServers, OutputStream, ProgressMessage, ReconciliationRequest,
     ReconciliationResultSuccess, ResolverFunctionRequest, ResolverFunctionResultSuccess,
-    ResourceStatus, SchemaVariantDefinitionRequest, SchemaVariantDefinitionResultSuccess,
-    ValidationRequest, ValidationResultSuccess,
+    ResourceStatus, SandboxProfile, SandboxProfiles, SchemaVariantDefinitionRequest,
+    SchemaVariantDefinitionResultSuccess, ValidationRequest, ValidationResultSuccess,
 };
 
 /// [`Instance`] implementations.
 pub mod instance;
+mod warm_pool;
 
 /// Type alias for using [`managed::Pool`] with Cyclone.
 pub type Pool<S> = managed::Pool<Manager<S>>;
@@ -114,10 +121,20 @@ mod tests {
             .expect("failed to determine test configuration");
 
         let spec = LocalUdsInstance::spec()
-            .try_cyclone_cmd_path(config_file.cyclone.cyclone_cmd_path())
+            .try_cyclone_cmd_path(
+                config_file
+                    .cyclone
+                    .cyclone_cmd_path()
+                    .expect("local config has a cyclone program"),
+            )
             .expect("failed to find cyclone program")
             .cyclone_decryption_key_path(config_file.cyclone.cyclone_decryption_key_path())
-            .try_lang_server_cmd_path(config_file.cyclone.lang_server_cmd_path())
+            .try_lang_server_cmd_path(
+                config_file
+                    .cyclone
+                    .lang_server_cmd_path()
+                    .expect("local config has a lang server program"),
+            )
             .expect("failed to find lang server program")
             .limit_requests(2)
             .ping()
@@ -0,0 +1,202 @@
+use std::{
+    cmp,
+    future::Future,
+    time::{Duration, Instant},
+};
+
+use deadpool::managed::{self, HookError, HookErrorCause};
+use futures::future;
+use serde::{Deserialize, Serialize};
+use telemetry::prelude::*;
+use tokio::time;
+
+use crate::{Manager, Pool, PoolBuilder};
+
+const DEFAULT_CHECK_INTERVAL_MS: u64 = 1000;
+
+/// Settings for keeping spawned [`Instance`](crate::Instance)s ready in a [`Pool`], ahead of the
+/// requests that will use them.
+#[derive(Clone, Copy, Debug, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct WarmPoolConfig {
+    /// How many idle instances the pool keeps ready.
+    pub min_idle: usize,
+    /// How many instances are spawned when the pool starts.
+    pub prewarm: usize,
+    /// How long an instance lives, in seconds, before it is replaced. Instances live until they
+    /// are unhealthy if unset.
+    pub max_lifetime_secs: Option<u64>,
+    /// How large the pool may grow while requests are queued waiting for an instance. The pool
+    /// keeps its original size if unset.
+    pub max_scaled_size: Option<usize>,
+    /// How often the pool is checked, in milliseconds.
+    pub check_interval_ms: u64,
+}
+
+impl Default for WarmPoolConfig {
+    fn default() -> Self {
+        Self {
+            min_idle: 0,
+            prewarm: 0,
+            max_lifetime_secs: None,
+            max_scaled_size: None,
+            check_interval_ms: DEFAULT_CHECK_INTERVAL_MS,
+        }
+    }
+}
+
+impl WarmPoolConfig {
+    fn max_lifetime(&self) -> Option<Duration> {
+        self.max_lifetime_secs.map(Duration::from_secs)
+    }
+}
+
+/// Keeps a [`Pool`] warm: prewarms it when started, tops up its idle instances, replaces
+/// instances that outlived their max lifetime and scales the pool while requests are queued.
+///
+/// The pool's state is reported through telemetry on every check.
+pub struct WarmPool<S> {
+    pool: Pool<S>,
+    config: WarmPoolConfig,
+    base_size: usize,
+    gauges: PoolGauges,
+}
+
+impl<S> WarmPool<S>
+where
+    Manager<S>: managed::Manager,
+{
+    /// Applies the parts of the config that are enforced by the pool itself to a pool being
+    /// built, namely the max lifetime of its instances.
+    pub fn configure(builder: PoolBuilder<S>, config: &WarmPoolConfig) -> PoolBuilder<S> {
+        match config.max_lifetime() {
+            Some(max_lifetime) => builder.pre_recycle(managed::Hook::sync_fn(move |_, metrics| {
+                if metrics.age() > max_lifetime {
+                    Err(HookError::Continue(Some(HookErrorCause::StaticMessage(
+                        "instance exceeded its max lifetime",
+                    ))))
+                } else {
+                    Ok(())
+                }
+            })),
+            None => builder,
+        }
+    }
+
+    /// Creates a warm pool manager for the given pool. The pool's current max size is the size
+    /// it scales back down to.
+    pub fn new(pool: Pool<S>, config: WarmPoolConfig) -> Self {
+        let base_size = pool.status().max_size;
+
+        Self {
+            pool,
+            config,
+            base_size,
+            gauges: PoolGauges::default(),
+        }
+    }
+
+    /// Prewarms the pool and then keeps it warm until `shutdown` completes.
+    pub async fn run(mut self, shutdown: impl Future<Output = ()>) {
+        tokio::pin!(shutdown);
+
+        if self.config.prewarm > 0 {
+            debug!(instances = self.config.prewarm, "prewarming cyclone pool");
+            self.warm(self.config.prewarm).await;
+        }
+
+        let mut interval = time::interval(Duration::from_millis(self.config.check_interval_ms));
+        loop {
+            tokio::select! {
+                _ = &mut shutdown => {
+                    trace!("warm pool task received shutdown");
+                    break;
+                }
+                _ = interval.tick() => self.check().await,
+            }
+        }
+    }
+
+    async fn check(&mut self) {
+        if let Some(max_lifetime) = self.config.max_lifetime() {
+            self.pool.retain(|_, metrics| metrics.age() <= max_lifetime);
+        }
+
+        let status = self.pool.status();
+        let idle = usize::try_from(status.available).unwrap_or_default();
+        let waiting = usize::try_from(-status.available).unwrap_or_default();
+
+        if waiting > 0 {
+            self.scale_up(status.max_size, waiting);
+        } else if status.max_size > self.base_size && idle > self.config.min_idle {
+            debug!(size = self.base_size, "scaling cyclone pool back down");
+            self.pool.resize(self.base_size);
+        }
+
+        let in_use = status.size.saturating_sub(idle);
+        let wanted = cmp::min(self.config.min_idle, status.max_size.saturating_sub(in_use));
+        if wanted > idle {
+            // Checking out the idle instances alongside the missing ones makes the pool spawn
+            // the missing ones, and all of them are idle again once they're dropped
+            self.warm(wanted).await;
+        }
+
+        self.gauges.record(&self.pool.status());
+    }
+
+    fn scale_up(&self, max_size: usize, waiting: usize) {
+        if let Some(max_scaled_size) = self.config.max_scaled_size {
+            let size = cmp::min(max_size + waiting, max_scaled_size);
+            if size > max_size {
+                debug!(size, waiting, "scaling cyclone pool up for queued requests");
+                self.pool.resize(size);
+            }
+        }
+    }
+
+    async fn warm(&self, count: usize) {
+        let started_at = Instant::now();
+        let results = future::join_all((0..count).map(|_| self.pool.get())).await;
+        let failed = results.iter().filter(|result| result.is_err()).count();
+        if failed > 0 {
+            warn!(failed, count, "failed to warm cyclone instances");
+        }
+        metric!(
+            histogram.cyclone.pool.warm_duration_ms = started_at.elapsed().as_millis() as u64,
+            cyclone.pool.instances = count as u64
+        );
+    }
+}
+
+/// Reports the state of a pool as gauges, which are recorded as up/down counters moved by how
+/// much each value changed since it was last reported.
+#[derive(Debug, Default)]
+struct PoolGauges {
+    max_size: i64,
+    size: i64,
+    idle: i64,
+    waiting: i64,
+}
+
+impl PoolGauges {
+    fn record(&mut self, status: &managed::Status) {
+        let max_size = status.max_size as i64;
+        let size = status.size as i64;
+        let idle = cmp::max(status.available as i64, 0);
+        let waiting = cmp::max(-(status.available as i64), 0);
+
+        metric!(
+            counter.cyclone.pool.max_size = max_size - self.max_size,
+            counter.cyclone.pool.size = size - self.size,
+            counter.cyclone.pool.idle = idle - self.idle,
+            counter.cyclone.pool.waiting = waiting - self.waiting
+        );
+
+        *self = Self {
+            max_size,
+            size,
+            idle,
+            waiting,
+        };
+    }
+}
@@ -1,11 +1,14 @@
 //! This module contains [`SubscriptionBuilder`], which is used for creating
 //! [`Subscriptions`](crate::Subscription).
 
-use std::marker::PhantomData;
+use std::{collections::HashSet, marker::PhantomData, sync::Arc};
 
+use futures::stream::BoxStream;
 use si_data_nats::NatsClient;
 
-use crate::{SubscriberError, SubscriberResult, Subscription};
+use crate::{
+    MessageSource, PayloadDecoder, RawMessage, SubscriberError, SubscriberResult, Subscription,
+};
 
 /// The [`builder`](Self) used for creating a [`Subscription`].
 pub struct SubscriptionBuilder<T> {
@@ -24,6 +27,15 @@ pub struct SubscriptionBuilder<T> {
     /// [`Request`](crate::Request).
     /// Otherwise, it will not perform the check.
     pub check_for_reply_mailbox: bool,
+    /// If provided, the [`Subscription`] will decode every payload with it before deserializing.
+    pub payload_decoder: Option<Arc<dyn PayloadDecoder>>,
+    /// If set, the [`Subscription`] will skip messages whose
+    /// [message id](crate::MESSAGE_ID_HEADER_KEY) it has already yielded. Every id seen is kept
+    /// for the lifetime of the subscription, so this suits short-lived subscriptions.
+    pub deduplicate_messages: bool,
+    /// If set, every [`Request`](crate::Request) carries a copy of the message as it was
+    /// received, as does the error for a message that could not be decoded.
+    pub keep_raw_messages: bool,
 }
 
 impl<T> SubscriptionBuilder<T> {
@@ -35,6 +47,9 @@ impl<T> SubscriptionBuilder<T> {
             queue_name: None,
             final_message_header_key: None,
             check_for_reply_mailbox: false,
+            payload_decoder: None,
+            deduplicate_messages: false,
+            keep_raw_messages: false,
         }
     }
 
@@ -55,13 +70,27 @@ impl<T> SubscriptionBuilder<T> {
                 .map_err(SubscriberError::NatsSubscribe)?
         };
 
-        Ok(Subscription {
+        Ok(self.start_on(MessageSource::Nats(inner)))
+    }
+
+    /// Start a new [`Subscription`] that receives its messages from the given stream rather than
+    /// from NATS, for example from an in-process transport. The "queue_name" field is ignored, as
+    /// the stream decides who receives which messages. This will consume [`Self`].
+    pub fn start_on_stream(self, messages: BoxStream<'static, RawMessage>) -> Subscription<T> {
+        self.start_on(MessageSource::Stream(messages))
+    }
+
+    fn start_on(self, inner: MessageSource) -> Subscription<T> {
+        Subscription {
             inner,
             _phantom: PhantomData::<T>,
             subject: self.subject,
             final_message_header_key: self.final_message_header_key,
             check_for_reply_mailbox: self.check_for_reply_mailbox,
-        })
+            payload_decoder: self.payload_decoder,
+            seen_message_ids: self.deduplicate_messages.then(HashSet::new),
+            keep_raw_messages: self.keep_raw_messages,
+        }
     }
 
     /// Sets the "queue_name" field.
@@ -81,4 +110,22 @@ impl<T> SubscriptionBuilder<T> {
         self.check_for_reply_mailbox = true;
         self
     }
+
+    /// Sets the "payload_decoder" field.
+    pub fn payload_decoder(mut self, payload_decoder: Arc<dyn PayloadDecoder>) -> Self {
+        self.payload_decoder = Some(payload_decoder);
+        self
+    }
+
+    /// Sets the "deduplicate_messages" field.
+    pub fn deduplicate_messages(mut self) -> Self {
+        self.deduplicate_messages = true;
+        self
+    }
+
+    /// Sets the "keep_raw_messages" field.
+    pub fn keep_raw_messages(mut self) -> Self {
+        self.keep_raw_messages = true;
+        self
+    }
 }
@@ -0,0 +1,48 @@
+//! This module contains helpers for compressing message payloads and marking them as compressed,
+//! which [`Subscriptions`](crate::Subscription) undo transparently.
+
+use std::io::{self, Read, Write};
+
+use flate2::{read::GzDecoder, write::GzEncoder, Compression};
+use si_data_nats::HeaderMap;
+
+/// The header naming the encoding of a compressed payload.
+pub const CONTENT_ENCODING_HEADER_KEY: &str = "Content-Encoding";
+/// The only encoding used for compressed payloads.
+pub const GZIP_ENCODING: &str = "gzip";
+
+/// Compresses a payload, to be published with a [`CONTENT_ENCODING_HEADER_KEY`] header set to
+/// [`GZIP_ENCODING`].
+///
+/// # Errors
+///
+/// Returns an [`io::Error`] if the payload could not be compressed.
+pub fn compress(payload: &[u8]) -> io::Result<Vec<u8>> {
+    let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+    encoder.write_all(payload)?;
+    encoder.finish()
+}
+
+/// Returns `true` if the headers mark a payload as compressed.
+pub fn is_compressed(headers: Option<&HeaderMap>) -> bool {
+    headers
+        .and_then(|headers| headers.get(CONTENT_ENCODING_HEADER_KEY))
+        .map_or(false, |values| {
+            values.iter().any(|value| value == GZIP_ENCODING)
+        })
+}
+
+/// Decompresses a payload if its headers mark it as compressed, returning it unchanged otherwise.
+///
+/// # Errors
+///
+/// Returns an [`io::Error`] if the payload is marked as compressed but could not be decompressed.
+pub fn decompress(headers: Option<&HeaderMap>, payload: Vec<u8>) -> io::Result<Vec<u8>> {
+    if !is_compressed(headers) {
+        return Ok(payload);
+    }
+
+    let mut decompressed = Vec::new();
+    GzDecoder::new(payload.as_slice()).read_to_end(&mut decompressed)?;
+    Ok(decompressed)
+}
@@ -4,18 +4,22 @@
 #![warn(missing_docs, clippy::missing_errors_doc, clippy::missing_panics_doc)]
 
 pub mod builder;
+pub mod compression;
 
 use std::{
+    collections::HashSet,
+    fmt,
     marker::PhantomData,
     pin::Pin,
+    sync::Arc,
     task::{Context, Poll},
 };
 
-use futures::{Stream, StreamExt};
+use futures::{stream::BoxStream, Stream, StreamExt};
 use futures_lite::future::FutureExt;
 use pin_project_lite::pin_project;
 use serde::de::DeserializeOwned;
-use si_data_nats::NatsError;
+use si_data_nats::{HeaderMap, NatsError};
 use telemetry::prelude::*;
 use thiserror::Error;
 
@@ -25,6 +29,8 @@ pub use crate::builder::SubscriptionBuilder;
 #[remain::sorted]
 #[derive(Error, Debug)]
 pub enum SubscriberError {
+    #[error("failed to decompress message payload")]
+    Decompress(#[source] std::io::Error),
     #[error("failed to deserialize json message")]
     JSONDeserialize(#[source] serde_json::Error),
     #[error("failed to drain from nats subscription")]
@@ -37,12 +43,99 @@ pub enum SubscriberError {
     NatsUnsubscribe(#[source] NatsError),
     #[error("no return mailbox specified; bug! message data: {0:?}")]
     NoReplyMailbox(Vec<u8>),
+    #[error("failed to decode message payload")]
+    PayloadDecode(#[source] Box<dyn std::error::Error + Send + Sync + 'static>),
+    #[error("failed to decode message received on {}", .message.subject)]
+    Undecodable {
+        message: Box<RawMessage>,
+        #[source]
+        source: Box<SubscriberError>,
+    },
     #[error("the nats subscription closed before seeing a final message (expected key: {0})")]
     UnexpectedNatsSubscriptionClosed(String),
 }
 
 type SubscriberResult<T> = Result<T, SubscriberError>;
 
+/// The header carrying a unique id for a message, so that subscribers can recognize a message
+/// they have already seen when it is delivered more than once.
+pub const MESSAGE_ID_HEADER_KEY: &str = "Nats-Msg-Id";
+
+/// Transforms the raw payload of each message before it is deserialized, for example to decrypt
+/// it. Decoders are given the message's headers, so they can tell how each payload was encoded.
+pub trait PayloadDecoder: fmt::Debug + Send + Sync {
+    /// Returns the payload to deserialize in place of the raw `payload`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the payload can't be decoded, which the [`Subscription`] yields as a
+    /// [`SubscriberError::PayloadDecode`].
+    fn decode(
+        &self,
+        headers: Option<&HeaderMap>,
+        payload: Vec<u8>,
+    ) -> Result<Vec<u8>, Box<dyn std::error::Error + Send + Sync + 'static>>;
+}
+
+/// A message as it was received, before its payload was decoded.
+#[derive(Clone, Debug)]
+pub struct RawMessage {
+    /// The subject the message was received on.
+    pub subject: String,
+    /// The raw message payload.
+    pub payload: Vec<u8>,
+    /// An optional reply mailbox.
+    pub reply_mailbox: Option<String>,
+    /// The headers of the message, if it had any.
+    pub headers: Option<HeaderMap>,
+}
+
+impl From<si_data_nats::Message> for RawMessage {
+    fn from(message: si_data_nats::Message) -> Self {
+        let subject = message.subject().to_string();
+        let headers = message.headers().cloned();
+        let (payload, reply_mailbox) = message.into_parts();
+        Self {
+            subject,
+            payload,
+            reply_mailbox,
+            headers,
+        }
+    }
+}
+
+/// Where a [`Subscription`] receives its messages from.
+pub(crate) enum MessageSource {
+    /// A subscription to [NATS](https://nats.io).
+    Nats(si_data_nats::Subscription),
+    /// Messages delivered some other way, such as by an in-process transport.
+    Stream(BoxStream<'static, RawMessage>),
+}
+
+impl MessageSource {
+    fn poll_next_message(
+        &mut self,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<Result<RawMessage, NatsError>>> {
+        match self {
+            Self::Nats(subscription) => subscription
+                .next()
+                .poll(cx)
+                .map(|message| message.map(|message| message.map(RawMessage::from))),
+            Self::Stream(messages) => messages.next().poll(cx).map(|message| message.map(Ok)),
+        }
+    }
+}
+
+impl fmt::Debug for MessageSource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Nats(subscription) => f.debug_tuple("Nats").field(subscription).finish(),
+            Self::Stream(_) => f.debug_tuple("Stream").finish(),
+        }
+    }
+}
+
 /// Contains the Rust type expected in the subscription stream.
 #[derive(Debug)]
 pub struct Request<T> {
@@ -50,6 +143,12 @@ pub struct Request<T> {
     pub payload: T,
     /// An optional reply mailbox.
     pub reply_mailbox: Option<String>,
+    /// The size of the raw message payload, in bytes.
+    pub payload_size: usize,
+    /// The headers of the message, if it had any.
+    pub headers: Option<HeaderMap>,
+    /// The message as it was received, if the subscription was told to keep raw messages.
+    pub raw_message: Option<RawMessage>,
 }
 
 impl<T> Request<T> {
@@ -63,12 +162,14 @@ pin_project! {
     /// A subscription corresponding to a [NATS](https://nats.io) subject.
     #[derive(Debug)]
     pub struct Subscription<T> {
-        #[pin]
-        inner: si_data_nats::Subscription,
+        inner: MessageSource,
         _phantom: PhantomData<T>,
         subject: String,
         final_message_header_key: Option<String>,
         check_for_reply_mailbox: bool,
+        payload_decoder: Option<Arc<dyn PayloadDecoder>>,
+        seen_message_ids: Option<HashSet<String>>,
+        keep_raw_messages: bool,
     }
 }
 
@@ -85,7 +186,13 @@ impl<T> Subscription<T> {
     /// Returns [`SubscriberError`] if a [`Subscription`] could not be created.
     #[allow(dead_code)]
     pub async fn drain(&self) -> SubscriberResult<()> {
-        self.inner.drain().await.map_err(SubscriberError::NatsDrain)
+        match &self.inner {
+            MessageSource::Nats(subscription) => subscription
+                .drain()
+                .await
+                .map_err(SubscriberError::NatsDrain),
+            MessageSource::Stream(_) => Ok(()),
+        }
     }
 
     /// Unsubscribe from [NATS](https://nats.io).
@@ -94,10 +201,14 @@ impl<T> Subscription<T> {
     ///
     /// Returns [`SubscriberError`] if the [`Subscription`] does not successfully unsubscribe.
     pub async fn unsubscribe(self) -> SubscriberResult<()> {
-        self.inner
-            .unsubscribe()
-            .await
-            .map_err(SubscriberError::NatsUnsubscribe)
+        match self.inner {
+            MessageSource::Nats(subscription) => subscription
+                .unsubscribe()
+                .await
+                .map_err(SubscriberError::NatsUnsubscribe),
+            // Dropping the stream is all it takes to stop receiving its messages
+            MessageSource::Stream(_) => Ok(()),
+        }
     }
 
     /// Returns the NATS subject to which this subscription is subscribed.
@@ -113,19 +224,19 @@ where
     type Item = SubscriberResult<Request<T>>;
 
     fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
-        let mut this = self.project();
+        let this = self.project();
 
-        match this.inner.next().poll(cx) {
+        match this.inner.poll_next_message(cx) {
             // Convert this NATS message into the request type `T` and return any errors
             // for the caller to decide how to proceed (i.e. does the caller fail on first error,
             // ignore error items, etc.)
-            Poll::Ready(Some(Ok(nats_msg))) => {
+            Poll::Ready(Some(Ok(message))) => {
                 // Only check if the message has a final message header if our subscription config
                 // specified one (or used the default).
                 if let Some(final_message_header_key) = this.final_message_header_key {
                     // If the NATS message has a final message header, then treat this as an
                     // end-of-stream marker and close our stream.
-                    if let Some(headers) = nats_msg.headers() {
+                    if let Some(headers) = &message.headers {
                         if headers.keys().any(|key| key == final_message_header_key) {
                             trace!(
                                 "{} header detected in NATS message, closing stream",
@@ -136,8 +247,30 @@ where
                     }
                 }
 
-                let (data, reply) = nats_msg.into_parts();
-                let reply_mailbox = reply;
+                // Skip any message whose id was already seen, if our subscription was told to
+                // deduplicate messages, and ask to be polled again for the next one.
+                if let Some(seen_message_ids) = this.seen_message_ids {
+                    let message_id = message
+                        .headers
+                        .as_ref()
+                        .and_then(|headers| headers.get(MESSAGE_ID_HEADER_KEY))
+                        .and_then(|values| values.iter().next().cloned());
+                    if let Some(message_id) = message_id {
+                        if !seen_message_ids.insert(message_id) {
+                            trace!("skipping duplicate NATS message");
+                            cx.waker().wake_by_ref();
+                            return Poll::Pending;
+                        }
+                    }
+                }
+
+                let raw_message = this.keep_raw_messages.then(|| message.clone());
+                let RawMessage {
+                    payload: data,
+                    reply_mailbox,
+                    headers,
+                    ..
+                } = message;
 
                 // Always provide the reply_mailbox if there is one, but only make it an error if
                 // we were told to explicitly check for one.
@@ -145,12 +278,38 @@ where
                     return Poll::Ready(Some(Err(SubscriberError::NoReplyMailbox(data))));
                 }
 
+                let payload_size = data.len();
+                let data = match this.payload_decoder {
+                    Some(payload_decoder) => match payload_decoder.decode(headers.as_ref(), data) {
+                        Ok(data) => data,
+                        Err(err) => {
+                            return Poll::Ready(Some(Err(undecodable(
+                                raw_message,
+                                SubscriberError::PayloadDecode(err),
+                            ))));
+                        }
+                    },
+                    None => data,
+                };
+                let data = match compression::decompress(headers.as_ref(), data) {
+                    Ok(data) => data,
+                    Err(err) => {
+                        return Poll::Ready(Some(Err(undecodable(
+                            raw_message,
+                            SubscriberError::Decompress(err),
+                        ))));
+                    }
+                };
+
                 let payload: T = match serde_json::from_slice(&data) {
                     // Deserializing from JSON into a formal request type was successful
                     Ok(request) => request,
                     // Deserializing failed
                     Err(err) => {
-                        return Poll::Ready(Some(Err(SubscriberError::JSONDeserialize(err))));
+                        return Poll::Ready(Some(Err(undecodable(
+                            raw_message,
+                            SubscriberError::JSONDeserialize(err),
+                        ))));
                     }
                 };
 
@@ -158,6 +317,9 @@ where
                 Poll::Ready(Some(Ok(Request {
                     payload,
                     reply_mailbox,
+                    payload_size,
+                    headers,
+                    raw_message,
                 })))
             }
             // A NATS error occurred (async error or other i/o)
@@ -178,3 +340,14 @@ where
         }
     }
 }
+
+/// Attaches the raw message, if it was kept, to an error decoding it.
+fn undecodable(raw_message: Option<RawMessage>, err: SubscriberError) -> SubscriberError {
+    match raw_message {
+        Some(message) => SubscriberError::Undecodable {
+            message: Box::new(message),
+            source: Box::new(err),
+        },
+        None => err,
+    }
+}
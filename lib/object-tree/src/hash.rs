@@ -27,6 +27,12 @@ impl Hash {
         Self(blake3::hash(input))
     }
 
+    /// Returns the bytes of the hashed value.
+    #[must_use]
+    pub fn as_bytes(&self) -> &[u8; 32] {
+        self.0.as_bytes()
+    }
+
     /// Returns a shortened String representation of the hashed value.
     ///
     /// Note that this value might not be sufficient to determine equality and/or uniqueness
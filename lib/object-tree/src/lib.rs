@@ -80,7 +80,7 @@ mod hash;
 mod tar;
 
 pub use crate::tar::{
-    read::TarReadError,
+    read::{TarReadError, TarReader},
     write::{TarWriter, TarWriterError},
 };
 pub use graph::{
@@ -5,6 +5,26 @@ use crate::Hash;
 pub mod read;
 pub mod write;
 
+/// The format of a tar whose objects are the plain bytes of their nodes. Tars without a format
+/// entry are in this format.
+const FORMAT_VERSION_PLAIN: u8 = 1;
+/// The format of a tar whose objects start with an encoding byte, followed by the bytes of their
+/// node in that encoding.
+const FORMAT_VERSION_ENCODED: u8 = 2;
+
+/// An object holding the bytes of its node as is.
+const ENCODING_RAW: u8 = 0;
+/// An object holding the bytes of its node compressed with deflate.
+const ENCODING_DEFLATE: u8 = 1;
+
+/// The most bytes an object may decode to (64 MiB), which keeps a small compressed object from
+/// inflating to exhaust memory.
+const MAX_DECODED_OBJECT_SIZE: u64 = 67_108_864;
+
+fn format_path() -> PathBuf {
+    PathBuf::from("format")
+}
+
 fn object_path(hash: &Hash) -> PathBuf {
     Path::new("objects").join(hash.to_string())
 }
@@ -1,12 +1,25 @@
-use std::{collections::HashMap, io::Read, path::PathBuf, str::FromStr, string::FromUtf8Error};
+use std::{
+    collections::HashMap,
+    fs::File,
+    io::Read,
+    path::{Path, PathBuf},
+    str::FromStr,
+    string::FromUtf8Error,
+};
 
+use flate2::read::DeflateDecoder;
 use petgraph::prelude::*;
 use thiserror::Error;
+use vfs::{FileSystem, SeekAndRead, VfsError};
+use vfs_tar::TarFS;
 
 use crate::{
     graph::{GraphError, HashedNodeWithEntries, NodeWithEntries, ObjectTree, ReadBytes},
     hash::{Hash, HashParseError},
-    tar::{object_path, ref_path},
+    tar::{
+        format_path, object_path, ref_path, ENCODING_DEFLATE, ENCODING_RAW, FORMAT_VERSION_ENCODED,
+        FORMAT_VERSION_PLAIN, MAX_DECODED_OBJECT_SIZE,
+    },
 };
 
 /// Errors that can occur when reading a module bundle from a tar file
@@ -25,12 +38,25 @@ pub enum TarReadError {
     /// When failing to parse a node with entries from bytes
     #[error("failed parse node with entries from bytes: {0}")]
     NodeWithEntriesParse(#[source] GraphError),
+    /// When an object decodes to more bytes than a node may have
+    #[error("object decodes to more than {0} bytes")]
+    ObjectTooLarge(u64),
     /// When failing while reading a tree
     #[error("error when reading tree: {0}")]
     ReadTree(#[source] GraphError),
     /// When the given byte sequence is not parsable as a UTF8 [`String`]
     #[error("Invalid string: {0}")]
     StringParse(#[from] FromUtf8Error),
+    /// When an object of the tar is in an encoding this reader does not know
+    #[error("unsupported object encoding: {0}")]
+    UnsupportedEncoding(u8),
+    /// When the tar is in a format this reader does not know, such as one written by a newer
+    /// version
+    #[error("unsupported tar format version: {0}")]
+    UnsupportedFormatVersion(u8),
+    /// When an error occurs while reading from a memory-mapped tar file
+    #[error("error reading from tar file: {0}")]
+    Vfs(#[from] VfsError),
 }
 
 impl<T> ObjectTree<T> {
@@ -48,9 +74,6 @@ impl<T> ObjectTree<T> {
     where
         N: ReadBytes,
     {
-        let mut graph = Graph::new();
-        let mut root_idx: Option<NodeIndex> = None;
-
         let mut unpacked_tar = ::tar::Archive::new(tar_data.as_slice());
         let mut tar_data = HashMap::new();
         for maybe_tar_entry in unpacked_tar.entries()? {
@@ -62,44 +85,185 @@ impl<T> ObjectTree<T> {
             tar_data.insert(entry_path, entry_data);
         }
 
+        let format_version = get_format_version(&tar_data)?;
         let root_hash = get_root_ref(&mut tar_data)?;
-        let root_node = get_node(&mut tar_data, root_hash)?;
 
-        let mut stack: Vec<(HashedNodeWithEntries<N>, Option<NodeIndex>)> = vec![(root_node, None)];
+        read_tree(
+            root_hash,
+            |hash| get_node(&mut tar_data, hash, format_version),
+            |_| false,
+        )
+    }
+}
+
+/// A reader over a memory-mapped tar file, which reads nodes from the file only when asked for
+/// them rather than loading the whole tree up front.
+///
+/// This keeps memory use close to the size of the subtrees being read, whatever the size of the
+/// file.
+#[derive(Debug)]
+pub struct TarReader {
+    fs: Box<dyn FileSystem>,
+    format_version: u8,
+    root_hash: Hash,
+}
 
-        while let Some((node_with_entries, parent_idx)) = stack.pop() {
-            let (node, child_entries) = node_with_entries.into();
+impl TarReader {
+    /// Memory-maps the tar file at `path` and reads its root ref, without reading any node.
+    ///
+    /// # Errors
+    ///
+    /// Returns `Err` if:
+    ///
+    /// - The file cannot be opened or memory-mapped
+    /// - The root ref does not exist or cannot be parsed
+    pub fn open(path: impl AsRef<Path>) -> Result<Self, TarReadError> {
+        let file = File::open(path)?;
+        let fs: Box<dyn FileSystem> = Box::new(TarFS::from_std_file(&file)?);
 
-            let node_idx = graph.add_node(node);
+        let format_version = if entry_exists(fs.as_ref(), &format_path())? {
+            let mut buf = Vec::new();
+            read_entry(fs.as_ref(), &format_path())?.read_to_end(&mut buf)?;
+            parse_format_version(&buf)?
+        } else {
+            FORMAT_VERSION_PLAIN
+        };
 
-            match parent_idx {
-                Some(parent_idx) => {
-                    graph.add_edge(parent_idx, node_idx, ());
-                }
-                None => match root_idx {
-                    None => {
-                        root_idx = Some(node_idx);
-                    }
-                    Some(_) => return Err(TarReadError::ReadTree(GraphError::MultipleRootNode)),
-                },
-            };
-
-            for child_entry in child_entries.into_iter().rev() {
-                let child_node = get_node(&mut tar_data, child_entry.hash())?;
-                stack.push((child_node, Some(node_idx)));
+        let mut buf = String::new();
+        read_entry(fs.as_ref(), &ref_path("root"))?.read_to_string(&mut buf)?;
+        let root_hash = Hash::from_str(&buf)?;
+
+        Ok(Self {
+            fs,
+            format_version,
+            root_hash,
+        })
+    }
+
+    /// Returns the [`struct@Hash`] of the root node of the tree.
+    pub fn root_hash(&self) -> Hash {
+        self.root_hash
+    }
+
+    /// Reads the subtree whose root node has the given [`struct@Hash`].
+    ///
+    /// # Errors
+    ///
+    /// Returns `Err` if a node of the subtree does not exist or cannot be parsed.
+    pub fn read_tree<N>(&self, hash: Hash) -> Result<ObjectTree<N>, TarReadError>
+    where
+        N: ReadBytes,
+    {
+        self.read_tree_pruned(hash, |_| false)
+    }
+
+    /// Reads the subtree whose root node has the given [`struct@Hash`], without reading the
+    /// children of any node for which `is_pruned` returns `true`.
+    ///
+    /// A pruned node keeps the hash it was written with, so its own subtree can be read later
+    /// with [`TarReader::read_tree`].
+    ///
+    /// # Errors
+    ///
+    /// Returns `Err` if a node of the subtree does not exist or cannot be parsed.
+    pub fn read_tree_pruned<N, F>(
+        &self,
+        hash: Hash,
+        is_pruned: F,
+    ) -> Result<ObjectTree<N>, TarReadError>
+    where
+        N: ReadBytes,
+        F: Fn(&N) -> bool,
+    {
+        read_tree(hash, |hash| self.read_node(hash), is_pruned)
+    }
+
+    fn read_node<N>(&self, hash: Hash) -> Result<HashedNodeWithEntries<N>, TarReadError>
+    where
+        N: ReadBytes,
+    {
+        let mut buf = Vec::new();
+        read_entry(self.fs.as_ref(), &object_path(&hash))?.read_to_end(&mut buf)?;
+
+        parse_node(buf, hash, self.format_version)
+    }
+}
+
+fn read_entry(
+    fs: &dyn FileSystem,
+    path: &Path,
+) -> Result<Box<dyn SeekAndRead + Send>, TarReadError> {
+    if !entry_exists(fs, path)? {
+        return Err(TarReadError::NodeNotFound(path.to_path_buf()));
+    }
+
+    Ok(fs.open_file(&vfs_path(path))?)
+}
+
+fn entry_exists(fs: &dyn FileSystem, path: &Path) -> Result<bool, TarReadError> {
+    Ok(fs.exists(&vfs_path(path))?)
+}
+
+fn vfs_path(path: &Path) -> String {
+    Path::new("/").join(path).to_string_lossy().into_owned()
+}
+
+/// Builds a tree from its root node down, getting each node with `get_node` and skipping the
+/// children of nodes for which `is_pruned` returns `true`.
+fn read_tree<N, G, F>(
+    root_hash: Hash,
+    mut get_node: G,
+    is_pruned: F,
+) -> Result<ObjectTree<N>, TarReadError>
+where
+    G: FnMut(Hash) -> Result<HashedNodeWithEntries<N>, TarReadError>,
+    F: Fn(&N) -> bool,
+{
+    let mut graph = Graph::new();
+    let mut root_idx: Option<NodeIndex> = None;
+
+    let root_node = get_node(root_hash)?;
+
+    let mut stack: Vec<(HashedNodeWithEntries<N>, Option<NodeIndex>)> = vec![(root_node, None)];
+
+    while let Some((node_with_entries, parent_idx)) = stack.pop() {
+        let (node, child_entries) = node_with_entries.into();
+        let is_pruned = is_pruned(node.inner());
+
+        let node_idx = graph.add_node(node);
+
+        match parent_idx {
+            Some(parent_idx) => {
+                graph.add_edge(parent_idx, node_idx, ());
             }
+            None => match root_idx {
+                None => {
+                    root_idx = Some(node_idx);
+                }
+                Some(_) => return Err(TarReadError::ReadTree(GraphError::MultipleRootNode)),
+            },
+        };
+
+        if is_pruned {
+            continue;
         }
 
-        match root_idx {
-            Some(root_idx) => Ok(ObjectTree::new(graph, root_idx)),
-            None => Err(TarReadError::ReadTree(GraphError::MissingRootNode)),
+        for child_entry in child_entries.into_iter().rev() {
+            let child_node = get_node(child_entry.hash())?;
+            stack.push((child_node, Some(node_idx)));
         }
     }
+
+    match root_idx {
+        Some(root_idx) => Ok(ObjectTree::new(graph, root_idx)),
+        None => Err(TarReadError::ReadTree(GraphError::MissingRootNode)),
+    }
 }
 
 fn get_node<N>(
     tar_data: &mut HashMap<PathBuf, Vec<u8>>,
     hash: Hash,
+    format_version: u8,
 ) -> Result<HashedNodeWithEntries<N>, TarReadError>
 where
     N: ReadBytes,
@@ -109,8 +273,23 @@ where
         .get(&dst_path)
         .ok_or_else(|| TarReadError::NodeNotFound(dst_path))?;
 
+    parse_node(buf.clone(), hash, format_version)
+}
+
+fn parse_node<N>(
+    buf: Vec<u8>,
+    hash: Hash,
+    format_version: u8,
+) -> Result<HashedNodeWithEntries<N>, TarReadError>
+where
+    N: ReadBytes,
+{
+    let node_bytes = match format_version {
+        FORMAT_VERSION_ENCODED => decode_object(buf, MAX_DECODED_OBJECT_SIZE)?,
+        _ => buf,
+    };
     let node_with_entries: NodeWithEntries<N> =
-        NodeWithEntries::from_bytes(buf.clone()).map_err(TarReadError::NodeWithEntriesParse)?;
+        NodeWithEntries::from_bytes(node_bytes).map_err(TarReadError::NodeWithEntriesParse)?;
 
     Ok(HashedNodeWithEntries::from_node_with_entries_and_hash(
         node_with_entries,
@@ -118,6 +297,44 @@ where
     ))
 }
 
+/// Returns the bytes of the node held by an object of an encoded tar, refusing objects that
+/// decode to more than `max_size` bytes.
+fn decode_object(object_bytes: Vec<u8>, max_size: u64) -> Result<Vec<u8>, TarReadError> {
+    match object_bytes.split_first() {
+        Some((&ENCODING_RAW, node_bytes)) => Ok(node_bytes.to_vec()),
+        Some((&ENCODING_DEFLATE, compressed)) => {
+            let mut node_bytes = Vec::new();
+            // Reading one byte past the limit tells an object of exactly `max_size` bytes apart
+            // from a larger one
+            DeflateDecoder::new(compressed)
+                .take(max_size.saturating_add(1))
+                .read_to_end(&mut node_bytes)?;
+            if u64::try_from(node_bytes.len()).map_or(true, |len| len > max_size) {
+                return Err(TarReadError::ObjectTooLarge(max_size));
+            }
+            Ok(node_bytes)
+        }
+        Some((encoding, _)) => Err(TarReadError::UnsupportedEncoding(*encoding)),
+        // Left for the node parser to reject
+        None => Ok(object_bytes),
+    }
+}
+
+fn get_format_version(tar_data: &HashMap<PathBuf, Vec<u8>>) -> Result<u8, TarReadError> {
+    match tar_data.get(&format_path()) {
+        Some(buf) => parse_format_version(buf),
+        None => Ok(FORMAT_VERSION_PLAIN),
+    }
+}
+
+fn parse_format_version(buf: &[u8]) -> Result<u8, TarReadError> {
+    match buf {
+        [version @ (FORMAT_VERSION_PLAIN | FORMAT_VERSION_ENCODED)] => Ok(*version),
+        [version, ..] => Err(TarReadError::UnsupportedFormatVersion(*version)),
+        [] => Err(TarReadError::UnsupportedFormatVersion(0)),
+    }
+}
+
 fn get_root_ref(tar_data: &mut HashMap<PathBuf, Vec<u8>>) -> Result<Hash, TarReadError> {
     let dst_path = ref_path("root");
     let buf = String::from_utf8(
@@ -129,3 +346,38 @@ fn get_root_ref(tar_data: &mut HashMap<PathBuf, Vec<u8>>) -> Result<Hash, TarRea
 
     Hash::from_str(&buf).map_err(Into::into)
 }
+
+#[cfg(test)]
+mod tests {
+    use std::io::Write;
+
+    use flate2::{write::DeflateEncoder, Compression};
+
+    use super::*;
+
+    fn deflated_object(node_bytes: &[u8]) -> Vec<u8> {
+        let mut encoder = DeflateEncoder::new(vec![ENCODING_DEFLATE], Compression::default());
+        encoder.write_all(node_bytes).expect("failed to compress");
+        encoder.finish().expect("failed to compress")
+    }
+
+    #[test]
+    fn decode_object_up_to_max_size() {
+        let node_bytes = vec![0; 1024];
+
+        let decoded =
+            decode_object(deflated_object(&node_bytes), 1024).expect("failed to decode object");
+
+        assert_eq!(node_bytes, decoded);
+    }
+
+    #[test]
+    fn decode_object_over_max_size() {
+        let object = deflated_object(&[0; 1024]);
+
+        assert!(matches!(
+            decode_object(object, 1023),
+            Err(TarReadError::ObjectTooLarge(1023))
+        ));
+    }
+}
@@ -1,12 +1,15 @@
-use std::{num::TryFromIntError, path::PathBuf};
+use std::{collections::HashSet, io::Write, num::TryFromIntError, path::PathBuf};
 
 use ::tar::{Builder, Header};
+use flate2::{write::DeflateEncoder, Compression};
 use petgraph::prelude::*;
 use thiserror::Error;
 
 use crate::{
     graph::{HashedNodeWithEntries, NodeEntry},
-    tar::{object_path, ref_path},
+    tar::{
+        format_path, object_path, ref_path, ENCODING_DEFLATE, ENCODING_RAW, FORMAT_VERSION_ENCODED,
+    },
     GraphError, NameStr, ObjectTree, WriteBytes,
 };
 
@@ -25,6 +28,9 @@ pub enum TarWriterError {
     TryFromInt(#[from] TryFromIntError),
 }
 
+/// Nodes smaller than this are never compressed, as deflate would barely shrink them.
+const MIN_COMPRESSED_NODE_LEN: usize = 512;
+
 /// Create a tar from an [`ObjectTree`]
 pub struct TarWriter {
     bytes: Vec<u8>,
@@ -33,12 +39,36 @@ pub struct TarWriter {
 impl TarWriter {
     /// Return a [`TarWriter`] populated from the provided [`ObjectTree`]
     pub fn new<T>(tree: &ObjectTree<T>) -> Result<Self, TarWriterError>
+    where
+        T: Clone + NameStr + WriteBytes + Send + Sync + 'static,
+    {
+        Self::write(tree, false)
+    }
+
+    /// Return a [`TarWriter`] populated from the provided [`ObjectTree`], compressing the nodes
+    /// that get smaller when compressed.
+    ///
+    /// The tar is written in a newer format, which older readers refuse rather than misread.
+    pub fn new_compressed<T>(tree: &ObjectTree<T>) -> Result<Self, TarWriterError>
+    where
+        T: Clone + NameStr + WriteBytes + Send + Sync + 'static,
+    {
+        Self::write(tree, true)
+    }
+
+    fn write<T>(tree: &ObjectTree<T>, compress: bool) -> Result<Self, TarWriterError>
     where
         T: Clone + NameStr + WriteBytes + Send + Sync + 'static,
     {
         let (graph, root_idx) = tree.as_petgraph();
         let mut tar_builder = Builder::new(Vec::new());
 
+        if compress {
+            write_tar_entry(&mut tar_builder, format_path(), &[FORMAT_VERSION_ENCODED])?;
+        }
+
+        // Objects are addressed by hash, so identical subtrees only need to be written once
+        let mut written_hashes = HashSet::new();
         let mut dfspo = DfsPostOrder::new(graph, root_idx);
 
         while let Some(node_idx) = dfspo.next(graph) {
@@ -49,6 +79,9 @@ impl TarWriter {
                     "tar writer: could not find next node for index for dfspo",
                 ))?
                 .clone();
+            if !written_hashes.insert(node.hash()) {
+                continue;
+            }
 
             let mut entries = Vec::new();
             for child_idx in graph.neighbors_directed(node_idx, Outgoing) {
@@ -67,10 +100,16 @@ impl TarWriter {
             }
 
             let tar_entry = HashedNodeWithEntries::new(node, entries);
+            let node_bytes = tar_entry.to_bytes()?;
+            let object_bytes = if compress {
+                encode_object(node_bytes)?
+            } else {
+                node_bytes
+            };
             write_tar_entry(
                 &mut tar_builder,
                 object_path(&tar_entry.hash()),
-                &tar_entry.to_bytes()?,
+                &object_bytes,
             )?;
         }
 
@@ -98,6 +137,25 @@ impl TarWriter {
     }
 }
 
+/// Prefixes the bytes of a node with their encoding, compressing them if that makes them smaller.
+fn encode_object(node_bytes: Vec<u8>) -> Result<Vec<u8>, TarWriterError> {
+    if node_bytes.len() >= MIN_COMPRESSED_NODE_LEN {
+        let mut encoder = DeflateEncoder::new(vec![ENCODING_DEFLATE], Compression::default());
+        encoder.write_all(&node_bytes)?;
+        let compressed = encoder.finish()?;
+
+        if compressed.len() < node_bytes.len() {
+            return Ok(compressed);
+        }
+    }
+
+    let mut object_bytes = Vec::with_capacity(node_bytes.len().saturating_add(1));
+    object_bytes.push(ENCODING_RAW);
+    object_bytes.extend(node_bytes);
+
+    Ok(object_bytes)
+}
+
 fn write_tar_entry(
     tar_builder: &mut Builder<Vec<u8>>,
     path: PathBuf,
@@ -32,6 +32,10 @@ pub fn routes(state: AppState) -> Router {
         )
         .nest("/api/fix", crate::server::service::fix::routes())
         .nest("/api/func", crate::server::service::func::routes())
+        .nest(
+            "/api/onboarding",
+            crate::server::service::onboarding::routes(),
+        )
         .nest("/api/pkg", crate::server::service::pkg::routes())
         .nest("/api/provider", crate::server::service::provider::routes())
         .nest(
@@ -5,9 +5,9 @@ use axum::{
     Json, Router,
 };
 use dal::{
-    change_status::ChangeStatusError, ChangeSetError as DalChangeSetError,
-    ComponentError as DalComponentError, FixError, StandardModelError, TransactionsError,
-    UserError, UserPk,
+    change_set_review::ChangeSetReviewError, change_status::ChangeStatusError,
+    ChangeSetError as DalChangeSetError, ComponentError as DalComponentError, FixError,
+    StandardModelError, TransactionsError, UserError, UserPk,
 };
 use module_index_client::IndexClientError;
 use telemetry::prelude::*;
@@ -21,6 +21,10 @@ pub mod create_change_set;
 pub mod get_change_set;
 pub mod get_stats;
 pub mod list_open_change_sets;
+pub mod list_reviews;
+pub mod request_review;
+pub mod review_change_set;
+pub mod update_review_policy;
 pub mod update_selected_change_set;
 
 #[remain::sorted]
@@ -31,6 +35,8 @@ pub enum ChangeSetError {
     #[error("change set not found")]
     ChangeSetNotFound,
     #[error(transparent)]
+    ChangeSetReview(#[from] ChangeSetReviewError),
+    #[error(transparent)]
     ChangeStatusError(#[from] ChangeStatusError),
     #[error(transparent)]
     Component(#[from] DalComponentError),
@@ -66,6 +72,19 @@ impl IntoResponse for ChangeSetError {
     fn into_response(self) -> Response {
         let (status, error_message) = match self {
             ChangeSetError::ChangeSetNotFound => (StatusCode::NOT_FOUND, self.to_string()),
+            ChangeSetError::ChangeSetReview(
+                ChangeSetReviewError::NotEnoughApprovals(..) | ChangeSetReviewError::Rejected(_),
+            ) => (StatusCode::CONFLICT, self.to_string()),
+            ChangeSetError::ChangeSetReview(
+                ChangeSetReviewError::NotAWorkspaceAdmin(_)
+                | ChangeSetReviewError::NotRequested(..)
+                | ChangeSetReviewError::SelfApproval(..),
+            ) => (StatusCode::FORBIDDEN, self.to_string()),
+            ChangeSetError::ChangeSetReview(
+                ChangeSetReviewError::InvalidDecision(_)
+                | ChangeSetReviewError::InvalidRequiredApprovals(_)
+                | ChangeSetReviewError::NotAUser,
+            ) => (StatusCode::UNPROCESSABLE_ENTITY, self.to_string()),
             _ => (StatusCode::INTERNAL_SERVER_ERROR, self.to_string()),
         };
 
@@ -97,6 +116,16 @@ pub fn routes() -> Router<AppState> {
             "/apply_change_set2",
             post(apply_change_set2::apply_change_set),
         )
+        .route("/request_review", post(request_review::request_review))
+        .route(
+            "/review_change_set",
+            post(review_change_set::review_change_set),
+        )
+        .route("/list_reviews", get(list_reviews::list_reviews))
+        .route(
+            "/update_review_policy",
+            post(update_review_policy::update_review_policy),
+        )
         .route(
             "/update_selected_change_set",
             post(update_selected_change_set::update_selected_change_set),
@@ -4,7 +4,7 @@ use crate::server::service::change_set::ChangeSetError;
 use crate::server::tracking::track;
 use axum::extract::OriginalUri;
 use axum::Json;
-use dal::{ChangeSet, ChangeSetPk};
+use dal::{ChangeSet, ChangeSetPk, ChangeSetReview};
 use serde::{Deserialize, Serialize};
 
 #[derive(Deserialize, Serialize, Debug)]
@@ -31,6 +31,7 @@ pub async fn apply_change_set(
     let mut change_set = ChangeSet::get_by_pk(&ctx, &request.change_set_pk)
         .await?
         .ok_or(ChangeSetError::ChangeSetNotFound)?;
+    ChangeSetReview::ensure_approved(&ctx, request.change_set_pk).await?;
     change_set.apply(&mut ctx).await?;
 
     track(
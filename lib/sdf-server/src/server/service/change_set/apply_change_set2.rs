@@ -6,8 +6,8 @@ use axum::extract::OriginalUri;
 use axum::Json;
 use dal::job::definition::{FixItem, FixesJob};
 use dal::{
-    ActionPrototypeId, AttributeValueId, ChangeSet, ChangeSetPk, ComponentId, Fix, FixBatch,
-    HistoryActor, StandardModel, User,
+    ActionPrototypeId, AttributeValueId, ChangeSet, ChangeSetPk, ChangeSetReview, ComponentId, Fix,
+    FixBatch, HistoryActor, StandardModel, User,
 };
 use serde::{Deserialize, Serialize};
 //use telemetry::tracing::{info_span, Instrument, log::warn};
@@ -45,6 +45,7 @@ pub async fn apply_change_set(
     let mut change_set = ChangeSet::get_by_pk(&ctx, &request.change_set_pk)
         .await?
         .ok_or(ChangeSetError::ChangeSetNotFound)?;
+    ChangeSetReview::ensure_approved(&ctx, request.change_set_pk).await?;
     change_set.apply_raw(&mut ctx, false).await?;
 
     track(
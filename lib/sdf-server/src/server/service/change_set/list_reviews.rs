@@ -0,0 +1,32 @@
+use super::{ChangeSetError, ChangeSetResult};
+use crate::server::extract::{AccessBuilder, HandlerContext};
+use axum::extract::Query;
+use axum::Json;
+use dal::{ChangeSet, ChangeSetPk, ChangeSetReview, ChangeSetReviewSummary};
+use serde::{Deserialize, Serialize};
+
+#[derive(Deserialize, Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct ListReviewsRequest {
+    pub change_set_pk: ChangeSetPk,
+}
+
+pub type ListReviewsResponse = ChangeSetReviewSummary;
+
+pub async fn list_reviews(
+    HandlerContext(builder): HandlerContext,
+    AccessBuilder(access_builder): AccessBuilder,
+    Query(request): Query<ListReviewsRequest>,
+) -> ChangeSetResult<Json<ListReviewsResponse>> {
+    let ctx = builder.build_head(access_builder).await?;
+
+    ChangeSet::get_by_pk(&ctx, &request.change_set_pk)
+        .await?
+        .ok_or(ChangeSetError::ChangeSetNotFound)?;
+
+    let summary = ChangeSetReview::summary(&ctx, request.change_set_pk).await?;
+
+    ctx.commit().await?;
+
+    Ok(Json(summary))
+}
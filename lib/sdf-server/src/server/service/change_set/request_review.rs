@@ -0,0 +1,54 @@
+use super::{ChangeSetError, ChangeSetResult};
+use crate::server::extract::{AccessBuilder, HandlerContext, PosthogClient};
+use crate::server::tracking::track;
+use axum::extract::OriginalUri;
+use axum::Json;
+use dal::{ChangeSet, ChangeSetPk, ChangeSetReview, UserPk};
+use serde::{Deserialize, Serialize};
+
+#[derive(Deserialize, Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct RequestReviewRequest {
+    pub change_set_pk: ChangeSetPk,
+    pub reviewer_pks: Vec<UserPk>,
+}
+
+#[derive(Deserialize, Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct RequestReviewResponse {
+    pub reviews: Vec<ChangeSetReview>,
+}
+
+pub async fn request_review(
+    HandlerContext(builder): HandlerContext,
+    AccessBuilder(access_builder): AccessBuilder,
+    PosthogClient(posthog_client): PosthogClient,
+    OriginalUri(original_uri): OriginalUri,
+    Json(request): Json<RequestReviewRequest>,
+) -> ChangeSetResult<Json<RequestReviewResponse>> {
+    let ctx = builder.build_head(access_builder).await?;
+
+    ChangeSet::get_by_pk(&ctx, &request.change_set_pk)
+        .await?
+        .ok_or(ChangeSetError::ChangeSetNotFound)?;
+
+    let mut reviews = Vec::with_capacity(request.reviewer_pks.len());
+    for reviewer_pk in &request.reviewer_pks {
+        reviews.push(ChangeSetReview::request(&ctx, request.change_set_pk, *reviewer_pk).await?);
+    }
+
+    track(
+        &posthog_client,
+        &ctx,
+        &original_uri,
+        "request_change_set_review",
+        serde_json::json!({
+            "change_set_pk": request.change_set_pk,
+            "number_of_reviewers": request.reviewer_pks.len(),
+        }),
+    );
+
+    ctx.commit().await?;
+
+    Ok(Json(RequestReviewResponse { reviews }))
+}
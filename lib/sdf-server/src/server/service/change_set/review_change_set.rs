@@ -0,0 +1,54 @@
+use super::{ChangeSetError, ChangeSetResult};
+use crate::server::extract::{AccessBuilder, HandlerContext, PosthogClient};
+use crate::server::tracking::track;
+use axum::extract::OriginalUri;
+use axum::Json;
+use dal::{ChangeSet, ChangeSetPk, ChangeSetReview, ChangeSetReviewStatus};
+use serde::{Deserialize, Serialize};
+
+#[derive(Deserialize, Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct ReviewChangeSetRequest {
+    pub change_set_pk: ChangeSetPk,
+    pub status: ChangeSetReviewStatus,
+    pub comment: Option<String>,
+}
+
+#[derive(Deserialize, Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct ReviewChangeSetResponse {
+    pub review: ChangeSetReview,
+}
+
+pub async fn review_change_set(
+    HandlerContext(builder): HandlerContext,
+    AccessBuilder(access_builder): AccessBuilder,
+    PosthogClient(posthog_client): PosthogClient,
+    OriginalUri(original_uri): OriginalUri,
+    Json(request): Json<ReviewChangeSetRequest>,
+) -> ChangeSetResult<Json<ReviewChangeSetResponse>> {
+    let ctx = builder.build_head(access_builder).await?;
+
+    ChangeSet::get_by_pk(&ctx, &request.change_set_pk)
+        .await?
+        .ok_or(ChangeSetError::ChangeSetNotFound)?;
+
+    let review =
+        ChangeSetReview::decide(&ctx, request.change_set_pk, request.status, request.comment)
+            .await?;
+
+    track(
+        &posthog_client,
+        &ctx,
+        &original_uri,
+        "review_change_set",
+        serde_json::json!({
+            "change_set_pk": request.change_set_pk,
+            "status": request.status,
+        }),
+    );
+
+    ctx.commit().await?;
+
+    Ok(Json(ReviewChangeSetResponse { review }))
+}
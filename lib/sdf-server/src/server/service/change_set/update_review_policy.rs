@@ -0,0 +1,46 @@
+use super::ChangeSetResult;
+use crate::server::extract::{AccessBuilder, HandlerContext, PosthogClient};
+use crate::server::tracking::track;
+use axum::extract::OriginalUri;
+use axum::Json;
+use dal::ChangeSetReviewPolicy;
+use serde::{Deserialize, Serialize};
+
+#[derive(Deserialize, Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct UpdateReviewPolicyRequest {
+    pub required_approvals: i32,
+}
+
+#[derive(Deserialize, Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct UpdateReviewPolicyResponse {
+    pub policy: ChangeSetReviewPolicy,
+}
+
+pub async fn update_review_policy(
+    HandlerContext(builder): HandlerContext,
+    AccessBuilder(access_builder): AccessBuilder,
+    PosthogClient(posthog_client): PosthogClient,
+    OriginalUri(original_uri): OriginalUri,
+    Json(request): Json<UpdateReviewPolicyRequest>,
+) -> ChangeSetResult<Json<UpdateReviewPolicyResponse>> {
+    let ctx = builder.build_head(access_builder).await?;
+
+    let policy =
+        ChangeSetReviewPolicy::set_required_approvals(&ctx, request.required_approvals).await?;
+
+    track(
+        &posthog_client,
+        &ctx,
+        &original_uri,
+        "update_change_set_review_policy",
+        serde_json::json!({
+            "required_approvals": request.required_approvals,
+        }),
+    );
+
+    ctx.commit().await?;
+
+    Ok(Json(UpdateReviewPolicyResponse { policy }))
+}
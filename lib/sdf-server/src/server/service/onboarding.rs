@@ -0,0 +1,52 @@
+use axum::http::StatusCode;
+use axum::response::{IntoResponse, Response};
+use axum::routing::{get, post};
+use axum::Json;
+use axum::Router;
+use dal::{OnboardingError as DalOnboardingError, TransactionsError, WsEventError};
+use thiserror::Error;
+
+use crate::server::state::AppState;
+
+pub mod list_templates;
+pub mod provision;
+
+#[remain::sorted]
+#[derive(Debug, Error)]
+pub enum OnboardingError {
+    #[error(transparent)]
+    ContextTransactions(#[from] TransactionsError),
+    #[error(transparent)]
+    Onboarding(#[from] DalOnboardingError),
+    #[error("ws event error: {0}")]
+    WsEvent(#[from] WsEventError),
+}
+
+pub type OnboardingResult<T> = std::result::Result<T, OnboardingError>;
+
+impl IntoResponse for OnboardingError {
+    fn into_response(self) -> Response {
+        let (status, error_message) = match self {
+            OnboardingError::Onboarding(DalOnboardingError::TemplateNotFound(_)) => {
+                (StatusCode::NOT_FOUND, self.to_string())
+            }
+            _ => (StatusCode::INTERNAL_SERVER_ERROR, self.to_string()),
+        };
+
+        let body = Json(serde_json::json!({
+            "error": {
+                "message": error_message,
+                "code": 42,
+                "statusCode": status.as_u16()
+            }
+        }));
+
+        (status, body).into_response()
+    }
+}
+
+pub fn routes() -> Router<AppState> {
+    Router::new()
+        .route("/list_templates", get(list_templates::list_templates))
+        .route("/provision", post(provision::provision))
+}
@@ -0,0 +1,20 @@
+use axum::Json;
+use dal::StarterTemplate;
+use serde::{Deserialize, Serialize};
+
+use super::OnboardingResult;
+use crate::server::extract::AccessBuilder;
+
+#[derive(Deserialize, Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct ListTemplatesResponse {
+    pub templates: Vec<StarterTemplate>,
+}
+
+pub async fn list_templates(
+    AccessBuilder(_request_ctx): AccessBuilder,
+) -> OnboardingResult<Json<ListTemplatesResponse>> {
+    Ok(Json(ListTemplatesResponse {
+        templates: StarterTemplate::list(),
+    }))
+}
@@ -0,0 +1,61 @@
+use axum::extract::OriginalUri;
+use axum::Json;
+use dal::{OnboardingManifest, StarterTemplate, Visibility, WsEvent};
+use serde::{Deserialize, Serialize};
+
+use super::OnboardingResult;
+use crate::server::extract::{AccessBuilder, HandlerContext, PosthogClient};
+use crate::server::tracking::track;
+
+#[derive(Deserialize, Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct ProvisionRequest {
+    pub template: String,
+}
+
+#[derive(Deserialize, Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct ProvisionResponse {
+    pub manifest: OnboardingManifest,
+}
+
+/// Provisions a [`StarterTemplate`](dal::StarterTemplate): installs its modules on head and
+/// creates a demo change set with its components and connections.
+pub async fn provision(
+    HandlerContext(builder): HandlerContext,
+    AccessBuilder(request_ctx): AccessBuilder,
+    PosthogClient(posthog_client): PosthogClient,
+    OriginalUri(original_uri): OriginalUri,
+    Json(request): Json<ProvisionRequest>,
+) -> OnboardingResult<Json<ProvisionResponse>> {
+    let mut ctx = builder
+        .build(request_ctx.build(Visibility::new_head(false)))
+        .await?;
+
+    let template = StarterTemplate::find(&request.template)?;
+    let manifest = template.provision(&mut ctx).await?;
+
+    WsEvent::change_set_created(&ctx, manifest.change_set_pk)
+        .await?
+        .publish_on_commit(&ctx)
+        .await?;
+    WsEvent::change_set_written(&ctx)
+        .await?
+        .publish_on_commit(&ctx)
+        .await?;
+
+    track(
+        &posthog_client,
+        &ctx,
+        &original_uri,
+        "workspace_onboarding_provisioned",
+        serde_json::json!({
+            "template": template.name,
+            "change_set_pk": manifest.change_set_pk,
+        }),
+    );
+
+    ctx.commit().await?;
+
+    Ok(Json(ProvisionResponse { manifest }))
+}
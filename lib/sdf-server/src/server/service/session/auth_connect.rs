@@ -1,7 +1,7 @@
 use super::{SessionError, SessionResult};
 use crate::server::extract::HandlerContext;
 use axum::Json;
-use dal::{HistoryActor, KeyPair, Tenancy, User, UserPk, Workspace, WorkspacePk};
+use dal::{HistoryActor, KeyPair, Tenancy, User, UserPk, Workspace, WorkspacePk, WorkspaceRole};
 use serde::{Deserialize, Serialize};
 use serde_json::json;
 
@@ -43,8 +43,8 @@ pub struct AuthApiUser {
 pub struct AuthApiWorkspace {
     pub id: WorkspacePk,
     pub display_name: String,
-    // dont need to do anything with these for now
     pub creator_user_id: UserPk,
+    // dont need to do anything with these for now
     pub instance_url: String,
     pub instance_env_type: String,
 }
@@ -124,8 +124,14 @@ pub async fn auth_connect(
         }
     };
 
-    // ensure workspace is associated to user
-    user.associate_workspace(&ctx, *workspace.pk()).await?;
+    // ensure workspace is associated to user, with the workspace's creator as its owner
+    let role = if res_body.workspace.creator_user_id == user.pk() {
+        WorkspaceRole::Owner
+    } else {
+        WorkspaceRole::Member
+    };
+    user.associate_workspace(&ctx, *workspace.pk(), role)
+        .await?;
 
     ctx.commit().await?;
 
@@ -130,6 +130,7 @@ pub async fn exec_variant_def(
                 asset_func.clone(),
             )])),
             no_record: true,
+            ..Default::default()
         }),
     )
     .await?;
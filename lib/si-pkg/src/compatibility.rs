@@ -0,0 +1,357 @@
+use std::collections::HashMap;
+
+use semver::Version;
+
+use crate::{
+    pkg::PkgResult, FuncSpec, PropSpec, PropSpecKind, SchemaVariantSpec, SiPkg, SocketSpecArity,
+    SocketSpecKind,
+};
+
+/// A change between two versions of a package that can break workspaces using the older one.
+#[remain::sorted]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum BreakingChange {
+    FuncRemoved {
+        func: String,
+    },
+    /// The func's arguments or response type changed.
+    FuncSignatureChanged {
+        func: String,
+    },
+    PropKindChanged {
+        schema: String,
+        variant: String,
+        path: String,
+        old_kind: PropSpecKind,
+        new_kind: PropSpecKind,
+    },
+    PropRemoved {
+        schema: String,
+        variant: String,
+        path: String,
+    },
+    SchemaRemoved {
+        schema: String,
+    },
+    SchemaVariantRemoved {
+        schema: String,
+        variant: String,
+    },
+    SocketArityChanged {
+        schema: String,
+        variant: String,
+        socket: String,
+        old_arity: SocketSpecArity,
+        new_arity: SocketSpecArity,
+    },
+    SocketKindChanged {
+        schema: String,
+        variant: String,
+        socket: String,
+        old_kind: SocketSpecKind,
+        new_kind: SocketSpecKind,
+    },
+    SocketRemoved {
+        schema: String,
+        variant: String,
+        socket: String,
+    },
+}
+
+/// How the version of a package changed between two of its versions.
+#[remain::sorted]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum VersionChange {
+    Downgrade,
+    /// A bump allowed to break compatibility: the major version, or the minor version of a `0.x`
+    /// version.
+    Major,
+    Minor,
+    None,
+    Patch,
+    /// One of the versions isn't semver, such as a date.
+    Unversioned,
+}
+
+impl VersionChange {
+    fn between(old: Option<&Version>, new: Option<&Version>) -> Self {
+        let (old, new) = match (old, new) {
+            (Some(old), Some(new)) => (old, new),
+            _ => return Self::Unversioned,
+        };
+
+        if new < old {
+            Self::Downgrade
+        } else if new.major != old.major || (old.major == 0 && new.minor != old.minor) {
+            Self::Major
+        } else if new.minor != old.minor {
+            Self::Minor
+        } else if new.patch != old.patch {
+            Self::Patch
+        } else {
+            Self::None
+        }
+    }
+}
+
+/// What upgrading from one version of a package to another would break.
+#[derive(Clone, Debug)]
+pub struct CompatibilityReport {
+    old_version: String,
+    new_version: String,
+    version_change: VersionChange,
+    breaking_changes: Vec<BreakingChange>,
+}
+
+impl CompatibilityReport {
+    pub fn old_version(&self) -> &str {
+        self.old_version.as_ref()
+    }
+
+    pub fn new_version(&self) -> &str {
+        self.new_version.as_ref()
+    }
+
+    pub fn version_change(&self) -> VersionChange {
+        self.version_change
+    }
+
+    pub fn breaking_changes(&self) -> &[BreakingChange] {
+        &self.breaking_changes
+    }
+
+    /// Whether the new version can replace the old one without breaking anything.
+    pub fn is_compatible(&self) -> bool {
+        self.breaking_changes.is_empty()
+    }
+
+    /// Whether any breaking changes come with a version bump that announces them.
+    pub fn is_version_bump_sufficient(&self) -> bool {
+        self.is_compatible() || self.version_change == VersionChange::Major
+    }
+}
+
+/// Compares two versions of a package, flagging the changes that would break a workspace where
+/// the old one is installed: removed schemas, variants, props, sockets and funcs, props and
+/// sockets of another kind, sockets of another arity and funcs with another signature.
+///
+/// Schemas, variants, sockets and funcs are matched by name and props by their path.
+pub async fn is_compatible_upgrade(old: &SiPkg, new: &SiPkg) -> PkgResult<CompatibilityReport> {
+    let old_metadata = old.metadata()?;
+    let new_metadata = new.metadata()?;
+    let old_spec = old.to_spec().await?;
+    let new_spec = new.to_spec().await?;
+
+    let mut breaking_changes = Vec::new();
+
+    let new_funcs: HashMap<&str, &FuncSpec> = new_spec
+        .funcs
+        .iter()
+        .map(|func| (func.name.as_str(), func))
+        .collect();
+    for old_func in &old_spec.funcs {
+        match new_funcs.get(old_func.name.as_str()) {
+            None => breaking_changes.push(BreakingChange::FuncRemoved {
+                func: old_func.name.clone(),
+            }),
+            Some(new_func) if !same_signature(old_func, new_func) => {
+                breaking_changes.push(BreakingChange::FuncSignatureChanged {
+                    func: old_func.name.clone(),
+                })
+            }
+            Some(_) => {}
+        }
+    }
+
+    for old_schema in &old_spec.schemas {
+        let new_schema = match new_spec
+            .schemas
+            .iter()
+            .find(|schema| schema.name == old_schema.name)
+        {
+            Some(new_schema) => new_schema,
+            None => {
+                breaking_changes.push(BreakingChange::SchemaRemoved {
+                    schema: old_schema.name.clone(),
+                });
+                continue;
+            }
+        };
+
+        for old_variant in &old_schema.variants {
+            match new_schema
+                .variants
+                .iter()
+                .find(|variant| variant.name == old_variant.name)
+            {
+                Some(new_variant) => compare_variants(
+                    &old_schema.name,
+                    old_variant,
+                    new_variant,
+                    &mut breaking_changes,
+                ),
+                None => breaking_changes.push(BreakingChange::SchemaVariantRemoved {
+                    schema: old_schema.name.clone(),
+                    variant: old_variant.name.clone(),
+                }),
+            }
+        }
+    }
+
+    Ok(CompatibilityReport {
+        version_change: VersionChange::between(
+            old_metadata.semver().as_ref(),
+            new_metadata.semver().as_ref(),
+        ),
+        old_version: old_metadata.version().to_string(),
+        new_version: new_metadata.version().to_string(),
+        breaking_changes,
+    })
+}
+
+/// Whether two funcs take the same arguments, in any order, and return the same type.
+fn same_signature(old: &FuncSpec, new: &FuncSpec) -> bool {
+    old.response_type.as_ref() == new.response_type.as_ref()
+        && old.arguments.len() == new.arguments.len()
+        && old.arguments.iter().all(|old_argument| {
+            new.arguments.iter().any(|new_argument| {
+                old_argument.name == new_argument.name
+                    && old_argument.kind == new_argument.kind
+                    && old_argument.element_kind == new_argument.element_kind
+            })
+        })
+}
+
+fn compare_variants(
+    schema: &str,
+    old: &SchemaVariantSpec,
+    new: &SchemaVariantSpec,
+    breaking_changes: &mut Vec<BreakingChange>,
+) {
+    for old_socket in &old.sockets {
+        let same_name = || new.sockets.iter().filter(|s| s.name == old_socket.name);
+        match same_name()
+            .find(|s| s.kind == old_socket.kind)
+            .or_else(|| same_name().next())
+        {
+            None => breaking_changes.push(BreakingChange::SocketRemoved {
+                schema: schema.to_string(),
+                variant: old.name.clone(),
+                socket: old_socket.name.clone(),
+            }),
+            Some(new_socket) if new_socket.kind != old_socket.kind => {
+                breaking_changes.push(BreakingChange::SocketKindChanged {
+                    schema: schema.to_string(),
+                    variant: old.name.clone(),
+                    socket: old_socket.name.clone(),
+                    old_kind: old_socket.kind,
+                    new_kind: new_socket.kind,
+                })
+            }
+            Some(new_socket) if new_socket.arity != old_socket.arity => {
+                breaking_changes.push(BreakingChange::SocketArityChanged {
+                    schema: schema.to_string(),
+                    variant: old.name.clone(),
+                    socket: old_socket.name.clone(),
+                    old_arity: old_socket.arity,
+                    new_arity: new_socket.arity,
+                })
+            }
+            Some(_) => {}
+        }
+    }
+
+    for (old_root, new_root) in [
+        (&old.domain, &new.domain),
+        (&old.resource_value, &new.resource_value),
+    ] {
+        let mut new_props = HashMap::new();
+        collect_prop_kinds(new_root, "", &mut new_props);
+
+        let mut removed_or_changed = Vec::new();
+        compare_props(old_root, "", &new_props, &mut removed_or_changed);
+        for (path, new_kind) in removed_or_changed {
+            breaking_changes.push(match new_kind {
+                None => BreakingChange::PropRemoved {
+                    schema: schema.to_string(),
+                    variant: old.name.clone(),
+                    path,
+                },
+                Some((old_kind, new_kind)) => BreakingChange::PropKindChanged {
+                    schema: schema.to_string(),
+                    variant: old.name.clone(),
+                    path,
+                    old_kind,
+                    new_kind,
+                },
+            });
+        }
+    }
+}
+
+fn collect_prop_kinds(
+    prop: &PropSpec,
+    parent_path: &str,
+    kinds: &mut HashMap<String, PropSpecKind>,
+) {
+    let path = format!("{parent_path}/{}", prop_name(prop));
+    for child in prop_children(prop) {
+        collect_prop_kinds(child, &path, kinds);
+    }
+    kinds.insert(path, prop_kind(prop));
+}
+
+/// Records the path of each old prop that is gone or of another kind, along with its old and new
+/// kinds, without descending into it.
+fn compare_props(
+    old: &PropSpec,
+    parent_path: &str,
+    new_kinds: &HashMap<String, PropSpecKind>,
+    removed_or_changed: &mut Vec<(String, Option<(PropSpecKind, PropSpecKind)>)>,
+) {
+    let path = format!("{parent_path}/{}", prop_name(old));
+    let old_kind = prop_kind(old);
+    match new_kinds.get(&path) {
+        None => removed_or_changed.push((path, None)),
+        Some(new_kind) if *new_kind != old_kind => {
+            removed_or_changed.push((path, Some((old_kind, *new_kind))))
+        }
+        Some(_) => {
+            for child in prop_children(old) {
+                compare_props(child, &path, new_kinds, removed_or_changed);
+            }
+        }
+    }
+}
+
+fn prop_name(prop: &PropSpec) -> &str {
+    match prop {
+        PropSpec::Array { name, .. }
+        | PropSpec::Boolean { name, .. }
+        | PropSpec::Map { name, .. }
+        | PropSpec::Number { name, .. }
+        | PropSpec::Object { name, .. }
+        | PropSpec::String { name, .. } => name,
+    }
+}
+
+fn prop_kind(prop: &PropSpec) -> PropSpecKind {
+    match prop {
+        PropSpec::Array { .. } => PropSpecKind::Array,
+        PropSpec::Boolean { .. } => PropSpecKind::Boolean,
+        PropSpec::Map { .. } => PropSpecKind::Map,
+        PropSpec::Number { .. } => PropSpecKind::Number,
+        PropSpec::Object { .. } => PropSpecKind::Object,
+        PropSpec::String { .. } => PropSpecKind::String,
+    }
+}
+
+fn prop_children(prop: &PropSpec) -> Vec<&PropSpec> {
+    match prop {
+        PropSpec::Array { type_prop, .. } | PropSpec::Map { type_prop, .. } => {
+            vec![type_prop.as_ref()]
+        }
+        PropSpec::Object { entries, .. } => entries.iter().collect(),
+        PropSpec::Boolean { .. } | PropSpec::Number { .. } | PropSpec::String { .. } => vec![],
+    }
+}
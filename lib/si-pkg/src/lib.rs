@@ -1,19 +1,27 @@
+mod compatibility;
 pub(crate) mod node;
 mod pkg;
+mod resolve;
 mod spec;
 
+pub use compatibility::{
+    is_compatible_upgrade, BreakingChange, CompatibilityReport, VersionChange,
+};
 pub use pkg::{
-    SiPkg, SiPkgActionFunc, SiPkgAttrFuncInput, SiPkgAttrFuncInputView, SiPkgError, SiPkgFunc,
-    SiPkgFuncDescription, SiPkgLeafFunction, SiPkgMapKeyFunc, SiPkgMetadata, SiPkgProp,
-    SiPkgSchema, SiPkgSchemaVariant, SiPkgSocket, SiPkgValidation,
+    PkgDiff, PkgDiffChange, PkgDiffEntry, PkgDiffEntryKind, SiPkg, SiPkgActionFunc,
+    SiPkgAttrFuncInput, SiPkgAttrFuncInputView, SiPkgAuthenticationFunc, SiPkgDependency,
+    SiPkgError, SiPkgFunc, SiPkgFuncDescription, SiPkgLeafFunction, SiPkgMapKeyFunc, SiPkgMetadata,
+    SiPkgProp, SiPkgSchema, SiPkgSchemaTree, SiPkgSchemaVariant, SiPkgSocket, SiPkgValidation,
 };
+pub use resolve::{resolve_install_order, DependencyProblem, DependencyResolution};
 pub use spec::{
     ActionFuncSpec, ActionFuncSpecBuilder, ActionFuncSpecKind, AttrFuncInputSpec,
-    AttrFuncInputSpecKind, FuncArgumentKind, FuncArgumentSpec, FuncArgumentSpecBuilder,
-    FuncDescriptionSpec, FuncDescriptionSpecBuilder, FuncSpec, FuncSpecBackendKind,
-    FuncSpecBackendResponseType, FuncUniqueId, LeafFunctionSpec, LeafFunctionSpecBuilder,
-    LeafInputLocation, LeafKind, MapKeyFuncSpec, MapKeyFuncSpecBuilder, PkgSpec, PkgSpecBuilder,
-    PropSpec, PropSpecBuilder, PropSpecKind, PropSpecWidgetKind, SchemaSpec, SchemaSpecBuilder,
+    AttrFuncInputSpecKind, AuthenticationFuncSpec, AuthenticationFuncSpecBuilder, FuncArgumentKind,
+    FuncArgumentSpec, FuncArgumentSpecBuilder, FuncDescriptionSpec, FuncDescriptionSpecBuilder,
+    FuncSpec, FuncSpecBackendKind, FuncSpecBackendResponseType, FuncUniqueId, LeafFunctionSpec,
+    LeafFunctionSpecBuilder, LeafInputLocation, LeafKind, MapKeyFuncSpec, MapKeyFuncSpecBuilder,
+    PkgDependencySpec, PkgDependencySpecBuilder, PkgSpec, PkgSpecBuilder, PropSpec,
+    PropSpecBuilder, PropSpecKind, PropSpecWidgetKind, SchemaSpec, SchemaSpecBuilder,
     SchemaVariantSpec, SchemaVariantSpecBuilder, SchemaVariantSpecComponentType,
     SchemaVariantSpecPropRoot, SiPropFuncSpec, SiPropFuncSpecBuilder, SiPropFuncSpecKind,
     SocketSpec, SocketSpecArity, SocketSpecKind, SpecError, ValidationSpec, ValidationSpecKind,
@@ -142,4 +150,494 @@ mod tests {
 
         let _ = dbg!(props.lock().await);
     }
+
+    #[tokio::test]
+    async fn signed_pkg_round_trip() {
+        let spec: PkgSpec = serde_json::from_str(PACKAGE_JSON).unwrap();
+        let pkg = SiPkg::load_from_spec(spec).expect("failed to load spec");
+        let (public_key, secret_key) = sodiumoxide::crypto::sign::gen_keypair();
+        let (other_public_key, other_secret_key) = sodiumoxide::crypto::sign::gen_keypair();
+
+        assert!(matches!(
+            pkg.verify(&[public_key]),
+            Err(SiPkgError::Unsigned)
+        ));
+
+        let signed_pkg = pkg.sign(&secret_key).expect("failed to sign pkg");
+
+        // Signing adds a node to the root, but leaves the hashes of the contents alone
+        let func_hashes = |pkg: &SiPkg| -> Vec<_> {
+            pkg.funcs()
+                .expect("failed to get funcs")
+                .iter()
+                .map(SiPkgFunc::hash)
+                .collect()
+        };
+        assert_eq!(func_hashes(&pkg), func_hashes(&signed_pkg));
+
+        let pkg_data = signed_pkg
+            .write_to_bytes()
+            .expect("failed to serialize pkg");
+        let read_pkg = SiPkg::load_from_bytes(pkg_data).expect("failed to load pkg from bytes");
+
+        assert_eq!(
+            public_key,
+            read_pkg
+                .verify(&[public_key])
+                .expect("failed to verify pkg")
+        );
+        assert!(matches!(
+            read_pkg.verify(&[other_public_key]),
+            Err(SiPkgError::NoTrustedSignature)
+        ));
+        assert_eq!(
+            pkg.funcs().expect("failed to get funcs").len(),
+            read_pkg.funcs().expect("failed to get funcs").len()
+        );
+
+        let twice_signed_pkg = read_pkg
+            .sign(&other_secret_key)
+            .expect("failed to sign pkg again");
+        assert_eq!(2, twice_signed_pkg.signed_by().len());
+        assert_eq!(
+            public_key,
+            twice_signed_pkg
+                .verify(&[public_key])
+                .expect("first signature no longer verifies")
+        );
+    }
+
+    fn pkg_with_dependencies(name: &str, version: &str, dependencies: &[(&str, &str)]) -> SiPkg {
+        let mut builder = PkgSpec::builder();
+        builder
+            .name(name)
+            .version(version)
+            .created_by("sally@systeminit.com");
+        for (dependency_name, version_req) in dependencies {
+            builder.dependency(
+                PkgDependencySpec::builder()
+                    .name(*dependency_name)
+                    .version_req(*version_req)
+                    .build()
+                    .expect("failed to build dependency"),
+            );
+        }
+
+        SiPkg::load_from_spec(builder).expect("failed to load spec")
+    }
+
+    #[tokio::test]
+    async fn resolves_install_order() {
+        let base = pkg_with_dependencies("base", "1.2.0", &[]);
+        let app = pkg_with_dependencies("app", "0.1.0", &[("base", "^1.1")]);
+        let plugin = pkg_with_dependencies("plugin", "0.1.0", &[("app", "*")]);
+        let orphan = pkg_with_dependencies("orphan", "2023-05-23", &[("missing", "*")]);
+        let stale = pkg_with_dependencies("stale", "0.1.0", &[("base", "^2")]);
+
+        let read_app = SiPkg::load_from_bytes(app.write_to_bytes().expect("failed to write pkg"))
+            .expect("failed to read pkg");
+        let dependencies = read_app.dependencies().expect("failed to get dependencies");
+        assert_eq!(1, dependencies.len());
+        assert_eq!("base", dependencies[0].name());
+        assert_eq!("^1.1", dependencies[0].version_req());
+
+        let resolution = resolve_install_order(vec![plugin, orphan, read_app, stale, base])
+            .expect("failed to resolve");
+
+        let install_order: Vec<String> = resolution
+            .install_order()
+            .iter()
+            .map(|pkg| pkg.metadata().expect("get metadata").name().to_string())
+            .collect();
+        assert_eq!(vec!["base", "app", "plugin"], install_order);
+        assert!(!resolution.is_resolved());
+        assert_eq!(2, resolution.problems().len());
+        assert!(resolution.problems().iter().any(|problem| matches!(
+            problem,
+            DependencyProblem::Missing { package, .. } if package == "orphan"
+        )));
+        assert!(resolution.problems().iter().any(|problem| matches!(
+            problem,
+            DependencyProblem::Conflicting { package, found_versions, .. }
+                if package == "stale" && found_versions == &vec!["1.2.0".to_string()]
+        )));
+    }
+
+    #[tokio::test]
+    async fn reports_breaking_changes() {
+        let mut old_spec: PkgSpec = serde_json::from_str(PACKAGE_JSON).unwrap();
+        old_spec.schemas[0].variants[0].sockets.push(
+            SocketSpec::builder()
+                .name("image")
+                .kind(SocketSpecKind::Input)
+                .arity(SocketSpecArity::One)
+                .build()
+                .expect("failed to build socket"),
+        );
+        let old = SiPkg::load_from_spec(old_spec.clone()).expect("failed to load spec");
+
+        let same_report = is_compatible_upgrade(&old, &old)
+            .await
+            .expect("failed to compare pkgs");
+        assert!(same_report.is_compatible());
+        assert_eq!(VersionChange::None, same_report.version_change());
+
+        let mut new_spec = old_spec;
+        new_spec.version = "12.12.0".to_string();
+        new_spec.funcs[0].arguments.pop();
+        new_spec.schemas[0].variants[0].sockets[0].arity = SocketSpecArity::Many;
+        if let PropSpec::Object { entries, .. } = &mut new_spec.schemas[0].variants[0].domain {
+            entries
+                .retain(|entry| !matches!(entry, PropSpec::String { name, .. } if name == "kind"));
+        }
+        let new = SiPkg::load_from_spec(new_spec).expect("failed to load spec");
+
+        let report = is_compatible_upgrade(&old, &new)
+            .await
+            .expect("failed to compare pkgs");
+        assert_eq!(VersionChange::Minor, report.version_change());
+        assert!(!report.is_version_bump_sufficient());
+        assert_eq!(
+            vec![
+                BreakingChange::FuncSignatureChanged {
+                    func: "si:truthy".to_string()
+                },
+                BreakingChange::SocketArityChanged {
+                    schema: "k8sDeployment".to_string(),
+                    variant: "v0".to_string(),
+                    socket: "image".to_string(),
+                    old_arity: SocketSpecArity::One,
+                    new_arity: SocketSpecArity::Many,
+                },
+                BreakingChange::PropRemoved {
+                    schema: "k8sDeployment".to_string(),
+                    variant: "v0".to_string(),
+                    path: "/domain/kind".to_string(),
+                },
+            ],
+            report.breaking_changes()
+        );
+    }
+
+    #[tokio::test]
+    async fn diffs_pkgs() {
+        let old_spec: PkgSpec = serde_json::from_str(PACKAGE_JSON).unwrap();
+        let old = SiPkg::load_from_spec(old_spec.clone()).expect("failed to load spec");
+
+        assert!(old.diff(&old).expect("failed to diff pkgs").is_empty());
+
+        let mut new_spec = old_spec;
+        new_spec.funcs.retain(|func| func.name != "si:falsey");
+        if let PropSpec::Object { entries, .. } = &mut new_spec.schemas[0].variants[0].domain {
+            entries
+                .retain(|entry| !matches!(entry, PropSpec::String { name, .. } if name == "kind"));
+        }
+        let new = SiPkg::load_from_spec(new_spec).expect("failed to load spec");
+
+        let diff = old.diff(&new).expect("failed to diff pkgs");
+        let changes: Vec<(PkgDiffEntryKind, Vec<&str>, PkgDiffChange)> = diff
+            .entries()
+            .iter()
+            .map(|entry| {
+                (
+                    entry.kind(),
+                    entry.path().iter().map(String::as_str).collect(),
+                    entry.change(),
+                )
+            })
+            .collect();
+        assert_eq!(
+            vec![
+                (
+                    PkgDiffEntryKind::Func,
+                    vec!["si:falsey"],
+                    PkgDiffChange::Removed
+                ),
+                (
+                    PkgDiffEntryKind::Schema,
+                    vec!["k8sDeployment"],
+                    PkgDiffChange::Changed
+                ),
+                (
+                    PkgDiffEntryKind::SchemaVariant,
+                    vec!["k8sDeployment", "v0"],
+                    PkgDiffChange::Changed
+                ),
+                (
+                    PkgDiffEntryKind::Prop,
+                    vec!["k8sDeployment", "v0", "domain"],
+                    PkgDiffChange::Changed
+                ),
+                (
+                    PkgDiffEntryKind::Prop,
+                    vec!["k8sDeployment", "v0", "domain", "kind"],
+                    PkgDiffChange::Removed
+                ),
+            ],
+            changes
+        );
+
+        let removed = &diff.entries()[4];
+        assert!(removed.old_hash().is_some());
+        assert!(removed.new_hash().is_none());
+    }
+
+    #[tokio::test]
+    async fn spec_text_round_trip() {
+        let spec = PkgSpec::from_json(PACKAGE_JSON).expect("failed to parse json");
+        let hash = SiPkg::load_from_spec(spec.clone())
+            .expect("failed to load spec")
+            .hash()
+            .expect("failed to get hash");
+
+        let yaml = spec.to_yaml().expect("failed to write yaml");
+        let yaml_spec = PkgSpec::from_yaml(&yaml).expect("failed to parse yaml");
+        assert_eq!(yaml, yaml_spec.to_yaml().expect("failed to write yaml"));
+        let yaml_pkg = SiPkg::load_from_spec(yaml_spec).expect("failed to load spec");
+        assert_eq!(hash, yaml_pkg.hash().expect("failed to get hash"));
+
+        let json = spec.to_json().expect("failed to write json");
+        let json_spec = PkgSpec::from_json(&json).expect("failed to parse json");
+        assert_eq!(json, json_spec.to_json().expect("failed to write json"));
+        let json_pkg = SiPkg::load_from_spec(json_spec).expect("failed to load spec");
+        assert_eq!(hash, json_pkg.hash().expect("failed to get hash"));
+    }
+
+    #[tokio::test]
+    async fn opened_pkg_reads_schemas_on_demand() {
+        let spec: PkgSpec = serde_json::from_str(PACKAGE_JSON).unwrap();
+        let pkg = SiPkg::load_from_spec(spec).expect("failed to load spec");
+        let pkg_data = pkg.write_to_bytes().expect("failed to serialize pkg");
+
+        let mut file = tempfile::NamedTempFile::new().expect("failed to create temp file");
+        std::io::Write::write_all(&mut file, &pkg_data).expect("failed to write pkg");
+
+        let opened = SiPkg::open(file.path()).expect("failed to open pkg");
+        assert!(!opened.is_hydrated());
+        assert_eq!(
+            pkg.hash().expect("failed to get hash"),
+            opened.hash().expect("failed to get hash")
+        );
+        assert_eq!(2, opened.funcs().expect("failed to get funcs").len());
+        assert!(matches!(opened.schemas(), Err(SiPkgError::NotHydrated)));
+
+        let schema_trees: Vec<SiPkgSchemaTree> = opened
+            .schema_trees()
+            .expect("failed to get schema trees")
+            .collect::<Result<_, _>>()
+            .expect("failed to read schema tree");
+        assert_eq!(1, schema_trees.len());
+        let schema = schema_trees[0].schema().expect("failed to get schema");
+        assert_eq!("k8sDeployment", schema.name());
+        assert_eq!(1, schema.variants().expect("failed to get variants").len());
+
+        let hydrated = opened.hydrate().expect("failed to hydrate pkg");
+        assert!(hydrated.is_hydrated());
+        assert_eq!(1, hydrated.schemas().expect("failed to get schemas").len());
+        let written =
+            SiPkg::load_from_bytes(opened.write_to_bytes().expect("failed to serialize pkg"))
+                .expect("failed to load pkg from bytes");
+        assert_eq!(
+            pkg.hash().expect("failed to get hash"),
+            written.hash().expect("failed to get hash")
+        );
+    }
+
+    #[tokio::test]
+    async fn compressed_pkg_round_trip() {
+        let mut spec: PkgSpec = serde_json::from_str(PACKAGE_JSON).unwrap();
+        spec.funcs[0].code_base64 = "Y29uc3QgeCA9IDE7Cg".repeat(1024);
+        let pkg = SiPkg::load_from_spec(spec).expect("failed to load spec");
+        let hash = pkg.hash().expect("failed to get hash");
+
+        let plain_data = pkg.write_to_bytes().expect("failed to serialize pkg");
+        let compressed_data = pkg
+            .write_to_bytes_compressed()
+            .expect("failed to serialize pkg");
+        assert!(compressed_data.len() < plain_data.len());
+
+        let read_pkg =
+            SiPkg::load_from_bytes(compressed_data.clone()).expect("failed to load pkg from bytes");
+        assert_eq!(hash, read_pkg.hash().expect("failed to get hash"));
+        let funcs = read_pkg.funcs().expect("failed to get funcs");
+        assert_eq!("Y29uc3QgeCA9IDE7Cg".repeat(1024), funcs[0].code_base64());
+
+        let mut file = tempfile::NamedTempFile::new().expect("failed to create temp file");
+        std::io::Write::write_all(&mut file, &compressed_data).expect("failed to write pkg");
+        let opened = SiPkg::open(file.path()).expect("failed to open pkg");
+        assert_eq!(hash, opened.hash().expect("failed to get hash"));
+        let spec = opened.to_spec().await.expect("failed to get spec");
+        assert_eq!(1, spec.schemas.len());
+        assert_eq!("Y29uc3QgeCA9IDE7Cg".repeat(1024), spec.funcs[0].code_base64);
+    }
+
+    #[tokio::test]
+    async fn validations_round_trip() {
+        let validations = vec![
+            ValidationSpec::StringLengthIsBetweenTwoIntegers {
+                lower_bound: 1,
+                upper_bound: 63,
+            },
+            ValidationSpec::StringMatchesRegex {
+                expected: "^[A-Z][A-Za-z]*$".to_string(),
+            },
+        ];
+
+        let mut spec: PkgSpec = serde_json::from_str(PACKAGE_JSON).unwrap();
+        if let PropSpec::Object { entries, .. } = &mut spec.schemas[0].variants[0].domain {
+            for entry in entries.iter_mut() {
+                if let PropSpec::String {
+                    name,
+                    validations: entry_validations,
+                    ..
+                } = entry
+                {
+                    if name == "kind" {
+                        *entry_validations = Some(validations.clone());
+                    }
+                }
+            }
+        }
+        let pkg = SiPkg::load_from_spec(spec).expect("failed to load spec");
+        let read_pkg = SiPkg::load_from_bytes(pkg.write_to_bytes().expect("failed to write pkg"))
+            .expect("failed to load pkg from bytes");
+
+        let read_spec = read_pkg.to_spec().await.expect("failed to get spec");
+        let kind_validations = match &read_spec.schemas[0].variants[0].domain {
+            PropSpec::Object { entries, .. } => entries.iter().find_map(|entry| match entry {
+                PropSpec::String {
+                    name, validations, ..
+                } if name == "kind" => validations.clone(),
+                _ => None,
+            }),
+            _ => None,
+        };
+        assert_eq!(Some(validations), kind_validations);
+    }
+
+    #[tokio::test]
+    async fn map_and_array_defaults_round_trip() {
+        let string_prop = |name: &str| {
+            PropSpec::builder()
+                .name(name)
+                .kind(PropSpecKind::String)
+                .build()
+                .expect("failed to build string prop")
+        };
+
+        let tags = PropSpec::builder()
+            .name("tags")
+            .kind(PropSpecKind::Map)
+            .type_prop(string_prop("tag"))
+            .default_entry("owner", "ops")
+            .default_entry("env", "prod")
+            .build()
+            .expect("failed to build map prop");
+        let zones = PropSpec::builder()
+            .name("zones")
+            .kind(PropSpecKind::Array)
+            .type_prop(string_prop("zone"))
+            .default_item("us-east-2b")
+            .default_item("us-east-2a")
+            .build()
+            .expect("failed to build array prop");
+
+        assert!(PropSpec::builder()
+            .name("tags")
+            .kind(PropSpecKind::Map)
+            .type_prop(string_prop("tag"))
+            .default_entry("owner", 1)
+            .build()
+            .is_err());
+        assert!(PropSpec::builder()
+            .name("zones")
+            .kind(PropSpecKind::Array)
+            .type_prop(string_prop("zone"))
+            .default_value(serde_json::json!({ "zone": "us-east-2a" }))
+            .build()
+            .is_err());
+
+        let mut spec: PkgSpec = serde_json::from_str(PACKAGE_JSON).unwrap();
+        if let PropSpec::Object { entries, .. } = &mut spec.schemas[0].variants[0].domain {
+            entries.push(tags);
+            entries.push(zones);
+        }
+        let pkg = SiPkg::load_from_spec(spec).expect("failed to load spec");
+        let read_pkg = SiPkg::load_from_bytes(pkg.write_to_bytes().expect("failed to write pkg"))
+            .expect("failed to load pkg from bytes");
+
+        let read_spec = read_pkg.to_spec().await.expect("failed to get spec");
+        let entries = match &read_spec.schemas[0].variants[0].domain {
+            PropSpec::Object { entries, .. } => entries,
+            _ => panic!("domain should be an object"),
+        };
+        let default_of = |prop_name: &str| {
+            entries.iter().find_map(|entry| match entry {
+                PropSpec::Map {
+                    name,
+                    default_value,
+                    ..
+                }
+                | PropSpec::Array {
+                    name,
+                    default_value,
+                    ..
+                } if name == prop_name => default_value.clone(),
+                _ => None,
+            })
+        };
+
+        let tags_default = default_of("tags").expect("tags should have a default value");
+        let tag_keys: Vec<&String> = tags_default
+            .as_object()
+            .expect("tags default should be an object")
+            .keys()
+            .collect();
+        assert_eq!(vec!["owner", "env"], tag_keys);
+        assert_eq!(
+            Some(serde_json::json!(["us-east-2b", "us-east-2a"])),
+            default_of("zones")
+        );
+    }
+
+    #[tokio::test]
+    async fn authentication_funcs_round_trip() {
+        let mut spec: PkgSpec = serde_json::from_str(PACKAGE_JSON).unwrap();
+        let first = spec.funcs[0].unique_id;
+        let second = spec.funcs[1].unique_id;
+        spec.schemas[0].variants[0].authentication_funcs = vec![
+            AuthenticationFuncSpec::builder()
+                .func_unique_id(second)
+                .position(1)
+                .build()
+                .expect("failed to build authentication func"),
+            AuthenticationFuncSpec::builder()
+                .func_unique_id(first)
+                .position(0)
+                .build()
+                .expect("failed to build authentication func"),
+        ];
+
+        let pkg = SiPkg::load_from_spec(spec).expect("failed to load spec");
+        let read_pkg = SiPkg::load_from_bytes(pkg.write_to_bytes().expect("failed to write pkg"))
+            .expect("failed to load pkg from bytes");
+
+        let schemas = read_pkg.schemas().expect("failed to get schemas");
+        let variants = schemas[0].variants().expect("failed to get variants");
+        let authentication_funcs: Vec<_> = variants[0]
+            .authentication_funcs()
+            .expect("failed to get authentication funcs")
+            .iter()
+            .map(|func| (func.func_unique_id(), func.position()))
+            .collect();
+        assert_eq!(vec![(first, 0), (second, 1)], authentication_funcs);
+
+        let read_spec = read_pkg.to_spec().await.expect("failed to get spec");
+        let spec_funcs: Vec<_> = read_spec.schemas[0].variants[0]
+            .authentication_funcs
+            .iter()
+            .map(|func| func.func_unique_id)
+            .collect();
+        assert_eq!(vec![first, second], spec_funcs);
+    }
 }
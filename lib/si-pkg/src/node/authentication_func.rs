@@ -0,0 +1,70 @@
+use std::{
+    io::{BufRead, Write},
+    str::FromStr,
+};
+
+use object_tree::{
+    read_key_value_line, write_key_value_line, GraphError, NodeChild, NodeKind, NodeWithChildren,
+    ReadBytes, WriteBytes,
+};
+
+use crate::{AuthenticationFuncSpec, FuncUniqueId};
+
+use super::PkgNode;
+
+const KEY_FUNC_UNIQUE_ID_STR: &str = "func_unique_id";
+const KEY_POSITION_STR: &str = "position";
+
+#[derive(Clone, Debug)]
+pub struct AuthenticationFuncNode {
+    pub func_unique_id: FuncUniqueId,
+    pub position: i64,
+}
+
+impl WriteBytes for AuthenticationFuncNode {
+    fn write_bytes<W: Write>(&self, writer: &mut W) -> Result<(), GraphError> {
+        write_key_value_line(
+            writer,
+            KEY_FUNC_UNIQUE_ID_STR,
+            self.func_unique_id.to_string(),
+        )?;
+
+        write_key_value_line(writer, KEY_POSITION_STR, self.position)?;
+
+        Ok(())
+    }
+}
+
+impl ReadBytes for AuthenticationFuncNode {
+    fn read_bytes<R: BufRead>(reader: &mut R) -> Result<Self, GraphError>
+    where
+        Self: std::marker::Sized,
+    {
+        let func_unique_id_str = read_key_value_line(reader, KEY_FUNC_UNIQUE_ID_STR)?;
+        let func_unique_id =
+            FuncUniqueId::from_str(&func_unique_id_str).map_err(GraphError::parse)?;
+
+        let position_str = read_key_value_line(reader, KEY_POSITION_STR)?;
+        let position = i64::from_str(&position_str).map_err(GraphError::parse)?;
+
+        Ok(Self {
+            func_unique_id,
+            position,
+        })
+    }
+}
+
+impl NodeChild for AuthenticationFuncSpec {
+    type NodeType = PkgNode;
+
+    fn as_node_with_children(&self) -> NodeWithChildren<Self::NodeType> {
+        NodeWithChildren::new(
+            NodeKind::Leaf,
+            Self::NodeType::AuthenticationFunc(AuthenticationFuncNode {
+                func_unique_id: self.func_unique_id,
+                position: self.position,
+            }),
+            vec![],
+        )
+    }
+}
@@ -6,12 +6,13 @@ use object_tree::{
 };
 use serde::{Deserialize, Serialize};
 
-use crate::{FuncSpec, SchemaSpec};
+use crate::{FuncSpec, PkgDependencySpec, SchemaSpec};
 
 use super::PkgNode;
 
 const CATEGORY_TYPE_SCHEMAS: &str = "schemas";
 const CATEGORY_TYPE_FUNCS: &str = "funcs";
+const CATEGORY_TYPE_DEPENDENCIES: &str = "dependencies";
 
 const KEY_KIND_STR: &str = "kind";
 
@@ -19,6 +20,7 @@ const KEY_KIND_STR: &str = "kind";
 #[derive(Debug, Deserialize, Serialize)]
 #[serde(rename_all = "camelCase")]
 pub enum PackageCategory {
+    Dependencies(Vec<PkgDependencySpec>),
     Funcs(Vec<FuncSpec>),
     Schemas(Vec<SchemaSpec>),
 }
@@ -26,6 +28,7 @@ pub enum PackageCategory {
 #[remain::sorted]
 #[derive(Clone, Copy, Debug, Serialize, PartialEq, Eq)]
 pub enum CategoryNode {
+    Dependencies,
     Funcs,
     Schemas,
 }
@@ -35,6 +38,7 @@ impl CategoryNode {
         match self {
             Self::Schemas => CATEGORY_TYPE_SCHEMAS,
             Self::Funcs => CATEGORY_TYPE_FUNCS,
+            Self::Dependencies => CATEGORY_TYPE_DEPENDENCIES,
         }
     }
 }
@@ -44,6 +48,7 @@ impl NameStr for CategoryNode {
         match self {
             Self::Schemas => CATEGORY_TYPE_SCHEMAS,
             Self::Funcs => CATEGORY_TYPE_FUNCS,
+            Self::Dependencies => CATEGORY_TYPE_DEPENDENCIES,
         }
     }
 }
@@ -65,6 +70,7 @@ impl ReadBytes for CategoryNode {
         let node = match kind_str.as_str() {
             CATEGORY_TYPE_SCHEMAS => Self::Schemas,
             CATEGORY_TYPE_FUNCS => Self::Funcs,
+            CATEGORY_TYPE_DEPENDENCIES => Self::Dependencies,
             invalid_kind => {
                 return Err(GraphError::parse_custom(format!(
                     "invalid package category node kind: {invalid_kind}"
@@ -109,6 +115,20 @@ impl NodeChild for PackageCategory {
                     children,
                 )
             }
+            Self::Dependencies(entries) => {
+                let mut children = Vec::new();
+                for entry in entries {
+                    children
+                        .push(Box::new(entry.clone())
+                            as Box<dyn NodeChild<NodeType = Self::NodeType>>);
+                }
+
+                NodeWithChildren::new(
+                    NodeKind::Tree,
+                    Self::NodeType::Category(CategoryNode::Dependencies),
+                    children,
+                )
+            }
         }
     }
 }
@@ -6,7 +6,9 @@ use object_tree::{
 
 mod action_func;
 mod attr_func_input;
+mod authentication_func;
 mod category;
+mod dependency;
 mod func;
 mod func_argument;
 mod func_description;
@@ -19,13 +21,16 @@ mod schema;
 mod schema_variant;
 mod schema_variant_child;
 mod si_prop_func;
+mod signature;
 mod socket;
 mod validation;
 
 pub(crate) use self::{
     action_func::ActionFuncNode,
     attr_func_input::AttrFuncInputNode,
+    authentication_func::AuthenticationFuncNode,
     category::CategoryNode,
+    dependency::DependencyNode,
     func::FuncNode,
     func_argument::FuncArgumentNode,
     func_description::FuncDescriptionNode,
@@ -38,13 +43,16 @@ pub(crate) use self::{
     schema_variant::SchemaVariantNode,
     schema_variant_child::{SchemaVariantChild, SchemaVariantChildNode},
     si_prop_func::SiPropFuncNode,
+    signature::SignatureNode,
     socket::SocketNode,
     validation::ValidationNode,
 };
 
 const NODE_KIND_ACTION_FUNC: &str = "action_func";
 const NODE_KIND_ATTR_FUNC_INPUT: &str = "attr_func_input";
+const NODE_KIND_AUTHENTICATION_FUNC: &str = "authentication_func";
 const NODE_KIND_CATEGORY: &str = "category";
+const NODE_KIND_DEPENDENCY: &str = "dependency";
 const NODE_KIND_FUNC: &str = "func";
 const NODE_KIND_FUNC_ARGUMENT: &str = "func_argument";
 const NODE_KIND_FUNC_DESCRIPTION: &str = "func_description";
@@ -58,6 +66,7 @@ const NODE_KIND_SCHEMA_VARIANT: &str = "schema_variant";
 const NODE_KIND_SCHEMA_VARIANT_CHILD: &str = "schema_variant_child";
 const NODE_KIND_SOCKET: &str = "socket";
 const NODE_KIND_SI_PROP_FUNC: &str = "si_prop_func";
+const NODE_KIND_SIGNATURE: &str = "signature";
 const NODE_KIND_VALIDATION: &str = "validation";
 
 const KEY_NODE_KIND_STR: &str = "node_kind";
@@ -67,7 +76,9 @@ const KEY_NODE_KIND_STR: &str = "node_kind";
 pub enum PkgNode {
     ActionFunc(ActionFuncNode),
     AttrFuncInput(AttrFuncInputNode),
+    AuthenticationFunc(AuthenticationFuncNode),
     Category(CategoryNode),
+    Dependency(DependencyNode),
     Func(FuncNode),
     FuncArgument(FuncArgumentNode),
     FuncDescription(FuncDescriptionNode),
@@ -79,6 +90,7 @@ pub enum PkgNode {
     Schema(SchemaNode),
     SchemaVariant(SchemaVariantNode),
     SchemaVariantChild(SchemaVariantChildNode),
+    Signature(SignatureNode),
     SiPropFunc(SiPropFuncNode),
     Socket(SocketNode),
     Validation(ValidationNode),
@@ -87,7 +99,9 @@ pub enum PkgNode {
 impl PkgNode {
     pub const ACTION_FUNC_KIND_STR: &str = NODE_KIND_ACTION_FUNC;
     pub const ATTR_FUNC_INPUT_KIND_STR: &str = NODE_KIND_ATTR_FUNC_INPUT;
+    pub const AUTHENTICATION_FUNC_KIND_STR: &str = NODE_KIND_AUTHENTICATION_FUNC;
     pub const CATEGORY_KIND_STR: &str = NODE_KIND_CATEGORY;
+    pub const DEPENDENCY_KIND_STR: &str = NODE_KIND_DEPENDENCY;
     pub const FUNC_KIND_STR: &str = NODE_KIND_FUNC;
     pub const FUNC_ARGUMENT_KIND_STR: &str = NODE_KIND_FUNC_ARGUMENT;
     pub const FUNC_DESCRIPTION_KIND_STR: &str = NODE_KIND_FUNC_DESCRIPTION;
@@ -101,12 +115,15 @@ impl PkgNode {
     pub const SCHEMA_VARIANT_KIND_CHILD_STR: &str = NODE_KIND_SCHEMA_VARIANT_CHILD;
     pub const SOCKET_KIND_STR: &str = NODE_KIND_SOCKET;
     pub const SI_PROP_FUNC_KIND_STR: &str = NODE_KIND_SI_PROP_FUNC;
+    pub const SIGNATURE_KIND_STR: &str = NODE_KIND_SIGNATURE;
     pub const VALIDATION_KIND_STR: &str = NODE_KIND_VALIDATION;
 
     pub fn node_kind_str(&self) -> &'static str {
         match self {
             Self::AttrFuncInput(_) => NODE_KIND_ATTR_FUNC_INPUT,
+            Self::AuthenticationFunc(_) => NODE_KIND_AUTHENTICATION_FUNC,
             Self::Category(_) => NODE_KIND_CATEGORY,
+            Self::Dependency(_) => NODE_KIND_DEPENDENCY,
             Self::ActionFunc(_) => NODE_KIND_ACTION_FUNC,
             Self::Func(_) => NODE_KIND_FUNC,
             Self::FuncArgument(_) => NODE_KIND_FUNC_ARGUMENT,
@@ -121,6 +138,7 @@ impl PkgNode {
             Self::SchemaVariantChild(_) => NODE_KIND_SCHEMA_VARIANT_CHILD,
             Self::Socket(_) => NODE_KIND_SOCKET,
             Self::SiPropFunc(_) => NODE_KIND_SI_PROP_FUNC,
+            Self::Signature(_) => NODE_KIND_SIGNATURE,
             Self::Validation(_) => NODE_KIND_VALIDATION,
         }
     }
@@ -130,7 +148,9 @@ impl NameStr for PkgNode {
     fn name(&self) -> &str {
         match self {
             Self::AttrFuncInput(node) => node.name(),
+            Self::AuthenticationFunc(_) => NODE_KIND_AUTHENTICATION_FUNC,
             Self::Category(node) => node.name(),
+            Self::Dependency(node) => node.name(),
             Self::ActionFunc(_) => NODE_KIND_ACTION_FUNC,
             Self::Func(node) => node.name(),
             Self::FuncArgument(node) => node.name(),
@@ -145,6 +165,7 @@ impl NameStr for PkgNode {
             Self::SchemaVariantChild(node) => node.name(),
             Self::Socket(node) => node.name(),
             Self::SiPropFunc(_) => NODE_KIND_SI_PROP_FUNC,
+            Self::Signature(_) => NODE_KIND_SIGNATURE,
             Self::Validation(_) => NODE_KIND_VALIDATION,
         }
     }
@@ -156,7 +177,9 @@ impl WriteBytes for PkgNode {
 
         match self {
             Self::AttrFuncInput(node) => node.write_bytes(writer)?,
+            Self::AuthenticationFunc(node) => node.write_bytes(writer)?,
             Self::Category(node) => node.write_bytes(writer)?,
+            Self::Dependency(node) => node.write_bytes(writer)?,
             Self::ActionFunc(node) => node.write_bytes(writer)?,
             Self::Func(node) => node.write_bytes(writer)?,
             Self::FuncArgument(node) => node.write_bytes(writer)?,
@@ -171,6 +194,7 @@ impl WriteBytes for PkgNode {
             Self::SchemaVariantChild(node) => node.write_bytes(writer)?,
             Self::Socket(node) => node.write_bytes(writer)?,
             Self::SiPropFunc(node) => node.write_bytes(writer)?,
+            Self::Signature(node) => node.write_bytes(writer)?,
             Self::Validation(node) => node.write_bytes(writer)?,
         };
 
@@ -190,7 +214,11 @@ impl ReadBytes for PkgNode {
             NODE_KIND_ATTR_FUNC_INPUT => {
                 Self::AttrFuncInput(AttrFuncInputNode::read_bytes(reader)?)
             }
+            NODE_KIND_AUTHENTICATION_FUNC => {
+                Self::AuthenticationFunc(AuthenticationFuncNode::read_bytes(reader)?)
+            }
             NODE_KIND_CATEGORY => Self::Category(CategoryNode::read_bytes(reader)?),
+            NODE_KIND_DEPENDENCY => Self::Dependency(DependencyNode::read_bytes(reader)?),
             NODE_KIND_FUNC => Self::Func(FuncNode::read_bytes(reader)?),
             NODE_KIND_FUNC_ARGUMENT => Self::FuncArgument(FuncArgumentNode::read_bytes(reader)?),
             NODE_KIND_FUNC_DESCRIPTION => {
@@ -208,6 +236,7 @@ impl ReadBytes for PkgNode {
             }
             NODE_KIND_SOCKET => Self::Socket(SocketNode::read_bytes(reader)?),
             NODE_KIND_SI_PROP_FUNC => Self::SiPropFunc(SiPropFuncNode::read_bytes(reader)?),
+            NODE_KIND_SIGNATURE => Self::Signature(SignatureNode::read_bytes(reader)?),
             NODE_KIND_VALIDATION => Self::Validation(ValidationNode::read_bytes(reader)?),
             invalid_kind => {
                 return Err(GraphError::parse_custom(format!(
@@ -85,6 +85,8 @@ impl NodeChild for PkgSpec {
                     as Box<dyn NodeChild<NodeType = Self::NodeType>>,
                 Box::new(PackageCategory::Funcs(self.funcs.clone()))
                     as Box<dyn NodeChild<NodeType = Self::NodeType>>,
+                Box::new(PackageCategory::Dependencies(self.dependencies.clone()))
+                    as Box<dyn NodeChild<NodeType = Self::NodeType>>,
             ],
         )
     }
@@ -108,6 +108,9 @@ impl NodeChild for SchemaVariantSpec {
             vec![
                 Box::new(SchemaVariantChild::ActionFuncs(self.action_funcs.clone()))
                     as Box<dyn NodeChild<NodeType = Self::NodeType>>,
+                Box::new(SchemaVariantChild::AuthenticationFuncs(
+                    self.authentication_funcs.clone(),
+                )) as Box<dyn NodeChild<NodeType = Self::NodeType>>,
                 Box::new(SchemaVariantChild::Domain(self.domain.clone()))
                     as Box<dyn NodeChild<NodeType = Self::NodeType>>,
                 Box::new(SchemaVariantChild::ResourceValue(
@@ -7,12 +7,14 @@ use object_tree::{
 use serde::{Deserialize, Serialize};
 
 use crate::{
-    ActionFuncSpec, FuncDescriptionSpec, LeafFunctionSpec, PropSpec, SiPropFuncSpec, SocketSpec,
+    ActionFuncSpec, AuthenticationFuncSpec, FuncDescriptionSpec, LeafFunctionSpec, PropSpec,
+    SiPropFuncSpec, SocketSpec,
 };
 
 use super::PkgNode;
 
 const VARIANT_CHILD_TYPE_ACTION_FUNCS: &str = "action_funcs";
+const VARIANT_CHILD_TYPE_AUTHENTICATION_FUNCS: &str = "authentication_funcs";
 const VARIANT_CHILD_TYPE_DOMAIN: &str = "domain";
 const VARIANT_CHILD_TYPE_FUNC_DESCRIPTIONS: &str = "func_descriptions";
 const VARIANT_CHILD_TYPE_LEAF_FUNCTIONS: &str = "leaf_functions";
@@ -27,6 +29,7 @@ const KEY_KIND_STR: &str = "kind";
 #[serde(rename_all = "camelCase")]
 pub enum SchemaVariantChild {
     ActionFuncs(Vec<ActionFuncSpec>),
+    AuthenticationFuncs(Vec<AuthenticationFuncSpec>),
     Domain(PropSpec),
     FuncDescriptions(Vec<FuncDescriptionSpec>),
     LeafFunctions(Vec<LeafFunctionSpec>),
@@ -39,6 +42,7 @@ pub enum SchemaVariantChild {
 #[derive(Clone, Copy, Debug, Serialize, PartialEq, Eq)]
 pub enum SchemaVariantChildNode {
     ActionFuncs,
+    AuthenticationFuncs,
     Domain,
     FuncDescriptions,
     LeafFunctions,
@@ -51,6 +55,7 @@ impl SchemaVariantChildNode {
     pub fn kind_str(&self) -> &'static str {
         match self {
             Self::ActionFuncs => VARIANT_CHILD_TYPE_ACTION_FUNCS,
+            Self::AuthenticationFuncs => VARIANT_CHILD_TYPE_AUTHENTICATION_FUNCS,
             Self::Domain => VARIANT_CHILD_TYPE_DOMAIN,
             Self::FuncDescriptions => VARIANT_CHILD_TYPE_FUNC_DESCRIPTIONS,
             Self::LeafFunctions => VARIANT_CHILD_TYPE_LEAF_FUNCTIONS,
@@ -65,6 +70,7 @@ impl NameStr for SchemaVariantChildNode {
     fn name(&self) -> &str {
         match self {
             Self::ActionFuncs => VARIANT_CHILD_TYPE_ACTION_FUNCS,
+            Self::AuthenticationFuncs => VARIANT_CHILD_TYPE_AUTHENTICATION_FUNCS,
             Self::Domain => VARIANT_CHILD_TYPE_DOMAIN,
             Self::FuncDescriptions => VARIANT_CHILD_TYPE_FUNC_DESCRIPTIONS,
             Self::LeafFunctions => VARIANT_CHILD_TYPE_LEAF_FUNCTIONS,
@@ -91,6 +97,7 @@ impl ReadBytes for SchemaVariantChildNode {
 
         let node = match kind_str.as_str() {
             VARIANT_CHILD_TYPE_ACTION_FUNCS => Self::ActionFuncs,
+            VARIANT_CHILD_TYPE_AUTHENTICATION_FUNCS => Self::AuthenticationFuncs,
             VARIANT_CHILD_TYPE_DOMAIN => Self::Domain,
             VARIANT_CHILD_TYPE_FUNC_DESCRIPTIONS => Self::FuncDescriptions,
             VARIANT_CHILD_TYPE_LEAF_FUNCTIONS => Self::LeafFunctions,
@@ -124,6 +131,17 @@ impl NodeChild for SchemaVariantChild {
                     })
                     .collect(),
             ),
+            Self::AuthenticationFuncs(authentication_funcs) => NodeWithChildren::new(
+                NodeKind::Tree,
+                Self::NodeType::SchemaVariantChild(SchemaVariantChildNode::AuthenticationFuncs),
+                authentication_funcs
+                    .iter()
+                    .map(|authentication_func| {
+                        Box::new(authentication_func.clone())
+                            as Box<dyn NodeChild<NodeType = Self::NodeType>>
+                    })
+                    .collect(),
+            ),
             Self::Domain(domain) => {
                 let domain =
                     Box::new(domain.clone()) as Box<dyn NodeChild<NodeType = Self::NodeType>>;
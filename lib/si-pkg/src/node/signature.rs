@@ -0,0 +1,74 @@
+use std::io::{BufRead, Write};
+
+use base64::{engine::general_purpose, Engine};
+use object_tree::{
+    read_key_value_line, write_key_value_line, GraphError, NodeChild, NodeKind, NodeWithChildren,
+    ReadBytes, WriteBytes,
+};
+use sodiumoxide::crypto::sign::{PublicKey, Signature};
+
+use super::PkgNode;
+
+const KEY_PUBLIC_KEY_STR: &str = "public_key";
+const KEY_SIGNATURE_STR: &str = "signature";
+
+#[derive(Clone, Debug)]
+pub struct SignatureNode {
+    pub public_key: PublicKey,
+    pub signature: Signature,
+}
+
+impl WriteBytes for SignatureNode {
+    fn write_bytes<W: Write>(&self, writer: &mut W) -> Result<(), GraphError> {
+        write_key_value_line(
+            writer,
+            KEY_PUBLIC_KEY_STR,
+            general_purpose::STANDARD_NO_PAD.encode(self.public_key),
+        )?;
+        write_key_value_line(
+            writer,
+            KEY_SIGNATURE_STR,
+            general_purpose::STANDARD_NO_PAD.encode(self.signature.to_bytes()),
+        )?;
+
+        Ok(())
+    }
+}
+
+impl ReadBytes for SignatureNode {
+    fn read_bytes<R: BufRead>(reader: &mut R) -> Result<Self, GraphError>
+    where
+        Self: std::marker::Sized,
+    {
+        let public_key_str = read_key_value_line(reader, KEY_PUBLIC_KEY_STR)?;
+        let public_key_bytes = general_purpose::STANDARD_NO_PAD
+            .decode(public_key_str)
+            .map_err(GraphError::parse)?;
+        let public_key = PublicKey::from_slice(&public_key_bytes)
+            .ok_or_else(|| GraphError::parse_custom("invalid signature public key"))?;
+
+        let signature_str = read_key_value_line(reader, KEY_SIGNATURE_STR)?;
+        let signature_bytes = general_purpose::STANDARD_NO_PAD
+            .decode(signature_str)
+            .map_err(GraphError::parse)?;
+        let signature = Signature::from_bytes(&signature_bytes)
+            .map_err(|_| GraphError::parse_custom("invalid signature"))?;
+
+        Ok(Self {
+            public_key,
+            signature,
+        })
+    }
+}
+
+impl NodeChild for SignatureNode {
+    type NodeType = PkgNode;
+
+    fn as_node_with_children(&self) -> NodeWithChildren<Self::NodeType> {
+        NodeWithChildren::new(
+            NodeKind::Leaf,
+            Self::NodeType::Signature(self.clone()),
+            vec![],
+        )
+    }
+}
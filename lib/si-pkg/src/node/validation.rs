@@ -50,7 +50,8 @@ impl WriteBytes for ValidationNode {
         write_key_value_line(writer, KEY_KIND_STR, self.kind)?;
 
         match self.kind {
-            ValidationSpecKind::IntegerIsBetweenTwoIntegers => {
+            ValidationSpecKind::IntegerIsBetweenTwoIntegers
+            | ValidationSpecKind::StringLengthIsBetweenTwoIntegers => {
                 write_key_value_line(
                     writer,
                     KEY_UPPER_BOUND_STR,
@@ -66,13 +67,13 @@ impl WriteBytes for ValidationNode {
                         .unwrap_or("".to_string()),
                 )?;
             }
-            ValidationSpecKind::StringEquals | ValidationSpecKind::StringHasPrefix => {
-                write_key_value_line(
-                    writer,
-                    KEY_EXPECTED_STRING_STR,
-                    self.expected_string.clone().unwrap_or("".to_string()),
-                )?
-            }
+            ValidationSpecKind::StringEquals
+            | ValidationSpecKind::StringHasPrefix
+            | ValidationSpecKind::StringMatchesRegex => write_key_value_line(
+                writer,
+                KEY_EXPECTED_STRING_STR,
+                self.expected_string.clone().unwrap_or("".to_string()),
+            )?,
             ValidationSpecKind::StringInStringArray => {
                 write_key_value_line(
                     writer,
@@ -120,14 +121,17 @@ impl ReadBytes for ValidationNode {
         let mut func_unique_id = None;
 
         match kind {
-            ValidationSpecKind::IntegerIsBetweenTwoIntegers => {
+            ValidationSpecKind::IntegerIsBetweenTwoIntegers
+            | ValidationSpecKind::StringLengthIsBetweenTwoIntegers => {
                 let upper_bound_str = read_key_value_line(reader, KEY_UPPER_BOUND_STR)?;
                 upper_bound = Some(i64::from_str(&upper_bound_str).map_err(GraphError::parse)?);
 
                 let lower_bound_str = read_key_value_line(reader, KEY_LOWER_BOUND_STR)?;
                 lower_bound = Some(i64::from_str(&lower_bound_str).map_err(GraphError::parse)?);
             }
-            ValidationSpecKind::StringEquals | ValidationSpecKind::StringHasPrefix => {
+            ValidationSpecKind::StringEquals
+            | ValidationSpecKind::StringHasPrefix
+            | ValidationSpecKind::StringMatchesRegex => {
                 let expected_string_str = read_key_value_line(reader, KEY_EXPECTED_STRING_STR)?;
                 if !expected_string_str.is_empty() {
                     expected_string = Some(expected_string_str);
@@ -222,6 +226,20 @@ impl NodeChild for ValidationSpec {
                     kind: ValidationSpecKind::StringIsNotEmpty,
                     ..ValidationNode::default()
                 },
+                ValidationSpec::StringLengthIsBetweenTwoIntegers {
+                    lower_bound,
+                    upper_bound,
+                } => ValidationNode {
+                    kind: ValidationSpecKind::StringLengthIsBetweenTwoIntegers,
+                    upper_bound: Some(*upper_bound),
+                    lower_bound: Some(*lower_bound),
+                    ..ValidationNode::default()
+                },
+                ValidationSpec::StringMatchesRegex { expected } => ValidationNode {
+                    kind: ValidationSpecKind::StringMatchesRegex,
+                    expected_string: Some(expected.clone()),
+                    ..ValidationNode::default()
+                },
                 ValidationSpec::CustomValidation { func_unique_id } => ValidationNode {
                     kind: ValidationSpecKind::CustomValidation,
                     func_unique_id: Some(*func_unique_id),
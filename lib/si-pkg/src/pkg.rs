@@ -3,14 +3,19 @@ use std::{collections::HashMap, convert::Infallible, path::Path, sync::Arc};
 
 use chrono::{DateTime, Utc};
 use object_tree::{
-    GraphError, Hash, HashedNode, NameStr, NodeChild, ObjectTree, TarReadError, TarWriter,
-    TarWriterError,
+    GraphError, Hash, HashedNode, NameStr, NodeChild, NodeKind, NodeWithChildren, ObjectTree,
+    TarReadError, TarReader, TarWriter, TarWriterError, WriteBytes,
 };
 use petgraph::prelude::*;
+use semver::Version;
+use sodiumoxide::crypto::sign::{self, PublicKey, SecretKey};
 use thiserror::Error;
 
 mod action_func;
 mod attr_func_input;
+mod authentication_func;
+mod dependency;
+mod diff;
 mod func;
 mod func_description;
 mod leaf_function;
@@ -23,13 +28,14 @@ mod validation;
 mod variant;
 
 pub use {
-    action_func::*, attr_func_input::*, func::*, func_description::*, leaf_function::*,
-    map_key_func::*, prop::*, schema::*, si_prop_func::*, socket::*, validation::*, variant::*,
+    action_func::*, attr_func_input::*, authentication_func::*, dependency::*, diff::*, func::*,
+    func_description::*, leaf_function::*, map_key_func::*, prop::*, schema::*, si_prop_func::*,
+    socket::*, validation::*, variant::*,
 };
 
 use crate::{
-    node::{CategoryNode, PkgNode},
-    spec::{FuncSpec, PkgSpec, SchemaVariantSpecPropRoot, SpecError},
+    node::{CategoryNode, PkgNode, SignatureNode},
+    spec::{FuncSpec, PkgDependencySpec, PkgSpec, SchemaVariantSpecPropRoot, SpecError},
 };
 
 #[remain::sorted]
@@ -47,6 +53,10 @@ pub enum SiPkgError {
     NodeWithHashNotFound(Hash),
     #[error("node not found with name={0}")]
     NodeWithNameNotFound(String),
+    #[error("package was opened without its schemas; use schema_trees() or hydrate it first")]
+    NotHydrated,
+    #[error("package is not signed by any trusted key")]
+    NoTrustedSignature,
     #[error("found multiple pkg node domain props for variant with hash={0}")]
     PropRootMultipleFound(SchemaVariantSpecPropRoot, Hash),
     #[error("could not find pkg node root prop {0} for variant with hash={1}")]
@@ -57,12 +67,16 @@ pub enum SiPkgError {
     SchemaVariantChildNotFound(&'static str),
     #[error(transparent)]
     SerdeJson(#[from] serde_json::Error),
+    #[error("package signature does not match its contents")]
+    SignatureInvalid,
     #[error(transparent)]
     Spec(#[from] SpecError),
     #[error(transparent)]
     TarRead(#[from] TarReadError),
     #[error("unexpected pkg node type; expected={0}, actual={1}")]
     UnexpectedPkgNodeType(&'static str, &'static str),
+    #[error("package is not signed")]
+    Unsigned,
     #[error("Validation spec missing required field: {0}")]
     ValidationMissingField(String),
     #[error("error while visiting prop: {0}")]
@@ -92,6 +106,9 @@ impl From<Infallible> for SiPkgError {
 #[derive(Clone, Debug)]
 pub struct SiPkg {
     tree: Arc<ObjectTree<PkgNode>>,
+    /// Set when the package was opened from a file, in which case `tree` stops at the schema
+    /// nodes and their subtrees are read from the file on demand.
+    reader: Option<Arc<TarReader>>,
 }
 
 impl SiPkg {
@@ -105,9 +122,44 @@ impl SiPkg {
 
         Ok(Self {
             tree: Arc::new(tree),
+            reader: None,
         })
     }
 
+    /// Opens a package file without reading it into memory.
+    ///
+    /// The file is memory-mapped and everything but the subtrees of its schemas is read, so the
+    /// metadata, funcs and dependencies are available right away. Each schema is read from the
+    /// file when [`SiPkg::schema_trees`] gets to it, and [`SiPkg::hydrate`] reads the rest.
+    pub fn open(path: impl AsRef<Path>) -> PkgResult<Self> {
+        let reader = TarReader::open(path)?;
+        let tree = reader.read_tree_pruned(reader.root_hash(), |node: &PkgNode| {
+            matches!(node, PkgNode::Schema(_))
+        })?;
+
+        Ok(Self {
+            tree: Arc::new(tree),
+            reader: Some(Arc::new(reader)),
+        })
+    }
+
+    /// Whether the whole package is in memory, as opposed to being opened with [`SiPkg::open`].
+    pub fn is_hydrated(&self) -> bool {
+        self.reader.is_none()
+    }
+
+    /// Returns the package with all of its subtrees in memory, reading what is missing from the
+    /// file it was opened from.
+    pub fn hydrate(&self) -> PkgResult<Self> {
+        match &self.reader {
+            None => Ok(self.clone()),
+            Some(reader) => Ok(Self {
+                tree: Arc::new(reader.read_tree(reader.root_hash())?),
+                reader: None,
+            }),
+        }
+    }
+
     pub fn load_from_spec<I>(spec: I) -> PkgResult<Self>
     where
         I: TryInto<PkgSpec>,
@@ -118,11 +170,18 @@ impl SiPkg {
 
         Ok(Self {
             tree: Arc::new(tree),
+            reader: None,
         })
     }
 
     pub fn write_to_bytes(&self) -> PkgResult<Vec<u8>> {
-        Ok(TarWriter::new(&self.tree)?.bytes())
+        Ok(TarWriter::new(&self.hydrate()?.tree)?.bytes())
+    }
+
+    /// Like [`SiPkg::write_to_bytes`], but compresses large nodes such as the code of funcs. The
+    /// package is written in a newer format that older versions can't load.
+    pub fn write_to_bytes_compressed(&self) -> PkgResult<Vec<u8>> {
+        Ok(TarWriter::new_compressed(&self.hydrate()?.tree)?.bytes())
     }
 
     pub fn metadata(&self) -> PkgResult<SiPkgMetadata> {
@@ -135,6 +194,111 @@ impl SiPkg {
         Ok(self.metadata()?.hash())
     }
 
+    /// Returns a copy of the package signed by `secret_key`, keeping any signatures by other keys.
+    ///
+    /// The signature is embedded in the package and covers a canonical hash of its unsigned
+    /// contents, so it stays valid as other signatures are added.
+    pub fn sign(&self, secret_key: &SecretKey) -> PkgResult<Self> {
+        if !self.is_hydrated() {
+            return self.hydrate()?.sign(secret_key);
+        }
+
+        let public_key = secret_key.public_key();
+        let mut signatures: Vec<SignatureNode> = self
+            .signature_nodes()
+            .into_iter()
+            .filter(|node| node.public_key != public_key)
+            .collect();
+        signatures.push(SignatureNode {
+            public_key,
+            signature: sign::sign_detached(self.unsigned_hash()?.as_bytes(), secret_key),
+        });
+
+        let tree = ObjectTree::create_from_root(self.root_with_signatures(signatures))?;
+
+        Ok(Self {
+            tree: Arc::new(tree),
+            reader: None,
+        })
+    }
+
+    /// Checks that the package is signed by one of the `trusted_keys` and that its contents were
+    /// not changed since, returning the key it was signed with.
+    pub fn verify(&self, trusted_keys: &[PublicKey]) -> PkgResult<PublicKey> {
+        let signatures = self.signature_nodes();
+        if signatures.is_empty() {
+            return Err(SiPkgError::Unsigned);
+        }
+
+        let mut trusted_signatures = signatures
+            .into_iter()
+            .filter(|node| trusted_keys.contains(&node.public_key))
+            .peekable();
+        if trusted_signatures.peek().is_none() {
+            return Err(SiPkgError::NoTrustedSignature);
+        }
+
+        let unsigned_hash = self.unsigned_hash()?;
+        trusted_signatures
+            .find(|node| {
+                sign::verify_detached(&node.signature, unsigned_hash.as_bytes(), &node.public_key)
+            })
+            .map(|node| node.public_key)
+            .ok_or(SiPkgError::SignatureInvalid)
+    }
+
+    /// Returns the keys the package was signed with, without checking the signatures.
+    pub fn signed_by(&self) -> Vec<PublicKey> {
+        self.signature_nodes()
+            .into_iter()
+            .map(|node| node.public_key)
+            .collect()
+    }
+
+    fn signature_nodes(&self) -> Vec<SignatureNode> {
+        let (graph, root_idx) = self.as_petgraph();
+
+        graph
+            .neighbors_directed(root_idx, Outgoing)
+            .filter_map(|node_idx| match graph[node_idx].inner() {
+                PkgNode::Signature(node) => Some(node.clone()),
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// Computes the canonical hash of the package without its signatures, which is what signatures
+    /// are made over. Every node is hashed again rather than trusting the hashes read from the
+    /// package, so changed contents are caught.
+    fn unsigned_hash(&self) -> PkgResult<Hash> {
+        if !self.is_hydrated() {
+            return self.hydrate()?.unsigned_hash();
+        }
+
+        let (graph, root_idx) = self.as_petgraph();
+        let children = graph
+            .neighbors_directed(root_idx, Outgoing)
+            .filter(|node_idx| !matches!(graph[*node_idx].inner(), PkgNode::Signature(_)))
+            .collect();
+
+        canonical_hash(graph, root_idx, children)
+    }
+
+    fn root_with_signatures(&self, signatures: Vec<SignatureNode>) -> NodeWithChildren<PkgNode> {
+        let (graph, root_idx) = self.as_petgraph();
+
+        let mut children = TreeNode::children(&self.tree, root_idx, |node| {
+            !matches!(node, PkgNode::Signature(_))
+        });
+        children.extend(
+            signatures
+                .into_iter()
+                .map(|node| Box::new(node) as Box<dyn NodeChild<NodeType = PkgNode>>),
+        );
+
+        NodeWithChildren::new(NodeKind::Tree, graph[root_idx].inner().clone(), children)
+    }
+
     pub fn funcs_by_unique_id(&self) -> PkgResult<HashMap<Hash, SiPkgFunc>> {
         let func_map: HashMap<Hash, SiPkgFunc> = self
             .funcs()?
@@ -157,7 +321,25 @@ impl SiPkg {
         Ok(funcs)
     }
 
+    pub fn dependencies(&self) -> PkgResult<Vec<SiPkgDependency>> {
+        let (graph, root_idx) = self.as_petgraph();
+
+        let node_idxs = match dependency_node_idxs(graph, root_idx) {
+            Ok(node_idxs) => node_idxs,
+            // Packages built before dependencies could be declared have no such category
+            Err(SiPkgError::CategoryNotFound(_)) => vec![],
+            Err(err) => return Err(err),
+        };
+        let mut dependencies = Vec::with_capacity(node_idxs.len());
+        for node_idx in node_idxs {
+            dependencies.push(SiPkgDependency::from_graph(graph, node_idx)?);
+        }
+
+        Ok(dependencies)
+    }
+
     pub fn schemas(&self) -> PkgResult<Vec<SiPkgSchema>> {
+        self.ensure_hydrated()?;
         let (graph, root_idx) = self.as_petgraph();
 
         let node_idxs = schema_node_idxs(graph, root_idx)?;
@@ -171,6 +353,7 @@ impl SiPkg {
     }
 
     pub fn schema_by_name(&self, name: impl AsRef<str>) -> PkgResult<SiPkgSchema> {
+        self.ensure_hydrated()?;
         let (graph, root_idx) = self.as_petgraph();
 
         let node_idx = idx_for_name(graph, schema_node_idxs(graph, root_idx)?.into_iter(), name)?;
@@ -179,6 +362,7 @@ impl SiPkg {
     }
 
     pub fn schema_by_hash(&self, hash: Hash) -> PkgResult<SiPkgSchema> {
+        self.ensure_hydrated()?;
         let (graph, root_idx) = self.as_petgraph();
 
         let node_idx = idx_for_hash(graph, schema_node_idxs(graph, root_idx)?.into_iter(), hash)?;
@@ -186,6 +370,40 @@ impl SiPkg {
         SiPkgSchema::from_graph(graph, node_idx)
     }
 
+    /// Returns each schema of the package along with its subtree, reading the subtree from the
+    /// file only when the iterator gets to it if the package was opened with [`SiPkg::open`].
+    pub fn schema_trees(&self) -> PkgResult<impl Iterator<Item = PkgResult<SiPkgSchemaTree>> + '_> {
+        let (graph, root_idx) = self.as_petgraph();
+
+        let node_idxs = schema_node_idxs(graph, root_idx)?;
+
+        Ok(node_idxs
+            .into_iter()
+            .map(move |node_idx| match &self.reader {
+                None => Ok(SiPkgSchemaTree {
+                    tree: self.tree.clone(),
+                    node_idx,
+                }),
+                Some(reader) => {
+                    let tree = reader.read_tree(graph[node_idx].hash())?;
+                    let (_, node_idx) = tree.as_petgraph();
+
+                    Ok(SiPkgSchemaTree {
+                        tree: Arc::new(tree),
+                        node_idx,
+                    })
+                }
+            }))
+    }
+
+    fn ensure_hydrated(&self) -> PkgResult<()> {
+        if self.is_hydrated() {
+            Ok(())
+        } else {
+            Err(SiPkgError::NotHydrated)
+        }
+    }
+
     pub fn as_petgraph(&self) -> (&Graph<HashedNode<PkgNode>, ()>, NodeIndex) {
         self.tree.as_petgraph()
     }
@@ -206,8 +424,12 @@ impl SiPkg {
             builder.func(FuncSpec::try_from(func)?);
         }
 
-        for schema in self.schemas()? {
-            builder.schema(schema.to_spec().await?);
+        for schema_tree in self.schema_trees()? {
+            builder.schema(schema_tree?.schema()?.to_spec().await?);
+        }
+
+        for dependency in self.dependencies()? {
+            builder.dependency(PkgDependencySpec::try_from(dependency)?);
         }
 
         Ok(builder.build()?)
@@ -269,6 +491,110 @@ fn func_node_idxs(
     category_node_idxs(CategoryNode::Funcs, graph, root_idx)
 }
 
+fn dependency_node_idxs(
+    graph: &Graph<HashedNode<PkgNode>, ()>,
+    root_idx: NodeIndex,
+) -> PkgResult<Vec<NodeIndex>> {
+    category_node_idxs(CategoryNode::Dependencies, graph, root_idx)
+}
+
+/// Hashes a node with the given children, and their descendants, in a form that doesn't depend on
+/// the order of children sharing a name, which are ordered by their canonical hash. The hashes
+/// stored in a tree keep such children in the order they were added, so a package rebuilt from
+/// another can order them differently while its signature must stay valid.
+fn canonical_hash(
+    graph: &Graph<HashedNode<PkgNode>, ()>,
+    node_idx: NodeIndex,
+    children: Vec<NodeIndex>,
+) -> PkgResult<Hash> {
+    let mut entries = children
+        .into_iter()
+        .map(|child_idx| {
+            let grandchildren = graph.neighbors_directed(child_idx, Outgoing).collect();
+            Ok((
+                graph[child_idx].name(),
+                canonical_hash(graph, child_idx, grandchildren)?,
+            ))
+        })
+        .collect::<PkgResult<Vec<_>>>()?;
+    entries.sort_by(|(a_name, a_hash), (b_name, b_hash)| {
+        a_name
+            .cmp(b_name)
+            .then_with(|| a_hash.as_bytes().cmp(b_hash.as_bytes()))
+    });
+
+    let node = &graph[node_idx];
+    let mut bytes = format!("{}\n", node.kind().as_ref()).into_bytes();
+    bytes.extend(node.inner().to_bytes()?);
+    for (name, hash) in entries {
+        bytes.extend(format!("{hash} {name}\n").into_bytes());
+    }
+
+    Ok(Hash::new(&bytes))
+}
+
+/// A node of an existing package tree, used to build a new tree with the same contents.
+struct TreeNode {
+    tree: Arc<ObjectTree<PkgNode>>,
+    node_idx: NodeIndex,
+}
+
+impl TreeNode {
+    /// Returns the children of a node that match the filter, in an order that gives them the same
+    /// order in the new tree.
+    fn children(
+        tree: &Arc<ObjectTree<PkgNode>>,
+        node_idx: NodeIndex,
+        filter: impl Fn(&PkgNode) -> bool,
+    ) -> Vec<Box<dyn NodeChild<NodeType = PkgNode>>> {
+        let (graph, _) = tree.as_petgraph();
+
+        let mut children: Vec<Box<dyn NodeChild<NodeType = PkgNode>>> = graph
+            .neighbors_directed(node_idx, Outgoing)
+            .filter(|child_idx| filter(graph[*child_idx].inner()))
+            .map(|child_idx| {
+                Box::new(Self {
+                    tree: tree.clone(),
+                    node_idx: child_idx,
+                }) as Box<dyn NodeChild<NodeType = PkgNode>>
+            })
+            .collect();
+        children.reverse();
+
+        children
+    }
+}
+
+impl NodeChild for TreeNode {
+    type NodeType = PkgNode;
+
+    fn as_node_with_children(&self) -> NodeWithChildren<Self::NodeType> {
+        let (graph, _) = self.tree.as_petgraph();
+        let node = &graph[self.node_idx];
+
+        NodeWithChildren::new(
+            node.kind(),
+            node.inner().clone(),
+            Self::children(&self.tree, self.node_idx, |_| true),
+        )
+    }
+}
+
+/// A schema of a package along with the tree holding its subtree.
+#[derive(Clone, Debug)]
+pub struct SiPkgSchemaTree {
+    tree: Arc<ObjectTree<PkgNode>>,
+    node_idx: NodeIndex,
+}
+
+impl SiPkgSchemaTree {
+    pub fn schema(&self) -> PkgResult<SiPkgSchema> {
+        let (graph, _) = self.tree.as_petgraph();
+
+        SiPkgSchema::from_graph(graph, self.node_idx)
+    }
+}
+
 #[derive(Clone)]
 pub struct Source<'a> {
     graph: &'a Graph<HashedNode<PkgNode>, ()>,
@@ -332,6 +658,11 @@ impl SiPkgMetadata {
         self.version.as_ref()
     }
 
+    /// The version parsed as semver, or `None` for packages versioned otherwise, such as by date.
+    pub fn semver(&self) -> Option<Version> {
+        Version::parse(self.version.trim()).ok()
+    }
+
     pub fn description(&self) -> &str {
         self.description.as_ref()
     }
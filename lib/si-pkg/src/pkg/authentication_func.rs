@@ -0,0 +1,69 @@
+use object_tree::{Hash, HashedNode};
+use petgraph::prelude::*;
+
+use super::{PkgResult, SiPkgError, Source};
+
+use crate::{
+    AuthenticationFuncSpec,
+    {node::PkgNode, spec::FuncUniqueId},
+};
+
+#[derive(Clone, Debug)]
+pub struct SiPkgAuthenticationFunc<'a> {
+    func_unique_id: FuncUniqueId,
+    position: i64,
+    hash: Hash,
+    source: Source<'a>,
+}
+
+impl<'a> SiPkgAuthenticationFunc<'a> {
+    pub fn from_graph(
+        graph: &'a Graph<HashedNode<PkgNode>, ()>,
+        node_idx: NodeIndex,
+    ) -> PkgResult<Self> {
+        let hashed_node = &graph[node_idx];
+        let node = match hashed_node.inner() {
+            PkgNode::AuthenticationFunc(node) => node.clone(),
+            unexpected => {
+                return Err(SiPkgError::UnexpectedPkgNodeType(
+                    PkgNode::AUTHENTICATION_FUNC_KIND_STR,
+                    unexpected.node_kind_str(),
+                ))
+            }
+        };
+
+        Ok(Self {
+            func_unique_id: node.func_unique_id,
+            position: node.position,
+            hash: hashed_node.hash(),
+            source: Source::new(graph, node_idx),
+        })
+    }
+
+    pub fn func_unique_id(&self) -> FuncUniqueId {
+        self.func_unique_id
+    }
+
+    pub fn position(&self) -> i64 {
+        self.position
+    }
+
+    pub fn hash(&self) -> Hash {
+        self.hash
+    }
+
+    pub fn source(&self) -> &Source<'a> {
+        &self.source
+    }
+}
+
+impl<'a> TryFrom<SiPkgAuthenticationFunc<'a>> for AuthenticationFuncSpec {
+    type Error = SiPkgError;
+
+    fn try_from(value: SiPkgAuthenticationFunc<'a>) -> Result<Self, Self::Error> {
+        Ok(AuthenticationFuncSpec::builder()
+            .func_unique_id(value.func_unique_id)
+            .position(value.position)
+            .build()?)
+    }
+}
@@ -0,0 +1,78 @@
+use object_tree::{Hash, HashedNode};
+use petgraph::prelude::*;
+
+use super::{PkgResult, SiPkgError, Source};
+
+use crate::{node::PkgNode, PkgDependencySpec};
+
+#[derive(Clone, Debug)]
+pub struct SiPkgDependency<'a> {
+    name: String,
+    version_req: String,
+    pinned_hash: Option<Hash>,
+
+    hash: Hash,
+    source: Source<'a>,
+}
+
+impl<'a> SiPkgDependency<'a> {
+    pub fn from_graph(
+        graph: &'a Graph<HashedNode<PkgNode>, ()>,
+        node_idx: NodeIndex,
+    ) -> PkgResult<Self> {
+        let hashed_node = &graph[node_idx];
+        let node = match hashed_node.inner() {
+            PkgNode::Dependency(node) => node.clone(),
+            unexpected => {
+                return Err(SiPkgError::UnexpectedPkgNodeType(
+                    PkgNode::DEPENDENCY_KIND_STR,
+                    unexpected.node_kind_str(),
+                ))
+            }
+        };
+
+        Ok(Self {
+            name: node.name,
+            version_req: node.version_req,
+            pinned_hash: node.hash,
+
+            hash: hashed_node.hash(),
+            source: Source::new(graph, node_idx),
+        })
+    }
+
+    pub fn name(&self) -> &str {
+        self.name.as_ref()
+    }
+
+    pub fn version_req(&self) -> &str {
+        self.version_req.as_ref()
+    }
+
+    /// The root hash of the package build this dependency is pinned to, if any.
+    pub fn pinned_hash(&self) -> Option<Hash> {
+        self.pinned_hash
+    }
+
+    pub fn hash(&self) -> Hash {
+        self.hash
+    }
+
+    pub fn source(&self) -> &Source<'a> {
+        &self.source
+    }
+}
+
+impl<'a> TryFrom<SiPkgDependency<'a>> for PkgDependencySpec {
+    type Error = SiPkgError;
+
+    fn try_from(value: SiPkgDependency<'a>) -> Result<Self, Self::Error> {
+        let mut builder = PkgDependencySpec::builder();
+        builder.name(value.name).version_req(value.version_req);
+        if let Some(pinned_hash) = value.pinned_hash {
+            builder.hash(pinned_hash);
+        }
+
+        Ok(builder.build()?)
+    }
+}
@@ -0,0 +1,346 @@
+use std::collections::HashMap;
+
+use object_tree::{Hash, HashedNode, NameStr};
+use petgraph::prelude::*;
+
+use super::{PkgResult, SiPkg, SiPkgFunc, SiPkgSchemaVariant};
+
+use crate::node::{PkgNode, PropChildNode, SchemaVariantChildNode};
+
+/// What is different about an entry of a package in another package.
+#[remain::sorted]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum PkgDiffChange {
+    Added,
+    /// The entry is in both packages, but it or something under it is different.
+    Changed,
+    Removed,
+}
+
+#[remain::sorted]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum PkgDiffEntryKind {
+    Func,
+    Prop,
+    Schema,
+    SchemaVariant,
+    Socket,
+}
+
+/// An entry that differs between two packages.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct PkgDiffEntry {
+    kind: PkgDiffEntryKind,
+    path: Vec<String>,
+    change: PkgDiffChange,
+    old_hash: Option<Hash>,
+    new_hash: Option<Hash>,
+}
+
+impl PkgDiffEntry {
+    pub fn kind(&self) -> PkgDiffEntryKind {
+        self.kind
+    }
+
+    /// The names leading to the entry, such as the schema, variant and prop names of a prop.
+    pub fn path(&self) -> &[String] {
+        &self.path
+    }
+
+    pub fn change(&self) -> PkgDiffChange {
+        self.change
+    }
+
+    /// The hash of the entry in the old package, unless it was added.
+    pub fn old_hash(&self) -> Option<Hash> {
+        self.old_hash
+    }
+
+    /// The hash of the entry in the new package, unless it was removed.
+    pub fn new_hash(&self) -> Option<Hash> {
+        self.new_hash
+    }
+}
+
+/// The entries that differ between two packages. Only the outermost entry of anything added or
+/// removed is listed, while a change lists every entry down to the one that changed.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct PkgDiff {
+    entries: Vec<PkgDiffEntry>,
+}
+
+impl PkgDiff {
+    pub fn entries(&self) -> &[PkgDiffEntry] {
+        &self.entries
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    fn push(
+        &mut self,
+        kind: PkgDiffEntryKind,
+        path: Vec<String>,
+        old_hash: Option<Hash>,
+        new_hash: Option<Hash>,
+    ) {
+        let change = match (old_hash, new_hash) {
+            (None, _) => PkgDiffChange::Added,
+            (_, None) => PkgDiffChange::Removed,
+            (Some(_), Some(_)) => PkgDiffChange::Changed,
+        };
+
+        self.entries.push(PkgDiffEntry {
+            kind,
+            path,
+            change,
+            old_hash,
+            new_hash,
+        });
+    }
+
+    /// Pairs up entries by name and records the added and removed ones, returning the pairs whose
+    /// hashes differ.
+    fn pair_by_name<T>(
+        &mut self,
+        kind: PkgDiffEntryKind,
+        parent_path: &[String],
+        old: Vec<(String, Hash, T)>,
+        new: Vec<(String, Hash, T)>,
+    ) -> Vec<(Vec<String>, T, T)> {
+        let mut new: HashMap<String, (Hash, T)> = new
+            .into_iter()
+            .map(|(name, hash, item)| (name, (hash, item)))
+            .collect();
+
+        let mut changed = Vec::new();
+        for (name, old_hash, old_item) in old {
+            let mut path = parent_path.to_vec();
+            path.push(name.clone());
+
+            match new.remove(&name) {
+                Some((new_hash, _)) if new_hash == old_hash => {}
+                Some((new_hash, new_item)) => {
+                    self.push(kind, path.clone(), Some(old_hash), Some(new_hash));
+                    changed.push((path, old_item, new_item));
+                }
+                None => self.push(kind, path, Some(old_hash), None),
+            }
+        }
+
+        let mut added: Vec<_> = new.into_iter().collect();
+        added.sort_by(|(a, _), (b, _)| a.cmp(b));
+        for (name, (new_hash, _)) in added {
+            let mut path = parent_path.to_vec();
+            path.push(name);
+            self.push(kind, path, None, Some(new_hash));
+        }
+
+        changed
+    }
+}
+
+impl SiPkg {
+    /// Compares this package with another one, such as a newer version of it.
+    ///
+    /// Funcs are paired up by unique id, then by name. Schemas, variants, sockets and props are
+    /// paired up by name, and only looked into when their hashes differ.
+    pub fn diff(&self, other: &SiPkg) -> PkgResult<PkgDiff> {
+        let mut diff = PkgDiff::default();
+        if self.hash()? == other.hash()? {
+            return Ok(diff);
+        }
+
+        let (old, new) = (self.hydrate()?, other.hydrate()?);
+
+        diff_funcs(&mut diff, old.funcs()?, new.funcs()?);
+
+        let old_schemas = old
+            .schemas()?
+            .into_iter()
+            .map(|schema| (schema.name().to_string(), schema.hash(), schema))
+            .collect();
+        let new_schemas = new
+            .schemas()?
+            .into_iter()
+            .map(|schema| (schema.name().to_string(), schema.hash(), schema))
+            .collect();
+
+        for (path, old_schema, new_schema) in
+            diff.pair_by_name(PkgDiffEntryKind::Schema, &[], old_schemas, new_schemas)
+        {
+            let old_variants = old_schema
+                .variants()?
+                .into_iter()
+                .map(|variant| (variant.name().to_string(), variant.hash(), variant))
+                .collect();
+            let new_variants = new_schema
+                .variants()?
+                .into_iter()
+                .map(|variant| (variant.name().to_string(), variant.hash(), variant))
+                .collect();
+
+            for (path, old_variant, new_variant) in diff.pair_by_name(
+                PkgDiffEntryKind::SchemaVariant,
+                &path,
+                old_variants,
+                new_variants,
+            ) {
+                diff_variants(&mut diff, &path, old_variant, new_variant)?;
+            }
+        }
+
+        Ok(diff)
+    }
+}
+
+fn diff_funcs(diff: &mut PkgDiff, old: Vec<SiPkgFunc>, new: Vec<SiPkgFunc>) {
+    let mut new: Vec<Option<SiPkgFunc>> = new.into_iter().map(Some).collect();
+
+    for old_func in old {
+        let by_unique_id = new.iter().position(|func| {
+            func.as_ref()
+                .map_or(false, |func| func.unique_id() == old_func.unique_id())
+        });
+        let new_func = by_unique_id
+            .or_else(|| {
+                new.iter().position(|func| {
+                    func.as_ref()
+                        .map_or(false, |func| func.name() == old_func.name())
+                })
+            })
+            .and_then(|idx| new[idx].take());
+
+        match new_func {
+            Some(new_func) if new_func.hash() == old_func.hash() => {}
+            Some(new_func) => diff.push(
+                PkgDiffEntryKind::Func,
+                vec![old_func.name().to_string()],
+                Some(old_func.hash()),
+                Some(new_func.hash()),
+            ),
+            None => diff.push(
+                PkgDiffEntryKind::Func,
+                vec![old_func.name().to_string()],
+                Some(old_func.hash()),
+                None,
+            ),
+        }
+    }
+
+    for new_func in new.into_iter().flatten() {
+        diff.push(
+            PkgDiffEntryKind::Func,
+            vec![new_func.name().to_string()],
+            None,
+            Some(new_func.hash()),
+        );
+    }
+}
+
+fn diff_variants(
+    diff: &mut PkgDiff,
+    path: &[String],
+    old: SiPkgSchemaVariant,
+    new: SiPkgSchemaVariant,
+) -> PkgResult<()> {
+    let old_sockets = old
+        .sockets()?
+        .into_iter()
+        .map(|socket| (socket.name().to_string(), socket.hash(), ()))
+        .collect();
+    let new_sockets = new
+        .sockets()?
+        .into_iter()
+        .map(|socket| (socket.name().to_string(), socket.hash(), ()))
+        .collect();
+    diff.pair_by_name(PkgDiffEntryKind::Socket, path, old_sockets, new_sockets);
+
+    for prop_root in [
+        SchemaVariantChildNode::Domain,
+        SchemaVariantChildNode::ResourceValue,
+    ] {
+        let old_props = root_prop(old.source.graph, old.source.node_idx, prop_root)
+            .map(|node_idx| vec![prop_entry(old.source.graph, node_idx)])
+            .unwrap_or_default();
+        let new_props = root_prop(new.source.graph, new.source.node_idx, prop_root)
+            .map(|node_idx| vec![prop_entry(new.source.graph, node_idx)])
+            .unwrap_or_default();
+
+        diff_props(
+            diff,
+            path,
+            (old.source.graph, old_props),
+            (new.source.graph, new_props),
+        );
+    }
+
+    Ok(())
+}
+
+type PropEntries<'a> = (
+    &'a Graph<HashedNode<PkgNode>, ()>,
+    Vec<(String, Hash, NodeIndex)>,
+);
+
+fn diff_props(diff: &mut PkgDiff, parent_path: &[String], old: PropEntries, new: PropEntries) {
+    let (old_graph, old_props) = old;
+    let (new_graph, new_props) = new;
+
+    for (path, old_idx, new_idx) in
+        diff.pair_by_name(PkgDiffEntryKind::Prop, parent_path, old_props, new_props)
+    {
+        diff_props(
+            diff,
+            &path,
+            (old_graph, child_props(old_graph, old_idx)),
+            (new_graph, child_props(new_graph, new_idx)),
+        );
+    }
+}
+
+fn root_prop(
+    graph: &Graph<HashedNode<PkgNode>, ()>,
+    variant_idx: NodeIndex,
+    prop_root: SchemaVariantChildNode,
+) -> Option<NodeIndex> {
+    let prop_root_idx =
+        graph
+            .neighbors_directed(variant_idx, Outgoing)
+            .find(|node_idx| match graph[*node_idx].inner() {
+                PkgNode::SchemaVariantChild(node) => *node == prop_root,
+                _ => false,
+            })?;
+
+    graph.neighbors_directed(prop_root_idx, Outgoing).next()
+}
+
+fn child_props(
+    graph: &Graph<HashedNode<PkgNode>, ()>,
+    prop_idx: NodeIndex,
+) -> Vec<(String, Hash, NodeIndex)> {
+    graph
+        .neighbors_directed(prop_idx, Outgoing)
+        .find(|node_idx| {
+            matches!(
+                graph[*node_idx].inner(),
+                PkgNode::PropChild(PropChildNode::Props)
+            )
+        })
+        .map(|props_idx| {
+            graph
+                .neighbors_directed(props_idx, Outgoing)
+                .map(|node_idx| prop_entry(graph, node_idx))
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+fn prop_entry(
+    graph: &Graph<HashedNode<PkgNode>, ()>,
+    node_idx: NodeIndex,
+) -> (String, Hash, NodeIndex) {
+    let node = &graph[node_idx];
+    (node.name().to_string(), node.hash(), node_idx)
+}
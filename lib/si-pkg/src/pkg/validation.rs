@@ -51,6 +51,17 @@ pub enum SiPkgValidation<'a> {
         hash: Hash,
         source: Source<'a>,
     },
+    StringLengthIsBetweenTwoIntegers {
+        lower_bound: i64,
+        upper_bound: i64,
+        hash: Hash,
+        source: Source<'a>,
+    },
+    StringMatchesRegex {
+        expected: String,
+        hash: Hash,
+        source: Source<'a>,
+    },
 }
 
 impl<'a> SiPkgValidation<'a> {
@@ -128,6 +139,27 @@ impl<'a> SiPkgValidation<'a> {
             ValidationSpecKind::StringIsNotEmpty => {
                 SiPkgValidation::StringIsNotEmpty { hash, source }
             }
+            ValidationSpecKind::StringLengthIsBetweenTwoIntegers => {
+                SiPkgValidation::StringLengthIsBetweenTwoIntegers {
+                    upper_bound: node.upper_bound.ok_or(SiPkgError::ValidationMissingField(
+                        "upper_bound".to_string(),
+                    ))?,
+                    lower_bound: node.lower_bound.ok_or(SiPkgError::ValidationMissingField(
+                        "lower_bound".to_string(),
+                    ))?,
+                    hash,
+                    source,
+                }
+            }
+            ValidationSpecKind::StringMatchesRegex => SiPkgValidation::StringMatchesRegex {
+                expected: node
+                    .expected_string
+                    .ok_or(SiPkgError::ValidationMissingField(
+                        "expected_string".to_string(),
+                    ))?,
+                hash,
+                source,
+            },
             ValidationSpecKind::CustomValidation => {
                 SiPkgValidation::CustomValidation {
                     func_unique_id: node.func_unique_id.ok_or(
@@ -190,6 +222,19 @@ impl<'a> TryFrom<SiPkgValidation<'a>> for ValidationSpec {
             SiPkgValidation::StringIsNotEmpty { .. } => {
                 builder.kind(ValidationSpecKind::StringIsNotEmpty);
             }
+            SiPkgValidation::StringLengthIsBetweenTwoIntegers {
+                lower_bound,
+                upper_bound,
+                ..
+            } => {
+                builder.kind(ValidationSpecKind::StringLengthIsBetweenTwoIntegers);
+                builder.lower_bound(lower_bound);
+                builder.upper_bound(upper_bound);
+            }
+            SiPkgValidation::StringMatchesRegex { expected, .. } => {
+                builder.kind(ValidationSpecKind::StringMatchesRegex);
+                builder.expected_string(expected);
+            }
         }
 
         Ok(builder.build()?)
@@ -7,8 +7,8 @@ use tokio::sync::Mutex;
 use url::Url;
 
 use super::{
-    PkgResult, SiPkgActionFunc, SiPkgError, SiPkgFuncDescription, SiPkgLeafFunction, SiPkgProp,
-    SiPkgSiPropFunc, SiPkgSocket, Source,
+    PkgResult, SiPkgActionFunc, SiPkgAuthenticationFunc, SiPkgError, SiPkgFuncDescription,
+    SiPkgLeafFunction, SiPkgProp, SiPkgSiPropFunc, SiPkgSocket, Source,
 };
 
 use crate::{
@@ -32,8 +32,8 @@ pub struct SiPkgSchemaVariant<'a> {
 }
 
 macro_rules! impl_variant_children_from_graph {
-    ($fn_name:ident, SchemaVariantChildNode::$child_node:ident, $pkg_type:ident) => {
-        pub fn $fn_name(&self) -> PkgResult<Vec<$pkg_type>> {
+    ($vis:vis $fn_name:ident, SchemaVariantChildNode::$child_node:ident, $pkg_type:ident) => {
+        $vis fn $fn_name(&self) -> PkgResult<Vec<$pkg_type>> {
             let mut entries = vec![];
             if let Some(child_idxs) = self
                 .source
@@ -111,24 +111,37 @@ impl<'a> SiPkgSchemaVariant<'a> {
         self.func_unique_id
     }
 
-    impl_variant_children_from_graph!(sockets, SchemaVariantChildNode::Sockets, SiPkgSocket);
+    impl_variant_children_from_graph!(pub sockets, SchemaVariantChildNode::Sockets, SiPkgSocket);
     impl_variant_children_from_graph!(
-        func_descriptions,
+        pub func_descriptions,
         SchemaVariantChildNode::FuncDescriptions,
         SiPkgFuncDescription
     );
     impl_variant_children_from_graph!(
-        leaf_functions,
+        pub leaf_functions,
         SchemaVariantChildNode::LeafFunctions,
         SiPkgLeafFunction
     );
     impl_variant_children_from_graph!(
-        action_funcs,
+        pub action_funcs,
         SchemaVariantChildNode::ActionFuncs,
         SiPkgActionFunc
     );
     impl_variant_children_from_graph!(
-        si_prop_funcs,
+        unordered_authentication_funcs,
+        SchemaVariantChildNode::AuthenticationFuncs,
+        SiPkgAuthenticationFunc
+    );
+
+    /// The authentication funcs of the variant, in the order they run.
+    pub fn authentication_funcs(&self) -> PkgResult<Vec<SiPkgAuthenticationFunc>> {
+        let mut authentication_funcs = self.unordered_authentication_funcs()?;
+        authentication_funcs.sort_by_key(|authentication_func| authentication_func.position());
+        Ok(authentication_funcs)
+    }
+
+    impl_variant_children_from_graph!(
+        pub si_prop_funcs,
         SchemaVariantChildNode::SiPropFuncs,
         SiPkgSiPropFunc
     );
@@ -341,6 +354,10 @@ impl<'a> SiPkgSchemaVariant<'a> {
             builder.action_func(action_func.try_into()?);
         }
 
+        for authentication_func in self.authentication_funcs()? {
+            builder.authentication_func(authentication_func.try_into()?);
+        }
+
         for func_description in self.func_descriptions()? {
             builder.func_description(func_description.try_into()?);
         }
@@ -407,14 +424,23 @@ async fn create_prop_stack(
                 builder.default_value(serde_json::to_value(dv)?);
             }
         }
-        SiPkgProp::Object { .. } => {
+        SiPkgProp::Object { default_value, .. } => {
             builder.kind(PropSpecKind::Object);
+            if let Some(dv) = default_value {
+                builder.default_value(dv.to_owned());
+            }
         }
-        SiPkgProp::Array { .. } => {
+        SiPkgProp::Array { default_value, .. } => {
             builder.kind(PropSpecKind::Array);
+            if let Some(dv) = default_value {
+                builder.default_value(dv.to_owned());
+            }
         }
-        SiPkgProp::Map { .. } => {
+        SiPkgProp::Map { default_value, .. } => {
             builder.kind(PropSpecKind::Map);
+            if let Some(dv) = default_value {
+                builder.default_value(dv.to_owned());
+            }
             for map_key_func in spec.map_key_funcs()? {
                 builder.map_key_func(MapKeyFuncSpec::try_from(map_key_func)?);
             }
@@ -0,0 +1,207 @@
+use object_tree::Hash;
+use petgraph::{algo::tarjan_scc, prelude::*};
+use semver::{Version, VersionReq};
+
+use crate::{pkg::PkgResult, PkgDependencySpec, SiPkg};
+
+/// Why a package can't be installed with the rest of a set of packages.
+#[remain::sorted]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum DependencyProblem {
+    /// The package depends on another package of the set that can't be installed.
+    Blocked { package: String, dependency: String },
+    /// The set has packages with the dependency's name, but none with a version (or root hash)
+    /// that satisfies it.
+    Conflicting {
+        package: String,
+        dependency: PkgDependencySpec,
+        found_versions: Vec<String>,
+    },
+    /// The packages depend on one another, so none of them can be installed first.
+    Cycle { packages: Vec<String> },
+    /// The set has no package with the dependency's name.
+    Missing {
+        package: String,
+        dependency: PkgDependencySpec,
+    },
+}
+
+/// The order in which a set of packages can be installed, and the problems keeping any of them
+/// from being installed.
+#[derive(Clone, Debug)]
+pub struct DependencyResolution {
+    install_order: Vec<SiPkg>,
+    problems: Vec<DependencyProblem>,
+}
+
+impl DependencyResolution {
+    /// The packages that can be installed, each after the packages it depends on.
+    pub fn install_order(&self) -> &[SiPkg] {
+        &self.install_order
+    }
+
+    pub fn problems(&self) -> &[DependencyProblem] {
+        &self.problems
+    }
+
+    /// Whether every package of the set can be installed.
+    pub fn is_resolved(&self) -> bool {
+        self.problems.is_empty()
+    }
+
+    pub fn into_install_order(self) -> Vec<SiPkg> {
+        self.install_order
+    }
+}
+
+struct PkgEntry {
+    pkg: SiPkg,
+    name: String,
+    version: String,
+    hash: Hash,
+    dependencies: Vec<PkgDependencySpec>,
+}
+
+/// Orders a set of packages so that every package comes after the packages it depends on.
+///
+/// Dependencies are only looked for within the set, so packages that are already installed must
+/// be part of it. When more than one package satisfies a dependency, the one with the highest
+/// version is used. Packages whose dependencies can't be satisfied are left out of the install
+/// order, along with the packages depending on them, and are reported as problems.
+pub fn resolve_install_order(pkgs: Vec<SiPkg>) -> PkgResult<DependencyResolution> {
+    let mut entries = Vec::with_capacity(pkgs.len());
+    for pkg in pkgs {
+        let metadata = pkg.metadata()?;
+        let mut dependencies = Vec::new();
+        for dependency in pkg.dependencies()? {
+            dependencies.push(PkgDependencySpec::try_from(dependency)?);
+        }
+
+        entries.push(PkgEntry {
+            name: metadata.name().to_string(),
+            version: metadata.version().to_string(),
+            hash: metadata.hash(),
+            dependencies,
+            pkg,
+        });
+    }
+
+    let mut problems = Vec::new();
+    let mut installable = vec![true; entries.len()];
+
+    // An edge goes from a package to each package depending on it
+    let mut graph: Graph<usize, ()> = Graph::new();
+    let node_idxs: Vec<NodeIndex> = (0..entries.len()).map(|idx| graph.add_node(idx)).collect();
+
+    for (idx, entry) in entries.iter().enumerate() {
+        for dependency in &entry.dependencies {
+            let candidates: Vec<usize> = entries
+                .iter()
+                .enumerate()
+                .filter(|(_, candidate)| candidate.name == dependency.name)
+                .map(|(candidate_idx, _)| candidate_idx)
+                .collect();
+            if candidates.is_empty() {
+                installable[idx] = false;
+                problems.push(DependencyProblem::Missing {
+                    package: entry.name.clone(),
+                    dependency: dependency.clone(),
+                });
+                continue;
+            }
+
+            let chosen = candidates
+                .iter()
+                .copied()
+                .filter(|candidate_idx| satisfies(&entries[*candidate_idx], dependency))
+                .max_by_key(|candidate_idx| {
+                    Version::parse(entries[*candidate_idx].version.trim()).ok()
+                });
+            match chosen {
+                Some(dependency_idx) => {
+                    graph.update_edge(node_idxs[dependency_idx], node_idxs[idx], ());
+                }
+                None => {
+                    installable[idx] = false;
+                    problems.push(DependencyProblem::Conflicting {
+                        package: entry.name.clone(),
+                        dependency: dependency.clone(),
+                        found_versions: candidates
+                            .iter()
+                            .map(|candidate_idx| entries[*candidate_idx].version.clone())
+                            .collect(),
+                    });
+                }
+            }
+        }
+    }
+
+    // Strongly connected components come out with the packages depending on others first
+    let mut components = tarjan_scc(&graph);
+    components.reverse();
+
+    let mut install_idxs = Vec::with_capacity(entries.len());
+    for component in components {
+        let is_cycle = component.len() > 1 || graph.contains_edge(component[0], component[0]);
+        if is_cycle {
+            for node_idx in &component {
+                installable[graph[*node_idx]] = false;
+            }
+            problems.push(DependencyProblem::Cycle {
+                packages: component
+                    .iter()
+                    .map(|node_idx| entries[graph[*node_idx]].name.clone())
+                    .collect(),
+            });
+            continue;
+        }
+
+        let idx = graph[component[0]];
+        if !installable[idx] {
+            continue;
+        }
+        if let Some(blocking_idx) = graph
+            .neighbors_directed(component[0], Incoming)
+            .map(|node_idx| graph[node_idx])
+            .find(|dependency_idx| !installable[*dependency_idx])
+        {
+            installable[idx] = false;
+            problems.push(DependencyProblem::Blocked {
+                package: entries[idx].name.clone(),
+                dependency: entries[blocking_idx].name.clone(),
+            });
+            continue;
+        }
+
+        install_idxs.push(idx);
+    }
+
+    let mut pkgs: Vec<Option<SiPkg>> = entries.into_iter().map(|entry| Some(entry.pkg)).collect();
+    let install_order = install_idxs
+        .into_iter()
+        .filter_map(|idx| pkgs[idx].take())
+        .collect();
+
+    Ok(DependencyResolution {
+        install_order,
+        problems,
+    })
+}
+
+/// Whether a package satisfies a dependency on it. Versions that aren't semver, such as dates,
+/// only satisfy a requirement that is the same version or `*`.
+fn satisfies(entry: &PkgEntry, dependency: &PkgDependencySpec) -> bool {
+    if let Some(hash) = dependency.hash {
+        if hash != entry.hash {
+            return false;
+        }
+    }
+
+    let version = entry.version.trim();
+    let version_req = dependency.version_req.trim();
+    match (VersionReq::parse(version_req), Version::parse(version)) {
+        (Ok(req), _) if req == VersionReq::STAR => true,
+        (Ok(req), Ok(version)) => req.matches(&version),
+        _ => version_req == version,
+    }
+}
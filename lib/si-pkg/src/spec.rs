@@ -5,6 +5,8 @@ use thiserror::Error;
 
 mod action_func;
 mod attr_func_input;
+mod authentication_func;
+mod dependency;
 mod func;
 mod func_description;
 mod leaf_function;
@@ -17,8 +19,9 @@ mod validation;
 mod variant;
 
 pub use {
-    action_func::*, attr_func_input::*, func::*, func_description::*, leaf_function::*,
-    map_key_func::*, prop::*, schema::*, si_prop_func::*, socket::*, validation::*, variant::*,
+    action_func::*, attr_func_input::*, authentication_func::*, dependency::*, func::*,
+    func_description::*, leaf_function::*, map_key_func::*, prop::*, schema::*, si_prop_func::*,
+    socket::*, validation::*, variant::*,
 };
 
 #[derive(Builder, Clone, Debug, Deserialize, Serialize)]
@@ -42,6 +45,10 @@ pub struct PkgSpec {
 
     #[builder(setter(each(name = "func", into)), default)]
     pub funcs: Vec<FuncSpec>,
+
+    #[builder(setter(each(name = "dependency", into)), default)]
+    #[serde(default)]
+    pub dependencies: Vec<PkgDependencySpec>,
 }
 
 impl PkgSpec {
@@ -62,6 +69,26 @@ impl PkgSpec {
             .iter()
             .find(|func_spec| func_spec.name.as_str() == name)
     }
+
+    /// Serializes the spec as pretty-printed JSON, which [`PkgSpec::from_json`] reads back into
+    /// the same spec, and so into a package with the same hashes.
+    pub fn to_json(&self) -> Result<String, SpecError> {
+        Ok(serde_json::to_string_pretty(self)?)
+    }
+
+    pub fn from_json(json: impl AsRef<str>) -> Result<Self, SpecError> {
+        Ok(serde_json::from_str(json.as_ref())?)
+    }
+
+    /// Serializes the spec as YAML, which [`PkgSpec::from_yaml`] reads back into the same spec,
+    /// and so into a package with the same hashes.
+    pub fn to_yaml(&self) -> Result<String, SpecError> {
+        Ok(serde_yaml::to_string(self)?)
+    }
+
+    pub fn from_yaml(yaml: impl AsRef<str>) -> Result<Self, SpecError> {
+        Ok(serde_yaml::from_str(yaml.as_ref())?)
+    }
 }
 
 impl PkgSpecBuilder {
@@ -74,6 +101,15 @@ impl PkgSpecBuilder {
         Ok(self.schema(converted))
     }
 
+    #[allow(unused_mut)]
+    pub fn try_dependency<I>(&mut self, item: I) -> Result<&mut Self, I::Error>
+    where
+        I: TryInto<PkgDependencySpec>,
+    {
+        let converted: PkgDependencySpec = item.try_into()?;
+        Ok(self.dependency(converted))
+    }
+
     #[allow(unused_mut)]
     pub fn try_func<I>(&mut self, item: I) -> Result<&mut Self, I::Error>
     where
@@ -97,6 +133,10 @@ impl TryFrom<PkgSpecBuilder> for PkgSpec {
 pub enum SpecError {
     #[error("Can't convert {0} to LeafInputLocation")]
     LeafInputLocationConversionError(String),
+    #[error(transparent)]
+    SerdeJson(#[from] serde_json::Error),
+    #[error(transparent)]
+    SerdeYaml(#[from] serde_yaml::Error),
     /// Uninitialized field
     #[error("{0} must be initialized")]
     UninitializedField(&'static str),
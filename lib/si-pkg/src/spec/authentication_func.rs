@@ -0,0 +1,23 @@
+use derive_builder::Builder;
+use serde::{Deserialize, Serialize};
+
+use super::{FuncUniqueId, SpecError};
+
+/// A func that sets up credentials for a schema variant before its actions run. Funcs with a lower
+/// position run first.
+#[derive(Builder, Clone, Debug, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+#[builder(build_fn(error = "SpecError"))]
+pub struct AuthenticationFuncSpec {
+    #[builder(setter(into))]
+    pub func_unique_id: FuncUniqueId,
+
+    #[builder(setter(into))]
+    pub position: i64,
+}
+
+impl AuthenticationFuncSpec {
+    pub fn builder() -> AuthenticationFuncSpecBuilder {
+        AuthenticationFuncSpecBuilder::default()
+    }
+}
@@ -0,0 +1,27 @@
+use derive_builder::Builder;
+use object_tree::Hash;
+use serde::{Deserialize, Serialize};
+
+use super::SpecError;
+
+/// Another package that must be installed before this one.
+#[derive(Builder, Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+#[builder(build_fn(error = "SpecError"))]
+pub struct PkgDependencySpec {
+    #[builder(setter(into))]
+    pub name: String,
+    /// A semver requirement such as `^1.2`, or the exact version for packages not versioned with
+    /// semver.
+    #[builder(setter(into), default = "\"*\".to_string()")]
+    pub version_req: String,
+    /// The root hash of the one package build that satisfies this dependency, if pinned.
+    #[builder(setter(into, strip_option), default)]
+    pub hash: Option<Hash>,
+}
+
+impl PkgDependencySpec {
+    pub fn builder() -> PkgDependencySpecBuilder {
+        PkgDependencySpecBuilder::default()
+    }
+}
@@ -69,6 +69,7 @@ pub enum FuncSpecBackendKind {
     String,
     Unset,
     Validation,
+    WorkspaceIntegration,
 }
 
 #[remain::sorted]
@@ -169,6 +169,35 @@ impl PropSpecBuilder {
         self
     }
 
+    /// Appends an item to the default value of an array prop.
+    pub fn default_item(&mut self, value: impl Into<serde_json::Value>) -> &mut Self {
+        match &mut self.default_value {
+            Some(serde_json::Value::Array(items)) => items.push(value.into()),
+            _ => self.default_value = Some(serde_json::Value::Array(vec![value.into()])),
+        }
+        self
+    }
+
+    /// Appends a keyed entry to the default value of a map prop. Entries keep the order they
+    /// were added in.
+    pub fn default_entry(
+        &mut self,
+        key: impl Into<String>,
+        value: impl Into<serde_json::Value>,
+    ) -> &mut Self {
+        match &mut self.default_value {
+            Some(serde_json::Value::Object(entries)) => {
+                entries.insert(key.into(), value.into());
+            }
+            _ => {
+                let mut entries = serde_json::Map::new();
+                entries.insert(key.into(), value.into());
+                self.default_value = Some(serde_json::Value::Object(entries));
+            }
+        }
+        self
+    }
+
     #[allow(unused_mut)]
     pub fn kind(&mut self, value: impl Into<PropSpecKind>) -> &mut Self {
         self.kind = Some(value.into());
@@ -341,42 +370,77 @@ impl PropSpecBuilder {
                     hidden: Some(hidden),
                     doc_link,
                 },
-                PropSpecKind::Map => PropSpec::Map {
-                    name,
-                    // TODO: Validate these types
-                    default_value: self.default_value.to_owned(),
-                    type_prop: match self.type_prop {
+                PropSpecKind::Map => {
+                    let type_prop = match self.type_prop {
                         Some(ref value) => Box::new(value.clone()),
                         None => {
                             return Err(UninitializedFieldError::from("type_prop").into());
                         }
-                    },
-                    validations: Some(validations),
-                    func_unique_id,
-                    inputs: Some(inputs),
-                    widget_kind,
-                    widget_options,
-                    hidden: Some(hidden),
-                    doc_link,
-                    map_key_funcs: Some(self.map_key_funcs.to_owned()),
-                },
-                PropSpecKind::Array => PropSpec::Array {
-                    name,
-                    default_value: self.default_value.to_owned(),
-                    type_prop: match self.type_prop {
+                    };
+                    if let Some(default_value) = &self.default_value {
+                        match default_value.as_object() {
+                            Some(entries) => {
+                                for value in entries.values() {
+                                    check_default_item(&type_prop, value)?;
+                                }
+                            }
+                            None => {
+                                return Err(SpecError::ValidationError(
+                                    "Map props must get an object as a default value".to_string(),
+                                ));
+                            }
+                        }
+                    }
+
+                    PropSpec::Map {
+                        name,
+                        default_value: self.default_value.to_owned(),
+                        type_prop,
+                        validations: Some(validations),
+                        func_unique_id,
+                        inputs: Some(inputs),
+                        widget_kind,
+                        widget_options,
+                        hidden: Some(hidden),
+                        doc_link,
+                        map_key_funcs: Some(self.map_key_funcs.to_owned()),
+                    }
+                }
+                PropSpecKind::Array => {
+                    let type_prop = match self.type_prop {
                         Some(ref value) => Box::new(value.clone()),
                         None => {
                             return Err(UninitializedFieldError::from("type_prop").into());
                         }
-                    },
-                    validations: Some(validations),
-                    func_unique_id,
-                    inputs: Some(inputs),
-                    widget_kind,
-                    widget_options,
-                    hidden: Some(hidden),
-                    doc_link,
-                },
+                    };
+                    if let Some(default_value) = &self.default_value {
+                        match default_value.as_array() {
+                            Some(items) => {
+                                for value in items {
+                                    check_default_item(&type_prop, value)?;
+                                }
+                            }
+                            None => {
+                                return Err(SpecError::ValidationError(
+                                    "Array props must get an array as a default value".to_string(),
+                                ));
+                            }
+                        }
+                    }
+
+                    PropSpec::Array {
+                        name,
+                        default_value: self.default_value.to_owned(),
+                        type_prop,
+                        validations: Some(validations),
+                        func_unique_id,
+                        inputs: Some(inputs),
+                        widget_kind,
+                        widget_options,
+                        hidden: Some(hidden),
+                        doc_link,
+                    }
+                }
                 PropSpecKind::Object => PropSpec::Object {
                     name,
                     default_value: self.default_value.to_owned(),
@@ -397,6 +461,26 @@ impl PropSpecBuilder {
     }
 }
 
+/// Checks that an item of the default value of an array or map prop is of the kind of the prop's
+/// items.
+fn check_default_item(type_prop: &PropSpec, value: &serde_json::Value) -> Result<(), SpecError> {
+    let (matches, kind) = match type_prop {
+        PropSpec::Array { .. } => (value.is_array(), "an array"),
+        PropSpec::Boolean { .. } => (value.is_boolean(), "a bool"),
+        PropSpec::Map { .. } | PropSpec::Object { .. } => (value.is_object(), "an object"),
+        PropSpec::Number { .. } => (value.is_i64(), "an i64"),
+        PropSpec::String { .. } => (value.is_string(), "a string"),
+    };
+
+    if matches {
+        Ok(())
+    } else {
+        Err(SpecError::ValidationError(format!(
+            "Default items of props with {kind} as their item must be {kind}, found {value}"
+        )))
+    }
+}
+
 impl TryFrom<PropSpecBuilder> for PropSpec {
     type Error = SpecError;
 
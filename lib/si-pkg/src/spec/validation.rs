@@ -31,6 +31,15 @@ pub enum ValidationSpec {
     StringIsHexColor,
     StringIsNotEmpty,
     StringIsValidIpAddr,
+    /// The number of characters of the string, inclusive of both bounds.
+    StringLengthIsBetweenTwoIntegers {
+        lower_bound: i64,
+        upper_bound: i64,
+    },
+    /// The string must match `expected`, a regular expression.
+    StringMatchesRegex {
+        expected: String,
+    },
 }
 
 impl ValidationSpec {
@@ -53,6 +62,8 @@ pub enum ValidationSpecKind {
     StringIsHexColor,
     StringIsNotEmpty,
     StringIsValidIpAddr,
+    StringLengthIsBetweenTwoIntegers,
+    StringMatchesRegex,
 }
 
 #[derive(Clone, Debug, Default)]
@@ -140,6 +151,23 @@ impl ValidationSpecBuilder {
                         .ok_or(UninitializedFieldError::from("expected_string"))?,
                 },
                 ValidationSpecKind::StringIsValidIpAddr => ValidationSpec::StringIsValidIpAddr,
+                ValidationSpecKind::StringLengthIsBetweenTwoIntegers => {
+                    ValidationSpec::StringLengthIsBetweenTwoIntegers {
+                        lower_bound: self
+                            .lower_bound
+                            .ok_or(UninitializedFieldError::from("lower_bound"))?,
+                        upper_bound: self
+                            .upper_bound
+                            .ok_or(UninitializedFieldError::from("upper_bound"))?,
+                    }
+                }
+                ValidationSpecKind::StringMatchesRegex => ValidationSpec::StringMatchesRegex {
+                    expected: self
+                        .expected_string
+                        .as_ref()
+                        .ok_or(UninitializedFieldError::from("expected_string"))?
+                        .to_string(),
+                },
                 ValidationSpecKind::StringIsHexColor => ValidationSpec::StringIsHexColor,
                 ValidationSpecKind::StringIsNotEmpty => ValidationSpec::StringIsNotEmpty,
                 ValidationSpecKind::CustomValidation => ValidationSpec::CustomValidation {
@@ -5,8 +5,8 @@ use strum::{AsRefStr, Display, EnumIter, EnumString};
 use url::Url;
 
 use super::{
-    ActionFuncSpec, FuncDescriptionSpec, LeafFunctionSpec, PropSpec, PropSpecWidgetKind,
-    SiPropFuncSpec, SocketSpec, SpecError,
+    ActionFuncSpec, AuthenticationFuncSpec, FuncDescriptionSpec, LeafFunctionSpec, PropSpec,
+    PropSpecWidgetKind, SiPropFuncSpec, SocketSpec, SpecError,
 };
 
 #[remain::sorted]
@@ -77,6 +77,10 @@ pub struct SchemaVariantSpec {
     #[builder(setter(each(name = "action_func"), into), default)]
     pub action_funcs: Vec<ActionFuncSpec>,
 
+    #[serde(default)]
+    #[builder(setter(each(name = "authentication_func"), into), default)]
+    pub authentication_funcs: Vec<AuthenticationFuncSpec>,
+
     #[builder(setter(each(name = "leaf_function"), into), default)]
     pub leaf_functions: Vec<LeafFunctionSpec>,
 
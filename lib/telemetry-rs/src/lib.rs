@@ -18,7 +18,7 @@ pub use opentelemetry::{self, trace::SpanKind};
 pub use tracing;
 
 pub mod prelude {
-    pub use super::{FormattedSpanKind, SpanExt, SpanKind};
+    pub use super::{metric, FormattedSpanKind, SpanExt, SpanKind};
     pub use tracing::{
         self, debug, debug_span, enabled, error, event, event_enabled, field::Empty, info,
         info_span, instrument, span, span_enabled, trace, trace_span, warn, Instrument, Level,
@@ -26,6 +26,29 @@ pub mod prelude {
     };
 }
 
+/// The tracing target of events emitted with [`metric!`].
+pub const METRICS_TARGET: &str = "metrics";
+
+/// Emits a metric as a tracing event.
+///
+/// Field names follow the conventions of `tracing_opentelemetry::MetricsLayer`: a
+/// `monotonic_counter.`, `counter.` or `histogram.` prefix selects the kind of instrument and the
+/// rest of the name is the name of the metric. Any other fields describe the measurement.
+///
+/// ```
+/// telemetry::metric!(monotonic_counter.jobs.processed = 1_u64, job.kind = "build");
+/// ```
+#[macro_export]
+macro_rules! metric {
+    ($($fields:tt)+) => {
+        $crate::tracing::event!(
+            target: $crate::METRICS_TARGET,
+            $crate::tracing::Level::INFO,
+            $($fields)+
+        )
+    };
+}
+
 pub struct FormattedSpanKind(pub SpanKind);
 
 impl fmt::Display for FormattedSpanKind {
@@ -0,0 +1,430 @@
+use std::{collections::BTreeSet, path::Path, sync::Arc};
+
+use cyclone_core::{
+    ActionRunRequest, ActionRunResultSuccess, FunctionResult, OutputStream, ReconciliationRequest,
+    ReconciliationResultSuccess, ResolverFunctionRequest, ResolverFunctionResultSuccess,
+    SchemaVariantDefinitionRequest, SchemaVariantDefinitionResultSuccess, ValidationRequest,
+    ValidationResultSuccess,
+};
+use futures::{stream::BoxStream, StreamExt};
+use nats_subscriber::{compression, RawMessage, SubscriberError};
+use telemetry::prelude::*;
+use tokio::sync::mpsc;
+use veritech_core::{
+    nats_action_run_subject, nats_reconciliation_subject, nats_resolver_function_subject,
+    nats_schema_variant_definition_subject, nats_validation_subject, reply_mailbox_for_output,
+    reply_mailbox_for_result, FINAL_MESSAGE_HEADER_KEY,
+};
+
+use crate::{
+    chunks,
+    envelope::RequestEnvelope,
+    output::{DroppedOutput, OutputForwarder},
+    recording, simulated_result, ClientError, ClientResult, SimulatedResults, Transport,
+};
+
+/// A request for any kind of function, used to run several functions with
+/// [`Client::execute_batch`](crate::Client::execute_batch).
+#[remain::sorted]
+#[derive(Clone, Debug)]
+pub enum BatchRequest {
+    ActionRun(ActionRunRequest),
+    Reconciliation(ReconciliationRequest),
+    ResolverFunction(ResolverFunctionRequest),
+    SchemaVariantDefinition(SchemaVariantDefinitionRequest),
+    Validation(ValidationRequest),
+}
+
+/// The result of a [`BatchRequest`].
+#[remain::sorted]
+#[derive(Debug)]
+pub enum VeritechResult {
+    ActionRun(FunctionResult<ActionRunResultSuccess>),
+    Reconciliation(FunctionResult<ReconciliationResultSuccess>),
+    ResolverFunction(FunctionResult<ResolverFunctionResultSuccess>),
+    SchemaVariantDefinition(FunctionResult<SchemaVariantDefinitionResultSuccess>),
+    Validation(FunctionResult<ValidationResultSuccess>),
+}
+
+/// One result from [`Client::execute_batch`](crate::Client::execute_batch). Results arrive in
+/// the order the executions finish, so `index` is the position of the request in the batch.
+#[derive(Debug)]
+pub struct BatchResult {
+    pub index: usize,
+    pub result: ClientResult<VeritechResult>,
+    /// How many lines of the execution's output were dropped by the time its result arrived,
+    /// because the caller's channel was full.
+    pub dropped_output_lines: u64,
+}
+
+impl BatchRequest {
+    pub fn execution_id(&self) -> &str {
+        match self {
+            Self::ActionRun(request) => &request.execution_id,
+            Self::Reconciliation(request) => &request.execution_id,
+            Self::ResolverFunction(request) => &request.execution_id,
+            Self::SchemaVariantDefinition(request) => &request.execution_id,
+            Self::Validation(request) => &request.execution_id,
+        }
+    }
+
+    fn subject(&self, prefix: Option<&str>) -> String {
+        match self {
+            Self::ActionRun(_) => nats_action_run_subject(prefix),
+            Self::Reconciliation(_) => nats_reconciliation_subject(prefix),
+            Self::ResolverFunction(_) => nats_resolver_function_subject(prefix),
+            Self::SchemaVariantDefinition(_) => nats_schema_variant_definition_subject(prefix),
+            Self::Validation(_) => nats_validation_subject(prefix),
+        }
+    }
+
+    fn to_message(&self) -> ClientResult<Vec<u8>> {
+        match self {
+            Self::ActionRun(request) => serde_json::to_vec(request),
+            Self::Reconciliation(request) => serde_json::to_vec(request),
+            Self::ResolverFunction(request) => serde_json::to_vec(request),
+            Self::SchemaVariantDefinition(request) => serde_json::to_vec(request),
+            Self::Validation(request) => serde_json::to_vec(request),
+        }
+        .map_err(ClientError::JSONSerialize)
+    }
+
+    fn result_from_message(&self, data: &[u8]) -> ClientResult<VeritechResult> {
+        match self {
+            Self::ActionRun(_) => serde_json::from_slice(data).map(VeritechResult::ActionRun),
+            Self::Reconciliation(_) => {
+                serde_json::from_slice(data).map(VeritechResult::Reconciliation)
+            }
+            Self::ResolverFunction(_) => {
+                serde_json::from_slice(data).map(VeritechResult::ResolverFunction)
+            }
+            Self::SchemaVariantDefinition(_) => {
+                serde_json::from_slice(data).map(VeritechResult::SchemaVariantDefinition)
+            }
+            Self::Validation(_) => serde_json::from_slice(data).map(VeritechResult::Validation),
+        }
+        .map_err(ClientError::JSONDeserialize)
+    }
+
+    async fn simulated(
+        &self,
+        simulation: &SimulatedResults,
+        output_tx: mpsc::Sender<OutputStream>,
+    ) -> ClientResult<VeritechResult> {
+        Ok(match self {
+            Self::ActionRun(request) => {
+                VeritechResult::ActionRun(simulated_result(simulation, output_tx, request).await?)
+            }
+            Self::Reconciliation(request) => VeritechResult::Reconciliation(
+                simulated_result(simulation, output_tx, request).await?,
+            ),
+            Self::ResolverFunction(request) => VeritechResult::ResolverFunction(
+                simulated_result(simulation, output_tx, request).await?,
+            ),
+            Self::SchemaVariantDefinition(request) => VeritechResult::SchemaVariantDefinition(
+                simulated_result(simulation, output_tx, request).await?,
+            ),
+            Self::Validation(request) => {
+                VeritechResult::Validation(simulated_result(simulation, output_tx, request).await?)
+            }
+        })
+    }
+
+    async fn replayed(&self, fixture_dir: &Path) -> ClientResult<VeritechResult> {
+        Ok(match self {
+            Self::ActionRun(request) => {
+                VeritechResult::ActionRun(recording::replay(fixture_dir, request).await?)
+            }
+            Self::Reconciliation(request) => {
+                VeritechResult::Reconciliation(recording::replay(fixture_dir, request).await?)
+            }
+            Self::ResolverFunction(request) => {
+                VeritechResult::ResolverFunction(recording::replay(fixture_dir, request).await?)
+            }
+            Self::SchemaVariantDefinition(request) => VeritechResult::SchemaVariantDefinition(
+                recording::replay(fixture_dir, request).await?,
+            ),
+            Self::Validation(request) => {
+                VeritechResult::Validation(recording::replay(fixture_dir, request).await?)
+            }
+        })
+    }
+
+    async fn record(&self, fixture_dir: &Path, result: &VeritechResult) -> ClientResult<()> {
+        match (self, result) {
+            (Self::ActionRun(request), VeritechResult::ActionRun(result)) => {
+                recording::record(fixture_dir, request, result).await
+            }
+            (Self::Reconciliation(request), VeritechResult::Reconciliation(result)) => {
+                recording::record(fixture_dir, request, result).await
+            }
+            (Self::ResolverFunction(request), VeritechResult::ResolverFunction(result)) => {
+                recording::record(fixture_dir, request, result).await
+            }
+            (
+                Self::SchemaVariantDefinition(request),
+                VeritechResult::SchemaVariantDefinition(result),
+            ) => recording::record(fixture_dir, request, result).await,
+            (Self::Validation(request), VeritechResult::Validation(result)) => {
+                recording::record(fixture_dir, request, result).await
+            }
+            // A result is always of the same kind as its request
+            _ => Ok(()),
+        }
+    }
+}
+
+/// Runs every request in simulation mode, one after the other.
+pub(crate) async fn simulated_batch(
+    simulation: &SimulatedResults,
+    requests: Vec<BatchRequest>,
+    output_tx: mpsc::Sender<OutputStream>,
+) -> BoxStream<'static, BatchResult> {
+    let mut results = Vec::with_capacity(requests.len());
+    for (index, request) in requests.iter().enumerate() {
+        results.push(BatchResult {
+            index,
+            result: request.simulated(simulation, output_tx.clone()).await,
+            dropped_output_lines: 0,
+        });
+    }
+    futures::stream::iter(results).boxed()
+}
+
+/// Answers every request with its recorded result, one after the other.
+pub(crate) async fn replayed_batch(
+    fixture_dir: &Path,
+    requests: Vec<BatchRequest>,
+) -> BoxStream<'static, BatchResult> {
+    let mut results = Vec::with_capacity(requests.len());
+    for (index, request) in requests.iter().enumerate() {
+        results.push(BatchResult {
+            index,
+            result: request.replayed(fixture_dir).await,
+            dropped_output_lines: 0,
+        });
+    }
+    futures::stream::iter(results).boxed()
+}
+
+/// Records the result of every execution of a batch as it arrives.
+pub(crate) fn record_batch(
+    fixture_dir: &Path,
+    requests: Vec<BatchRequest>,
+    results: BoxStream<'static, BatchResult>,
+) -> BoxStream<'static, BatchResult> {
+    let fixture_dir = Arc::new(fixture_dir.to_path_buf());
+    let requests = Arc::new(requests);
+    results
+        .then(move |batch_result| {
+            let fixture_dir = fixture_dir.clone();
+            let requests = requests.clone();
+            async move {
+                if let (Some(request), Ok(result)) =
+                    (requests.get(batch_result.index), &batch_result.result)
+                {
+                    if let Err(err) = request.record(&fixture_dir, result).await {
+                        warn!(error = ?err, "failed to record execution");
+                    }
+                }
+                batch_result
+            }
+        })
+        .boxed()
+}
+
+/// Publishes every request with its own reply mailbox under a shared root, then returns a stream
+/// of results as they arrive on the shared subscriptions.
+pub(crate) async fn execute_batch(
+    transport: &dyn Transport,
+    requests: Vec<BatchRequest>,
+    envelope: &RequestEnvelope,
+    forwarder: OutputForwarder,
+    dropped_output: DroppedOutput,
+) -> ClientResult<BoxStream<'static, BatchResult>> {
+    let reply_mailbox_root = transport.new_inbox();
+    // Each request replies to `<root>.<index>`, so one wildcard subscription per kind of message
+    // covers the whole batch
+    let item_mailboxes = format!("{reply_mailbox_root}.*");
+
+    let result_subscription = chunks::reassemble_chunks(
+        transport
+            .subscribe(&reply_mailbox_for_result(&item_mailboxes))
+            .await?,
+        None,
+    );
+    let output_subscription = transport
+        .subscribe(&reply_mailbox_for_output(&item_mailboxes))
+        .await?;
+    // The item mailboxes themselves receive a reply if nobody is listening to a request subject
+    let root_subscription = transport.subscribe(&item_mailboxes).await?;
+
+    tokio::spawn(forward_batch_output_task(
+        output_subscription,
+        forwarder,
+        requests.len(),
+    ));
+
+    let prefix = transport.subject_prefix();
+    for (index, request) in requests.iter().enumerate() {
+        let subject = envelope.route(request.subject(prefix));
+        trace!(
+            messaging.destination = &subject.as_str(),
+            index,
+            "publishing batch message"
+        );
+        let msg = envelope.seal(request.to_message()?)?;
+        transport
+            .publish_with_reply(
+                &subject,
+                Some(format!("{reply_mailbox_root}.{index}")),
+                msg.headers.as_ref(),
+                msg.payload,
+            )
+            .await?;
+    }
+
+    let state = BatchState {
+        pending: (0..requests.len()).collect(),
+        requests,
+        dropped_output,
+        result_subscription: Some(result_subscription),
+        root_subscription: Some(root_subscription),
+    };
+
+    Ok(futures::stream::unfold(state, next_batch_result).boxed())
+}
+
+struct BatchState {
+    requests: Vec<BatchRequest>,
+    pending: BTreeSet<usize>,
+    dropped_output: DroppedOutput,
+    result_subscription: Option<BoxStream<'static, RawMessage>>,
+    root_subscription: Option<BoxStream<'static, RawMessage>>,
+}
+
+impl BatchState {
+    fn dropped_output_lines(&self, index: usize) -> u64 {
+        self.dropped_output
+            .count(self.requests[index].execution_id())
+    }
+
+    /// Ends the batch's subscriptions by dropping them.
+    fn unsubscribe(&mut self) {
+        self.result_subscription = None;
+        self.root_subscription = None;
+    }
+}
+
+async fn next_batch_result(mut state: BatchState) -> Option<(BatchResult, BatchState)> {
+    loop {
+        if state.pending.is_empty() {
+            state.unsubscribe();
+            return None;
+        }
+
+        let (result_subscription, root_subscription) = match (
+            state.result_subscription.as_mut(),
+            state.root_subscription.as_mut(),
+        ) {
+            (Some(result_subscription), Some(root_subscription)) => {
+                (result_subscription, root_subscription)
+            }
+            // The subscriptions have closed, so whatever is left will never get a result
+            _ => {
+                let index = state.pending.pop_first()?;
+                return Some((
+                    BatchResult {
+                        index,
+                        result: Err(ClientError::NoResult),
+                        dropped_output_lines: state.dropped_output_lines(index),
+                    },
+                    state,
+                ));
+            }
+        };
+
+        let (index, result) = tokio::select! {
+            msg = result_subscription.next() => match msg {
+                Some(msg) => match item_index(&msg.subject) {
+                    Some(index) if state.pending.contains(&index) => {
+                        let result = compression::decompress(msg.headers.as_ref(), msg.payload)
+                            .map_err(SubscriberError::Decompress)
+                            .map_err(ClientError::Subscriber)
+                            .and_then(|data| state.requests[index].result_from_message(&data));
+                        (index, result)
+                    }
+                    _ => {
+                        warn!(subject = msg.subject, "received unexpected batch result");
+                        continue;
+                    }
+                },
+                None => {
+                    error!("batch result subscription unexpectedly closed");
+                    state.unsubscribe();
+                    continue;
+                }
+            },
+            reply = root_subscription.next() => match reply {
+                Some(msg) => match item_index(&msg.subject) {
+                    Some(index) if state.pending.contains(&index) => {
+                        error!(
+                            subject = msg.subject,
+                            "received an unexpected message on batch reply subject"
+                        );
+                        (index, Err(ClientError::PublishingFailed(msg)))
+                    }
+                    _ => continue,
+                },
+                None => {
+                    error!("batch reply subscription unexpectedly closed");
+                    state.unsubscribe();
+                    continue;
+                }
+            },
+        };
+
+        state.pending.remove(&index);
+        let dropped_output_lines = state.dropped_output_lines(index);
+        return Some((
+            BatchResult {
+                index,
+                result,
+                dropped_output_lines,
+            },
+            state,
+        ));
+    }
+}
+
+/// Returns the batch index from a `<root>.<index>` or `<root>.<index>.result` subject.
+fn item_index(subject: &str) -> Option<usize> {
+    let subject = subject.strip_suffix(".result").unwrap_or(subject);
+    subject.rsplit('.').next()?.parse().ok()
+}
+
+async fn forward_batch_output_task(
+    mut output_subscription: BoxStream<'static, RawMessage>,
+    mut forwarder: OutputForwarder,
+    batch_size: usize,
+) {
+    // Every execution ends its output with a final message, so stop once each one has
+    let mut finished = 0;
+    while finished < batch_size {
+        let msg = match output_subscription.next().await {
+            Some(msg) => msg,
+            None => break,
+        };
+        if msg.headers.as_ref().map_or(false, |headers| {
+            headers.keys().any(|key| key == FINAL_MESSAGE_HEADER_KEY)
+        }) {
+            finished += 1;
+            continue;
+        }
+        match serde_json::from_slice::<OutputStream>(&msg.payload) {
+            Ok(output) => forwarder.forward(output).await,
+            Err(err) => warn!(error = ?err, "batch output forwarder failed to parse message"),
+        }
+    }
+    forwarder.finish().await;
+}
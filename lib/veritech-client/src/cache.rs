@@ -0,0 +1,133 @@
+use std::{
+    collections::{HashMap, VecDeque},
+    fmt,
+    sync::{Mutex, PoisonError},
+};
+
+use crate::{ClientError, ClientResult, VeritechRequest};
+
+/// Identifies what an execution computes: the kind of function and its request, minus the
+/// execution id. Two requests with the same key run the same code on the same arguments.
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub struct CacheKey(String);
+
+impl CacheKey {
+    pub fn for_request<R: VeritechRequest>(request: &R) -> ClientResult<Self> {
+        let mut value = serde_json::to_value(request).map_err(ClientError::JSONSerialize)?;
+        if let Some(object) = value.as_object_mut() {
+            object.remove("executionId");
+        }
+        let bytes = serde_json::to_vec(&value).map_err(ClientError::JSONSerialize)?;
+
+        let mut hasher = blake3::Hasher::new();
+        hasher.update(R::KIND.as_bytes());
+        hasher.update(b"\0");
+        hasher.update(&bytes);
+        Ok(Self(hasher.finalize().to_hex().to_string()))
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+impl fmt::Display for CacheKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+/// A store for the results of pure functions, used by a [`Client`](crate::Client) to skip
+/// executions it has already seen.
+///
+/// Results are stored as the JSON form of a successful [`FunctionResult`](crate::FunctionResult).
+pub trait ResultCache: fmt::Debug + Send + Sync {
+    fn get(&self, key: &CacheKey) -> Option<serde_json::Value>;
+    fn insert(&self, key: CacheKey, result: serde_json::Value);
+}
+
+/// A [`ResultCache`] that keeps the most recently used results in memory.
+#[derive(Debug)]
+pub struct InMemoryResultCache {
+    capacity: usize,
+    entries: Mutex<LruEntries>,
+}
+
+#[derive(Debug, Default)]
+struct LruEntries {
+    values: HashMap<CacheKey, serde_json::Value>,
+    /// Keys from least to most recently used.
+    order: VecDeque<CacheKey>,
+}
+
+impl LruEntries {
+    fn touch(&mut self, key: &CacheKey) {
+        if let Some(position) = self.order.iter().position(|k| k == key) {
+            self.order.remove(position);
+        }
+        self.order.push_back(key.clone());
+    }
+}
+
+impl InMemoryResultCache {
+    pub const DEFAULT_CAPACITY: usize = 1024;
+
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity: capacity.max(1),
+            entries: Mutex::new(LruEntries::default()),
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .values
+            .len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+impl Default for InMemoryResultCache {
+    fn default() -> Self {
+        Self::new(Self::DEFAULT_CAPACITY)
+    }
+}
+
+impl ResultCache for InMemoryResultCache {
+    fn get(&self, key: &CacheKey) -> Option<serde_json::Value> {
+        let mut entries = self.entries.lock().unwrap_or_else(PoisonError::into_inner);
+        let value = entries.values.get(key).cloned()?;
+        entries.touch(key);
+        Some(value)
+    }
+
+    fn insert(&self, key: CacheKey, result: serde_json::Value) {
+        let mut entries = self.entries.lock().unwrap_or_else(PoisonError::into_inner);
+        entries.touch(&key);
+        entries.values.insert(key, result);
+        while entries.values.len() > self.capacity {
+            match entries.order.pop_front() {
+                Some(oldest) => {
+                    entries.values.remove(&oldest);
+                }
+                None => break,
+            }
+        }
+    }
+}
+
+/// Rewrites the execution id of a cached result to the one of the request being answered.
+pub(crate) fn with_execution_id(
+    mut result: serde_json::Value,
+    execution_id: &str,
+) -> serde_json::Value {
+    if let Some(success) = result.get_mut("Success").and_then(|s| s.as_object_mut()) {
+        success.insert("executionId".to_string(), execution_id.into());
+    }
+    result
+}
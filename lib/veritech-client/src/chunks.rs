@@ -0,0 +1,169 @@
+use std::{
+    collections::{BTreeMap, HashMap},
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use cyclone_core::FunctionProgress;
+use futures::{future, stream::BoxStream, StreamExt};
+use nats_subscriber::{RawMessage, MESSAGE_ID_HEADER_KEY};
+use telemetry::prelude::*;
+use tokio::sync::mpsc;
+use veritech_core::{RESULT_CHUNK_HEADER_KEY, RESULT_FINAL_CHUNK_HEADER_KEY};
+
+/// The step reported in the progress of a result that is being received in chunks.
+const RECEIVING_RESULT_STEP: &str = "receiving result";
+
+/// Where a result stream reports how much of a chunked result was received, so that callers can
+/// show progress on large results such as generated code.
+#[derive(Clone, Debug)]
+pub(crate) struct ChunkProgress {
+    pub(crate) execution_id: String,
+    pub(crate) progress_tx: mpsc::Sender<FunctionProgress>,
+}
+
+/// Reassembles the results a server split into chunks (see
+/// [`Client::with_chunked_results`](crate::Client::with_chunked_results)), yielding each result
+/// as a single message once its last chunk is in. Messages that aren't chunks pass through as
+/// they are.
+///
+/// The chunks of a result are told apart from those of others by their subject and message id,
+/// so a stream may carry the results of several executions, and copies of a chunk that was
+/// delivered more than once are ignored.
+pub(crate) fn reassemble_chunks(
+    messages: BoxStream<'static, RawMessage>,
+    progress: Option<ChunkProgress>,
+) -> BoxStream<'static, RawMessage> {
+    let mut partial_results: HashMap<(String, String), PartialResult> = HashMap::new();
+
+    messages
+        .filter_map(move |message| {
+            let chunk = match Chunk::of(&message) {
+                Some(chunk) => chunk,
+                None => return future::ready(Some(message)),
+            };
+
+            let key = (message.subject.clone(), chunk.message_id);
+            let partial_result = partial_results.entry(key.clone()).or_default();
+            partial_result.add(chunk.sequence, chunk.is_final, message.payload);
+            if let Some(progress) = &progress {
+                progress.report(partial_result.received_bytes);
+            }
+
+            let payload = match partial_result.assemble() {
+                Some(payload) => payload,
+                None => return future::ready(None),
+            };
+            partial_results.remove(&key);
+            trace!(
+                subject = message.subject,
+                bytes = payload.len(),
+                "reassembled chunked result"
+            );
+
+            future::ready(Some(RawMessage {
+                subject: message.subject,
+                payload,
+                reply_mailbox: message.reply_mailbox,
+                headers: message.headers.as_ref().map(|headers| {
+                    let headers: Vec<(String, String)> = headers
+                        .keys()
+                        .filter(|key| {
+                            *key != RESULT_CHUNK_HEADER_KEY && *key != RESULT_FINAL_CHUNK_HEADER_KEY
+                        })
+                        .flat_map(|key| {
+                            headers
+                                .get(key)
+                                .into_iter()
+                                .flatten()
+                                .map(move |value| (key.clone(), value.clone()))
+                        })
+                        .collect();
+                    headers.iter().collect()
+                }),
+            }))
+        })
+        .boxed()
+}
+
+/// What a message's headers say about it being one chunk of a result.
+struct Chunk {
+    message_id: String,
+    sequence: usize,
+    is_final: bool,
+}
+
+impl Chunk {
+    fn of(message: &RawMessage) -> Option<Self> {
+        let headers = message.headers.as_ref()?;
+        let sequence = headers
+            .get(RESULT_CHUNK_HEADER_KEY)?
+            .iter()
+            .next()?
+            .parse()
+            .ok()?;
+        let message_id = headers
+            .get(MESSAGE_ID_HEADER_KEY)
+            .and_then(|values| values.iter().next().cloned())
+            .unwrap_or_default();
+        let is_final = headers
+            .keys()
+            .any(|key| key == RESULT_FINAL_CHUNK_HEADER_KEY);
+
+        Some(Self {
+            message_id,
+            sequence,
+            is_final,
+        })
+    }
+}
+
+/// The chunks of a result received so far.
+#[derive(Default)]
+struct PartialResult {
+    chunks: BTreeMap<usize, Vec<u8>>,
+    chunk_count: Option<usize>,
+    received_bytes: usize,
+}
+
+impl PartialResult {
+    fn add(&mut self, sequence: usize, is_final: bool, payload: Vec<u8>) {
+        if is_final {
+            self.chunk_count = Some(sequence + 1);
+        }
+        if !self.chunks.contains_key(&sequence) {
+            self.received_bytes += payload.len();
+            self.chunks.insert(sequence, payload);
+        }
+    }
+
+    /// Returns the whole result once every chunk up to the final one is in.
+    fn assemble(&mut self) -> Option<Vec<u8>> {
+        if self.chunk_count? != self.chunks.len() {
+            return None;
+        }
+
+        let mut payload = Vec::with_capacity(self.received_bytes);
+        for chunk in std::mem::take(&mut self.chunks).into_values() {
+            payload.extend(chunk);
+        }
+        Some(payload)
+    }
+}
+
+impl ChunkProgress {
+    fn report(&self, received_bytes: usize) {
+        let progress = FunctionProgress {
+            execution_id: self.execution_id.clone(),
+            current: received_bytes as u64,
+            total: None,
+            step: Some(RECEIVING_RESULT_STEP.to_string()),
+            timestamp: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map(|elapsed| elapsed.as_secs())
+                .unwrap_or_default(),
+        };
+        if let Err(mpsc::error::TrySendError::Full(_)) = self.progress_tx.try_send(progress) {
+            trace!("progress receiver is full, dropping result progress report");
+        }
+    }
+}
@@ -0,0 +1,159 @@
+use std::{convert::Infallible, future, sync::Arc, time::Duration};
+
+use telemetry::prelude::*;
+use tokio::{
+    sync::{watch, Semaphore},
+    task::JoinHandle,
+    time::{self, MissedTickBehavior},
+};
+
+use crate::{ClientError, ClientResult, Transport};
+
+/// The state of a [`Client`](crate::Client)'s connection to NATS, as last seen by its health
+/// checks.
+#[remain::sorted]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum ConnectionState {
+    /// The last health check reached the NATS server.
+    Connected,
+    /// The last health check could not reach the NATS server. Requests are held until it can.
+    Disconnected,
+}
+
+/// How a [`Client`](crate::Client) watches its connection to NATS, and what it does with requests
+/// while the connection is down.
+///
+/// The NATS connection reconnects and restores its subscriptions on its own, so reply mailboxes
+/// keep working across a reconnect. A request published while the connection was down may be
+/// lost, though, so requests with an execution id that are still waiting for their result are
+/// published again once the connection is back. Servers recognize the copy by its execution id
+/// and don't run the function twice.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct ReconnectPolicy {
+    /// How often the connection is checked.
+    pub check_interval: Duration,
+    /// How long a check may wait for the NATS server before the connection is considered lost.
+    pub check_timeout: Duration,
+    /// How many requests may wait for the connection to come back. Further requests fail with
+    /// [`ClientError::RequestBufferFull`] until it does.
+    pub max_buffered_requests: usize,
+}
+
+impl Default for ReconnectPolicy {
+    fn default() -> Self {
+        Self {
+            check_interval: Duration::from_secs(5),
+            check_timeout: Duration::from_secs(2),
+            max_buffered_requests: 100,
+        }
+    }
+}
+
+/// Checks a client's connection to NATS in the background, tracking its [`ConnectionState`].
+#[derive(Clone, Debug)]
+pub(crate) struct ConnectionMonitor {
+    state_rx: watch::Receiver<ConnectionState>,
+    buffered_requests: Arc<Semaphore>,
+    max_buffered_requests: usize,
+    _checker: Arc<Checker>,
+}
+
+/// Stops the health checks once the last clone of a [`ConnectionMonitor`] is dropped.
+#[derive(Debug)]
+struct Checker(JoinHandle<()>);
+
+impl Drop for Checker {
+    fn drop(&mut self) {
+        self.0.abort();
+    }
+}
+
+impl ConnectionMonitor {
+    pub(crate) fn start(transport: Arc<dyn Transport>, policy: ReconnectPolicy) -> Self {
+        let (state_tx, state_rx) = watch::channel(ConnectionState::Connected);
+        let checker = tokio::spawn(check_connection(transport, policy, state_tx));
+
+        Self {
+            state_rx,
+            buffered_requests: Arc::new(Semaphore::new(policy.max_buffered_requests)),
+            max_buffered_requests: policy.max_buffered_requests,
+            _checker: Arc::new(Checker(checker)),
+        }
+    }
+
+    pub(crate) fn state(&self) -> watch::Receiver<ConnectionState> {
+        self.state_rx.clone()
+    }
+
+    /// Returns once the connection is up, holding the caller's request in the buffer while it is
+    /// down.
+    pub(crate) async fn until_connected(&self) -> ClientResult<()> {
+        let mut state_rx = self.state_rx.clone();
+        if *state_rx.borrow_and_update() == ConnectionState::Connected {
+            return Ok(());
+        }
+
+        let _permit = self
+            .buffered_requests
+            .try_acquire()
+            .map_err(|_| ClientError::RequestBufferFull(self.max_buffered_requests))?;
+        debug!("connection to nats is down, holding request until it is back");
+        while *state_rx.borrow_and_update() != ConnectionState::Connected {
+            // The checks have stopped, so there is nothing left to wait for
+            if state_rx.changed().await.is_err() {
+                break;
+            }
+        }
+        Ok(())
+    }
+
+    /// Calls `on_reconnect` every time the connection comes back after being lost. Never returns.
+    pub(crate) async fn on_reconnect<F, Fut>(&self, mut on_reconnect: F) -> Infallible
+    where
+        F: FnMut() -> Fut,
+        Fut: future::Future<Output = ()>,
+    {
+        let mut state_rx = self.state_rx.clone();
+        state_rx.borrow_and_update();
+        loop {
+            if state_rx.changed().await.is_err() {
+                return future::pending().await;
+            }
+            if *state_rx.borrow_and_update() == ConnectionState::Connected {
+                on_reconnect().await;
+            }
+        }
+    }
+}
+
+/// Checks the transport's health every check interval, publishing a new [`ConnectionState`]
+/// whenever the outcome changes.
+async fn check_connection(
+    transport: Arc<dyn Transport>,
+    policy: ReconnectPolicy,
+    state_tx: watch::Sender<ConnectionState>,
+) {
+    let mut interval = time::interval(policy.check_interval);
+    interval.set_missed_tick_behavior(MissedTickBehavior::Delay);
+    loop {
+        interval.tick().await;
+        let state = match transport.check_health(policy.check_timeout).await {
+            Ok(()) => ConnectionState::Connected,
+            Err(err) => {
+                debug!(error = ?err, "nats connection health check failed");
+                ConnectionState::Disconnected
+            }
+        };
+        state_tx.send_if_modified(|current| {
+            if *current == state {
+                return false;
+            }
+            match state {
+                ConnectionState::Connected => info!("connection to nats is back"),
+                ConnectionState::Disconnected => warn!("lost connection to nats"),
+            }
+            *current = state;
+            true
+        });
+    }
+}
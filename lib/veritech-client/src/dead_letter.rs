@@ -0,0 +1,141 @@
+use std::sync::{Arc, Mutex, PoisonError};
+
+use futures::StreamExt;
+use nats_subscriber::RawMessage;
+use si_data_nats::HeaderMap;
+use telemetry::prelude::*;
+use tokio::task::JoinHandle;
+use veritech_core::{
+    nats_dead_letter_subject, DEAD_LETTER_ERROR_HEADER_KEY, DEAD_LETTER_REPLY_MAILBOX_HEADER_KEY,
+    DEAD_LETTER_SUBJECT_HEADER_KEY,
+};
+
+use crate::{ClientResult, Transport};
+
+/// A request that a veritech server could not execute, as it republished it on a dead letter
+/// subject.
+#[derive(Clone, Debug)]
+pub struct DeadLetter {
+    /// Identifies the dead letter within its [`DeadLetterQueue`].
+    pub id: u64,
+    /// The kind of request, such as `resolverfunction`.
+    pub kind: String,
+    /// Why the request could not be executed.
+    pub error: String,
+    /// The subject the request was originally published on.
+    pub subject: String,
+    /// The reply mailbox of the request, if it had one.
+    pub reply_mailbox: Option<String>,
+    /// The original headers of the request.
+    pub headers: Option<HeaderMap>,
+    /// The original payload of the request, still encrypted or compressed if it was.
+    pub payload: Vec<u8>,
+}
+
+impl DeadLetter {
+    fn from_message(id: u64, message: RawMessage) -> Self {
+        let kind = message
+            .subject
+            .rsplit('.')
+            .next()
+            .unwrap_or_default()
+            .to_string();
+        let mut error = String::new();
+        let mut subject = String::new();
+        let mut reply_mailbox = None;
+        let mut headers = Vec::new();
+        if let Some(message_headers) = &message.headers {
+            for key in message_headers.keys() {
+                for value in message_headers.get(key).into_iter().flatten() {
+                    match key.as_str() {
+                        DEAD_LETTER_ERROR_HEADER_KEY => error = value.clone(),
+                        DEAD_LETTER_REPLY_MAILBOX_HEADER_KEY => reply_mailbox = Some(value.clone()),
+                        DEAD_LETTER_SUBJECT_HEADER_KEY => subject = value.clone(),
+                        _ => headers.push((key.clone(), value.clone())),
+                    }
+                }
+            }
+        }
+
+        Self {
+            id,
+            kind,
+            error,
+            subject,
+            reply_mailbox,
+            headers: (!headers.is_empty()).then(|| headers.iter().collect()),
+            payload: message.payload,
+        }
+    }
+}
+
+/// Collects the dead letters veritech servers publish, from the moment the queue is created, so
+/// that they can be listed and replayed.
+#[derive(Debug)]
+pub struct DeadLetterQueue {
+    transport: Arc<dyn Transport>,
+    dead_letters: Arc<Mutex<Vec<DeadLetter>>>,
+    collector: JoinHandle<()>,
+}
+
+impl DeadLetterQueue {
+    pub(crate) async fn subscribe(transport: Arc<dyn Transport>) -> ClientResult<Self> {
+        let subject = nats_dead_letter_subject(transport.subject_prefix(), ">");
+        trace!(
+            messaging.destination = subject.as_str(),
+            "subscribing for dead letters"
+        );
+        let mut subscription = transport.subscribe(&subject).await?;
+
+        let dead_letters: Arc<Mutex<Vec<DeadLetter>>> = Default::default();
+        let collected = dead_letters.clone();
+        let collector = tokio::spawn(async move {
+            let mut next_id = 0;
+            while let Some(message) = subscription.next().await {
+                collected
+                    .lock()
+                    .unwrap_or_else(PoisonError::into_inner)
+                    .push(DeadLetter::from_message(next_id, message));
+                next_id += 1;
+            }
+        });
+
+        Ok(Self {
+            transport,
+            dead_letters,
+            collector,
+        })
+    }
+
+    /// Returns the dead letters collected so far that haven't been replayed, oldest first.
+    pub fn list(&self) -> Vec<DeadLetter> {
+        self.dead_letters
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .clone()
+    }
+
+    /// Publishes a dead letter's request again, on its original subject and with its original
+    /// reply mailbox and headers, and removes it from the queue.
+    pub async fn replay(&self, dead_letter: &DeadLetter) -> ClientResult<()> {
+        self.transport
+            .publish_with_reply(
+                &dead_letter.subject,
+                dead_letter.reply_mailbox.clone(),
+                dead_letter.headers.as_ref(),
+                dead_letter.payload.clone(),
+            )
+            .await?;
+        self.dead_letters
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .retain(|queued| queued.id != dead_letter.id);
+        Ok(())
+    }
+}
+
+impl Drop for DeadLetterQueue {
+    fn drop(&mut self) {
+        self.collector.abort();
+    }
+}
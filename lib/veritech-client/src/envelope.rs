@@ -0,0 +1,93 @@
+use cyclone_core::EncryptionKey;
+use nats_subscriber::compression::{self, CONTENT_ENCODING_HEADER_KEY, GZIP_ENCODING};
+use si_data_nats::HeaderMap;
+use veritech_core::{
+    nats_sharded_subject, shard_for_workspace, ACCEPT_CHUNKED_RESULTS_HEADER_KEY,
+    ACCEPT_COMPRESSION_HEADER_KEY, ENCRYPTED_PAYLOAD_HEADER_KEY, SANDBOX_PROFILE_HEADER_KEY,
+    USER_ID_HEADER_KEY, WORKSPACE_ID_HEADER_KEY,
+};
+
+use crate::{ClientError, ClientResult};
+
+/// How a [`Client`](crate::Client) wraps requests for the trip over NATS: the headers sent with
+/// them and how their payloads are encoded.
+#[derive(Clone, Debug, Default)]
+pub(crate) struct RequestEnvelope {
+    pub(crate) workspace_id: Option<String>,
+    pub(crate) user_id: Option<String>,
+    pub(crate) encryption_key: Option<EncryptionKey>,
+    pub(crate) compression_threshold: Option<usize>,
+    pub(crate) result_chunk_size: Option<usize>,
+    pub(crate) shard_count: Option<u32>,
+    pub(crate) sandbox_profile: Option<String>,
+}
+
+/// A serialized request, encoded and ready to publish.
+#[derive(Debug)]
+pub(crate) struct SealedRequest {
+    pub(crate) payload: Vec<u8>,
+    pub(crate) headers: Option<HeaderMap>,
+}
+
+impl RequestEnvelope {
+    /// Returns the subject to publish a request on, moved onto its workspace's shard if the
+    /// client shards requests. Requests without a workspace id go to the first shard.
+    pub(crate) fn route(&self, subject: String) -> String {
+        match self.shard_count {
+            Some(shard_count) => {
+                let shard = self.workspace_id.as_deref().map_or(0, |workspace_id| {
+                    shard_for_workspace(workspace_id, shard_count)
+                });
+                nats_sharded_subject(&subject, shard)
+            }
+            None => subject,
+        }
+    }
+
+    /// Encodes a serialized request for publishing. Requests larger than the compression
+    /// threshold are compressed, then requests are sealed with cyclone's public key if the client
+    /// encrypts payloads.
+    pub(crate) fn seal(&self, payload: Vec<u8>) -> ClientResult<SealedRequest> {
+        let mut headers: Vec<(&str, String)> = Vec::new();
+        if let Some(workspace_id) = &self.workspace_id {
+            headers.push((WORKSPACE_ID_HEADER_KEY, workspace_id.clone()));
+        }
+        if let Some(user_id) = &self.user_id {
+            headers.push((USER_ID_HEADER_KEY, user_id.clone()));
+        }
+        if let Some(sandbox_profile) = &self.sandbox_profile {
+            headers.push((SANDBOX_PROFILE_HEADER_KEY, sandbox_profile.clone()));
+        }
+
+        if let Some(result_chunk_size) = self.result_chunk_size {
+            headers.push((
+                ACCEPT_CHUNKED_RESULTS_HEADER_KEY,
+                result_chunk_size.to_string(),
+            ));
+        }
+
+        let mut payload = payload;
+        if let Some(compression_threshold) = self.compression_threshold {
+            headers.push((
+                ACCEPT_COMPRESSION_HEADER_KEY,
+                compression_threshold.to_string(),
+            ));
+            if payload.len() > compression_threshold {
+                payload = compression::compress(&payload).map_err(ClientError::Compress)?;
+                headers.push((CONTENT_ENCODING_HEADER_KEY, GZIP_ENCODING.to_string()));
+            }
+        }
+        // Compress before encrypting, as sealed payloads don't compress
+        if let Some(encryption_key) = &self.encryption_key {
+            payload = encryption_key.encrypt(payload);
+            headers.push((ENCRYPTED_PAYLOAD_HEADER_KEY, "true".to_string()));
+        }
+
+        let headers = if headers.is_empty() {
+            None
+        } else {
+            Some(headers.iter().collect())
+        };
+        Ok(SealedRequest { payload, headers })
+    }
+}
@@ -1,115 +1,514 @@
-use futures::{StreamExt, TryStreamExt};
-use nats_subscriber::{SubscriberError, Subscription};
+use std::{
+    convert::Infallible,
+    future::Future,
+    path::{Path, PathBuf},
+    sync::Arc,
+    time::{Duration, Instant, SystemTime, UNIX_EPOCH},
+};
+
+use futures::{future, stream::BoxStream, StreamExt, TryStreamExt};
+use nats_subscriber::{RawMessage, SubscriberError, Subscription};
 use serde::{de::DeserializeOwned, Serialize};
 use telemetry::prelude::*;
 use thiserror::Error;
-use tokio::sync::mpsc;
+use tokio::{
+    sync::{mpsc, watch},
+    time,
+};
 
 use veritech_core::{
-    nats_action_run_subject, nats_reconciliation_subject, nats_resolver_function_subject,
-    nats_schema_variant_definition_subject, nats_subject, nats_validation_subject,
-    reply_mailbox_for_output, reply_mailbox_for_result, FINAL_MESSAGE_HEADER_KEY,
+    nats_control_stats_subject, nats_subject, reply_mailbox_for_cancel,
+    reply_mailbox_for_heartbeat, reply_mailbox_for_output, reply_mailbox_for_progress,
+    reply_mailbox_for_result, FINAL_MESSAGE_HEADER_KEY, HEARTBEAT_INTERVAL,
 };
 
 pub use cyclone_core::{
-    ActionRunRequest, ActionRunResultSuccess, ComponentKind, ComponentView, EncryptionKey,
-    EncryptionKeyError, FunctionResult, FunctionResultFailure, OutputStream, ReconciliationRequest,
+    schemas, validate_payload, ActionRunRequest, ActionRunResultSuccess, BeforeFunction,
+    ComponentKind, ComponentView, EncryptionKey, EncryptionKeyError, FunctionErrorKind,
+    FunctionLanguage, FunctionProgress, FunctionResult, FunctionResultFailure,
+    FunctionResultFailureError, OutputStream, PayloadSchemaError, ReconciliationRequest,
     ReconciliationResultSuccess, ResolverFunctionComponent, ResolverFunctionRequest,
     ResolverFunctionResponseType, ResolverFunctionResultSuccess, ResourceStatus,
     SchemaVariantDefinitionRequest, SchemaVariantDefinitionResultSuccess, SensitiveContainer,
-    ValidationRequest, ValidationResultSuccess,
+    ValidationRequest, ValidationResultSuccess, PAYLOAD_SCHEMA_VERSION,
 };
+pub use veritech_core::{KindStats, PoolStats, ServerStats};
+
 use si_data_nats::NatsClient;
 
+mod batch;
+mod cache;
+mod chunks;
+mod connection;
+mod dead_letter;
+mod envelope;
+mod output;
+mod recording;
+mod request;
+mod retry;
+mod simulation;
+mod transport;
+
+pub use batch::{BatchRequest, BatchResult, VeritechResult};
+pub use cache::{CacheKey, InMemoryResultCache, ResultCache};
+use chunks::ChunkProgress;
+use connection::ConnectionMonitor;
+pub use connection::{ConnectionState, ReconnectPolicy};
+pub use dead_letter::{DeadLetter, DeadLetterQueue};
+use envelope::{RequestEnvelope, SealedRequest};
+use output::{DroppedOutput, OutputForwarder};
+pub use output::{InMemoryOutputStore, OutputBackpressure, OutputStore};
+pub use recording::{RecordReplay, RecordedExecution, RecordingError, RecordingResult};
+pub use request::VeritechRequest;
+pub use retry::{RetryOn, RetryPolicy};
+pub use simulation::{SimulatedResults, SimulationError, SimulationResult};
+pub use transport::{LoopbackTransport, NatsTransport, Transport};
+
 #[remain::sorted]
 #[derive(Error, Debug)]
 pub enum ClientError {
+    #[error("failed to compress request")]
+    Compress(#[source] std::io::Error),
+    #[error("failed to deserialize json message")]
+    JSONDeserialize(#[source] serde_json::Error),
     #[error("failed to serialize json message")]
     JSONSerialize(#[source] serde_json::Error),
     #[error("nats error")]
     Nats(#[from] si_data_nats::NatsError),
+    #[error("no recorded execution of {0} request: {1}")]
+    NoRecordedExecution(&'static str, CacheKey),
     #[error("no function result from cyclone; bug!")]
     NoResult,
+    #[error("no veritech server answered the stats request")]
+    NoServerStats,
+    #[error("no simulated result for handler: {0}")]
+    NoSimulatedResult(String),
     #[error("unable to publish message: {0:?}")]
-    PublishingFailed(si_data_nats::Message),
+    PublishingFailed(RawMessage),
+    #[error(transparent)]
+    Recording(#[from] RecordingError),
+    #[error("too many requests are waiting for the nats connection to come back (limit {0})")]
+    RequestBufferFull(usize),
     #[error("root connection closed")]
     RootConnectionClosed,
+    #[error("no heartbeat from the server for {0:?}; it was likely lost")]
+    ServerLost(Duration),
     #[error(transparent)]
     Subscriber(#[from] SubscriberError),
+    #[error("execution timed out after {0:?}")]
+    Timeout(Duration),
 }
 
 pub type ClientResult<T> = Result<T, ClientError>;
 
+/// How long an execution may go without a heartbeat before its server is considered lost, by
+/// default. Servers publish heartbeats every [`HEARTBEAT_INTERVAL`].
+pub const DEFAULT_HEARTBEAT_TIMEOUT: Duration =
+    Duration::from_secs(HEARTBEAT_INTERVAL.as_secs() * 6);
+
+/// How long [`Client::server_stats`] waits for a server to answer.
+const SERVER_STATS_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// How many progress reports are held for a caller of [`Client::execute_with_progress`] that
+/// hasn't received them yet. Further reports are dropped until the caller catches up.
+const PROGRESS_CHANNEL_CAPACITY: usize = 32;
+
+/// How long an execution that has a result waits for its remaining output to be forwarded, so
+/// that [`ExecutionHandle::dropped_output_lines`] accounts for all of it.
+const OUTPUT_DRAIN_TIMEOUT: Duration = Duration::from_secs(1);
+
 #[derive(Clone, Debug)]
 pub struct Client {
-    nats: NatsClient,
+    transport: Arc<dyn Transport>,
+    simulation: Option<Arc<SimulatedResults>>,
+    record_replay: Option<RecordReplay>,
+    cache: Option<Arc<dyn ResultCache>>,
+    output_store: Option<Arc<dyn OutputStore>>,
+    output_backpressure: OutputBackpressure,
+    retry_policy: Option<RetryPolicy>,
+    timeout: Option<Duration>,
+    heartbeat_timeout: Option<Duration>,
+    envelope: RequestEnvelope,
+    connection: Option<ConnectionMonitor>,
 }
 
 impl Client {
     pub fn new(nats: NatsClient) -> Self {
-        Self { nats }
+        Self::new_with_transport(Arc::new(NatsTransport::new(nats)))
+    }
+
+    /// Creates a client that sends requests over the given [`Transport`] rather than NATS, such as
+    /// a [`LoopbackTransport`] in tests that have no NATS server.
+    pub fn new_with_transport(transport: Arc<dyn Transport>) -> Self {
+        Self {
+            transport,
+            simulation: None,
+            record_replay: None,
+            cache: None,
+            output_store: None,
+            output_backpressure: OutputBackpressure::default(),
+            retry_policy: None,
+            timeout: None,
+            heartbeat_timeout: Some(DEFAULT_HEARTBEAT_TIMEOUT),
+            envelope: RequestEnvelope::default(),
+            connection: None,
+        }
+    }
+
+    /// Creates a client that answers every execution with its result recorded in the fixture
+    /// directory, with no connection to NATS. See [`RecordReplay`].
+    pub fn new_replaying(fixture_dir: impl Into<PathBuf>) -> Self {
+        Self::new_with_transport(Arc::new(LoopbackTransport::new()))
+            .with_record_replay(RecordReplay::Replay(fixture_dir.into()))
+    }
+
+    /// Returns cached results for functions that are pure and have already run with the same
+    /// arguments, instead of sending the request to veritech again. Impure kinds of functions,
+    /// such as actions and reconciliations, always run (see [`VeritechRequest::CACHEABLE`]), as do
+    /// requests with before functions (see [`VeritechRequest::before`]).
+    ///
+    /// [`InMemoryResultCache`] is a good default. The same cache can be shared between clients.
+    pub fn with_result_cache(mut self, cache: Arc<dyn ResultCache>) -> Self {
+        self.cache = Some(cache);
+        self
+    }
+
+    /// Keeps the output of every execution in the given [`OutputStore`], in addition to sending
+    /// it to the caller, so that it can be fetched later with [`Client::stored_output`].
+    pub fn with_output_store(mut self, output_store: Arc<dyn OutputStore>) -> Self {
+        self.output_store = Some(output_store);
+        self
+    }
+
+    /// Sets what happens to output when the channel passed to an execution is full. By default
+    /// the lines that don't fit are dropped and counted, see
+    /// [`ExecutionHandle::dropped_output_lines`] and [`BatchResult::dropped_output_lines`].
+    pub fn with_output_backpressure(mut self, output_backpressure: OutputBackpressure) -> Self {
+        self.output_backpressure = output_backpressure;
+        self
+    }
+
+    /// Returns the stored output of a past execution, if the client has an [`OutputStore`] and
+    /// the execution produced any output.
+    pub fn stored_output(&self, execution_id: &str) -> Option<Vec<OutputStream>> {
+        self.output_store
+            .as_ref()
+            .and_then(|output_store| output_store.get(execution_id))
+    }
+
+    /// Resends requests whose execution fails for a transient reason, according to the given
+    /// [`RetryPolicy`]. Without a policy, every failure is returned to the caller.
+    pub fn with_retry_policy(mut self, retry_policy: RetryPolicy) -> Self {
+        self.retry_policy = Some(retry_policy);
+        self
+    }
+
+    /// Sets how long every execution may take before it is cancelled and
+    /// [`ClientError::Timeout`] is returned. Without a timeout, executions wait for a result
+    /// indefinitely. A single execution can override this with
+    /// [`ExecutionHandle::with_timeout`].
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    /// Sets how long an execution may go without a heartbeat from the server before
+    /// [`ClientError::ServerLost`] is returned. `None` waits on silent servers indefinitely.
+    pub fn with_heartbeat_timeout(mut self, heartbeat_timeout: Option<Duration>) -> Self {
+        self.heartbeat_timeout = heartbeat_timeout;
+        self
+    }
+
+    /// Sends every request on behalf of the given workspace, so that veritech servers can limit
+    /// how many executions of a single workspace run at once.
+    pub fn with_workspace_id(mut self, workspace_id: impl Into<String>) -> Self {
+        self.envelope.workspace_id = Some(workspace_id.into());
+        self
+    }
+
+    /// Sends every request on behalf of the given user, who veritech servers record in the audit
+    /// records of their executions.
+    pub fn with_user_id(mut self, user_id: impl Into<String>) -> Self {
+        self.envelope.user_id = Some(user_id.into());
+        self
+    }
+
+    /// Runs the functions of every request under the named cyclone sandbox profile, such as one
+    /// that allows the programs a qualification shells out to. Functions run under cyclone's
+    /// default profile otherwise.
+    pub fn with_sandbox_profile(mut self, sandbox_profile: impl Into<String>) -> Self {
+        self.envelope.sandbox_profile = Some(sandbox_profile.into());
+        self
+    }
+
+    /// Splits requests across `shard_count` shards of the veritech subjects by workspace (see
+    /// [`Client::with_workspace_id`]), so that separate fleets of veritech servers can each serve
+    /// some of the shards. Requests sent on an explicit subject are not sharded.
+    pub fn with_sharding(mut self, shard_count: u32) -> Self {
+        self.envelope.shard_count = Some(shard_count);
+        self
+    }
+
+    /// Checks the connection to NATS in the background, holding requests while it is down and
+    /// resending the ones still waiting for a result once it is back. See [`ReconnectPolicy`].
+    ///
+    /// Must be called from within a Tokio runtime, as it spawns the checks.
+    pub fn with_reconnect(mut self, policy: ReconnectPolicy) -> Self {
+        self.connection = Some(ConnectionMonitor::start(self.transport.clone(), policy));
+        self
+    }
+
+    /// Watches the state of the connection to NATS, if the client checks it (see
+    /// [`Client::with_reconnect`]).
+    pub fn connection_state(&self) -> Option<watch::Receiver<ConnectionState>> {
+        self.connection.as_ref().map(ConnectionMonitor::state)
+    }
+
+    /// Seals every request with cyclone's public key before publishing it, so that function
+    /// arguments and secrets can't be read by anything relaying the message. Only veritech
+    /// servers holding cyclone's decryption key can open them.
+    pub fn with_payload_encryption(mut self, encryption_key: EncryptionKey) -> Self {
+        self.envelope.encryption_key = Some(encryption_key);
+        self
+    }
+
+    /// Compresses requests larger than `threshold` bytes, and asks servers to do the same with
+    /// results. Smaller payloads are sent as they are, as compressing them isn't worth the cost.
+    pub fn with_compression(mut self, threshold: usize) -> Self {
+        self.envelope.compression_threshold = Some(threshold);
+        self
+    }
+
+    /// Asks servers to split results larger than `chunk_size` bytes into chunks of at most that
+    /// size, such as the large documents produced by code generation functions. The client
+    /// reassembles the chunks, reporting how much of the result was received on the progress
+    /// channel of executions that have one.
+    pub fn with_chunked_results(mut self, chunk_size: usize) -> Self {
+        self.envelope.result_chunk_size = Some(chunk_size);
+        self
+    }
+
+    /// Puts the client into simulation mode, where every execution returns a result from the
+    /// given [`SimulatedResults`] and no request is sent to veritech.
+    pub fn with_simulation(mut self, simulation: SimulatedResults) -> Self {
+        self.simulation = Some(Arc::new(simulation));
+        self
+    }
+
+    /// Returns `true` if the client is in simulation mode.
+    pub fn is_simulated(&self) -> bool {
+        self.simulation.is_some()
+    }
+
+    /// Records the request and result of every execution to a fixture directory, or answers every
+    /// execution with the result recorded there. See [`RecordReplay`].
+    pub fn with_record_replay(mut self, record_replay: RecordReplay) -> Self {
+        self.record_replay = Some(record_replay);
+        self
+    }
+
+    /// Returns a new [`ExecutionHandle`] to pass to one of the `execute_*_with_handle` methods.
+    pub fn new_execution_handle(&self) -> ExecutionHandle {
+        ExecutionHandle {
+            transport: self.transport.clone(),
+            reply_mailbox_root: self.transport.new_inbox(),
+            timeout: None,
+            dropped_output: DroppedOutput::default(),
+        }
+    }
+
+    /// Starts collecting the requests that veritech servers could not execute, which they
+    /// republish on dead letter subjects. Only dead letters published from now on are collected.
+    pub async fn dead_letter_queue(&self) -> ClientResult<DeadLetterQueue> {
+        DeadLetterQueue::subscribe(self.transport.clone()).await
+    }
+
+    /// Asks a veritech server how busy it is, such as to warn that the function backend is
+    /// saturated. When several servers share the subject prefix, the first one to answer is
+    /// returned.
+    #[instrument(name = "client.server_stats", skip_all)]
+    pub async fn server_stats(&self) -> ClientResult<ServerStats> {
+        let reply_mailbox = self.transport.new_inbox();
+        let mut replies = self.transport.subscribe(&reply_mailbox).await?;
+        self.transport
+            .publish_with_reply(
+                &nats_control_stats_subject(self.nats_subject_prefix()),
+                Some(reply_mailbox),
+                None,
+                vec![],
+            )
+            .await?;
+
+        let reply = time::timeout(SERVER_STATS_TIMEOUT, replies.next())
+            .await
+            .map_err(|_| ClientError::Timeout(SERVER_STATS_TIMEOUT))?
+            .ok_or(ClientError::NoServerStats)?;
+        // NATS answers with an empty message when no server is subscribed
+        if reply.payload.is_empty() {
+            return Err(ClientError::NoServerStats);
+        }
+
+        serde_json::from_slice(&reply.payload).map_err(ClientError::JSONDeserialize)
     }
 
     fn nats_subject_prefix(&self) -> Option<&str> {
-        self.nats.metadata().subject_prefix()
+        self.transport.subject_prefix()
     }
 
-    #[instrument(name = "client.execute_resolver_function", skip_all)]
-    pub async fn execute_resolver_function(
+    /// Runs a function of any kind, publishing the request on the kind's default subject.
+    #[instrument(name = "client.execute", skip_all, fields(veritech.kind = R::KIND))]
+    pub async fn execute<R: VeritechRequest>(
         &self,
         output_tx: mpsc::Sender<OutputStream>,
-        request: &ResolverFunctionRequest,
-    ) -> ClientResult<FunctionResult<ResolverFunctionResultSuccess>> {
+        request: &R,
+    ) -> ClientResult<FunctionResult<R::Success>> {
         self.execute_request(
-            nats_resolver_function_subject(self.nats_subject_prefix()),
+            self.envelope
+                .route(nats_subject(self.nats_subject_prefix(), R::SUBJECT_SUFFIX)),
+            &self.new_execution_handle(),
             output_tx,
+            None,
             request,
         )
         .await
     }
 
-    #[instrument(name = "client.execute_resolver_function_with_subject", skip_all)]
-    pub async fn execute_resolver_function_with_subject(
+    /// Like [`Client::execute`], but publishes the request on the given subject.
+    #[instrument(
+        name = "client.execute_with_subject",
+        skip_all,
+        fields(veritech.kind = R::KIND)
+    )]
+    pub async fn execute_with_subject<R: VeritechRequest>(
         &self,
         output_tx: mpsc::Sender<OutputStream>,
-        request: &ResolverFunctionRequest,
+        request: &R,
         subject_suffix: impl AsRef<str>,
-    ) -> ClientResult<FunctionResult<ResolverFunctionResultSuccess>> {
+    ) -> ClientResult<FunctionResult<R::Success>> {
         self.execute_request(
             nats_subject(self.nats_subject_prefix(), subject_suffix),
+            &self.new_execution_handle(),
             output_tx,
+            None,
             request,
         )
         .await
     }
 
-    #[instrument(name = "client.execute_validation", skip_all)]
-    pub async fn execute_validation(
+    /// Like [`Client::execute`], but the execution can be cancelled through the given
+    /// [`ExecutionHandle`] while it is running.
+    #[instrument(
+        name = "client.execute_with_handle",
+        skip_all,
+        fields(veritech.kind = R::KIND)
+    )]
+    pub async fn execute_with_handle<R: VeritechRequest>(
         &self,
+        handle: &ExecutionHandle,
         output_tx: mpsc::Sender<OutputStream>,
-        request: &ValidationRequest,
-    ) -> ClientResult<FunctionResult<ValidationResultSuccess>> {
+        request: &R,
+    ) -> ClientResult<FunctionResult<R::Success>> {
         self.execute_request(
-            nats_validation_subject(self.nats_subject_prefix()),
+            self.envelope
+                .route(nats_subject(self.nats_subject_prefix(), R::SUBJECT_SUFFIX)),
+            handle,
             output_tx,
+            None,
             request,
         )
         .await
     }
 
+    /// Like [`Client::execute`], but also returns a receiver for the progress the function reports
+    /// as it runs. The receiver closes once the execution has finished.
+    ///
+    /// Each report supersedes the last, so reports that arrive while the receiver is full are
+    /// dropped rather than holding up the execution.
+    pub fn execute_with_progress<'a, R: VeritechRequest>(
+        &'a self,
+        output_tx: mpsc::Sender<OutputStream>,
+        request: &'a R,
+    ) -> (
+        mpsc::Receiver<FunctionProgress>,
+        impl Future<Output = ClientResult<FunctionResult<R::Success>>> + 'a,
+    ) {
+        let (progress_tx, progress_rx) = mpsc::channel(PROGRESS_CHANNEL_CAPACITY);
+        let result = async move {
+            self.execute_request(
+                self.envelope
+                    .route(nats_subject(self.nats_subject_prefix(), R::SUBJECT_SUFFIX)),
+                &self.new_execution_handle(),
+                output_tx,
+                Some(progress_tx),
+                request,
+            )
+            .await
+        };
+        (progress_rx, result)
+    }
+
+    #[instrument(name = "client.execute_resolver_function", skip_all)]
+    pub async fn execute_resolver_function(
+        &self,
+        output_tx: mpsc::Sender<OutputStream>,
+        request: &ResolverFunctionRequest,
+    ) -> ClientResult<FunctionResult<ResolverFunctionResultSuccess>> {
+        self.execute(output_tx, request).await
+    }
+
+    #[instrument(name = "client.execute_resolver_function_with_subject", skip_all)]
+    pub async fn execute_resolver_function_with_subject(
+        &self,
+        output_tx: mpsc::Sender<OutputStream>,
+        request: &ResolverFunctionRequest,
+        subject_suffix: impl AsRef<str>,
+    ) -> ClientResult<FunctionResult<ResolverFunctionResultSuccess>> {
+        self.execute_with_subject(output_tx, request, subject_suffix)
+            .await
+    }
+
+    /// Like [`Client::execute_resolver_function`], but the execution can be cancelled through the given
+    /// [`ExecutionHandle`] while it is running.
+    #[instrument(name = "client.execute_resolver_function_with_handle", skip_all)]
+    pub async fn execute_resolver_function_with_handle(
+        &self,
+        handle: &ExecutionHandle,
+        output_tx: mpsc::Sender<OutputStream>,
+        request: &ResolverFunctionRequest,
+    ) -> ClientResult<FunctionResult<ResolverFunctionResultSuccess>> {
+        self.execute_with_handle(handle, output_tx, request).await
+    }
+
+    #[instrument(name = "client.execute_validation", skip_all)]
+    pub async fn execute_validation(
+        &self,
+        output_tx: mpsc::Sender<OutputStream>,
+        request: &ValidationRequest,
+    ) -> ClientResult<FunctionResult<ValidationResultSuccess>> {
+        self.execute(output_tx, request).await
+    }
+
     #[instrument(name = "client.execute_validation_with_subject", skip_all)]
     pub async fn execute_validation_with_subject(
         &self,
         output_tx: mpsc::Sender<OutputStream>,
-        request: &ValidationResultSuccess,
+        request: &ValidationRequest,
         subject_suffix: impl AsRef<str>,
     ) -> ClientResult<FunctionResult<ValidationResultSuccess>> {
-        self.execute_request(
-            nats_subject(self.nats_subject_prefix(), subject_suffix),
-            output_tx,
-            request,
-        )
-        .await
+        self.execute_with_subject(output_tx, request, subject_suffix)
+            .await
+    }
+
+    /// Like [`Client::execute_validation`], but the execution can be cancelled through the given
+    /// [`ExecutionHandle`] while it is running.
+    #[instrument(name = "client.execute_validation_with_handle", skip_all)]
+    pub async fn execute_validation_with_handle(
+        &self,
+        handle: &ExecutionHandle,
+        output_tx: mpsc::Sender<OutputStream>,
+        request: &ValidationRequest,
+    ) -> ClientResult<FunctionResult<ValidationResultSuccess>> {
+        self.execute_with_handle(handle, output_tx, request).await
     }
 
     #[instrument(name = "client.execute_action_run", skip_all)]
@@ -118,12 +517,7 @@ impl Client {
         output_tx: mpsc::Sender<OutputStream>,
         request: &ActionRunRequest,
     ) -> ClientResult<FunctionResult<ActionRunResultSuccess>> {
-        self.execute_request(
-            nats_action_run_subject(self.nats_subject_prefix()),
-            output_tx,
-            request,
-        )
-        .await
+        self.execute(output_tx, request).await
     }
 
     #[instrument(name = "client.execute_action_run_with_subject", skip_all)]
@@ -133,12 +527,20 @@ impl Client {
         request: &ActionRunRequest,
         subject_suffix: impl AsRef<str>,
     ) -> ClientResult<FunctionResult<ActionRunResultSuccess>> {
-        self.execute_request(
-            nats_subject(self.nats_subject_prefix(), subject_suffix),
-            output_tx,
-            request,
-        )
-        .await
+        self.execute_with_subject(output_tx, request, subject_suffix)
+            .await
+    }
+
+    /// Like [`Client::execute_action_run`], but the execution can be cancelled through the given
+    /// [`ExecutionHandle`] while it is running.
+    #[instrument(name = "client.execute_action_run_with_handle", skip_all)]
+    pub async fn execute_action_run_with_handle(
+        &self,
+        handle: &ExecutionHandle,
+        output_tx: mpsc::Sender<OutputStream>,
+        request: &ActionRunRequest,
+    ) -> ClientResult<FunctionResult<ActionRunResultSuccess>> {
+        self.execute_with_handle(handle, output_tx, request).await
     }
 
     #[instrument(name = "client.execute_reconciliation", skip_all)]
@@ -147,12 +549,7 @@ impl Client {
         output_tx: mpsc::Sender<OutputStream>,
         request: &ReconciliationRequest,
     ) -> ClientResult<FunctionResult<ReconciliationResultSuccess>> {
-        self.execute_request(
-            nats_reconciliation_subject(self.nats_subject_prefix()),
-            output_tx,
-            request,
-        )
-        .await
+        self.execute(output_tx, request).await
     }
 
     #[instrument(name = "client.execute_reconciliation_with_subject", skip_all)]
@@ -162,55 +559,194 @@ impl Client {
         request: &ReconciliationRequest,
         subject_suffix: impl AsRef<str>,
     ) -> ClientResult<FunctionResult<ReconciliationResultSuccess>> {
-        self.execute_request(
-            nats_subject(self.nats_subject_prefix(), subject_suffix),
-            output_tx,
-            request,
-        )
-        .await
+        self.execute_with_subject(output_tx, request, subject_suffix)
+            .await
     }
 
-    #[instrument(name = "client.execute_reconciliation", skip_all)]
+    /// Like [`Client::execute_reconciliation`], but the execution can be cancelled through the given
+    /// [`ExecutionHandle`] while it is running.
+    #[instrument(name = "client.execute_reconciliation_with_handle", skip_all)]
+    pub async fn execute_reconciliation_with_handle(
+        &self,
+        handle: &ExecutionHandle,
+        output_tx: mpsc::Sender<OutputStream>,
+        request: &ReconciliationRequest,
+    ) -> ClientResult<FunctionResult<ReconciliationResultSuccess>> {
+        self.execute_with_handle(handle, output_tx, request).await
+    }
+
+    #[instrument(name = "client.execute_schema_variant_definition", skip_all)]
     pub async fn execute_schema_variant_definition(
         &self,
         output_tx: mpsc::Sender<OutputStream>,
         request: &SchemaVariantDefinitionRequest,
     ) -> ClientResult<FunctionResult<SchemaVariantDefinitionResultSuccess>> {
-        self.execute_request(
-            nats_schema_variant_definition_subject(self.nats_subject_prefix()),
-            output_tx,
-            request,
-        )
-        .await
+        self.execute(output_tx, request).await
     }
 
-    #[instrument(name = "client.execute_reconciliation_with_subject", skip_all)]
+    #[instrument(
+        name = "client.execute_schema_variant_definition_with_subject",
+        skip_all
+    )]
     pub async fn execute_schema_variant_definition_with_subject(
         &self,
         output_tx: mpsc::Sender<OutputStream>,
         request: &SchemaVariantDefinitionRequest,
         subject_suffix: impl AsRef<str>,
     ) -> ClientResult<FunctionResult<SchemaVariantDefinitionResultSuccess>> {
-        self.execute_request(
-            nats_subject(self.nats_subject_prefix(), subject_suffix),
+        self.execute_with_subject(output_tx, request, subject_suffix)
+            .await
+    }
+
+    /// Like [`Client::execute_schema_variant_definition`], but the execution can be cancelled through the given
+    /// [`ExecutionHandle`] while it is running.
+    #[instrument(
+        name = "client.execute_schema_variant_definition_with_handle",
+        skip_all
+    )]
+    pub async fn execute_schema_variant_definition_with_handle(
+        &self,
+        handle: &ExecutionHandle,
+        output_tx: mpsc::Sender<OutputStream>,
+        request: &SchemaVariantDefinitionRequest,
+    ) -> ClientResult<FunctionResult<SchemaVariantDefinitionResultSuccess>> {
+        self.execute_with_handle(handle, output_tx, request).await
+    }
+
+    /// Runs several functions at once over a single reply mailbox hierarchy, returning their
+    /// results as they finish. Output from every execution is sent on `output_tx`; each
+    /// [`OutputStream`] carries the execution id of the request it belongs to.
+    ///
+    /// Batched requests are not retried and do not time out.
+    #[instrument(name = "client.execute_batch", skip_all, fields(batch.size = requests.len()))]
+    pub async fn execute_batch(
+        &self,
+        requests: Vec<BatchRequest>,
+        output_tx: mpsc::Sender<OutputStream>,
+    ) -> ClientResult<BoxStream<'static, BatchResult>> {
+        if let Some(simulation) = &self.simulation {
+            return Ok(batch::simulated_batch(simulation, requests, output_tx).await);
+        }
+        let recording_dir = match &self.record_replay {
+            Some(RecordReplay::Replay(fixture_dir)) => {
+                return Ok(batch::replayed_batch(fixture_dir, requests).await);
+            }
+            Some(RecordReplay::Record(fixture_dir)) => Some((fixture_dir, requests.clone())),
+            None => None,
+        };
+        let dropped_output = DroppedOutput::default();
+        let forwarder = OutputForwarder::new(
             output_tx,
-            request,
+            self.output_store.clone(),
+            self.output_backpressure,
+            dropped_output.clone(),
+        );
+        let results = batch::execute_batch(
+            self.transport.as_ref(),
+            requests,
+            &self.envelope,
+            forwarder,
+            dropped_output,
         )
-        .await
+        .await?;
+
+        Ok(match recording_dir {
+            Some((fixture_dir, requests)) => batch::record_batch(fixture_dir, requests, results),
+            None => results,
+        })
     }
 
-    async fn execute_request<R, S>(
+    #[instrument(name = "client.execute_request", skip_all, fields(veritech.attempts = Empty))]
+    async fn execute_request<R: VeritechRequest>(
         &self,
         subject: impl Into<String>,
+        handle: &ExecutionHandle,
         output_tx: mpsc::Sender<OutputStream>,
+        progress_tx: Option<mpsc::Sender<FunctionProgress>>,
         request: &R,
+    ) -> ClientResult<FunctionResult<R::Success>> {
+        if let Some(simulation) = &self.simulation {
+            return simulated_result(simulation, output_tx, request).await;
+        }
+        let recording_dir = match &self.record_replay {
+            Some(RecordReplay::Replay(fixture_dir)) => {
+                return recording::replay(fixture_dir, request).await;
+            }
+            Some(RecordReplay::Record(fixture_dir)) => Some(fixture_dir),
+            None => None,
+        };
+
+        let execution_id = request.execution_id();
+        let cache = self
+            .cache
+            .as_ref()
+            .filter(|_| R::CACHEABLE && request.before().is_empty());
+        let cache_key = match cache {
+            Some(cache) => {
+                let key = CacheKey::for_request(request)?;
+                if let Some(cached) = cache.get(&key) {
+                    debug!(execution_id = %execution_id, cache_key = %key, "using cached result");
+                    metric!(
+                        monotonic_counter.veritech.client.cache_hits = 1_u64,
+                        veritech.kind = R::KIND
+                    );
+                    let result =
+                        serde_json::from_value(cache::with_execution_id(cached, execution_id))
+                            .map_err(ClientError::JSONDeserialize)?;
+                    if let Some(fixture_dir) = recording_dir {
+                        record_execution(fixture_dir, request, &result).await;
+                    }
+                    return Ok(result);
+                }
+                Some(key)
+            }
+            None => None,
+        };
+
+        let msg = self
+            .envelope
+            .seal(serde_json::to_vec(request).map_err(ClientError::JSONSerialize)?)?;
+        let started = Instant::now();
+        let outcome = self
+            .execute_message(subject, handle, output_tx, progress_tx, execution_id, &msg)
+            .await;
+        record_execution_metrics(
+            R::KIND,
+            self.nats_subject_prefix(),
+            started.elapsed(),
+            msg.payload.len(),
+            &outcome,
+        );
+
+        if let (Some(fixture_dir), Ok(result)) = (recording_dir, &outcome) {
+            record_execution(fixture_dir, request, result).await;
+        }
+        if let (Some(cache), Some(key), Ok(result @ FunctionResult::Success(_))) =
+            (cache, cache_key, &outcome)
+        {
+            match serde_json::to_value(result) {
+                Ok(value) => cache.insert(key, value),
+                Err(err) => warn!(error = ?err, "failed to serialize result for the cache"),
+            }
+        }
+        outcome
+    }
+
+    /// Publishes a serialized request, retrying it according to the client's [`RetryPolicy`],
+    /// and waits for its result.
+    async fn execute_message<S>(
+        &self,
+        subject: impl Into<String>,
+        handle: &ExecutionHandle,
+        output_tx: mpsc::Sender<OutputStream>,
+        progress_tx: Option<mpsc::Sender<FunctionProgress>>,
+        execution_id: &str,
+        msg: &SealedRequest,
     ) -> ClientResult<FunctionResult<S>>
     where
-        R: Serialize,
         S: DeserializeOwned,
     {
-        let msg = serde_json::to_vec(request).map_err(ClientError::JSONSerialize)?;
-        let reply_mailbox_root = self.nats.new_inbox();
+        let reply_mailbox_root = handle.reply_mailbox_root.clone();
 
         // Construct a subscription stream for the result
         let result_subscription_subject = reply_mailbox_for_result(&reply_mailbox_root);
@@ -218,11 +754,22 @@ impl Client {
             messaging.destination = &result_subscription_subject.as_str(),
             "subscribing for result messages"
         );
+        // A retried request can be answered more than once with the same result, so only the
+        // first copy of each result is yielded
+        let result_messages = chunks::reassemble_chunks(
+            self.transport
+                .subscribe(&result_subscription_subject)
+                .await?,
+            progress_tx.clone().map(|progress_tx| ChunkProgress {
+                execution_id: execution_id.to_string(),
+                progress_tx,
+            }),
+        );
         let mut result_subscription: Subscription<FunctionResult<S>> =
             Subscription::create(result_subscription_subject)
                 .final_message_header_key(FINAL_MESSAGE_HEADER_KEY)
-                .start(&self.nats)
-                .await?;
+                .deduplicate_messages()
+                .start_on_stream(result_messages);
 
         // Construct a subscription stream for output messages
         let output_subscription_subject = reply_mailbox_for_output(&reply_mailbox_root);
@@ -230,33 +777,159 @@ impl Client {
             messaging.destination = &output_subscription_subject.as_str(),
             "subscribing for output messages"
         );
+        let output_messages = self
+            .transport
+            .subscribe(&output_subscription_subject)
+            .await?;
         let output_subscription = Subscription::create(output_subscription_subject)
             .final_message_header_key(FINAL_MESSAGE_HEADER_KEY)
-            .start(&self.nats)
+            .start_on_stream(output_messages);
+
+        // Construct a subscription stream for progress messages, if the caller wants them
+        let progress_forwarding = match progress_tx {
+            Some(progress_tx) => {
+                let progress_subscription_subject = reply_mailbox_for_progress(&reply_mailbox_root);
+                trace!(
+                    messaging.destination = &progress_subscription_subject.as_str(),
+                    "subscribing for progress messages"
+                );
+                let progress_messages = self
+                    .transport
+                    .subscribe(&progress_subscription_subject)
+                    .await?;
+                let progress_subscription = Subscription::create(progress_subscription_subject)
+                    .final_message_header_key(FINAL_MESSAGE_HEADER_KEY)
+                    .start_on_stream(progress_messages);
+                Some((progress_subscription, progress_tx))
+            }
+            None => None,
+        };
+
+        // Root reply mailbox will receive a reply if nobody is listening to the channel `subject`
+        let mut root_subscription = self.transport.subscribe(&reply_mailbox_root).await?;
+
+        // A running execution's server publishes heartbeats until it has a result
+        let mut heartbeat_subscription = self
+            .transport
+            .subscribe(&reply_mailbox_for_heartbeat(&reply_mailbox_root))
             .await?;
 
-        // Spawn a task to forward output to the sender provided by the caller
-        tokio::spawn(forward_output_task(output_subscription, output_tx));
+        // Spawn tasks to forward output and progress to the senders provided by the caller. They
+        // are spawned once every subscription is in place, so that nothing can return early
+        // before their handles are kept below.
+        let mut forwarding_tasks = vec![tokio::spawn(forward_output_task(
+            output_subscription,
+            OutputForwarder::new(
+                output_tx,
+                self.output_store.clone(),
+                self.output_backpressure,
+                handle.dropped_output.clone(),
+            ),
+        ))];
+        if let Some((progress_subscription, progress_tx)) = progress_forwarding {
+            forwarding_tasks.push(tokio::spawn(forward_progress_task(
+                progress_subscription,
+                progress_tx,
+            )));
+        }
 
-        // Submit the request message
+        // Only requests with an execution id can be safely resent
+        let retry_policy = self
+            .retry_policy
+            .as_ref()
+            .filter(|_| !execution_id.is_empty());
         let subject = subject.into();
-        trace!(
-            messaging.destination = &subject.as_str(),
-            "publishing message"
-        );
+        let mut attempt = 0;
+        let outcome = loop {
+            attempt += 1;
+            let outcome = self
+                .execute_attempt(
+                    &subject,
+                    handle,
+                    execution_id,
+                    msg,
+                    &mut result_subscription,
+                    &mut root_subscription,
+                    &mut heartbeat_subscription,
+                )
+                .await;
+            match retry_policy.and_then(|policy| policy.retry_delay(attempt, &outcome)) {
+                Some(delay) => {
+                    warn!(
+                        execution_id = %execution_id,
+                        attempt,
+                        ?delay,
+                        outcome = ?outcome.as_ref().err(),
+                        "execution failed for a transient reason, retrying"
+                    );
+                    time::sleep(delay).await;
+                }
+                None => break outcome,
+            }
+        };
+        Span::current().record("veritech.attempts", attempt);
 
-        // Root reply mailbox will receive a reply if nobody is listening to the channel `subject`
-        let mut root_subscription = self.nats.subscribe(reply_mailbox_root.clone()).await?;
+        // Without a result, the server won't send the final messages that end the forwarding
+        // tasks, so they are aborted to end their subscriptions before returning
+        if outcome.is_err() {
+            for task in &forwarding_tasks {
+                task.abort();
+            }
+            future::join_all(forwarding_tasks).await;
+        } else if self.output_backpressure != OutputBackpressure::Block {
+            // The output is over before the result is published, so the output task is only left
+            // with lines it has yet to forward. Waiting a little for it lets the dropped line
+            // count cover them, and is skipped when blocking as no line is ever dropped.
+            let output_task = &mut forwarding_tasks[0];
+            if time::timeout(OUTPUT_DRAIN_TIMEOUT, output_task)
+                .await
+                .is_err()
+            {
+                debug!("output still being forwarded after the execution returned its result");
+            }
+        }
 
-        self.nats
-            .publish_with_reply_or_headers(subject, Some(reply_mailbox_root.clone()), None, msg)
-            .await?;
+        // Dropping the heartbeat and root subscriptions ends them
+        result_subscription.unsubscribe().await?;
+        outcome
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    async fn execute_attempt<S>(
+        &self,
+        subject: &str,
+        handle: &ExecutionHandle,
+        execution_id: &str,
+        msg: &SealedRequest,
+        result_subscription: &mut Subscription<FunctionResult<S>>,
+        root_subscription: &mut BoxStream<'static, RawMessage>,
+        heartbeat_subscription: &mut BoxStream<'static, RawMessage>,
+    ) -> ClientResult<FunctionResult<S>>
+    where
+        S: DeserializeOwned,
+    {
+        // Submit the request message, once there is a connection to submit it on
+        if let Some(connection) = &self.connection {
+            connection.until_connected().await?;
+        }
+        self.publish_request(subject, handle, msg).await?;
+
+        let timeout = handle.timeout.or(self.timeout);
+        let timed_out = async move {
+            match timeout {
+                Some(timeout) => {
+                    time::sleep(timeout).await;
+                    timeout
+                }
+                None => future::pending().await,
+            }
+        };
+        let server_lost = server_lost(heartbeat_subscription, self.heartbeat_timeout);
+        let resend_on_reconnect = self.resend_on_reconnect(subject, handle, execution_id, msg);
 
         tokio::select! {
             // Wait for one message on the result reply mailbox
             result = result_subscription.try_next() => {
-                root_subscription.unsubscribe().await?;
-                result_subscription.unsubscribe().await?;
                 match result? {
                     Some(result) => Ok(result.payload),
                     None => Err(ClientError::NoResult)
@@ -264,16 +937,16 @@ impl Client {
             }
             reply = root_subscription.next() => {
                 match &reply {
-                    Some(maybe_msg) => {
+                    Some(msg) => {
                         error!(
-                            subject = reply_mailbox_root,
-                            maybe_msg = ?maybe_msg,
-                            "received an unexpected message or error on reply subject prefix"
+                            subject = handle.reply_mailbox_root,
+                            msg = ?msg,
+                            "received an unexpected message on reply subject prefix"
                         )
                     }
                     None => {
                         error!(
-                            subject = reply_mailbox_root,
+                            subject = handle.reply_mailbox_root,
                             "reply subject prefix subscription unexpectedly closed"
                         )
                     }
@@ -281,29 +954,259 @@ impl Client {
 
                 // In all cases, we're considering a message on this subscription to be fatal and
                 // will return with an error
-                Err(ClientError::PublishingFailed(reply.ok_or(ClientError::RootConnectionClosed)??))
+                Err(ClientError::PublishingFailed(reply.ok_or(ClientError::RootConnectionClosed)?))
+            }
+            // Give up on the result and ask the server to stop the function, which frees up its
+            // cyclone instance
+            timeout = timed_out => {
+                warn!(subject = handle.reply_mailbox_root, ?timeout, "execution timed out");
+                if let Err(err) = handle.cancel().await {
+                    warn!(error = ?err, "failed to cancel timed out execution");
+                }
+                Err(ClientError::Timeout(timeout))
             }
+            silence = server_lost => {
+                error!(
+                    subject = handle.reply_mailbox_root,
+                    ?silence,
+                    "no heartbeat from the server, assuming it was lost"
+                );
+                Err(ClientError::ServerLost(silence))
+            }
+            never = resend_on_reconnect => match never {},
+        }
+    }
+
+    async fn publish_request(
+        &self,
+        subject: &str,
+        handle: &ExecutionHandle,
+        msg: &SealedRequest,
+    ) -> ClientResult<()> {
+        trace!(messaging.destination = subject, "publishing message");
+        self.transport
+            .publish_with_reply(
+                subject,
+                Some(handle.reply_mailbox_root.clone()),
+                msg.headers.as_ref(),
+                msg.payload.clone(),
+            )
+            .await
+    }
+
+    /// Publishes the request again every time the connection to NATS comes back, in case it was
+    /// lost while the connection was down. Only requests with an execution id are resent, as
+    /// servers can only recognize a copy of those. Never returns.
+    async fn resend_on_reconnect(
+        &self,
+        subject: &str,
+        handle: &ExecutionHandle,
+        execution_id: &str,
+        msg: &SealedRequest,
+    ) -> Infallible {
+        match self
+            .connection
+            .as_ref()
+            .filter(|_| !execution_id.is_empty())
+        {
+            Some(connection) => {
+                connection
+                    .on_reconnect(|| async move {
+                        info!(
+                            execution_id,
+                            "connection to nats is back, resending request"
+                        );
+                        if let Err(err) = self.publish_request(subject, handle, msg).await {
+                            warn!(error = ?err, "failed to resend request after reconnecting");
+                        }
+                    })
+                    .await
+            }
+            None => future::pending().await,
+        }
+    }
+}
+
+/// A handle on a single execution, used to cancel it while it is running.
+///
+/// Cancelling asks veritech to stop the function; the pending `execute_*_with_handle` call then
+/// returns a [`FunctionResult::Failure`] with a `cancelled` error kind. Cancelling an execution
+/// that has already finished, or that has not started yet, does nothing.
+#[derive(Clone, Debug)]
+pub struct ExecutionHandle {
+    transport: Arc<dyn Transport>,
+    reply_mailbox_root: String,
+    timeout: Option<Duration>,
+    dropped_output: DroppedOutput,
+}
+
+impl ExecutionHandle {
+    /// Overrides the [`Client`]'s timeout for this execution.
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    /// Returns how many lines of the execution's output were dropped because the caller's
+    /// channel was full, according to the [`Client`]'s [`OutputBackpressure`]. Once the
+    /// `execute_*_with_handle` call has returned a result, this covers all of its output unless
+    /// the caller was still holding it up a second later.
+    pub fn dropped_output_lines(&self) -> u64 {
+        self.dropped_output.total()
+    }
+
+    #[instrument(name = "client.execution_handle.cancel", skip_all)]
+    pub async fn cancel(&self) -> ClientResult<()> {
+        let subject = reply_mailbox_for_cancel(&self.reply_mailbox_root);
+        trace!(
+            messaging.destination = &subject.as_str(),
+            "publishing cancellation"
+        );
+        self.transport
+            .publish_with_reply(&subject, None, None, vec![])
+            .await
+    }
+}
+
+/// Resolves once `heartbeat_timeout` passes without a heartbeat, returning how long the server
+/// was silent.
+async fn server_lost(
+    heartbeat_subscription: &mut BoxStream<'static, RawMessage>,
+    heartbeat_timeout: Option<Duration>,
+) -> Duration {
+    let heartbeat_timeout = match heartbeat_timeout {
+        Some(heartbeat_timeout) => heartbeat_timeout,
+        None => return future::pending().await,
+    };
+    loop {
+        match time::timeout(heartbeat_timeout, heartbeat_subscription.next()).await {
+            Ok(Some(_)) => continue,
+            // The subscription closed, so heartbeats can no longer be observed
+            Ok(None) => return future::pending().await,
+            Err(_) => return heartbeat_timeout,
+        }
+    }
+}
+
+/// Records the outcome of an execution, as seen by the client. Durations include any retries.
+fn record_execution_metrics<S>(
+    kind: &str,
+    subject_prefix: Option<&str>,
+    duration: Duration,
+    request_bytes: usize,
+    outcome: &ClientResult<FunctionResult<S>>,
+) {
+    let outcome = match outcome {
+        Ok(FunctionResult::Success(_)) => "success",
+        Ok(FunctionResult::Failure(failure))
+            if failure.error.category == FunctionErrorKind::Cancelled =>
+        {
+            "cancelled"
         }
+        Ok(FunctionResult::Failure(_)) => "failure",
+        Err(ClientError::ServerLost(_)) => "serverLost",
+        Err(ClientError::Timeout(_)) => "timeout",
+        Err(_) => "error",
+    };
+    metric!(
+        monotonic_counter.veritech.client.executions = 1_u64,
+        histogram.veritech.client.execution_duration_ms = duration.as_millis() as u64,
+        histogram.veritech.client.request_bytes = request_bytes as u64,
+        veritech.kind = kind,
+        veritech.subject_prefix = subject_prefix.unwrap_or_default(),
+        veritech.outcome = outcome
+    );
+}
+
+/// Records an execution, only logging failures to do so as they don't affect its result.
+async fn record_execution<R: VeritechRequest>(
+    fixture_dir: &Path,
+    request: &R,
+    result: &FunctionResult<R::Success>,
+) {
+    if let Err(err) = recording::record(fixture_dir, request, result).await {
+        warn!(error = ?err, execution_id = request.execution_id(), "failed to record execution");
+    }
+}
+
+async fn simulated_result<R, S>(
+    simulation: &SimulatedResults,
+    output_tx: mpsc::Sender<OutputStream>,
+    request: &R,
+) -> ClientResult<FunctionResult<S>>
+where
+    R: Serialize,
+    S: DeserializeOwned,
+{
+    let request = serde_json::to_value(request).map_err(ClientError::JSONSerialize)?;
+    let handler = request
+        .get("handler")
+        .and_then(|h| h.as_str())
+        .unwrap_or_default()
+        .to_string();
+    let result = simulation
+        .result_for(&request)
+        .ok_or_else(|| ClientError::NoSimulatedResult(handler.clone()))?;
+
+    let output = OutputStream {
+        stream: "output".to_string(),
+        execution_id: request
+            .get("executionId")
+            .and_then(|id| id.as_str())
+            .unwrap_or_default()
+            .to_string(),
+        level: "info".to_string(),
+        group: None,
+        message: format!("simulated execution of {handler}; no function was run"),
+        timestamp: SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or_default(),
+    };
+    if let Err(err) = output_tx.send(output).await {
+        warn!(error = ?err, "failed to send simulated output on channel");
     }
+
+    serde_json::from_value(result).map_err(ClientError::JSONDeserialize)
 }
 
 async fn forward_output_task(
     mut output_subscription: Subscription<OutputStream>,
-    output_tx: mpsc::Sender<OutputStream>,
+    mut forwarder: OutputForwarder,
 ) {
     while let Some(msg) = output_subscription.next().await {
         match msg {
-            Ok(output) => {
-                if let Err(err) = output_tx.send(output.payload).await {
-                    warn!(error = ?err, "output forwarder failed to send message on channel");
-                }
-            }
+            Ok(output) => forwarder.forward(output.payload).await,
             Err(err) => {
                 warn!(error = ?err, "output forwarder received an error on its subscription")
             }
         }
     }
+    forwarder.finish().await;
     if let Err(err) = output_subscription.unsubscribe().await {
         warn!(error = ?err, "error when unsubscribing from output subscription");
     }
 }
+
+async fn forward_progress_task(
+    mut progress_subscription: Subscription<FunctionProgress>,
+    progress_tx: mpsc::Sender<FunctionProgress>,
+) {
+    while let Some(msg) = progress_subscription.next().await {
+        match msg {
+            Ok(progress) => {
+                if let Err(mpsc::error::TrySendError::Full(_)) =
+                    progress_tx.try_send(progress.payload)
+                {
+                    trace!("progress receiver is full, dropping progress report");
+                }
+            }
+            Err(err) => {
+                warn!(error = ?err, "progress forwarder received an error on its subscription")
+            }
+        }
+    }
+    if let Err(err) = progress_subscription.unsubscribe().await {
+        warn!(error = ?err, "error when unsubscribing from progress subscription");
+    }
+}
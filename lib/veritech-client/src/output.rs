@@ -0,0 +1,246 @@
+use std::{
+    collections::{BTreeMap, HashMap, VecDeque},
+    fmt,
+    sync::{Arc, Mutex, MutexGuard, PoisonError},
+    time::Duration,
+};
+
+use cyclone_core::OutputStream;
+use telemetry::prelude::*;
+use tokio::{
+    sync::mpsc::{self, error::TrySendError},
+    time,
+};
+
+/// What to do with output when the caller's channel is full.
+#[remain::sorted]
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum OutputBackpressure {
+    /// Wait for the caller to make room, however long it takes. Nothing is ever dropped, but a
+    /// caller that stops reading holds up the execution's output indefinitely.
+    Block,
+    /// Wait up to the given duration for the caller to make room, then drop the line.
+    BlockWithTimeout(Duration),
+    /// Hold up to the given number of lines in memory until the caller makes room, then drop
+    /// lines beyond that.
+    Buffer(usize),
+    /// Drop every line that does not fit in the channel.
+    #[default]
+    Drop,
+}
+
+/// A store for the output of executions, kept so that the output of past runs can be fetched by
+/// execution id after its receiver is gone.
+pub trait OutputStore: fmt::Debug + Send + Sync {
+    /// Appends a line to the output of its execution.
+    fn append(&self, output: &OutputStream);
+    /// Returns the output of an execution, in the order it was produced.
+    fn get(&self, execution_id: &str) -> Option<Vec<OutputStream>>;
+}
+
+/// An [`OutputStore`] that keeps the output of the most recent executions in memory.
+#[derive(Debug)]
+pub struct InMemoryOutputStore {
+    capacity: usize,
+    executions: Mutex<StoredExecutions>,
+}
+
+#[derive(Debug, Default)]
+struct StoredExecutions {
+    output: HashMap<String, Vec<OutputStream>>,
+    /// Execution ids from oldest to newest.
+    order: VecDeque<String>,
+}
+
+impl InMemoryOutputStore {
+    pub const DEFAULT_CAPACITY: usize = 256;
+
+    /// Creates a store that keeps the output of up to `capacity` executions.
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity: capacity.max(1),
+            executions: Mutex::new(StoredExecutions::default()),
+        }
+    }
+}
+
+impl Default for InMemoryOutputStore {
+    fn default() -> Self {
+        Self::new(Self::DEFAULT_CAPACITY)
+    }
+}
+
+impl OutputStore for InMemoryOutputStore {
+    fn append(&self, output: &OutputStream) {
+        let mut executions = self
+            .executions
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner);
+        if let Some(lines) = executions.output.get_mut(&output.execution_id) {
+            lines.push(output.clone());
+            return;
+        }
+
+        executions.order.push_back(output.execution_id.clone());
+        executions
+            .output
+            .insert(output.execution_id.clone(), vec![output.clone()]);
+        while executions.output.len() > self.capacity {
+            match executions.order.pop_front() {
+                Some(oldest) => {
+                    executions.output.remove(&oldest);
+                }
+                None => break,
+            }
+        }
+    }
+
+    fn get(&self, execution_id: &str) -> Option<Vec<OutputStream>> {
+        self.executions
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .output
+            .get(execution_id)
+            .cloned()
+    }
+}
+
+/// The number of output lines dropped for each execution, shared between the
+/// [`OutputForwarder`] that drops them and the result they are reported on.
+#[derive(Clone, Debug, Default)]
+pub(crate) struct DroppedOutput(Arc<Mutex<BTreeMap<String, u64>>>);
+
+impl DroppedOutput {
+    /// Returns the number of lines dropped for an execution so far.
+    pub(crate) fn count(&self, execution_id: &str) -> u64 {
+        self.counts().get(execution_id).copied().unwrap_or_default()
+    }
+
+    /// Returns the number of lines dropped for every execution so far.
+    pub(crate) fn total(&self) -> u64 {
+        self.counts().values().sum()
+    }
+
+    fn increment(&self, execution_id: &str) {
+        *self.counts().entry(execution_id.to_string()).or_default() += 1;
+    }
+
+    fn counts(&self) -> MutexGuard<'_, BTreeMap<String, u64>> {
+        self.0.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+/// Sends output to the caller's channel according to an [`OutputBackpressure`] strategy, counting
+/// the lines it drops in a [`DroppedOutput`].
+pub(crate) struct OutputForwarder {
+    output_tx: mpsc::Sender<OutputStream>,
+    output_store: Option<Arc<dyn OutputStore>>,
+    backpressure: OutputBackpressure,
+    buffer: VecDeque<OutputStream>,
+    dropped: DroppedOutput,
+}
+
+impl OutputForwarder {
+    pub(crate) fn new(
+        output_tx: mpsc::Sender<OutputStream>,
+        output_store: Option<Arc<dyn OutputStore>>,
+        backpressure: OutputBackpressure,
+        dropped: DroppedOutput,
+    ) -> Self {
+        Self {
+            output_tx,
+            output_store,
+            backpressure,
+            buffer: VecDeque::new(),
+            dropped,
+        }
+    }
+
+    pub(crate) async fn forward(&mut self, output: OutputStream) {
+        if let Some(output_store) = &self.output_store {
+            output_store.append(&output);
+        }
+
+        match self.backpressure {
+            OutputBackpressure::Block => {
+                if let Err(err) = self.output_tx.send(output).await {
+                    warn!(error = ?err, "output forwarder failed to send message on channel");
+                }
+            }
+            OutputBackpressure::BlockWithTimeout(timeout) => {
+                let execution_id = output.execution_id.clone();
+                match time::timeout(timeout, self.output_tx.send(output)).await {
+                    Ok(Ok(())) => {}
+                    Ok(Err(err)) => {
+                        warn!(error = ?err, "output forwarder failed to send message on channel");
+                    }
+                    Err(_) => self.drop_output(&execution_id),
+                }
+            }
+            OutputBackpressure::Buffer(max_buffered) => {
+                self.flush_buffer();
+                if !self.buffer.is_empty() {
+                    self.buffer_output(output, max_buffered);
+                    return;
+                }
+                match self.output_tx.try_send(output) {
+                    Ok(()) => {}
+                    Err(TrySendError::Full(output)) => self.buffer_output(output, max_buffered),
+                    Err(TrySendError::Closed(_)) => {
+                        warn!("output forwarder failed to send message on closed channel");
+                    }
+                }
+            }
+            OutputBackpressure::Drop => match self.output_tx.try_send(output) {
+                Ok(()) => {}
+                Err(TrySendError::Full(output)) => self.drop_output(&output.execution_id),
+                Err(TrySendError::Closed(_)) => {
+                    warn!("output forwarder failed to send message on closed channel");
+                }
+            },
+        }
+    }
+
+    /// Sends any buffered output, waiting for room if necessary, and logs dropped lines.
+    pub(crate) async fn finish(mut self) {
+        while let Some(output) = self.buffer.pop_front() {
+            if let Err(err) = self.output_tx.send(output).await {
+                warn!(error = ?err, "output forwarder failed to send buffered message on channel");
+                break;
+            }
+        }
+
+        for (execution_id, count) in self.dropped.counts().iter() {
+            warn!(%execution_id, count, "output forwarder dropped output lines");
+            metric!(monotonic_counter.veritech.client.output_dropped = *count);
+        }
+    }
+
+    fn flush_buffer(&mut self) {
+        while let Some(output) = self.buffer.pop_front() {
+            match self.output_tx.try_send(output) {
+                Ok(()) => {}
+                Err(TrySendError::Full(output)) => {
+                    self.buffer.push_front(output);
+                    return;
+                }
+                Err(TrySendError::Closed(_)) => {
+                    self.buffer.clear();
+                    return;
+                }
+            }
+        }
+    }
+
+    fn buffer_output(&mut self, output: OutputStream, max_buffered: usize) {
+        if self.buffer.len() < max_buffered {
+            self.buffer.push_back(output);
+        } else {
+            self.drop_output(&output.execution_id);
+        }
+    }
+
+    fn drop_output(&mut self, execution_id: &str) {
+        self.dropped.increment(execution_id);
+    }
+}
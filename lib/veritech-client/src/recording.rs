@@ -0,0 +1,126 @@
+use std::{
+    io,
+    path::{Path, PathBuf},
+};
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use telemetry::prelude::*;
+use thiserror::Error;
+use tokio::fs;
+
+use crate::{CacheKey, ClientError, ClientResult, FunctionResult, VeritechRequest};
+
+#[remain::sorted]
+#[derive(Debug, Error)]
+pub enum RecordingError {
+    #[error("failed to parse recorded execution {0}: {1}")]
+    FixtureParse(PathBuf, #[source] serde_json::Error),
+    #[error("failed to read recorded execution {0}: {1}")]
+    FixtureRead(PathBuf, #[source] io::Error),
+    #[error("failed to serialize execution for recording: {0}")]
+    FixtureSerialize(#[source] serde_json::Error),
+    #[error("failed to write recorded execution {0}: {1}")]
+    FixtureWrite(PathBuf, #[source] io::Error),
+}
+
+pub type RecordingResult<T> = Result<T, RecordingError>;
+
+/// Whether a [`Client`](crate::Client) records the executions it runs to a fixture directory, or
+/// replays the ones recorded there instead of sending requests to veritech.
+///
+/// Executions are matched by their kind of function and request, minus the execution id (see
+/// [`CacheKey`]), so a replayed request must be the same as the recorded one. Replaying needs
+/// neither NATS nor cyclone, which makes for deterministic tests of code calling functions that
+/// shell out to programs such as skopeo or kubeval.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum RecordReplay {
+    /// Runs every execution and writes its request and result to the directory, replacing any
+    /// earlier recording of the same request.
+    Record(PathBuf),
+    /// Answers every execution with its result recorded in the directory, failing with
+    /// [`ClientError::NoRecordedExecution`] for requests that weren't recorded.
+    Replay(PathBuf),
+}
+
+/// The request and result of a recorded execution, as stored in a fixture file.
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RecordedExecution {
+    /// The kind of function, such as `resolverFunction` or `actionRun`.
+    pub kind: String,
+    /// The request, without its execution id. It is kept for whoever reviews the fixture and
+    /// isn't read back.
+    pub request: Value,
+    /// The JSON form of the [`FunctionResult`].
+    pub result: Value,
+}
+
+/// Returns the recorded result of a request, with its execution id rewritten to the request's.
+pub(crate) async fn replay<R: VeritechRequest>(
+    fixture_dir: &Path,
+    request: &R,
+) -> ClientResult<FunctionResult<R::Success>> {
+    let key = CacheKey::for_request(request)?;
+    let path = fixture_path(fixture_dir, R::KIND, &key);
+    trace!(fixture_path = %path.display(), "replaying recorded execution");
+
+    let bytes = match fs::read(&path).await {
+        Ok(bytes) => bytes,
+        Err(err) if err.kind() == io::ErrorKind::NotFound => {
+            return Err(ClientError::NoRecordedExecution(R::KIND, key));
+        }
+        Err(err) => return Err(RecordingError::FixtureRead(path, err).into()),
+    };
+    let recorded: RecordedExecution = serde_json::from_slice(&bytes)
+        .map_err(|err| RecordingError::FixtureParse(path.clone(), err))?;
+
+    serde_json::from_value(with_execution_id(recorded.result, request.execution_id()))
+        .map_err(|err| RecordingError::FixtureParse(path, err).into())
+}
+
+/// Writes the request and result of an execution to the fixture directory.
+pub(crate) async fn record<R: VeritechRequest>(
+    fixture_dir: &Path,
+    request: &R,
+    result: &FunctionResult<R::Success>,
+) -> ClientResult<()> {
+    let key = CacheKey::for_request(request)?;
+    let path = fixture_path(fixture_dir, R::KIND, &key);
+
+    let mut request = serde_json::to_value(request).map_err(RecordingError::FixtureSerialize)?;
+    if let Some(object) = request.as_object_mut() {
+        object.remove("executionId");
+    }
+    let recorded = RecordedExecution {
+        kind: R::KIND.to_string(),
+        request,
+        result: serde_json::to_value(result).map_err(RecordingError::FixtureSerialize)?,
+    };
+    let bytes = serde_json::to_vec_pretty(&recorded).map_err(RecordingError::FixtureSerialize)?;
+
+    fs::create_dir_all(fixture_dir)
+        .await
+        .map_err(|err| RecordingError::FixtureWrite(fixture_dir.to_path_buf(), err))?;
+    fs::write(&path, bytes)
+        .await
+        .map_err(|err| RecordingError::FixtureWrite(path.clone(), err))?;
+    debug!(fixture_path = %path.display(), "recorded execution");
+
+    Ok(())
+}
+
+fn fixture_path(fixture_dir: &Path, kind: &str, key: &CacheKey) -> PathBuf {
+    fixture_dir.join(format!("{kind}-{key}.json"))
+}
+
+/// Rewrites the execution id of a recorded result, successful or not.
+fn with_execution_id(mut result: Value, execution_id: &str) -> Value {
+    if let Some(success) = result.get_mut("Success").and_then(|s| s.as_object_mut()) {
+        success.insert("executionId".to_string(), execution_id.into());
+    }
+    if let Some(failure) = result.get_mut("Failure").and_then(|f| f.as_object_mut()) {
+        failure.insert("execution_id".to_string(), execution_id.into());
+    }
+    result
+}
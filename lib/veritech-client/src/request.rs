@@ -0,0 +1,118 @@
+use cyclone_core::{
+    ActionRunRequest, ActionRunResultSuccess, BeforeFunction, ReconciliationRequest,
+    ReconciliationResultSuccess, ResolverFunctionRequest, ResolverFunctionResultSuccess,
+    SchemaVariantDefinitionRequest, SchemaVariantDefinitionResultSuccess, ValidationRequest,
+    ValidationResultSuccess,
+};
+use serde::{de::DeserializeOwned, Serialize};
+use veritech_core::{
+    NATS_ACTION_RUN_DEFAULT_SUBJECT, NATS_CONCILIATION_DEFAULT_SUBJECT,
+    NATS_RESOLVER_FUNCTION_DEFAULT_SUBJECT, NATS_SCHEMA_VARIANT_DEFINITION_DEFAULT_SUBJECT,
+    NATS_VALIDATION_DEFAULT_SUBJECT,
+};
+
+/// A request for one kind of function that veritech can run, executed with
+/// [`Client::execute`](crate::Client::execute).
+///
+/// Supporting a new kind of function on the client only takes an implementation of this trait.
+pub trait VeritechRequest: Serialize + Send + Sync {
+    /// The successful result of running the function.
+    type Success: DeserializeOwned + Serialize;
+
+    /// The subject the request is published on, before any subject prefix is applied.
+    const SUBJECT_SUFFIX: &'static str;
+    /// A short name for the kind of function, used in telemetry.
+    const KIND: &'static str;
+    /// Whether the function only depends on its request, so that its result can be reused from a
+    /// [`ResultCache`](crate::ResultCache). Functions with side effects must not be cached.
+    const CACHEABLE: bool;
+
+    fn execution_id(&self) -> &str;
+
+    /// The functions run before the request's function, such as to authenticate. Results of
+    /// requests with before functions are never cached, as the before functions may reach outside
+    /// systems.
+    fn before(&self) -> &[BeforeFunction] {
+        &[]
+    }
+}
+
+impl VeritechRequest for ActionRunRequest {
+    type Success = ActionRunResultSuccess;
+
+    const SUBJECT_SUFFIX: &'static str = NATS_ACTION_RUN_DEFAULT_SUBJECT;
+    const KIND: &'static str = "actionRun";
+    const CACHEABLE: bool = false;
+
+    fn execution_id(&self) -> &str {
+        &self.execution_id
+    }
+
+    fn before(&self) -> &[BeforeFunction] {
+        &self.before
+    }
+}
+
+impl VeritechRequest for ReconciliationRequest {
+    type Success = ReconciliationResultSuccess;
+
+    const SUBJECT_SUFFIX: &'static str = NATS_CONCILIATION_DEFAULT_SUBJECT;
+    const KIND: &'static str = "reconciliation";
+    const CACHEABLE: bool = false;
+
+    fn execution_id(&self) -> &str {
+        &self.execution_id
+    }
+
+    fn before(&self) -> &[BeforeFunction] {
+        &self.before
+    }
+}
+
+impl VeritechRequest for ResolverFunctionRequest {
+    type Success = ResolverFunctionResultSuccess;
+
+    const SUBJECT_SUFFIX: &'static str = NATS_RESOLVER_FUNCTION_DEFAULT_SUBJECT;
+    const KIND: &'static str = "resolverFunction";
+    const CACHEABLE: bool = true;
+
+    fn execution_id(&self) -> &str {
+        &self.execution_id
+    }
+
+    fn before(&self) -> &[BeforeFunction] {
+        &self.before
+    }
+}
+
+impl VeritechRequest for SchemaVariantDefinitionRequest {
+    type Success = SchemaVariantDefinitionResultSuccess;
+
+    const SUBJECT_SUFFIX: &'static str = NATS_SCHEMA_VARIANT_DEFINITION_DEFAULT_SUBJECT;
+    const KIND: &'static str = "schemaVariantDefinition";
+    const CACHEABLE: bool = true;
+
+    fn execution_id(&self) -> &str {
+        &self.execution_id
+    }
+
+    fn before(&self) -> &[BeforeFunction] {
+        &self.before
+    }
+}
+
+impl VeritechRequest for ValidationRequest {
+    type Success = ValidationResultSuccess;
+
+    const SUBJECT_SUFFIX: &'static str = NATS_VALIDATION_DEFAULT_SUBJECT;
+    const KIND: &'static str = "validation";
+    const CACHEABLE: bool = true;
+
+    fn execution_id(&self) -> &str {
+        &self.execution_id
+    }
+
+    fn before(&self) -> &[BeforeFunction] {
+        &self.before
+    }
+}
@@ -0,0 +1,84 @@
+use std::time::Duration;
+
+use cyclone_core::FunctionResult;
+
+use crate::{ClientError, ClientResult};
+
+/// The kind of failure recorded for an execution that the veritech server could not run, for
+/// example because no cyclone instance could be checked out of its pool.
+const SERVER_FAILURE_KIND: &str = "veritechServer";
+
+/// The transient failures a [`RetryPolicy`] may retry.
+#[remain::sorted]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum RetryOn {
+    /// An error from NATS, such as a dropped connection.
+    Nats,
+    /// The request was not delivered because no veritech server was listening for it.
+    PublishingFailed,
+    /// The veritech server failed the execution without running the function, for example when
+    /// its cyclone pool was exhausted.
+    ServerFailure,
+    /// The server stopped sending heartbeats while the function was running.
+    ServerLost,
+    /// The execution timed out (see [`Client::with_timeout`](crate::Client::with_timeout)).
+    Timeout,
+}
+
+/// Resends a request when its execution fails for a transient reason.
+///
+/// Only requests with an execution id are retried. Every attempt shares the same reply mailbox,
+/// so the caller receives exactly one result even if an earlier attempt answers late.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct RetryPolicy {
+    /// The total number of attempts, including the first one.
+    pub max_attempts: u32,
+    /// How long to wait before the first retry. The delay doubles with every retry after that.
+    pub backoff: Duration,
+    pub retry_on: Vec<RetryOn>,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_attempts: 3,
+            backoff: Duration::from_millis(250),
+            retry_on: vec![
+                RetryOn::Nats,
+                RetryOn::PublishingFailed,
+                RetryOn::ServerFailure,
+            ],
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// Returns how long to wait before retrying, or `None` if the outcome of the given attempt
+    /// (counting from 1) should be returned to the caller.
+    pub(crate) fn retry_delay<S>(
+        &self,
+        attempt: u32,
+        outcome: &ClientResult<FunctionResult<S>>,
+    ) -> Option<Duration> {
+        if attempt >= self.max_attempts {
+            return None;
+        }
+        let retry_on = match outcome {
+            Err(ClientError::Nats(_)) => RetryOn::Nats,
+            Err(ClientError::PublishingFailed(_)) => RetryOn::PublishingFailed,
+            Err(ClientError::ServerLost(_)) => RetryOn::ServerLost,
+            Err(ClientError::Timeout(_)) => RetryOn::Timeout,
+            Ok(FunctionResult::Failure(failure)) if failure.error.kind == SERVER_FAILURE_KIND => {
+                RetryOn::ServerFailure
+            }
+            _ => return None,
+        };
+        if !self.retry_on.contains(&retry_on) {
+            return None;
+        }
+        Some(
+            self.backoff
+                .saturating_mul(2_u32.saturating_pow(attempt.saturating_sub(1))),
+        )
+    }
+}
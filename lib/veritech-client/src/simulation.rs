@@ -0,0 +1,87 @@
+use std::{collections::HashMap, io, path::Path};
+
+use serde::{Deserialize, Serialize};
+use telemetry::prelude::*;
+use thiserror::Error;
+use tokio::{fs::File, io::AsyncReadExt};
+
+#[remain::sorted]
+#[derive(Debug, Error)]
+pub enum SimulationError {
+    #[error("failed to parse simulated results fixture: {0}")]
+    FixtureParse(#[source] serde_json::Error),
+    #[error("failed to read simulated results fixture: {0}")]
+    FixtureRead(#[source] io::Error),
+}
+
+pub type SimulationResult<T> = Result<T, SimulationError>;
+
+/// A set of canned function results which a [`Client`](crate::Client) returns instead of
+/// dispatching requests to veritech.
+///
+/// Results are keyed by function handler and stored as the JSON form of a
+/// [`FunctionResult`](crate::FunctionResult), i.e. `{ "Success": { ... } }` or
+/// `{ "Failure": { ... } }`. The execution id in a result is replaced with the execution id of
+/// the request being simulated.
+#[derive(Clone, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+pub struct SimulatedResults {
+    results: HashMap<String, serde_json::Value>,
+    #[serde(default)]
+    fallback: Option<serde_json::Value>,
+}
+
+impl SimulatedResults {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Loads simulated results from a JSON fixture file.
+    pub async fn load(fixture_path: impl AsRef<Path>) -> SimulationResult<Self> {
+        trace!(
+            fixture_path = %fixture_path.as_ref().display(),
+            "loading simulated results fixture from disk",
+        );
+        let mut file = File::open(fixture_path)
+            .await
+            .map_err(SimulationError::FixtureRead)?;
+        let mut buf = Vec::new();
+        file.read_to_end(&mut buf)
+            .await
+            .map_err(SimulationError::FixtureRead)?;
+
+        serde_json::from_slice(&buf).map_err(SimulationError::FixtureParse)
+    }
+
+    /// Adds (or replaces) the result returned for executions of `handler`.
+    pub fn insert(&mut self, handler: impl Into<String>, result: serde_json::Value) -> &mut Self {
+        self.results.insert(handler.into(), result);
+        self
+    }
+
+    /// Sets the result returned for handlers without an explicit entry.
+    pub fn fallback(&mut self, result: serde_json::Value) -> &mut Self {
+        self.fallback = Some(result);
+        self
+    }
+
+    /// Returns the simulated result for a request, with its execution id rewritten to match.
+    pub(crate) fn result_for(&self, request: &serde_json::Value) -> Option<serde_json::Value> {
+        let handler = request.get("handler").and_then(|h| h.as_str())?;
+        let mut result = self
+            .results
+            .get(handler)
+            .or(self.fallback.as_ref())?
+            .clone();
+
+        if let Some(execution_id) = request.get("executionId").cloned() {
+            if let Some(success) = result.get_mut("Success").and_then(|s| s.as_object_mut()) {
+                success.insert("executionId".to_string(), execution_id.clone());
+            }
+            if let Some(failure) = result.get_mut("Failure").and_then(|f| f.as_object_mut()) {
+                failure.insert("execution_id".to_string(), execution_id);
+            }
+        }
+
+        Some(result)
+    }
+}
@@ -0,0 +1,248 @@
+use std::{
+    fmt,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc, Mutex, PoisonError,
+    },
+    time::Duration,
+};
+
+use async_trait::async_trait;
+use futures::{channel::mpsc, future, stream::BoxStream, StreamExt};
+use nats_subscriber::RawMessage;
+use si_data_nats::{HeaderMap, NatsClient};
+use telemetry::prelude::*;
+
+use crate::ClientResult;
+
+/// Carries requests from a [`Client`](crate::Client) to veritech servers, and their replies back.
+///
+/// [`NatsTransport`] is what clients use by default. [`LoopbackTransport`] delivers messages within
+/// the process instead, so that functions can be run against an in-process responder without a
+/// NATS server.
+#[async_trait]
+pub trait Transport: fmt::Debug + Send + Sync {
+    /// The prefix of every subject used with this transport, if it has one.
+    fn subject_prefix(&self) -> Option<&str>;
+
+    /// Returns a new, unique subject to receive replies on.
+    fn new_inbox(&self) -> String;
+
+    /// Publishes a message on `subject`, asking for replies to be sent to `reply_mailbox`.
+    async fn publish_with_reply(
+        &self,
+        subject: &str,
+        reply_mailbox: Option<String>,
+        headers: Option<&HeaderMap>,
+        payload: Vec<u8>,
+    ) -> ClientResult<()>;
+
+    /// Subscribes to `subject`, which may contain NATS style `*` and `>` wildcards. Dropping the
+    /// returned stream ends the subscription.
+    async fn subscribe(&self, subject: &str) -> ClientResult<BoxStream<'static, RawMessage>>;
+
+    /// Returns an error if the transport can't reach its broker within `timeout`.
+    async fn check_health(&self, timeout: Duration) -> ClientResult<()>;
+}
+
+/// A [`Transport`] over [NATS](https://nats.io).
+#[derive(Clone, Debug)]
+pub struct NatsTransport {
+    nats: NatsClient,
+}
+
+impl NatsTransport {
+    pub fn new(nats: NatsClient) -> Self {
+        Self { nats }
+    }
+}
+
+#[async_trait]
+impl Transport for NatsTransport {
+    fn subject_prefix(&self) -> Option<&str> {
+        self.nats.metadata().subject_prefix()
+    }
+
+    fn new_inbox(&self) -> String {
+        self.nats.new_inbox()
+    }
+
+    async fn publish_with_reply(
+        &self,
+        subject: &str,
+        reply_mailbox: Option<String>,
+        headers: Option<&HeaderMap>,
+        payload: Vec<u8>,
+    ) -> ClientResult<()> {
+        self.nats
+            .publish_with_reply_or_headers(subject, reply_mailbox, headers, payload)
+            .await?;
+        Ok(())
+    }
+
+    async fn subscribe(&self, subject: &str) -> ClientResult<BoxStream<'static, RawMessage>> {
+        let subscription = self.nats.subscribe(subject).await?;
+        Ok(subscription
+            .filter_map(|message| {
+                future::ready(match message {
+                    Ok(message) => Some(RawMessage::from(message)),
+                    Err(err) => {
+                        warn!(error = ?err, "nats subscription received an error");
+                        None
+                    }
+                })
+            })
+            .boxed())
+    }
+
+    async fn check_health(&self, timeout: Duration) -> ClientResult<()> {
+        self.nats.flush_timeout(timeout).await?;
+        Ok(())
+    }
+}
+
+/// A [`Transport`] that delivers messages to subscribers in the same process, with no broker in
+/// between. Clones share their subscriptions, so a client and an in-process responder (such as a
+/// test double for veritech) can talk through clones of the same transport.
+///
+/// Every matching subscriber receives every message, as there are no queue groups. Like NATS, a
+/// request with a reply mailbox that no one is subscribed to is answered with an empty message on
+/// the reply mailbox.
+#[derive(Clone, Debug, Default)]
+pub struct LoopbackTransport {
+    subject_prefix: Option<String>,
+    subscribers: Arc<Mutex<Vec<LoopbackSubscriber>>>,
+    next_inbox: Arc<AtomicU64>,
+}
+
+#[derive(Debug)]
+struct LoopbackSubscriber {
+    subject: String,
+    messages_tx: mpsc::UnboundedSender<RawMessage>,
+}
+
+impl LoopbackTransport {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the prefix reported by [`Transport::subject_prefix`], which clients add to the
+    /// subjects they publish requests on.
+    pub fn with_subject_prefix(mut self, subject_prefix: impl Into<String>) -> Self {
+        self.subject_prefix = Some(subject_prefix.into());
+        self
+    }
+
+    /// Returns the subject of every subscription whose stream has not been dropped yet.
+    pub fn subscribed_subjects(&self) -> Vec<String> {
+        let mut subscribers = self
+            .subscribers
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner);
+        subscribers.retain(|subscriber| !subscriber.messages_tx.is_closed());
+        subscribers
+            .iter()
+            .map(|subscriber| subscriber.subject.clone())
+            .collect()
+    }
+}
+
+#[async_trait]
+impl Transport for LoopbackTransport {
+    fn subject_prefix(&self) -> Option<&str> {
+        self.subject_prefix.as_deref()
+    }
+
+    fn new_inbox(&self) -> String {
+        format!(
+            "_INBOX.loopback.{}",
+            self.next_inbox.fetch_add(1, Ordering::Relaxed)
+        )
+    }
+
+    async fn publish_with_reply(
+        &self,
+        subject: &str,
+        reply_mailbox: Option<String>,
+        headers: Option<&HeaderMap>,
+        payload: Vec<u8>,
+    ) -> ClientResult<()> {
+        let mut subscribers = self
+            .subscribers
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner);
+        let delivered = deliver(
+            &mut subscribers,
+            RawMessage {
+                subject: subject.to_string(),
+                payload,
+                reply_mailbox: reply_mailbox.clone(),
+                headers: headers.cloned(),
+            },
+        );
+        if let (false, Some(reply_mailbox)) = (delivered, reply_mailbox) {
+            trace!(
+                subject,
+                "no loopback subscribers for request, replying with no responders"
+            );
+            deliver(
+                &mut subscribers,
+                RawMessage {
+                    subject: reply_mailbox,
+                    payload: vec![],
+                    reply_mailbox: None,
+                    headers: None,
+                },
+            );
+        }
+        Ok(())
+    }
+
+    async fn subscribe(&self, subject: &str) -> ClientResult<BoxStream<'static, RawMessage>> {
+        let (messages_tx, messages_rx) = mpsc::unbounded();
+        self.subscribers
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .push(LoopbackSubscriber {
+                subject: subject.to_string(),
+                messages_tx,
+            });
+        Ok(messages_rx.boxed())
+    }
+
+    async fn check_health(&self, _timeout: Duration) -> ClientResult<()> {
+        Ok(())
+    }
+}
+
+/// Sends a message to every subscriber whose subject matches, dropping subscribers whose stream
+/// has been dropped. Returns whether any subscriber received the message.
+fn deliver(subscribers: &mut Vec<LoopbackSubscriber>, message: RawMessage) -> bool {
+    subscribers.retain(|subscriber| !subscriber.messages_tx.is_closed());
+    let mut delivered = false;
+    for subscriber in subscribers
+        .iter()
+        .filter(|subscriber| subject_matches(&subscriber.subject, &message.subject))
+    {
+        delivered |= subscriber
+            .messages_tx
+            .unbounded_send(message.clone())
+            .is_ok();
+    }
+    delivered
+}
+
+/// Matches a subject against a subscription subject, where `*` matches any one token and a
+/// trailing `>` matches one or more tokens.
+fn subject_matches(pattern: &str, subject: &str) -> bool {
+    let mut subject_tokens = subject.split('.');
+    for token in pattern.split('.') {
+        match (token, subject_tokens.next()) {
+            (">", Some(_)) => return true,
+            ("*", Some(_)) => {}
+            (token, Some(subject_token)) if token == subject_token => {}
+            _ => return false,
+        }
+    }
+    subject_tokens.next().is_none()
+}
@@ -1,19 +1,35 @@
-use std::env;
+use std::{
+    env,
+    path::PathBuf,
+    sync::Arc,
+    time::{Duration, Instant},
+};
 
 use base64::{engine::general_purpose, Engine};
 use cyclone_core::{
-    ComponentKind, ComponentView, FunctionResult, ResolverFunctionComponent,
-    ResolverFunctionRequest, ResolverFunctionResponseType, SchemaVariantDefinitionRequest,
-    ValidationRequest,
+    BeforeFunction, ComponentKind, ComponentView, FunctionLanguage, FunctionResult, OutputStream,
+    ResolverFunctionComponent, ResolverFunctionRequest, ResolverFunctionResponseType,
+    ResolverFunctionResultSuccess, SchemaVariantDefinitionRequest, ValidationRequest,
 };
-use si_data_nats::{NatsClient, NatsConfig};
+use futures::StreamExt;
+use si_data_nats::{HeaderMap, NatsClient, NatsConfig};
 use test_log::test;
 use tokio::{sync::mpsc, task::JoinHandle};
 use tracing::info;
 use uuid::Uuid;
-use veritech_client::Client;
+use veritech_client::{
+    schemas, validate_payload, BatchRequest, Client, ClientError, ConnectionState, DeadLetter,
+    DeadLetterQueue, EncryptionKey, FunctionErrorKind, InMemoryOutputStore, InMemoryResultCache,
+    LoopbackTransport, ReconnectPolicy, RecordReplay, SimulatedResults, Transport, VeritechResult,
+    PAYLOAD_SCHEMA_VERSION,
+};
+use veritech_core::{
+    nats_resolver_function_subject, reply_mailbox_for_output, reply_mailbox_for_result,
+    shard_for_workspace, FINAL_MESSAGE_HEADER_KEY,
+};
 use veritech_server::{
-    Config, CycloneSpec, Instance, LocalUdsInstance, Server, ServerError, StandardConfig,
+    Config, CycloneSpec, Instance, LocalUdsInstance, Server, ServerError, ShardConfig,
+    StandardConfig,
 };
 
 fn nats_config(subject_prefix: String) -> NatsConfig {
@@ -36,25 +52,43 @@ fn nats_prefix() -> String {
     Uuid::new_v4().as_simple().to_string()
 }
 
-async fn veritech_server_for_uds_cyclone(subject_prefix: String) -> Server {
+fn dev_config_file() -> veritech_server::ConfigFile {
     let mut config_file = veritech_server::ConfigFile::default_local_uds();
     veritech_server::detect_and_configure_development(&mut config_file)
         .expect("failed to determine test configuration");
+    config_file
+}
+
+fn uds_cyclone_spec() -> CycloneSpec {
+    let config_file = dev_config_file();
 
-    let cyclone_spec = CycloneSpec::LocalUds(
+    CycloneSpec::LocalUds(
         LocalUdsInstance::spec()
-            .try_cyclone_cmd_path(config_file.cyclone.cyclone_cmd_path())
+            .try_cyclone_cmd_path(
+                config_file
+                    .cyclone
+                    .cyclone_cmd_path()
+                    .expect("local config has a cyclone program"),
+            )
             .expect("failed to setup cyclone_cmd_path")
             .cyclone_decryption_key_path(config_file.cyclone.cyclone_decryption_key_path())
-            .try_lang_server_cmd_path(config_file.cyclone.lang_server_cmd_path())
+            .try_lang_server_cmd_path(
+                config_file
+                    .cyclone
+                    .lang_server_cmd_path()
+                    .expect("local config has a lang server program"),
+            )
             .expect("failed to setup lang_js_cmd_path")
             .all_endpoints()
             .build()
             .expect("failed to build cyclone spec"),
-    );
+    )
+}
+
+async fn veritech_server_for_uds_cyclone(subject_prefix: String) -> Server {
     let config = Config::builder()
         .nats(nats_config(subject_prefix.clone()))
-        .cyclone_spec(cyclone_spec)
+        .cyclone_spec(uds_cyclone_spec())
         .build()
         .expect("failed to build spec");
     Server::for_cyclone_uds(config)
@@ -105,6 +139,8 @@ async fn executes_simple_resolver_function() {
         code_base64: base64_encode(
             "function numberOfInputs(input) { return Object.keys(input)?.length ?? 0; }",
         ),
+        lang: FunctionLanguage::JavaScript,
+        before: vec![],
     };
 
     let result = client
@@ -126,165 +162,99 @@ async fn executes_simple_resolver_function() {
 
 #[allow(clippy::disallowed_methods)] // `$RUST_LOG` is checked for in macro
 #[test(tokio::test)]
-async fn type_checks_resolve_function() {
+async fn reports_server_stats() {
     let prefix = nats_prefix();
     run_veritech_server_for_uds_cyclone(prefix.clone()).await;
     let client = client(prefix).await;
 
-    for response_type in [
-        ResolverFunctionResponseType::Array,
-        ResolverFunctionResponseType::Integer,
-        ResolverFunctionResponseType::Boolean,
-        ResolverFunctionResponseType::String,
-        ResolverFunctionResponseType::Map,
-        ResolverFunctionResponseType::Object,
-    ] {
-        let value = match response_type {
-            ResolverFunctionResponseType::Array => serde_json::json!({ "value": [1, 2, 3, 4] }),
-            ResolverFunctionResponseType::Integer => serde_json::json!({ "value": 31337 }),
-            ResolverFunctionResponseType::Boolean => serde_json::json!({ "value": true }),
-            ResolverFunctionResponseType::String => {
-                serde_json::json!({ "value": "a string is a sequence of characters" })
-            }
-            ResolverFunctionResponseType::Map | ResolverFunctionResponseType::Object => {
-                serde_json::json!({ "value": { "an_object": "has keys" } })
-            }
-            _ => serde_json::json!({ "value": null }),
-        };
-        // Not going to check output here--we aren't emitting anything
-        let (tx, mut rx) = mpsc::channel(64);
-        tokio::spawn(async move {
-            while let Some(output) = rx.recv().await {
-                info!("output: {:?}", output)
-            }
-        });
-
-        let request = ResolverFunctionRequest {
-            execution_id: "1234".to_string(),
-            handler: "returnInputValue".to_string(),
-            component: ResolverFunctionComponent {
-                data: ComponentView {
-                    properties: value.clone(),
-                    kind: ComponentKind::Standard,
-                },
-                parents: vec![],
+    let request = ResolverFunctionRequest {
+        execution_id: "stats".to_string(),
+        handler: "numberOfInputs".to_string(),
+        component: ResolverFunctionComponent {
+            data: ComponentView {
+                properties: serde_json::json!({ "foo": "bar" }),
+                kind: ComponentKind::Standard,
             },
-            response_type,
-            code_base64: base64_encode("function returnInputValue(input) { return input.value; }"),
-        };
+            parents: vec![],
+        },
+        response_type: ResolverFunctionResponseType::Integer,
+        code_base64: base64_encode(
+            "function numberOfInputs(input) { return Object.keys(input)?.length ?? 0; }",
+        ),
+        lang: FunctionLanguage::JavaScript,
+        before: vec![],
+    };
+    let (tx, _rx) = mpsc::channel(64);
+    client
+        .execute_resolver_function(tx, &request)
+        .await
+        .expect("failed to execute resolver function");
 
-        let result = client
-            .execute_resolver_function(tx, &request)
+    // The server counts an execution once its result is published, which can be just after the
+    // client receives it
+    let deadline = Instant::now() + Duration::from_secs(5);
+    let stats = loop {
+        let stats = client
+            .server_stats()
             .await
-            .expect("failed to execute resolver function");
-
-        match result {
-            FunctionResult::Success(success) => {
-                assert_eq!(success.execution_id, "1234");
-                if let serde_json::Value::Object(inner) = value {
-                    let value = inner.get("value").expect("value should exist").clone();
-                    assert_eq!(value, success.data);
-                } else {
-                    panic!("no value in return data :(")
-                }
-            }
-            FunctionResult::Failure(_) => {
-                panic!("should have failed :(");
-            }
+            .expect("failed to get server stats");
+        let recent_executions = stats
+            .kinds
+            .get("resolverFunction")
+            .map_or(0, |kind| kind.recent_executions);
+        if recent_executions > 0 || Instant::now() > deadline {
+            break stats;
         }
-    }
-
-    for response_type in [
-        ResolverFunctionResponseType::Array,
-        ResolverFunctionResponseType::Integer,
-        ResolverFunctionResponseType::Boolean,
-        ResolverFunctionResponseType::String,
-        ResolverFunctionResponseType::Map,
-        ResolverFunctionResponseType::Object,
-    ] {
-        let value = match response_type {
-            ResolverFunctionResponseType::Array => serde_json::json!({ "value": "foo"}),
-            ResolverFunctionResponseType::Integer => serde_json::json!({ "value": "a string" }),
-            ResolverFunctionResponseType::Boolean => serde_json::json!({ "value": "a string" }),
-            ResolverFunctionResponseType::String => serde_json::json!({ "value": 12345 }),
-            ResolverFunctionResponseType::Map | ResolverFunctionResponseType::Object => {
-                serde_json::json!({ "value": ["an_object", "has keys" ] })
-            }
-            _ => serde_json::json!({ "value": null }),
-        };
-        // Not going to check output here--we aren't emitting anything
-        let (tx, mut rx) = mpsc::channel(64);
-        tokio::spawn(async move {
-            while let Some(output) = rx.recv().await {
-                info!("output: {:?}", output)
-            }
-        });
-
-        let request = ResolverFunctionRequest {
-            execution_id: "1234".to_string(),
-            handler: "returnInputValue".to_string(),
-            component: ResolverFunctionComponent {
-                data: ComponentView {
-                    properties: value,
-                    kind: ComponentKind::Standard,
-                },
-                parents: vec![],
-            },
-            response_type: response_type.clone(),
-            code_base64: base64_encode("function returnInputValue(input) { return input.value; }"),
-        };
-
-        let result = client
-            .execute_resolver_function(tx, &request)
-            .await
-            .expect("failed to execute resolver function");
+        tokio::time::sleep(Duration::from_millis(50)).await;
+    };
 
-        match result {
-            FunctionResult::Success(success) => {
-                dbg!(success, response_type);
-                panic!("should have failed :(");
-            }
-            FunctionResult::Failure(failure) => {
-                assert_eq!(failure.error.kind, "InvalidReturnType");
-                assert_eq!(failure.execution_id, "1234");
-            }
-        }
-    }
+    let resolver_function = stats
+        .kinds
+        .get("resolverFunction")
+        .expect("resolver function executions should be counted");
+    assert_eq!(resolver_function.recent_executions, 1);
+    assert_eq!(resolver_function.recent_errors, 0);
+    assert_eq!(resolver_function.queued, 0);
+    assert_eq!(resolver_function.running, 0);
+    assert!(stats.pool.max_size > 0);
+    assert!(!stats.is_saturated());
 }
 
 #[allow(clippy::disallowed_methods)] // `$RUST_LOG` is checked for in macro
 #[test(tokio::test)]
-async fn executes_simple_validation() {
+async fn executes_before_functions_ahead_of_resolver_function() {
     let prefix = nats_prefix();
     run_veritech_server_for_uds_cyclone(prefix.clone()).await;
     let client = client(prefix).await;
 
-    // Not going to check output here--we aren't emitting anything
-    let (tx, mut rx) = mpsc::channel(64);
-    tokio::spawn(async move {
-        while let Some(output) = rx.recv().await {
-            info!("output: {:?}", output)
-        }
-    });
-
-    let request = ValidationRequest {
-        execution_id: "31337".to_string(),
-        handler: "isThirtyThree".to_string(),
-        value: 33.into(),
-        code_base64: base64_encode(
-            "function isThirtyThree(value) { return { valid: value === 33 }; };",
-        ),
+    let (tx, _rx) = mpsc::channel(64);
+    let request = ResolverFunctionRequest {
+        execution_id: "1234".to_string(),
+        handler: "token".to_string(),
+        component: ResolverFunctionComponent {
+            data: ComponentView::default(),
+            parents: vec![],
+        },
+        response_type: ResolverFunctionResponseType::String,
+        code_base64: base64_encode("function token() { return requestStorage.getEnv('TOKEN'); }"),
+        lang: FunctionLanguage::JavaScript,
+        before: vec![BeforeFunction {
+            handler: "auth".to_string(),
+            code_base64: base64_encode(
+                "function auth(arg) { requestStorage.setEnv('TOKEN', arg.token); }",
+            ),
+            arg: serde_json::json!({ "token": "ufo" }),
+        }],
     };
 
     let result = client
-        .execute_validation(tx, &request)
+        .execute_resolver_function(tx, &request)
         .await
-        .expect("failed to execute validation");
+        .expect("failed to execute resolver function");
 
     match result {
         FunctionResult::Success(success) => {
-            assert_eq!(success.execution_id, "31337");
-            assert!(success.valid);
+            assert_eq!(success.data, serde_json::json!("ufo"));
         }
         FunctionResult::Failure(failure) => {
             panic!("function did not succeed and should have: {failure:?}")
@@ -294,54 +264,1477 @@ async fn executes_simple_validation() {
 
 #[allow(clippy::disallowed_methods)] // `$RUST_LOG` is checked for in macro
 #[test(tokio::test)]
-async fn executes_simple_schema_variant_definition() {
+async fn executes_resolver_function_with_connection_checks() {
     let prefix = nats_prefix();
     run_veritech_server_for_uds_cyclone(prefix.clone()).await;
-    let client = client(prefix).await;
+    let client = client(prefix).await.with_reconnect(ReconnectPolicy {
+        check_interval: Duration::from_millis(100),
+        ..Default::default()
+    });
+    let mut connection_state = client
+        .connection_state()
+        .expect("client should check its connection");
 
-    // Not going to check output here--we aren't emitting anything
-    let (tx, mut rx) = mpsc::channel(64);
-    tokio::spawn(async move {
-        while let Some(output) = rx.recv().await {
-            info!("output: {:?}", output)
+    let (tx, _rx) = mpsc::channel(64);
+    let request = ResolverFunctionRequest {
+        execution_id: "checked".to_string(),
+        handler: "one".to_string(),
+        component: ResolverFunctionComponent {
+            data: ComponentView {
+                properties: serde_json::json!({}),
+                kind: ComponentKind::Standard,
+            },
+            parents: vec![],
+        },
+        response_type: ResolverFunctionResponseType::Integer,
+        code_base64: base64_encode("function one(input) { return 1; }"),
+        lang: FunctionLanguage::JavaScript,
+        before: vec![],
+    };
+
+    let result = client
+        .execute_resolver_function(tx, &request)
+        .await
+        .expect("failed to execute resolver function");
+
+    match result {
+        FunctionResult::Success(success) => assert_eq!(success.data, serde_json::json!(1)),
+        FunctionResult::Failure(failure) => {
+            panic!("function did not succeed and should have: {failure:?}")
         }
-    });
+    }
+    // A few checks have run by now, and all of them reached the server
+    tokio::time::sleep(Duration::from_millis(300)).await;
+    assert_eq!(
+        *connection_state.borrow_and_update(),
+        ConnectionState::Connected
+    );
+}
 
-    let request = SchemaVariantDefinitionRequest {
-        execution_id: "8badf00d".to_string(),
-        handler: "asset".to_string(),
-        code_base64: base64_encode(
-            "function asset() {
-                    return {
-                        props: [{kind: 'string', name: 'string_prop'}],
-                        inputSockets: [], outputSockets: []
-                    };
-                }",
-        ),
+#[allow(clippy::disallowed_methods)] // `$RUST_LOG` is checked for in macro
+#[test(tokio::test)]
+async fn executes_encrypted_resolver_function() {
+    let prefix = nats_prefix();
+    // The public half of the development keypair sits next to its private half
+    let decryption_key_path =
+        PathBuf::from(dev_config_file().cyclone.cyclone_decryption_key_path());
+    let encryption_key =
+        EncryptionKey::load(decryption_key_path.with_file_name("dev.encryption.key"))
+            .await
+            .expect("failed to load encryption key");
+    let config = Config::builder()
+        .nats(nats_config(prefix.clone()))
+        .cyclone_spec(uds_cyclone_spec())
+        .decryption_key_path(Some(decryption_key_path))
+        .build()
+        .expect("failed to build spec");
+    let server = Server::for_cyclone_uds(config)
+        .await
+        .expect("failed to create server");
+    tokio::spawn(server.run());
+    let client = client(prefix).await.with_payload_encryption(encryption_key);
+
+    let (tx, _rx) = mpsc::channel(64);
+    let request = ResolverFunctionRequest {
+        execution_id: "sealed".to_string(),
+        handler: "secretLength".to_string(),
+        component: ResolverFunctionComponent {
+            data: ComponentView {
+                properties: serde_json::json!({ "secret": "hunter2" }),
+                kind: ComponentKind::Standard,
+            },
+            parents: vec![],
+        },
+        response_type: ResolverFunctionResponseType::Integer,
+        code_base64: base64_encode("function secretLength(input) { return input.secret.length; }"),
+        lang: FunctionLanguage::JavaScript,
+        before: vec![],
     };
 
     let result = client
-        .execute_schema_variant_definition(tx, &request)
+        .execute_resolver_function(tx, &request)
         .await
-        .expect("failed to execute schema variant definition");
+        .expect("failed to execute resolver function");
 
     match result {
         FunctionResult::Success(success) => {
-            assert_eq!(success.execution_id, "8badf00d");
-            assert_eq!(
-                success.definition,
-                serde_json::json!({
-                    "props": [{
-                        "kind": "string",
-                        "name": "string_prop",
-                    }],
-                    "inputSockets": [],
-                    "outputSockets": []
-                })
-            );
+            assert_eq!(success.execution_id, "sealed");
+            assert_eq!(success.data, serde_json::json!(7));
         }
         FunctionResult::Failure(failure) => {
             panic!("function did not succeed and should have: {failure:?}")
         }
     }
 }
+
+#[allow(clippy::disallowed_methods)] // `$RUST_LOG` is checked for in macro
+#[test(tokio::test)]
+async fn executes_resolver_function_with_compressed_payloads() {
+    let prefix = nats_prefix();
+    run_veritech_server_for_uds_cyclone(prefix.clone()).await;
+    // Both the request and the result carry the blob, so both are over the threshold
+    let client = client(prefix).await.with_compression(1024);
+
+    let (tx, _rx) = mpsc::channel(64);
+    let blob = "generated code ".repeat(1000);
+    let request = ResolverFunctionRequest {
+        execution_id: "compressed".to_string(),
+        handler: "echo".to_string(),
+        component: ResolverFunctionComponent {
+            data: ComponentView {
+                properties: serde_json::json!({ "blob": blob }),
+                kind: ComponentKind::Standard,
+            },
+            parents: vec![],
+        },
+        response_type: ResolverFunctionResponseType::String,
+        code_base64: base64_encode("function echo(input) { return input.blob; }"),
+        lang: FunctionLanguage::JavaScript,
+        before: vec![],
+    };
+
+    let result = client
+        .execute_resolver_function(tx, &request)
+        .await
+        .expect("failed to execute resolver function");
+
+    match result {
+        FunctionResult::Success(success) => {
+            assert_eq!(success.execution_id, "compressed");
+            assert_eq!(success.data, serde_json::json!(blob));
+        }
+        FunctionResult::Failure(failure) => {
+            panic!("function did not succeed and should have: {failure:?}")
+        }
+    }
+}
+
+#[allow(clippy::disallowed_methods)] // `$RUST_LOG` is checked for in macro
+#[test(tokio::test)]
+async fn reassembles_chunked_resolver_function_result() {
+    let prefix = nats_prefix();
+    run_veritech_server_for_uds_cyclone(prefix.clone()).await;
+    let client = client(prefix).await.with_chunked_results(1024);
+
+    let (tx, _rx) = mpsc::channel(64);
+    let blob = "generated code ".repeat(1000);
+    let request = ResolverFunctionRequest {
+        execution_id: "chunked".to_string(),
+        handler: "generate".to_string(),
+        component: ResolverFunctionComponent {
+            data: ComponentView {
+                properties: serde_json::json!({}),
+                kind: ComponentKind::Standard,
+            },
+            parents: vec![],
+        },
+        response_type: ResolverFunctionResponseType::String,
+        code_base64: base64_encode(
+            "function generate() { return \"generated code \".repeat(1000); }",
+        ),
+        lang: FunctionLanguage::JavaScript,
+        before: vec![],
+    };
+
+    let (mut progress_rx, result) = client.execute_with_progress(tx, &request);
+    let result = result.await.expect("failed to execute resolver function");
+
+    match result {
+        FunctionResult::Success(success) => {
+            assert_eq!(success.execution_id, "chunked");
+            assert_eq!(success.data, serde_json::json!(blob));
+        }
+        FunctionResult::Failure(failure) => {
+            panic!("function did not succeed and should have: {failure:?}")
+        }
+    }
+
+    let mut receiving_reports = 0;
+    while let Some(progress) = progress_rx.recv().await {
+        if progress.step.as_deref() == Some("receiving result") {
+            assert_eq!(progress.execution_id, "chunked");
+            receiving_reports += 1;
+        }
+    }
+    assert!(
+        receiving_reports > 0,
+        "no progress reported while receiving the result"
+    );
+}
+
+#[allow(clippy::disallowed_methods)] // `$RUST_LOG` is checked for in macro
+#[test(tokio::test)]
+async fn answers_duplicate_request_with_recorded_result() {
+    let prefix = nats_prefix();
+    run_veritech_server_for_uds_cyclone(prefix.clone()).await;
+    let client = client(prefix).await;
+
+    let request = ResolverFunctionRequest {
+        execution_id: "duplicated".to_string(),
+        handler: "roll".to_string(),
+        component: ResolverFunctionComponent {
+            data: ComponentView {
+                properties: serde_json::json!({}),
+                kind: ComponentKind::Standard,
+            },
+            parents: vec![],
+        },
+        response_type: ResolverFunctionResponseType::String,
+        code_base64: base64_encode("function roll() { return String(Math.random()); }"),
+        lang: FunctionLanguage::JavaScript,
+        before: vec![],
+    };
+
+    let mut rolls = Vec::new();
+    for _ in 0..2 {
+        let (tx, _rx) = mpsc::channel(64);
+        match client
+            .execute_resolver_function(tx, &request)
+            .await
+            .expect("failed to execute resolver function")
+        {
+            FunctionResult::Success(success) => rolls.push(success.data),
+            FunctionResult::Failure(failure) => {
+                panic!("function did not succeed and should have: {failure:?}")
+            }
+        }
+    }
+
+    // The function only ran once, so both requests were answered with the same roll
+    assert_eq!(rolls[0], rolls[1]);
+}
+
+#[allow(clippy::disallowed_methods)] // `$RUST_LOG` is checked for in macro
+#[test(tokio::test)]
+async fn dead_letters_undecodable_request() {
+    let prefix = nats_prefix();
+    run_veritech_server_for_uds_cyclone(prefix.clone()).await;
+    let client = client(prefix.clone()).await;
+    let dead_letters = client
+        .dead_letter_queue()
+        .await
+        .expect("failed to subscribe for dead letters");
+
+    let nats = nats(prefix.clone()).await;
+    let subject = nats_resolver_function_subject(Some(&prefix));
+    let reply_mailbox = nats.new_inbox();
+    nats.publish_with_reply_or_headers(
+        &subject,
+        Some(reply_mailbox.clone()),
+        None,
+        b"not a request".to_vec(),
+    )
+    .await
+    .expect("failed to publish request");
+
+    let dead_letter = wait_for_dead_letters(&dead_letters, 1).await.remove(0);
+    assert_eq!(dead_letter.kind, "resolverfunction");
+    assert_eq!(dead_letter.subject, subject);
+    assert_eq!(dead_letter.reply_mailbox, Some(reply_mailbox));
+    assert_eq!(dead_letter.payload, b"not a request");
+    assert!(!dead_letter.error.is_empty());
+
+    // The replayed request still can't be decoded, so it comes straight back
+    dead_letters
+        .replay(&dead_letter)
+        .await
+        .expect("failed to replay dead letter");
+    let replayed = wait_for_dead_letters(&dead_letters, 1).await.remove(0);
+    assert_ne!(replayed.id, dead_letter.id);
+    assert_eq!(replayed.payload, dead_letter.payload);
+}
+
+async fn wait_for_dead_letters(dead_letters: &DeadLetterQueue, count: usize) -> Vec<DeadLetter> {
+    let started = Instant::now();
+    loop {
+        let listed = dead_letters.list();
+        if listed.len() >= count {
+            return listed;
+        }
+        assert!(
+            started.elapsed() < Duration::from_secs(10),
+            "timed out waiting for dead letters"
+        );
+        tokio::time::sleep(Duration::from_millis(50)).await;
+    }
+}
+
+#[allow(clippy::disallowed_methods)] // `$RUST_LOG` is checked for in macro
+#[test(tokio::test)]
+async fn reports_resolver_function_progress() {
+    let prefix = nats_prefix();
+    run_veritech_server_for_uds_cyclone(prefix.clone()).await;
+    let client = client(prefix).await;
+
+    let (tx, _rx) = mpsc::channel(64);
+    let request = ResolverFunctionRequest {
+        execution_id: "progressing".to_string(),
+        handler: "sync".to_string(),
+        component: ResolverFunctionComponent {
+            data: ComponentView {
+                properties: serde_json::json!({}),
+                kind: ComponentKind::Standard,
+            },
+            parents: vec![],
+        },
+        response_type: ResolverFunctionResponseType::Integer,
+        code_base64: base64_encode(
+            "function sync(input) { \
+                for (let i = 1; i <= 3; i++) { progress.report(i, 3, `resource ${i}`); } \
+                return 3; \
+            }",
+        ),
+        lang: FunctionLanguage::JavaScript,
+        before: vec![],
+    };
+
+    let (mut progress_rx, result) = client.execute_with_progress(tx, &request);
+    match result.await.expect("failed to execute resolver function") {
+        FunctionResult::Success(success) => assert_eq!(success.data, serde_json::json!(3)),
+        FunctionResult::Failure(failure) => {
+            panic!("function did not succeed and should have: {failure:?}")
+        }
+    }
+
+    // The receiver closes once the server has finalized the execution's progress
+    let mut reports = Vec::new();
+    while let Some(progress) = progress_rx.recv().await {
+        reports.push(progress);
+    }
+    assert_eq!(
+        reports
+            .iter()
+            .map(|progress| (progress.current, progress.total, progress.step.as_deref()))
+            .collect::<Vec<_>>(),
+        vec![
+            (1, Some(3), Some("resource 1")),
+            (2, Some(3), Some("resource 2")),
+            (3, Some(3), Some("resource 3")),
+        ]
+    );
+    assert_eq!(
+        reports.last().and_then(|progress| progress.percentage()),
+        Some(100.0)
+    );
+}
+
+#[allow(clippy::disallowed_methods)] // `$RUST_LOG` is checked for in macro
+#[test(tokio::test)]
+async fn stores_resolver_function_output() {
+    let prefix = nats_prefix();
+    run_veritech_server_for_uds_cyclone(prefix.clone()).await;
+    let client = client(prefix)
+        .await
+        .with_output_store(Arc::new(InMemoryOutputStore::default()));
+
+    let (tx, mut rx) = mpsc::channel(64);
+    let request = ResolverFunctionRequest {
+        execution_id: "4321".to_string(),
+        handler: "chatty".to_string(),
+        component: ResolverFunctionComponent {
+            data: ComponentView {
+                properties: serde_json::json!({}),
+                kind: ComponentKind::Standard,
+            },
+            parents: vec![],
+        },
+        response_type: ResolverFunctionResponseType::Boolean,
+        code_base64: base64_encode(
+            "function chatty(input) { console.log('first'); console.warn('second'); return true; }",
+        ),
+        lang: FunctionLanguage::JavaScript,
+        before: vec![],
+    };
+
+    let result = client
+        .execute_resolver_function(tx, &request)
+        .await
+        .expect("failed to execute resolver function");
+    assert!(matches!(result, FunctionResult::Success(_)));
+
+    // The channel closes once all of the output has been forwarded (and stored)
+    let mut received = Vec::new();
+    while let Some(output) = rx.recv().await {
+        received.push(output);
+    }
+
+    let stored = client
+        .stored_output("4321")
+        .expect("no output stored for execution");
+    assert_eq!(received, stored);
+    assert_eq!(
+        vec!["first", "second"],
+        stored
+            .iter()
+            .map(|output| output.message.as_str())
+            .collect::<Vec<_>>()
+    );
+}
+
+#[allow(clippy::disallowed_methods)] // `$RUST_LOG` is checked for in macro
+#[test(tokio::test)]
+async fn reports_dropped_output_lines() {
+    let prefix = nats_prefix();
+    run_veritech_server_for_uds_cyclone(prefix.clone()).await;
+    // Lines that don't fit in the channel are dropped by default
+    let client = client(prefix).await;
+    let handle = client.new_execution_handle();
+
+    // Nothing reads from the channel until the function has finished, so most lines won't fit
+    let (tx, mut rx) = mpsc::channel(1);
+    let request = ResolverFunctionRequest {
+        execution_id: "noisy".to_string(),
+        handler: "noisy".to_string(),
+        component: ResolverFunctionComponent {
+            data: ComponentView {
+                properties: serde_json::json!({}),
+                kind: ComponentKind::Standard,
+            },
+            parents: vec![],
+        },
+        response_type: ResolverFunctionResponseType::Boolean,
+        code_base64: base64_encode(
+            "function noisy(input) { for (let i = 0; i < 50; i++) { console.log(`line ${i}`); } return true; }",
+        ),
+        lang: FunctionLanguage::JavaScript,
+        before: vec![],
+    };
+
+    let result = client
+        .execute_resolver_function_with_handle(&handle, tx, &request)
+        .await
+        .expect("failed to execute resolver function");
+    assert!(matches!(result, FunctionResult::Success(_)));
+
+    let mut received = 0;
+    while let Some(output) = rx.recv().await {
+        assert_ne!("warn", output.level);
+        received += 1;
+    }
+    let dropped = handle.dropped_output_lines();
+    assert!(dropped > 0);
+    assert_eq!(50, received + dropped);
+}
+
+#[allow(clippy::disallowed_methods)] // `$RUST_LOG` is checked for in macro
+#[test(tokio::test)]
+async fn type_checks_resolve_function() {
+    let prefix = nats_prefix();
+    run_veritech_server_for_uds_cyclone(prefix.clone()).await;
+    let client = client(prefix).await;
+
+    for response_type in [
+        ResolverFunctionResponseType::Array,
+        ResolverFunctionResponseType::Integer,
+        ResolverFunctionResponseType::Boolean,
+        ResolverFunctionResponseType::String,
+        ResolverFunctionResponseType::Map,
+        ResolverFunctionResponseType::Object,
+    ] {
+        let value = match response_type {
+            ResolverFunctionResponseType::Array => serde_json::json!({ "value": [1, 2, 3, 4] }),
+            ResolverFunctionResponseType::Integer => serde_json::json!({ "value": 31337 }),
+            ResolverFunctionResponseType::Boolean => serde_json::json!({ "value": true }),
+            ResolverFunctionResponseType::String => {
+                serde_json::json!({ "value": "a string is a sequence of characters" })
+            }
+            ResolverFunctionResponseType::Map | ResolverFunctionResponseType::Object => {
+                serde_json::json!({ "value": { "an_object": "has keys" } })
+            }
+            _ => serde_json::json!({ "value": null }),
+        };
+        // Not going to check output here--we aren't emitting anything
+        let (tx, mut rx) = mpsc::channel(64);
+        tokio::spawn(async move {
+            while let Some(output) = rx.recv().await {
+                info!("output: {:?}", output)
+            }
+        });
+
+        // Each request needs its own execution id, or the server would answer it with the
+        // recorded result of the first one
+        let execution_id = format!("{response_type:?}");
+        let request = ResolverFunctionRequest {
+            execution_id: execution_id.clone(),
+            handler: "returnInputValue".to_string(),
+            component: ResolverFunctionComponent {
+                data: ComponentView {
+                    properties: value.clone(),
+                    kind: ComponentKind::Standard,
+                },
+                parents: vec![],
+            },
+            response_type,
+            code_base64: base64_encode("function returnInputValue(input) { return input.value; }"),
+            lang: FunctionLanguage::JavaScript,
+            before: vec![],
+        };
+
+        let result = client
+            .execute_resolver_function(tx, &request)
+            .await
+            .expect("failed to execute resolver function");
+
+        match result {
+            FunctionResult::Success(success) => {
+                assert_eq!(success.execution_id, execution_id);
+                if let serde_json::Value::Object(inner) = value {
+                    let value = inner.get("value").expect("value should exist").clone();
+                    assert_eq!(value, success.data);
+                } else {
+                    panic!("no value in return data :(")
+                }
+            }
+            FunctionResult::Failure(_) => {
+                panic!("should have failed :(");
+            }
+        }
+    }
+
+    for response_type in [
+        ResolverFunctionResponseType::Array,
+        ResolverFunctionResponseType::Integer,
+        ResolverFunctionResponseType::Boolean,
+        ResolverFunctionResponseType::String,
+        ResolverFunctionResponseType::Map,
+        ResolverFunctionResponseType::Object,
+    ] {
+        let value = match response_type {
+            ResolverFunctionResponseType::Array => serde_json::json!({ "value": "foo"}),
+            ResolverFunctionResponseType::Integer => serde_json::json!({ "value": "a string" }),
+            ResolverFunctionResponseType::Boolean => serde_json::json!({ "value": "a string" }),
+            ResolverFunctionResponseType::String => serde_json::json!({ "value": 12345 }),
+            ResolverFunctionResponseType::Map | ResolverFunctionResponseType::Object => {
+                serde_json::json!({ "value": ["an_object", "has keys" ] })
+            }
+            _ => serde_json::json!({ "value": null }),
+        };
+        // Not going to check output here--we aren't emitting anything
+        let (tx, mut rx) = mpsc::channel(64);
+        tokio::spawn(async move {
+            while let Some(output) = rx.recv().await {
+                info!("output: {:?}", output)
+            }
+        });
+
+        let request = ResolverFunctionRequest {
+            execution_id: "1234".to_string(),
+            handler: "returnInputValue".to_string(),
+            component: ResolverFunctionComponent {
+                data: ComponentView {
+                    properties: value,
+                    kind: ComponentKind::Standard,
+                },
+                parents: vec![],
+            },
+            response_type: response_type.clone(),
+            code_base64: base64_encode("function returnInputValue(input) { return input.value; }"),
+            lang: FunctionLanguage::JavaScript,
+            before: vec![],
+        };
+
+        let result = client
+            .execute_resolver_function(tx, &request)
+            .await
+            .expect("failed to execute resolver function");
+
+        match result {
+            FunctionResult::Success(success) => {
+                dbg!(success, response_type);
+                panic!("should have failed :(");
+            }
+            FunctionResult::Failure(failure) => {
+                assert_eq!(failure.error.kind, "InvalidReturnType");
+                assert_eq!(failure.error.category, FunctionErrorKind::InvalidReturnType);
+                assert_eq!(failure.execution_id, "1234");
+            }
+        }
+    }
+}
+
+#[allow(clippy::disallowed_methods)] // `$RUST_LOG` is checked for in macro
+#[test(tokio::test)]
+async fn executes_simple_validation() {
+    let prefix = nats_prefix();
+    run_veritech_server_for_uds_cyclone(prefix.clone()).await;
+    let client = client(prefix).await;
+
+    // Not going to check output here--we aren't emitting anything
+    let (tx, mut rx) = mpsc::channel(64);
+    tokio::spawn(async move {
+        while let Some(output) = rx.recv().await {
+            info!("output: {:?}", output)
+        }
+    });
+
+    let request = ValidationRequest {
+        execution_id: "31337".to_string(),
+        handler: "isThirtyThree".to_string(),
+        value: 33.into(),
+        code_base64: base64_encode(
+            "function isThirtyThree(value) { return { valid: value === 33 }; };",
+        ),
+        lang: FunctionLanguage::JavaScript,
+        before: vec![],
+    };
+
+    let result = client
+        .execute_validation(tx, &request)
+        .await
+        .expect("failed to execute validation");
+
+    match result {
+        FunctionResult::Success(success) => {
+            assert_eq!(success.execution_id, "31337");
+            assert!(success.valid);
+        }
+        FunctionResult::Failure(failure) => {
+            panic!("function did not succeed and should have: {failure:?}")
+        }
+    }
+}
+
+#[allow(clippy::disallowed_methods)] // `$RUST_LOG` is checked for in macro
+#[test(tokio::test)]
+async fn payloads_match_their_schemas() {
+    let prefix = nats_prefix();
+    run_veritech_server_for_uds_cyclone(prefix.clone()).await;
+    let client = client(prefix).await;
+
+    let (tx, mut rx) = mpsc::channel(64);
+    let resolver_function_request = ResolverFunctionRequest {
+        execution_id: "schematic".to_string(),
+        handler: "shout".to_string(),
+        component: ResolverFunctionComponent {
+            data: ComponentView {
+                properties: serde_json::json!({ "name": "schematic" }),
+                kind: ComponentKind::Standard,
+            },
+            parents: vec![],
+        },
+        response_type: ResolverFunctionResponseType::String,
+        code_base64: base64_encode(
+            "function shout(input) { \
+                console.log('shouting'); \
+                return input.name.toUpperCase(); \
+            }",
+        ),
+        lang: FunctionLanguage::JavaScript,
+        before: vec![],
+    };
+    let resolver_function_result = client
+        .execute_resolver_function(tx.clone(), &resolver_function_request)
+        .await
+        .expect("failed to execute resolver function");
+
+    let validation_request = ValidationRequest {
+        execution_id: "schematic-validation".to_string(),
+        handler: "isThirtyThree".to_string(),
+        value: 33.into(),
+        code_base64: base64_encode(
+            "function isThirtyThree(value) { return { valid: value === 33 }; };",
+        ),
+        lang: FunctionLanguage::JavaScript,
+        before: vec![],
+    };
+    let validation_result = client
+        .execute_validation(tx, &validation_request)
+        .await
+        .expect("failed to execute validation");
+    let output = rx.recv().await.expect("no output from resolver function");
+
+    let payloads = [
+        (
+            "ResolverFunctionRequest",
+            serde_json::to_value(&resolver_function_request),
+        ),
+        (
+            "ResolverFunctionResult",
+            serde_json::to_value(&resolver_function_result),
+        ),
+        (
+            "ValidationRequest",
+            serde_json::to_value(&validation_request),
+        ),
+        ("ValidationResult", serde_json::to_value(&validation_result)),
+        ("OutputStream", serde_json::to_value(&output)),
+    ];
+    for (name, payload) in payloads {
+        let payload = payload.expect("failed to serialize payload");
+        validate_payload(name, &payload)
+            .unwrap_or_else(|err| panic!("payload drifted from its schema: {err}"));
+    }
+    assert_eq!(
+        schemas()["version"],
+        serde_json::json!(PAYLOAD_SCHEMA_VERSION)
+    );
+}
+
+#[allow(clippy::disallowed_methods)] // `$RUST_LOG` is checked for in macro
+#[test(tokio::test)]
+async fn returns_cached_validation_result() {
+    let prefix = nats_prefix();
+    run_veritech_server_for_uds_cyclone(prefix.clone()).await;
+    let cache = Arc::new(InMemoryResultCache::default());
+    // Nothing is listening on the second prefix, so that client can only answer from the cache
+    let offline_client = client(nats_prefix()).await.with_result_cache(cache.clone());
+    let client = client(prefix).await.with_result_cache(cache.clone());
+
+    let request = ValidationRequest {
+        execution_id: "1".to_string(),
+        handler: "isThirtyThree".to_string(),
+        value: 33.into(),
+        code_base64: base64_encode(
+            "function isThirtyThree(value) { return { valid: value === 33 }; };",
+        ),
+        lang: FunctionLanguage::JavaScript,
+        before: vec![],
+    };
+
+    let (tx, _rx) = mpsc::channel(64);
+    let result = client
+        .execute_validation(tx, &request)
+        .await
+        .expect("failed to execute validation");
+    assert!(matches!(result, FunctionResult::Success(_)));
+    assert_eq!(1, cache.len());
+
+    let (tx, _rx) = mpsc::channel(64);
+    let result = offline_client
+        .execute_validation(
+            tx,
+            &ValidationRequest {
+                execution_id: "2".to_string(),
+                ..request
+            },
+        )
+        .await
+        .expect("failed to return cached validation result");
+
+    match result {
+        FunctionResult::Success(success) => {
+            assert_eq!(success.execution_id, "2");
+            assert!(success.valid);
+        }
+        FunctionResult::Failure(failure) => {
+            panic!("function did not succeed and should have: {failure:?}")
+        }
+    }
+}
+
+#[allow(clippy::disallowed_methods)] // `$RUST_LOG` is checked for in macro
+#[test(tokio::test)]
+async fn cancels_running_resolver_function() {
+    let prefix = nats_prefix();
+    run_veritech_server_for_uds_cyclone(prefix.clone()).await;
+    let client = client(prefix).await;
+    let handle = client.new_execution_handle();
+
+    // Cancel once the function has started, which we know from its first line of output
+    let (tx, mut rx) = mpsc::channel(64);
+    let canceller = handle.clone();
+    tokio::spawn(async move {
+        if rx.recv().await.is_some() {
+            canceller
+                .cancel()
+                .await
+                .expect("failed to cancel execution");
+        }
+        while let Some(output) = rx.recv().await {
+            info!("output: {:?}", output)
+        }
+    });
+
+    let request = ResolverFunctionRequest {
+        execution_id: "5678".to_string(),
+        handler: "wait".to_string(),
+        component: ResolverFunctionComponent {
+            data: ComponentView {
+                properties: serde_json::json!({}),
+                kind: ComponentKind::Standard,
+            },
+            parents: vec![],
+        },
+        response_type: ResolverFunctionResponseType::Integer,
+        code_base64: base64_encode(
+            "async function wait(input) { \
+                console.log('waiting'); \
+                await new Promise((resolve) => setTimeout(resolve, 600000)); \
+                return 1; \
+            }",
+        ),
+        lang: FunctionLanguage::JavaScript,
+        before: vec![],
+    };
+
+    let result = client
+        .execute_resolver_function_with_handle(&handle, tx, &request)
+        .await
+        .expect("failed to execute resolver function");
+
+    match result {
+        FunctionResult::Success(success) => {
+            panic!("function succeeded and should have been cancelled: {success:?}")
+        }
+        FunctionResult::Failure(failure) => {
+            assert_eq!(failure.execution_id, "5678");
+            assert_eq!(failure.error.kind, "cancelled");
+            assert_eq!(failure.error.category, FunctionErrorKind::Cancelled);
+        }
+    }
+}
+
+#[allow(clippy::disallowed_methods)] // `$RUST_LOG` is checked for in macro
+#[test(tokio::test)]
+async fn drains_running_executions_on_shutdown() {
+    let prefix = nats_prefix();
+    let server = veritech_server_for_uds_cyclone(prefix.clone()).await;
+    let shutdown_handle = server.shutdown_handle();
+    let server_task = tokio::spawn(server.run());
+    let client = client(prefix).await;
+
+    // Shut down once the function has started, which we know from its first line of output
+    let (tx, mut rx) = mpsc::channel(64);
+    tokio::spawn(async move {
+        if rx.recv().await.is_some() {
+            shutdown_handle.shutdown().await;
+        }
+        while let Some(output) = rx.recv().await {
+            info!("output: {:?}", output)
+        }
+    });
+
+    let request = ResolverFunctionRequest {
+        execution_id: "8765".to_string(),
+        handler: "wait".to_string(),
+        component: ResolverFunctionComponent {
+            data: ComponentView {
+                properties: serde_json::json!({}),
+                kind: ComponentKind::Standard,
+            },
+            parents: vec![],
+        },
+        response_type: ResolverFunctionResponseType::Integer,
+        code_base64: base64_encode(
+            "async function wait(input) { \
+                console.log('waiting'); \
+                await new Promise((resolve) => setTimeout(resolve, 2000)); \
+                return 1; \
+            }",
+        ),
+        lang: FunctionLanguage::JavaScript,
+        before: vec![],
+    };
+
+    let result = client
+        .execute_resolver_function(tx, &request)
+        .await
+        .expect("failed to execute resolver function");
+
+    match result {
+        FunctionResult::Success(success) => {
+            assert_eq!(success.execution_id, "8765");
+            assert_eq!(success.data, serde_json::json!(1));
+        }
+        FunctionResult::Failure(failure) => {
+            panic!("function did not succeed and should have: {failure:?}")
+        }
+    }
+
+    server_task
+        .await
+        .expect("server task panicked")
+        .expect("server failed to shut down");
+}
+
+#[allow(clippy::disallowed_methods)] // `$RUST_LOG` is checked for in macro
+#[test(tokio::test)]
+async fn times_out_long_running_resolver_function() {
+    let prefix = nats_prefix();
+    run_veritech_server_for_uds_cyclone(prefix.clone()).await;
+    let client = client(prefix).await.with_timeout(Duration::from_secs(5));
+
+    let (tx, mut rx) = mpsc::channel(64);
+    tokio::spawn(async move {
+        while let Some(output) = rx.recv().await {
+            info!("output: {:?}", output)
+        }
+    });
+
+    let request = ResolverFunctionRequest {
+        execution_id: "9012".to_string(),
+        handler: "wait".to_string(),
+        component: ResolverFunctionComponent {
+            data: ComponentView {
+                properties: serde_json::json!({}),
+                kind: ComponentKind::Standard,
+            },
+            parents: vec![],
+        },
+        response_type: ResolverFunctionResponseType::Integer,
+        code_base64: base64_encode(
+            "async function wait(input) { \
+                await new Promise((resolve) => setTimeout(resolve, 600000)); \
+                return 1; \
+            }",
+        ),
+        lang: FunctionLanguage::JavaScript,
+        before: vec![],
+    };
+
+    match client.execute_resolver_function(tx, &request).await {
+        Err(ClientError::Timeout(timeout)) => assert_eq!(timeout, Duration::from_secs(5)),
+        unexpected => panic!("execution should have timed out: {unexpected:?}"),
+    }
+}
+
+#[allow(clippy::disallowed_methods)] // `$RUST_LOG` is checked for in macro
+#[test(tokio::test)]
+async fn errors_when_server_is_lost() {
+    let prefix = nats_prefix();
+    // Stands in for a server that dies right after receiving a request: the request is delivered
+    // but nothing ever answers it
+    let silent_server = nats(prefix.clone()).await;
+    let _requests = silent_server
+        .subscribe(format!("{prefix}.veritech.fn.resolverfunction"))
+        .await
+        .expect("failed to subscribe to resolver function requests");
+    let client = client(prefix)
+        .await
+        .with_heartbeat_timeout(Some(Duration::from_secs(1)));
+
+    let (tx, _rx) = mpsc::channel(64);
+    let request = ResolverFunctionRequest {
+        execution_id: "lost".to_string(),
+        handler: "lost".to_string(),
+        component: ResolverFunctionComponent {
+            data: ComponentView {
+                properties: serde_json::json!({}),
+                kind: ComponentKind::Standard,
+            },
+            parents: vec![],
+        },
+        response_type: ResolverFunctionResponseType::Integer,
+        code_base64: base64_encode("function lost(input) { return 1; }"),
+        lang: FunctionLanguage::JavaScript,
+        before: vec![],
+    };
+
+    match client.execute_resolver_function(tx, &request).await {
+        Err(ClientError::ServerLost(silence)) => assert_eq!(silence, Duration::from_secs(1)),
+        unexpected => panic!("execution should have lost its server: {unexpected:?}"),
+    }
+}
+
+#[allow(clippy::disallowed_methods)] // `$RUST_LOG` is checked for in macro
+#[test(tokio::test)]
+async fn queues_executions_beyond_workspace_limit() {
+    let prefix = nats_prefix();
+    let config = Config::builder()
+        .nats(nats_config(prefix.clone()))
+        .cyclone_spec(uds_cyclone_spec())
+        .max_concurrent_executions_per_workspace(Some(1))
+        .build()
+        .expect("failed to build spec");
+    let server = Server::for_cyclone_uds(config)
+        .await
+        .expect("failed to create server");
+    tokio::spawn(server.run());
+    let client = client(prefix).await.with_workspace_id("busy-workspace");
+
+    let request = |execution_id: &str| ResolverFunctionRequest {
+        execution_id: execution_id.to_string(),
+        handler: "wait".to_string(),
+        component: ResolverFunctionComponent {
+            data: ComponentView {
+                properties: serde_json::json!({}),
+                kind: ComponentKind::Standard,
+            },
+            parents: vec![],
+        },
+        response_type: ResolverFunctionResponseType::Integer,
+        code_base64: base64_encode(
+            "async function wait(input) { \
+                await new Promise((resolve) => setTimeout(resolve, 1000)); \
+                return 1; \
+            }",
+        ),
+        lang: FunctionLanguage::JavaScript,
+        before: vec![],
+    };
+    let (first, second) = (request("limited-1"), request("limited-2"));
+
+    // With a limit of one, the second execution can only start once the first has finished
+    let started = Instant::now();
+    let (first_tx, _first_rx) = mpsc::channel(64);
+    let (second_tx, _second_rx) = mpsc::channel(64);
+    let (first_result, second_result) = tokio::join!(
+        client.execute_resolver_function(first_tx, &first),
+        client.execute_resolver_function(second_tx, &second),
+    );
+    let elapsed = started.elapsed();
+
+    for result in [first_result, second_result] {
+        match result.expect("failed to execute resolver function") {
+            FunctionResult::Success(success) => assert_eq!(success.data, serde_json::json!(1)),
+            FunctionResult::Failure(failure) => {
+                panic!("function did not succeed and should have: {failure:?}")
+            }
+        }
+    }
+    assert!(
+        elapsed >= Duration::from_secs(2),
+        "executions of the same workspace ran concurrently (took {elapsed:?})"
+    );
+}
+
+#[allow(clippy::disallowed_methods)] // `$RUST_LOG` is checked for in macro
+#[test(tokio::test)]
+async fn executes_resolver_function_on_workspace_shard() {
+    let prefix = nats_prefix();
+    let workspace_id = "sharded-workspace";
+    // The server only serves the workspace's shard, so the request must be routed onto it
+    let config = Config::builder()
+        .nats(nats_config(prefix.clone()))
+        .cyclone_spec(uds_cyclone_spec())
+        .sharding(Some(ShardConfig {
+            count: 4,
+            shards: vec![shard_for_workspace(workspace_id, 4)],
+        }))
+        .build()
+        .expect("failed to build spec");
+    let server = Server::for_cyclone_uds(config)
+        .await
+        .expect("failed to create server");
+    tokio::spawn(server.run());
+    let client = client(prefix)
+        .await
+        .with_workspace_id(workspace_id)
+        .with_sharding(4);
+
+    let (tx, _rx) = mpsc::channel(64);
+    let request = ResolverFunctionRequest {
+        execution_id: "sharded".to_string(),
+        handler: "numberOfParents".to_string(),
+        component: ResolverFunctionComponent {
+            data: ComponentView {
+                properties: serde_json::json!({}),
+                kind: ComponentKind::Standard,
+            },
+            parents: vec![],
+        },
+        response_type: ResolverFunctionResponseType::Integer,
+        code_base64: base64_encode(
+            "function numberOfParents(input) { return input.parents.length; }",
+        ),
+        lang: FunctionLanguage::JavaScript,
+        before: vec![],
+    };
+
+    let result = client
+        .execute_resolver_function(tx, &request)
+        .await
+        .expect("failed to execute resolver function");
+
+    match result {
+        FunctionResult::Success(success) => {
+            assert_eq!(success.execution_id, "sharded");
+            assert_eq!(success.data, serde_json::json!(0));
+        }
+        FunctionResult::Failure(failure) => {
+            panic!("function did not succeed and should have: {failure:?}")
+        }
+    }
+}
+
+#[allow(clippy::disallowed_methods)] // `$RUST_LOG` is checked for in macro
+#[test(tokio::test)]
+async fn executes_batch_of_validations() {
+    let prefix = nats_prefix();
+    run_veritech_server_for_uds_cyclone(prefix.clone()).await;
+    let client = client(prefix).await;
+
+    let (tx, mut rx) = mpsc::channel(64);
+    tokio::spawn(async move {
+        while let Some(output) = rx.recv().await {
+            info!("output: {:?}", output)
+        }
+    });
+
+    let requests = (0..5)
+        .map(|value| {
+            BatchRequest::Validation(ValidationRequest {
+                execution_id: format!("batch-{value}"),
+                handler: "isEven".to_string(),
+                value: value.into(),
+                code_base64: base64_encode(
+                    "function isEven(value) { return { valid: value % 2 === 0 }; };",
+                ),
+                lang: FunctionLanguage::JavaScript,
+                before: vec![],
+            })
+        })
+        .collect::<Vec<_>>();
+
+    let mut results = client
+        .execute_batch(requests, tx)
+        .await
+        .expect("failed to execute batch")
+        .collect::<Vec<_>>()
+        .await;
+    results.sort_by_key(|result| result.index);
+
+    assert_eq!(5, results.len());
+    for (index, batch_result) in results.into_iter().enumerate() {
+        assert_eq!(index, batch_result.index);
+        match batch_result.result.expect("failed to execute validation") {
+            VeritechResult::Validation(FunctionResult::Success(success)) => {
+                assert_eq!(success.execution_id, format!("batch-{index}"));
+                assert_eq!(success.valid, index % 2 == 0);
+            }
+            unexpected => panic!("validation did not succeed and should have: {unexpected:?}"),
+        }
+    }
+}
+
+#[allow(clippy::disallowed_methods)] // `$RUST_LOG` is checked for in macro
+#[test(tokio::test)]
+async fn executes_simple_schema_variant_definition() {
+    let prefix = nats_prefix();
+    run_veritech_server_for_uds_cyclone(prefix.clone()).await;
+    let client = client(prefix).await;
+
+    // Not going to check output here--we aren't emitting anything
+    let (tx, mut rx) = mpsc::channel(64);
+    tokio::spawn(async move {
+        while let Some(output) = rx.recv().await {
+            info!("output: {:?}", output)
+        }
+    });
+
+    let request = SchemaVariantDefinitionRequest {
+        execution_id: "8badf00d".to_string(),
+        handler: "asset".to_string(),
+        code_base64: base64_encode(
+            "function asset() {
+                    return {
+                        props: [{kind: 'string', name: 'string_prop'}],
+                        inputSockets: [], outputSockets: []
+                    };
+                }",
+        ),
+        lang: FunctionLanguage::JavaScript,
+        before: vec![],
+    };
+
+    let result = client
+        .execute_schema_variant_definition(tx, &request)
+        .await
+        .expect("failed to execute schema variant definition");
+
+    match result {
+        FunctionResult::Success(success) => {
+            assert_eq!(success.execution_id, "8badf00d");
+            assert_eq!(
+                success.definition,
+                serde_json::json!({
+                    "props": [{
+                        "kind": "string",
+                        "name": "string_prop",
+                    }],
+                    "inputSockets": [],
+                    "outputSockets": []
+                })
+            );
+        }
+        FunctionResult::Failure(failure) => {
+            panic!("function did not succeed and should have: {failure:?}")
+        }
+    }
+}
+
+#[allow(clippy::disallowed_methods)] // `$RUST_LOG` is checked for in macro
+#[test(tokio::test)]
+async fn returns_simulated_results_without_a_server() {
+    let prefix = nats_prefix();
+    // Note that no veritech server is started for this prefix
+    let mut simulation = SimulatedResults::new();
+    simulation.insert(
+        "isThirtyThree",
+        serde_json::json!({
+            "Success": {
+                "executionId": "ignored",
+                "valid": false,
+                "message": "simulated",
+            }
+        }),
+    );
+    let client = client(prefix).await.with_simulation(simulation);
+    assert!(client.is_simulated());
+
+    let (tx, mut rx) = mpsc::channel(64);
+    let request = ValidationRequest {
+        execution_id: "5150".to_string(),
+        handler: "isThirtyThree".to_string(),
+        value: 33.into(),
+        code_base64: base64_encode(
+            "function isThirtyThree(value) { return { valid: value === 33 }; };",
+        ),
+        lang: FunctionLanguage::JavaScript,
+        before: vec![],
+    };
+
+    let result = client
+        .execute_validation(tx, &request)
+        .await
+        .expect("failed to execute simulated validation");
+
+    match result {
+        FunctionResult::Success(success) => {
+            assert_eq!(success.execution_id, "5150");
+            assert!(!success.valid);
+            assert_eq!(success.message.as_deref(), Some("simulated"));
+        }
+        FunctionResult::Failure(failure) => {
+            panic!("simulated function did not succeed and should have: {failure:?}")
+        }
+    }
+    let output = rx
+        .recv()
+        .await
+        .expect("expected a simulated output message");
+    assert_eq!(output.execution_id, "5150");
+
+    let (tx, _rx) = mpsc::channel(64);
+    let request = ValidationRequest {
+        handler: "unknownHandler".to_string(),
+        ..request
+    };
+    assert!(matches!(
+        client.execute_validation(tx, &request).await,
+        Err(ClientError::NoSimulatedResult(handler)) if handler == "unknownHandler"
+    ));
+}
+
+#[allow(clippy::disallowed_methods)] // `$RUST_LOG` is checked for in macro
+#[test(tokio::test)]
+async fn replays_recorded_executions_without_a_server() {
+    let prefix = nats_prefix();
+    run_veritech_server_for_uds_cyclone(prefix.clone()).await;
+    let fixture_dir = env::temp_dir().join(format!("veritech-recordings-{}", Uuid::new_v4()));
+    let recording_client = client(prefix)
+        .await
+        .with_record_replay(RecordReplay::Record(fixture_dir.clone()));
+
+    let request = ValidationRequest {
+        execution_id: "recorded".to_string(),
+        handler: "isThirtyThree".to_string(),
+        value: 33.into(),
+        code_base64: base64_encode(
+            "function isThirtyThree(value) { return { valid: value === 33 }; };",
+        ),
+        lang: FunctionLanguage::JavaScript,
+        before: vec![],
+    };
+    let (tx, _rx) = mpsc::channel(64);
+    recording_client
+        .execute_validation(tx, &request)
+        .await
+        .expect("failed to execute recorded validation");
+
+    // The replaying client has no connection to NATS
+    let replaying_client = Client::new_replaying(&fixture_dir);
+    let (tx, _rx) = mpsc::channel(64);
+    let result = replaying_client
+        .execute_validation(
+            tx,
+            &ValidationRequest {
+                execution_id: "replayed".to_string(),
+                ..request.clone()
+            },
+        )
+        .await
+        .expect("failed to replay validation");
+
+    match result {
+        FunctionResult::Success(success) => {
+            assert_eq!(success.execution_id, "replayed");
+            assert!(success.valid);
+        }
+        FunctionResult::Failure(failure) => {
+            panic!("replayed function did not succeed and should have: {failure:?}")
+        }
+    }
+
+    let (tx, _rx) = mpsc::channel(64);
+    let request = ValidationRequest {
+        value: 34.into(),
+        ..request
+    };
+    assert!(matches!(
+        replaying_client.execute_validation(tx, &request).await,
+        Err(ClientError::NoRecordedExecution("validation", _))
+    ));
+
+    std::fs::remove_dir_all(fixture_dir).expect("failed to remove fixture directory");
+}
+
+/// Answers every resolver function request published on the transport with some output and a
+/// successful result, the way a veritech server would.
+fn spawn_loopback_responder(transport: LoopbackTransport) -> JoinHandle<()> {
+    tokio::spawn(async move {
+        let mut requests = transport
+            .subscribe(&nats_resolver_function_subject(None))
+            .await
+            .expect("failed to subscribe to requests");
+        while let Some(message) = requests.next().await {
+            let request: ResolverFunctionRequest =
+                serde_json::from_slice(&message.payload).expect("failed to parse request");
+            let reply_mailbox = message.reply_mailbox.expect("request should have a reply");
+
+            let output = OutputStream {
+                stream: "output".to_string(),
+                execution_id: request.execution_id.clone(),
+                level: "info".to_string(),
+                group: None,
+                message: "responding from loopback".to_string(),
+                timestamp: 0,
+            };
+            let final_message: HeaderMap = [(FINAL_MESSAGE_HEADER_KEY, "true")].iter().collect();
+            let result = FunctionResult::Success(ResolverFunctionResultSuccess {
+                execution_id: request.execution_id,
+                data: serde_json::json!(1),
+                unset: false,
+                timestamp: 0,
+            });
+            for (subject, headers, payload) in [
+                (
+                    reply_mailbox_for_output(&reply_mailbox),
+                    None,
+                    serde_json::to_vec(&output).expect("failed to serialize output"),
+                ),
+                (
+                    reply_mailbox_for_output(&reply_mailbox),
+                    Some(&final_message),
+                    vec![],
+                ),
+                (
+                    reply_mailbox_for_result(&reply_mailbox),
+                    None,
+                    serde_json::to_vec(&result).expect("failed to serialize result"),
+                ),
+            ] {
+                transport
+                    .publish_with_reply(&subject, None, headers, payload)
+                    .await
+                    .expect("failed to publish reply");
+            }
+        }
+    })
+}
+
+#[allow(clippy::disallowed_methods)] // `$RUST_LOG` is checked for in macro
+#[test(tokio::test)]
+async fn executes_resolver_function_over_loopback_transport() {
+    let transport = LoopbackTransport::new();
+    let responder = spawn_loopback_responder(transport.clone());
+    let client = Client::new_with_transport(Arc::new(transport));
+
+    let (tx, mut rx) = mpsc::channel(64);
+    let request = ResolverFunctionRequest {
+        execution_id: "loopback".to_string(),
+        handler: "one".to_string(),
+        component: ResolverFunctionComponent {
+            data: ComponentView {
+                properties: serde_json::json!({}),
+                kind: ComponentKind::Standard,
+            },
+            parents: vec![],
+        },
+        response_type: ResolverFunctionResponseType::Integer,
+        code_base64: base64_encode("function one(input) { return 1; }"),
+        lang: FunctionLanguage::JavaScript,
+        before: vec![],
+    };
+
+    let result = client
+        .execute_resolver_function(tx, &request)
+        .await
+        .expect("failed to execute resolver function");
+
+    match result {
+        FunctionResult::Success(success) => {
+            assert_eq!(success.execution_id, "loopback");
+            assert_eq!(success.data, serde_json::json!(1));
+        }
+        FunctionResult::Failure(failure) => {
+            panic!("function did not succeed and should have: {failure:?}")
+        }
+    }
+    let output = rx.recv().await.expect("expected an output message");
+    assert_eq!(output.message, "responding from loopback");
+    responder.abort();
+}
+
+#[allow(clippy::disallowed_methods)] // `$RUST_LOG` is checked for in macro
+#[test(tokio::test)]
+async fn errors_when_nothing_listens_on_loopback_transport() {
+    // Note that no responder subscribes to the transport
+    let client = Client::new_with_transport(Arc::new(LoopbackTransport::new()));
+
+    let (tx, _rx) = mpsc::channel(64);
+    let request = ValidationRequest {
+        execution_id: "unanswered".to_string(),
+        handler: "isThirtyThree".to_string(),
+        value: 33.into(),
+        code_base64: base64_encode(
+            "function isThirtyThree(value) { return { valid: value === 33 }; };",
+        ),
+        lang: FunctionLanguage::JavaScript,
+        before: vec![],
+    };
+
+    assert!(matches!(
+        client.execute_validation(tx, &request).await,
+        Err(ClientError::PublishingFailed(_))
+    ));
+}
+
+#[allow(clippy::disallowed_methods)] // `$RUST_LOG` is checked for in macro
+#[test(tokio::test)]
+async fn ends_reply_mailbox_subscriptions_on_timeout() {
+    let transport = LoopbackTransport::new();
+    // Stands in for a server that accepts requests and never answers them
+    let request_subject = nats_resolver_function_subject(None);
+    let _requests = transport
+        .subscribe(&request_subject)
+        .await
+        .expect("failed to subscribe to requests");
+    let client = Client::new_with_transport(Arc::new(transport.clone()))
+        .with_timeout(Duration::from_millis(100));
+
+    let (tx, _rx) = mpsc::channel(64);
+    let request = ResolverFunctionRequest {
+        execution_id: "abandoned".to_string(),
+        handler: "one".to_string(),
+        component: ResolverFunctionComponent {
+            data: ComponentView {
+                properties: serde_json::json!({}),
+                kind: ComponentKind::Standard,
+            },
+            parents: vec![],
+        },
+        response_type: ResolverFunctionResponseType::Integer,
+        code_base64: base64_encode("function one(input) { return 1; }"),
+        lang: FunctionLanguage::JavaScript,
+        before: vec![],
+    };
+
+    let (mut progress_rx, result) = client.execute_with_progress(tx, &request);
+    match result.await {
+        Err(ClientError::Timeout(timeout)) => assert_eq!(timeout, Duration::from_millis(100)),
+        unexpected => panic!("execution should have timed out: {unexpected:?}"),
+    }
+    assert!(progress_rx.recv().await.is_none());
+    assert_eq!(vec![request_subject], transport.subscribed_subjects());
+}
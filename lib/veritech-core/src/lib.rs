@@ -11,18 +11,74 @@
     clippy::module_name_repetitions
 )]
 
-const NATS_ACTION_RUN_DEFAULT_SUBJECT: &str = "veritech.fn.actionrun";
-const NATS_CONCILIATION_DEFAULT_SUBJECT: &str = "veritech.fn.reconciliation";
-const NATS_RESOLVER_FUNCTION_DEFAULT_SUBJECT: &str = "veritech.fn.resolverfunction";
-const NATS_SCHEMA_VARIANT_DEFINITION_DEFAULT_SUBJECT: &str = "veritech.fn.schemavariantdefinition";
-const NATS_VALIDATION_DEFAULT_SUBJECT: &str = "veritech.fn.validation";
+use std::time::Duration;
 
+mod stats;
+
+pub use stats::{KindStats, PoolStats, ServerStats};
+
+pub const NATS_ACTION_RUN_DEFAULT_SUBJECT: &str = "veritech.fn.actionrun";
+pub const NATS_CONCILIATION_DEFAULT_SUBJECT: &str = "veritech.fn.reconciliation";
+pub const NATS_CONTROL_STATS_DEFAULT_SUBJECT: &str = "veritech.control.stats";
+pub const NATS_DEAD_LETTER_DEFAULT_SUBJECT: &str = "veritech.dlq";
+pub const NATS_RESOLVER_FUNCTION_DEFAULT_SUBJECT: &str = "veritech.fn.resolverfunction";
+pub const NATS_SCHEMA_VARIANT_DEFINITION_DEFAULT_SUBJECT: &str =
+    "veritech.fn.schemavariantdefinition";
+pub const NATS_VALIDATION_DEFAULT_SUBJECT: &str = "veritech.fn.validation";
+
+/// Tells the server that the client accepts results split into chunks. Its value is the size, in
+/// bytes, of the largest chunk the client wants to receive.
+pub const ACCEPT_CHUNKED_RESULTS_HEADER_KEY: &str = "X-Accept-Chunked-Results";
+/// Tells the server that the client accepts compressed results. Its value is the size, in bytes,
+/// above which a result is worth compressing.
+pub const ACCEPT_COMPRESSION_HEADER_KEY: &str = "X-Accept-Compression";
+/// Why a dead letter's request could not be executed.
+pub const DEAD_LETTER_ERROR_HEADER_KEY: &str = "X-Dead-Letter-Error";
+/// The reply mailbox of a dead letter's request, if it had one.
+pub const DEAD_LETTER_REPLY_MAILBOX_HEADER_KEY: &str = "X-Dead-Letter-Reply-Mailbox";
+/// The subject a dead letter's request was originally published on.
+pub const DEAD_LETTER_SUBJECT_HEADER_KEY: &str = "X-Dead-Letter-Subject";
+pub const ENCRYPTED_PAYLOAD_HEADER_KEY: &str = "X-Encrypted-Payload";
 pub const FINAL_MESSAGE_HEADER_KEY: &str = "X-Final-Message";
 
+/// Names the cyclone sandbox profile a request's function runs under, such as one that allows the
+/// programs a qualification shells out to. Functions run under cyclone's default profile if a
+/// request names none.
+pub const SANDBOX_PROFILE_HEADER_KEY: &str = "X-Sandbox-Profile";
+
+/// Numbers a chunk of a result that was split into chunks, counting from 0. All chunks of a
+/// result share its message id.
+pub const RESULT_CHUNK_HEADER_KEY: &str = "X-Result-Chunk";
+/// Marks the last chunk of a result that was split into chunks.
+pub const RESULT_FINAL_CHUNK_HEADER_KEY: &str = "X-Result-Final-Chunk";
+
+/// Identifies the user a request runs on behalf of, which servers record in the audit record of
+/// its execution.
+pub const USER_ID_HEADER_KEY: &str = "X-User-Id";
+
+/// Identifies the workspace a request runs on behalf of, so that servers can limit how many
+/// executions of a single workspace run at once.
+pub const WORKSPACE_ID_HEADER_KEY: &str = "X-Workspace-Id";
+
+/// How often a server publishes heartbeats on the reply mailbox of a running execution.
+pub const HEARTBEAT_INTERVAL: Duration = Duration::from_secs(5);
+
+pub fn reply_mailbox_for_cancel(reply_mailbox: &str) -> String {
+    format!("{reply_mailbox}.cancel")
+}
+
+pub fn reply_mailbox_for_heartbeat(reply_mailbox: &str) -> String {
+    format!("{reply_mailbox}.heartbeat")
+}
+
 pub fn reply_mailbox_for_output(reply_mailbox: &str) -> String {
     format!("{reply_mailbox}.output")
 }
 
+pub fn reply_mailbox_for_progress(reply_mailbox: &str) -> String {
+    format!("{reply_mailbox}.progress")
+}
+
 pub fn reply_mailbox_for_result(reply_mailbox: &str) -> String {
     format!("{reply_mailbox}.result")
 }
@@ -47,6 +103,38 @@ pub fn nats_schema_variant_definition_subject(prefix: Option<&str>) -> String {
     nats_subject(prefix, NATS_SCHEMA_VARIANT_DEFINITION_DEFAULT_SUBJECT)
 }
 
+/// The subject on which servers answer requests for their [`ServerStats`].
+pub fn nats_control_stats_subject(prefix: Option<&str>) -> String {
+    nats_subject(prefix, NATS_CONTROL_STATS_DEFAULT_SUBJECT)
+}
+
+/// The subject on which servers republish requests of the given kind that they could not
+/// execute. A kind of `>` matches the dead letters of every kind.
+pub fn nats_dead_letter_subject(prefix: Option<&str>, kind: &str) -> String {
+    nats_subject(prefix, format!("{NATS_DEAD_LETTER_DEFAULT_SUBJECT}.{kind}"))
+}
+
+/// Moves a function subject onto a shard by replacing its `fn` token with the shard number, so
+/// that `veritech.fn.resolverfunction` becomes `veritech.3.resolverfunction`.
+pub fn nats_sharded_subject(subject: &str, shard: u32) -> String {
+    match subject.rsplit_once(".fn.") {
+        Some((head, kind)) => format!("{head}.{shard}.{kind}"),
+        None => subject.to_string(),
+    }
+}
+
+/// Picks the shard that serves a workspace's requests when they are split across `shard_count`
+/// shards. Clients and servers must agree on the shard of every workspace, so this uses a hash
+/// that is stable across processes and releases (FNV-1a) rather than the standard library's.
+pub fn shard_for_workspace(workspace_id: &str, shard_count: u32) -> u32 {
+    let hash = workspace_id
+        .bytes()
+        .fold(0xcbf2_9ce4_8422_2325_u64, |hash, byte| {
+            (hash ^ u64::from(byte)).wrapping_mul(0x0100_0000_01b3)
+        });
+    (hash % u64::from(shard_count.max(1))) as u32
+}
+
 pub fn nats_subject(prefix: Option<&str>, suffix: impl AsRef<str>) -> String {
     let suffix = suffix.as_ref();
     match prefix {
@@ -0,0 +1,59 @@
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+
+/// How busy a veritech server is, as it answers on its control stats subject, so that callers can
+/// warn when the function backend is saturated.
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ServerStats {
+    pub pool: PoolStats,
+    /// The executions of each kind of function, such as `resolverFunction` or `actionRun`.
+    pub kinds: BTreeMap<String, KindStats>,
+    /// How far back, in seconds, the recent executions of each kind go.
+    pub recent_window_secs: u64,
+}
+
+impl ServerStats {
+    /// The number of executions, of every kind, waiting for a cyclone instance.
+    pub fn queued(&self) -> u64 {
+        self.kinds.values().map(|kind| kind.queued).sum()
+    }
+
+    /// Returns `true` if every cyclone instance the pool may hold is busy and executions are
+    /// waiting for one.
+    pub fn is_saturated(&self) -> bool {
+        self.pool.busy >= self.pool.max_size && self.queued() > 0
+    }
+}
+
+/// The cyclone instances of a server's pool.
+#[derive(Clone, Copy, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PoolStats {
+    /// The most instances the pool holds at the moment.
+    pub max_size: u64,
+    /// The instances in the pool, busy or idle.
+    pub size: u64,
+    /// The instances running a function.
+    pub busy: u64,
+    /// The instances ready to run a function.
+    pub idle: u64,
+}
+
+/// The executions of one kind of function on a server.
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct KindStats {
+    /// Executions waiting for a cyclone instance.
+    pub queued: u64,
+    /// Executions running on a cyclone instance.
+    pub running: u64,
+    /// Executions that ended within the recent window.
+    pub recent_executions: u64,
+    /// Executions that ended within the recent window with an error or a failed result, other
+    /// than being cancelled.
+    pub recent_errors: u64,
+    /// The share of recent executions that ended with an error, from 0 to 1.
+    pub error_rate: f64,
+}
@@ -0,0 +1,286 @@
+use std::{
+    collections::VecDeque,
+    fmt,
+    sync::{Arc, Mutex, PoisonError},
+    time::Instant,
+};
+
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use nats_subscriber::Request;
+use serde::{Deserialize, Serialize};
+use telemetry::prelude::*;
+use thiserror::Error;
+use veritech_core::USER_ID_HEADER_KEY;
+
+use crate::{concurrency, server::CycloneRequest};
+
+/// How many records an [`InMemoryAuditStore`] keeps by default.
+const DEFAULT_IN_MEMORY_CAPACITY: usize = 10_000;
+
+#[remain::sorted]
+#[derive(Debug, Error)]
+pub enum AuditStoreError {
+    #[error("audit store error: {0}")]
+    Store(#[source] Box<dyn std::error::Error + Sync + Send + 'static>),
+}
+
+pub type AuditStoreResult<T> = Result<T, AuditStoreError>;
+
+/// What a server records about every function it was asked to execute, so that the user supplied
+/// code that ran, and who ran it, can be reviewed later.
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AuditRecord {
+    pub execution_id: String,
+    /// The workspace the execution ran on behalf of, if the request named one.
+    pub workspace_id: Option<String>,
+    /// The user the execution ran on behalf of, if the request named one.
+    pub user_id: Option<String>,
+    /// The kind of function, such as `resolverFunction` or `actionRun`.
+    pub kind: String,
+    pub handler: String,
+    /// The hex encoded BLAKE3 hash of the function's code, as it was sent in the request, followed
+    /// by the code of its before functions.
+    pub code_hash: String,
+    pub received_at: DateTime<Utc>,
+    /// How long the execution took, in milliseconds, from receiving its request until its result
+    /// was published.
+    pub duration_ms: u64,
+    pub status: AuditStatus,
+}
+
+/// How an audited execution ended.
+#[derive(Clone, Copy, Debug, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum AuditStatus {
+    /// The function ran and its result was published.
+    Success,
+    /// The function, or the server running it, failed and the failure was published.
+    Failure,
+    /// The client cancelled the execution.
+    Cancelled,
+    /// The execution ended without publishing a result.
+    Error,
+}
+
+impl AuditStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Success => "success",
+            Self::Failure => "failure",
+            Self::Cancelled => "cancelled",
+            Self::Error => "error",
+        }
+    }
+}
+
+/// Selects audit records. Every field that is set must match for a record to be selected.
+#[derive(Clone, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct AuditQuery {
+    pub workspace_id: Option<String>,
+    pub user_id: Option<String>,
+    pub kind: Option<String>,
+    pub code_hash: Option<String>,
+    pub status: Option<AuditStatus>,
+    /// Selects records received at or after this time.
+    pub since: Option<DateTime<Utc>>,
+    /// Selects records received before this time.
+    pub until: Option<DateTime<Utc>>,
+    /// The most records returned.
+    pub limit: Option<usize>,
+}
+
+impl AuditQuery {
+    /// Returns whether the record is selected by the query, ignoring its limit.
+    pub fn matches(&self, record: &AuditRecord) -> bool {
+        (self.workspace_id.is_none() || self.workspace_id == record.workspace_id)
+            && (self.user_id.is_none() || self.user_id == record.user_id)
+            && self.kind.as_ref().map_or(true, |kind| *kind == record.kind)
+            && self
+                .code_hash
+                .as_ref()
+                .map_or(true, |code_hash| *code_hash == record.code_hash)
+            && self.status.map_or(true, |status| status == record.status)
+            && self.since.map_or(true, |since| record.received_at >= since)
+            && self.until.map_or(true, |until| record.received_at < until)
+    }
+}
+
+/// Where a server writes its [`AuditRecord`]s, and where they are read back from for review.
+#[async_trait]
+pub trait AuditStore: Send + Sync + 'static {
+    /// Stores the record of an execution.
+    async fn record(&self, record: AuditRecord) -> AuditStoreResult<()>;
+
+    /// Returns the records selected by the query, most recently received first.
+    async fn query(&self, query: &AuditQuery) -> AuditStoreResult<Vec<AuditRecord>>;
+}
+
+/// An [`AuditStore`] keeping the most recent records in memory, forgetting the oldest ones once
+/// it's full.
+#[derive(Clone, Debug)]
+pub struct InMemoryAuditStore {
+    capacity: usize,
+    records: Arc<Mutex<VecDeque<AuditRecord>>>,
+}
+
+impl InMemoryAuditStore {
+    /// Creates a store keeping up to `capacity` records.
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity: capacity.max(1),
+            records: Default::default(),
+        }
+    }
+}
+
+impl Default for InMemoryAuditStore {
+    fn default() -> Self {
+        Self::new(DEFAULT_IN_MEMORY_CAPACITY)
+    }
+}
+
+#[async_trait]
+impl AuditStore for InMemoryAuditStore {
+    async fn record(&self, record: AuditRecord) -> AuditStoreResult<()> {
+        let mut records = self.records.lock().unwrap_or_else(PoisonError::into_inner);
+        if records.len() >= self.capacity {
+            records.pop_front();
+        }
+        records.push_back(record);
+
+        Ok(())
+    }
+
+    async fn query(&self, query: &AuditQuery) -> AuditStoreResult<Vec<AuditRecord>> {
+        let records = self.records.lock().unwrap_or_else(PoisonError::into_inner);
+        let mut selected: Vec<_> = records
+            .iter()
+            .filter(|record| query.matches(record))
+            .cloned()
+            .collect();
+        // Records are pushed as executions finish, so order them by when they were received
+        selected.sort_by(|a, b| b.received_at.cmp(&a.received_at));
+        if let Some(limit) = query.limit {
+            selected.truncate(limit);
+        }
+
+        Ok(selected)
+    }
+}
+
+/// Hands each execution of a server the [`AuditStore`] its record is written to, if the server
+/// has one.
+#[derive(Clone, Default)]
+pub(crate) struct AuditTrail {
+    store: Option<Arc<dyn AuditStore>>,
+}
+
+impl AuditTrail {
+    pub(crate) fn new(store: Arc<dyn AuditStore>) -> Self {
+        Self { store: Some(store) }
+    }
+
+    /// Starts auditing the execution of a request that was just received.
+    pub(crate) fn begin<R>(&self, request: &Request<R>) -> Option<ExecutionAudit>
+    where
+        R: CycloneRequest,
+    {
+        let store = self.store.clone()?;
+
+        Some(ExecutionAudit {
+            store,
+            execution_id: request.payload.execution_id().to_string(),
+            workspace_id: concurrency::workspace_id(request),
+            user_id: user_id(request),
+            handler: request.payload.handler().to_string(),
+            code_hash: code_hash(&request.payload),
+            received_at: Utc::now(),
+            started: Instant::now(),
+        })
+    }
+}
+
+impl fmt::Debug for AuditTrail {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("AuditTrail")
+            .field("enabled", &self.store.is_some())
+            .finish()
+    }
+}
+
+/// The audit record of one execution, written once the execution ends.
+pub(crate) struct ExecutionAudit {
+    store: Arc<dyn AuditStore>,
+    execution_id: String,
+    workspace_id: Option<String>,
+    user_id: Option<String>,
+    handler: String,
+    code_hash: String,
+    received_at: DateTime<Utc>,
+    started: Instant,
+}
+
+impl ExecutionAudit {
+    /// Writes the record of the execution in the background, so that a slow store doesn't hold up
+    /// the server.
+    pub(crate) fn finish(self, kind: &str, status: AuditStatus) {
+        let record = AuditRecord {
+            execution_id: self.execution_id,
+            workspace_id: self.workspace_id,
+            user_id: self.user_id,
+            kind: kind.to_string(),
+            handler: self.handler,
+            code_hash: self.code_hash,
+            received_at: self.received_at,
+            duration_ms: self.started.elapsed().as_millis() as u64,
+            status,
+        };
+        let store = self.store;
+
+        tokio::spawn(async move {
+            let execution_id = record.execution_id.clone();
+            if let Err(err) = store.record(record).await {
+                error!(error = ?err, execution_id, "failed to write execution audit record");
+            }
+        });
+    }
+}
+
+impl fmt::Debug for ExecutionAudit {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ExecutionAudit")
+            .field("execution_id", &self.execution_id)
+            .field("workspace_id", &self.workspace_id)
+            .field("user_id", &self.user_id)
+            .field("handler", &self.handler)
+            .field("code_hash", &self.code_hash)
+            .field("received_at", &self.received_at)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Hashes the code of a request's function along with the code of its before functions, which
+/// run in the same process.
+fn code_hash<R: CycloneRequest>(request: &R) -> String {
+    let mut hasher = blake3::Hasher::new();
+    hasher.update(request.code().as_bytes());
+    for before in request.before() {
+        hasher.update(b"\0");
+        hasher.update(before.code_base64.as_bytes());
+    }
+    hasher.finalize().to_hex().to_string()
+}
+
+/// Returns the user id carried in a request's headers, if any.
+fn user_id<T>(request: &Request<T>) -> Option<String> {
+    request
+        .headers
+        .as_ref()?
+        .get(USER_ID_HEADER_KEY)?
+        .iter()
+        .next()
+        .cloned()
+}
@@ -0,0 +1,101 @@
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex, PoisonError},
+};
+
+use nats_subscriber::Request;
+use telemetry::prelude::*;
+use tokio::sync::{OwnedSemaphorePermit, Semaphore};
+use veritech_core::WORKSPACE_ID_HEADER_KEY;
+
+/// Limits how many executions of each workspace run at once, so that one busy workspace can't
+/// starve the others of cyclone instances. Executions beyond the limit wait for a free slot in
+/// the order they arrived.
+#[derive(Clone, Debug, Default)]
+pub(crate) struct WorkspaceLimiter {
+    max_per_workspace: Option<usize>,
+    workspaces: Arc<Mutex<HashMap<String, Arc<Semaphore>>>>,
+}
+
+impl WorkspaceLimiter {
+    /// Creates a limiter allowing up to `max_per_workspace` concurrent executions per workspace.
+    /// `None` doesn't limit executions at all.
+    pub(crate) fn new(max_per_workspace: Option<usize>) -> Self {
+        Self {
+            max_per_workspace: max_per_workspace.map(|max| max.max(1)),
+            workspaces: Default::default(),
+        }
+    }
+
+    /// Waits for a free slot in the workspace, which is held until the returned permit is dropped.
+    /// Executions without a workspace id are never limited.
+    pub(crate) async fn acquire(&self, workspace_id: Option<&str>) -> Option<WorkspacePermit> {
+        let (max_per_workspace, workspace_id) = match (self.max_per_workspace, workspace_id) {
+            (Some(max_per_workspace), Some(workspace_id)) => (max_per_workspace, workspace_id),
+            _ => return None,
+        };
+
+        let semaphore = self
+            .workspaces
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .entry(workspace_id.to_string())
+            .or_insert_with(|| Arc::new(Semaphore::new(max_per_workspace)))
+            .clone();
+        if semaphore.available_permits() == 0 {
+            debug!(
+                workspace_id,
+                max_per_workspace, "workspace is at its concurrency limit, queueing execution"
+            );
+        }
+        let permit = semaphore
+            .acquire_owned()
+            .await
+            .expect("workspace semaphores are never closed");
+
+        Some(WorkspacePermit {
+            limiter: self.clone(),
+            workspace_id: workspace_id.to_string(),
+            permit: Some(permit),
+        })
+    }
+}
+
+/// A slot in a workspace's limit, freed when dropped.
+#[derive(Debug)]
+pub(crate) struct WorkspacePermit {
+    limiter: WorkspaceLimiter,
+    workspace_id: String,
+    permit: Option<OwnedSemaphorePermit>,
+}
+
+impl Drop for WorkspacePermit {
+    fn drop(&mut self) {
+        drop(self.permit.take());
+
+        // Forget the workspace's semaphore once nobody holds or waits on it, so that the map only
+        // grows with the number of busy workspaces
+        let mut workspaces = self
+            .limiter
+            .workspaces
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner);
+        if workspaces
+            .get(&self.workspace_id)
+            .map_or(false, |semaphore| Arc::strong_count(semaphore) == 1)
+        {
+            workspaces.remove(&self.workspace_id);
+        }
+    }
+}
+
+/// Returns the workspace id carried in a request's headers, if any.
+pub(crate) fn workspace_id<T>(request: &Request<T>) -> Option<String> {
+    request
+        .headers
+        .as_ref()?
+        .get(WORKSPACE_ID_HEADER_KEY)?
+        .iter()
+        .next()
+        .cloned()
+}
@@ -9,9 +9,9 @@ use buck2_resources::Buck2Resources;
 use deadpool_cyclone::{
     instance::cyclone::{
         LocalHttpInstance, LocalHttpInstanceSpec, LocalHttpSocketStrategy, LocalUdsInstance,
-        LocalUdsInstanceSpec, LocalUdsSocketStrategy,
+        LocalUdsInstanceSpec, LocalUdsSocketStrategy, RemoteHttpInstance, RemoteHttpInstanceSpec,
     },
-    Instance,
+    ExecutionLimits, Instance, LangServers, SandboxProfiles, WarmPoolConfig,
 };
 use derive_builder::Builder;
 use serde::{Deserialize, Serialize};
@@ -44,12 +44,51 @@ impl ConfigError {
 
 type Result<T> = std::result::Result<T, ConfigError>;
 
+const DEFAULT_GRACEFUL_SHUTDOWN_TIMEOUT_SECS: u64 = 30;
+
 #[derive(Debug, Builder)]
 pub struct Config {
     #[builder(default = "NatsConfig::default()")]
     nats: NatsConfig,
 
     cyclone_spec: CycloneSpec,
+
+    #[builder(default = "default_graceful_shutdown_timeout()")]
+    graceful_shutdown_timeout: Duration,
+
+    #[builder(default)]
+    max_concurrent_executions_per_workspace: Option<usize>,
+
+    #[builder(default)]
+    decryption_key_path: Option<PathBuf>,
+
+    #[builder(default)]
+    sharding: Option<ShardConfig>,
+
+    #[builder(default)]
+    warm_pool: WarmPoolConfig,
+}
+
+/// Which shards of the veritech subjects a server serves, when clients split requests across
+/// shards by workspace (see [`veritech_core::shard_for_workspace`]).
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct ShardConfig {
+    /// How many shards requests are split across, which must match the clients' shard count.
+    pub count: u32,
+    /// The shards this server subscribes to, or every shard if empty.
+    #[serde(default)]
+    pub shards: Vec<u32>,
+}
+
+impl ShardConfig {
+    /// Returns the shards this server subscribes to.
+    pub fn served_shards(&self) -> Vec<u32> {
+        if self.shards.is_empty() {
+            (0..self.count).collect()
+        } else {
+            self.shards.clone()
+        }
+    }
 }
 
 #[remain::sorted]
@@ -57,30 +96,52 @@ pub struct Config {
 pub enum CycloneSpec {
     LocalHttp(LocalHttpInstanceSpec),
     LocalUds(LocalUdsInstanceSpec),
+    RemoteHttp(RemoteHttpInstanceSpec),
 }
 
 impl StandardConfig for Config {
     type Builder = ConfigBuilder;
 }
 
-#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+#[derive(Clone, Debug, Deserialize, Serialize)]
 pub struct ConfigFile {
     pub nats: NatsConfig,
     pub cyclone: CycloneConfig,
+    #[serde(default = "default_graceful_shutdown_timeout_secs")]
+    pub graceful_shutdown_timeout_secs: u64,
+    #[serde(default)]
+    pub max_concurrent_executions_per_workspace: Option<usize>,
+    #[serde(default)]
+    pub sharding: Option<ShardConfig>,
+    #[serde(default)]
+    pub warm_pool: WarmPoolConfig,
+}
+
+impl Default for ConfigFile {
+    fn default() -> Self {
+        Self {
+            nats: Default::default(),
+            cyclone: Default::default(),
+            graceful_shutdown_timeout_secs: default_graceful_shutdown_timeout_secs(),
+            max_concurrent_executions_per_workspace: None,
+            sharding: None,
+            warm_pool: Default::default(),
+        }
+    }
 }
 
 impl ConfigFile {
     pub fn default_local_http() -> Self {
         Self {
-            nats: Default::default(),
             cyclone: CycloneConfig::default_local_http(),
+            ..Default::default()
         }
     }
 
     pub fn default_local_uds() -> Self {
         Self {
-            nats: Default::default(),
             cyclone: CycloneConfig::default_local_uds(),
+            ..Default::default()
         }
     }
 }
@@ -96,8 +157,16 @@ impl TryFrom<ConfigFile> for Config {
         detect_and_configure_development(&mut value)?;
 
         let mut config = Config::builder();
+        config.decryption_key_path(Some(PathBuf::from(
+            value.cyclone.cyclone_decryption_key_path(),
+        )));
         config.nats(value.nats);
         config.cyclone_spec(value.cyclone.try_into()?);
+        config.graceful_shutdown_timeout(Duration::from_secs(value.graceful_shutdown_timeout_secs));
+        config
+            .max_concurrent_executions_per_workspace(value.max_concurrent_executions_per_workspace);
+        config.sharding(value.sharding);
+        config.warm_pool(value.warm_pool);
         config.build().map_err(Into::into)
     }
 }
@@ -119,6 +188,31 @@ impl Config {
         self.nats.subject_prefix.as_deref()
     }
 
+    /// Gets how long a shutting down server waits for in-flight executions to finish.
+    pub fn graceful_shutdown_timeout(&self) -> Duration {
+        self.graceful_shutdown_timeout
+    }
+
+    /// Gets how many executions of a single workspace may run at once, if they are limited.
+    pub fn max_concurrent_executions_per_workspace(&self) -> Option<usize> {
+        self.max_concurrent_executions_per_workspace
+    }
+
+    /// Gets the path to cyclone's decryption key, used to open encrypted request payloads.
+    pub fn decryption_key_path(&self) -> Option<&Path> {
+        self.decryption_key_path.as_deref()
+    }
+
+    /// Gets which shards of the veritech subjects the server serves, if requests are sharded.
+    pub fn sharding(&self) -> Option<&ShardConfig> {
+        self.sharding.as_ref()
+    }
+
+    /// Gets how the cyclone pool is kept warm.
+    pub fn warm_pool(&self) -> &WarmPoolConfig {
+        &self.warm_pool
+    }
+
     // Consumes into a [`CycloneSpec`].
     pub fn into_cyclone_spec(self) -> CycloneSpec {
         self.cyclone_spec
@@ -164,6 +258,9 @@ pub enum CycloneConfig {
         cyclone_decryption_key_path: String,
         #[serde(default = "default_lang_server_cmd_path")]
         lang_server_cmd_path: String,
+        /// Language servers running functions of other languages than JavaScript, by language.
+        #[serde(default)]
+        lang_servers: LangServers,
         #[serde(default)]
         socket_strategy: LocalHttpSocketStrategy,
         #[serde(default)]
@@ -176,6 +273,10 @@ pub enum CycloneConfig {
         resolver: bool,
         #[serde(default = "default_enable_endpoint")]
         action: bool,
+        #[serde(default)]
+        execution_limits: ExecutionLimits,
+        #[serde(default)]
+        sandbox_profiles: SandboxProfiles,
     },
     LocalUds {
         #[serde(default = "default_cyclone_cmd_path")]
@@ -184,6 +285,9 @@ pub enum CycloneConfig {
         cyclone_decryption_key_path: String,
         #[serde(default = "default_lang_server_cmd_path")]
         lang_server_cmd_path: String,
+        /// Language servers running functions of other languages than JavaScript, by language.
+        #[serde(default)]
+        lang_servers: LangServers,
         #[serde(default)]
         socket_strategy: LocalUdsSocketStrategy,
         #[serde(default)]
@@ -196,6 +300,22 @@ pub enum CycloneConfig {
         resolver: bool,
         #[serde(default = "default_enable_endpoint")]
         action: bool,
+        #[serde(default)]
+        execution_limits: ExecutionLimits,
+        #[serde(default)]
+        sandbox_profiles: SandboxProfiles,
+    },
+    /// Cyclone servers run as a separate fleet, which veritech reaches over the network.
+    RemoteHttp {
+        host: String,
+        #[serde(default = "default_remote_port")]
+        port: u16,
+        #[serde(default)]
+        tls: bool,
+        #[serde(default)]
+        bearer_token: Option<String>,
+        #[serde(default = "default_cyclone_decryption_key_path")]
+        cyclone_decryption_key_path: String,
     },
 }
 
@@ -205,12 +325,15 @@ impl CycloneConfig {
             cyclone_cmd_path: default_cyclone_cmd_path(),
             cyclone_decryption_key_path: default_cyclone_decryption_key_path(),
             lang_server_cmd_path: default_lang_server_cmd_path(),
+            lang_servers: Default::default(),
             socket_strategy: Default::default(),
             watch_timeout: Default::default(),
             limit_requets: default_limit_requests(),
             ping: default_enable_endpoint(),
             resolver: default_enable_endpoint(),
             action: default_enable_endpoint(),
+            execution_limits: Default::default(),
+            sandbox_profiles: Default::default(),
         }
     }
 
@@ -219,23 +342,28 @@ impl CycloneConfig {
             cyclone_cmd_path: default_cyclone_cmd_path(),
             cyclone_decryption_key_path: default_cyclone_decryption_key_path(),
             lang_server_cmd_path: default_lang_server_cmd_path(),
+            lang_servers: Default::default(),
             socket_strategy: Default::default(),
             watch_timeout: Default::default(),
             limit_requets: default_limit_requests(),
             ping: default_enable_endpoint(),
             resolver: default_enable_endpoint(),
             action: default_enable_endpoint(),
+            execution_limits: Default::default(),
+            sandbox_profiles: Default::default(),
         }
     }
 
-    pub fn cyclone_cmd_path(&self) -> &str {
+    /// Gets the path to the `cyclone` program, which only local cyclone instances have.
+    pub fn cyclone_cmd_path(&self) -> Option<&str> {
         match self {
             CycloneConfig::LocalUds {
                 cyclone_cmd_path, ..
-            } => cyclone_cmd_path,
+            } => Some(cyclone_cmd_path),
             CycloneConfig::LocalHttp {
                 cyclone_cmd_path, ..
-            } => cyclone_cmd_path,
+            } => Some(cyclone_cmd_path),
+            CycloneConfig::RemoteHttp { .. } => None,
         }
     }
 
@@ -247,6 +375,7 @@ impl CycloneConfig {
             CycloneConfig::LocalHttp {
                 cyclone_cmd_path, ..
             } => *cyclone_cmd_path = value,
+            CycloneConfig::RemoteHttp { .. } => {}
         };
     }
 
@@ -260,6 +389,10 @@ impl CycloneConfig {
                 cyclone_decryption_key_path,
                 ..
             } => cyclone_decryption_key_path,
+            CycloneConfig::RemoteHttp {
+                cyclone_decryption_key_path,
+                ..
+            } => cyclone_decryption_key_path,
         }
     }
 
@@ -273,19 +406,25 @@ impl CycloneConfig {
                 cyclone_decryption_key_path,
                 ..
             } => *cyclone_decryption_key_path = value,
+            CycloneConfig::RemoteHttp {
+                cyclone_decryption_key_path,
+                ..
+            } => *cyclone_decryption_key_path = value,
         };
     }
 
-    pub fn lang_server_cmd_path(&self) -> &str {
+    /// Gets the path to the language server program, which only local cyclone instances have.
+    pub fn lang_server_cmd_path(&self) -> Option<&str> {
         match self {
             CycloneConfig::LocalUds {
                 lang_server_cmd_path,
                 ..
-            } => lang_server_cmd_path,
+            } => Some(lang_server_cmd_path),
             CycloneConfig::LocalHttp {
                 lang_server_cmd_path,
                 ..
-            } => lang_server_cmd_path,
+            } => Some(lang_server_cmd_path),
+            CycloneConfig::RemoteHttp { .. } => None,
         }
     }
 
@@ -299,6 +438,7 @@ impl CycloneConfig {
                 lang_server_cmd_path,
                 ..
             } => *lang_server_cmd_path = value,
+            CycloneConfig::RemoteHttp { .. } => {}
         };
     }
 
@@ -306,6 +446,7 @@ impl CycloneConfig {
         match self {
             CycloneConfig::LocalUds { limit_requets, .. } => *limit_requets = value.into(),
             CycloneConfig::LocalHttp { limit_requets, .. } => *limit_requets = value.into(),
+            CycloneConfig::RemoteHttp { .. } => {}
         };
     }
 
@@ -313,6 +454,7 @@ impl CycloneConfig {
         match self {
             CycloneConfig::LocalUds { ping, .. } => *ping = value,
             CycloneConfig::LocalHttp { ping, .. } => *ping = value,
+            CycloneConfig::RemoteHttp { .. } => {}
         };
     }
 
@@ -320,6 +462,7 @@ impl CycloneConfig {
         match self {
             CycloneConfig::LocalUds { resolver, .. } => *resolver = value,
             CycloneConfig::LocalHttp { resolver, .. } => *resolver = value,
+            CycloneConfig::RemoteHttp { .. } => {}
         };
     }
 
@@ -327,6 +470,7 @@ impl CycloneConfig {
         match self {
             CycloneConfig::LocalUds { action, .. } => *action = value,
             CycloneConfig::LocalHttp { action, .. } => *action = value,
+            CycloneConfig::RemoteHttp { .. } => {}
         };
     }
 }
@@ -346,12 +490,15 @@ impl TryFrom<CycloneConfig> for CycloneSpec {
                 cyclone_cmd_path,
                 cyclone_decryption_key_path,
                 lang_server_cmd_path,
+                lang_servers,
                 socket_strategy,
                 watch_timeout,
                 limit_requets,
                 ping,
                 resolver,
                 action,
+                execution_limits,
+                sandbox_profiles,
             } => {
                 let mut builder = LocalUdsInstance::spec();
                 builder
@@ -361,6 +508,7 @@ impl TryFrom<CycloneConfig> for CycloneSpec {
                 builder
                     .try_lang_server_cmd_path(lang_server_cmd_path)
                     .map_err(ConfigError::cyclone_spec_build)?;
+                builder.lang_servers(lang_servers);
                 builder.socket_strategy(socket_strategy);
                 if let Some(watch_timeout) = watch_timeout {
                     builder.watch_timeout(watch_timeout);
@@ -375,6 +523,8 @@ impl TryFrom<CycloneConfig> for CycloneSpec {
                 if action {
                     builder.action();
                 }
+                builder.execution_limits(execution_limits);
+                builder.sandbox_profiles(sandbox_profiles);
 
                 Ok(Self::LocalUds(
                     builder.build().map_err(ConfigError::cyclone_spec_build)?,
@@ -384,12 +534,15 @@ impl TryFrom<CycloneConfig> for CycloneSpec {
                 cyclone_cmd_path,
                 cyclone_decryption_key_path,
                 lang_server_cmd_path,
+                lang_servers,
                 socket_strategy,
                 watch_timeout,
                 limit_requets,
                 ping,
                 resolver,
                 action,
+                execution_limits,
+                sandbox_profiles,
             } => {
                 let mut builder = LocalHttpInstance::spec();
                 builder
@@ -399,6 +552,7 @@ impl TryFrom<CycloneConfig> for CycloneSpec {
                 builder
                     .try_lang_server_cmd_path(lang_server_cmd_path)
                     .map_err(ConfigError::cyclone_spec_build)?;
+                builder.lang_servers(lang_servers);
                 builder.socket_strategy(socket_strategy);
                 if let Some(watch_timeout) = watch_timeout {
                     builder.watch_timeout(watch_timeout);
@@ -413,11 +567,32 @@ impl TryFrom<CycloneConfig> for CycloneSpec {
                 if action {
                     builder.action();
                 }
+                builder.execution_limits(execution_limits);
+                builder.sandbox_profiles(sandbox_profiles);
 
                 Ok(Self::LocalHttp(
                     builder.build().map_err(ConfigError::cyclone_spec_build)?,
                 ))
             }
+            CycloneConfig::RemoteHttp {
+                host,
+                port,
+                tls,
+                bearer_token,
+                cyclone_decryption_key_path: _,
+            } => {
+                let mut builder = RemoteHttpInstance::spec();
+                builder.host(host);
+                builder.port(port);
+                builder.tls(tls);
+                if let Some(bearer_token) = bearer_token {
+                    builder.bearer_token(bearer_token);
+                }
+
+                Ok(Self::RemoteHttp(
+                    builder.build().map_err(ConfigError::cyclone_spec_build)?,
+                ))
+            }
         }
     }
 }
@@ -434,6 +609,10 @@ fn default_lang_server_cmd_path() -> String {
     "/usr/local/bin/lang-js".to_string()
 }
 
+fn default_remote_port() -> u16 {
+    5157
+}
+
 fn default_limit_requests() -> Option<u32> {
     Some(1)
 }
@@ -442,6 +621,14 @@ fn default_enable_endpoint() -> bool {
     true
 }
 
+fn default_graceful_shutdown_timeout_secs() -> u64 {
+    DEFAULT_GRACEFUL_SHUTDOWN_TIMEOUT_SECS
+}
+
+fn default_graceful_shutdown_timeout() -> Duration {
+    Duration::from_secs(DEFAULT_GRACEFUL_SHUTDOWN_TIMEOUT_SECS)
+}
+
 #[allow(clippy::disallowed_methods)] // Used to determine if running in development
 pub fn detect_and_configure_development(config: &mut ConfigFile) -> Result<()> {
     if env::var("BUCK_RUN_BUILD_ID").is_ok() || env::var("BUCK_BUILD_ID").is_ok() {
@@ -0,0 +1,73 @@
+use std::fmt;
+
+use nats_subscriber::RawMessage;
+use si_data_nats::{HeaderMap, NatsClient};
+use telemetry::prelude::*;
+use veritech_core::{
+    nats_dead_letter_subject, DEAD_LETTER_ERROR_HEADER_KEY, DEAD_LETTER_REPLY_MAILBOX_HEADER_KEY,
+    DEAD_LETTER_SUBJECT_HEADER_KEY,
+};
+
+/// Republishes requests the server could not execute on the dead letter subject for their kind,
+/// so that they can be inspected and replayed later.
+///
+/// A dead letter carries the original payload untouched, which stays encrypted or compressed if
+/// the request was. The original headers are kept and the request's subject, reply mailbox and
+/// error are added as `X-Dead-Letter-*` headers.
+#[derive(Clone, Debug)]
+pub(crate) struct DeadLetterQueue {
+    nats: NatsClient,
+    subject: String,
+}
+
+impl DeadLetterQueue {
+    pub(crate) fn new(nats: NatsClient, subject_prefix: Option<&str>, kind: &str) -> Self {
+        Self {
+            nats,
+            subject: nats_dead_letter_subject(subject_prefix, kind),
+        }
+    }
+
+    pub(crate) async fn publish(&self, message: RawMessage, error: &dyn fmt::Display) {
+        let mut headers: Vec<(String, String)> = message
+            .headers
+            .iter()
+            .flat_map(|headers| {
+                headers.keys().flat_map(move |key| {
+                    headers
+                        .get(key)
+                        .into_iter()
+                        .flatten()
+                        .map(move |value| (key.to_string(), value.to_string()))
+                })
+            })
+            .collect();
+        headers.push((DEAD_LETTER_SUBJECT_HEADER_KEY.to_string(), message.subject));
+        if let Some(reply_mailbox) = message.reply_mailbox {
+            headers.push((
+                DEAD_LETTER_REPLY_MAILBOX_HEADER_KEY.to_string(),
+                reply_mailbox,
+            ));
+        }
+        // Header values can't span lines
+        headers.push((
+            DEAD_LETTER_ERROR_HEADER_KEY.to_string(),
+            error.to_string().replace(['\r', '\n'], " "),
+        ));
+        let headers: HeaderMap = headers.iter().collect();
+
+        warn!(subject = %self.subject, %error, "publishing request to the dead letter queue");
+        if let Err(err) = self
+            .nats
+            .publish_with_reply_or_headers(
+                &self.subject,
+                None::<String>,
+                Some(&headers),
+                message.payload,
+            )
+            .await
+        {
+            error!(error = ?err, subject = %self.subject, "failed to publish dead letter");
+        }
+    }
+}
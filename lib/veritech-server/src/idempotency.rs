@@ -0,0 +1,161 @@
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex, PoisonError},
+    time::Duration,
+};
+
+use telemetry::prelude::*;
+use tokio::time::Instant;
+
+/// How long the result of a successful execution is kept to answer duplicates of its request.
+const RESULT_TTL: Duration = Duration::from_secs(60);
+
+/// Tracks executions by their execution id, so that a request that is resent or redelivered
+/// doesn't run its function a second time.
+///
+/// A duplicate of a running execution is dropped when it shares the original's reply mailbox,
+/// as the original's result will answer it. A duplicate of an execution that succeeded in the
+/// last [`RESULT_TTL`] is answered with the recorded result. Failed executions are forgotten
+/// straight away, so that retrying them runs the function again.
+#[derive(Clone, Debug, Default)]
+pub(crate) struct IdempotencyTracker {
+    executions: Arc<Mutex<HashMap<String, TrackedExecution>>>,
+}
+
+#[derive(Debug)]
+enum TrackedExecution {
+    Running {
+        reply_mailbox: String,
+    },
+    Succeeded {
+        result: RecordedResult,
+        expires_at: Instant,
+    },
+}
+
+/// A published result, kept to answer duplicate requests with.
+#[derive(Clone, Debug)]
+pub(crate) struct RecordedResult {
+    /// The serialized result, uncompressed.
+    pub(crate) payload: Vec<u8>,
+    /// The message id the result was published with, so clients can recognize a copy of it.
+    pub(crate) message_id: String,
+}
+
+/// What to do with a request, as decided by [`IdempotencyTracker::admit`].
+#[derive(Debug)]
+pub(crate) enum Admission {
+    /// The request is a duplicate of a running execution which will answer it.
+    Duplicate,
+    /// The request is a duplicate of a successful execution, whose result answers it.
+    Replay(RecordedResult),
+    /// The request should run, recording its result with the given recorder.
+    Run(ResultRecorder),
+}
+
+impl IdempotencyTracker {
+    /// Decides whether a request with the given execution id and reply mailbox should run.
+    /// Requests without an execution id always run.
+    pub(crate) fn admit(&self, execution_id: &str, reply_mailbox: &str) -> Admission {
+        if execution_id.is_empty() {
+            return Admission::Run(ResultRecorder::default());
+        }
+
+        let now = Instant::now();
+        let mut executions = self
+            .executions
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner);
+        executions.retain(|_, execution| match execution {
+            TrackedExecution::Running { .. } => true,
+            TrackedExecution::Succeeded { expires_at, .. } => *expires_at > now,
+        });
+
+        match executions.get(execution_id) {
+            Some(TrackedExecution::Running {
+                reply_mailbox: running_reply_mailbox,
+            }) => {
+                if running_reply_mailbox == reply_mailbox {
+                    debug!(execution_id, "dropping duplicate of a running execution");
+                    return Admission::Duplicate;
+                }
+                // The running execution can't answer another mailbox, so let this one run
+                // untracked
+                debug!(
+                    execution_id,
+                    "running duplicate of a running execution with a different reply mailbox"
+                );
+                return Admission::Run(ResultRecorder::default());
+            }
+            Some(TrackedExecution::Succeeded { result, .. }) => {
+                debug!(execution_id, "answering duplicate of a finished execution");
+                return Admission::Replay(result.clone());
+            }
+            None => {}
+        }
+
+        executions.insert(
+            execution_id.to_string(),
+            TrackedExecution::Running {
+                reply_mailbox: reply_mailbox.to_string(),
+            },
+        );
+        Admission::Run(ResultRecorder {
+            tracker: Some(self.clone()),
+            execution_id: execution_id.to_string(),
+        })
+    }
+}
+
+/// Records the result of an admitted execution. The execution stops being tracked when the
+/// recorder is dropped, unless it recorded a successful result.
+#[derive(Debug, Default)]
+pub(crate) struct ResultRecorder {
+    tracker: Option<IdempotencyTracker>,
+    execution_id: String,
+}
+
+impl ResultRecorder {
+    /// Keeps a successful result to answer duplicates of the execution's request with.
+    pub(crate) fn record_success(&self, payload: &[u8], message_id: &str) {
+        let tracker = match &self.tracker {
+            Some(tracker) => tracker,
+            None => return,
+        };
+
+        tracker
+            .executions
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .insert(
+                self.execution_id.clone(),
+                TrackedExecution::Succeeded {
+                    result: RecordedResult {
+                        payload: payload.to_vec(),
+                        message_id: message_id.to_string(),
+                    },
+                    expires_at: Instant::now() + RESULT_TTL,
+                },
+            );
+    }
+}
+
+impl Drop for ResultRecorder {
+    fn drop(&mut self) {
+        let tracker = match &self.tracker {
+            Some(tracker) => tracker,
+            None => return,
+        };
+
+        let mut executions = tracker
+            .executions
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner);
+        if matches!(
+            executions.get(&self.execution_id),
+            Some(TrackedExecution::Running { .. })
+        ) {
+            executions.remove(&self.execution_id);
+        }
+    }
+}
@@ -0,0 +1,225 @@
+use std::{
+    collections::HashMap,
+    future::Future,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc, Mutex, PoisonError,
+    },
+    time::Duration,
+};
+
+use deadpool_cyclone::{
+    FunctionErrorKind, FunctionResult, FunctionResultFailure, FunctionResultFailureError,
+};
+use si_data_nats::NatsClient;
+use telemetry::prelude::*;
+use tokio::time::{self, Instant};
+use veritech_core::{reply_mailbox_for_heartbeat, HEARTBEAT_INTERVAL};
+
+use crate::{
+    concurrency::WorkspaceLimiter,
+    idempotency::{Admission, IdempotencyTracker, RecordedResult, ResultRecorder},
+    server::timestamp,
+    Publisher,
+};
+
+/// How often draining checks whether the remaining executions have finished.
+const DRAIN_POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Tracks the executions a server has accepted but not yet answered, so that a shutdown can wait
+/// for them to finish and answer for the ones that don't.
+#[derive(Clone, Debug, Default)]
+pub(crate) struct InFlightExecutions {
+    next_id: Arc<AtomicU64>,
+    executions: Arc<Mutex<HashMap<u64, InFlightExecution>>>,
+    limiter: WorkspaceLimiter,
+    idempotency: IdempotencyTracker,
+}
+
+#[derive(Clone, Debug)]
+struct InFlightExecution {
+    execution_id: String,
+    reply_mailbox: String,
+}
+
+impl InFlightExecutions {
+    pub(crate) fn new(limiter: WorkspaceLimiter) -> Self {
+        Self {
+            limiter,
+            ..Default::default()
+        }
+    }
+
+    /// Spawns the task that handles a request, tracking it until the task completes. The task
+    /// first waits for a slot in its workspace's concurrency limit, if the request names one.
+    ///
+    /// While the task waits and runs, heartbeats are published on the request's reply mailbox so
+    /// the client can tell a queued or running execution from a lost server. Requests without a
+    /// reply mailbox can't be answered, so they are not tracked.
+    ///
+    /// A request that duplicates an execution the server already knows of doesn't run again (see
+    /// [`IdempotencyTracker`]), so `task` is given the recorder for the result it publishes.
+    pub(crate) fn spawn<F>(
+        &self,
+        nats: &NatsClient,
+        execution_id: &str,
+        reply_mailbox: Option<&str>,
+        workspace_id: Option<String>,
+        task: impl FnOnce(ResultRecorder) -> F,
+    ) where
+        F: Future<Output = ()> + Send + 'static,
+    {
+        let result_recorder = match reply_mailbox {
+            Some(reply_mailbox) => match self.idempotency.admit(execution_id, reply_mailbox) {
+                Admission::Duplicate => return,
+                Admission::Replay(result) => {
+                    tokio::spawn(replay_result(
+                        nats.clone(),
+                        reply_mailbox.to_string(),
+                        result,
+                    ));
+                    return;
+                }
+                Admission::Run(result_recorder) => result_recorder,
+            },
+            None => ResultRecorder::default(),
+        };
+
+        let limiter = self.limiter.clone();
+        let task = task(result_recorder);
+        let task = async move {
+            let _permit = limiter.acquire(workspace_id.as_deref()).await;
+            task.await;
+        };
+
+        let reply_mailbox = match reply_mailbox {
+            Some(reply_mailbox) => reply_mailbox,
+            None => {
+                tokio::spawn(task);
+                return;
+            }
+        };
+
+        let guard = self.start(execution_id, reply_mailbox);
+        let heartbeats =
+            publish_heartbeats(nats.clone(), reply_mailbox_for_heartbeat(reply_mailbox));
+        tokio::spawn(async move {
+            tokio::select! {
+                _ = task => {}
+                _ = heartbeats => {}
+            }
+            drop(guard);
+        });
+    }
+
+    /// Waits up to `timeout` for every in-flight execution to finish, then publishes a failure
+    /// result on the reply mailbox of each execution that is still running.
+    pub(crate) async fn drain(&self, nats: &NatsClient, timeout: Duration) {
+        let deadline = Instant::now() + timeout;
+        loop {
+            let remaining = self.len();
+            if remaining == 0 {
+                info!("all in-flight executions have finished");
+                return;
+            }
+            if Instant::now() >= deadline {
+                break;
+            }
+            debug!(remaining, "waiting for in-flight executions to finish");
+            time::sleep(DRAIN_POLL_INTERVAL.min(deadline - Instant::now())).await;
+        }
+
+        let stranded: Vec<InFlightExecution> = self
+            .executions
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .drain()
+            .map(|(_, execution)| execution)
+            .collect();
+        warn!(
+            count = stranded.len(),
+            "executions still running at shutdown, answering with failures"
+        );
+        for execution in stranded {
+            let publisher = Publisher::new(nats, &execution.reply_mailbox);
+            if let Err(err) = publisher.finalize_output().await {
+                error!(error = ?err, "failed to finalize output of stranded execution");
+            }
+            let result = FunctionResult::<serde_json::Value>::Failure(FunctionResultFailure {
+                execution_id: execution.execution_id,
+                error: FunctionResultFailureError {
+                    kind: "veritechServer".to_string(),
+                    message: "veritech server shut down before the execution finished".to_string(),
+                    category: FunctionErrorKind::TransportError,
+                },
+                timestamp: timestamp(),
+            });
+            if let Err(err) = publisher.publish_result(&result).await {
+                error!(error = ?err, "failed to publish result of stranded execution");
+            }
+        }
+    }
+
+    fn start(&self, execution_id: &str, reply_mailbox: &str) -> InFlightGuard {
+        let id = self.next_id.fetch_add(1, Ordering::Relaxed);
+        self.executions
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .insert(
+                id,
+                InFlightExecution {
+                    execution_id: execution_id.to_string(),
+                    reply_mailbox: reply_mailbox.to_string(),
+                },
+            );
+        InFlightGuard {
+            executions: self.clone(),
+            id,
+        }
+    }
+
+    fn len(&self) -> usize {
+        self.executions
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .len()
+    }
+}
+
+/// Removes an execution from [`InFlightExecutions`] when its task completes.
+struct InFlightGuard {
+    executions: InFlightExecutions,
+    id: u64,
+}
+
+impl Drop for InFlightGuard {
+    fn drop(&mut self) {
+        self.executions
+            .executions
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .remove(&self.id);
+    }
+}
+
+/// Answers a duplicate request with the result recorded for its execution.
+async fn replay_result(nats: NatsClient, reply_mailbox: String, result: RecordedResult) {
+    let publisher = Publisher::new(&nats, &reply_mailbox);
+    if let Err(err) = publisher.finalize_output().await {
+        error!(error = ?err, "failed to finalize output of replayed execution");
+    }
+    if let Err(err) = publisher.replay_result(&result).await {
+        error!(error = ?err, "failed to publish result of replayed execution");
+    }
+}
+
+/// Publishes a heartbeat every [`HEARTBEAT_INTERVAL`], forever.
+async fn publish_heartbeats(nats: NatsClient, subject: String) {
+    let mut interval = time::interval(HEARTBEAT_INTERVAL);
+    loop {
+        interval.tick().await;
+        if let Err(err) = nats.publish(&subject, vec![]).await {
+            warn!(error = ?err, %subject, "failed to publish heartbeat");
+        }
+    }
+}
@@ -1,12 +1,28 @@
+mod audit;
+mod concurrency;
 mod config;
+mod dead_letter;
+mod idempotency;
+mod in_flight;
+mod metrics;
+mod middleware;
+mod payload;
 mod publisher;
 mod server;
+mod stats;
 mod subscriber;
 
 pub use crate::{
+    audit::{
+        AuditQuery, AuditRecord, AuditStatus, AuditStore, AuditStoreError, AuditStoreResult,
+        InMemoryAuditStore,
+    },
     config::{
         detect_and_configure_development, Config, ConfigBuilder, ConfigError, ConfigFile,
-        CycloneSpec, CycloneStream, StandardConfig, StandardConfigFile,
+        CycloneSpec, CycloneStream, ShardConfig, StandardConfig, StandardConfigFile,
+    },
+    middleware::{
+        FunctionExecution, FunctionExecutionMiddleware, MiddlewareError, MiddlewareResult,
     },
     server::{Server, ServerError, VeritechShutdownHandle},
 };
@@ -0,0 +1,123 @@
+use std::time::Instant;
+
+use deadpool_cyclone::{FunctionErrorKind, FunctionResult};
+use telemetry::prelude::*;
+
+use crate::{
+    audit::{AuditStatus, ExecutionAudit},
+    stats::{ExecutionStats, StatsTracker},
+};
+
+/// Measures one execution on the server, from the moment its request is received until its
+/// result is published. The execution's audit record, if it has one, is written along with its
+/// result, and the server's stats follow the execution, if it's counted in them.
+#[derive(Debug)]
+pub(crate) struct ExecutionMetrics {
+    kind: &'static str,
+    subject_prefix: String,
+    request_bytes: usize,
+    received_at: Instant,
+    checked_out_at: Option<Instant>,
+    recorded: bool,
+    audit: Option<ExecutionAudit>,
+    stats: Option<ExecutionStats>,
+}
+
+impl ExecutionMetrics {
+    pub(crate) fn new(
+        kind: &'static str,
+        subject_prefix: Option<&str>,
+        request_bytes: usize,
+    ) -> Self {
+        Self {
+            kind,
+            subject_prefix: subject_prefix.unwrap_or_default().to_string(),
+            request_bytes,
+            received_at: Instant::now(),
+            checked_out_at: None,
+            recorded: false,
+            audit: None,
+            stats: None,
+        }
+    }
+
+    /// Writes the execution's audit record once its result is recorded.
+    pub(crate) fn with_audit(mut self, audit: Option<ExecutionAudit>) -> Self {
+        self.audit = audit;
+        self
+    }
+
+    /// Counts the execution in the stats of the server, as waiting for a cyclone instance.
+    pub(crate) fn with_stats(mut self, stats: &StatsTracker) -> Self {
+        self.stats = Some(stats.begin(self.kind));
+        self
+    }
+
+    /// The kind of function executed, such as `resolverFunction`.
+    pub(crate) fn kind(&self) -> &'static str {
+        self.kind
+    }
+
+    /// Records how long the request waited for a cyclone instance.
+    pub(crate) fn checked_out(&mut self) {
+        let now = Instant::now();
+        self.checked_out_at = Some(now);
+        if let Some(stats) = &mut self.stats {
+            stats.checked_out();
+        }
+        metric!(
+            histogram.veritech.server.queue_wait_ms =
+                now.duration_since(self.received_at).as_millis() as u64,
+            veritech.kind = self.kind,
+            veritech.subject_prefix = self.subject_prefix.as_str()
+        );
+    }
+
+    /// Records the result published for the execution.
+    pub(crate) fn published<S>(&mut self, result: &FunctionResult<S>, result_bytes: usize) {
+        let status = match result {
+            FunctionResult::Success(_) => AuditStatus::Success,
+            FunctionResult::Failure(failure)
+                if failure.error.category == FunctionErrorKind::Cancelled =>
+            {
+                AuditStatus::Cancelled
+            }
+            FunctionResult::Failure(_) => AuditStatus::Failure,
+        };
+        self.record(status, result_bytes);
+    }
+
+    /// Records an execution that ended without publishing a result. Does nothing if a result was
+    /// already recorded.
+    pub(crate) fn errored(&mut self) {
+        self.record(AuditStatus::Error, 0);
+    }
+
+    fn record(&mut self, status: AuditStatus, result_bytes: usize) {
+        if self.recorded {
+            return;
+        }
+        self.recorded = true;
+
+        let execution_duration_ms = self
+            .checked_out_at
+            .map(|checked_out_at| checked_out_at.elapsed().as_millis() as u64)
+            .unwrap_or_default();
+        metric!(
+            monotonic_counter.veritech.server.executions = 1_u64,
+            histogram.veritech.server.execution_duration_ms = execution_duration_ms,
+            histogram.veritech.server.request_bytes = self.request_bytes as u64,
+            histogram.veritech.server.result_bytes = result_bytes as u64,
+            veritech.kind = self.kind,
+            veritech.subject_prefix = self.subject_prefix.as_str(),
+            veritech.outcome = status.as_str()
+        );
+
+        if let Some(stats) = &mut self.stats {
+            stats.ended(matches!(status, AuditStatus::Failure | AuditStatus::Error));
+        }
+        if let Some(audit) = self.audit.take() {
+            audit.finish(self.kind, status);
+        }
+    }
+}
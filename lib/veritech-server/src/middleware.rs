@@ -0,0 +1,133 @@
+use std::{fmt, sync::Arc};
+
+use async_trait::async_trait;
+use deadpool_cyclone::FunctionResult;
+use serde::{de::DeserializeOwned, Serialize};
+use serde_json::Value;
+use thiserror::Error;
+
+#[remain::sorted]
+#[derive(Debug, Error)]
+pub enum MiddlewareError {
+    #[error("middleware error: {0}")]
+    Middleware(#[source] Box<dyn std::error::Error + Sync + Send + 'static>),
+    /// The execution is not allowed to run. Its client is answered with a failed result carrying
+    /// the message.
+    #[error("execution rejected: {0}")]
+    Rejected(String),
+    #[error("failed to convert {0} for middleware: {1}")]
+    Serde(&'static str, #[source] serde_json::Error),
+}
+
+pub type MiddlewareResult<T> = Result<T, MiddlewareError>;
+
+/// An execution on its way through the server's [`FunctionExecutionMiddleware`].
+///
+/// The request is given as JSON so that middleware can work with every kind of function alike.
+/// Changes to it must keep it a valid request of its kind, or the execution fails.
+#[derive(Clone, Debug)]
+pub struct FunctionExecution {
+    /// The kind of function, such as `resolverFunction` or `actionRun`.
+    pub kind: &'static str,
+    pub execution_id: String,
+    /// The cyclone sandbox profile the function runs under, if any.
+    pub sandbox_profile: Option<String>,
+    pub request: Value,
+}
+
+/// Hooks run by the server around every execution, in the order the middleware was added to the
+/// server, such as to inject secrets into requests or to enforce policies on what runs.
+#[async_trait]
+pub trait FunctionExecutionMiddleware: Send + Sync + 'static {
+    /// Runs before the execution is dispatched to cyclone. The request may be changed, and
+    /// returning [`MiddlewareError::Rejected`] vetoes the execution.
+    async fn before(&self, _execution: &mut FunctionExecution) -> MiddlewareResult<()> {
+        Ok(())
+    }
+
+    /// Runs once the function's result is in, before it is published, and may annotate it. The
+    /// result is given as JSON and fields that aren't part of its kind's result are dropped.
+    async fn after(
+        &self,
+        _execution: &FunctionExecution,
+        _result: &mut Value,
+    ) -> MiddlewareResult<()> {
+        Ok(())
+    }
+}
+
+/// The middleware of a server, run in the order it was added.
+#[derive(Clone, Default)]
+pub(crate) struct MiddlewareChain {
+    middleware: Arc<Vec<Arc<dyn FunctionExecutionMiddleware>>>,
+}
+
+impl MiddlewareChain {
+    pub(crate) fn push(&mut self, middleware: Arc<dyn FunctionExecutionMiddleware>) {
+        Arc::make_mut(&mut self.middleware).push(middleware);
+    }
+
+    /// Runs the `before` hooks on a request, returning the request to dispatch along with the
+    /// execution the `after` hooks are given. Requests pass through untouched without middleware.
+    pub(crate) async fn before<R>(
+        &self,
+        kind: &'static str,
+        execution_id: &str,
+        sandbox_profile: Option<String>,
+        request: R,
+    ) -> MiddlewareResult<(R, Option<String>, Option<FunctionExecution>)>
+    where
+        R: Serialize + DeserializeOwned,
+    {
+        if self.middleware.is_empty() {
+            return Ok((request, sandbox_profile, None));
+        }
+
+        let mut execution = FunctionExecution {
+            kind,
+            execution_id: execution_id.to_string(),
+            sandbox_profile,
+            request: serde_json::to_value(request)
+                .map_err(|err| MiddlewareError::Serde("request", err))?,
+        };
+        for middleware in self.middleware.iter() {
+            middleware.before(&mut execution).await?;
+        }
+        let request = serde_json::from_value(execution.request.clone())
+            .map_err(|err| MiddlewareError::Serde("request", err))?;
+        let sandbox_profile = execution.sandbox_profile.clone();
+
+        Ok((request, sandbox_profile, Some(execution)))
+    }
+
+    /// Runs the `after` hooks on a function's result.
+    pub(crate) async fn after<S>(
+        &self,
+        execution: Option<&FunctionExecution>,
+        result: FunctionResult<S>,
+    ) -> MiddlewareResult<FunctionResult<S>>
+    where
+        S: Serialize + DeserializeOwned,
+    {
+        let execution = match execution {
+            Some(execution) => execution,
+            None => return Ok(result),
+        };
+
+        let mut result =
+            serde_json::to_value(result).map_err(|err| MiddlewareError::Serde("result", err))?;
+        for middleware in self.middleware.iter() {
+            middleware.after(execution, &mut result).await?;
+        }
+
+        serde_json::from_value(result).map_err(|err| MiddlewareError::Serde("result", err))
+    }
+}
+
+impl fmt::Debug for MiddlewareChain {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("MiddlewareChain")
+            .field("len", &self.middleware.len())
+            .finish()
+    }
+}
@@ -0,0 +1,56 @@
+use std::sync::Arc;
+
+use deadpool_cyclone::{DecryptionKey, DecryptionKeyError};
+use nats_subscriber::PayloadDecoder;
+use si_data_nats::HeaderMap;
+use thiserror::Error;
+use veritech_core::ENCRYPTED_PAYLOAD_HEADER_KEY;
+
+#[remain::sorted]
+#[derive(Debug, Error)]
+pub(crate) enum PayloadDecryptionError {
+    #[error("failed to decrypt request payload: {0}")]
+    Decrypt(#[from] DecryptionKeyError),
+    #[error("received an encrypted request but no decryption key is configured")]
+    NoDecryptionKey,
+}
+
+/// Opens request payloads that clients sealed with cyclone's public key, passing every other
+/// payload through untouched.
+#[derive(Clone, Debug, Default)]
+pub(crate) struct PayloadDecryptor {
+    decryption_key: Option<Arc<DecryptionKey>>,
+}
+
+impl PayloadDecryptor {
+    pub(crate) fn new(decryption_key: Option<DecryptionKey>) -> Self {
+        Self {
+            decryption_key: decryption_key.map(Arc::new),
+        }
+    }
+}
+
+impl PayloadDecoder for PayloadDecryptor {
+    fn decode(
+        &self,
+        headers: Option<&HeaderMap>,
+        payload: Vec<u8>,
+    ) -> Result<Vec<u8>, Box<dyn std::error::Error + Send + Sync + 'static>> {
+        let encrypted = headers.map_or(false, |headers| {
+            headers
+                .keys()
+                .any(|key| key == ENCRYPTED_PAYLOAD_HEADER_KEY)
+        });
+        if !encrypted {
+            return Ok(payload);
+        }
+
+        let decryption_key = self
+            .decryption_key
+            .as_ref()
+            .ok_or(PayloadDecryptionError::NoDecryptionKey)?;
+        decryption_key
+            .decrypt(payload)
+            .map_err(|err| PayloadDecryptionError::Decrypt(err).into())
+    }
+}
@@ -1,12 +1,26 @@
-use deadpool_cyclone::{FunctionResult, OutputStream};
+use deadpool_cyclone::{FunctionProgress, FunctionResult, OutputStream};
+use nats_subscriber::{
+    compression::{self, CONTENT_ENCODING_HEADER_KEY, GZIP_ENCODING},
+    Request, MESSAGE_ID_HEADER_KEY,
+};
 use serde::Serialize;
-use si_data_nats::NatsClient;
+use si_data_nats::{HeaderMap, NatsClient};
+use telemetry::prelude::*;
 use thiserror::Error;
-use veritech_core::{reply_mailbox_for_output, reply_mailbox_for_result, FINAL_MESSAGE_HEADER_KEY};
+use uuid::Uuid;
+use veritech_core::{
+    reply_mailbox_for_cancel, reply_mailbox_for_output, reply_mailbox_for_progress,
+    reply_mailbox_for_result, ACCEPT_CHUNKED_RESULTS_HEADER_KEY, ACCEPT_COMPRESSION_HEADER_KEY,
+    FINAL_MESSAGE_HEADER_KEY, RESULT_CHUNK_HEADER_KEY, RESULT_FINAL_CHUNK_HEADER_KEY,
+};
+
+use crate::idempotency::{RecordedResult, ResultRecorder};
 
 #[remain::sorted]
 #[derive(Error, Debug)]
 pub enum PublisherError {
+    #[error("failed to compress message")]
+    Compress(#[source] std::io::Error),
     #[error("failed to serialize json message")]
     JSONSerialize(#[source] serde_json::Error),
     #[error("failed to publish message to nats subject: {1}")]
@@ -18,19 +32,53 @@ type Result<T> = std::result::Result<T, PublisherError>;
 #[derive(Debug)]
 pub struct Publisher<'a> {
     nats: &'a NatsClient,
+    reply_mailbox_cancel: String,
     reply_mailbox_output: String,
+    reply_mailbox_progress: String,
     reply_mailbox_result: String,
+    result_compression_threshold: Option<usize>,
+    result_chunk_size: Option<usize>,
+    result_recorder: ResultRecorder,
 }
 
 impl<'a> Publisher<'a> {
     pub fn new(nats: &'a NatsClient, reply_mailbox: &str) -> Self {
         Self {
             nats,
+            reply_mailbox_cancel: reply_mailbox_for_cancel(reply_mailbox),
             reply_mailbox_output: reply_mailbox_for_output(reply_mailbox),
+            reply_mailbox_progress: reply_mailbox_for_progress(reply_mailbox),
             reply_mailbox_result: reply_mailbox_for_result(reply_mailbox),
+            result_compression_threshold: None,
+            result_chunk_size: None,
+            result_recorder: ResultRecorder::default(),
         }
     }
 
+    /// Compresses results larger than `threshold` bytes, if there is a threshold.
+    pub fn with_result_compression(mut self, threshold: Option<usize>) -> Self {
+        self.result_compression_threshold = threshold;
+        self
+    }
+
+    /// Splits results larger than `chunk_size` bytes into chunks of at most that size, if there
+    /// is a chunk size.
+    pub fn with_result_chunking(mut self, chunk_size: Option<usize>) -> Self {
+        self.result_chunk_size = chunk_size.filter(|chunk_size| *chunk_size > 0);
+        self
+    }
+
+    /// Records successful results with the given recorder before publishing them.
+    pub(crate) fn with_result_recorder(mut self, result_recorder: ResultRecorder) -> Self {
+        self.result_recorder = result_recorder;
+        self
+    }
+
+    /// The subject a client publishes to when it cancels this execution.
+    pub fn reply_mailbox_cancel(&self) -> &str {
+        &self.reply_mailbox_cancel
+    }
+
     pub async fn publish_output(&self, output: &OutputStream) -> Result<()> {
         let nats_msg = serde_json::to_string(output).map_err(PublisherError::JSONSerialize)?;
 
@@ -40,28 +88,128 @@ impl<'a> Publisher<'a> {
             .map_err(|err| PublisherError::NatsPublish(err, self.reply_mailbox_output.clone()))
     }
 
-    pub async fn finalize_output(&self) -> Result<()> {
-        let headers = [(FINAL_MESSAGE_HEADER_KEY, "true")].iter().collect();
+    pub async fn publish_progress(&self, progress: &FunctionProgress) -> Result<()> {
+        let nats_msg = serde_json::to_string(progress).map_err(PublisherError::JSONSerialize)?;
+
         self.nats
-            .publish_with_reply_or_headers(
-                &self.reply_mailbox_output,
-                None::<String>,
-                Some(&headers),
-                vec![],
-            )
+            .publish(&self.reply_mailbox_progress, nats_msg)
             .await
-            .map_err(|err| PublisherError::NatsPublish(err, self.reply_mailbox_output.clone()))
+            .map_err(|err| PublisherError::NatsPublish(err, self.reply_mailbox_progress.clone()))
+    }
+
+    /// Tells the client that no more output or progress will be published for the execution.
+    pub async fn finalize_output(&self) -> Result<()> {
+        let headers: HeaderMap = [(FINAL_MESSAGE_HEADER_KEY, "true")].iter().collect();
+        for reply_mailbox in [&self.reply_mailbox_output, &self.reply_mailbox_progress] {
+            self.nats
+                .publish_with_reply_or_headers(
+                    reply_mailbox,
+                    None::<String>,
+                    Some(&headers),
+                    vec![],
+                )
+                .await
+                .map_err(|err| PublisherError::NatsPublish(err, reply_mailbox.clone()))?;
+        }
+        Ok(())
     }
 
-    pub async fn publish_result<R>(&self, result: &FunctionResult<R>) -> Result<()>
+    /// Publishes the result of the execution, returning the size of the published message in
+    /// bytes.
+    pub async fn publish_result<R>(&self, result: &FunctionResult<R>) -> Result<usize>
     where
         R: Serialize,
     {
-        let nats_msg = serde_json::to_string(result).map_err(PublisherError::JSONSerialize)?;
+        let nats_msg = serde_json::to_vec(result).map_err(PublisherError::JSONSerialize)?;
+        let message_id = Uuid::new_v4().to_string();
+        if let FunctionResult::Success(_) = result {
+            self.result_recorder.record_success(&nats_msg, &message_id);
+        }
+
+        self.publish_result_message(nats_msg, &message_id).await
+    }
+
+    /// Publishes a result recorded for an earlier execution of the same request, returning the
+    /// size of the published message in bytes.
+    pub(crate) async fn replay_result(&self, result: &RecordedResult) -> Result<usize> {
+        self.publish_result_message(result.payload.clone(), &result.message_id)
+            .await
+    }
+
+    async fn publish_result_message(&self, nats_msg: Vec<u8>, message_id: &str) -> Result<usize> {
+        let mut headers: Vec<(&str, &str)> = vec![(MESSAGE_ID_HEADER_KEY, message_id)];
+        let nats_msg = match self.result_compression_threshold {
+            Some(threshold) if nats_msg.len() > threshold => {
+                headers.push((CONTENT_ENCODING_HEADER_KEY, GZIP_ENCODING));
+                compression::compress(&nats_msg).map_err(PublisherError::Compress)?
+            }
+            _ => nats_msg,
+        };
+        let size = nats_msg.len();
 
+        match self.result_chunk_size {
+            Some(chunk_size) if size > chunk_size => {
+                // Every chunk carries the result's headers, so that the client can reassemble the
+                // chunks of each result by its message id
+                let chunks = nats_msg.chunks(chunk_size);
+                let chunk_count = chunks.len();
+                for (sequence, chunk) in chunks.enumerate() {
+                    let sequence_value = sequence.to_string();
+                    let mut chunk_headers = headers.clone();
+                    chunk_headers.push((RESULT_CHUNK_HEADER_KEY, &sequence_value));
+                    if sequence + 1 == chunk_count {
+                        chunk_headers.push((RESULT_FINAL_CHUNK_HEADER_KEY, "true"));
+                    }
+                    self.publish_result_payload(&chunk_headers, chunk.to_vec())
+                        .await?;
+                }
+                metric!(monotonic_counter.veritech.server.result_chunks = chunk_count as u64);
+            }
+            _ => self.publish_result_payload(&headers, nats_msg).await?,
+        }
+        Ok(size)
+    }
+
+    async fn publish_result_payload(
+        &self,
+        headers: &[(&str, &str)],
+        payload: Vec<u8>,
+    ) -> Result<()> {
+        let headers: HeaderMap = headers.iter().collect();
         self.nats
-            .publish(&self.reply_mailbox_result, nats_msg)
+            .publish_with_reply_or_headers(
+                &self.reply_mailbox_result,
+                None::<String>,
+                Some(&headers),
+                payload,
+            )
             .await
             .map_err(|err| PublisherError::NatsPublish(err, self.reply_mailbox_result.clone()))
     }
 }
+
+/// Returns the size of the largest result chunk the client that sent a request wants to receive,
+/// if it accepts chunked results at all.
+pub fn accepted_result_chunk_size<T>(request: &Request<T>) -> Option<usize> {
+    request
+        .headers
+        .as_ref()?
+        .get(ACCEPT_CHUNKED_RESULTS_HEADER_KEY)?
+        .iter()
+        .next()?
+        .parse()
+        .ok()
+}
+
+/// Returns the size above which the client that sent a request wants its result compressed, if
+/// it accepts compressed results at all.
+pub fn accepted_compression_threshold<T>(request: &Request<T>) -> Option<usize> {
+    request
+        .headers
+        .as_ref()?
+        .get(ACCEPT_COMPRESSION_HEADER_KEY)?
+        .iter()
+        .next()?
+        .parse()
+        .ok()
+}
@@ -1,23 +1,48 @@
+use async_trait::async_trait;
 use chrono::Utc;
 use deadpool_cyclone::{
-    instance::cyclone::LocalUdsInstanceSpec, ActionRunRequest, ActionRunResultSuccess,
-    CycloneClient, FunctionResult, FunctionResultFailure, FunctionResultFailureError, Manager,
-    Pool, ProgressMessage, ReconciliationRequest, ReconciliationResultSuccess,
-    ResolverFunctionRequest, ResolverFunctionResultSuccess, SchemaVariantDefinitionRequest,
-    SchemaVariantDefinitionResultSuccess, ValidationRequest, ValidationResultSuccess,
+    instance::cyclone::{LocalUdsInstanceSpec, RemoteHttpInstanceSpec},
+    ActionRunRequest, ActionRunResultSuccess, BeforeFunction, ClientError, Connection,
+    CycloneClient, DecryptionKey, Execution, ExecutionError, FunctionErrorKind, FunctionResult,
+    FunctionResultFailure, FunctionResultFailureError, Manager, Pool, ProgressMessage,
+    ReconciliationRequest, ReconciliationResultSuccess, RemoteStream, ResolverFunctionRequest,
+    ResolverFunctionResultSuccess, SchemaVariantDefinitionRequest,
+    SchemaVariantDefinitionResultSuccess, UnixStream, ValidationRequest, ValidationResultSuccess,
+    WarmPool, WarmPoolConfig,
 };
-use futures::{channel::oneshot, join, StreamExt};
-use nats_subscriber::Request;
+use futures::{channel::oneshot, future, join, Stream, StreamExt};
+use nats_subscriber::{RawMessage, Request, SubscriberError};
+use serde::{de::DeserializeOwned, Serialize};
 use si_data_nats::NatsClient;
-use std::io;
+use std::{fmt::Debug, io, sync::Arc, time::Duration};
 use telemetry::prelude::*;
 use thiserror::Error;
 use tokio::{
+    io::{AsyncRead, AsyncWrite},
     signal::unix,
     sync::{broadcast, mpsc},
 };
+use veritech_core::{
+    nats_control_stats_subject, PoolStats, ServerStats, NATS_ACTION_RUN_DEFAULT_SUBJECT,
+    NATS_CONCILIATION_DEFAULT_SUBJECT, NATS_RESOLVER_FUNCTION_DEFAULT_SUBJECT,
+    NATS_SCHEMA_VARIANT_DEFINITION_DEFAULT_SUBJECT, NATS_VALIDATION_DEFAULT_SUBJECT,
+    SANDBOX_PROFILE_HEADER_KEY,
+};
 
-use crate::{config::CycloneSpec, Config, FunctionSubscriber, Publisher, PublisherError};
+use crate::{
+    audit::{AuditStore, AuditTrail},
+    concurrency::{self, WorkspaceLimiter},
+    config::CycloneSpec,
+    dead_letter::DeadLetterQueue,
+    idempotency::ResultRecorder,
+    in_flight::InFlightExecutions,
+    metrics::ExecutionMetrics,
+    middleware::{FunctionExecutionMiddleware, MiddlewareChain, MiddlewareError},
+    payload::PayloadDecryptor,
+    publisher,
+    stats::StatsTracker,
+    Config, FunctionSubscriber, Publisher, PublisherError, ShardConfig,
+};
 
 #[remain::sorted]
 #[derive(Error, Debug)]
@@ -28,10 +53,18 @@ pub enum ServerError {
     Cyclone(#[from] deadpool_cyclone::ClientError),
     #[error("cyclone pool error: {0}")]
     CyclonePool(#[source] Box<dyn std::error::Error + Sync + Send + 'static>),
+    #[error("cyclone pool can't provide instances: {0}")]
+    CyclonePoolUnavailable(#[source] Box<dyn std::error::Error + Sync + Send + 'static>),
     #[error("cyclone progress error: {0}")]
     CycloneProgress(#[source] Box<dyn std::error::Error + Sync + Send + 'static>),
     #[error("cyclone spec builder error: {0}")]
     CycloneSpec(#[source] Box<dyn std::error::Error + Sync + Send + 'static>),
+    #[error("failed to load decryption key: {0}")]
+    DecryptionKey(#[source] deadpool_cyclone::DecryptionKeyError),
+    #[error(transparent)]
+    Middleware(#[from] MiddlewareError),
+    #[error("nats error: {0}")]
+    Nats(#[from] si_data_nats::NatsError),
     #[error("error connecting to nats: {0}")]
     NatsConnect(#[source] si_data_nats::NatsError),
     #[error("no reply mailbox found")]
@@ -61,10 +94,15 @@ type ServerResult<T> = Result<T, ServerError>;
 pub struct Server {
     nats: NatsClient,
     subject_prefix: Option<String>,
-    cyclone_pool: Pool<LocalUdsInstanceSpec>,
+    cyclone_pool: CyclonePool,
     shutdown_broadcast_tx: broadcast::Sender<()>,
     shutdown_tx: mpsc::Sender<ShutdownSource>,
     shutdown_rx: oneshot::Receiver<()>,
+    in_flight: InFlightExecutions,
+    audit_trail: AuditTrail,
+    payload_decryptor: PayloadDecryptor,
+    graceful_shutdown_timeout: Duration,
+    shards: Option<Vec<u32>>,
 }
 
 impl Server {
@@ -88,10 +126,9 @@ impl Server {
                 // Ok(Server { nats, cyclone_pool })
                 unimplemented!("get ready for a surprise!!")
             }
-            wrong @ CycloneSpec::LocalUds(_) => Err(ServerError::WrongCycloneSpec(
-                "LocalHttp",
-                Box::new(wrong.clone()),
-            )),
+            wrong @ (CycloneSpec::LocalUds(_) | CycloneSpec::RemoteHttp(_)) => Err(
+                ServerError::WrongCycloneSpec("LocalHttp", Box::new(wrong.clone())),
+            ),
         }
     }
 
@@ -99,38 +136,98 @@ impl Server {
     pub async fn for_cyclone_uds(config: Config) -> ServerResult<Server> {
         match config.cyclone_spec() {
             CycloneSpec::LocalUds(spec) => {
-                let (shutdown_tx, shutdown_rx) = mpsc::channel(4);
-                // Note the channel parameter corresponds to the number of channels that may be
-                // maintained when the sender is guaranteeing delivery. While this number may end
-                // of being related to the number of subscriptions, it's not
-                // necessarily the same number.
-                let (shutdown_broadcast_tx, _) = broadcast::channel(16);
-
-                let nats = connect_to_nats(&config).await?;
                 let manager = Manager::new(spec.clone());
-                let cyclone_pool = Pool::builder(manager)
+                let cyclone_pool = WarmPool::configure(Pool::builder(manager), config.warm_pool())
                     .build()
                     .map_err(|err| ServerError::CycloneSpec(Box::new(err)))?;
 
-                let graceful_shutdown_rx =
-                    prepare_graceful_shutdown(shutdown_rx, shutdown_broadcast_tx.clone())?;
-
-                Ok(Server {
-                    nats,
-                    subject_prefix: config.subject_prefix().map(|s| s.to_string()),
-                    cyclone_pool,
-                    shutdown_broadcast_tx,
-                    shutdown_tx,
-                    shutdown_rx: graceful_shutdown_rx,
-                })
+                Self::new(
+                    config,
+                    CyclonePool::new(CycloneInstances::LocalUds(cyclone_pool)),
+                )
+                .await
             }
-            wrong @ CycloneSpec::LocalHttp(_) => Err(ServerError::WrongCycloneSpec(
-                "LocalUds",
-                Box::new(wrong.clone()),
-            )),
+            wrong @ (CycloneSpec::LocalHttp(_) | CycloneSpec::RemoteHttp(_)) => Err(
+                ServerError::WrongCycloneSpec("LocalUds", Box::new(wrong.clone())),
+            ),
+        }
+    }
+
+    #[instrument(name = "veritech.init.cyclone.remote_http", skip(config))]
+    pub async fn for_cyclone_remote_http(config: Config) -> ServerResult<Server> {
+        match config.cyclone_spec() {
+            CycloneSpec::RemoteHttp(spec) => {
+                let manager = Manager::new(spec.clone());
+                let cyclone_pool = WarmPool::configure(Pool::builder(manager), config.warm_pool())
+                    .build()
+                    .map_err(|err| ServerError::CycloneSpec(Box::new(err)))?;
+
+                Self::new(
+                    config,
+                    CyclonePool::new(CycloneInstances::RemoteHttp(cyclone_pool)),
+                )
+                .await
+            }
+            wrong @ (CycloneSpec::LocalHttp(_) | CycloneSpec::LocalUds(_)) => Err(
+                ServerError::WrongCycloneSpec("RemoteHttp", Box::new(wrong.clone())),
+            ),
         }
     }
 
+    async fn new(config: Config, cyclone_pool: CyclonePool) -> ServerResult<Server> {
+        let (shutdown_tx, shutdown_rx) = mpsc::channel(4);
+        // Note the channel parameter corresponds to the number of channels that may be
+        // maintained when the sender is guaranteeing delivery. While this number may end
+        // of being related to the number of subscriptions, it's not
+        // necessarily the same number.
+        let (shutdown_broadcast_tx, _) = broadcast::channel(16);
+
+        cyclone_pool.keep_warm(*config.warm_pool(), shutdown_broadcast_tx.subscribe());
+
+        let nats = connect_to_nats(&config).await?;
+
+        let graceful_shutdown_rx =
+            prepare_graceful_shutdown(shutdown_rx, shutdown_broadcast_tx.clone())?;
+
+        let decryption_key = match config.decryption_key_path() {
+            Some(path) => Some(
+                DecryptionKey::load(path)
+                    .await
+                    .map_err(ServerError::DecryptionKey)?,
+            ),
+            None => None,
+        };
+
+        Ok(Server {
+            nats,
+            subject_prefix: config.subject_prefix().map(|s| s.to_string()),
+            cyclone_pool,
+            shutdown_broadcast_tx,
+            shutdown_tx,
+            shutdown_rx: graceful_shutdown_rx,
+            in_flight: InFlightExecutions::new(WorkspaceLimiter::new(
+                config.max_concurrent_executions_per_workspace(),
+            )),
+            audit_trail: AuditTrail::default(),
+            payload_decryptor: PayloadDecryptor::new(decryption_key),
+            graceful_shutdown_timeout: config.graceful_shutdown_timeout(),
+            shards: config.sharding().map(ShardConfig::served_shards),
+        })
+    }
+
+    /// Writes an [`AuditRecord`](crate::AuditRecord) of every execution to the given store, which
+    /// is where they can be queried for review.
+    pub fn with_audit_store(mut self, store: Arc<dyn AuditStore>) -> Self {
+        self.audit_trail = AuditTrail::new(store);
+        self
+    }
+
+    /// Runs every execution through the given middleware, after any middleware added before it.
+    pub fn with_middleware(mut self, middleware: Arc<dyn FunctionExecutionMiddleware>) -> Self {
+        self.cyclone_pool.middleware.push(middleware);
+        self
+    }
+
     /// Gets a shutdown handle that can trigger the server's graceful shutdown process.
     pub fn shutdown_handle(&self) -> VeritechShutdownHandle {
         VeritechShutdownHandle {
@@ -141,32 +238,14 @@ impl Server {
 
 impl Server {
     pub async fn run(self) -> ServerResult<()> {
+        // An unsharded server serves the unsharded subjects, a sharded one each of its shards
+        let shards = match &self.shards {
+            Some(shards) => shards.iter().copied().map(Some).collect(),
+            None => vec![None],
+        };
         let _ = join!(
-            process_resolver_function_requests_task(
-                self.nats.clone(),
-                self.subject_prefix.clone(),
-                self.cyclone_pool.clone(),
-                self.shutdown_broadcast_tx.subscribe(),
-            ),
-            process_validation_requests_task(
-                self.nats.clone(),
-                self.subject_prefix.clone(),
-                self.cyclone_pool.clone(),
-                self.shutdown_broadcast_tx.subscribe(),
-            ),
-            process_action_run_requests_task(
-                self.nats.clone(),
-                self.subject_prefix.clone(),
-                self.cyclone_pool.clone(),
-                self.shutdown_broadcast_tx.subscribe(),
-            ),
-            process_reconciliation_requests_task(
-                self.nats.clone(),
-                self.subject_prefix.clone(),
-                self.cyclone_pool.clone(),
-                self.shutdown_broadcast_tx.subscribe(),
-            ),
-            process_schema_variant_definition_requests_task(
+            future::join_all(shards.into_iter().map(|shard| self.process_requests(shard))),
+            process_control_requests_task(
                 self.nats.clone(),
                 self.subject_prefix.clone(),
                 self.cyclone_pool.clone(),
@@ -175,130 +254,261 @@ impl Server {
         );
 
         let _ = self.shutdown_rx.await;
-        info!("received graceful shutdown, terminating server instance");
+        info!("received graceful shutdown, draining in-flight executions");
+        self.in_flight
+            .drain(&self.nats, self.graceful_shutdown_timeout)
+            .await;
+        info!("terminating server instance");
 
         Ok(())
     }
-}
 
-pub struct VeritechShutdownHandle {
-    shutdown_tx: mpsc::Sender<ShutdownSource>,
-}
+    /// Processes requests of every kind on the given shard's subjects, or on the unsharded
+    /// subjects if there is no shard.
+    async fn process_requests(&self, shard: Option<u32>) {
+        let _ = join!(
+            self.process_requests_task::<ResolverFunctionRequest>(shard),
+            self.process_requests_task::<ValidationRequest>(shard),
+            self.process_requests_task::<ActionRunRequest>(shard),
+            self.process_requests_task::<ReconciliationRequest>(shard),
+            self.process_requests_task::<SchemaVariantDefinitionRequest>(shard),
+        );
+    }
 
-impl VeritechShutdownHandle {
-    pub async fn shutdown(self) {
-        if let Err(err) = self.shutdown_tx.send(ShutdownSource::Handle).await {
-            warn!(error = ?err, "shutdown tx returned error, receiver is likely already closed");
+    async fn process_requests_task<R>(&self, shard: Option<u32>)
+    where
+        R: CycloneRequest,
+        ServerError: From<ExecutionError<R::Success>>,
+    {
+        if let Err(err) = self
+            .process_kind_requests::<R>(shard, self.shutdown_broadcast_tx.subscribe())
+            .await
+        {
+            warn!(error = ?err, kind = R::KIND, "processing requests failed");
         }
     }
-}
 
-// NOTE(fnichol): resolver function, action are parallel and extremely similar, so there
-// is a lurking "unifying" refactor here. It felt like waiting until the third time adding one of
-// these would do the trick, and as a result the first 2 impls are here and not split apart into
-// their own modules.
+    /// Processes the requests of one kind of function until the server shuts down, running each
+    /// of them in its own task.
+    async fn process_kind_requests<R>(
+        &self,
+        shard: Option<u32>,
+        mut shutdown_broadcast_rx: broadcast::Receiver<()>,
+    ) -> ServerResult<()>
+    where
+        R: CycloneRequest,
+        ServerError: From<ExecutionError<R::Success>>,
+    {
+        let mut requests = FunctionSubscriber::subscribe::<R>(
+            &self.nats,
+            self.subject_prefix.as_deref(),
+            shard,
+            self.payload_decryptor.clone(),
+        )
+        .await?;
+
+        let dead_letters = DeadLetterQueue::new(
+            self.nats.clone(),
+            self.subject_prefix.as_deref(),
+            R::dead_letter_kind(),
+        );
+
+        loop {
+            tokio::select! {
+                // Got a broadcasted shutdown message
+                _ = shutdown_broadcast_rx.recv() => {
+                    trace!(kind = R::KIND, "process requests task received shutdown");
+                    break;
+                }
+                // Got the next message on from the subscriber
+                request = requests.next() => {
+                    match request {
+                        Some(Ok(request)) => {
+                            // Spawn a task an process the request
+                            let metrics = ExecutionMetrics::new(
+                                R::KIND,
+                                self.subject_prefix.as_deref(),
+                                request.payload_size,
+                            )
+                            .with_audit(self.audit_trail.begin(&request))
+                            .with_stats(&self.cyclone_pool.stats);
+                            let execution_id = request.payload.execution_id().to_string();
+                            let reply_mailbox = request.reply_mailbox.clone();
+                            self.in_flight.spawn(
+                                &self.nats,
+                                &execution_id,
+                                reply_mailbox.as_deref(),
+                                concurrency::workspace_id(&request),
+                                |result_recorder| request_task(
+                                    self.nats.clone(),
+                                    self.cyclone_pool.clone(),
+                                    metrics,
+                                    request,
+                                    result_recorder,
+                                    dead_letters.clone(),
+                                ),
+                            );
+                        }
+                        Some(Err(err)) => {
+                            warn!(error = ?err, kind = R::KIND, "next request had error");
+                            if let SubscriberError::Undecodable { message, source } = err {
+                                dead_letters.publish(*message, &source).await;
+                            }
+                        }
+                        None => {
+                            trace!(kind = R::KIND, "requests subscriber stream has closed");
+                            break;
+                        }
+                    }
+                }
+                // All other arms are closed, nothing left to do but return
+                else => {
+                    trace!("returning with all select arms closed");
+                    break
+                }
+            }
+        }
+
+        // Unsubscribe from subscription
+        requests.unsubscribe().await?;
 
-async fn process_resolver_function_requests_task(
+        Ok(())
+    }
+}
+
+async fn process_control_requests_task(
     nats: NatsClient,
     subject_prefix: Option<String>,
-    cyclone_pool: Pool<LocalUdsInstanceSpec>,
+    cyclone_pool: CyclonePool,
     shutdown_broadcast_rx: broadcast::Receiver<()>,
 ) {
-    if let Err(err) = process_resolver_function_requests(
-        nats,
-        subject_prefix,
-        cyclone_pool,
-        shutdown_broadcast_rx,
-    )
-    .await
+    if let Err(err) =
+        process_control_requests(nats, subject_prefix, cyclone_pool, shutdown_broadcast_rx).await
     {
-        warn!(error = ?err, "processing resolver function requests failed");
+        warn!(error = ?err, "processing control requests failed");
     }
 }
 
-async fn process_resolver_function_requests(
+/// Answers requests on the control stats subject with the server's [`ServerStats`]. Every server
+/// sharing the subject prefix answers, sharded or not.
+async fn process_control_requests(
     nats: NatsClient,
     subject_prefix: Option<String>,
-    cyclone_pool: Pool<LocalUdsInstanceSpec>,
+    cyclone_pool: CyclonePool,
     mut shutdown_broadcast_rx: broadcast::Receiver<()>,
 ) -> ServerResult<()> {
-    let mut requests =
-        FunctionSubscriber::resolver_function(&nats, subject_prefix.as_deref()).await?;
+    let mut requests = nats
+        .subscribe(nats_control_stats_subject(subject_prefix.as_deref()))
+        .await?;
 
     loop {
         tokio::select! {
-            // Got a broadcasted shutdown message
             _ = shutdown_broadcast_rx.recv() => {
-                trace!("process resolver function requests task received shutdown");
+                trace!("process control requests task received shutdown");
                 break;
             }
-            // Got the next message on from the subscriber
             request = requests.next() => {
                 match request {
                     Some(Ok(request)) => {
-                        // Spawn a task an process the request
-                        tokio::spawn(resolver_function_request_task(
-                            nats.clone(),
-                            cyclone_pool.clone(),
-                            request,
-                        ));
+                        let reply_mailbox = match request.reply() {
+                            Some(reply_mailbox) => reply_mailbox,
+                            None => {
+                                warn!("control stats request has no reply mailbox, ignoring");
+                                continue;
+                            }
+                        };
+                        match serde_json::to_vec(&cyclone_pool.server_stats()) {
+                            Ok(payload) => {
+                                if let Err(err) = nats.publish(reply_mailbox, payload).await {
+                                    warn!(error = ?err, "failed to publish server stats");
+                                }
+                            }
+                            Err(err) => warn!(error = ?err, "failed to serialize server stats"),
+                        }
                     }
                     Some(Err(err)) => {
-                        warn!(error = ?err, "next resolver function request had error");
+                        warn!(error = ?err, "next control request had error");
                     }
                     None => {
-                        trace!("resolver function requests subscriber stream has closed");
+                        trace!("control requests subscriber stream has closed");
                         break;
                     }
                 }
             }
-            // All other arms are closed, nothing left to do but return
-            else => {
-                trace!("returning with all select arms closed");
-                break
-            }
         }
     }
 
-    // Unsubscribe from subscription
     requests.unsubscribe().await?;
 
     Ok(())
 }
 
-async fn resolver_function_request_task(
+pub struct VeritechShutdownHandle {
+    shutdown_tx: mpsc::Sender<ShutdownSource>,
+}
+
+impl VeritechShutdownHandle {
+    pub async fn shutdown(self) {
+        if let Err(err) = self.shutdown_tx.send(ShutdownSource::Handle).await {
+            warn!(error = ?err, "shutdown tx returned error, receiver is likely already closed");
+        }
+    }
+}
+
+/// Executes a request on cyclone and publishes its result. A request that can't be run to
+/// completion still gets a failure result published, so that its client isn't left waiting.
+async fn request_task<R>(
     nats: NatsClient,
-    cyclone_pool: Pool<LocalUdsInstanceSpec>,
-    request: Request<ResolverFunctionRequest>,
-) {
+    cyclone_pool: CyclonePool,
+    mut metrics: ExecutionMetrics,
+    mut request: Request<R>,
+    result_recorder: ResultRecorder,
+    dead_letters: DeadLetterQueue,
+) where
+    R: CycloneRequest,
+    ServerError: From<ExecutionError<R::Success>>,
+{
+    let raw_message = request.raw_message.take();
+    let result_compression_threshold = publisher::accepted_compression_threshold(&request);
+    let result_chunk_size = publisher::accepted_result_chunk_size(&request);
+    let sandbox_profile = sandbox_profile(&request);
     let (cyclone_request, reply_mailbox) = request.into_parts();
     let reply_mailbox = match reply_mailbox {
         Some(reply_mailbox) => reply_mailbox,
         None => {
-            error!("no reply mailbox found");
+            error!(kind = R::KIND, "no reply mailbox found");
+            metrics.errored();
             return;
         }
     };
-    let execution_id = cyclone_request.execution_id.clone();
-    let publisher = Publisher::new(&nats, &reply_mailbox);
-
-    let function_result =
-        resolver_function_request(&publisher, cyclone_pool, cyclone_request).await;
+    let execution_id = cyclone_request.execution_id().to_string();
+    let publisher = Publisher::new(&nats, &reply_mailbox)
+        .with_result_compression(result_compression_threshold)
+        .with_result_chunking(result_chunk_size)
+        .with_result_recorder(result_recorder);
+
+    let function_result = cyclone_pool
+        .execute(
+            &nats,
+            &publisher,
+            &mut metrics,
+            cyclone_request,
+            sandbox_profile,
+        )
+        .await;
 
     if let Err(err) = publisher.finalize_output().await {
         error!(error = ?err, "failed to finalize output by sending final message");
-        let result = deadpool_cyclone::FunctionResult::Failure::<ResolverFunctionResultSuccess>(
-            FunctionResultFailure {
-                execution_id,
-                error: FunctionResultFailureError {
-                    kind: "veritechServer".to_string(),
-                    message: "failed to finalize output by sending final message".to_string(),
-                },
-                timestamp: timestamp(),
-            },
+        let result = transport_failure_result::<R::Success>(
+            execution_id,
+            "failed to finalize output by sending final message".to_string(),
         );
-        if let Err(err) = publisher.publish_result(&result).await {
-            error!(error = ?err, "failed to publish errored result");
+        match publisher.publish_result(&result).await {
+            Ok(result_bytes) => metrics.published(&result, result_bytes),
+            Err(err) => {
+                error!(error = ?err, "failed to publish errored result");
+                metrics.errored();
+            }
         }
         return;
     }
@@ -306,520 +516,493 @@ async fn resolver_function_request_task(
     let function_result = match function_result {
         Ok(fr) => fr,
         Err(err) => {
-            error!(error = ?err, "failure trying to run function to completion");
-            deadpool_cyclone::FunctionResult::Failure::<ResolverFunctionResultSuccess>(
-                FunctionResultFailure {
-                    execution_id,
-                    error: FunctionResultFailureError {
-                        kind: "veritechServer".to_string(),
-                        message: err.to_string(),
-                    },
-                    timestamp: timestamp(),
-                },
-            )
+            error!(error = ?err, kind = R::KIND, "failure trying to run function to completion");
+            dead_letter_if_permanent(&dead_letters, raw_message, &err).await;
+            transport_failure_result(execution_id, err.to_string())
         }
     };
 
-    if let Err(err) = publisher.publish_result(&function_result).await {
-        error!(error = ?err, "failed to publish result");
-    };
+    match publisher.publish_result(&function_result).await {
+        Ok(result_bytes) => metrics.published(&function_result, result_bytes),
+        Err(err) => {
+            error!(error = ?err, "failed to publish result");
+            metrics.errored();
+        }
+    }
 }
 
-async fn resolver_function_request(
-    publisher: &Publisher<'_>,
-    cyclone_pool: Pool<LocalUdsInstanceSpec>,
-    cyclone_request: ResolverFunctionRequest,
-) -> ServerResult<FunctionResult<ResolverFunctionResultSuccess>> {
-    let mut client = cyclone_pool
-        .get()
-        .await
-        .map_err(|err| ServerError::CyclonePool(Box::new(err)))?;
-    let mut progress = client
-        .execute_resolver(cyclone_request)
-        .await?
-        .start()
-        .await?;
+/// The pool of cyclone instances functions are executed on, along with the middleware every
+/// execution passes through on its way to an instance.
+#[derive(Clone)]
+struct CyclonePool {
+    instances: CycloneInstances,
+    middleware: MiddlewareChain,
+    stats: StatsTracker,
+}
 
-    while let Some(msg) = progress.next().await {
-        match msg {
-            Ok(ProgressMessage::OutputStream(output)) => {
-                publisher.publish_output(&output).await?;
-            }
-            Ok(ProgressMessage::Heartbeat) => {
-                trace!("received heartbeat message");
+/// The cyclone instances of a pool, which depend on how cyclone is deployed alongside veritech.
+#[derive(Clone)]
+enum CycloneInstances {
+    LocalUds(Pool<LocalUdsInstanceSpec>),
+    RemoteHttp(Pool<RemoteHttpInstanceSpec>),
+}
+
+impl CyclonePool {
+    fn new(instances: CycloneInstances) -> Self {
+        Self {
+            instances,
+            middleware: MiddlewareChain::default(),
+            stats: StatsTracker::default(),
+        }
+    }
+
+    /// Returns the stats of the server, along with the state of the pool's instances.
+    fn server_stats(&self) -> ServerStats {
+        let status = match &self.instances {
+            CycloneInstances::LocalUds(pool) => pool.status(),
+            CycloneInstances::RemoteHttp(pool) => pool.status(),
+        };
+        let idle = status.available.max(0) as u64;
+
+        self.stats.snapshot(PoolStats {
+            max_size: status.max_size as u64,
+            size: status.size as u64,
+            busy: (status.size as u64).saturating_sub(idle),
+            idle,
+        })
+    }
+
+    /// Spawns a task keeping the pool warm until the server shuts down.
+    fn keep_warm(
+        &self,
+        config: WarmPoolConfig,
+        mut shutdown_broadcast_rx: broadcast::Receiver<()>,
+    ) {
+        let shutdown = async move {
+            let _ = shutdown_broadcast_rx.recv().await;
+        };
+        match &self.instances {
+            CycloneInstances::LocalUds(pool) => {
+                tokio::spawn(WarmPool::new(pool.clone(), config).run(shutdown));
             }
-            Err(err) => {
-                warn!(error = ?err, "next progress message was an error, bailing out");
-                break;
+            CycloneInstances::RemoteHttp(pool) => {
+                tokio::spawn(WarmPool::new(pool.clone(), config).run(shutdown));
             }
         }
     }
 
-    let function_result = progress.finish().await?;
+    /// Executes a function on an instance checked out of the pool, publishing its output and
+    /// progress as they arrive, and returns the function's result.
+    ///
+    /// The request passes through the middleware first, which may reject it without checking out
+    /// an instance, and the result passes through the middleware before it is returned.
+    async fn execute<R>(
+        &self,
+        nats: &NatsClient,
+        publisher: &Publisher<'_>,
+        metrics: &mut ExecutionMetrics,
+        cyclone_request: R,
+        sandbox_profile: Option<String>,
+    ) -> ServerResult<FunctionResult<R::Success>>
+    where
+        R: CycloneRequest,
+        ServerError: From<ExecutionError<R::Success>>,
+    {
+        let execution_id = cyclone_request.execution_id().to_string();
+        let (cyclone_request, sandbox_profile, execution) = match self
+            .middleware
+            .before(
+                metrics.kind(),
+                &execution_id,
+                sandbox_profile,
+                cyclone_request,
+            )
+            .await
+        {
+            Ok(before) => before,
+            Err(MiddlewareError::Rejected(reason)) => {
+                debug!(execution_id, reason, "middleware rejected execution");
+                return Ok(rejected_result(execution_id, reason));
+            }
+            Err(err) => return Err(err.into()),
+        };
 
-    Ok(function_result)
-}
+        let mut cancel_subscription = nats.subscribe(publisher.reply_mailbox_cancel()).await?;
+        let function_result = match &self.instances {
+            CycloneInstances::LocalUds(pool) => {
+                let mut client = pool.get().await.map_err(cyclone_pool_error)?;
+                execute_on::<_, UnixStream, _>(
+                    &mut *client,
+                    publisher,
+                    &mut cancel_subscription,
+                    metrics,
+                    cyclone_request,
+                    sandbox_profile,
+                )
+                .await?
+            }
+            CycloneInstances::RemoteHttp(pool) => {
+                let mut client = pool.get().await.map_err(cyclone_pool_error)?;
+                execute_on::<_, RemoteStream, _>(
+                    &mut *client,
+                    publisher,
+                    &mut cancel_subscription,
+                    metrics,
+                    cyclone_request,
+                    sandbox_profile,
+                )
+                .await?
+            }
+        };
+        cancel_subscription.unsubscribe().await?;
 
-async fn process_validation_requests_task(
-    nats: NatsClient,
-    subject_prefix: Option<String>,
-    cyclone_pool: Pool<LocalUdsInstanceSpec>,
-    shutdown_broadcast_rx: broadcast::Receiver<()>,
-) {
-    if let Err(err) =
-        process_validation_requests(nats, subject_prefix, cyclone_pool, shutdown_broadcast_rx).await
-    {
-        warn!(error = ?err, "processing validation requests failed");
+        let function_result = self
+            .middleware
+            .after(execution.as_ref(), function_result)
+            .await?;
+
+        Ok(function_result)
     }
 }
 
-async fn process_validation_requests(
-    nats: NatsClient,
-    subject_prefix: Option<String>,
-    cyclone_pool: Pool<LocalUdsInstanceSpec>,
-    mut shutdown_broadcast_rx: broadcast::Receiver<()>,
-) -> ServerResult<()> {
-    let mut requests = FunctionSubscriber::validation(&nats, subject_prefix.as_deref()).await?;
+async fn execute_on<C, Strm, R>(
+    client: &mut C,
+    publisher: &Publisher<'_>,
+    cancel_subscription: &mut si_data_nats::Subscription,
+    metrics: &mut ExecutionMetrics,
+    cyclone_request: R,
+    sandbox_profile: Option<String>,
+) -> ServerResult<FunctionResult<R::Success>>
+where
+    C: CycloneClient<Strm> + Send,
+    Strm: AsyncRead + AsyncWrite + Connection + Unpin + Send + 'static,
+    R: CycloneRequest,
+    ServerError: From<ExecutionError<R::Success>>,
+{
+    let execution_id = cyclone_request.execution_id().to_string();
+    client.set_sandbox_profile(sandbox_profile);
+    metrics.checked_out();
+    let mut progress = cyclone_request.execute(client).await?.start().await?;
 
-    loop {
-        tokio::select! {
-            // Got a broadcasted shutdown message
-            _ = shutdown_broadcast_rx.recv() => {
-                trace!("process validation requests task received shutdown");
-                break;
-            }
-            // Got the next message on from the subscriber
-            request = requests.next() => {
-                match request {
-                    Some(Ok(request)) => {
-                        // Spawn a task an process the request
-                        tokio::spawn(validation_request_task(
-                            nats.clone(),
-                            cyclone_pool.clone(),
-                            request,
-                        ));
-                    }
-                    Some(Err(err)) => {
-                        warn!(error = ?err, "next validation request had error");
-                    }
-                    None => {
-                        trace!("validation requests subscriber stream has closed");
-                        break;
-                    }
-                }
-            }
-            // All other arms are closed, nothing left to do but return
-            else => {
-                trace!("returning with all select arms closed");
-                break
+    let function_result =
+        match forward_progress(publisher, &mut progress, cancel_subscription).await? {
+            Progress::Cancelled => {
+                drop(progress);
+                cancelled_result(execution_id)
             }
-        }
+            Progress::Finished => progress.finish().await?,
+        };
+
+    Ok(function_result)
+}
+
+/// A request cyclone executes as a function, whichever kind of client it is sent with.
+#[async_trait]
+pub(crate) trait CycloneRequest:
+    Serialize + DeserializeOwned + Send + Sized + 'static
+{
+    type Success: Serialize + DeserializeOwned + Unpin + Debug + Send;
+
+    /// The subject requests are received on, before any subject prefix or shard is applied.
+    const SUBJECT_SUFFIX: &'static str;
+    /// The queue group the servers sharing the subject split its requests with.
+    const QUEUE_NAME: &'static str;
+    /// A short name for the kind of function, used in telemetry.
+    const KIND: &'static str;
+
+    /// The kind dead letters of these requests are republished under, which is the last token of
+    /// their subject.
+    fn dead_letter_kind() -> &'static str {
+        Self::SUBJECT_SUFFIX
+            .rsplit('.')
+            .next()
+            .unwrap_or(Self::SUBJECT_SUFFIX)
     }
 
-    // Unsubscribe from subscription
-    requests.unsubscribe().await?;
+    fn execution_id(&self) -> &str;
 
-    Ok(())
-}
+    fn handler(&self) -> &str;
 
-async fn validation_request_task(
-    nats: NatsClient,
-    cyclone_pool: Pool<LocalUdsInstanceSpec>,
-    request: Request<ValidationRequest>,
-) {
-    if let Err(err) = validation_request(nats, cyclone_pool, request).await {
-        warn!(error = ?err, "validation execution failed");
+    /// The function's code, as it is sent to cyclone.
+    fn code(&self) -> &str;
+
+    /// The functions cyclone runs before this one.
+    fn before(&self) -> &[BeforeFunction] {
+        &[]
     }
+
+    async fn execute<C, Strm>(
+        self,
+        client: &mut C,
+    ) -> Result<Execution<Strm, Self, Self::Success>, ClientError>
+    where
+        C: CycloneClient<Strm> + Send,
+        Strm: AsyncRead + AsyncWrite + Connection + Unpin + Send + 'static;
 }
 
-async fn validation_request(
-    nats: NatsClient,
-    cyclone_pool: Pool<LocalUdsInstanceSpec>,
-    request: Request<ValidationRequest>,
-) -> ServerResult<()> {
-    let (cyclone_request, reply_mailbox) = request.into_parts();
-    let reply_mailbox = reply_mailbox.ok_or(ServerError::NoReplyMailboxFound)?;
+#[async_trait]
+impl CycloneRequest for ResolverFunctionRequest {
+    type Success = ResolverFunctionResultSuccess;
 
-    let publisher = Publisher::new(&nats, &reply_mailbox);
-    let mut client = cyclone_pool
-        .get()
-        .await
-        .map_err(|err| ServerError::CyclonePool(Box::new(err)))?;
-    let mut progress = client
-        .execute_validation(cyclone_request)
-        .await?
-        .start()
-        .await?;
+    const SUBJECT_SUFFIX: &'static str = NATS_RESOLVER_FUNCTION_DEFAULT_SUBJECT;
+    const QUEUE_NAME: &'static str = "resolver";
+    const KIND: &'static str = "resolverFunction";
 
-    while let Some(msg) = progress.next().await {
-        match msg {
-            Ok(ProgressMessage::OutputStream(output)) => {
-                publisher.publish_output(&output).await?;
-            }
-            Ok(ProgressMessage::Heartbeat) => {
-                trace!("received heartbeat message");
-            }
-            Err(err) => {
-                warn!(error = ?err, "next progress message was an error, bailing out");
-                break;
-            }
-        }
+    fn execution_id(&self) -> &str {
+        &self.execution_id
     }
-    publisher.finalize_output().await?;
 
-    let function_result = progress.finish().await?;
-    publisher.publish_result(&function_result).await?;
+    fn handler(&self) -> &str {
+        &self.handler
+    }
 
-    Ok(())
-}
+    fn code(&self) -> &str {
+        &self.code_base64
+    }
 
-async fn process_schema_variant_definition_requests_task(
-    nats: NatsClient,
-    subject_prefix: Option<String>,
-    cyclone_pool: Pool<LocalUdsInstanceSpec>,
-    shutdown_broadcast_rx: broadcast::Receiver<()>,
-) {
-    if let Err(err) = process_schema_variant_definition_requests(
-        nats,
-        subject_prefix,
-        cyclone_pool,
-        shutdown_broadcast_rx,
-    )
-    .await
+    fn before(&self) -> &[BeforeFunction] {
+        &self.before
+    }
+
+    async fn execute<C, Strm>(
+        self,
+        client: &mut C,
+    ) -> Result<Execution<Strm, Self, Self::Success>, ClientError>
+    where
+        C: CycloneClient<Strm> + Send,
+        Strm: AsyncRead + AsyncWrite + Connection + Unpin + Send + 'static,
     {
-        warn!(error = ?err, "processing schema variant definition requests failed");
+        client.execute_resolver(self).await
     }
 }
 
-async fn process_schema_variant_definition_requests(
-    nats: NatsClient,
-    subject_prefix: Option<String>,
-    cyclone_pool: Pool<LocalUdsInstanceSpec>,
-    mut shutdown_broadcast_rx: broadcast::Receiver<()>,
-) -> ServerResult<()> {
-    let mut requests =
-        FunctionSubscriber::schema_variant_definition(&nats, subject_prefix.as_deref()).await?;
+#[async_trait]
+impl CycloneRequest for ValidationRequest {
+    type Success = ValidationResultSuccess;
 
-    loop {
-        tokio::select! {
-            // Got a broadcasted shutdown message
-            _ = shutdown_broadcast_rx.recv() => {
-                trace!("process schema_variant_definition requests task received shutdown");
-                break;
-            }
-            // Got the next message on from the subscriber
-            request = requests.next() => {
-                match request {
-                    Some(Ok(request)) => {
-                        // Spawn a task an process the request
-                        tokio::spawn(schema_variant_definition_request_task(
-                            nats.clone(),
-                            cyclone_pool.clone(),
-                            request,
-                        ));
-                    }
-                    Some(Err(err)) => {
-                        warn!(error = ?err, "next schema variant definition request had error");
-                    }
-                    None => {
-                        trace!("schema variant definition requests subscriber stream has closed");
-                        break;
-                    }
-                }
-            }
-            // All other arms are closed, nothing left to do but return
-            else => {
-                trace!("returning with all select arms closed");
-                break
-            }
-        }
+    const SUBJECT_SUFFIX: &'static str = NATS_VALIDATION_DEFAULT_SUBJECT;
+    const QUEUE_NAME: &'static str = "validation";
+    const KIND: &'static str = "validation";
+
+    fn execution_id(&self) -> &str {
+        &self.execution_id
     }
 
-    // Unsubscribe from subscription
-    requests.unsubscribe().await?;
+    fn handler(&self) -> &str {
+        &self.handler
+    }
 
-    Ok(())
-}
+    fn code(&self) -> &str {
+        &self.code_base64
+    }
 
-async fn schema_variant_definition_request_task(
-    nats: NatsClient,
-    cyclone_pool: Pool<LocalUdsInstanceSpec>,
-    request: Request<SchemaVariantDefinitionRequest>,
-) {
-    if let Err(err) = schema_variant_definition_request(nats, cyclone_pool, request).await {
-        warn!(error = ?err, "schema variant definition execution failed");
+    fn before(&self) -> &[BeforeFunction] {
+        &self.before
+    }
+
+    async fn execute<C, Strm>(
+        self,
+        client: &mut C,
+    ) -> Result<Execution<Strm, Self, Self::Success>, ClientError>
+    where
+        C: CycloneClient<Strm> + Send,
+        Strm: AsyncRead + AsyncWrite + Connection + Unpin + Send + 'static,
+    {
+        client.execute_validation(self).await
     }
 }
 
-async fn schema_variant_definition_request(
-    nats: NatsClient,
-    cyclone_pool: Pool<LocalUdsInstanceSpec>,
-    request: Request<SchemaVariantDefinitionRequest>,
-) -> ServerResult<()> {
-    let (cyclone_request, reply_mailbox) = request.into_parts();
-    let reply_mailbox = reply_mailbox.ok_or(ServerError::NoReplyMailboxFound)?;
+#[async_trait]
+impl CycloneRequest for ActionRunRequest {
+    type Success = ActionRunResultSuccess;
 
-    let publisher = Publisher::new(&nats, &reply_mailbox);
-    let mut client = cyclone_pool
-        .get()
-        .await
-        .map_err(|err| ServerError::CyclonePool(Box::new(err)))?;
+    const SUBJECT_SUFFIX: &'static str = NATS_ACTION_RUN_DEFAULT_SUBJECT;
+    const QUEUE_NAME: &'static str = "action";
+    const KIND: &'static str = "actionRun";
 
-    let mut progress = client
-        .execute_schema_variant_definition(cyclone_request)
-        .await?
-        .start()
-        .await?;
+    fn execution_id(&self) -> &str {
+        &self.execution_id
+    }
 
-    while let Some(msg) = progress.next().await {
-        match msg {
-            Ok(ProgressMessage::OutputStream(output)) => {
-                publisher.publish_output(&output).await?;
-            }
-            Ok(ProgressMessage::Heartbeat) => {
-                trace!("received heartbeat message");
-            }
-            Err(err) => {
-                warn!(error = ?err, "next progress message was an error, bailing out");
-                break;
-            }
-        }
+    fn handler(&self) -> &str {
+        &self.handler
     }
-    publisher.finalize_output().await?;
 
-    let function_result = progress.finish().await?;
-    publisher.publish_result(&function_result).await?;
+    fn code(&self) -> &str {
+        &self.code_base64
+    }
 
-    Ok(())
-}
+    fn before(&self) -> &[BeforeFunction] {
+        &self.before
+    }
 
-async fn process_action_run_requests_task(
-    nats: NatsClient,
-    subject_prefix: Option<String>,
-    cyclone_pool: Pool<LocalUdsInstanceSpec>,
-    shutdown_broadcast_rx: broadcast::Receiver<()>,
-) {
-    if let Err(err) =
-        process_action_run_requests(nats, subject_prefix, cyclone_pool, shutdown_broadcast_rx).await
+    async fn execute<C, Strm>(
+        self,
+        client: &mut C,
+    ) -> Result<Execution<Strm, Self, Self::Success>, ClientError>
+    where
+        C: CycloneClient<Strm> + Send,
+        Strm: AsyncRead + AsyncWrite + Connection + Unpin + Send + 'static,
     {
-        warn!(error = ?err, "processing action run requests failed");
+        client.execute_action_run(self).await
     }
 }
 
-async fn process_action_run_requests(
-    nats: NatsClient,
-    subject_prefix: Option<String>,
-    cyclone_pool: Pool<LocalUdsInstanceSpec>,
-    mut shutdown_broadcast_rx: broadcast::Receiver<()>,
-) -> ServerResult<()> {
-    let mut requests = FunctionSubscriber::action_run(&nats, subject_prefix.as_deref()).await?;
+#[async_trait]
+impl CycloneRequest for ReconciliationRequest {
+    type Success = ReconciliationResultSuccess;
 
-    loop {
-        tokio::select! {
-            // Got a broadcasted shutdown message
-            _ = shutdown_broadcast_rx.recv() => {
-                trace!("process action_run requests task received shutdown");
-                break;
-            }
-            // Got the next message on from the subscriber
-            request = requests.next() => {
-                match request {
-                    Some(Ok(request)) => {
-                        // Spawn a task an process the request
-                        tokio::spawn(action_run_request_task(
-                            nats.clone(),
-                            cyclone_pool.clone(),
-                            request,
-                        ));
-                    }
-                    Some(Err(err)) => {
-                        warn!(error = ?err, "next action run request had error");
-                    }
-                    None => {
-                        trace!("action run requests subscriber stream has closed");
-                        break;
-                    }
-                }
-            }
-            // All other arms are closed, nothing left to do but return
-            else => {
-                trace!("returning with all select arms closed");
-                break
-            }
-        }
+    const SUBJECT_SUFFIX: &'static str = NATS_CONCILIATION_DEFAULT_SUBJECT;
+    const QUEUE_NAME: &'static str = "reconciliation";
+    const KIND: &'static str = "reconciliation";
+
+    fn execution_id(&self) -> &str {
+        &self.execution_id
     }
 
-    // Unsubscribe from subscription
-    requests.unsubscribe().await?;
+    fn handler(&self) -> &str {
+        &self.handler
+    }
 
-    Ok(())
-}
+    fn code(&self) -> &str {
+        &self.code_base64
+    }
 
-async fn action_run_request_task(
-    nats: NatsClient,
-    cyclone_pool: Pool<LocalUdsInstanceSpec>,
-    request: Request<ActionRunRequest>,
-) {
-    if let Err(err) = action_run_request(nats, cyclone_pool, request).await {
-        warn!(error = ?err, "action run execution failed");
+    fn before(&self) -> &[BeforeFunction] {
+        &self.before
+    }
+
+    async fn execute<C, Strm>(
+        self,
+        client: &mut C,
+    ) -> Result<Execution<Strm, Self, Self::Success>, ClientError>
+    where
+        C: CycloneClient<Strm> + Send,
+        Strm: AsyncRead + AsyncWrite + Connection + Unpin + Send + 'static,
+    {
+        client.execute_reconciliation(self).await
     }
 }
 
-async fn action_run_request(
-    nats: NatsClient,
-    cyclone_pool: Pool<LocalUdsInstanceSpec>,
-    request: Request<ActionRunRequest>,
-) -> ServerResult<()> {
-    let (cyclone_request, reply_mailbox) = request.into_parts();
-    let reply_mailbox = reply_mailbox.ok_or(ServerError::NoReplyMailboxFound)?;
+#[async_trait]
+impl CycloneRequest for SchemaVariantDefinitionRequest {
+    type Success = SchemaVariantDefinitionResultSuccess;
 
-    let publisher = Publisher::new(&nats, &reply_mailbox);
-    let mut client = cyclone_pool
-        .get()
-        .await
-        .map_err(|err| ServerError::CyclonePool(Box::new(err)))?;
+    const SUBJECT_SUFFIX: &'static str = NATS_SCHEMA_VARIANT_DEFINITION_DEFAULT_SUBJECT;
+    const QUEUE_NAME: &'static str = "schema_variant_definition";
+    const KIND: &'static str = "schemaVariantDefinition";
 
-    let mut progress = client
-        .execute_action_run(cyclone_request)
-        .await?
-        .start()
-        .await?;
+    fn execution_id(&self) -> &str {
+        &self.execution_id
+    }
 
-    while let Some(msg) = progress.next().await {
-        match msg {
-            Ok(ProgressMessage::OutputStream(output)) => {
-                publisher.publish_output(&output).await?;
-            }
-            Ok(ProgressMessage::Heartbeat) => {
-                trace!("received heartbeat message");
-            }
-            Err(err) => {
-                warn!(error = ?err, "next progress message was an error, bailing out");
-                break;
-            }
-        }
+    fn handler(&self) -> &str {
+        &self.handler
     }
-    publisher.finalize_output().await?;
 
-    let function_result = progress.finish().await?;
-    publisher.publish_result(&function_result).await?;
+    fn code(&self) -> &str {
+        &self.code_base64
+    }
 
-    Ok(())
-}
+    fn before(&self) -> &[BeforeFunction] {
+        &self.before
+    }
 
-async fn process_reconciliation_requests_task(
-    nats: NatsClient,
-    subject_prefix: Option<String>,
-    cyclone_pool: Pool<LocalUdsInstanceSpec>,
-    shutdown_broadcast_rx: broadcast::Receiver<()>,
-) {
-    if let Err(err) =
-        process_reconciliation_requests(nats, subject_prefix, cyclone_pool, shutdown_broadcast_rx)
-            .await
+    async fn execute<C, Strm>(
+        self,
+        client: &mut C,
+    ) -> Result<Execution<Strm, Self, Self::Success>, ClientError>
+    where
+        C: CycloneClient<Strm> + Send,
+        Strm: AsyncRead + AsyncWrite + Connection + Unpin + Send + 'static,
     {
-        warn!(error = ?err, "processing reconciliation requests failed");
+        client.execute_schema_variant_definition(self).await
     }
 }
 
-async fn process_reconciliation_requests(
-    nats: NatsClient,
-    subject_prefix: Option<String>,
-    cyclone_pool: Pool<LocalUdsInstanceSpec>,
-    mut shutdown_broadcast_rx: broadcast::Receiver<()>,
-) -> ServerResult<()> {
-    let mut requests = FunctionSubscriber::reconciliation(&nats, subject_prefix.as_deref()).await?;
+/// How a function's progress stream ended.
+enum Progress {
+    /// The client cancelled the execution before it finished.
+    Cancelled,
+    /// The progress stream closed, so a result is ready to be read.
+    Finished,
+}
 
+/// Publishes a function's output and progress as they arrive, until either the progress stream
+/// closes or the client cancels the execution.
+async fn forward_progress<S, E>(
+    publisher: &Publisher<'_>,
+    progress: &mut S,
+    cancel_subscription: &mut si_data_nats::Subscription,
+) -> ServerResult<Progress>
+where
+    S: Stream<Item = Result<ProgressMessage, E>> + Unpin,
+    E: std::fmt::Debug,
+{
     loop {
         tokio::select! {
-            // Got a broadcasted shutdown message
-            _ = shutdown_broadcast_rx.recv() => {
-                trace!("process reconciliation requests task received shutdown");
-                break;
+            _ = cancel_subscription.next() => {
+                info!("execution cancelled by client");
+                return Ok(Progress::Cancelled);
             }
-            // Got the next message on from the subscriber
-            request = requests.next() => {
-                match request {
-                    Some(Ok(request)) => {
-                        // Spawn a task an process the request
-                        tokio::spawn(reconciliation_request_task(
-                            nats.clone(),
-                            cyclone_pool.clone(),
-                            request,
-                        ));
-                    }
-                    Some(Err(err)) => {
-                        warn!(error = ?err, "next reconciliation request had error");
-                    }
-                    None => {
-                        trace!("reconciliation requests subscriber stream has closed");
-                        break;
-                    }
+            msg = progress.next() => match msg {
+                Some(Ok(ProgressMessage::OutputStream(output))) => {
+                    publisher.publish_output(&output).await?;
                 }
-            }
-            // All other arms are closed, nothing left to do but return
-            else => {
-                trace!("returning with all select arms closed");
-                break
-            }
+                Some(Ok(ProgressMessage::Progress(progress))) => {
+                    publisher.publish_progress(&progress).await?;
+                }
+                Some(Ok(ProgressMessage::Heartbeat)) => {
+                    trace!("received heartbeat message");
+                }
+                Some(Err(err)) => {
+                    warn!(error = ?err, "next progress message was an error, bailing out");
+                    return Ok(Progress::Finished);
+                }
+                None => return Ok(Progress::Finished),
+            },
         }
     }
-
-    // Unsubscribe from subscription
-    requests.unsubscribe().await?;
-
-    Ok(())
 }
 
-async fn reconciliation_request_task(
-    nats: NatsClient,
-    cyclone_pool: Pool<LocalUdsInstanceSpec>,
-    request: Request<ReconciliationRequest>,
-) {
-    if let Err(err) = reconciliation_request(nats, cyclone_pool, request).await {
-        warn!(error = ?err, "reconciliation execution failed");
-    }
+/// The result published for a cancelled execution. Dropping the progress stream closes the
+/// websocket to cyclone, which stops the function's process.
+fn cancelled_result<S>(execution_id: String) -> FunctionResult<S> {
+    FunctionResult::Failure(FunctionResultFailure {
+        execution_id,
+        error: FunctionResultFailureError {
+            kind: "cancelled".to_string(),
+            message: "execution was cancelled by the client".to_string(),
+            category: FunctionErrorKind::Cancelled,
+        },
+        timestamp: timestamp(),
+    })
 }
 
-async fn reconciliation_request(
-    nats: NatsClient,
-    cyclone_pool: Pool<LocalUdsInstanceSpec>,
-    request: Request<ReconciliationRequest>,
-) -> ServerResult<()> {
-    let (cyclone_request, reply_mailbox) = request.into_parts();
-    let reply_mailbox = reply_mailbox.ok_or(ServerError::NoReplyMailboxFound)?;
-
-    let publisher = Publisher::new(&nats, &reply_mailbox);
-    let mut client = cyclone_pool
-        .get()
-        .await
-        .map_err(|err| ServerError::CyclonePool(Box::new(err)))?;
-
-    let mut progress = client
-        .execute_reconciliation(cyclone_request)
-        .await?
-        .start()
-        .await?;
-
-    while let Some(msg) = progress.next().await {
-        match msg {
-            Ok(ProgressMessage::OutputStream(output)) => {
-                publisher.publish_output(&output).await?;
-            }
-            Ok(ProgressMessage::Heartbeat) => {
-                trace!("received heartbeat message");
-            }
-            Err(err) => {
-                warn!(error = ?err, "next progress message was an error, bailing out");
-                break;
-            }
-        }
-    }
-    publisher.finalize_output().await?;
-
-    let function_result = progress.finish().await?;
-    publisher.publish_result(&function_result).await?;
+fn rejected_result<S>(execution_id: String, message: String) -> FunctionResult<S> {
+    FunctionResult::Failure(FunctionResultFailure {
+        execution_id,
+        error: FunctionResultFailureError {
+            kind: "rejected".to_string(),
+            message,
+            category: FunctionErrorKind::Rejected,
+        },
+        timestamp: timestamp(),
+    })
+}
 
-    Ok(())
+/// The result published for an execution that couldn't be run to completion, such as when no
+/// cyclone instance could be checked out or the function's output couldn't be published.
+fn transport_failure_result<S>(execution_id: String, message: String) -> FunctionResult<S> {
+    FunctionResult::Failure(FunctionResultFailure {
+        execution_id,
+        error: FunctionResultFailureError {
+            kind: "veritechServer".to_string(),
+            message,
+            category: FunctionErrorKind::TransportError,
+        },
+        timestamp: timestamp(),
+    })
 }
 
 async fn connect_to_nats(config: &Config) -> ServerResult<NatsClient> {
@@ -893,3 +1076,38 @@ impl Default for ShutdownSource {
         Self::Handle
     }
 }
+
+/// The cyclone sandbox profile a request asks its function to run under, if it names one.
+fn sandbox_profile<T>(request: &Request<T>) -> Option<String> {
+    request
+        .headers
+        .as_ref()?
+        .get(SANDBOX_PROFILE_HEADER_KEY)?
+        .iter()
+        .next()
+        .cloned()
+}
+
+/// Wraps an error checking an instance out of the cyclone pool. A timeout only means that every
+/// instance was busy, while any other error means the pool can't provide instances at all.
+fn cyclone_pool_error<E>(err: deadpool_cyclone::PoolError<E>) -> ServerError
+where
+    deadpool_cyclone::PoolError<E>: std::error::Error + Send + Sync + 'static,
+{
+    match err {
+        deadpool_cyclone::PoolError::Timeout(_) => ServerError::CyclonePool(Box::new(err)),
+        err => ServerError::CyclonePoolUnavailable(Box::new(err)),
+    }
+}
+
+/// Sends a request to the dead letter queue if the error executing it will keep it from running
+/// on this server, rather than being a failure a retry could get past.
+async fn dead_letter_if_permanent(
+    dead_letters: &DeadLetterQueue,
+    raw_message: Option<RawMessage>,
+    err: &ServerError,
+) {
+    if let (ServerError::CyclonePoolUnavailable(_), Some(raw_message)) = (err, raw_message) {
+        dead_letters.publish(raw_message, err).await;
+    }
+}
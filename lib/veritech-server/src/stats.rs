@@ -0,0 +1,157 @@
+use std::{
+    collections::{BTreeMap, VecDeque},
+    sync::{Arc, Mutex, PoisonError},
+    time::{Duration, Instant},
+};
+
+use veritech_core::{KindStats, PoolStats, ServerStats};
+
+/// How long ended executions count towards the recent executions of their kind.
+const RECENT_WINDOW: Duration = Duration::from_secs(5 * 60);
+
+/// Keeps count of the executions of each kind of function that are waiting, running and recently
+/// ended on a server, for its [`ServerStats`].
+#[derive(Clone, Debug, Default)]
+pub(crate) struct StatsTracker {
+    kinds: Arc<Mutex<BTreeMap<&'static str, KindTracker>>>,
+}
+
+#[derive(Debug, Default)]
+struct KindTracker {
+    queued: u64,
+    running: u64,
+    /// When recent executions ended, and whether they ended with an error, oldest first.
+    recent: VecDeque<(Instant, bool)>,
+}
+
+impl KindTracker {
+    fn forget_before(&mut self, cutoff: Instant) {
+        while matches!(self.recent.front(), Some((ended_at, _)) if *ended_at < cutoff) {
+            self.recent.pop_front();
+        }
+    }
+}
+
+impl StatsTracker {
+    /// Starts counting an execution that was just received, as waiting for a cyclone instance.
+    pub(crate) fn begin(&self, kind: &'static str) -> ExecutionStats {
+        self.update(kind, |tracker| tracker.queued += 1);
+
+        ExecutionStats {
+            tracker: self.clone(),
+            kind,
+            stage: Stage::Queued,
+        }
+    }
+
+    /// Returns the server's stats, given the state of its pool of cyclone instances.
+    pub(crate) fn snapshot(&self, pool: PoolStats) -> ServerStats {
+        let cutoff = Instant::now().checked_sub(RECENT_WINDOW);
+        let mut kinds = self.kinds.lock().unwrap_or_else(PoisonError::into_inner);
+
+        ServerStats {
+            pool,
+            kinds: kinds
+                .iter_mut()
+                .map(|(kind, tracker)| {
+                    if let Some(cutoff) = cutoff {
+                        tracker.forget_before(cutoff);
+                    }
+                    let recent_executions = tracker.recent.len() as u64;
+                    let recent_errors = tracker
+                        .recent
+                        .iter()
+                        .filter(|(_, errored)| *errored)
+                        .count() as u64;
+                    let error_rate = if recent_executions == 0 {
+                        0.0
+                    } else {
+                        recent_errors as f64 / recent_executions as f64
+                    };
+
+                    (
+                        kind.to_string(),
+                        KindStats {
+                            queued: tracker.queued,
+                            running: tracker.running,
+                            recent_executions,
+                            recent_errors,
+                            error_rate,
+                        },
+                    )
+                })
+                .collect(),
+            recent_window_secs: RECENT_WINDOW.as_secs(),
+        }
+    }
+
+    fn update(&self, kind: &'static str, f: impl FnOnce(&mut KindTracker)) {
+        let mut kinds = self.kinds.lock().unwrap_or_else(PoisonError::into_inner);
+        f(kinds.entry(kind).or_default());
+    }
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+enum Stage {
+    Queued,
+    Running,
+    Ended,
+}
+
+/// The counts of one execution, moved along as the execution progresses. An execution dropped
+/// before it ended stops being counted without being counted as a recent execution.
+#[derive(Debug)]
+pub(crate) struct ExecutionStats {
+    tracker: StatsTracker,
+    kind: &'static str,
+    stage: Stage,
+}
+
+impl ExecutionStats {
+    /// Counts the execution as running, now that it has a cyclone instance.
+    pub(crate) fn checked_out(&mut self) {
+        if self.stage == Stage::Queued {
+            self.tracker.update(self.kind, |tracker| {
+                tracker.queued = tracker.queued.saturating_sub(1);
+                tracker.running += 1;
+            });
+            self.stage = Stage::Running;
+        }
+    }
+
+    /// Counts the execution as a recent one.
+    pub(crate) fn ended(&mut self, errored: bool) {
+        let stage = self.stage;
+        if stage == Stage::Ended {
+            return;
+        }
+        self.stage = Stage::Ended;
+
+        let now = Instant::now();
+        self.tracker.update(self.kind, |tracker| {
+            leave(tracker, stage);
+            tracker.recent.push_back((now, errored));
+            if let Some(cutoff) = now.checked_sub(RECENT_WINDOW) {
+                tracker.forget_before(cutoff);
+            }
+        });
+    }
+}
+
+impl Drop for ExecutionStats {
+    fn drop(&mut self) {
+        let stage = self.stage;
+        if stage != Stage::Ended {
+            self.tracker
+                .update(self.kind, |tracker| leave(tracker, stage));
+        }
+    }
+}
+
+fn leave(tracker: &mut KindTracker, stage: Stage) {
+    match stage {
+        Stage::Queued => tracker.queued = tracker.queued.saturating_sub(1),
+        Stage::Running => tracker.running = tracker.running.saturating_sub(1),
+        Stage::Ended => {}
+    }
+}
@@ -1,3 +1,5 @@
+use std::sync::Arc;
+
 use deadpool_cyclone::{
     ActionRunRequest, ReconciliationRequest, ResolverFunctionRequest,
     SchemaVariantDefinitionRequest, ValidationRequest,
@@ -5,10 +7,9 @@ use deadpool_cyclone::{
 use nats_subscriber::Subscription;
 use si_data_nats::NatsClient;
 use telemetry::prelude::*;
-use veritech_core::{
-    nats_action_run_subject, nats_reconciliation_subject, nats_resolver_function_subject,
-    nats_schema_variant_definition_subject, nats_validation_subject,
-};
+use veritech_core::{nats_sharded_subject, nats_subject};
+
+use crate::{payload::PayloadDecryptor, server::CycloneRequest};
 
 type Result<T> = std::result::Result<T, nats_subscriber::SubscriberError>;
 
@@ -18,80 +19,79 @@ impl FunctionSubscriber {
     pub async fn resolver_function(
         nats: &NatsClient,
         subject_prefix: Option<&str>,
+        shard: Option<u32>,
+        payload_decryptor: PayloadDecryptor,
     ) -> Result<Subscription<ResolverFunctionRequest>> {
-        let subject = nats_resolver_function_subject(subject_prefix);
-        debug!(
-            messaging.destination = &subject.as_str(),
-            "subscribing for resolver function requests"
-        );
-        Subscription::create(subject)
-            .queue_name("resolver")
-            .check_for_reply_mailbox()
-            .start(nats)
-            .await
+        Self::subscribe(nats, subject_prefix, shard, payload_decryptor).await
     }
 
     pub async fn validation(
         nats: &NatsClient,
         subject_prefix: Option<&str>,
+        shard: Option<u32>,
+        payload_decryptor: PayloadDecryptor,
     ) -> Result<Subscription<ValidationRequest>> {
-        let subject = nats_validation_subject(subject_prefix);
-        debug!(
-            messaging.destination = &subject.as_str(),
-            "subscribing for validation requests"
-        );
-        Subscription::create(subject)
-            .queue_name("validation")
-            .check_for_reply_mailbox()
-            .start(nats)
-            .await
+        Self::subscribe(nats, subject_prefix, shard, payload_decryptor).await
     }
 
     pub async fn action_run(
         nats: &NatsClient,
         subject_prefix: Option<&str>,
+        shard: Option<u32>,
+        payload_decryptor: PayloadDecryptor,
     ) -> Result<Subscription<ActionRunRequest>> {
-        let subject = nats_action_run_subject(subject_prefix);
-        debug!(
-            messaging.destination = &subject.as_str(),
-            "subscribing for command run requests"
-        );
-        Subscription::create(subject)
-            .queue_name("action")
-            .check_for_reply_mailbox()
-            .start(nats)
-            .await
+        Self::subscribe(nats, subject_prefix, shard, payload_decryptor).await
     }
 
     pub async fn reconciliation(
         nats: &NatsClient,
         subject_prefix: Option<&str>,
+        shard: Option<u32>,
+        payload_decryptor: PayloadDecryptor,
     ) -> Result<Subscription<ReconciliationRequest>> {
-        let subject = nats_reconciliation_subject(subject_prefix);
-        debug!(
-            messaging.destination = &subject.as_str(),
-            "subscribing for reconciliation requests"
-        );
-        Subscription::create(subject)
-            .queue_name("reconciliation")
-            .check_for_reply_mailbox()
-            .start(nats)
-            .await
+        Self::subscribe(nats, subject_prefix, shard, payload_decryptor).await
     }
 
     pub async fn schema_variant_definition(
         nats: &NatsClient,
         subject_prefix: Option<&str>,
+        shard: Option<u32>,
+        payload_decryptor: PayloadDecryptor,
     ) -> Result<Subscription<SchemaVariantDefinitionRequest>> {
-        let subject = nats_schema_variant_definition_subject(subject_prefix);
+        Self::subscribe(nats, subject_prefix, shard, payload_decryptor).await
+    }
+
+    /// Subscribes to the requests of one kind of function, on the given shard's subject or on the
+    /// unsharded subject if there is no shard.
+    pub(crate) async fn subscribe<R>(
+        nats: &NatsClient,
+        subject_prefix: Option<&str>,
+        shard: Option<u32>,
+        payload_decryptor: PayloadDecryptor,
+    ) -> Result<Subscription<R>>
+    where
+        R: CycloneRequest,
+    {
+        let subject = sharded(nats_subject(subject_prefix, R::SUBJECT_SUFFIX), shard);
         debug!(
             messaging.destination = &subject.as_str(),
-            "subscribing for schema_variant_definition requests"
+            kind = R::KIND,
+            "subscribing for requests"
         );
         Subscription::create(subject)
-            .queue_name("schema_variant_definition")
+            .queue_name(R::QUEUE_NAME)
             .check_for_reply_mailbox()
+            .keep_raw_messages()
+            .payload_decoder(Arc::new(payload_decryptor))
             .start(nats)
             .await
     }
 }
+
+/// Moves a subject onto the given shard, if there is one.
+fn sharded(subject: String, shard: Option<u32>) -> String {
+    match shard {
+        Some(shard) => nats_sharded_subject(&subject, shard),
+        None => subject,
+    }
+}